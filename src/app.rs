@@ -0,0 +1,940 @@
+//! Command dispatch shared by the `git-insights` binary and the Python
+//! bindings, so both print the same output and exit with the same codes.
+
+use crate::{
+    badge::{run_badge, BadgeMetric},
+    branches::run_branches,
+    bursts::{BurstOptions, DEFAULT_BURST_FACTOR},
+    churn::{run_churn, DEFAULT_WEEKS},
+    classes::run_classes,
+    cli::{render_help, version_string, Cli, Commands},
+    code_frequency::{
+        compute_code_frequency_weighted, export_code_frequency_heatmap_svg, render_code_frequency,
+        run_code_frequency_per_author, run_code_frequency_summary, FrequencyWeight, Group,
+        HeatmapKind,
+    },
+    codeowners::{run_codeowners_audit, run_codeowners_check},
+    commits::export_log_json,
+    compare::{parse_window, run_compare, run_stats_window_compare},
+    config::expand_args,
+    contributors::{run_contributors, ContributorSort},
+    daemon::{build_daemon_config, run_daemon},
+    encoding::run_encoding_audit,
+    error::{ExitKind, GitInsightsError},
+    filter::{set_active, CommitFilter},
+    git::{is_git_installed, is_in_git_repo, set_mailmap_file},
+    growth::{run_growth, run_growth_loc, DEFAULT_LOC_SAMPLES},
+    hotspots::{run_hotspots, HotspotSize},
+    interactive::run_interactive,
+    languages::run_languages,
+    log::set_verbosity,
+    man::run_man,
+    matrix::{run_ownership_matrix, DEFAULT_MIN_PCT},
+    messages::{
+        run_message_keywords, run_messages, MessageGroup, DEFAULT_KEYWORDS, DEFAULT_SHORT_SUBJECT,
+    },
+    output::{print_user_ownership, print_user_stats, TableOutput, DEFAULT_MAX_ROWS},
+    owners::{run_file, run_owners},
+    ownership_history::{run_ownership_history, DEFAULT_HISTORY_SAMPLES},
+    palette::set_palette,
+    prometheus::export_prometheus,
+    quality::run_quality,
+    releases::run_releases,
+    report::{run_report, ReportFormat},
+    risk::{run_risk, RiskWeights},
+    serve::{run_serve, DEFAULT_SERVE_HOST, DEFAULT_SERVE_PORT},
+    signing::run_signing,
+    snapshot::run_stats_incremental,
+    stats::{
+        export_json, gather_user_stats_github, gather_user_stats_with,
+        get_user_file_ownership_with, run_stats, run_stats_fast, set_jobs, set_text_detection,
+        StatsSort,
+    },
+    submodules::set_recurse_submodules,
+    survival::{run_survival, DEFAULT_SURVIVAL_SAMPLES},
+    tenure::run_ownership_by_tenure,
+    time_audit::{run_time_audit, DEFAULT_MAX_SKEW},
+    top::{run_top, TopMetric, DEFAULT_TOP_LIMIT},
+    velocity::{parse_iteration, parse_start, run_velocity, DEFAULT_ITERATION},
+    view::{run_view, ViewFormat},
+    visualize::{
+        export_heatmap_svg, export_timeline_chart, run_heatmap_scaled, run_timeline_metric,
+        ChartSize, HeatmapScale, TimelineGranularity, TimelineMetric, TimelineStyle, TimelineView,
+    },
+    watch::run_watch,
+    xlsx::export_xlsx,
+};
+
+/// Print `e` and return the exit code for its kind (see
+/// [`GitInsightsError::exit_kind`]).
+fn failed(e: impl Into<GitInsightsError>) -> i32 {
+    let e = e.into();
+    eprintln!("Error: {}", e);
+    e.exit_kind().code()
+}
+
+/// Parse `args` (program name first), run the command and return the
+/// process exit code (see [`ExitKind`]). Both the binary and the Python
+/// `run()` go through here.
+pub fn run(args: Vec<String>) -> i32 {
+    let cli = match expand_args(args).and_then(Cli::parse_from_args) {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitKind::Usage.code();
+        }
+    };
+
+    let cli = if matches!(cli.command, Commands::Interactive) {
+        match run_interactive()
+            .and_then(expand_args)
+            .and_then(Cli::parse_from_args)
+        {
+            Ok(cli) => cli,
+            Err(e) => {
+                return failed(e);
+            }
+        }
+    } else {
+        cli
+    };
+
+    match &cli.command {
+        Commands::Help { topic } => {
+            println!("{}", render_help(topic.clone()));
+            return 0;
+        }
+        Commands::Version => {
+            println!("{}", version_string());
+            return 0;
+        }
+        Commands::View {
+            path,
+            format,
+            output,
+        } => {
+            let format = match format.as_deref() {
+                None | Some("table") => ViewFormat::Table,
+                Some("html") => ViewFormat::Html,
+                Some(other) => {
+                    eprintln!(
+                        "Error: unsupported view format '{}'. Use table or html.",
+                        other
+                    );
+                    return ExitKind::Usage.code();
+                }
+            };
+            if let Err(e) = run_view(path, format, output.as_deref()) {
+                return failed(e);
+            }
+            return 0;
+        }
+        Commands::Man { command, dir } => {
+            if let Err(e) = run_man(command.as_deref(), dir.as_deref()) {
+                return failed(e);
+            }
+            return 0;
+        }
+        _ => {}
+    }
+
+    if !is_git_installed() {
+        eprintln!(
+            "Error: 'git' command not found. Please ensure Git is installed and in your PATH."
+        );
+        return ExitKind::GitMissing.code();
+    }
+    if let Commands::Daemon {
+        interval,
+        repos_file,
+        export_dir,
+        analyses,
+        notify,
+        once,
+    } = &cli.command
+    {
+        set_active(cli.filter.clone());
+        set_text_detection(cli.text_detection);
+        set_jobs(cli.jobs.unwrap_or(0));
+        set_mailmap_file(cli.mailmap.clone());
+        set_verbosity(cli.verbosity);
+        let result = build_daemon_config(
+            interval.as_deref(),
+            repos_file.as_deref(),
+            export_dir.as_deref(),
+            analyses.as_deref(),
+            notify.as_deref(),
+            *once,
+        )
+        .and_then(|config| run_daemon(&config));
+        if let Err(e) = result {
+            return failed(e);
+        }
+        return 0;
+    }
+    if !is_in_git_repo() {
+        eprintln!("Error: Not a git repository.");
+        return ExitKind::NotARepo.code();
+    }
+
+    match cli
+        .filter
+        .clone()
+        .read_stdin_commits()
+        .and_then(CommitFilter::resolve_ignore_revs)
+    {
+        Ok(filter) => set_active(filter),
+        Err(e) => {
+            return failed(e);
+        }
+    }
+    set_text_detection(cli.text_detection);
+    set_jobs(cli.jobs.unwrap_or(0));
+    set_mailmap_file(cli.mailmap.clone());
+    set_verbosity(cli.verbosity);
+    set_recurse_submodules(cli.recurse_submodules);
+    set_palette(cli.palette.clone());
+
+    match &cli.command {
+        Commands::Stats {
+            by_name,
+            by_domain,
+            incremental,
+            max_rows,
+            output,
+            compare_with,
+            fast,
+            watch,
+            sort,
+            ascending,
+            top,
+        } => {
+            if cli.recurse_submodules && (*incremental || *fast || compare_with.is_some()) {
+                eprintln!(
+                    "Error: --recurse-submodules cannot be combined with --incremental, --fast or --compare-with."
+                );
+                return ExitKind::Usage.code();
+            }
+            if let Some(window) = compare_with {
+                if *incremental || *fast || output.is_some() {
+                    eprintln!(
+                        "Error: --compare-with cannot be combined with --incremental, --fast or --output."
+                    );
+                    return ExitKind::Usage.code();
+                }
+                let result = parse_window(window)
+                    .map_err(GitInsightsError::Usage)
+                    .and_then(|(start, end)| run_stats_window_compare(start, end, *by_name));
+                if let Err(e) = result {
+                    return failed(e);
+                }
+                return 0;
+            }
+            let sort = match sort.as_deref().map(StatsSort::parse).transpose() {
+                Ok(sort) => sort.unwrap_or_default(),
+                Err(e) => {
+                    return failed(GitInsightsError::Usage(e));
+                }
+            };
+            let table = TableOutput {
+                max_rows: match max_rows.unwrap_or(DEFAULT_MAX_ROWS) {
+                    0 => None,
+                    n => Some(n),
+                },
+                path: output.clone(),
+                sort,
+                ascending: ascending.unwrap_or(sort.ascending_by_default()),
+                top: top.filter(|&n| n > 0),
+                by_domain: *by_domain,
+            };
+            if *incremental && *fast {
+                eprintln!("Error: --fast cannot be combined with --incremental.");
+                return ExitKind::Usage.code();
+            }
+            let render = || {
+                if *incremental {
+                    run_stats_incremental(*by_name, &table)
+                } else if *fast {
+                    run_stats_fast(*by_name, &table)
+                } else {
+                    run_stats(*by_name, &table)
+                }
+            };
+            let result = match watch {
+                Some(secs) => run_watch(*secs, render),
+                None => render(),
+            };
+            if let Err(e) = result {
+                return failed(e);
+            }
+        }
+        Commands::Json { output } => {
+            if let Err(e) = export_json(output.as_deref()) {
+                return failed(e);
+            }
+        }
+        Commands::User {
+            username,
+            ownership,
+            by_email,
+            top,
+            sort,
+            github,
+            pr_patterns,
+            min_pct,
+            min_loc,
+        } => {
+            if let Some(login) = github {
+                let result =
+                    gather_user_stats_github(username, login, pr_patterns).and_then(|stats| {
+                        print_user_stats(username, &stats).map_err(GitInsightsError::from)
+                    });
+                if let Err(e) = result {
+                    return failed(e);
+                }
+            } else if *ownership {
+                let top_n = top.unwrap_or(10);
+                let sort_pct = sort.as_deref().map(|s| s == "pct").unwrap_or(false);
+                let result = get_user_file_ownership_with(
+                    username,
+                    *by_email,
+                    top_n,
+                    sort_pct,
+                    min_pct.unwrap_or(0.0),
+                    min_loc.unwrap_or(0),
+                )
+                .and_then(|report| print_user_ownership(&report).map_err(GitInsightsError::from));
+                if let Err(e) = result {
+                    return failed(e);
+                }
+            } else {
+                let result = gather_user_stats_with(username, pr_patterns).and_then(|stats| {
+                    print_user_stats(username, &stats).map_err(GitInsightsError::from)
+                });
+                if let Err(e) = result {
+                    return failed(e);
+                }
+            }
+        }
+        Commands::Timeline {
+            weeks,
+            color,
+            capacity,
+            height,
+            bursts,
+            burst_commits,
+            metric,
+            granularity,
+            style,
+            output,
+            watch,
+        } => {
+            let size = ChartSize {
+                width: None,
+                height: *height,
+            };
+            let bursts = (bursts.is_some() || burst_commits.is_some()).then(|| BurstOptions {
+                factor: bursts.unwrap_or(DEFAULT_BURST_FACTOR),
+                top_commits: burst_commits.unwrap_or(0),
+            });
+            let render = || {
+                let granularity = granularity
+                    .as_deref()
+                    .map(TimelineGranularity::parse)
+                    .transpose()?
+                    .unwrap_or_default();
+                let style = style
+                    .as_deref()
+                    .map(TimelineStyle::parse)
+                    .transpose()?
+                    .unwrap_or_default();
+                let w = weeks.unwrap_or(granularity.default_weeks());
+                metric
+                    .as_deref()
+                    .map(TimelineMetric::parse)
+                    .transpose()
+                    .and_then(|m| match m.unwrap_or_default() {
+                        TimelineMetric::Contributors if capacity.is_some() => {
+                            Err(GitInsightsError::Usage(
+                                "--metric contributors cannot be combined with --capacity"
+                                    .to_string(),
+                            ))
+                        }
+                        m => {
+                            let view = TimelineView {
+                                metric: m,
+                                granularity,
+                                style,
+                            };
+                            match output {
+                                Some(path) => export_timeline_chart(w, view, path),
+                                None => run_timeline_metric(
+                                    w,
+                                    color.enabled(),
+                                    capacity.as_deref(),
+                                    size,
+                                    bursts,
+                                    view,
+                                ),
+                            }
+                        }
+                    })
+            };
+            let result = match watch {
+                Some(secs) => run_watch(*secs, render),
+                None => render(),
+            };
+            if let Err(e) = result {
+                return failed(e);
+            }
+        }
+        Commands::Heatmap {
+            weeks,
+            color,
+            output,
+            normalize,
+            watch,
+        } => {
+            let render = || {
+                let scale = normalize
+                    .as_deref()
+                    .map(HeatmapScale::parse)
+                    .transpose()?
+                    .unwrap_or_default();
+                run_heatmap_scaled(*weeks, color.enabled(), scale)
+            };
+            let result = match (output, watch) {
+                (Some(path), _) => export_heatmap_svg(*weeks, path),
+                (None, Some(secs)) => run_watch(*secs, render),
+                (None, None) => render(),
+            };
+            if let Err(e) = result {
+                return failed(e);
+            }
+        }
+        Commands::CodeFrequency {
+            group,
+            heatmap,
+            weeks,
+            color,
+            table,
+            output,
+            width,
+            per_author,
+            top,
+            summary,
+            normalize,
+            weight,
+        } => {
+            let parsed_heatmap = match heatmap.as_deref() {
+                Some("dow-hod") => Some(HeatmapKind::DowByHod),
+                Some("dom-hod") => Some(HeatmapKind::DomByHod),
+                Some("type-hod") => Some(HeatmapKind::TypeByHod),
+                Some("add-del") => Some(HeatmapKind::AddDelByHod),
+                Some(other) => {
+                    eprintln!(
+                        "Error: unknown --heatmap '{}'. Expected dow-hod|dom-hod|type-hod|add-del.",
+                        other
+                    );
+                    return ExitKind::Usage.code();
+                }
+                None => None,
+            };
+            let parsed_group = match group.as_deref() {
+                Some("hod") => Some(Group::HourOfDay),
+                Some("dow") => Some(Group::DayOfWeek),
+                Some("dom") => Some(Group::DayOfMonth),
+                Some("moy") => Some(Group::MonthOfYear),
+                Some("year") => Some(Group::Year),
+                Some(other) => {
+                    eprintln!(
+                        "Error: unknown --group '{}'. Expected hod|dow|dom|moy|year.",
+                        other
+                    );
+                    return ExitKind::Usage.code();
+                }
+                None => None,
+            };
+            if *per_author {
+                if !matches!(parsed_heatmap, Some(HeatmapKind::DowByHod)) {
+                    eprintln!("Error: --per-author requires --heatmap dow-hod.");
+                    return ExitKind::Usage.code();
+                }
+                if output.is_some() || *table {
+                    eprintln!("Error: --per-author cannot be combined with --output or --table.");
+                    return ExitKind::Usage.code();
+                }
+            }
+            if normalize.is_some() && (parsed_heatmap.is_none() || output.is_some() || *per_author)
+            {
+                eprintln!(
+                    "Error: --normalize requires --heatmap and cannot be combined with --output or --per-author."
+                );
+                return ExitKind::Usage.code();
+            }
+            if weight.is_some() && (*summary || *per_author || output.is_some()) {
+                eprintln!(
+                    "Error: --weight cannot be combined with --summary, --per-author or --output."
+                );
+                return ExitKind::Usage.code();
+            }
+            let scale = match normalize.as_deref().map(HeatmapScale::parse).transpose() {
+                Ok(scale) => scale.unwrap_or_default(),
+                Err(e) => return failed(e),
+            };
+            let weight = match weight.as_deref().map(FrequencyWeight::parse).transpose() {
+                Ok(weight) => weight.unwrap_or_default(),
+                Err(e) => return failed(GitInsightsError::Usage(e)),
+            };
+            if *summary
+                && (parsed_group.is_some()
+                    || parsed_heatmap.is_some()
+                    || output.is_some()
+                    || *table)
+            {
+                eprintln!(
+                    "Error: --summary cannot be combined with --group, --heatmap, --output or --table."
+                );
+                return ExitKind::Usage.code();
+            }
+            let result = match (output, parsed_heatmap) {
+                (None, None) if *summary => run_code_frequency_summary(*weeks, top.unwrap_or(10)),
+                (None, Some(HeatmapKind::DowByHod)) if *per_author => {
+                    run_code_frequency_per_author(*weeks, color.enabled(), top.unwrap_or(6))
+                }
+                (Some(path), Some(kind)) => export_code_frequency_heatmap_svg(kind, *weeks, path),
+                (Some(_), None) => {
+                    eprintln!(
+                        "Error: --output requires --heatmap dow-hod|dom-hod|type-hod|add-del."
+                    );
+                    return ExitKind::Usage.code();
+                }
+                (None, parsed_heatmap) => {
+                    compute_code_frequency_weighted(parsed_group, parsed_heatmap, *weeks, weight)
+                        .and_then(|data| {
+                            let size = ChartSize {
+                                width: *width,
+                                height: None,
+                            };
+                            let mut out = std::io::stdout().lock();
+                            render_code_frequency(
+                                &mut out,
+                                &data,
+                                color.enabled(),
+                                *table,
+                                size,
+                                scale,
+                            )
+                            .map_err(GitInsightsError::from)
+                        })
+                }
+            };
+            if let Err(e) = result {
+                return failed(e);
+            }
+        }
+        Commands::Export { prometheus, xlsx } => {
+            if prometheus.is_none() && xlsx.is_none() {
+                eprintln!("Error: export requires --prometheus <file> or --xlsx <file>.");
+                return ExitKind::Usage.code();
+            }
+            let result = prometheus
+                .as_deref()
+                .map_or(Ok(()), export_prometheus)
+                .and_then(|_| xlsx.as_deref().map_or(Ok(()), export_xlsx));
+            if let Err(e) = result {
+                return failed(e);
+            }
+        }
+        Commands::LogJson { output } => {
+            if let Err(e) = export_log_json(output.as_deref()) {
+                return failed(e);
+            }
+        }
+        Commands::Hotspots { top, size } => {
+            let result = HotspotSize::parse(size.as_deref().unwrap_or("loc"))
+                .map_err(GitInsightsError::Usage)
+                .and_then(|size| run_hotspots(top.unwrap_or(20), size));
+            if let Err(e) = result {
+                return failed(e);
+            }
+        }
+        Commands::CodeownersCheck {
+            file,
+            min_pct,
+            stale_days,
+        } => match run_codeowners_check(
+            file.as_deref(),
+            min_pct.unwrap_or(10.0),
+            stale_days.unwrap_or(365),
+        ) {
+            Ok(0) => {}
+            Ok(_) => return ExitKind::Failure.code(),
+            Err(e) => {
+                return failed(e);
+            }
+        },
+        Commands::CodeownersAudit { file, min_pct } => {
+            match run_codeowners_audit(file.as_deref(), min_pct.unwrap_or(10.0)) {
+                Ok(0) => {}
+                Ok(_) => return ExitKind::Failure.code(),
+                Err(e) => {
+                    return failed(e);
+                }
+            }
+        }
+        Commands::Messages {
+            by,
+            keywords,
+            top,
+            under,
+        } => {
+            let result = MessageGroup::parse(by.as_deref().unwrap_or("author"))
+                .map_err(GitInsightsError::Usage)
+                .and_then(|group| {
+                    if *keywords {
+                        run_message_keywords(
+                            group,
+                            top.unwrap_or(DEFAULT_KEYWORDS),
+                            under.unwrap_or(DEFAULT_SHORT_SUBJECT),
+                        )
+                    } else {
+                        run_messages(group)
+                    }
+                });
+            if let Err(e) = result {
+                return failed(e);
+            }
+        }
+        Commands::Owners {
+            file,
+            line,
+            format,
+            top,
+        } => {
+            let result = match (file.as_deref(), format.as_deref()) {
+                (None, _) => Err(GitInsightsError::Usage(
+                    "owners requires --file PATH".to_string(),
+                )),
+                (Some(path), None | Some("text")) => {
+                    run_owners(path, *line, top.unwrap_or(5), false)
+                }
+                (Some(path), Some("json")) => run_owners(path, *line, top.unwrap_or(5), true),
+                (_, Some(other)) => Err(GitInsightsError::Usage(format!(
+                    "unknown --format '{}'. Expected text or json.",
+                    other
+                ))),
+            };
+            if let Err(e) = result {
+                return failed(e);
+            }
+        }
+        Commands::File { path, format, top } => {
+            let result = match format.as_deref() {
+                None | Some("text") => run_file(path, top.unwrap_or(10), false),
+                Some("json") => run_file(path, top.unwrap_or(10), true),
+                Some(other) => Err(GitInsightsError::Usage(format!(
+                    "unknown --format '{}'. Expected text or json.",
+                    other
+                ))),
+            };
+            if let Err(e) = result {
+                return failed(e);
+            }
+        }
+        Commands::Releases { tags, top } => {
+            if let Err(e) = run_releases(tags.as_deref(), top.unwrap_or(3)) {
+                return failed(e);
+            }
+        }
+        Commands::Branches { base, stale_days } => {
+            if let Err(e) = run_branches(base.as_deref(), stale_days.unwrap_or(90)) {
+                return failed(e);
+            }
+        }
+        Commands::Languages { top } => {
+            if let Err(e) = run_languages(top.unwrap_or(3)) {
+                return failed(e);
+            }
+        }
+        Commands::Classes { classes } => {
+            if let Err(e) = run_classes(classes) {
+                return failed(e);
+            }
+        }
+        Commands::Risk {
+            weights,
+            top,
+            format,
+        } => {
+            let as_json = match format.as_deref() {
+                None | Some("text") => Ok(false),
+                Some("json") => Ok(true),
+                Some(other) => Err(GitInsightsError::Usage(format!(
+                    "unknown --format '{}'. Expected text or json.",
+                    other
+                ))),
+            };
+            let result = as_json.and_then(|as_json| {
+                let weights = match weights.as_deref() {
+                    Some(spec) => RiskWeights::parse(spec).map_err(GitInsightsError::Usage)?,
+                    None => RiskWeights::default(),
+                };
+                run_risk(weights, top.unwrap_or(20), as_json)
+            });
+            if let Err(e) = result {
+                return failed(e);
+            }
+        }
+        Commands::TimeAudit { max_skew_hours } => {
+            let max_skew = max_skew_hours.map_or(DEFAULT_MAX_SKEW, |h| h * 3_600);
+            if let Err(e) = run_time_audit(max_skew) {
+                return failed(e);
+            }
+        }
+        Commands::EncodingAudit => {
+            if let Err(e) = run_encoding_audit() {
+                return failed(e);
+            }
+        }
+        Commands::Contributors { sort } => {
+            let result = match sort.as_deref() {
+                Some(s) => ContributorSort::parse(s),
+                None => Ok(ContributorSort::default()),
+            }
+            .map_err(GitInsightsError::Usage)
+            .and_then(run_contributors);
+            if let Err(e) = result {
+                return failed(e);
+            }
+        }
+        Commands::Growth {
+            color,
+            format,
+            loc,
+            samples,
+        } => {
+            let result = match format.as_deref() {
+                None | Some("text") => Ok(false),
+                Some("json") => Ok(true),
+                Some(other) => Err(GitInsightsError::Usage(format!(
+                    "unknown --format '{}'. Expected text or json.",
+                    other
+                ))),
+            }
+            .and_then(|as_json| {
+                if *loc {
+                    run_growth_loc(
+                        samples.unwrap_or(DEFAULT_LOC_SAMPLES),
+                        color.enabled(),
+                        as_json,
+                    )
+                } else {
+                    run_growth(color.enabled(), as_json)
+                }
+            });
+            if let Err(e) = result {
+                return failed(e);
+            }
+        }
+        Commands::Quality => {
+            if let Err(e) = run_quality() {
+                return failed(e);
+            }
+        }
+        Commands::Signing => {
+            if let Err(e) = run_signing() {
+                return failed(e);
+            }
+        }
+        Commands::Churn { weeks, by_ext, top } => {
+            if let Err(e) = run_churn(weeks.unwrap_or(DEFAULT_WEEKS), *by_ext, top.unwrap_or(20)) {
+                return failed(e);
+            }
+        }
+        Commands::OwnershipHistory {
+            username,
+            by_email,
+            samples,
+            color,
+            format,
+        } => {
+            let result = match format.as_deref() {
+                None | Some("text") => Ok(false),
+                Some("json") => Ok(true),
+                Some(other) => Err(GitInsightsError::Usage(format!(
+                    "unknown --format '{}'. Expected text or json.",
+                    other
+                ))),
+            }
+            .and_then(|as_json| {
+                run_ownership_history(
+                    username,
+                    *by_email,
+                    samples.unwrap_or(DEFAULT_HISTORY_SAMPLES),
+                    color.enabled(),
+                    as_json,
+                )
+            });
+            if let Err(e) = result {
+                return failed(e);
+            }
+        }
+        Commands::OwnershipByTenure => {
+            if let Err(e) = run_ownership_by_tenure() {
+                return failed(e);
+            }
+        }
+        Commands::Velocity {
+            iteration,
+            start,
+            color,
+        } => {
+            let length = match iteration.as_deref() {
+                Some(s) => parse_iteration(s),
+                None => Ok(DEFAULT_ITERATION),
+            };
+            let start = start
+                .as_deref()
+                .map(parse_start)
+                .transpose()
+                .map_err(GitInsightsError::Usage);
+            let result = length
+                .map_err(GitInsightsError::Usage)
+                .and_then(|length| run_velocity(length, start?, color.enabled()));
+            if let Err(e) = result {
+                return failed(e);
+            }
+        }
+        Commands::OwnershipMatrix {
+            min_pct,
+            format,
+            by_name,
+            output,
+        } => {
+            let as_json = match format.as_deref() {
+                None | Some("markdown") | Some("md") => false,
+                Some("json") => true,
+                Some(other) => {
+                    eprintln!(
+                        "Error: unknown --format '{}'. Expected markdown|json.",
+                        other
+                    );
+                    return ExitKind::Usage.code();
+                }
+            };
+            if let Err(e) = run_ownership_matrix(
+                min_pct.unwrap_or(DEFAULT_MIN_PCT),
+                as_json,
+                *by_name,
+                output.as_deref(),
+            ) {
+                return failed(e);
+            }
+        }
+        Commands::Compare {
+            ref_a,
+            ref_b,
+            by_name,
+            color,
+        } => {
+            if let Err(e) = run_compare(ref_a, ref_b, *by_name, color.enabled()) {
+                return failed(e);
+            }
+        }
+        Commands::Survival { samples } => {
+            if let Err(e) = run_survival(samples.unwrap_or(DEFAULT_SURVIVAL_SAMPLES)) {
+                return failed(e);
+            }
+        }
+        Commands::Top {
+            metric,
+            limit,
+            color,
+        } => {
+            let result = TopMetric::parse(metric.as_deref().unwrap_or("loc"))
+                .map_err(GitInsightsError::Usage)
+                .and_then(|m| run_top(m, limit.unwrap_or(DEFAULT_TOP_LIMIT), color.enabled()));
+            if let Err(e) = result {
+                return failed(e);
+            }
+        }
+        Commands::Serve { port, host } => {
+            let host = host.as_deref().unwrap_or(DEFAULT_SERVE_HOST);
+            if let Err(e) = run_serve(host, port.unwrap_or(DEFAULT_SERVE_PORT)) {
+                return failed(e);
+            }
+        }
+        Commands::Badge { metric } => {
+            let result = BadgeMetric::parse(metric.as_deref().unwrap_or("contributors"))
+                .map_err(GitInsightsError::Usage)
+                .and_then(run_badge);
+            if let Err(e) = result {
+                return failed(e);
+            }
+        }
+        Commands::Report { format, output } => {
+            let parsed_format = match format.as_deref() {
+                Some("html") | None => ReportFormat::Html,
+                Some(other) => {
+                    eprintln!("Error: unknown --format '{}'. Expected html.", other);
+                    return ExitKind::Usage.code();
+                }
+            };
+            if let Err(e) = run_report(parsed_format, output.as_deref()) {
+                return failed(e);
+            }
+        }
+        _ => {}
+    }
+
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bad_option_values_exit_with_usage() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", "a", 1_700_000_000)
+            .expect("commit");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        let code = |args: &[&str]| {
+            let mut argv = vec!["git-insights".to_string()];
+            argv.extend(args.iter().map(|a| a.to_string()));
+            run(argv)
+        };
+        let codes: Vec<(&str, i32)> = [
+            &["stats", "--sort", "bogus"][..],
+            &["owners", "--file", "a.txt", "--format", "xml"],
+            &["risk", "--format", "x"],
+            &["timeline", "--granularity", "year"],
+            &["heatmap", "--normalize", "x"],
+            &["top", "--metric", "x"],
+            &["report", "--format", "xml"],
+            &["code-frequency", "--weight", "x"],
+            &[
+                "daemon",
+                "--repos-file",
+                "r.txt",
+                "--export-dir",
+                "out",
+                "--interval",
+                "1w",
+            ],
+        ]
+        .iter()
+        .map(|args| (args[0], code(args)))
+        .collect();
+        std::env::set_current_dir(old).ok();
+
+        for (command, code) in codes {
+            assert_eq!(code, ExitKind::Usage.code(), "{}", command);
+        }
+    }
+}
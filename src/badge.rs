@@ -0,0 +1,128 @@
+use crate::error::GitInsightsError;
+use crate::json;
+use crate::stats::{gather_commit_statsx, gather_loc_and_file_statsx};
+
+/// Metrics that can be rendered as a shields.io badge.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum BadgeMetric {
+    Contributors,
+    BusFactor,
+    Loc,
+}
+
+impl BadgeMetric {
+    pub fn parse(s: &str) -> Result<BadgeMetric, String> {
+        match s {
+            "contributors" => Ok(BadgeMetric::Contributors),
+            "bus-factor" => Ok(BadgeMetric::BusFactor),
+            "loc" => Ok(BadgeMetric::Loc),
+            other => Err(format!(
+                "unknown --metric '{}'. Expected contributors, bus-factor or loc.",
+                other
+            )),
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            BadgeMetric::Contributors => "contributors",
+            BadgeMetric::BusFactor => "bus factor",
+            BadgeMetric::Loc => "lines of code",
+        }
+    }
+}
+
+/// Smallest number of authors that together own more than half of the LOC.
+pub fn bus_factor(mut loc: Vec<usize>) -> usize {
+    let total: usize = loc.iter().sum();
+    if total == 0 {
+        return 0;
+    }
+    loc.sort_unstable_by(|a, b| b.cmp(a));
+    let mut covered = 0;
+    for (i, l) in loc.iter().enumerate() {
+        covered += l;
+        if covered * 2 > total {
+            return i + 1;
+        }
+    }
+    loc.len()
+}
+
+/// Compact number formatting used in badge messages (e.g. 12.3k, 4.5M).
+fn human_count(n: usize) -> String {
+    match n {
+        0..=999 => n.to_string(),
+        1_000..=999_999 => format!("{:.1}k", n as f64 / 1_000.0),
+        _ => format!("{:.1}M", n as f64 / 1_000_000.0),
+    }
+}
+
+/// Build shields.io endpoint JSON for a metric value.
+pub fn render_badge_json(metric: BadgeMetric, value: usize) -> String {
+    let color = match metric {
+        BadgeMetric::BusFactor if value <= 1 => "red",
+        BadgeMetric::BusFactor if value == 2 => "yellow",
+        BadgeMetric::BusFactor => "brightgreen",
+        _ => "blue",
+    };
+    let message = match metric {
+        BadgeMetric::Loc => human_count(value),
+        _ => value.to_string(),
+    };
+    format!(
+        "{{\"schemaVersion\": 1, \"label\": \"{}\", \"message\": \"{}\", \"color\": \"{}\"}}",
+        json::escape(metric.label()),
+        json::escape(&message),
+        color
+    )
+}
+
+/// Compute a metric for the current repository.
+pub fn compute_badge_value(metric: BadgeMetric) -> Result<usize, GitInsightsError> {
+    match metric {
+        BadgeMetric::Contributors => Ok(gather_commit_statsx(true)?.len()),
+        BadgeMetric::BusFactor => Ok(bus_factor(
+            gather_loc_and_file_statsx(true)?
+                .values()
+                .map(|s| s.loc)
+                .collect(),
+        )),
+        BadgeMetric::Loc => Ok(gather_loc_and_file_statsx(true)?
+            .values()
+            .map(|s| s.loc)
+            .sum()),
+    }
+}
+
+/// Print badge JSON for `metric` to stdout.
+pub fn run_badge(metric: BadgeMetric) -> Result<(), GitInsightsError> {
+    let value = compute_badge_value(metric)?;
+    println!("{}", render_badge_json(metric, value));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bus_factor() {
+        assert_eq!(bus_factor(vec![]), 0);
+        assert_eq!(bus_factor(vec![60, 40]), 1);
+        assert_eq!(bus_factor(vec![50, 50]), 2);
+        assert_eq!(bus_factor(vec![10, 30, 20, 40]), 2);
+    }
+
+    #[test]
+    fn test_render_badge_json() {
+        assert_eq!(
+            render_badge_json(BadgeMetric::Contributors, 14),
+            "{\"schemaVersion\": 1, \"label\": \"contributors\", \"message\": \"14\", \"color\": \"blue\"}"
+        );
+        assert!(render_badge_json(BadgeMetric::Loc, 12_345).contains("\"message\": \"12.3k\""));
+        assert!(render_badge_json(BadgeMetric::BusFactor, 1).contains("\"color\": \"red\""));
+        assert!(BadgeMetric::parse("stars").is_err());
+    }
+}
@@ -0,0 +1,210 @@
+use crate::code_frequency::ymd_from_unix;
+use crate::error::GitInsightsError;
+use crate::git::{run_command, run_command_lines};
+use crate::output::truncate;
+use std::collections::HashSet;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One local or remote-tracking branch compared against the default branch.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BranchInfo {
+    /// Short name, e.g. `feature/x` or `origin/feature/x`.
+    pub name: String,
+    pub remote: bool,
+    /// Committer time of the branch tip.
+    pub last_commit: u64,
+    /// Commits on the branch but not on the base.
+    pub ahead: usize,
+    /// Commits on the base but not on the branch.
+    pub behind: usize,
+    /// Distinct authors of the commits the branch is ahead by.
+    pub authors: usize,
+    pub stale: bool,
+}
+
+/// Parse `for-each-ref --format=%(refname)%09%(refname:short)%09%(committerdate:unix)`
+/// into `(short name, is remote, tip time)`, skipping symbolic `*/HEAD` refs.
+pub fn parse_branch_refs(out: &str) -> Vec<(String, bool, u64)> {
+    out.lines()
+        .filter_map(|l| {
+            let mut parts = l.split('\t');
+            let full = parts.next()?;
+            let short = parts.next()?;
+            let ts = parts.next()?.trim().parse().unwrap_or(0);
+            if full.ends_with("/HEAD") {
+                return None;
+            }
+            Some((short.to_string(), full.starts_with("refs/remotes/"), ts))
+        })
+        .collect()
+}
+
+/// The branch others are compared against: `origin/HEAD` when set, else a
+/// local `main` or `master`, else the current branch.
+pub fn default_branch() -> Result<String, GitInsightsError> {
+    if let Ok(head) = run_command(&["symbolic-ref", "--short", "refs/remotes/origin/HEAD"]) {
+        return Ok(head);
+    }
+    for name in ["main", "master"] {
+        let full = format!("refs/heads/{}", name);
+        if run_command(&["show-ref", "--verify", "--quiet", &full]).is_ok() {
+            return Ok(name.to_string());
+        }
+    }
+    run_command(&["rev-parse", "--abbrev-ref", "HEAD"])
+}
+
+/// Every branch with ahead/behind counts vs `base` (default branch when `None`),
+/// newest tip first. Branches whose tip is older than `stale_days` are marked stale.
+pub fn gather_branches(
+    base: Option<&str>,
+    stale_days: u64,
+) -> Result<Vec<BranchInfo>, GitInsightsError> {
+    let base = match base {
+        Some(b) => b.to_string(),
+        None => default_branch()?,
+    };
+    let authors = crate::filter::author_matcher()?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| format!("clock error: {e}"))?
+        .as_secs();
+    let cutoff = now.saturating_sub(stale_days * 86_400);
+
+    let refs = run_command(&[
+        "for-each-ref",
+        "--format=%(refname)%09%(refname:short)%09%(committerdate:unix)",
+        "refs/heads",
+        "refs/remotes",
+    ])?;
+    let mut branches = Vec::new();
+    for (name, remote, last_commit) in parse_branch_refs(&refs) {
+        let range = format!("{}...{}", base, name);
+        let counts = run_command(&["rev-list", "--left-right", "--count", &range])?;
+        let mut counts = counts.split_whitespace().map(|n| n.parse().unwrap_or(0));
+        let behind = counts.next().unwrap_or(0);
+        let ahead = counts.next().unwrap_or(0);
+
+        let unique = format!("{}..{}", base, name);
+        let mut names: HashSet<String> = HashSet::new();
+        run_command_lines(
+            &["--no-pager", "log", "--format=%aN%x09%aE", &unique],
+            |l| {
+                if let Some((n, e)) = l.split_once('\t') {
+                    if authors.allows(n, e) && !names.contains(n) {
+                        names.insert(n.to_string());
+                    }
+                }
+            },
+        )?;
+
+        branches.push(BranchInfo {
+            name,
+            remote,
+            last_commit,
+            ahead,
+            behind,
+            authors: names.len(),
+            stale: last_commit < cutoff,
+        });
+    }
+    branches.sort_by(|a, b| {
+        b.last_commit
+            .cmp(&a.last_commit)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    Ok(branches)
+}
+
+/// Print the branch table; stale branches are flagged in the last column.
+pub fn run_branches(base: Option<&str>, stale_days: u64) -> Result<(), GitInsightsError> {
+    let base_name = match base {
+        Some(b) => b.to_string(),
+        None => default_branch()?,
+    };
+    let branches = gather_branches(Some(&base_name), stale_days)?;
+    println!("Compared against: {}", base_name);
+    println!(
+        "| {:<36} | {:<6} | {:<10} | {:>6} | {:>6} | {:>7} | {:<5} |",
+        "branch", "kind", "last", "ahead", "behind", "authors", "stale"
+    );
+    println!(
+        "|:{:-<37}|:{:-<7}|:{:-<11}|{:->8}|{:->8}|{:->9}|:{:-<6}|",
+        "", "", "", "", "", "", ""
+    );
+    for b in &branches {
+        let (y, m, d) = ymd_from_unix(b.last_commit);
+        println!(
+            "| {:<36} | {:<6} | {:04}-{:02}-{:02} | {:>6} | {:>6} | {:>7} | {:<5} |",
+            truncate(&b.name, 36),
+            if b.remote { "remote" } else { "local" },
+            y,
+            m,
+            d,
+            b.ahead,
+            b.behind,
+            b.authors,
+            if b.stale { "STALE" } else { "" }
+        );
+    }
+    let stale = branches.iter().filter(|b| b.stale).count();
+    if stale > 0 {
+        println!();
+        println!(
+            "{} branch(es) have no commits in the last {} days.",
+            stale, stale_days
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_branch_refs() {
+        let out = "refs/heads/main\tmain\t1700000000\n\
+                   refs/remotes/origin/HEAD\torigin\t1700000000\n\
+                   refs/remotes/origin/topic\torigin/topic\t1600000000\n";
+        assert_eq!(
+            parse_branch_refs(out),
+            vec![
+                ("main".to_string(), false, 1_700_000_000),
+                ("origin/topic".to_string(), true, 1_600_000_000),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_gather_branches_e2e() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        let p = repo.path.to_str().unwrap().to_string();
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", "a", 1_700_000_000)
+            .expect("commit");
+        run_command(&["-C", &p, "checkout", "-q", "-b", "topic"]).expect("branch");
+        repo.commit_with_epoch("Bob", "bob@x.io", "b.txt", "b", 1_700_000_100)
+            .expect("commit");
+        repo.commit_with_epoch("Cat", "cat@x.io", "b.txt", "c", 1_700_000_200)
+            .expect("commit");
+        run_command(&["-C", &p, "checkout", "-q", "main"]).expect("checkout");
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", "d", 1_700_000_300)
+            .expect("commit");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        let base = default_branch();
+        let branches = gather_branches(None, 30);
+        std::env::set_current_dir(old).ok();
+
+        assert_eq!(base.expect("base"), "main");
+        let branches = branches.expect("branches");
+        assert_eq!(branches.len(), 2);
+        let topic = branches.iter().find(|b| b.name == "topic").expect("topic");
+        assert_eq!((topic.ahead, topic.behind, topic.authors), (2, 1, 2));
+        assert!(topic.stale && !topic.remote);
+        let main = branches.iter().find(|b| b.name == "main").expect("main");
+        assert_eq!((main.ahead, main.behind, main.authors), (0, 0, 0));
+    }
+}
@@ -0,0 +1,260 @@
+use crate::code_frequency::ymd_from_unix;
+use crate::error::GitInsightsError;
+use crate::git::run_command_line_iter;
+use crate::output::truncate;
+
+/// Weeks in the trailing mean a week is compared against.
+pub const BURST_WINDOW: usize = 8;
+
+/// Default multiple of the trailing mean that makes a week a burst.
+pub const DEFAULT_BURST_FACTOR: f64 = 2.0;
+
+const WEEK: u64 = 7 * 24 * 60 * 60;
+
+/// How bursts are detected and reported on the timeline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BurstOptions {
+    /// A week is a burst when its commits exceed `factor` x the trailing mean.
+    pub factor: f64,
+    /// Largest commits listed under the chart per burst week (0 = none).
+    pub top_commits: usize,
+}
+
+impl Default for BurstOptions {
+    fn default() -> Self {
+        BurstOptions {
+            factor: DEFAULT_BURST_FACTOR,
+            top_commits: 0,
+        }
+    }
+}
+
+/// Flag weeks whose count exceeds `factor` x the mean of the preceding
+/// `window` weeks. Weeks with no preceding activity are never bursts.
+pub fn detect_bursts(counts: &[usize], window: usize, factor: f64) -> Vec<bool> {
+    (0..counts.len())
+        .map(|i| {
+            let prior = &counts[i.saturating_sub(window.max(1))..i];
+            let mean = rolling_mean(prior);
+            mean > 0.0 && counts[i] as f64 > factor * mean
+        })
+        .collect()
+}
+
+fn rolling_mean(prior: &[usize]) -> f64 {
+    if prior.is_empty() {
+        return 0.0;
+    }
+    prior.iter().sum::<usize>() as f64 / prior.len() as f64
+}
+
+/// Inclusive `(start, end)` seconds of timeline column `idx` (0 = oldest),
+/// using the same week alignment as `compute_timeline_weeks`.
+pub fn week_bounds(idx: usize, weeks: usize, now: u64) -> (u64, u64) {
+    let aligned_end = (now - now % WEEK).saturating_add(WEEK - 1);
+    let back = (weeks - 1 - idx) as u64;
+    let end = aligned_end.saturating_sub(back * WEEK);
+    (end.saturating_sub(WEEK - 1), end)
+}
+
+/// Marker row under the chart: `^` (or an accented `▲`) under burst weeks.
+pub fn render_burst_row(bursts: &[bool], left_pad: usize, color: bool) {
+    let label_width = left_pad.saturating_sub(2);
+    let axis_char = if color { '│' } else { '|' };
+    let mut row = String::new();
+    for &b in bursts {
+        match (b, color) {
+            (true, true) => row.push_str("\x1b[1;33m▲\x1b[0m"),
+            (true, false) => row.push('^'),
+            (false, _) => row.push(' '),
+        }
+    }
+    let (dim_start, dim_end) = if color {
+        ("\x1b[90m", "\x1b[0m")
+    } else {
+        ("", "")
+    };
+    println!(
+        "{}{:>width$} {}{}{}",
+        dim_start,
+        "bst",
+        axis_char,
+        dim_end,
+        row,
+        width = label_width
+    );
+}
+
+/// One non-merge commit with its size, for the burst listing.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BurstCommit {
+    pub sha: String,
+    pub author: String,
+    /// Committer time, as bucketed by the timeline.
+    pub time: u64,
+    pub authored: u64,
+    pub added: usize,
+    pub deleted: usize,
+    pub subject: String,
+}
+
+/// Parse `log --numstat --format=%x1f%h%x1f%aN%x1f%aE%x1f%at%x1f%ct%x1f%s`, keeping
+/// commits whose author passes `allow`.
+pub fn parse_sized_commits(out: &str, allow: impl Fn(&str, &str) -> bool) -> Vec<BurstCommit> {
+    sized_commits_from_lines(out.lines(), allow)
+}
+
+/// [`parse_sized_commits`] over lines as git streams them.
+fn sized_commits_from_lines<S: AsRef<str>>(
+    lines: impl Iterator<Item = S>,
+    allow: impl Fn(&str, &str) -> bool,
+) -> Vec<BurstCommit> {
+    let mut commits = Vec::new();
+    let mut keep = false;
+    for line in lines {
+        let line = line.as_ref();
+        if let Some(header) = line.strip_prefix('\x1f') {
+            let f: Vec<&str> = header.splitn(6, '\x1f').collect();
+            keep = f.len() == 6 && allow(f[1], f[2]);
+            if keep {
+                commits.push(BurstCommit {
+                    sha: f[0].to_string(),
+                    author: f[1].to_string(),
+                    authored: f[3].parse().unwrap_or(0),
+                    time: f[4].parse().unwrap_or(0),
+                    subject: f[5].to_string(),
+                    ..Default::default()
+                });
+            }
+        } else if keep {
+            let mut parts = line.split('\t');
+            if let (Some(a), Some(d), Some(_), Some(c)) =
+                (parts.next(), parts.next(), parts.next(), commits.last_mut())
+            {
+                c.added += a.parse::<usize>().unwrap_or(0);
+                c.deleted += d.parse::<usize>().unwrap_or(0);
+            }
+        }
+    }
+    commits
+}
+
+/// Commits with line counts for the active filter, newest first.
+pub fn collect_sized_commits() -> Result<Vec<BurstCommit>, GitInsightsError> {
+    let revs = crate::filter::revision_args()?;
+    let authors = crate::filter::author_matcher()?;
+    let mut args = vec![
+        "--no-pager",
+        "log",
+        "--numstat",
+        "--format=\x1f%h\x1f%aN\x1f%aE\x1f%at\x1f%ct\x1f%s",
+    ];
+    args.extend(crate::filter::history_args());
+    args.extend(revs.iter().map(|s| s.as_str()));
+    let commits = run_command_line_iter(&args, |lines| {
+        sized_commits_from_lines(lines, |n, e| authors.allows(n, e))
+    })?;
+    let names = crate::identity::active_normalization();
+    let sanity = crate::time_audit::active_sanity()?;
+    Ok(commits
+        .into_iter()
+        .filter(|c| sanity.is_none_or(|s| s.allows(c.authored, c.time)))
+        .map(|mut c| {
+            c.author = names.apply(c.author);
+            c
+        })
+        .collect())
+}
+
+/// List each burst week with its largest `top` commits by lines changed.
+pub fn print_burst_commits(
+    counts: &[usize],
+    bursts: &[bool],
+    now: u64,
+    opts: BurstOptions,
+) -> Result<(), GitInsightsError> {
+    let weeks = counts.len();
+    let flagged: Vec<usize> = (0..weeks).filter(|&i| bursts[i]).collect();
+    println!();
+    if flagged.is_empty() {
+        println!(
+            "No bursts (weeks above {}x the trailing {}-week mean).",
+            opts.factor, BURST_WINDOW
+        );
+        return Ok(());
+    }
+    println!(
+        "Bursts (weeks above {}x the trailing {}-week mean):",
+        opts.factor, BURST_WINDOW
+    );
+    let commits = collect_sized_commits()?;
+    for i in flagged {
+        let (start, end) = week_bounds(i, weeks, now);
+        let (y, m, d) = ymd_from_unix(start);
+        let mean = rolling_mean(&counts[i.saturating_sub(BURST_WINDOW)..i]);
+        println!(
+            "  week of {:04}-{:02}-{:02}: {} commits (trailing mean {:.1})",
+            y, m, d, counts[i], mean
+        );
+        let mut in_week: Vec<&BurstCommit> = commits
+            .iter()
+            .filter(|c| c.time >= start && c.time <= end)
+            .collect();
+        in_week.sort_by_key(|c| std::cmp::Reverse(c.added + c.deleted));
+        for c in in_week.iter().take(opts.top_commits) {
+            println!(
+                "    {:<10} {:<20} {:>7} {}",
+                c.sha,
+                truncate(&c.author, 20),
+                format!("+{}/-{}", c.added, c.deleted),
+                truncate(&c.subject, 60)
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_bursts() {
+        let counts = [0, 2, 2, 2, 7, 2, 0, 7];
+        let bursts = detect_bursts(&counts, 3, 2.0);
+        assert_eq!(
+            bursts,
+            vec![false, false, false, false, true, false, false, true]
+        );
+        // No prior activity: never a burst, however large.
+        assert_eq!(detect_bursts(&[0, 9], 8, 2.0), vec![false, false]);
+        assert_eq!(detect_bursts(&[2, 5], 8, 3.0), vec![false, false]);
+    }
+
+    #[test]
+    fn test_week_bounds_match_timeline_buckets() {
+        let now = 1_700_000_000;
+        let (start, end) = week_bounds(9, 10, now);
+        assert!(start <= now && now <= end);
+        assert_eq!(end - start, WEEK - 1);
+        let (prev_start, prev_end) = week_bounds(8, 10, now);
+        assert_eq!(prev_end + 1, start);
+        assert_eq!(
+            crate::visualize::compute_timeline_weeks(&[prev_start, prev_end], 10, now)[8],
+            2
+        );
+    }
+
+    #[test]
+    fn test_parse_sized_commits() {
+        let out =
+            "\x1fabc\x1fAnn\x1fann@x.io\x1f99\x1f100\x1ffix: a\n\n3\t1\ta.rs\n-\t-\tlogo.png\n\
+                   \x1fdef\x1fBob\x1fbob@x.io\x1f90\x1f90\x1finit\n\n10\t0\tb.rs\n";
+        let all = parse_sized_commits(out, |_, _| true);
+        assert_eq!(all.len(), 2);
+        assert_eq!((all[0].added, all[0].deleted, all[0].time), (3, 1, 100));
+        assert_eq!(all[1].subject, "init");
+        let no_bob = parse_sized_commits(out, |n, _| n != "Bob");
+        assert_eq!(no_bob.len(), 1);
+    }
+}
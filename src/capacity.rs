@@ -0,0 +1,207 @@
+use crate::code_frequency::unix_from_ymd;
+use crate::error::GitInsightsError;
+use std::fs;
+
+/// Available person-days starting at a given week (Unix seconds, 00:00 UTC).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapacityEntry {
+    pub week_start: u64,
+    pub person_days: f64,
+}
+
+/// Parse `YYYY-MM-DD,person_days` lines.
+///
+/// Blank lines, `#` comments and a non-numeric header row are skipped.
+pub fn parse_capacity_csv(text: &str) -> Result<Vec<CapacityEntry>, String> {
+    let mut entries = Vec::new();
+    let mut seen_row = false;
+    for (lineno, raw) in text.lines().enumerate() {
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (date, days) = line
+            .split_once(',')
+            .ok_or_else(|| format!("capacity line {}: expected 'date,days'", lineno + 1))?;
+        let date = date.trim().trim_matches('"');
+        let days = days.trim().trim_matches('"');
+        if !seen_row && days.parse::<f64>().is_err() {
+            // header row such as "week,person_days"
+            seen_row = true;
+            continue;
+        }
+        seen_row = true;
+        match (parse_date(date), days.parse::<f64>()) {
+            (Some(week_start), Ok(person_days)) if person_days >= 0.0 => {
+                entries.push(CapacityEntry {
+                    week_start,
+                    person_days,
+                })
+            }
+            _ => {
+                return Err(format!(
+                    "capacity line {}: invalid entry '{}'",
+                    lineno + 1,
+                    line
+                ))
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Load and parse a capacity CSV file.
+pub fn load_capacity_file(path: &str) -> Result<Vec<CapacityEntry>, GitInsightsError> {
+    let text = fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path, e))?;
+    Ok(parse_capacity_csv(&text)?)
+}
+
+/// `YYYY-MM-DD` as Unix seconds at UTC midnight.
+pub(crate) fn parse_date(s: &str) -> Option<u64> {
+    let mut it = s.splitn(3, '-');
+    let y = it.next()?.parse::<i32>().ok()?;
+    let m = it.next()?.parse::<u32>().ok()?;
+    let d = it.next()?.parse::<u32>().ok()?;
+    if !(1..=12).contains(&m) || !(1..=31).contains(&d) {
+        return None;
+    }
+    Some(unix_from_ymd(y, m, d))
+}
+
+/// Bucket capacity into the same weekly bins as `compute_timeline_weeks`.
+///
+/// Each entry lands in the timeline week containing its mid-point, i.e. the bin
+/// it overlaps most. Weeks without an entry are `None` so they can be told apart
+/// from zero-capacity (vacation) weeks.
+pub fn compute_capacity_weeks(
+    entries: &[CapacityEntry],
+    weeks: usize,
+    now: u64,
+) -> Vec<Option<f64>> {
+    let mut out: Vec<Option<f64>> = vec![None; weeks];
+    if weeks == 0 {
+        return out;
+    }
+    const WEEK: u64 = 7 * 24 * 60 * 60;
+    let start_of_week = now - (now % WEEK);
+    let aligned_end = start_of_week.saturating_add(WEEK - 1);
+    for e in entries {
+        let mid = e.week_start + WEEK / 2;
+        if mid > aligned_end {
+            continue;
+        }
+        let bin = ((aligned_end - mid) / WEEK) as usize;
+        if bin < weeks {
+            let idx = weeks - 1 - bin;
+            *out[idx].get_or_insert(0.0) += e.person_days;
+        }
+    }
+    out
+}
+
+/// Commits per available person-day; `None` when capacity is unknown or zero.
+pub fn activity_ratio(counts: &[usize], capacity: &[Option<f64>]) -> Vec<Option<f64>> {
+    counts
+        .iter()
+        .zip(capacity.iter())
+        .map(|(&c, cap)| match cap {
+            Some(days) if *days > 0.0 => Some(c as f64 / days),
+            _ => None,
+        })
+        .collect()
+}
+
+/// One row of ramp characters scaled to the row's max; `?` marks unknown weeks.
+pub fn build_overlay_row(values: &[Option<f64>]) -> String {
+    let ramp: &[u8] = b" .:-=+*#%@";
+    let max = values.iter().flatten().copied().fold(0.0f64, f64::max);
+    values
+        .iter()
+        .map(|v| match v {
+            None => '?',
+            Some(x) if max <= 0.0 || *x <= 0.0 => ' ',
+            Some(x) => {
+                let idx = ((x / max) * (ramp.len() - 1) as f64).round() as usize;
+                ramp[idx.clamp(1, ramp.len() - 1)] as char
+            }
+        })
+        .collect()
+}
+
+/// Print capacity and activity/capacity rows aligned under the timeline bars.
+pub fn render_capacity_overlay(
+    counts: &[usize],
+    capacity: &[Option<f64>],
+    left_pad: usize,
+    color: bool,
+) {
+    let label_width = left_pad.saturating_sub(2);
+    let axis_char = if color { '│' } else { '|' };
+    let (dim_start, dim_end) = if color {
+        ("\x1b[90m", "\x1b[0m")
+    } else {
+        ("", "")
+    };
+    let ratio = activity_ratio(counts, capacity);
+    for (label, row) in [("cap", capacity), ("act", ratio.as_slice())] {
+        println!(
+            "{}{:>width$} {}{}{}",
+            dim_start,
+            label,
+            axis_char,
+            dim_end,
+            build_overlay_row(row),
+            width = label_width
+        );
+    }
+    let cap_max = capacity.iter().flatten().copied().fold(0.0f64, f64::max);
+    let ratio_max = ratio.iter().flatten().copied().fold(0.0f64, f64::max);
+    println!(
+        "{}cap: person-days/week (max={:.1}), act: commits per person-day (max={:.2}), '?' = no capacity data{}",
+        dim_start, cap_max, ratio_max, dim_end
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_capacity_csv_with_header_and_comments() {
+        let text = "week,person_days\n# holidays\n2024-01-01,10\n2024-01-08, 2.5\n\n";
+        let entries = parse_capacity_csv(text).expect("parse");
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].week_start, unix_from_ymd(2024, 1, 1));
+        assert_eq!(entries[1].person_days, 2.5);
+
+        assert!(parse_capacity_csv("2024-01-01,10\nbogus,3\n").is_err());
+        assert!(parse_capacity_csv("2024-13-01,10\n").is_err());
+    }
+
+    #[test]
+    fn test_compute_capacity_weeks_bins_like_timeline() {
+        const WEEK: u64 = 604_800;
+        let now = 10 * WEEK;
+        let entries = vec![
+            CapacityEntry {
+                week_start: now - WEEK / 2,
+                person_days: 5.0,
+            },
+            CapacityEntry {
+                week_start: now - 2 * WEEK - 1,
+                person_days: 0.0,
+            },
+        ];
+        let cap = compute_capacity_weeks(&entries, 4, now);
+        assert_eq!(cap, vec![None, Some(0.0), None, Some(5.0)]);
+    }
+
+    #[test]
+    fn test_overlay_rows() {
+        let cap = vec![None, Some(0.0), Some(5.0), Some(10.0)];
+        let ratio = activity_ratio(&[3, 4, 5, 5], &cap);
+        assert_eq!(ratio, vec![None, None, Some(1.0), Some(0.5)]);
+        assert_eq!(build_overlay_row(&cap), "? +@");
+        assert_eq!(build_overlay_row(&ratio), "??@+");
+    }
+}
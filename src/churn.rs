@@ -0,0 +1,265 @@
+//! `churn`: lines added and deleted per file over a recent window, or per
+//! file extension with `--by-ext` to see whether change is in code, configs
+//! or docs.
+
+use crate::error::GitInsightsError;
+use crate::git::run_command_line_iter;
+use crate::output::truncate;
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Window used when `--weeks` is not given.
+pub const DEFAULT_WEEKS: usize = 12;
+
+/// Bucket for files without an extension, e.g. `Makefile` or `.gitignore`.
+pub const NO_EXTENSION: &str = "(none)";
+
+/// Lines added and deleted in one file or extension.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineChurn {
+    pub added: usize,
+    pub deleted: usize,
+    /// Commits touching the file, or files with the extension.
+    pub count: usize,
+}
+
+impl LineChurn {
+    pub fn total(&self) -> usize {
+        self.added + self.deleted
+    }
+}
+
+/// Lowercased extension of `path` with its dot (`.rs`), or [`NO_EXTENSION`].
+/// Dotfiles such as `.gitignore` have none.
+pub fn file_extension(path: &str) -> String {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() && !ext.is_empty() => {
+            format!(".{}", ext.to_lowercase())
+        }
+        _ => NO_EXTENSION.to_string(),
+    }
+}
+
+/// Per-path churn from `git log --numstat --format=%x1f%aN%x1f%aE` output.
+/// Binary files (`-` counts) are skipped.
+pub fn churn_from_numstat<S: AsRef<str>>(
+    lines: impl Iterator<Item = S>,
+    allow: impl Fn(&str, &str) -> bool,
+    exclude: impl Fn(&str) -> bool,
+) -> BTreeMap<String, LineChurn> {
+    let mut files: BTreeMap<String, LineChurn> = BTreeMap::new();
+    let mut counting = false;
+    for line in lines {
+        let line = line.as_ref();
+        if let Some(header) = line.strip_prefix('\x1f') {
+            let (name, email) = header.split_once('\x1f').unwrap_or((header, ""));
+            counting = allow(name, email);
+        } else if counting {
+            let mut parts = line.splitn(3, '\t');
+            let (Some(a), Some(d), Some(path)) = (parts.next(), parts.next(), parts.next()) else {
+                continue;
+            };
+            let (Ok(a), Ok(d)) = (a.parse::<usize>(), d.parse::<usize>()) else {
+                continue;
+            };
+            if exclude(path) {
+                continue;
+            }
+            let entry = files.entry(path.to_string()).or_default();
+            entry.added += a;
+            entry.deleted += d;
+            entry.count += 1;
+        }
+    }
+    files
+}
+
+/// Fold per-path churn into one row per extension; `count` becomes the
+/// number of files.
+pub fn churn_by_extension(files: &BTreeMap<String, LineChurn>) -> BTreeMap<String, LineChurn> {
+    let mut exts: BTreeMap<String, LineChurn> = BTreeMap::new();
+    for (path, c) in files {
+        let entry = exts.entry(file_extension(path)).or_default();
+        entry.added += c.added;
+        entry.deleted += c.deleted;
+        entry.count += 1;
+    }
+    exts
+}
+
+/// Per-path churn of the last `weeks` weeks (all history for 0) under the
+/// active filter.
+pub fn gather_churn_lines(weeks: usize) -> Result<BTreeMap<String, LineChurn>, GitInsightsError> {
+    let revs = crate::filter::revision_args()?;
+    let authors = crate::filter::author_matcher()?;
+    let filter = crate::filter::active();
+    let mut args: Vec<String> = [
+        "--no-pager",
+        "log",
+        "--numstat",
+        "--no-renames",
+        "--format=%x1f%aN%x1f%aE",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+    if weeks > 0 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let span = (weeks as u64).saturating_mul(7 * 86_400);
+        args.push(format!("--since=@{}", now.saturating_sub(span)));
+    }
+    args.extend(crate::filter::history_args().iter().map(|s| s.to_string()));
+    args.extend(revs);
+    let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+    run_command_line_iter(&args, |lines| {
+        churn_from_numstat(
+            lines,
+            |n, e| authors.allows(n, e),
+            |p| filter.excludes_path(p),
+        )
+    })
+}
+
+/// Rows sorted by lines changed, most first, then by key.
+fn ranked(map: &BTreeMap<String, LineChurn>) -> Vec<(&String, &LineChurn)> {
+    let mut rows: Vec<(&String, &LineChurn)> = map.iter().collect();
+    rows.sort_by(|a, b| b.1.total().cmp(&a.1.total()).then_with(|| a.0.cmp(b.0)));
+    rows
+}
+
+/// Print the `top` most churned files, or extensions with `by_ext`.
+pub fn run_churn(weeks: usize, by_ext: bool, top: usize) -> Result<(), GitInsightsError> {
+    let files = gather_churn_lines(weeks)?;
+    if files.is_empty() {
+        println!("No changes.");
+        return Ok(());
+    }
+    let window = if weeks == 0 {
+        "all history".to_string()
+    } else {
+        format!("last {} weeks", weeks)
+    };
+    let total: usize = files.values().map(|c| c.total()).sum();
+    println!(
+        "Churn over {}: {} lines changed in {} files",
+        window,
+        total,
+        files.len()
+    );
+    println!();
+    let (label, width, count, map) = if by_ext {
+        ("extension", 20, "files", churn_by_extension(&files))
+    } else {
+        ("file", 60, "commits", files)
+    };
+    println!(
+        "| {:<width$} | {:>8} | {:>8} | {:>8} | {:>7} | {:>7} |",
+        label,
+        "added",
+        "deleted",
+        "churn",
+        "churn %",
+        count,
+        width = width
+    );
+    println!(
+        "|:{:-<w$}|{:->10}|{:->10}|{:->10}|{:->9}|{:->9}|",
+        "",
+        "",
+        "",
+        "",
+        "",
+        "",
+        w = width + 1
+    );
+    for (key, c) in ranked(&map).into_iter().take(top) {
+        println!(
+            "| {:<width$} | {:>8} | {:>8} | {:>8} | {:>7.1} | {:>7} |",
+            truncate(key, width),
+            c.added,
+            c.deleted,
+            c.total(),
+            c.total() as f64 * 100.0 / total.max(1) as f64,
+            c.count,
+            width = width
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_extension() {
+        assert_eq!(file_extension("src/main.rs"), ".rs");
+        assert_eq!(file_extension("docs/Guide.MD"), ".md");
+        assert_eq!(file_extension("pkg.v2/Makefile"), NO_EXTENSION);
+        assert_eq!(file_extension(".gitignore"), NO_EXTENSION);
+        assert_eq!(file_extension("a/b.tar.gz"), ".gz");
+    }
+
+    #[test]
+    fn test_churn_from_numstat_and_by_extension() {
+        let lines = [
+            "\x1fAnn\x1fann@x.io",
+            "10\t2\tsrc/lib.rs",
+            "3\t0\tREADME.md",
+            "-\t-\tlogo.png",
+            "\x1fBot\x1fbot@x.io",
+            "100\t100\tsrc/lib.rs",
+            "\x1fBob\x1fbob@x.io",
+            "1\t4\tsrc/main.rs",
+            "7\t7\tvendor/x.rs",
+        ];
+        let files = churn_from_numstat(
+            lines.iter(),
+            |n, _| n != "Bot",
+            |p| p.starts_with("vendor/"),
+        );
+        assert_eq!(
+            files["src/lib.rs"],
+            LineChurn {
+                added: 10,
+                deleted: 2,
+                count: 1
+            }
+        );
+        assert!(!files.contains_key("logo.png"));
+        assert!(!files.contains_key("vendor/x.rs"));
+
+        let exts = churn_by_extension(&files);
+        assert_eq!(
+            exts[".rs"],
+            LineChurn {
+                added: 11,
+                deleted: 6,
+                count: 2
+            }
+        );
+        assert_eq!(exts[".md"].total(), 3);
+        let order: Vec<&String> = ranked(&exts).into_iter().map(|(k, _)| k).collect();
+        assert_eq!(order, [".rs", ".md"]);
+    }
+
+    #[test]
+    fn test_gather_churn_lines_e2e() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        repo.commit_with_epoch("Ann", "ann@x.io", "src/a.rs", "fn a() {}", 1_700_000_000)
+            .expect("commit");
+        repo.commit_with_epoch("Bob", "bob@x.io", "docs/a.md", "# a", 1_700_000_100)
+            .expect("commit");
+        let files = crate::git::Repo::open(&repo.path)
+            .expect("open")
+            .scope(|| gather_churn_lines(0))
+            .expect("churn");
+        assert_eq!(files["src/a.rs"].added, 1);
+        assert_eq!(churn_by_extension(&files)[".md"].count, 1);
+    }
+}
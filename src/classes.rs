@@ -0,0 +1,290 @@
+//! `classes`: surviving lines and commits per author in path classes such
+//! as tests, docs and production code, defined in config or with `--class`.
+
+use crate::codeowners::pattern_matches;
+use crate::error::GitInsightsError;
+use crate::git::run_command_lines;
+use crate::merges::{blame_line_authors, run_blame_head, MergeResolver};
+use crate::output::{finish_progress, print_progress, truncate};
+use crate::stats::tracked_text_files_head;
+use std::collections::{BTreeSet, HashMap};
+use std::time::Instant;
+
+/// Bucket for files that match no class.
+pub const OTHER_CLASS: &str = "other";
+
+/// Classes used when neither the config nor `--class` defines any.
+pub const DEFAULT_CLASSES: &[(&str, &[&str])] = &[
+    (
+        "tests",
+        &[
+            "tests/",
+            "test/",
+            "__tests__/",
+            "*_test.*",
+            "test_*.py",
+            "*.test.*",
+            "*.spec.*",
+        ],
+    ),
+    ("docs", &["docs/", "doc/", "*.md", "*.rst", "*.adoc"]),
+];
+
+/// A named set of gitignore-style patterns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathClass {
+    pub name: String,
+    pub patterns: Vec<String>,
+}
+
+/// Parse `--class NAME=PATTERN`, e.g. `tests=**/*_test.rs`.
+pub fn parse_class_spec(spec: &str) -> Result<(String, String), String> {
+    match spec.split_once('=') {
+        Some((name, pattern)) if !name.trim().is_empty() && !pattern.trim().is_empty() => {
+            Ok((name.trim().to_string(), pattern.trim().to_string()))
+        }
+        _ => Err(format!(
+            "invalid value '{}' for '--class': expected NAME=PATTERN, e.g. tests=tests/**",
+            spec
+        )),
+    }
+}
+
+/// Group `(name, pattern)` pairs into classes, in order of first mention;
+/// [`DEFAULT_CLASSES`] when `specs` is empty.
+pub fn build_classes(specs: &[(String, String)]) -> Vec<PathClass> {
+    if specs.is_empty() {
+        return DEFAULT_CLASSES
+            .iter()
+            .map(|(name, patterns)| PathClass {
+                name: name.to_string(),
+                patterns: patterns.iter().map(|p| p.to_string()).collect(),
+            })
+            .collect();
+    }
+    let mut classes: Vec<PathClass> = Vec::new();
+    for (name, pattern) in specs {
+        match classes.iter_mut().find(|c| &c.name == name) {
+            Some(class) => class.patterns.push(pattern.clone()),
+            None => classes.push(PathClass {
+                name: name.clone(),
+                patterns: vec![pattern.clone()],
+            }),
+        }
+    }
+    classes
+}
+
+/// The first class with a pattern matching `path`, else [`OTHER_CLASS`].
+pub fn class_for_path<'a>(classes: &'a [PathClass], path: &str) -> &'a str {
+    classes
+        .iter()
+        .find(|c| c.patterns.iter().any(|p| pattern_matches(p, path)))
+        .map(|c| c.name.as_str())
+        .unwrap_or(OTHER_CLASS)
+}
+
+/// One author's share of one class.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClassStats {
+    /// Surviving lines at HEAD.
+    pub loc: usize,
+    /// Commits touching at least one file of the class.
+    pub commits: usize,
+}
+
+/// `author -> class -> stats`.
+pub type ClassMap = HashMap<String, HashMap<String, ClassStats>>;
+
+/// Count each commit of `git log --format=%x1f%aN%x1f%aE --name-only` once
+/// per class it touches.
+fn tally_commit(map: &mut ClassMap, author: Option<&str>, touched: &mut BTreeSet<&str>) {
+    if let Some(author) = author {
+        let entry = map.entry(author.to_string()).or_default();
+        for class in touched.iter() {
+            entry.entry(class.to_string()).or_default().commits += 1;
+        }
+    }
+    touched.clear();
+}
+
+/// Blame every text file at HEAD and walk the log, grouping lines and
+/// commits by author and class.
+pub fn gather_classes(classes: &[PathClass]) -> Result<ClassMap, GitInsightsError> {
+    let files = tracked_text_files_head()?;
+    let authors = crate::filter::author_matcher()?;
+    let merges = MergeResolver::load()?;
+    let names = crate::identity::active_normalization();
+    let mut map = ClassMap::new();
+    let total = files.len();
+    let start = Instant::now();
+    for (n, file) in files.iter().enumerate() {
+        print_progress(n + 1, total, start);
+        let Ok(blame) = run_blame_head(file) else {
+            continue;
+        };
+        let class = class_for_path(classes, file);
+        for (name, mail) in blame_line_authors(&blame, file, &merges) {
+            if authors.allows(&name, &mail) {
+                let entry = map.entry(names.apply(name)).or_default();
+                entry.entry(class.to_string()).or_default().loc += 1;
+            }
+        }
+    }
+    if total > 0 {
+        finish_progress();
+    }
+
+    let revs = crate::filter::revision_args()?;
+    let mut args = vec![
+        "--no-pager",
+        "log",
+        "--format=%x1f%aN%x1f%aE",
+        "--name-only",
+    ];
+    args.extend(crate::filter::history_args());
+    args.extend(revs.iter().map(|s| s.as_str()));
+    let mut author: Option<String> = None;
+    let mut touched = BTreeSet::new();
+    run_command_lines(&args, |line| {
+        if let Some(header) = line.strip_prefix('\x1f') {
+            tally_commit(&mut map, author.as_deref(), &mut touched);
+            let (name, email) = header.split_once('\x1f').unwrap_or((header, ""));
+            author = authors
+                .allows(name, email)
+                .then(|| names.apply(name.to_string()));
+        } else if !line.trim().is_empty() {
+            touched.insert(class_for_path(classes, line.trim()));
+        }
+    })?;
+    tally_commit(&mut map, author.as_deref(), &mut touched);
+    Ok(map)
+}
+
+/// Print one row per author and class, authors with the most lines first.
+pub fn run_classes(specs: &[(String, String)]) -> Result<(), GitInsightsError> {
+    let classes = build_classes(specs);
+    let map = gather_classes(&classes)?;
+    let order: Vec<&str> = classes
+        .iter()
+        .map(|c| c.name.as_str())
+        .chain([OTHER_CLASS])
+        .collect();
+
+    let mut totals: HashMap<&str, ClassStats> = HashMap::new();
+    for per_class in map.values() {
+        for (class, s) in per_class {
+            let t = totals.entry(class.as_str()).or_default();
+            t.loc += s.loc;
+            t.commits += s.commits;
+        }
+    }
+    let total_loc: usize = totals.values().map(|s| s.loc).sum();
+    println!(
+        "| {:<20} | {:>8} | {:>6} | {:>7} | {:>7} |",
+        "class", "loc", "loc %", "commits", "authors"
+    );
+    println!(
+        "|:{:-<21}|{:->10}|{:->8}|{:->9}|{:->9}|",
+        "", "", "", "", ""
+    );
+    for class in &order {
+        let s = totals.get(class).copied().unwrap_or_default();
+        let authors = map
+            .values()
+            .filter(|c| c.get(*class).is_some_and(|s| s.loc > 0 || s.commits > 0))
+            .count();
+        println!(
+            "| {:<20} | {:>8} | {:>6.1} | {:>7} | {:>7} |",
+            truncate(class, 20),
+            s.loc,
+            s.loc as f64 * 100.0 / total_loc.max(1) as f64,
+            s.commits,
+            authors
+        );
+    }
+
+    let mut authors: Vec<(&String, usize)> = map
+        .iter()
+        .map(|(a, c)| (a, c.values().map(|s| s.loc).sum()))
+        .collect();
+    authors.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    println!();
+    println!(
+        "| {:<28} | {:<20} | {:>8} | {:>6} | {:>7} |",
+        "author", "class", "loc", "loc %", "commits"
+    );
+    println!(
+        "|:{:-<29}|:{:-<21}|{:->10}|{:->8}|{:->9}|",
+        "", "", "", "", ""
+    );
+    for (author, loc) in authors {
+        for class in &order {
+            let Some(s) = map[author].get(*class) else {
+                continue;
+            };
+            println!(
+                "| {:<28} | {:<20} | {:>8} | {:>6.1} | {:>7} |",
+                truncate(author, 28),
+                truncate(class, 20),
+                s.loc,
+                s.loc as f64 * 100.0 / loc.max(1) as f64,
+                s.commits
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_class_specs_and_matching() {
+        assert_eq!(
+            parse_class_spec("tests=**/*_test.rs"),
+            Ok(("tests".to_string(), "**/*_test.rs".to_string()))
+        );
+        assert!(parse_class_spec("tests").is_err());
+        assert!(parse_class_spec("=docs/").is_err());
+
+        let classes = build_classes(&[
+            ("tests".to_string(), "tests/**".to_string()),
+            ("docs".to_string(), "*.md".to_string()),
+            ("tests".to_string(), "**/*_test.rs".to_string()),
+        ]);
+        assert_eq!(classes.len(), 2);
+        assert_eq!(classes[0].patterns.len(), 2);
+        assert_eq!(class_for_path(&classes, "tests/it/main.rs"), "tests");
+        assert_eq!(class_for_path(&classes, "src/io_test.rs"), "tests");
+        assert_eq!(class_for_path(&classes, "src/lib/README.md"), "docs");
+        assert_eq!(class_for_path(&classes, "src/lib.rs"), OTHER_CLASS);
+
+        let defaults = build_classes(&[]);
+        assert_eq!(class_for_path(&defaults, "pkg/parse_test.go"), "tests");
+        assert_eq!(class_for_path(&defaults, "docs/guide.txt"), "docs");
+    }
+
+    #[test]
+    fn test_gather_classes_e2e() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        repo.commit_with_epoch("Ann", "ann@x.io", "src/lib.rs", "fn a() {}", 1_700_000_000)
+            .expect("commit");
+        repo.commit_with_epoch("Bob", "bob@x.io", "tests/a.rs", "#[test]", 1_700_000_100)
+            .expect("commit");
+        repo.commit_with_epoch("Bob", "bob@x.io", "README.md", "# hi", 1_700_000_200)
+            .expect("commit");
+        let classes = build_classes(&[("tests".to_string(), "tests/".to_string())]);
+
+        let map = crate::git::Repo::open(&repo.path)
+            .expect("open")
+            .scope(|| gather_classes(&classes))
+            .expect("classes");
+        assert_eq!(map["Ann"][OTHER_CLASS], ClassStats { loc: 1, commits: 1 });
+        assert_eq!(map["Bob"]["tests"], ClassStats { loc: 1, commits: 1 });
+        assert_eq!(map["Bob"][OTHER_CLASS].commits, 1);
+        assert!(!map["Ann"].contains_key("tests"));
+    }
+}
@@ -1,4 +1,15 @@
+use crate::classes::parse_class_spec;
+use crate::filter::{AuthorScope, BlameOptions, CommitFilter, MergeLines, TimeZoneMode, WeekStart};
+use crate::identity::NameNormalization;
+use crate::log::Verbosity;
+use crate::output::ColorChoice;
+use crate::palette::Palette;
+use crate::stats::TextDetection;
+use crate::watch::DEFAULT_WATCH_SECS;
+use std::path::PathBuf;
+
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum HelpTopic {
     Top,
     Stats,
@@ -7,28 +18,116 @@ pub enum HelpTopic {
     Timeline,
     Heatmap,
     CodeFrequency,
+    Report,
+    Export,
+    View,
+    Badge,
+    LogJson,
+    Hotspots,
+    Interactive,
+    CodeownersCheck,
+    CodeownersAudit,
+    Messages,
+    Quality,
+    Signing,
+    Churn,
+    Daemon,
+    Owners,
+    File,
+    Releases,
+    Branches,
+    Languages,
+    Classes,
+    Risk,
+    TimeAudit,
+    EncodingAudit,
+    Contributors,
+    Growth,
+    OwnershipByTenure,
+    OwnershipHistory,
+    Velocity,
+    OwnershipMatrix,
+    Compare,
+    Survival,
+    Leaderboard,
+    Man,
+    Serve,
 }
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Commands {
     Stats {
         by_name: bool,
+        /// One row per email domain (`--by-domain`); implies grouping by email.
+        by_domain: bool,
+        /// Reuse the stored blame snapshot, re-blaming only changed files.
+        incremental: bool,
+        /// Rows printed before the rest are summarized (`Some(0)` = all).
+        max_rows: Option<usize>,
+        /// Write the full table to this file instead of stdout.
+        output: Option<String>,
+        /// `SINCE..UNTIL` window to compare with the one after it.
+        compare_with: Option<String>,
+        /// Approximate LOC from `log --numstat` instead of blame.
+        fast: bool,
+        /// Refresh every N seconds (`--watch`); `None` runs once.
+        watch: Option<u64>,
+        /// Row order: `loc`, `commits`, `files` or `name` (`--sort`).
+        sort: Option<String>,
+        /// `--asc` (`Some(true)`) or `--desc`; `None` uses the sort's default.
+        ascending: Option<bool>,
+        /// Only the first N rows after sorting (`Some(0)` = all).
+        top: Option<usize>,
+    },
+    Json {
+        output: Option<String>,
     },
-    Json,
     Timeline {
         weeks: Option<usize>,
-        color: bool,
+        color: ColorChoice,
+        capacity: Option<String>,
+        height: Option<usize>,
+        /// Burst factor (`--bursts`); `None` leaves bursts unmarked.
+        bursts: Option<f64>,
+        burst_commits: Option<usize>,
+        /// `commits` (default) or `contributors` (`--metric`).
+        metric: Option<String>,
+        /// `day`, `week` (default) or `month` buckets (`--granularity`).
+        granularity: Option<String>,
+        /// `bars` (default) or `braille` (`--style`).
+        style: Option<String>,
+        /// Chart file (`--output`): `.svg`, or `.png` with the `charts` feature.
+        output: Option<String>,
+        /// Refresh every N seconds (`--watch`); `None` runs once.
+        watch: Option<u64>,
     },
     Heatmap {
         weeks: Option<usize>,
-        color: bool,
+        color: ColorChoice,
+        output: Option<String>,
+        /// `global` (default), `row` or `log` shading (`--normalize`).
+        normalize: Option<String>,
+        /// Refresh every N seconds (`--watch`); `None` runs once.
+        watch: Option<u64>,
     },
     CodeFrequency {
         group: Option<String>,
         heatmap: Option<String>,
         weeks: Option<usize>,
-        color: bool,
+        color: ColorChoice,
         table: bool,
+        output: Option<String>,
+        width: Option<usize>,
+        /// One small heatmap per author (`--per-author`), for the top `top`.
+        per_author: bool,
+        top: Option<usize>,
+        /// Weekday/weekend and working/off-hours breakdown (`--summary`).
+        summary: bool,
+        /// `global` (default), `row` or `log` heatmap shading (`--normalize`).
+        normalize: Option<String>,
+        /// `commits` (default) or `lines` added+deleted per commit (`--weight`).
+        weight: Option<String>,
     },
     User {
         username: String,
@@ -36,7 +135,166 @@ pub enum Commands {
         by_email: bool,
         top: Option<usize>,
         sort: Option<String>,
+        /// GitHub login to enrich the summary with (`--github`).
+        github: Option<String>,
+        /// Extra commit message patterns counted as pull requests.
+        pr_patterns: Vec<regex::Regex>,
+        /// `--ownership` rows below this share of the file are hidden.
+        min_pct: Option<f64>,
+        /// `--ownership` rows with fewer of the user's lines are hidden.
+        min_loc: Option<usize>,
+    },
+    Report {
+        format: Option<String>,
+        output: Option<String>,
+    },
+    Export {
+        prometheus: Option<String>,
+        xlsx: Option<String>,
+    },
+    View {
+        path: String,
+        format: Option<String>,
+        output: Option<String>,
+    },
+    Badge {
+        metric: Option<String>,
+    },
+    LogJson {
+        output: Option<String>,
+    },
+    Hotspots {
+        top: Option<usize>,
+        size: Option<String>,
+    },
+    CodeownersCheck {
+        file: Option<String>,
+        min_pct: Option<f64>,
+        stale_days: Option<u64>,
+    },
+    CodeownersAudit {
+        file: Option<String>,
+        /// Minimum share of surviving lines per declared owner.
+        min_pct: Option<f64>,
+    },
+    Messages {
+        by: Option<String>,
+        /// Top subject terms and length figures instead of commit types.
+        keywords: bool,
+        /// Terms per table with `--keywords`.
+        top: Option<usize>,
+        /// Subjects shorter than this many characters count as short.
+        under: Option<usize>,
+    },
+    Quality,
+    Signing,
+    Churn {
+        weeks: Option<usize>,
+        /// One row per file extension instead of per file.
+        by_ext: bool,
+        top: Option<usize>,
+    },
+    Daemon {
+        interval: Option<String>,
+        repos_file: Option<String>,
+        export_dir: Option<String>,
+        analyses: Option<String>,
+        /// Webhook URL for the per-cycle digest.
+        notify: Option<String>,
+        once: bool,
+    },
+    Owners {
+        file: Option<String>,
+        line: Option<usize>,
+        format: Option<String>,
+        top: Option<usize>,
+    },
+    /// Per-author breakdown of one file or directory.
+    File {
+        path: String,
+        format: Option<String>,
+        top: Option<usize>,
+    },
+    Releases {
+        tags: Option<String>,
+        top: Option<usize>,
+    },
+    Branches {
+        base: Option<String>,
+        stale_days: Option<u64>,
+    },
+    Languages {
+        top: Option<usize>,
+    },
+    /// `--class NAME=PATTERN` pairs, in order; empty for the defaults.
+    Classes {
+        classes: Vec<(String, String)>,
+    },
+    Risk {
+        weights: Option<String>,
+        top: Option<usize>,
+        format: Option<String>,
+    },
+    TimeAudit {
+        max_skew_hours: Option<u64>,
+    },
+    EncodingAudit,
+    Contributors {
+        sort: Option<String>,
+    },
+    Growth {
+        color: ColorChoice,
+        format: Option<String>,
+        /// Codebase size over time instead of contributors (`--loc`).
+        loc: bool,
+        samples: Option<usize>,
+    },
+    OwnershipByTenure,
+    OwnershipHistory {
+        username: String,
+        by_email: bool,
+        samples: Option<usize>,
+        color: ColorChoice,
+        format: Option<String>,
+    },
+    Velocity {
+        iteration: Option<String>,
+        start: Option<String>,
+        color: ColorChoice,
+    },
+    OwnershipMatrix {
+        min_pct: Option<f64>,
+        format: Option<String>,
+        by_name: bool,
+        output: Option<String>,
+    },
+    Compare {
+        ref_a: String,
+        ref_b: String,
+        by_name: bool,
+        color: ColorChoice,
+    },
+    Survival {
+        samples: Option<usize>,
+    },
+    Top {
+        metric: Option<String>,
+        limit: Option<usize>,
+        color: ColorChoice,
+    },
+    Man {
+        /// Page of this command; the main page when `None`.
+        command: Option<String>,
+        /// Write every page into this directory instead.
+        dir: Option<String>,
     },
+    Serve {
+        /// `None` for [`crate::serve::DEFAULT_SERVE_PORT`].
+        port: Option<u16>,
+        /// Address to bind; `None` for loopback only.
+        host: Option<String>,
+    },
+    Interactive,
     Help {
         topic: HelpTopic,
     },
@@ -46,20 +304,43 @@ pub enum Commands {
 #[derive(Debug)]
 pub struct Cli {
     pub command: Commands,
+    pub filter: CommitFilter,
+    pub text_detection: TextDetection,
+    /// Concurrent blame workers (`--jobs`); `None` uses one per CPU.
+    pub jobs: Option<usize>,
+    /// Extra mailmap file (`--mailmap`), made absolute.
+    pub mailmap: Option<PathBuf>,
+    /// Diagnostics on stderr (`--quiet`, `--verbose`).
+    pub verbosity: Verbosity,
+    /// Include initialized submodules (`--recurse-submodules`).
+    pub recurse_submodules: bool,
+    /// Color ramp of shaded charts (`--palette`).
+    pub palette: Palette,
 }
 
 impl Cli {
+    /// Parse the process arguments after applying `--profile` and the config
+    /// files (see [`crate::config::expand_args`]).
     pub fn parse() -> Result<Cli, String> {
         let args: Vec<String> = std::env::args().collect();
-        Cli::parse_from_args(args)
+        Cli::parse_from_args(crate::config::expand_args(args)?)
     }
 
+    /// Parse `args` (program name first) exactly as given: no config files
+    /// are read and no git commands run.
     pub fn parse_from_args(args: Vec<String>) -> Result<Cli, String> {
         if args.len() < 2 {
             return Ok(Cli {
                 command: Commands::Help {
                     topic: HelpTopic::Top,
                 },
+                filter: CommitFilter::default(),
+                text_detection: TextDetection::default(),
+                jobs: None,
+                mailmap: None,
+                verbosity: Verbosity::default(),
+                recurse_submodules: false,
+                palette: Palette::default(),
             });
         }
 
@@ -70,14 +351,32 @@ impl Cli {
                 command: Commands::Help {
                     topic: HelpTopic::Top,
                 },
+                filter: CommitFilter::default(),
+                text_detection: TextDetection::default(),
+                jobs: None,
+                mailmap: None,
+                verbosity: Verbosity::default(),
+                recurse_submodules: false,
+                palette: Palette::default(),
             });
         }
         if command_str == "-v" || command_str == "--version" {
             return Ok(Cli {
                 command: Commands::Version,
+                filter: CommitFilter::default(),
+                text_detection: TextDetection::default(),
+                jobs: None,
+                mailmap: None,
+                verbosity: Verbosity::default(),
+                recurse_submodules: false,
+                palette: Palette::default(),
             });
         }
 
+        if !has_flag(&args[2..], "-h") && !has_flag(&args[2..], "--help") {
+            validate_args(command_str, &args[2..])?;
+        }
+
         let command = match command_str.as_str() {
             "stats" => {
                 if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
@@ -86,8 +385,92 @@ impl Cli {
                     }
                 } else {
                     let by_email = has_flag(&args[2..], "--by-email") || has_flag(&args[2..], "-e");
-                    let by_name = !by_email;
-                    Commands::Stats { by_name }
+                    let by_domain = has_flag(&args[2..], "--by-domain");
+                    let by_name = !by_email && !by_domain;
+                    let incremental = has_flag(&args[2..], "--incremental");
+                    let fast = has_flag(&args[2..], "--fast");
+                    let mut max_rows: Option<usize> = None;
+                    let mut compare_with: Option<String> = None;
+                    let mut sort: Option<String> = None;
+                    let mut top: Option<usize> = None;
+                    let rest = &args[2..];
+                    let ascending = match (has_flag(rest, "--asc"), has_flag(rest, "--desc")) {
+                        (true, true) => {
+                            return Err("--asc and --desc cannot be combined".to_string())
+                        }
+                        (true, false) => Some(true),
+                        (false, true) => Some(false),
+                        (false, false) => None,
+                    };
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = &rest[i];
+                        if a == "--sort" {
+                            if i + 1 < rest.len() {
+                                sort = Some(rest[i + 1].to_lowercase());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--sort=") {
+                            sort = Some(eq.to_lowercase());
+                        } else if a == "--top" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<usize>() {
+                                    top = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--top=") {
+                            if let Ok(v) = eq.parse::<usize>() {
+                                top = Some(v);
+                            }
+                        } else if a == "--max-rows" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<usize>() {
+                                    max_rows = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--max-rows=") {
+                            if let Ok(v) = eq.parse::<usize>() {
+                                max_rows = Some(v);
+                            }
+                        } else if a == "--compare-with" {
+                            if i + 1 < rest.len() {
+                                compare_with = Some(rest[i + 1].clone());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--compare-with=") {
+                            compare_with = Some(eq.to_string());
+                        }
+                        i += 1;
+                    }
+                    let output = output_flag(rest);
+                    let watch = parse_watch(rest)?;
+                    if watch.is_some() && (output.is_some() || compare_with.is_some()) {
+                        return Err("--watch cannot be combined with --output or --compare-with"
+                            .to_string());
+                    }
+                    if compare_with.is_some()
+                        && (sort.is_some() || ascending.is_some() || top.is_some() || by_domain)
+                    {
+                        return Err(
+                            "--sort, --asc, --desc, --top and --by-domain cannot be combined with --compare-with"
+                                .to_string(),
+                        );
+                    }
+                    Commands::Stats {
+                        by_name,
+                        by_domain,
+                        incremental,
+                        max_rows,
+                        output,
+                        compare_with,
+                        fast,
+                        watch,
+                        sort,
+                        ascending,
+                        top,
+                    }
                 }
             }
             "json" => {
@@ -96,7 +479,9 @@ impl Cli {
                         topic: HelpTopic::Json,
                     }
                 } else {
-                    Commands::Json
+                    Commands::Json {
+                        output: output_flag(&args[2..]),
+                    }
                 }
             }
             "user" => {
@@ -113,6 +498,10 @@ impl Cli {
                     let mut by_email = false;
                     let mut top: Option<usize> = None;
                     let mut sort: Option<String> = None;
+                    let mut github_login: Option<String> = None;
+                    let mut pr_patterns: Vec<regex::Regex> = Vec::new();
+                    let mut min_pct: Option<f64> = None;
+                    let mut min_loc: Option<usize> = None;
 
                     let rest = &args[3..];
                     let mut i = 0;
@@ -140,9 +529,69 @@ impl Cli {
                             }
                         } else if let Some(eq) = a.strip_prefix("--sort=") {
                             sort = Some(eq.to_lowercase());
+                        } else if a == "--min-pct" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<f64>() {
+                                    min_pct = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--min-pct=") {
+                            if let Ok(v) = eq.parse::<f64>() {
+                                min_pct = Some(v);
+                            }
+                        } else if a == "--min-loc" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<usize>() {
+                                    min_loc = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--min-loc=") {
+                            if let Ok(v) = eq.parse::<usize>() {
+                                min_loc = Some(v);
+                            }
+                        } else if a == "--github-login" {
+                            if i + 1 < rest.len() {
+                                github_login = Some(rest[i + 1].clone());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--github-login=") {
+                            github_login = Some(eq.to_string());
+                        } else if a == "--pr-pattern" || a.starts_with("--pr-pattern=") {
+                            let pattern = match a.strip_prefix("--pr-pattern=") {
+                                Some(eq) => eq,
+                                None => {
+                                    i += 1;
+                                    rest.get(i).map(|v| v.as_str()).unwrap_or("")
+                                }
+                            };
+                            let re = regex::Regex::new(pattern).map_err(|e| {
+                                format!("invalid value '{}' for '--pr-pattern': {}", pattern, e)
+                            })?;
+                            pr_patterns.push(re);
                         }
                         i += 1;
                     }
+                    let github = if github_login.is_some() {
+                        github_login
+                    } else if has_flag(rest, "--github") {
+                        if username.contains('@') {
+                            return Err(
+                                "--github needs --github-login LOGIN when matching by email"
+                                    .to_string(),
+                            );
+                        }
+                        Some(username.clone())
+                    } else {
+                        None
+                    };
+                    if github.is_some() && ownership {
+                        return Err("--github cannot be combined with --ownership".to_string());
+                    }
+                    if !ownership && (min_pct.is_some() || min_loc.is_some()) {
+                        return Err("--min-pct and --min-loc require --ownership".to_string());
+                    }
 
                     Commands::User {
                         username,
@@ -150,6 +599,10 @@ impl Cli {
                         by_email,
                         top,
                         sort,
+                        github,
+                        pr_patterns,
+                        min_pct,
+                        min_loc,
                     }
                 }
             }
@@ -160,13 +613,73 @@ impl Cli {
                     }
                 } else {
                     let mut weeks: Option<usize> = None;
-                    let mut color = true;
+                    let color = parse_color(&args[2..])?;
+                    let mut capacity: Option<String> = None;
+                    let mut height: Option<usize> = None;
+                    let mut bursts: Option<f64> = None;
+                    let mut burst_commits: Option<usize> = None;
+                    let mut granularity: Option<String> = None;
+                    let mut style: Option<String> = None;
+                    let output = output_flag(&args[2..]);
 
+                    let mut metric: Option<String> = None;
                     let rest = &args[2..];
                     let mut i = 0;
                     while i < rest.len() {
                         let a = &rest[i];
-                        if a == "--weeks" {
+                        if a == "--output" || a == "-o" {
+                            i += 1;
+                        } else if a == "--metric" {
+                            if i + 1 < rest.len() {
+                                metric = Some(rest[i + 1].to_lowercase());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--metric=") {
+                            metric = Some(eq.to_lowercase());
+                        } else if a == "--bursts" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<f64>() {
+                                    bursts = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--bursts=") {
+                            if let Ok(v) = eq.parse::<f64>() {
+                                bursts = Some(v);
+                            }
+                        } else if a == "--burst-commits" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<usize>() {
+                                    burst_commits = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--burst-commits=") {
+                            if let Ok(v) = eq.parse::<usize>() {
+                                burst_commits = Some(v);
+                            }
+                        } else if a == "--capacity" {
+                            if i + 1 < rest.len() {
+                                capacity = Some(rest[i + 1].clone());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--capacity=") {
+                            capacity = Some(eq.to_string());
+                        } else if a == "--granularity" {
+                            if i + 1 < rest.len() {
+                                granularity = Some(rest[i + 1].to_lowercase());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--granularity=") {
+                            granularity = Some(eq.to_lowercase());
+                        } else if a == "--style" {
+                            if i + 1 < rest.len() {
+                                style = Some(rest[i + 1].to_lowercase());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--style=") {
+                            style = Some(eq.to_lowercase());
+                        } else if a == "--weeks" {
                             if i + 1 < rest.len() {
                                 if let Ok(v) = rest[i + 1].parse::<usize>() {
                                     weeks = Some(v);
@@ -177,10 +690,17 @@ impl Cli {
                             if let Ok(v) = eq.parse::<usize>() {
                                 weeks = Some(v);
                             }
-                        } else if a == "--color" || a == "-c" {
-                            color = true;
-                        } else if a == "--no-color" {
-                            color = false;
+                        } else if a == "--height" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<usize>() {
+                                    height = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--height=") {
+                            if let Ok(v) = eq.parse::<usize>() {
+                                height = Some(v);
+                            }
                         } else if let Some(num) = a.strip_prefix("--") {
                             if num.chars().all(|c| c.is_ascii_digit()) {
                                 if let Ok(v) = num.parse::<usize>() {
@@ -196,7 +716,32 @@ impl Cli {
                         }
                         i += 1;
                     }
-                    Commands::Timeline { weeks, color }
+                    let watch = parse_watch(rest)?;
+                    if output.is_some()
+                        && (watch.is_some()
+                            || capacity.is_some()
+                            || bursts.is_some()
+                            || burst_commits.is_some()
+                            || style.is_some())
+                    {
+                        return Err(
+                            "--output cannot be combined with --watch, --capacity, --bursts, --burst-commits or --style"
+                                .to_string(),
+                        );
+                    }
+                    Commands::Timeline {
+                        weeks,
+                        color,
+                        capacity,
+                        height,
+                        bursts,
+                        burst_commits,
+                        metric,
+                        granularity,
+                        style,
+                        output,
+                        watch,
+                    }
                 }
             }
             "heatmap" => {
@@ -206,13 +751,24 @@ impl Cli {
                     }
                 } else {
                     let mut weeks: Option<usize> = None;
-                    let mut color = true;
+                    let mut normalize: Option<String> = None;
+                    let color = parse_color(&args[2..])?;
+                    let output = output_flag(&args[2..]);
 
                     let rest = &args[2..];
                     let mut i = 0;
                     while i < rest.len() {
                         let a = &rest[i];
-                        if a == "--weeks" {
+                        if a == "--output" || a == "-o" {
+                            i += 1;
+                        } else if a == "--normalize" {
+                            if i + 1 < rest.len() {
+                                normalize = Some(rest[i + 1].to_lowercase());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--normalize=") {
+                            normalize = Some(eq.to_lowercase());
+                        } else if a == "--weeks" {
                             if i + 1 < rest.len() {
                                 if let Ok(v) = rest[i + 1].parse::<usize>() {
                                     weeks = Some(v);
@@ -223,10 +779,6 @@ impl Cli {
                             if let Ok(v) = eq.parse::<usize>() {
                                 weeks = Some(v);
                             }
-                        } else if a == "--color" || a == "-c" {
-                            color = true;
-                        } else if a == "--no-color" {
-                            color = false;
                         } else if let Some(num) = a.strip_prefix("--") {
                             if num.chars().all(|c| c.is_ascii_digit()) {
                                 if let Ok(v) = num.parse::<usize>() {
@@ -242,7 +794,20 @@ impl Cli {
                         }
                         i += 1;
                     }
-                    Commands::Heatmap { weeks, color }
+                    let watch = parse_watch(&args[2..])?;
+                    if watch.is_some() && output.is_some() {
+                        return Err("--watch cannot be combined with --output".to_string());
+                    }
+                    if normalize.is_some() && output.is_some() {
+                        return Err("--normalize cannot be combined with --output".to_string());
+                    }
+                    Commands::Heatmap {
+                        weeks,
+                        color,
+                        output,
+                        normalize,
+                        watch,
+                    }
                 }
             }
             "code-frequency" => {
@@ -254,14 +819,23 @@ impl Cli {
                     let mut group: Option<String> = None;
                     let mut heatmap: Option<String> = None;
                     let mut weeks: Option<usize> = None;
-                    let mut color = true;
+                    let color = parse_color(&args[2..])?;
                     let mut table = false;
+                    let mut width: Option<usize> = None;
+                    let mut per_author = false;
+                    let mut top: Option<usize> = None;
+                    let mut summary = false;
+                    let mut normalize: Option<String> = None;
+                    let mut weight: Option<String> = None;
+                    let output = output_flag(&args[2..]);
 
                     let rest = &args[2..];
                     let mut i = 0;
                     while i < rest.len() {
                         let a = &rest[i];
-                        if a == "--weeks" {
+                        if a == "--output" || a == "-o" {
+                            i += 1;
+                        } else if a == "--weeks" {
                             if i + 1 < rest.len() {
                                 if let Ok(v) = rest[i + 1].parse::<usize>() {
                                     weeks = Some(v);
@@ -286,12 +860,48 @@ impl Cli {
                             }
                         } else if let Some(eq) = a.strip_prefix("--heatmap=") {
                             heatmap = Some(eq.to_lowercase());
-                        } else if a == "--color" || a == "-c" {
-                            color = true;
-                        } else if a == "--no-color" {
-                            color = false;
+                        } else if a == "--normalize" {
+                            if i + 1 < rest.len() {
+                                normalize = Some(rest[i + 1].to_lowercase());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--normalize=") {
+                            normalize = Some(eq.to_lowercase());
+                        } else if a == "--weight" {
+                            if i + 1 < rest.len() {
+                                weight = Some(rest[i + 1].to_lowercase());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--weight=") {
+                            weight = Some(eq.to_lowercase());
+                        } else if a == "--width" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<usize>() {
+                                    width = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--width=") {
+                            if let Ok(v) = eq.parse::<usize>() {
+                                width = Some(v);
+                            }
                         } else if a == "--table" {
                             table = true;
+                        } else if a == "--per-author" {
+                            per_author = true;
+                        } else if a == "--summary" {
+                            summary = true;
+                        } else if a == "--top" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<usize>() {
+                                    top = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--top=") {
+                            if let Ok(v) = eq.parse::<usize>() {
+                                top = Some(v);
+                            }
                         } else if let Some(num) = a.strip_prefix("--") {
                             if num.chars().all(|c| c.is_ascii_digit()) {
                                 if let Ok(v) = num.parse::<usize>() {
@@ -313,626 +923,5688 @@ impl Cli {
                         weeks,
                         color,
                         table,
+                        output,
+                        width,
+                        per_author,
+                        top,
+                        summary,
+                        normalize,
+                        weight,
                     }
                 }
             }
-            _ => {
-                return Err(format!(
-                    "Unknown command: {}\n{}",
-                    command_str,
-                    render_help(HelpTopic::Top)
-                ));
-            }
-        };
-
-        Ok(Cli { command })
-    }
-}
-
-fn has_flag(args: &[String], needle: &str) -> bool {
-    args.iter().any(|a| a == needle)
-}
-
-pub fn render_help(topic: HelpTopic) -> String {
-    match topic {
-        HelpTopic::Top => {
-            let ver = version_string();
-            format!(
-                "\
-git-insights v{ver}
+            "report" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::Report,
+                    }
+                } else {
+                    let mut format: Option<String> = None;
 
-A CLI tool to generate Git repo stats and insights (no dependencies).
+                    let rest = &args[2..];
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = &rest[i];
+                        if a == "--format" {
+                            if i + 1 < rest.len() {
+                                format = Some(rest[i + 1].to_lowercase());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--format=") {
+                            format = Some(eq.to_lowercase());
+                        }
+                        i += 1;
+                    }
+                    Commands::Report {
+                        format,
+                        output: output_flag(&args[2..]),
+                    }
+                }
+            }
+            "export" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::Export,
+                    }
+                } else {
+                    let mut prometheus: Option<String> = None;
+                    let mut xlsx: Option<String> = None;
 
-USAGE:
-  git-insights <COMMAND> [OPTIONS]
+                    let rest = &args[2..];
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = &rest[i];
+                        if a == "--prometheus" {
+                            if i + 1 < rest.len() {
+                                prometheus = Some(rest[i + 1].clone());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--prometheus=") {
+                            prometheus = Some(eq.to_string());
+                        } else if a == "--xlsx" {
+                            if i + 1 < rest.len() {
+                                xlsx = Some(rest[i + 1].clone());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--xlsx=") {
+                            xlsx = Some(eq.to_string());
+                        }
+                        i += 1;
+                    }
+                    Commands::Export { prometheus, xlsx }
+                }
+            }
+            "log-json" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::LogJson,
+                    }
+                } else {
+                    Commands::LogJson {
+                        output: output_flag(&args[2..]),
+                    }
+                }
+            }
+            "codeowners-check" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::CodeownersCheck,
+                    }
+                } else {
+                    let mut file: Option<String> = None;
+                    let mut min_pct: Option<f64> = None;
+                    let mut stale_days: Option<u64> = None;
 
-COMMANDS:
+                    let rest = &args[2..];
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = &rest[i];
+                        if a == "--file" {
+                            if i + 1 < rest.len() {
+                                file = Some(rest[i + 1].clone());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--file=") {
+                            file = Some(eq.to_string());
+                        } else if a == "--min-pct" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<f64>() {
+                                    min_pct = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--min-pct=") {
+                            if let Ok(v) = eq.parse::<f64>() {
+                                min_pct = Some(v);
+                            }
+                        } else if a == "--stale-days" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<u64>() {
+                                    stale_days = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--stale-days=") {
+                            if let Ok(v) = eq.parse::<u64>() {
+                                stale_days = Some(v);
+                            }
+                        }
+                        i += 1;
+                    }
+                    Commands::CodeownersCheck {
+                        file,
+                        min_pct,
+                        stale_days,
+                    }
+                }
+            }
+            "codeowners-audit" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::CodeownersAudit,
+                    }
+                } else {
+                    let mut file: Option<String> = None;
+                    let mut min_pct: Option<f64> = None;
+
+                    let rest = &args[2..];
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = &rest[i];
+                        if a == "--file" {
+                            if i + 1 < rest.len() {
+                                file = Some(rest[i + 1].clone());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--file=") {
+                            file = Some(eq.to_string());
+                        } else if a == "--min-pct" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<f64>() {
+                                    min_pct = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--min-pct=") {
+                            if let Ok(v) = eq.parse::<f64>() {
+                                min_pct = Some(v);
+                            }
+                        }
+                        i += 1;
+                    }
+                    Commands::CodeownersAudit { file, min_pct }
+                }
+            }
+            "messages" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::Messages,
+                    }
+                } else {
+                    let mut by: Option<String> = None;
+                    let mut top: Option<usize> = None;
+                    let mut under: Option<usize> = None;
+
+                    let rest = &args[2..];
+                    let keywords = has_flag(rest, "--keywords");
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = &rest[i];
+                        if a == "--by" {
+                            if i + 1 < rest.len() {
+                                by = Some(rest[i + 1].to_lowercase());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--by=") {
+                            by = Some(eq.to_lowercase());
+                        } else if a == "--top" || a == "--under" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<usize>() {
+                                    if a == "--top" {
+                                        top = Some(v);
+                                    } else {
+                                        under = Some(v);
+                                    }
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--top=") {
+                            top = eq.parse().ok();
+                        } else if let Some(eq) = a.strip_prefix("--under=") {
+                            under = eq.parse().ok();
+                        }
+                        i += 1;
+                    }
+                    if !keywords && (top.is_some() || under.is_some()) {
+                        return Err("--top and --under need --keywords".to_string());
+                    }
+                    Commands::Messages {
+                        by,
+                        keywords,
+                        top,
+                        under,
+                    }
+                }
+            }
+            "quality" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::Quality,
+                    }
+                } else {
+                    Commands::Quality
+                }
+            }
+            "signing" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::Signing,
+                    }
+                } else {
+                    Commands::Signing
+                }
+            }
+            "churn" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::Churn,
+                    }
+                } else {
+                    let mut weeks: Option<usize> = None;
+                    let mut by_ext = false;
+                    let mut top: Option<usize> = None;
+
+                    let rest = &args[2..];
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = &rest[i];
+                        if a == "--by-ext" {
+                            by_ext = true;
+                        } else if a == "--weeks" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<usize>() {
+                                    weeks = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--weeks=") {
+                            if let Ok(v) = eq.parse::<usize>() {
+                                weeks = Some(v);
+                            }
+                        } else if a == "--top" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<usize>() {
+                                    top = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--top=") {
+                            if let Ok(v) = eq.parse::<usize>() {
+                                top = Some(v);
+                            }
+                        }
+                        i += 1;
+                    }
+                    Commands::Churn { weeks, by_ext, top }
+                }
+            }
+            "daemon" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::Daemon,
+                    }
+                } else {
+                    let mut interval: Option<String> = None;
+                    let mut repos_file: Option<String> = None;
+                    let mut export_dir: Option<String> = None;
+                    let mut analyses: Option<String> = None;
+                    let mut notify: Option<String> = None;
+                    let once = has_flag(&args[2..], "--once");
+
+                    let rest = &args[2..];
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = rest[i].as_str();
+                        let slot = match a.split_once('=').map(|(k, _)| k).unwrap_or(a) {
+                            "--interval" => Some(&mut interval),
+                            "--repos-file" => Some(&mut repos_file),
+                            "--export-dir" => Some(&mut export_dir),
+                            "--analyses" => Some(&mut analyses),
+                            "--notify" => Some(&mut notify),
+                            _ => None,
+                        };
+                        if let Some(slot) = slot {
+                            if let Some((_, v)) = a.split_once('=') {
+                                *slot = Some(v.to_string());
+                            } else if i + 1 < rest.len() {
+                                *slot = Some(rest[i + 1].clone());
+                                i += 1;
+                            }
+                        }
+                        i += 1;
+                    }
+                    Commands::Daemon {
+                        interval,
+                        repos_file,
+                        export_dir,
+                        analyses,
+                        notify,
+                        once,
+                    }
+                }
+            }
+            "owners" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::Owners,
+                    }
+                } else {
+                    let mut file: Option<String> = None;
+                    let mut line: Option<usize> = None;
+                    let mut format: Option<String> = None;
+                    let mut top: Option<usize> = None;
+
+                    let rest = &args[2..];
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = &rest[i];
+                        if a == "--file" {
+                            if i + 1 < rest.len() {
+                                file = Some(rest[i + 1].clone());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--file=") {
+                            file = Some(eq.to_string());
+                        } else if a == "--line" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<usize>() {
+                                    line = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--line=") {
+                            if let Ok(v) = eq.parse::<usize>() {
+                                line = Some(v);
+                            }
+                        } else if a == "--format" {
+                            if i + 1 < rest.len() {
+                                format = Some(rest[i + 1].to_lowercase());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--format=") {
+                            format = Some(eq.to_lowercase());
+                        } else if a == "--top" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<usize>() {
+                                    top = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--top=") {
+                            if let Ok(v) = eq.parse::<usize>() {
+                                top = Some(v);
+                            }
+                        }
+                        i += 1;
+                    }
+                    Commands::Owners {
+                        file,
+                        line,
+                        format,
+                        top,
+                    }
+                }
+            }
+            "file" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::File,
+                    }
+                } else {
+                    let Some(path) = args.get(2).filter(|a| !a.starts_with('-')).cloned() else {
+                        return Err(
+                            "Usage: git-insights file <path> [--format text|json] [--top N]"
+                                .to_string(),
+                        );
+                    };
+                    let mut format: Option<String> = None;
+                    let mut top: Option<usize> = None;
+
+                    let rest = &args[3..];
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = &rest[i];
+                        if a == "--format" {
+                            if i + 1 < rest.len() {
+                                format = Some(rest[i + 1].to_lowercase());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--format=") {
+                            format = Some(eq.to_lowercase());
+                        } else if a == "--top" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<usize>() {
+                                    top = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--top=") {
+                            if let Ok(v) = eq.parse::<usize>() {
+                                top = Some(v);
+                            }
+                        }
+                        i += 1;
+                    }
+                    Commands::File { path, format, top }
+                }
+            }
+            "releases" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::Releases,
+                    }
+                } else {
+                    let mut tags: Option<String> = None;
+                    let mut top: Option<usize> = None;
+
+                    let rest = &args[2..];
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = &rest[i];
+                        if a == "--tags" {
+                            if i + 1 < rest.len() {
+                                tags = Some(rest[i + 1].clone());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--tags=") {
+                            tags = Some(eq.to_string());
+                        } else if a == "--top" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<usize>() {
+                                    top = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--top=") {
+                            if let Ok(v) = eq.parse::<usize>() {
+                                top = Some(v);
+                            }
+                        }
+                        i += 1;
+                    }
+                    Commands::Releases { tags, top }
+                }
+            }
+            "branches" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::Branches,
+                    }
+                } else {
+                    let mut base: Option<String> = None;
+                    let mut stale_days: Option<u64> = None;
+
+                    let rest = &args[2..];
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = &rest[i];
+                        if a == "--base" {
+                            if i + 1 < rest.len() {
+                                base = Some(rest[i + 1].clone());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--base=") {
+                            base = Some(eq.to_string());
+                        } else if a == "--stale-days" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<u64>() {
+                                    stale_days = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--stale-days=") {
+                            if let Ok(v) = eq.parse::<u64>() {
+                                stale_days = Some(v);
+                            }
+                        }
+                        i += 1;
+                    }
+                    Commands::Branches { base, stale_days }
+                }
+            }
+            "languages" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::Languages,
+                    }
+                } else {
+                    let mut top: Option<usize> = None;
+
+                    let rest = &args[2..];
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = &rest[i];
+                        if a == "--top" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<usize>() {
+                                    top = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--top=") {
+                            if let Ok(v) = eq.parse::<usize>() {
+                                top = Some(v);
+                            }
+                        }
+                        i += 1;
+                    }
+                    Commands::Languages { top }
+                }
+            }
+            "classes" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::Classes,
+                    }
+                } else {
+                    let mut classes = Vec::new();
+
+                    let rest = &args[2..];
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = &rest[i];
+                        if a == "--class" {
+                            if i + 1 < rest.len() {
+                                classes.push(parse_class_spec(&rest[i + 1])?);
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--class=") {
+                            classes.push(parse_class_spec(eq)?);
+                        }
+                        i += 1;
+                    }
+                    Commands::Classes { classes }
+                }
+            }
+            "risk" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::Risk,
+                    }
+                } else {
+                    let mut weights: Option<String> = None;
+                    let mut top: Option<usize> = None;
+                    let mut format: Option<String> = None;
+
+                    let rest = &args[2..];
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = &rest[i];
+                        if a == "--weights" {
+                            if i + 1 < rest.len() {
+                                weights = Some(rest[i + 1].to_lowercase());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--weights=") {
+                            weights = Some(eq.to_lowercase());
+                        } else if a == "--top" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<usize>() {
+                                    top = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--top=") {
+                            if let Ok(v) = eq.parse::<usize>() {
+                                top = Some(v);
+                            }
+                        } else if a == "--format" {
+                            if i + 1 < rest.len() {
+                                format = Some(rest[i + 1].to_lowercase());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--format=") {
+                            format = Some(eq.to_lowercase());
+                        }
+                        i += 1;
+                    }
+                    Commands::Risk {
+                        weights,
+                        top,
+                        format,
+                    }
+                }
+            }
+            "time-audit" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::TimeAudit,
+                    }
+                } else {
+                    let mut max_skew_hours: Option<u64> = None;
+
+                    let rest = &args[2..];
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = &rest[i];
+                        if a == "--max-skew" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<u64>() {
+                                    max_skew_hours = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--max-skew=") {
+                            if let Ok(v) = eq.parse::<u64>() {
+                                max_skew_hours = Some(v);
+                            }
+                        }
+                        i += 1;
+                    }
+                    Commands::TimeAudit { max_skew_hours }
+                }
+            }
+            "encoding-audit" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::EncodingAudit,
+                    }
+                } else {
+                    Commands::EncodingAudit
+                }
+            }
+            "contributors" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::Contributors,
+                    }
+                } else {
+                    let mut sort: Option<String> = None;
+
+                    let rest = &args[2..];
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = &rest[i];
+                        if a == "--sort" {
+                            if i + 1 < rest.len() {
+                                sort = Some(rest[i + 1].to_lowercase());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--sort=") {
+                            sort = Some(eq.to_lowercase());
+                        }
+                        i += 1;
+                    }
+                    Commands::Contributors { sort }
+                }
+            }
+            "growth" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::Growth,
+                    }
+                } else {
+                    let color = parse_color(&args[2..])?;
+                    let mut format: Option<String> = None;
+                    let mut loc = false;
+                    let mut samples: Option<usize> = None;
+
+                    let rest = &args[2..];
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = &rest[i];
+                        if a == "--format" {
+                            if i + 1 < rest.len() {
+                                format = Some(rest[i + 1].to_lowercase());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--format=") {
+                            format = Some(eq.to_lowercase());
+                        } else if a == "--loc" {
+                            loc = true;
+                        } else if a == "--samples" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<usize>() {
+                                    samples = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--samples=") {
+                            if let Ok(v) = eq.parse::<usize>() {
+                                samples = Some(v);
+                            }
+                        }
+                        i += 1;
+                    }
+                    Commands::Growth {
+                        color,
+                        format,
+                        loc,
+                        samples,
+                    }
+                }
+            }
+            "ownership-by-tenure" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::OwnershipByTenure,
+                    }
+                } else {
+                    Commands::OwnershipByTenure
+                }
+            }
+            "ownership-history" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::OwnershipHistory,
+                    }
+                } else {
+                    if args.len() < 3 {
+                        return Err("Usage: git-insights ownership-history <username> [--by-email|-e] [--samples N] [--format text|json]".to_string());
+                    }
+                    let username = args[2].clone();
+                    let color = parse_color(&args[3..])?;
+                    let mut by_email = false;
+                    let mut samples: Option<usize> = None;
+                    let mut format: Option<String> = None;
+
+                    let rest = &args[3..];
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = &rest[i];
+                        if a == "--by-email" || a == "-e" {
+                            by_email = true;
+                        } else if a == "--samples" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<usize>() {
+                                    samples = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--samples=") {
+                            if let Ok(v) = eq.parse::<usize>() {
+                                samples = Some(v);
+                            }
+                        } else if a == "--format" {
+                            if i + 1 < rest.len() {
+                                format = Some(rest[i + 1].to_lowercase());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--format=") {
+                            format = Some(eq.to_lowercase());
+                        }
+                        i += 1;
+                    }
+                    Commands::OwnershipHistory {
+                        username,
+                        by_email,
+                        samples,
+                        color,
+                        format,
+                    }
+                }
+            }
+            "velocity" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::Velocity,
+                    }
+                } else {
+                    let mut iteration: Option<String> = None;
+                    let mut start: Option<String> = None;
+                    let color = parse_color(&args[2..])?;
+
+                    let rest = &args[2..];
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = &rest[i];
+                        if a == "--iteration" {
+                            if i + 1 < rest.len() {
+                                iteration = Some(rest[i + 1].clone());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--iteration=") {
+                            iteration = Some(eq.to_string());
+                        } else if a == "--start" {
+                            if i + 1 < rest.len() {
+                                start = Some(rest[i + 1].clone());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--start=") {
+                            start = Some(eq.to_string());
+                        }
+                        i += 1;
+                    }
+                    Commands::Velocity {
+                        iteration,
+                        start,
+                        color,
+                    }
+                }
+            }
+            "ownership-matrix" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::OwnershipMatrix,
+                    }
+                } else {
+                    let mut min_pct: Option<f64> = None;
+                    let mut format: Option<String> = None;
+
+                    let rest = &args[2..];
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = &rest[i];
+                        if a == "--min-pct" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<f64>() {
+                                    min_pct = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--min-pct=") {
+                            if let Ok(v) = eq.parse::<f64>() {
+                                min_pct = Some(v);
+                            }
+                        } else if a == "--format" {
+                            if i + 1 < rest.len() {
+                                format = Some(rest[i + 1].to_lowercase());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--format=") {
+                            format = Some(eq.to_lowercase());
+                        }
+                        i += 1;
+                    }
+                    let by_email = has_flag(rest, "--by-email") || has_flag(rest, "-e");
+                    Commands::OwnershipMatrix {
+                        min_pct,
+                        format,
+                        by_name: !by_email,
+                        output: output_flag(rest),
+                    }
+                }
+            }
+            "compare" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::Compare,
+                    }
+                } else {
+                    let usage =
+                        "Usage: git-insights compare <refA> <refB> [--by-email|-e] [--no-color]";
+                    let (Some(ref_a), Some(ref_b)) = (args.get(2), args.get(3)) else {
+                        return Err(usage.to_string());
+                    };
+                    if ref_a.starts_with('-') || ref_b.starts_with('-') {
+                        return Err(usage.to_string());
+                    }
+                    let rest = &args[4..];
+                    let color = parse_color(&args[2..])?;
+                    let by_email = has_flag(rest, "--by-email") || has_flag(rest, "-e");
+                    Commands::Compare {
+                        ref_a: ref_a.clone(),
+                        ref_b: ref_b.clone(),
+                        by_name: !by_email,
+                        color,
+                    }
+                }
+            }
+            "survival" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::Survival,
+                    }
+                } else {
+                    let mut samples: Option<usize> = None;
+
+                    let rest = &args[2..];
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = &rest[i];
+                        if a == "--samples" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<usize>() {
+                                    samples = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--samples=") {
+                            if let Ok(v) = eq.parse::<usize>() {
+                                samples = Some(v);
+                            }
+                        }
+                        i += 1;
+                    }
+                    Commands::Survival { samples }
+                }
+            }
+            "top" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::Leaderboard,
+                    }
+                } else {
+                    let mut metric: Option<String> = None;
+                    let mut limit: Option<usize> = None;
+                    let color = parse_color(&args[2..])?;
+
+                    let rest = &args[2..];
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = &rest[i];
+                        if a == "--metric" {
+                            if i + 1 < rest.len() {
+                                metric = Some(rest[i + 1].to_lowercase());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--metric=") {
+                            metric = Some(eq.to_lowercase());
+                        } else if a == "--limit" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<usize>() {
+                                    limit = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--limit=") {
+                            if let Ok(v) = eq.parse::<usize>() {
+                                limit = Some(v);
+                            }
+                        }
+                        i += 1;
+                    }
+                    Commands::Top {
+                        metric,
+                        limit,
+                        color,
+                    }
+                }
+            }
+            "man" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::Man,
+                    }
+                } else {
+                    let mut command: Option<String> = None;
+                    let mut dir: Option<String> = None;
+
+                    let rest = &args[2..];
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = &rest[i];
+                        if a == "--dir" {
+                            if i + 1 < rest.len() {
+                                dir = Some(rest[i + 1].clone());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--dir=") {
+                            dir = Some(eq.to_string());
+                        } else if !a.starts_with('-') {
+                            command = Some(a.clone());
+                        }
+                        i += 1;
+                    }
+                    Commands::Man { command, dir }
+                }
+            }
+            "serve" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::Serve,
+                    }
+                } else {
+                    let mut port: Option<String> = None;
+                    let mut host: Option<String> = None;
+
+                    let rest = &args[2..];
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = rest[i].as_str();
+                        let slot = match a.split_once('=').map(|(k, _)| k).unwrap_or(a) {
+                            "--port" => Some(&mut port),
+                            "--host" => Some(&mut host),
+                            _ => None,
+                        };
+                        if let Some(slot) = slot {
+                            if let Some((_, v)) = a.split_once('=') {
+                                *slot = Some(v.to_string());
+                            } else if i + 1 < rest.len() {
+                                *slot = Some(rest[i + 1].clone());
+                                i += 1;
+                            }
+                        }
+                        i += 1;
+                    }
+                    let port = port
+                        .map(|p| {
+                            p.parse::<u16>().map_err(|_| {
+                                format!(
+                                    "invalid value '{}' for '--port': expected a port number (0-65535)",
+                                    p
+                                )
+                            })
+                        })
+                        .transpose()?;
+                    Commands::Serve { port, host }
+                }
+            }
+            "interactive" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::Interactive,
+                    }
+                } else {
+                    Commands::Interactive
+                }
+            }
+            "hotspots" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::Hotspots,
+                    }
+                } else {
+                    let mut top: Option<usize> = None;
+                    let mut size: Option<String> = None;
+
+                    let rest = &args[2..];
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = &rest[i];
+                        if a == "--top" {
+                            if i + 1 < rest.len() {
+                                if let Ok(v) = rest[i + 1].parse::<usize>() {
+                                    top = Some(v);
+                                }
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--top=") {
+                            if let Ok(v) = eq.parse::<usize>() {
+                                top = Some(v);
+                            }
+                        } else if a == "--size" {
+                            if i + 1 < rest.len() {
+                                size = Some(rest[i + 1].to_lowercase());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--size=") {
+                            size = Some(eq.to_lowercase());
+                        }
+                        i += 1;
+                    }
+                    Commands::Hotspots { top, size }
+                }
+            }
+            "badge" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::Badge,
+                    }
+                } else {
+                    let mut metric: Option<String> = None;
+
+                    let rest = &args[2..];
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = &rest[i];
+                        if a == "--metric" {
+                            if i + 1 < rest.len() {
+                                metric = Some(rest[i + 1].to_lowercase());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--metric=") {
+                            metric = Some(eq.to_lowercase());
+                        }
+                        i += 1;
+                    }
+                    Commands::Badge { metric }
+                }
+            }
+            "view" => {
+                if has_flag(&args[2..], "-h") || has_flag(&args[2..], "--help") {
+                    Commands::Help {
+                        topic: HelpTopic::View,
+                    }
+                } else {
+                    let mut path: Option<String> = None;
+                    let mut format: Option<String> = None;
+
+                    let rest = &args[2..];
+                    let mut i = 0;
+                    while i < rest.len() {
+                        let a = &rest[i];
+                        if a == "--format" {
+                            if i + 1 < rest.len() {
+                                format = Some(rest[i + 1].to_lowercase());
+                                i += 1;
+                            }
+                        } else if let Some(eq) = a.strip_prefix("--format=") {
+                            format = Some(eq.to_lowercase());
+                        } else if a == "--output" || a == "-o" {
+                            i += 1;
+                        } else if !a.starts_with('-') && path.is_none() {
+                            path = Some(a.clone());
+                        }
+                        i += 1;
+                    }
+                    match path {
+                        Some(path) => Commands::View {
+                            path,
+                            format,
+                            output: output_flag(&args[2..]),
+                        },
+                        None => {
+                            return Err(
+                                "Usage: git-insights view <file.json> [--format table|html]"
+                                    .to_string(),
+                            )
+                        }
+                    }
+                }
+            }
+            _ => {
+                return Err(format!(
+                    "Unknown command: {}\n{}",
+                    command_str,
+                    render_help(HelpTopic::Top)
+                ));
+            }
+        };
+
+        let filter = parse_filter(&args[2..])?;
+        let text_detection = parse_text_detection(&args[2..])?;
+        let jobs = parse_jobs(&args[2..])?;
+        let mailmap = parse_mailmap(&args[2..])?;
+        Ok(Cli {
+            command,
+            filter,
+            text_detection,
+            jobs,
+            mailmap,
+            verbosity: parse_verbosity(&args[2..]),
+            recurse_submodules: has_flag(&args[2..], "--recurse-submodules"),
+            palette: parse_palette(&args[2..])?,
+        })
+    }
+}
+
+fn has_flag(args: &[String], needle: &str) -> bool {
+    args.iter().any(|a| a == needle)
+}
+
+/// Shared `-o/--output <path>` option of exporting commands (`-` means stdout).
+fn output_flag(args: &[String]) -> Option<String> {
+    let mut output = None;
+    let mut i = 0;
+    while i < args.len() {
+        let a = &args[i];
+        if a == "--output" || a == "-o" {
+            if let Some(v) = args.get(i + 1) {
+                output = Some(v.clone());
+            }
+            i += 1;
+        } else if let Some(eq) = a.strip_prefix("--output=") {
+            output = Some(eq.to_string());
+        }
+        i += 1;
+    }
+    output
+}
+
+/// Parse global commit-selection flags accepted by every command.
+fn parse_filter(args: &[String]) -> Result<CommitFilter, String> {
+    let mut filter = CommitFilter::default();
+    let mut i = 0;
+    while i < args.len() {
+        let a = &args[i];
+        if a == "--reachable-from" {
+            match args.get(i + 1) {
+                Some(v) => filter.reachable_from = Some(v.clone()),
+                None => return Err("--reachable-from requires a tag glob".to_string()),
+            }
+            i += 1;
+        } else if let Some(eq) = a.strip_prefix("--reachable-from=") {
+            filter.reachable_from = Some(eq.to_string());
+        } else if a == "--merge-lines" {
+            match args.get(i + 1) {
+                Some(v) => filter.merge_lines = MergeLines::parse(&v.to_lowercase())?,
+                None => {
+                    return Err("--merge-lines requires merger, branch or synthetic".to_string())
+                }
+            }
+            i += 1;
+        } else if let Some(eq) = a.strip_prefix("--merge-lines=") {
+            filter.merge_lines = MergeLines::parse(&eq.to_lowercase())?;
+        } else if a == "--blame-opts" {
+            match args.get(i + 1) {
+                Some(v) => filter.blame = BlameOptions::parse(v)?,
+                None => return Err("--blame-opts requires none or flags like 'w,M,C'".to_string()),
+            }
+            i += 1;
+        } else if let Some(eq) = a.strip_prefix("--blame-opts=") {
+            filter.blame = BlameOptions::parse(eq)?;
+        } else if a == "--normalize-names" {
+            match args.get(i + 1) {
+                Some(v) => filter.normalize_names = NameNormalization::parse(&v.to_lowercase())?,
+                None => {
+                    return Err("--normalize-names requires none, trim, nfc or casefold".to_string())
+                }
+            }
+            i += 1;
+        } else if let Some(eq) = a.strip_prefix("--normalize-names=") {
+            filter.normalize_names = NameNormalization::parse(&eq.to_lowercase())?;
+        } else if a == "--tz" {
+            match args.get(i + 1) {
+                Some(v) => filter.time_zone = TimeZoneMode::parse(&v.to_lowercase())?,
+                None => return Err("--tz requires author, utc or local".to_string()),
+            }
+            i += 1;
+        } else if let Some(eq) = a.strip_prefix("--tz=") {
+            filter.time_zone = TimeZoneMode::parse(&eq.to_lowercase())?;
+        } else if a == "--week-start" {
+            match args.get(i + 1) {
+                Some(v) => filter.week_start = WeekStart::parse(&v.to_lowercase())?,
+                None => return Err("--week-start requires sun or mon".to_string()),
+            }
+            i += 1;
+        } else if let Some(eq) = a.strip_prefix("--week-start=") {
+            filter.week_start = WeekStart::parse(&eq.to_lowercase())?;
+        } else if a == "--exclude" {
+            match args.get(i + 1) {
+                Some(v) => filter.exclude_paths.push(v.clone()),
+                None => return Err("--exclude requires a path pattern".to_string()),
+            }
+            i += 1;
+        } else if let Some(eq) = a.strip_prefix("--exclude=") {
+            filter.exclude_paths.push(eq.to_string());
+        } else if a == "--ignore-rev" {
+            match args.get(i + 1) {
+                Some(v) => filter.ignore_revs.push(v.clone()),
+                None => return Err("--ignore-rev requires a commit".to_string()),
+            }
+            i += 1;
+        } else if let Some(eq) = a.strip_prefix("--ignore-rev=") {
+            filter.ignore_revs.push(eq.to_string());
+        } else if a == "--no-ignore-revs-file" {
+            filter.skip_ignore_revs_file = true;
+        } else if a == "--exclude-bad-times" {
+            filter.exclude_bad_times = true;
+        } else if a == "--include-generated" {
+            filter.include_generated = true;
+        } else if a == "--include-merges" {
+            filter.include_merges = true;
+        } else if a == "--first-parent" {
+            filter.first_parent = true;
+        } else if a == "--stdin-commits" {
+            // Filled from stdin by `CommitFilter::read_stdin_commits`.
+            filter.only_commits = Some(Vec::new());
+        } else if a == "--exclude-me" || a == "--only-me" {
+            let scope = if a == "--exclude-me" {
+                AuthorScope::ExcludeMe
+            } else {
+                AuthorScope::OnlyMe
+            };
+            if filter.author_scope != AuthorScope::All && filter.author_scope != scope {
+                return Err("--exclude-me and --only-me cannot be combined".to_string());
+            }
+            filter.author_scope = scope;
+        }
+        i += 1;
+    }
+    if filter.only_commits.is_some() && filter.reachable_from.is_some() {
+        return Err("--stdin-commits and --reachable-from cannot be combined".to_string());
+    }
+    Ok(filter)
+}
+
+/// Parse the global `--text-detection auto|grep|sniff` option.
+fn parse_text_detection(args: &[String]) -> Result<TextDetection, String> {
+    let mut mode = TextDetection::default();
+    let mut i = 0;
+    while i < args.len() {
+        let a = &args[i];
+        if a == "--text-detection" {
+            match args.get(i + 1) {
+                Some(v) => mode = TextDetection::parse(&v.to_lowercase())?,
+                None => return Err("--text-detection requires auto, grep or sniff".to_string()),
+            }
+            i += 1;
+        } else if let Some(eq) = a.strip_prefix("--text-detection=") {
+            mode = TextDetection::parse(&eq.to_lowercase())?;
+        }
+        i += 1;
+    }
+    Ok(mode)
+}
+
+/// Parse the global `--palette NAME|#rrggbb,...` option.
+fn parse_palette(args: &[String]) -> Result<Palette, String> {
+    let mut palette = Palette::default();
+    let mut i = 0;
+    while i < args.len() {
+        let a = &args[i];
+        if a == "--palette" {
+            match args.get(i + 1) {
+                Some(v) => palette = Palette::parse(v)?,
+                None => {
+                    return Err(
+                        "--palette requires github, heat, viridis, mono or '#rrggbb,...'"
+                            .to_string(),
+                    )
+                }
+            }
+            i += 1;
+        } else if let Some(eq) = a.strip_prefix("--palette=") {
+            palette = Palette::parse(eq)?;
+        }
+        i += 1;
+    }
+    Ok(palette)
+}
+
+/// Parse `--watch [N]` / `--watch=N`: refresh every N seconds, or every
+/// [`DEFAULT_WATCH_SECS`] when no number follows.
+fn parse_watch(args: &[String]) -> Result<Option<u64>, String> {
+    let mut watch = None;
+    let mut i = 0;
+    while i < args.len() {
+        let a = &args[i];
+        let secs = if a == "--watch" {
+            match args.get(i + 1).map(|v| v.parse::<u64>()) {
+                Some(Ok(n)) => {
+                    i += 1;
+                    n
+                }
+                _ => DEFAULT_WATCH_SECS,
+            }
+        } else if let Some(eq) = a.strip_prefix("--watch=") {
+            eq.parse::<u64>()
+                .map_err(|_| format!("invalid --watch '{}'. Expected seconds.", eq))?
+        } else {
+            i += 1;
+            continue;
+        };
+        if secs == 0 {
+            return Err("--watch needs at least 1 second".to_string());
+        }
+        watch = Some(secs);
+        i += 1;
+    }
+    Ok(watch)
+}
+
+/// Parse the global `--quiet`/`-q` and `--verbose` switches; the last one wins.
+fn parse_verbosity(args: &[String]) -> Verbosity {
+    let mut level = Verbosity::default();
+    for a in args {
+        match a.as_str() {
+            "--quiet" | "-q" => level = Verbosity::Quiet,
+            "--verbose" => level = Verbosity::Verbose,
+            _ => {}
+        }
+    }
+    level
+}
+
+/// Parse `--color[=WHEN]` / `--color WHEN`, `-c` (always) and `--no-color`
+/// (never); the last one wins and the default is auto.
+fn parse_color(args: &[String]) -> Result<ColorChoice, String> {
+    let mut choice = ColorChoice::default();
+    let mut i = 0;
+    while i < args.len() {
+        let a = &args[i];
+        if a == "--color" || a == "-c" {
+            choice = ColorChoice::Always;
+            if a == "--color" {
+                if let Some(Ok(when)) = args.get(i + 1).map(|v| ColorChoice::parse(v)) {
+                    choice = when;
+                    i += 1;
+                }
+            }
+        } else if let Some(eq) = a.strip_prefix("--color=") {
+            choice = ColorChoice::parse(&eq.to_lowercase())?;
+        } else if a == "--no-color" {
+            choice = ColorChoice::Never;
+        }
+        i += 1;
+    }
+    Ok(choice)
+}
+
+/// Parse the global `--jobs N` option (a positive worker count).
+fn parse_jobs(args: &[String]) -> Result<Option<usize>, String> {
+    let mut jobs = None;
+    let mut i = 0;
+    while i < args.len() {
+        let a = &args[i];
+        let value = if a == "--jobs" || a == "-j" {
+            i += 1;
+            Some(args.get(i).map(|s| s.as_str()).unwrap_or(""))
+        } else {
+            a.strip_prefix("--jobs=")
+        };
+        if let Some(v) = value {
+            match v.parse::<usize>() {
+                Ok(n) if n > 0 => jobs = Some(n),
+                _ => {
+                    return Err(format!(
+                        "invalid --jobs '{}'. Expected a positive number.",
+                        v
+                    ))
+                }
+            }
+        }
+        i += 1;
+    }
+    Ok(jobs)
+}
+
+/// Parse the global `--mailmap FILE` option; relative paths are resolved
+/// against the working directory.
+fn parse_mailmap(args: &[String]) -> Result<Option<PathBuf>, String> {
+    let mut file = None;
+    let mut i = 0;
+    while i < args.len() {
+        let a = &args[i];
+        if a == "--mailmap" {
+            match args.get(i + 1) {
+                Some(v) => file = Some(v.clone()),
+                None => return Err("--mailmap requires a file".to_string()),
+            }
+            i += 1;
+        } else if let Some(eq) = a.strip_prefix("--mailmap=") {
+            file = Some(eq.to_string());
+        }
+        i += 1;
+    }
+    match file {
+        Some(f) => std::path::absolute(&f)
+            .map(Some)
+            .map_err(|e| format!("invalid --mailmap '{}': {}", f, e)),
+        None => Ok(None),
+    }
+}
+
+/// What an option expects after it, for [`validate_args`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Arg {
+    /// A switch such as `--no-color`; `--flag=value` is rejected.
+    Flag,
+    /// Any text, e.g. a path or ref; checked by the command.
+    Text,
+    /// A non-negative whole number.
+    Count,
+    /// A switch that may take a whole number, inline or as the next
+    /// argument (`--watch [N]`).
+    OptionalCount,
+    /// A switch that may take one of these values, inline (`--color=never`)
+    /// or as the next argument.
+    Switch(&'static [&'static str]),
+    /// A decimal number such as `1.5`.
+    Number,
+    /// One of these keywords, in any case (`--sort loc`).
+    Choice(&'static [&'static str]),
+}
+
+type Options = &'static [(&'static str, Arg)];
+
+/// Accepted by every command.
+const GLOBAL_OPTIONS: Options = &[
+    ("-h", Arg::Flag),
+    ("--help", Arg::Flag),
+    ("--reachable-from", Arg::Text),
+    ("--merge-lines", Arg::Text),
+    ("--blame-opts", Arg::Text),
+    ("--ignore-rev", Arg::Text),
+    ("--no-ignore-revs-file", Arg::Flag),
+    ("--normalize-names", Arg::Text),
+    ("--tz", Arg::Text),
+    ("--week-start", Arg::Text),
+    ("--palette", Arg::Text),
+    ("--exclude-bad-times", Arg::Flag),
+    ("--exclude", Arg::Text),
+    ("--include-generated", Arg::Flag),
+    ("--include-merges", Arg::Flag),
+    ("--first-parent", Arg::Flag),
+    ("--stdin-commits", Arg::Flag),
+    ("--exclude-me", Arg::Flag),
+    ("--only-me", Arg::Flag),
+    ("--text-detection", Arg::Text),
+    ("-j", Arg::Count),
+    ("--jobs", Arg::Count),
+    ("--mailmap", Arg::Text),
+    ("-q", Arg::Flag),
+    ("--quiet", Arg::Flag),
+    ("--verbose", Arg::Flag),
+];
+
+const COLOR_WHEN: &[&str] = &["auto", "always", "never"];
+
+const TEXT_OR_JSON: Arg = Arg::Choice(&["text", "json"]);
+
+const HEATMAP_SCALES: Arg = Arg::Choice(&["global", "row", "log"]);
+
+const COLOR_OPTIONS: Options = &[
+    ("--color", Arg::Switch(COLOR_WHEN)),
+    ("-c", Arg::Flag),
+    ("--no-color", Arg::Flag),
+];
+
+const OUTPUT_OPTIONS: Options = &[("--output", Arg::Text), ("-o", Arg::Text)];
+
+const WATCH_OPTIONS: Options = &[("--watch", Arg::OptionalCount)];
+
+const SUBMODULE_OPTIONS: Options = &[("--recurse-submodules", Arg::Flag)];
+
+const BY_EMAIL_OPTIONS: Options = &[("--by-email", Arg::Flag), ("-e", Arg::Flag)];
+
+/// Options a command accepts besides [`GLOBAL_OPTIONS`].
+struct CommandSpec {
+    topic: HelpTopic,
+    options: &'static [Options],
+    /// Arguments not starting with `-` (e.g. `compare <refA> <refB>`).
+    positionals: usize,
+    /// `-12` / `--12` as a shorthand for `--weeks 12`.
+    week_shorthand: bool,
+}
+
+fn command_spec(command: &str) -> Option<CommandSpec> {
+    let spec = |topic, options, positionals, week_shorthand| CommandSpec {
+        topic,
+        options,
+        positionals,
+        week_shorthand,
+    };
+    Some(match command {
+        "stats" => spec(
+            HelpTopic::Stats,
+            &[
+                BY_EMAIL_OPTIONS,
+                OUTPUT_OPTIONS,
+                WATCH_OPTIONS,
+                SUBMODULE_OPTIONS,
+                &[
+                    ("--by-domain", Arg::Flag),
+                    ("--incremental", Arg::Flag),
+                    ("--fast", Arg::Flag),
+                    ("--max-rows", Arg::Count),
+                    ("--compare-with", Arg::Text),
+                    ("--sort", Arg::Choice(&["loc", "commits", "files", "name"])),
+                    ("--asc", Arg::Flag),
+                    ("--desc", Arg::Flag),
+                    ("--top", Arg::Count),
+                ],
+            ],
+            0,
+            false,
+        ),
+        "json" => spec(HelpTopic::Json, &[OUTPUT_OPTIONS], 0, false),
+        "user" => spec(
+            HelpTopic::User,
+            &[
+                BY_EMAIL_OPTIONS,
+                &[
+                    ("--ownership", Arg::Flag),
+                    ("--top", Arg::Count),
+                    ("--sort", Arg::Choice(&["loc", "pct"])),
+                    ("--github", Arg::Flag),
+                    ("--github-login", Arg::Text),
+                    ("--pr-pattern", Arg::Text),
+                    ("--min-pct", Arg::Number),
+                    ("--min-loc", Arg::Count),
+                ],
+            ],
+            1,
+            false,
+        ),
+        "timeline" => spec(
+            HelpTopic::Timeline,
+            &[
+                COLOR_OPTIONS,
+                OUTPUT_OPTIONS,
+                WATCH_OPTIONS,
+                SUBMODULE_OPTIONS,
+                &[
+                    ("--weeks", Arg::Count),
+                    ("--metric", Arg::Choice(&["commits", "contributors"])),
+                    ("--granularity", Arg::Choice(&["day", "week", "month"])),
+                    ("--style", Arg::Choice(&["bars", "braille"])),
+                    ("--bursts", Arg::Number),
+                    ("--burst-commits", Arg::Count),
+                    ("--capacity", Arg::Text),
+                    ("--height", Arg::Count),
+                ],
+            ],
+            0,
+            true,
+        ),
+        "heatmap" => spec(
+            HelpTopic::Heatmap,
+            &[
+                COLOR_OPTIONS,
+                OUTPUT_OPTIONS,
+                WATCH_OPTIONS,
+                SUBMODULE_OPTIONS,
+                &[("--weeks", Arg::Count), ("--normalize", HEATMAP_SCALES)],
+            ],
+            0,
+            true,
+        ),
+        "code-frequency" => spec(
+            HelpTopic::CodeFrequency,
+            &[
+                COLOR_OPTIONS,
+                OUTPUT_OPTIONS,
+                &[
+                    ("--weeks", Arg::Count),
+                    (
+                        "--group",
+                        Arg::Choice(&["hod", "dow", "dom", "moy", "year"]),
+                    ),
+                    (
+                        "--heatmap",
+                        Arg::Choice(&["dow-hod", "dom-hod", "type-hod", "add-del"]),
+                    ),
+                    ("--normalize", HEATMAP_SCALES),
+                    ("--weight", Arg::Choice(&["commits", "lines"])),
+                    ("--width", Arg::Count),
+                    ("--table", Arg::Flag),
+                    ("--per-author", Arg::Flag),
+                    ("--summary", Arg::Flag),
+                    ("--top", Arg::Count),
+                ],
+            ],
+            0,
+            true,
+        ),
+        "report" => spec(
+            HelpTopic::Report,
+            &[OUTPUT_OPTIONS, &[("--format", Arg::Choice(&["html"]))]],
+            0,
+            false,
+        ),
+        "export" => spec(
+            HelpTopic::Export,
+            &[&[("--prometheus", Arg::Text), ("--xlsx", Arg::Text)]],
+            0,
+            false,
+        ),
+        "log-json" => spec(HelpTopic::LogJson, &[OUTPUT_OPTIONS], 0, false),
+        "codeowners-check" => spec(
+            HelpTopic::CodeownersCheck,
+            &[&[
+                ("--file", Arg::Text),
+                ("--min-pct", Arg::Number),
+                ("--stale-days", Arg::Count),
+            ]],
+            0,
+            false,
+        ),
+        "codeowners-audit" => spec(
+            HelpTopic::CodeownersAudit,
+            &[&[("--file", Arg::Text), ("--min-pct", Arg::Number)]],
+            0,
+            false,
+        ),
+        "messages" => spec(
+            HelpTopic::Messages,
+            &[&[
+                ("--by", Arg::Choice(&["author", "month"])),
+                ("--keywords", Arg::Flag),
+                ("--top", Arg::Count),
+                ("--under", Arg::Count),
+            ]],
+            0,
+            false,
+        ),
+        "quality" => spec(HelpTopic::Quality, &[], 0, false),
+        "signing" => spec(HelpTopic::Signing, &[], 0, false),
+        "churn" => spec(
+            HelpTopic::Churn,
+            &[&[
+                ("--weeks", Arg::Count),
+                ("--by-ext", Arg::Flag),
+                ("--top", Arg::Count),
+            ]],
+            0,
+            false,
+        ),
+        "daemon" => spec(
+            HelpTopic::Daemon,
+            &[&[
+                ("--interval", Arg::Text),
+                ("--repos-file", Arg::Text),
+                ("--export-dir", Arg::Text),
+                ("--analyses", Arg::Text),
+                ("--notify", Arg::Text),
+                ("--once", Arg::Flag),
+            ]],
+            0,
+            false,
+        ),
+        "owners" => spec(
+            HelpTopic::Owners,
+            &[&[
+                ("--file", Arg::Text),
+                ("--line", Arg::Count),
+                ("--format", TEXT_OR_JSON),
+                ("--top", Arg::Count),
+            ]],
+            0,
+            false,
+        ),
+        "file" => spec(
+            HelpTopic::File,
+            &[&[("--format", TEXT_OR_JSON), ("--top", Arg::Count)]],
+            1,
+            false,
+        ),
+        "releases" => spec(
+            HelpTopic::Releases,
+            &[&[("--tags", Arg::Text), ("--top", Arg::Count)]],
+            0,
+            false,
+        ),
+        "branches" => spec(
+            HelpTopic::Branches,
+            &[&[("--base", Arg::Text), ("--stale-days", Arg::Count)]],
+            0,
+            false,
+        ),
+        "languages" => spec(HelpTopic::Languages, &[&[("--top", Arg::Count)]], 0, false),
+        "classes" => spec(HelpTopic::Classes, &[&[("--class", Arg::Text)]], 0, false),
+        "risk" => spec(
+            HelpTopic::Risk,
+            &[&[
+                ("--weights", Arg::Text),
+                ("--top", Arg::Count),
+                ("--format", TEXT_OR_JSON),
+            ]],
+            0,
+            false,
+        ),
+        "time-audit" => spec(
+            HelpTopic::TimeAudit,
+            &[&[("--max-skew", Arg::Count)]],
+            0,
+            false,
+        ),
+        "encoding-audit" => spec(HelpTopic::EncodingAudit, &[], 0, false),
+        "contributors" => spec(
+            HelpTopic::Contributors,
+            &[&[("--sort", Arg::Choice(&["first", "last", "commits"]))]],
+            0,
+            false,
+        ),
+        "growth" => spec(
+            HelpTopic::Growth,
+            &[
+                COLOR_OPTIONS,
+                &[
+                    ("--format", TEXT_OR_JSON),
+                    ("--loc", Arg::Flag),
+                    ("--samples", Arg::Count),
+                ],
+            ],
+            0,
+            false,
+        ),
+        "ownership-by-tenure" => spec(HelpTopic::OwnershipByTenure, &[], 0, false),
+        "ownership-history" => spec(
+            HelpTopic::OwnershipHistory,
+            &[
+                COLOR_OPTIONS,
+                BY_EMAIL_OPTIONS,
+                &[("--samples", Arg::Count), ("--format", TEXT_OR_JSON)],
+            ],
+            1,
+            false,
+        ),
+        "velocity" => spec(
+            HelpTopic::Velocity,
+            &[
+                COLOR_OPTIONS,
+                &[("--iteration", Arg::Text), ("--start", Arg::Text)],
+            ],
+            0,
+            false,
+        ),
+        "ownership-matrix" => spec(
+            HelpTopic::OwnershipMatrix,
+            &[
+                BY_EMAIL_OPTIONS,
+                OUTPUT_OPTIONS,
+                &[
+                    ("--min-pct", Arg::Number),
+                    ("--format", Arg::Choice(&["markdown", "md", "json"])),
+                ],
+            ],
+            0,
+            false,
+        ),
+        "compare" => spec(
+            HelpTopic::Compare,
+            &[BY_EMAIL_OPTIONS, COLOR_OPTIONS],
+            2,
+            false,
+        ),
+        "survival" => spec(
+            HelpTopic::Survival,
+            &[&[("--samples", Arg::Count)]],
+            0,
+            false,
+        ),
+        "top" => spec(
+            HelpTopic::Leaderboard,
+            &[
+                COLOR_OPTIONS,
+                &[
+                    (
+                        "--metric",
+                        Arg::Choice(&["loc", "commits", "files", "churn"]),
+                    ),
+                    ("--limit", Arg::Count),
+                ],
+            ],
+            0,
+            false,
+        ),
+        "man" => spec(HelpTopic::Man, &[&[("--dir", Arg::Text)]], 1, false),
+        "serve" => spec(
+            HelpTopic::Serve,
+            &[&[("--port", Arg::Count), ("--host", Arg::Text)]],
+            0,
+            false,
+        ),
+        "interactive" => spec(HelpTopic::Interactive, &[], 0, false),
+        "hotspots" => spec(
+            HelpTopic::Hotspots,
+            &[&[
+                ("--top", Arg::Count),
+                ("--size", Arg::Choice(&["loc", "indent"])),
+            ]],
+            0,
+            false,
+        ),
+        "badge" => spec(
+            HelpTopic::Badge,
+            &[&[(
+                "--metric",
+                Arg::Choice(&["contributors", "bus-factor", "loc"]),
+            )]],
+            0,
+            false,
+        ),
+        "view" => spec(
+            HelpTopic::View,
+            &[
+                OUTPUT_OPTIONS,
+                &[("--format", Arg::Choice(&["table", "html"]))],
+            ],
+            1,
+            false,
+        ),
+        _ => return None,
+    })
+}
+
+/// Help topic of `command`, `None` for unknown commands.
+pub fn help_topic(command: &str) -> Option<HelpTopic> {
+    command_spec(command).map(|spec| spec.topic)
+}
+
+/// `problem`, the command's USAGE section and a pointer to its full help.
+fn usage_error(command: &str, topic: HelpTopic, problem: &str) -> String {
+    let help = render_help(topic);
+    let usage: Vec<&str> = help
+        .lines()
+        .skip_while(|l| *l != "USAGE:")
+        .take_while(|l| !l.is_empty())
+        .collect();
+    format!(
+        "{}\n\n{}\n\nRun 'git-insights {} --help' for all options.",
+        problem,
+        usage.join("\n"),
+        command
+    )
+}
+
+/// Whether `command` takes `option` (e.g. `--weeks`), global options included.
+pub(crate) fn accepts_option(command: &str, option: &str) -> bool {
+    command_spec(command).is_some_and(|spec| {
+        spec.options
+            .iter()
+            .copied()
+            .chain([GLOBAL_OPTIONS])
+            .flatten()
+            .any(|(n, _)| *n == option)
+    })
+}
+
+/// Reject options `command` does not know, missing or malformed values and
+/// extra positional arguments, instead of silently ignoring them.
+fn validate_args(command: &str, args: &[String]) -> Result<(), String> {
+    let Some(spec) = command_spec(command) else {
+        return Ok(());
+    };
+    let fail = |problem: String| Err(usage_error(command, spec.topic.clone(), &problem));
+    let lookup = |name: &str| {
+        spec.options
+            .iter()
+            .copied()
+            .chain([GLOBAL_OPTIONS])
+            .flatten()
+            .find(|(n, _)| *n == name)
+            .map(|(_, kind)| *kind)
+    };
+    let mut positionals = 0;
+    let mut i = 0;
+    while i < args.len() {
+        let a = args[i].as_str();
+        i += 1;
+        let digits = a.strip_prefix("--").or_else(|| a.strip_prefix('-'));
+        if spec.week_shorthand
+            && digits.is_some_and(|d| !d.is_empty() && d.chars().all(|c| c.is_ascii_digit()))
+        {
+            continue;
+        }
+        if !a.starts_with('-') || a == "-" {
+            positionals += 1;
+            if positionals > spec.positionals {
+                return fail(format!("unexpected argument '{}' for '{}'", a, command));
+            }
+            continue;
+        }
+        let (name, inline) = match a.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name, Some(value)),
+            _ => (a, None),
+        };
+        let Some(kind) = lookup(name) else {
+            return fail(format!("unknown option '{}' for '{}'", name, command));
+        };
+        if kind == Arg::OptionalCount {
+            match inline {
+                Some(v) if v.parse::<u64>().is_err() => {
+                    return fail(format!(
+                        "invalid value '{}' for '{}': expected a whole number",
+                        v, name
+                    ));
+                }
+                Some(_) => {}
+                None => {
+                    if args.get(i).is_some_and(|v| v.parse::<u64>().is_ok()) {
+                        i += 1;
+                    }
+                }
+            }
+            continue;
+        }
+        if let Arg::Switch(values) = kind {
+            match inline {
+                Some(v) if !values.contains(&v.to_lowercase().as_str()) => {
+                    return fail(format!(
+                        "invalid value '{}' for '{}': expected {}",
+                        v,
+                        name,
+                        values.join(", ")
+                    ));
+                }
+                Some(_) => {}
+                None => {
+                    if args.get(i).is_some_and(|v| values.contains(&v.as_str())) {
+                        i += 1;
+                    }
+                }
+            }
+            continue;
+        }
+        if kind == Arg::Flag {
+            if inline.is_some() {
+                return fail(format!("option '{}' does not take a value", name));
+            }
+            continue;
+        }
+        let value = match inline {
+            Some(v) => v,
+            None => match args.get(i) {
+                Some(v) => {
+                    i += 1;
+                    v.as_str()
+                }
+                // Global options name their expected values when parsed.
+                None if GLOBAL_OPTIONS.iter().any(|(n, _)| *n == name) => break,
+                None => return fail(format!("option '{}' requires a value", name)),
+            },
+        };
+        let valid = match kind {
+            Arg::Count => value.parse::<u64>().is_ok(),
+            Arg::Number => value.parse::<f64>().is_ok_and(f64::is_finite),
+            Arg::Choice(values) => values.contains(&value.to_lowercase().as_str()),
+            _ => true,
+        };
+        if !valid {
+            let expected = match kind {
+                Arg::Count => "a whole number".to_string(),
+                Arg::Choice(values) => values.join(", "),
+                _ => "a number".to_string(),
+            };
+            return fail(format!(
+                "invalid value '{}' for '{}': expected {}",
+                value, name, expected
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub fn render_help(topic: HelpTopic) -> String {
+    match topic {
+        HelpTopic::Top => {
+            let ver = version_string();
+            format!(
+                "\
+git-insights v{ver}
+
+A CLI tool to generate Git repo stats and insights (no dependencies).
+
+USAGE:
+  git-insights <COMMAND> [OPTIONS]
+
+COMMANDS:
   stats           Show repository stats (surviving LOC, commits, files)
   json            Export stats to git-insights.json
   timeline        Show weekly commit activity as ASCII/Unicode sparkline
   heatmap         Show UTC commit heatmap (weekday x hour)
-  code-frequency  Code-frequency histograms/heatmaps (group by hour/day-of-week/day-of-month)
+  code-frequency  Code-frequency histograms/heatmaps (hour, weekday, day, month or year)
   user <name>     Show insights for a specific user
+  report          Export a self-contained HTML report
+  export          Export metrics for other tools (Prometheus textfile, Excel)
+  view <file>     Render a file written by 'json' (no git needed)
+  badge           Print shields.io endpoint JSON for a metric
+  log-json        Export the commit stream (with parents and refs) as JSON Lines
+  hotspots        Rank files by change frequency x current size
+  messages        Conventional-commit type mix per author or month
+  quality         Revert and fixup!/squash! rates per month and per author
+  signing         Share of GPG/SSH-signed commits, overall and per author
+  churn           Lines added/deleted per file or extension over recent weeks
+  daemon          Periodically refresh repos and write timestamped exports
+  owners          Who owns a file or line (for editor integrations)
+  file            Per-author LOC, share and last touch for a file or directory
+  releases        Commits, authors and lines changed between consecutive tags
+  branches        Local/remote branches: last commit, ahead/behind, authors, staleness
+  languages       Surviving LOC and files per language, overall and per author
+  classes         Surviving LOC and commits per author in path classes (tests, docs, ...)
+  risk            Rank files by churn, single ownership, code age and size
+  time-audit      List commits with implausible timestamps (clock skew, future, epoch 0)
+  encoding-audit  Files with mixed line endings or non-UTF-8 lines, and who wrote them
+  contributors    First/last commit, active months and commits per author
+  growth          New vs returning authors per month
+  ownership-by-tenure
+                  Surviving lines by how long their author had been contributing
+  ownership-history <user>
+                  A user's surviving lines at sampled commits, as a timeline
+  velocity        Commits and lines changed per fixed-length iteration, with trend
+  ownership-matrix
+                  Files and directories each author significantly owns, as a document
+  compare <refA> <refB>
+                  Per-author LOC, commit and file deltas between two refs
+  survival        Median line lifetime per directory and author, from sampled blames
+  top             Ranked leaderboard of authors by LOC, commits, files or churn
+  man [command]   Print roff manual pages (or write them all with --dir)
+  serve           Serve a JSON API and HTML dashboard over HTTP
+  interactive     Guided prompts that build and run a command for you
+  codeowners-check
+                  Flag CODEOWNERS entries whose owners no longer own the code
+  codeowners-audit
+                  Each declared CODEOWNERS owner's share of the code vs blame
   help            Show this help
   version         Show version information
 
-GLOBAL OPTIONS:
-  -h, --help      Show help
-  -v, --version   Show version
+GLOBAL OPTIONS:
+  -h, --help      Show help
+  -v, --version   Show version
+  --reachable-from GLOB
+                  Only count commits reachable from tags matching GLOB (e.g. 'v*'),
+                  i.e. what actually shipped
+  --exclude-me    Ignore commits and lines by the current git identity
+                  (user.name / user.email)
+  --only-me       Only count commits and lines by the current git identity
+  --merge-lines merger|branch|synthetic
+                  Credit lines written while resolving merge conflicts to the
+                  merger (default), the latest author of the file on the merged
+                  branch, or a synthetic '(merge)' identity
+  --blame-opts none|w,M,C,...
+                  Blame flags of every line-ownership analysis: w ignores
+                  whitespace-only edits, M follows lines moved within a file,
+                  and up to three C follow lines copied from other files (in
+                  the same commit, also the file's creating commit, or any
+                  commit). More flags keep lines with their original author
+                  but blame slower; C,C,C can be very slow. Default: M,C
+  --ignore-rev SHA
+                  Blame past this commit (e.g. a mass reformat), crediting its
+                  lines to their previous authors; repeatable. The repo's
+                  .git-blame-ignore-revs is honored automatically
+  --no-ignore-revs-file
+                  Do not pass .git-blame-ignore-revs to blame
+  --include-merges
+                  Count merge commits in commit counts, timestamps and
+                  timelines; by default every analysis leaves them out
+  --first-parent  Follow only the first parent of merges, i.e. the history of
+                  the main branch as it was merged into; with --include-merges
+                  each merged branch counts as its merge commit
+  --normalize-names none|trim|nfc|casefold
+                  Merge author rows that differ only by whitespace (trim),
+                  also by composed/decomposed accents (nfc), or also by case
+                  (casefold; shows lowercase names). Default: none
+  --exclude-bad-times
+                  Leave commits flagged by 'time-audit' (default tolerance) out
+                  of time-based analyses such as timeline, heatmap and
+                  code-frequency
+  --tz author|utc|local
+                  Clock for hour-of-day and day-of-week buckets (heatmap,
+                  code-frequency, report): each author's own UTC offset from
+                  the author date (author), this machine's time zone (local),
+                  or UTC (default)
+  --week-start sun|mon
+                  First row of day-of-week charts (calendar and 7x24
+                  heatmaps, code-frequency day-of-week, report). Default: sun
+  --palette NAME  Colors of shaded heatmaps, timelines and bars: github, heat,
+                  viridis, mono, a [palettes] name from the config, or stops
+                  like '#0e4429,#39d353'. Default: 12 ANSI colors, or heat when
+                  COLORTERM=truecolor. Without truecolor the nearest of 256
+                  colors is used
+  --stdin-commits Read commit ids (one per line; the first word of each line,
+                  so 'git log --oneline' output works) from stdin and limit
+                  every history-based analysis to exactly that set. Blame-based
+                  line ownership still describes HEAD
+  --exclude PATTERN
+                  Leave files matching a gitignore-style pattern (e.g. 'vendor/',
+                  '*.lock') out of blame-based line ownership; repeatable
+  --include-generated
+                  Blame files that .gitattributes marks linguist-generated or
+                  -diff (e.g. via 'binary'); by default they are left out of
+                  line ownership. Files with binary content never count
+  --mailmap FILE  Map author names and emails through FILE (mailmap format) in
+                  addition to the repo's own .mailmap
+  --profile NAME  Apply flags from [profile.NAME] (and [profile.NAME.<command>])
+                  in the repo's .git-insights.toml; explicit flags still win
+  --text-detection auto|grep|sniff
+                  How files are classified as text before blaming: 'git grep -I'
+                  (grep), a NUL-byte sniff of blob contents (sniff), or grep with
+                  a sniff fallback when grep fails (auto, default)
+  -j, --jobs N    Blame at most N files at once (default: one per CPU)
+  -q, --quiet     No progress bars or notes on stderr, only warnings and errors
+  --verbose       Also log every git command with its duration, phase timings
+                  and snapshot reuse to stderr
+
+CONFIGURATION:
+  ~/.config/git-insights/config.toml
+                  Defaults for every repository (under $XDG_CONFIG_HOME if set)
+  .git-insights.toml
+                  Defaults for this repository, read from the work-tree root;
+                  they override the user file and explicit flags override both
+  [defaults]      Option values for every command that takes them, e.g.
+                  weeks = 12, color = false, exclude = [\"vendor/\"]
+  [defaults.CMD]  Option values for one command, e.g. sort = \"pct\" under
+                  [defaults.user]
+  [aliases]       Merge identities: \"Ann <ann@corp.io>\" = [\"ann <a@home.net>\"]
+                  maps each listed author (or bare <email>) to the key;
+                  combined with --mailmap FILE, whose entries win
+  [palettes]      Named color ramps for --palette, e.g.
+                  ocean = [\"#03045e\", \"#0077b6\", \"#90e0ef\"]
+
+EXIT STATUS:
+  0               Success
+  1               The analysis failed, or a check (codeowners-check,
+                  codeowners-audit) found problems
+  2               Not inside a git repository
+  3               git is not installed or not on PATH
+  4               Unknown command or option, or an invalid value
+  5               A git command failed
+
+EXAMPLES:
+  git-insights stats
+  git-insights stats --by-email
+  git-insights stats --by-domain
+  git-insights json
+  git-insights user alice
+  git-insights timeline --reachable-from 'v*'
+  git-insights report --profile weekly-report
+  git-insights stats --exclude-me
+  git rev-list --grep=hotfix HEAD | git-insights timeline --stdin-commits
+
+See 'git-insights <COMMAND> --help' for command-specific options."
+            )
+        }
+        HelpTopic::Stats => {
+            "\
+git-insights stats
+
+Compute repository stats using a gitfame-like method:
+- Surviving LOC via git blame --line-porcelain HEAD
+- Commits via git shortlog -s -e HEAD
+- Only text files considered (git grep -I --name-only . HEAD AND ls-files); if
+  git grep fails, blobs are sniffed for NUL bytes instead (see --text-detection)
+- Clean git commands (no pager), no dependencies
+- Partial (blobless) clones: missing blobs are pre-fetched in batches before blaming
+
+USAGE:
+  git-insights stats [OPTIONS]
+
+OPTIONS:
+  -e, --by-email  Group by \"Name <email>\" (default groups by name only)
+  --by-domain     One row per email domain (e.g. @company.com, @gmail.com)
+                  for an organization-level view; emails without a domain are
+                  grouped under \"(no domain)\"
+  --incremental   Store per-file blame results with the HEAD they were computed
+                  at (.git/git-insights/stats-snapshot.json) and, on later runs,
+                  only re-blame files changed since then. Changing grouping or
+                  global filters, or rewriting history, triggers a full rebuild
+  --max-rows N    Print at most N author rows (default: 500, 0 = all); rows
+                  are streamed as they are formatted and the rest summarized
+  -o, --output F  Write the full table to F instead of stdout
+  --sort loc|commits|files|name
+                  Order rows by surviving lines (default), commits, files or
+                  author name
+  --asc, --desc   Sort direction; largest first by default, A to Z for name
+  --top N         Show only the first N authors after sorting (0 = all); the
+                  totals still cover everyone
+  --compare-with SINCE..UNTIL
+                  Instead of surviving LOC, compare activity per author in the
+                  window SINCE..UNTIL (YYYY-MM-DD, both days included, UTC
+                  committer dates) with the equally long window right after
+                  it: commits, lines changed and files touched side by side
+                  with the percentage change
+  --fast          Approximate LOC from git log --numstat (lines added minus
+                  deleted per author, in files tracked at HEAD) instead of
+                  blaming every file: 10-100x faster on large repositories,
+                  but moved, rewritten and reverted lines are not followed.
+                  Files are those where the author's net change is positive
+  --watch [N]     Clear the screen and recompute every N seconds (default: 10)
+                  or as soon as new commits land; pairs well with --incremental
+  --recurse-submodules
+                  Include blame and commits of initialized submodules (files
+                  shown under their submodule path) and add a table with each
+                  repository's subtotals; not with --incremental, --fast or
+                  --compare-with
+  -h, --help      Show this help
+
+EXAMPLES:
+  git-insights stats
+  git-insights stats --by-email
+  git-insights stats --incremental
+  git-insights stats --max-rows 50
+  git-insights stats --sort commits --top 20
+  git-insights stats --sort name
+  git-insights stats --output authors.md
+  git-insights stats --compare-with 2024-01-01..2024-03-31
+  git-insights stats --fast
+  git-insights stats --incremental --watch 30
+  git-insights stats --recurse-submodules"
+                .to_string()
+        }
+        HelpTopic::Json => {
+            "\
+git-insights json
+
+Export stats to a JSON file (git-insights.json):
+  { schema_version, repo { name, remote }, head, generated_at,
+    generated_at_utc, totals { authors, loc, commits, files, added,
+    deleted }, authors { author -> { loc, commits, added, deleted,
+    files[] } } }
+The remote URL is written without credentials. schema_version changes only
+when a field is removed or changes meaning.
+
+USAGE:
+  git-insights json [-o|--output PATH]
+
+OPTIONS:
+  -o, --output PATH   Write to PATH instead of git-insights.json ('-' for stdout)
+  -h, --help          Show this help
+
+EXAMPLES:
+  git-insights json
+  git-insights json --output build/stats.json
+  git-insights json -o - | jq ."
+                .to_string()
+        }
+        HelpTopic::User => {
+            "\
+git-insights user
+
+Show insights for a specific user.
+
+Default behavior:
+- Merged pull request count (via commit message heuristics), broken down by
+  source: GitHub, GitLab (\"See merge request !N\"), Bitbucket and branch merge
+  commits, Gerrit Change-Id trailers, squash-merge subjects ending in \"(#N)\"
+  and --pr-pattern matches
+- Tags where the user authored commits
+
+GitHub mode (--github, opt-in):
+- Merged pull requests, reviews, issues opened and issues commented on, from
+  the GitHub search API, replacing the commit message heuristic
+- Needs curl and a token in GITHUB_TOKEN or GH_TOKEN; the repository is
+  GITHUB_REPOSITORY or the origin remote (GITHUB_API_URL for GitHub Enterprise)
+
+Ownership mode (per-file \"ownership\" list):
+- Computes surviving LOC per file attributed to this user at HEAD via blame
+- Shows file path, user LOC, file LOC, ownership percentage and the file's
+  share of the user's total LOC, then the user's total LOC, file count and
+  top directory
+
+USAGE:
+  git-insights user <username> [--ownership] [--by-email|-e] [--top N] [--sort loc|pct]
+                    [--min-pct X] [--min-loc N]
+  git-insights user <username> --github [--github-login LOGIN]
+
+OPTIONS:
+  --ownership       Show per-file ownership table for this user
+  -e, --by-email    Match by email (author-mail) instead of author name
+  --top N           Limit to top N rows (default: 10)
+  --sort loc|pct    Sort by user LOC (loc, default) or percentage (pct)
+  --min-pct X       With --ownership, hide files where the user holds less than
+                    X% of the lines
+  --min-loc N       With --ownership, hide files where the user has fewer than
+                    N lines
+  --pr-pattern REGEX
+                    Also count commits whose message matches REGEX as pull
+                    requests (repeatable; (?m) for per-line anchors)
+  --github          Add pull request, review and issue counts from GitHub
+  --github-login LOGIN
+                    GitHub login when it differs from <username> (implies --github)
+  -h, --help        Show this help
+
+EXAMPLES:
+  git-insights user alice
+  git-insights user alice --ownership
+  GITHUB_TOKEN=... git-insights user \"Alice Smith\" --github-login alice
+  git-insights user \"alice@example.com\" --ownership --by-email --top 5 --sort pct"
+                .to_string()
+        }
+        HelpTopic::Timeline => {
+            "\
+git-insights timeline
+
+Show weekly commit activity as a multi-row sparkline (ASCII/Unicode), or
+daily or monthly activity with --granularity. The axis under the chart marks
+how many days, weeks or months ago each column is.
+Colors are on when stdout is a terminal and NO_COLOR is unset.
+
+USAGE:
+  git-insights timeline [--weeks N|--NN|-NN] [--metric commits|contributors]
+                        [--granularity day|week|month] [--style bars|braille]
+                        [--capacity FILE] [--height N] [--bursts FACTOR]
+                        [--burst-commits N] [--no-color] [-c|--color[=WHEN]]
+                        [--output FILE.svg|FILE.png]
+
+OPTIONS:
+  --weeks N     Number of weeks to display (default: 26; 4 with day and 52
+                with month granularity). Shorthand: --52 or -52
+  --metric commits|contributors
+                Plot commits (default) or distinct active authors per week;
+                contributors cannot be combined with --capacity
+  --granularity day|week|month
+                One column per UTC day, per week (default) or per UTC
+                calendar month; --capacity and --bursts need week
+  --style bars|braille
+                Draw one block column per bucket (bars, default) or braille
+                dots with two buckets per column and four levels per row
+                (braille), so 104 weeks fit in 52 columns; --capacity and
+                --bursts need bars
+  --height N    Rows in the chart (default: 7)
+  --capacity FILE
+                CSV of 'YYYY-MM-DD,person_days' rows (one per week). Adds a
+                capacity row and a commits-per-person-day row under the chart
+  --bursts FACTOR
+                Mark weeks with more than FACTOR x the mean of the previous 8
+                weeks in a 'bst' row under the chart (e.g. 2)
+  --burst-commits N
+                List the N largest commits (by lines changed) of each burst
+                week below the chart; implies --bursts 2 unless given
+  -c, --color   Color even when piped; also --color=auto|always|never
+  --no-color    Disable ANSI colors
+  -o, --output F
+                Write the bars as an SVG file instead, or as a PNG for a .png
+                name in builds with the charts feature ('-' writes to
+                stdout); not with --capacity, --bursts, --style or --watch
+  --watch [N]   Redraw every N seconds (default: 10) or when new commits land
+  --recurse-submodules
+                Include commits of initialized submodules, with each
+                repository's commit count listed under the chart
+  -h, --help    Show this help
+
+EXAMPLES:
+  git-insights timeline
+  git-insights timeline --weeks 12
+  git-insights timeline --52
+  git-insights timeline -52 --no-color
+  git-insights timeline --height 12
+  git-insights timeline --capacity team-capacity.csv
+  git-insights timeline --52 --bursts 2.5 --burst-commits 3
+  git-insights timeline --52 --metric contributors
+  git-insights timeline --granularity day --weeks 8
+  git-insights timeline --granularity month --weeks 104
+  git-insights timeline --104 --style braille
+  git-insights timeline --52 --output timeline.png
+  git-insights timeline -12 --watch 60"
+                .to_string()
+        }
+        HelpTopic::Heatmap => {
+            "\
+git-insights heatmap
+
+Show a UTC commit heatmap (weekday x hour).
+The calendar view names each month above the week column it starts in.
+Colors are on when stdout is a terminal and NO_COLOR is unset; with
+COLORTERM=truecolor (or 24bit) cells use a smooth green-yellow-red gradient.
+Use the global --tz author|local to bucket by the authors' or this machine's
+clock instead.
+
+USAGE:
+  git-insights heatmap [--weeks N|--NN|-NN] [--no-color] [-c|--color[=WHEN]] [--output FILE.svg|FILE.png]
+                       [--normalize row|global|log] [--watch [N]]
+
+OPTIONS:
+  --weeks N        Limit to the last N weeks (default: all history). Shorthand: --60 or -60
+  --normalize M    Shade each cell against the busiest cell overall (global, default),
+                   in its weekday row (row), or on a log scale (log) so rare bursts
+                   don't wash out everything else
+  -c, --color      Color even when piped; also --color=auto|always|never
+  --no-color       Disable ANSI colors
+  -o, --output F   Write the heatmap as an SVG file (with color scale and axes) instead,
+                   or as a PNG for a .png name in builds with the charts feature
+                   ('-' writes to stdout)
+  --watch [N]      Redraw every N seconds (default: 10) or when new commits land
+  --recurse-submodules
+                   Include commits of initialized submodules, with each
+                   repository's commit count listed under the heatmap
+  -h, --help       Show this help
+
+EXAMPLES:
+  git-insights heatmap
+  git-insights heatmap --60
+  git-insights heatmap -60 --no-color
+  git-insights heatmap --output heatmap.svg
+  git-insights heatmap --output heatmap.png
+  git-insights heatmap --normalize log
+  git-insights heatmap --tz author"
+                .to_string()
+        }
+        HelpTopic::CodeFrequency => {
+            "\
+git-insights code-frequency
+
+Show code-frequency histograms and heatmaps derived from commit timestamps (UTC), no dependencies.
+
+Notes:
+- UTC: Hour-of-day and day-of-week are computed in UTC. The global --tz author uses each
+  author's own offset (so 3am means 3am on their clock); --tz local uses this machine's.
+- Windowing: When --weeks is provided, the window aligns to the end of the current week (Sun..Sat),
+  mirroring 'timeline' and 'heatmap' behavior.
+- Style/Color: Uses the same ASCII/ANSI ramps and color levels as other visualizations,
+  in 24-bit green-yellow-red when COLORTERM=truecolor. Disable colors with --no-color.
+
+Groupings and views:
+- Histograms: --group hod|dow|dom|moy|year
+    hod = hour-of-day (00..23)
+    dow = day-of-week (Sun..Sat)
+    dom = day-of-month (01..31)
+    moy = month-of-year (Jan..Dec), for seasonal patterns
+    year = calendar year (first..last year with commits), for long-term trends
+- Heatmaps: --heatmap dow-hod|dom-hod|type-hod|add-del
+    dow-hod = day-of-week x hour-of-day (7x24)
+    dom-hod = day-of-month x hour-of-day (31x24)
+    type-hod = conventional-commit type (feat, fix, ...) x hour-of-day
+    add-del = lines added and lines deleted (--numstat), two 7x24 panels side
+              by side on one scale; stacked into one grid with --output
+- Small multiples: --heatmap dow-hod --per-author [--top N]
+    one compact 7x24 panel per author (most commits first), laid out side by
+    side to fit $COLUMNS; each panel is shaded relative to its own busiest hour
+- Summary: --summary [--top N]
+    share of commits on weekdays vs weekends (Sat/Sun) and in working hours
+    (09:00-18:00) vs off hours, overall and per author, to spot overtime
+
+USAGE:
+  git-insights code-frequency [--group X | --heatmap Y [--normalize M]] [--weight W] [--weeks N|--NN|-NN] [--no-color] [-c|--color[=WHEN]]
+                              [--heatmap Y --output FILE.svg|FILE.png]
+                              [--heatmap dow-hod --per-author [--top N]]
+                              [--summary [--top N]]
+
+OPTIONS:
+  --group X       Histogram grouping: hod|dow|dom|moy|year (default: hod if no --heatmap)
+  --heatmap Y     Heatmap kind: dow-hod|dom-hod|type-hod|add-del
+  --normalize M   Heatmap shading: global (default), row (per row) or log
+  --weight W      Count each commit once (commits, default) or by its lines
+                  added+deleted (lines); not with type-hod or add-del
+  --weeks N       Limit to the last N weeks (default: all history). Shorthand: --52 or -52
+  -c, --color     Color even when piped; also --color=auto|always|never
+  --no-color      Disable ANSI colors
+  --table         Render numeric table instead of shaded chart (heatmaps and histograms)
+  --width N       Histogram bar width (default: 40, or 20 with --table; shrunk
+                  to fit $COLUMNS when that is set)
+  -o, --output F  Write the heatmap (requires --heatmap) as an SVG file instead, or as a
+                  PNG for a .png name in builds with the charts feature
+                  ('-' writes to stdout)
+  --per-author    With --heatmap dow-hod: one mini-heatmap per author
+  --summary       Weekday/weekend and working/off-hours percentages
+  --top N         Authors shown with --per-author (default: 6) or --summary
+                  (default: 10)
+  -h, --help      Show this help
+
+EXAMPLES:
+  git-insights code-frequency
+  git-insights code-frequency --group dow
+  git-insights code-frequency --group dom --width 80
+  git-insights code-frequency --group year
+  git-insights code-frequency --group dow --weight lines
+  git-insights code-frequency --heatmap dow-hod --weeks 26
+  git-insights code-frequency --heatmap dom-hod -26 --no-color
+  git-insights code-frequency --heatmap type-hod --table
+  git-insights code-frequency --heatmap add-del --weeks 26
+  git-insights code-frequency --heatmap dom-hod --normalize row
+  git-insights code-frequency --heatmap dow-hod --output dow-hod.svg
+  git-insights code-frequency --heatmap dow-hod --per-author --top 6
+  git-insights code-frequency --summary --weeks 26
+  git-insights code-frequency --heatmap dow-hod --tz author"
+                .to_string()
+        }
+        HelpTopic::Report => {
+            "\
+git-insights report
+
+Write a single self-contained HTML file (git-insights-report.html) for sharing
+with non-terminal users. Embeds, as inline SVG/CSS with no external assets:
+- Author stats table (surviving LOC, commits, files)
+- Weekly commit timeline (last 26 weeks)
+- Calendar heatmap (last 52 weeks, UTC)
+- Code-frequency charts (hour-of-day, day-of-week, weekday x hour)
+
+USAGE:
+  git-insights report [--format html] [-o|--output PATH]
+
+OPTIONS:
+  --format F          Output format: html (default)
+  -o, --output PATH   Write to PATH instead of git-insights-report.html ('-' for stdout)
+  -h, --help          Show this help
+
+EXAMPLES:
+  git-insights report
+  git-insights report --format html"
+                .to_string()
+        }
+        HelpTopic::Export => {
+            "\
+git-insights export
+
+Export repository metrics for consumption by other tools.
+
+--prometheus writes gauges in the Prometheus text exposition format, suitable
+for the node_exporter textfile collector (the file is replaced atomically):
+- git_insights_author_loc{author=\"...\"}, git_insights_author_commits{...},
+  git_insights_author_files{...}
+- git_insights_total_loc, git_insights_total_commits, git_insights_total_files,
+  git_insights_authors
+
+--xlsx writes an Excel workbook with four sheets:
+- stats: author, loc, commits, files (as in `stats`)
+- ownership: file, author, lines, share of the file's surviving lines
+- churn: file, non-merge commits touching it
+- timeline: week_start (UTC date), commits, for the last 52 weeks
+
+USAGE:
+  git-insights export --prometheus <FILE>
+  git-insights export --xlsx <FILE>
+
+OPTIONS:
+  --prometheus FILE   Write Prometheus textfile metrics to FILE
+  --xlsx FILE         Write an .xlsx workbook to FILE ('-' for stdout)
+  -h, --help          Show this help
+
+EXAMPLES:
+  git-insights export --prometheus /var/lib/node_exporter/textfile/git.prom
+  git-insights export --xlsx insights.xlsx"
+                .to_string()
+        }
+        HelpTopic::LogJson => {
+            "\
+git-insights log-json
+
+Export the commit stream as JSON Lines (git-insights-log.jsonl), one object per
+commit, newest first, merges included so topology can be rebuilt offline:
+  {\"sha\", \"parents\": [...], \"refs\": [...], \"author\", \"email\",
+   \"author_time\", \"author_time_utc\", \"committer_time\",
+   \"committer_time_utc\", \"subject\"}
+Times are Unix seconds; the *_utc twins are RFC 3339 UTC strings.
+parents are in order (first parent first); refs are short ref decorations such
+as \"HEAD -> main\" or \"tag: v1.0\". Respects --reachable-from, --exclude-me and
+--only-me.
+
+USAGE:
+  git-insights log-json [-o|--output PATH]
+
+OPTIONS:
+  -o, --output PATH   Write to PATH instead of git-insights-log.jsonl ('-' for stdout)
+  -h, --help          Show this help
+
+EXAMPLES:
+  git-insights log-json
+  git-insights log-json -o - | jq -c 'select(.parents | length > 1)'"
+                .to_string()
+        }
+        HelpTopic::CodeownersCheck => {
+            "\
+git-insights codeowners-check
+
+Compare CODEOWNERS (.github/, repo root or docs/ at HEAD) against blame. Each
+tracked text file belongs to the last matching rule; a rule drifts when its
+listed owners hold less than --min-pct of the surviving lines, or have made no
+commits to its files in the last --stale-days days. Owners match commits by
+email, or for @handles by author name, email local part or GitHub noreply
+address. Team owners (@org/team) can't be resolved and are reported as such.
+
+Exits with status 1 when any rule drifted, so it can gate CI.
+
+USAGE:
+  git-insights codeowners-check [--file PATH] [--min-pct X] [--stale-days N]
+
+OPTIONS:
+  --file PATH       Read this CODEOWNERS file from disk instead
+  --min-pct X       Minimum share of surviving lines held by owners (default: 10)
+  --stale-days N    Window for recent owner commits (default: 365; 0 disables)
+  -h, --help        Show this help
+
+EXAMPLES:
+  git-insights codeowners-check
+  git-insights codeowners-check --min-pct 25 --stale-days 180"
+                .to_string()
+        }
+        HelpTopic::CodeownersAudit => {
+            "\
+git-insights codeowners-audit
+
+Audit every owner declared in CODEOWNERS (.github/, repo root or docs/ at HEAD)
+against blame. Each tracked text file belongs to the last matching rule; for
+each rule, every declared owner's share of the surviving lines is shown next
+to the author who actually holds the most. Owners below --min-pct are flagged.
+Unlike codeowners-check, which judges a rule's owners together, a rule listing
+one active and one departed owner still flags the departed one. Team owners
+(@org/team) can't be resolved to commits and are never flagged.
+
+Exits with status 1 when any owner is flagged, so it can gate CI.
+
+USAGE:
+  git-insights codeowners-audit [--file PATH] [--min-pct X]
+
+OPTIONS:
+  --file PATH       Read this CODEOWNERS file from disk instead
+  --min-pct X       Minimum share of surviving lines per owner (default: 10)
+  -h, --help        Show this help
+
+EXAMPLES:
+  git-insights codeowners-audit
+  git-insights codeowners-audit --min-pct 5 --file CODEOWNERS.next"
+                .to_string()
+        }
+        HelpTopic::Messages => {
+            "\
+git-insights messages
+
+Classify non-merge commit subjects by conventional-commit type (feat, fix,
+docs, style, refactor, perf, test, build, ci, chore, revert; anything else is
+'other') and show the overall mix as a histogram plus a per-author or
+per-month table. 'type(scope)!:' prefixes are recognised case-insensitively.
+
+With --keywords, subjects are split into lowercase words instead (without
+the type prefix, stopwords, numbers and one-letter words) and the terms found
+in the most subjects are listed overall and per author or month, next to the
+average subject length and the share of short subjects.
+
+USAGE:
+  git-insights messages [--by author|month] [--keywords [--top N] [--under N]]
+
+OPTIONS:
+  --by author|month   Table rows (default: author)
+  --keywords          Report top subject terms and subject lengths
+  --top N             Terms listed per table (default: 10)
+  --under N           Subjects shorter than N characters count as short
+                      (default: 50)
+  -h, --help          Show this help
+
+EXAMPLES:
+  git-insights messages
+  git-insights messages --by month --reachable-from 'v*'
+  git-insights messages --keywords --top 5 --under 72"
+                .to_string()
+        }
+        HelpTopic::Quality => {
+            "\
+git-insights quality
+
+Count follow-up commits that undo or patch earlier work and show their share
+of all commits per month and per author; a rising revert rate is an early
+warning sign. Reverts are subjects written by 'git revert' (Revert \"...\")
+or with a conventional 'revert:' type; fixups are 'fixup!', 'amend!' and
+'squash!' commits from 'git commit --fixup/--squash' that were never
+autosquashed. Merge commits count only with --include-merges.
+
+USAGE:
+  git-insights quality
+
+OPTIONS:
+  -h, --help    Show this help
+
+EXAMPLES:
+  git-insights quality
+  git-insights quality --reachable-from 'v*'"
+                .to_string()
+        }
+        HelpTopic::Signing => {
+            "\
+git-insights signing
+
+Signature coverage for signing policies: how many commits carry a GPG, SSH or
+X.509 signature (git log's %G? field), overall and per author, least covered
+authors first. 'good %' counts signatures git verified; verifying needs the
+signers' keys (gpg keyring, or gpg.ssh.allowedSignersFile for SSH), so a
+signed commit whose key is missing is still counted as signed.
+
+USAGE:
+  git-insights signing
+
+OPTIONS:
+  -h, --help    Show this help
+
+EXAMPLES:
+  git-insights signing
+  git-insights signing --reachable-from 'v*'"
+                .to_string()
+        }
+        HelpTopic::Churn => {
+            "\
+git-insights churn
+
+Lines added and deleted over the last --weeks weeks (default 12), one row per
+file, most changed first. With --by-ext the rows are file extensions instead
+(files without one, such as Makefile or .gitignore, are grouped as '(none)'),
+showing whether recent change is in code, configs or docs. Binary files are
+not counted; paths matching --exclude are left out.
+
+USAGE:
+  git-insights churn [--weeks N] [--by-ext] [--top N]
+
+OPTIONS:
+  --weeks N     Window in weeks; 0 for all history (default: 12)
+  --by-ext      Aggregate per file extension
+  --top N       Rows to show (default: 20)
+  -h, --help    Show this help
+
+EXAMPLES:
+  git-insights churn
+  git-insights churn --by-ext --weeks 4
+  git-insights churn --weeks 0 --top 50"
+                .to_string()
+        }
+        HelpTopic::Daemon => {
+            "\
+git-insights daemon
+
+Replace cron scripts: every --interval, fetch each repository listed in the
+repos file, re-run the selected analyses and write timestamped exports to
+  <export-dir>/<repo>-<path hash>/<analysis>-YYYYMMDDTHHMMSSZ.<ext>
+When the checked-out branch has an upstream, the analyses run on a detached
+worktree at the fetched upstream commit, kept inside the repo's git directory;
+your working tree and branches are never modified. Bare mirrors are analysed
+as fetched. Failures are logged to stderr and do not stop the other repos.
+Global options (--reachable-from, --exclude-me, ...) apply to every repo.
+
+USAGE:
+  git-insights daemon --repos-file FILE --export-dir DIR [--interval DUR]
+                      [--analyses LIST] [--notify URL] [--once]
+
+OPTIONS:
+  --repos-file FILE   Repository paths, one per line ('#' comments allowed);
+                      re-read every cycle
+  --export-dir DIR    Where exports are written
+  --interval DUR      Time between cycles: 45s, 30m, 1h, 2d (default: 1h)
+  --analyses LIST     Comma-separated: json, report, log-json, prometheus
+                      (default: json,report)
+  --notify URL        POST a JSON digest of each cycle ({\"text\": ...}) to this
+                      webhook (Slack, Mattermost, ...); needs curl
+  --once              Run a single cycle and exit
+  -h, --help          Show this help
+
+EXAMPLES:
+  git-insights daemon --interval 1h --repos-file repos.txt --export-dir out/
+  git-insights daemon --repos-file repos.txt --export-dir out/ --analyses json,prometheus --once"
+                .to_string()
+        }
+        HelpTopic::Owners => {
+            "\
+git-insights owners
+
+Report who owns a file: the commit and author behind one line (--line) plus the
+top owners by surviving lines, and the matching CODEOWNERS entry if the repo
+has one. Only the requested file is blamed (at HEAD), so it is quick enough for
+editor plugins that show inline \"who owns this\" annotations. Paths may be
+absolute or relative to the current directory.
+
+USAGE:
+  git-insights owners --file PATH [--line N] [--format text|json] [--top N]
+
+OPTIONS:
+  --file PATH           File to inspect (required)
+  --line N              Also report the owner of line N (1-based, as of HEAD)
+  --format text|json    Output format (default: text); json is a single object:
+                        {\"file\", \"line\": {...}|null, \"owners\": [...],
+                         \"total_lines\", \"codeowners\": [...]}
+  --top N               Number of file owners to list (default: 5)
+  -h, --help            Show this help
+
+EXAMPLES:
+  git-insights owners --file src/main.rs
+  git-insights owners --file \"$PWD/src/main.rs\" --line 42 --format json"
+                .to_string()
+        }
+        HelpTopic::File => {
+            "\
+git-insights file
+
+Report who owns a file or a directory: surviving lines (blame at HEAD) per
+author across its text files, their share, and when each author last
+committed to the path. Authors whose lines have all been replaced still show,
+with 0 lines. The inverse of 'user <name> --ownership'.
+
+USAGE:
+  git-insights file <path> [--format text|json] [--top N]
+
+OPTIONS:
+  --format text|json    Output format (default: text); json is a single object:
+                        {\"path\", \"files\", \"total_lines\", \"owners\": [...]}
+  --top N               Number of authors to list (default: 10; 0 = all)
+  -h, --help            Show this help
+
+EXAMPLES:
+  git-insights file src/main.rs
+  git-insights file src/ --top 0 --format json"
+                .to_string()
+        }
+        HelpTopic::Releases => {
+            "\
+git-insights releases
+
+For each pair of consecutive tags (ordered by tag date) report the non-merge
+commits, distinct authors and lines added/deleted in tagA..tagB, using
+'git log --numstat'. The first tag is counted from the root commit, and work
+on HEAD since the newest tag is shown as (unreleased). The heaviest release by
+lines changed is named at the end.
+
+USAGE:
+  git-insights releases [--tags GLOB] [--top N]
+
+OPTIONS:
+  --tags GLOB     Only consider tags matching GLOB (e.g. 'v*'; default: all)
+  --top N         Contributors listed per release (default: 3)
+  -h, --help      Show this help
+
+EXAMPLES:
+  git-insights releases
+  git-insights releases --tags 'v1.*' --top 5"
+                .to_string()
+        }
+        HelpTopic::Branches => {
+            "\
+git-insights branches
+
+List local and remote-tracking branches with the date of their last commit,
+how many commits they are ahead of / behind the default branch, and how many
+distinct authors wrote the commits that are unique to them. Branches whose
+last commit is older than --stale-days are marked STALE.
+
+The default branch is origin/HEAD when set, otherwise a local main or master,
+otherwise the current branch.
+
+USAGE:
+  git-insights branches [--base REF] [--stale-days N]
+
+OPTIONS:
+  --base REF        Compare against REF instead of the default branch
+  --stale-days N    Days without commits before a branch is stale (default: 90)
+  -h, --help        Show this help
+
+EXAMPLES:
+  git-insights branches
+  git-insights branches --base origin/develop --stale-days 30"
+                .to_string()
+        }
+        HelpTopic::Languages => {
+            "\
+git-insights languages
+
+Group surviving lines (blame at HEAD) and file counts by language, using a
+built-in extension map (.rs -> Rust, .yml -> YAML, Dockerfile, ...). Unknown
+extensions are counted as Other. A second table lists each author's lines and
+their top languages, so you can see who writes the Rust and who the YAML.
+
+USAGE:
+  git-insights languages [--top N]
+
+OPTIONS:
+  --top N       Languages listed per author (default: 3)
+  -h, --help    Show this help
+
+EXAMPLES:
+  git-insights languages
+  git-insights languages --top 5 --exclude-me"
+                .to_string()
+        }
+        HelpTopic::Classes => {
+            "\
+git-insights classes
+
+Group surviving lines (blame at HEAD) and non-merge commits by author and path
+class, to see who writes tests, who writes docs and who writes production
+code. Each file belongs to the first class with a matching gitignore-style
+pattern; files matching none are counted as 'other'. A commit counts once for
+every class it touches.
+
+Classes come from the [classes] table of the config files, or --class:
+
+  [classes]
+  tests = [\"tests/\", \"**/*_test.rs\"]
+  docs = [\"docs/\", \"*.md\"]
+
+Without either, 'tests' and 'docs' use common layouts (tests/, *_test.*,
+*.spec.*, docs/, *.md, ...).
+
+USAGE:
+  git-insights classes [--class NAME=PATTERN]...
+
+OPTIONS:
+  --class NAME=PATTERN
+                Add PATTERN to class NAME; repeatable, replaces the defaults
+  -h, --help    Show this help
+
+EXAMPLES:
+  git-insights classes
+  git-insights classes --class tests=tests/ --class 'tests=**/*_test.rs'"
+                .to_string()
+        }
+        HelpTopic::Risk => {
+            "\
+git-insights risk
+
+Rank files by a composite \"where will we get hurt next\" score combining:
+  churn       non-merge commits touching the file (as in 'hotspots')
+  ownership   share of surviving lines held by the file's top author
+  age         mean age of the surviving lines
+  size        surviving lines
+Each signal is scaled to 0..1 by its maximum across files; the score is their
+weighted mean on a 0-100 scale. Every changed text file is blamed at HEAD.
+
+USAGE:
+  git-insights risk [--weights SPEC] [--top N] [--format text|json]
+
+OPTIONS:
+  --weights SPEC        Comma-separated signal=weight pairs, e.g. churn=2,age=0.5
+                        (unlisted signals keep weight 1; 0 disables a signal)
+  --top N               Number of files to show (default: 20)
+  --format text|json    Output format (default: text); json is an array of
+                        {\"file\", \"score\", \"commits\", \"loc\", \"top_owner\",
+                         \"owner_share\", \"age_days\", \"signals\": {...}}
+  -h, --help            Show this help
+
+EXAMPLES:
+  git-insights risk
+  git-insights risk --weights ownership=3,size=0 --top 10 --format json"
+                .to_string()
+        }
+        HelpTopic::TimeAudit => {
+            "\
+git-insights time-audit
+
+List commits whose timestamps would silently distort time-based charts:
+  epoch-zero                author or committer time in the first day of 1970
+  future                    dated after now (beyond the skew tolerance)
+  before-inception          dated before the oldest root commit
+  committer-before-author   committed long before it was authored
+Pass the global --exclude-bad-times to drop these commits from timeline,
+heatmap, code-frequency and other time-based analyses.
+
+USAGE:
+  git-insights time-audit [--max-skew HOURS]
+
+OPTIONS:
+  --max-skew HOURS   Clock skew tolerated for future and committer-before-author
+                     checks (default: 24)
+  -h, --help         Show this help
+
+EXAMPLES:
+  git-insights time-audit
+  git-insights timeline --exclude-bad-times"
+                .to_string()
+        }
+        HelpTopic::EncodingAudit => {
+            "\
+git-insights encoding-audit
+
+Scan every text file at HEAD for mixed line endings (both LF and CRLF) and
+lines that are not valid UTF-8, then blame the flagged lines to show who
+introduced them. In a mixed file the less common ending is flagged.
+Use it to clean up before enforcing a .gitattributes policy such as
+'* text=auto' followed by 'git add --renormalize .'.
+
+USAGE:
+  git-insights encoding-audit
+
+OPTIONS:
+  -h, --help   Show this help
+
+EXAMPLES:
+  git-insights encoding-audit
+  git-insights encoding-audit --exclude-me"
+                .to_string()
+        }
+        HelpTopic::Contributors => {
+            "\
+git-insights contributors
+
+Contributor lifecycle: per author, the first and last commit date, the number
+of distinct months with commits, and total commits. A second table counts
+authors whose first commit (joined) and last commit (last seen) fall in each
+year, to show onboarding and offboarding patterns.
+
+USAGE:
+  git-insights contributors [--sort first|last|commits]
+
+OPTIONS:
+  --sort first|last|commits
+                Order by first commit (default), most recent activity, or
+                commit count
+  -h, --help    Show this help
+
+EXAMPLES:
+  git-insights contributors
+  git-insights contributors --sort last --normalize-names casefold"
+                .to_string()
+        }
+        HelpTopic::Growth => {
+            "\
+git-insights growth
+
+Contributor growth: commits are bucketed by calendar month (UTC, author
+date) and each author active in a month counts as new (their first-ever
+commit is in that month) or returning. Months without commits are shown
+empty so gaps stay visible.
+
+With --loc, shows codebase size instead: total lines in text files (no NUL in
+the first 8000 bytes) at N evenly spaced commits from the first to the latest,
+drawn with the timeline chart.
+
+USAGE:
+  git-insights growth [--format text|json] [--no-color] [-c|--color[=WHEN]]
+  git-insights growth --loc [--samples N] [--format text|json] [--no-color]
+
+OPTIONS:
+  --format text|json    Output format (default: text); json is an array of
+                        {\"month\": \"YYYY-MM\", \"new\": N, \"returning\": N}, or
+                        with --loc {\"sha\": ..., \"date\": ..., \"lines\": N}
+  --loc                 Chart total lines over time instead of contributors
+  --samples N           Commits sampled by --loc (default: 40)
+  -c, --color           Color even when piped; also --color=auto|always|never
+  --no-color            Disable colors; new is drawn as '+', returning as '='
+  -h, --help            Show this help
+
+EXAMPLES:
+  git-insights growth
+  git-insights growth --format json --normalize-names casefold
+  git-insights growth --loc --samples 60"
+                .to_string()
+        }
+        HelpTopic::OwnershipByTenure => {
+            "\
+git-insights ownership-by-tenure
+
+Buckets every surviving line at HEAD by how long its author had been
+contributing when the line was written: under 3 months, 3-12 months, or over
+a year since their first commit. Shows how much of the codebase was written
+by newcomers. Lines whose author has no commit in the analysed history (such
+as synthetic merge lines) are reported as unknown.
+
+USAGE:
+  git-insights ownership-by-tenure
+
+OPTIONS:
+  -h, --help    Show this help
+
+EXAMPLES:
+  git-insights ownership-by-tenure
+  git-insights ownership-by-tenure --normalize-names casefold"
+                .to_string()
+        }
+        HelpTopic::OwnershipHistory => {
+            "\
+git-insights ownership-history <username>
+
+How a user's footprint grows or erodes: text files are blamed at N evenly
+spaced commits from the first to the latest, and the lines attributed to the
+user at each one are drawn with the timeline chart, followed by the change
+from the first sample, the peak, and one row per sample with the user's share
+of all lines. The user matches the exact author name, or the email with
+--by-email. Each sample blames the whole tree, so more samples cost more time.
+
+USAGE:
+  git-insights ownership-history <username> [--by-email|-e] [--samples N]
+                                 [--format text|json] [--no-color]
+
+OPTIONS:
+  -e, --by-email        Match <username> as an email address
+  --samples N           Commits to blame (default: 12)
+  --format text|json    Output format (default: text); json is an array of
+                        {\"sha\": ..., \"date\": ..., \"lines\": N, \"total\": N}
+  -c, --color           Color even when piped; also --color=auto|always|never
+  --no-color            Disable colors
+  -h, --help            Show this help
+
+EXAMPLES:
+  git-insights ownership-history \"Jane Doe\"
+  git-insights ownership-history jane@example.com --by-email --samples 24
+  git-insights ownership-history \"Jane Doe\" --format json"
+                .to_string()
+        }
+        HelpTopic::Velocity => {
+            "\
+git-insights velocity
+
+Sprint-style velocity: non-merge commits are grouped by committer date into
+fixed-length iterations, with per-iteration commits, lines added and deleted,
+distinct authors, and the change in commits from the previous iteration.
+
+USAGE:
+  git-insights velocity [--iteration LEN] [--start YYYY-MM-DD] [--no-color] [-c|--color[=WHEN]]
+
+OPTIONS:
+  --iteration LEN       Iteration length in days or weeks, e.g. 10d, 1w, 2w
+                        (default: 2w; a bare number means weeks)
+  --start YYYY-MM-DD    First day of the first iteration (UTC). Commits before
+                        it are ignored. Default: the Monday on or before the
+                        oldest commit
+  -c, --color           Color even when piped; also --color=auto|always|never
+  --no-color            Disable colors; trend arrows become ^ and v
+  -h, --help            Show this help
+
+EXAMPLES:
+  git-insights velocity
+  git-insights velocity --iteration 3w --start 2024-01-08"
+                .to_string()
+        }
+        HelpTopic::OwnershipMatrix => {
+            "\
+git-insights ownership-matrix
+
+For every author, a nested list of the files and directories they significantly
+own at HEAD: paths where they wrote at least --min-pct of the surviving lines
+(directories count all files below them). The whole many-to-many ownership
+matrix in one document, e.g. for handover packets; `user --ownership` shows
+one author at a time.
+
+USAGE:
+  git-insights ownership-matrix [--min-pct X] [--format markdown|json] [--by-email|-e] [-o FILE]
+
+OPTIONS:
+  --min-pct X           Share of a path's lines an author needs to own it (default: 30)
+  --format F            markdown (default) or json
+  -e, --by-email        Group by \"Name <email>\" (default groups by name only)
+  -o, --output FILE     Write to FILE instead of stdout
+  -h, --help            Show this help
+
+EXAMPLES:
+  git-insights ownership-matrix --min-pct 30 --format markdown
+  git-insights ownership-matrix --format json -o ownership.json"
+                .to_string()
+        }
+        HelpTopic::Compare => {
+            "\
+git-insights compare
+
+Compute stats at two refs (tags, branches or commits) the way 'stats' does at
+HEAD - surviving lines from blame at each ref, commits reachable from it, files
+with surviving lines - and print per-author deltas, largest ownership change
+first. Ownership is each author's share of all surviving lines at that ref;
+gains are green and losses red. Authors present at only one ref count as zero
+at the other. --reachable-from and --stdin-commits do not apply.
+
+USAGE:
+  git-insights compare <refA> <refB> [--by-email|-e] [--no-color] [-c|--color[=WHEN]]
+
+OPTIONS:
+  -e, --by-email    Group by \"Name <email>\" (default groups by name only)
+  -c, --color       Color even when piped; also --color=auto|always|never
+  --no-color        Disable ANSI colors
+  -h, --help        Show this help
+
+EXAMPLES:
+  git-insights compare v1.0 v2.0
+  git-insights compare v2.0 HEAD --by-email"
+                .to_string()
+        }
+        HelpTopic::Survival => {
+            "\
+git-insights survival
+
+Estimate how long lines live before they are rewritten or deleted. Text files
+are blamed at N evenly spaced commits (the newest included) and each line is
+followed by the commit, file and line number that introduced it. A line
+missing from a later sample was removed by that sample's date; lines still
+present at the newest sample have only a lower bound on their life. Reports
+lines seen, lines removed and the Kaplan-Meier median lifetime overall, per
+top-level directory and per author. A short median means much rework; '>Nd'
+means more than half of the lines outlived the window. Lines added and removed
+between two samples are not seen, so more samples give finer estimates at the
+cost of one blame per file per sample.
+
+USAGE:
+  git-insights survival [--samples N]
+
+OPTIONS:
+  --samples N   Commits to blame (default: 6)
+  -h, --help    Show this help
+
+EXAMPLES:
+  git-insights survival
+  git-insights survival --samples 12 --normalize-names casefold"
+                .to_string()
+        }
+        HelpTopic::Leaderboard => {
+            "\
+git-insights top
+
+Rank authors by one metric and draw the leaderboard as a bar chart. loc and
+files come from blame at HEAD (surviving lines, files with surviving lines),
+so they take as long as 'stats'; commits and churn (lines added plus deleted
+by non-merge commits) only read the log. Authors are grouped by name.
+
+USAGE:
+  git-insights top [--metric loc|commits|files|churn] [--limit N] [--no-color] [-c|--color[=WHEN]]
+
+OPTIONS:
+  --metric M    loc (default), commits, files or churn
+  --limit N     Authors shown (default: 10; 0 = all)
+  -c, --color   Color even when piped; also --color=auto|always|never
+  --no-color    Disable ANSI colors
+  -h, --help    Show this help
+
+EXAMPLES:
+  git-insights top
+  git-insights top --metric churn --limit 5
+  git-insights top --metric commits --limit 0 --no-color"
+                .to_string()
+        }
+        HelpTopic::Man => {
+            "\
+git-insights man
+
+Print a roff manual page built from the same text as --help: the main page
+(commands and global options) or the page of one command.
+
+USAGE:
+  git-insights man [COMMAND] [--dir DIR]
+
+OPTIONS:
+  --dir DIR     Write git-insights.1 and one git-insights-COMMAND.1 per
+                command into DIR instead of printing
+  -h, --help    Show this help
+
+EXAMPLES:
+  git-insights man | man -l -
+  git-insights man timeline > git-insights-timeline.1
+  git-insights man --dir share/man/man1"
+                .to_string()
+        }
+        HelpTopic::Serve => {
+            "\
+git-insights serve
+
+Serve the repository's insights over HTTP so teammates can browse them without
+installing the tool: a small HTML dashboard at / and a JSON API. Every request
+is computed fresh from the current repository; requests are handled one at a
+time. Only loopback is served by default; pass --host 0.0.0.0 to share it.
+
+USAGE:
+  git-insights serve [--port N] [--host ADDR]
+
+OPTIONS:
+  --port N       Port to listen on (default: 8080; 0 picks a free one)
+  --host ADDR    Address to bind (default: 127.0.0.1)
+  -h, --help     Show this help
+
+ENDPOINTS:
+  /                     HTML dashboard
+  /stats                Totals and per-author lines, commits and files
+                        (?by=email groups by email)
+  /timeline             Weekly counts (?weeks=N&metric=commits|contributors)
+  /heatmap              Day-of-week x week commit grid (?weeks=N)
+  /ownership/USER       Files USER owns lines in (?top=N&sort=pct&by=email)
+
+?weeks= accepts at most 520. Clients that stall for 10s while sending a
+request or reading a response are disconnected.
+
+EXAMPLES:
+  git-insights serve
+  git-insights serve --port 9000 --host 0.0.0.0
+  curl -s localhost:8080/ownership/alice?top=5"
+                .to_string()
+        }
+        HelpTopic::Interactive => {
+            "\
+git-insights interactive
+
+Walk through choosing an analysis, a history window and an output format with
+simple numbered prompts. The equivalent non-interactive command is printed
+before it runs, so it can be copied into scripts or shared with teammates.
+Prompts are written to stderr; press Enter to accept the default in [brackets].
+
+USAGE:
+  git-insights interactive
+
+OPTIONS:
+  -h, --help    Show this help"
+                .to_string()
+        }
+        HelpTopic::Hotspots => {
+            "\
+git-insights hotspots
+
+Rank files that are both large and frequently modified. Each text file at HEAD
+scores (non-merge commits touching it) x (its size), where size is either its
+line count or an indentation-based complexity proxy (sum of indent levels over
+non-blank lines). Respects --reachable-from, --exclude-me and --only-me.
+
+USAGE:
+  git-insights hotspots [--top N] [--size loc|indent]
+
+OPTIONS:
+  --top N               Show the top N files (default: 20)
+  --size loc|indent     Size measure (default: loc)
+  -h, --help            Show this help
+
+EXAMPLES:
+  git-insights hotspots
+  git-insights hotspots --size indent --top 10"
+                .to_string()
+        }
+        HelpTopic::Badge => {
+            "\
+git-insights badge
+
+Print shields.io endpoint JSON (schemaVersion 1) for a single metric, e.g.
+{\"schemaVersion\": 1, \"label\": \"contributors\", \"message\": \"14\", \"color\": \"blue\"}
+Commit the output (or publish it from CI) and point a shields.io endpoint badge at it.
+
+USAGE:
+  git-insights badge [--metric contributors|bus-factor|loc]
+
+OPTIONS:
+  --metric M    contributors (default): distinct commit authors
+                bus-factor: fewest authors owning more than half of surviving LOC
+                loc: total surviving LOC in HEAD
+  -h, --help    Show this help
+
+EXAMPLES:
+  git-insights badge > contributors.json
+  git-insights badge --metric bus-factor > bus-factor.json"
+                .to_string()
+        }
+        HelpTopic::View => {
+            "\
+git-insights view
+
+Render a stats file previously written by 'git-insights json' without touching
+git, so data can be collected on one machine and presented on another.
+
+USAGE:
+  git-insights view <file.json> [--format table|html] [-o|--output PATH]
+
+OPTIONS:
+  --format F          table (default): print the stats table to the terminal
+                      html: write a self-contained git-insights-report.html
+  -o, --output PATH   With --format html: write to PATH instead ('-' for stdout)
+  -h, --help          Show this help
+
+EXAMPLES:
+  git-insights view git-insights.json
+  git-insights view git-insights.json --format html"
+                .to_string()
+        }
+    }
+}
+
+pub fn channel_string() -> &'static str {
+    if cfg!(feature = "python") {
+        "pip"
+    } else {
+        "cargo"
+    }
+}
+
+pub fn version_string() -> String {
+    format!("{} ({})", env!("CARGO_PKG_VERSION"), channel_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_cli_stats_default_by_name() {
+        let cli = Cli::parse_from_args(vec!["git-insights".to_string(), "stats".to_string()])
+            .expect("Failed to parse args");
+        match cli.command {
+            Commands::Stats { by_name, .. } => assert!(by_name),
+            _ => panic!("Expected Stats command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_stats_by_email_flag() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "stats".to_string(),
+            "--by-email".to_string(),
+        ])
+        .expect("Failed to parse args");
+        match cli.command {
+            Commands::Stats { by_name, .. } => assert!(!by_name),
+            _ => panic!("Expected Stats command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_stats_short_e_flag() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "stats".to_string(),
+            "-e".to_string(),
+        ])
+        .expect("Failed to parse args");
+        match cli.command {
+            Commands::Stats { by_name, .. } => assert!(!by_name),
+            _ => panic!("Expected Stats command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_json() {
+        let cli = Cli::parse_from_args(vec!["git-insights".to_string(), "json".to_string()])
+            .expect("Failed to parse args");
+        assert!(matches!(cli.command, Commands::Json { output: None }));
+    }
+
+    #[test]
+    fn test_cli_user() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "user".to_string(),
+            "testuser".to_string(),
+        ])
+        .expect("Failed to parse args");
+        match cli.command {
+            Commands::User {
+                username,
+                ownership,
+                by_email,
+                top,
+                sort,
+                github,
+                pr_patterns,
+                min_pct,
+                min_loc,
+            } => {
+                assert_eq!(github, None);
+                assert!(pr_patterns.is_empty());
+                assert_eq!((min_pct, min_loc), (None, None));
+                assert_eq!(username, "testuser");
+                assert!(!ownership);
+                assert!(!by_email);
+                assert!(top.is_none());
+                assert!(sort.is_none());
+            }
+            _ => panic!("Expected User command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_user_ownership_flags() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "user".to_string(),
+            "palash".to_string(),
+            "--ownership".to_string(),
+            "--by-email".to_string(),
+            "--top".to_string(),
+            "5".to_string(),
+            "--sort".to_string(),
+            "pct".to_string(),
+        ])
+        .expect("Failed to parse args");
+        match cli.command {
+            Commands::User {
+                username,
+                ownership,
+                by_email,
+                top,
+                sort,
+                github,
+                pr_patterns,
+                min_pct,
+                min_loc,
+            } => {
+                assert_eq!(github, None);
+                assert!(pr_patterns.is_empty());
+                assert_eq!((min_pct, min_loc), (None, None));
+                assert_eq!(username, "palash");
+                assert!(ownership);
+                assert!(by_email);
+                assert_eq!(top, Some(5));
+                assert_eq!(sort.as_deref(), Some("pct"));
+            }
+            _ => panic!("Expected User command with ownership flags"),
+        }
+
+        let cli2 = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "user".to_string(),
+            "palash".to_string(),
+            "--ownership".to_string(),
+            "-e".to_string(),
+            "--top=3".to_string(),
+            "--sort=loc".to_string(),
+        ])
+        .expect("Failed to parse args");
+        match cli2.command {
+            Commands::User {
+                username,
+                ownership,
+                by_email,
+                top,
+                sort,
+                github,
+                pr_patterns,
+                min_pct,
+                min_loc,
+            } => {
+                assert_eq!(github, None);
+                assert!(pr_patterns.is_empty());
+                assert_eq!((min_pct, min_loc), (None, None));
+                assert_eq!(username, "palash");
+                assert!(ownership);
+                assert!(by_email);
+                assert_eq!(top, Some(3));
+                assert_eq!(sort.as_deref(), Some("loc"));
+            }
+            _ => panic!("Expected User command with equals-style flags"),
+        }
+    }
+
+    #[test]
+    fn test_cli_no_args_yields_help() {
+        let cli = Cli::parse_from_args(vec!["git-insights".to_string()]).expect("parse");
+        match cli.command {
+            Commands::Help { topic } => match topic {
+                HelpTopic::Top => {}
+                _ => panic!("Expected top-level help"),
+            },
+            _ => panic!("Expected Help command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_top_help_flag() {
+        let cli = Cli::parse_from_args(vec!["git-insights".to_string(), "--help".to_string()])
+            .expect("parse");
+        match cli.command {
+            Commands::Help { topic } => match topic {
+                HelpTopic::Top => {}
+                _ => panic!("Expected top-level help"),
+            },
+            _ => panic!("Expected Help command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_stats_help_flag() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "stats".to_string(),
+            "--help".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::Help { topic } => match topic {
+                HelpTopic::Stats => {}
+                _ => panic!("Expected stats help"),
+            },
+            _ => panic!("Expected Help command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_version_flag() {
+        let cli = Cli::parse_from_args(vec!["git-insights".to_string(), "--version".to_string()])
+            .expect("parse");
+        assert!(matches!(cli.command, Commands::Version));
+    }
+
+    #[test]
+    fn test_version_string_includes_channel_and_version() {
+        let v = super::version_string();
+        assert!(v.ends_with(" (cargo)"));
+        assert!(v.starts_with(env!("CARGO_PKG_VERSION")));
+    }
+
+    #[test]
+    fn test_cli_unknown_command() {
+        let err = Cli::parse_from_args(vec!["git-insights".to_string(), "invalid".to_string()])
+            .expect_err("Expected an error for unknown command");
+        assert!(err.contains("Unknown command: invalid"));
+    }
+
+    #[test]
+    fn test_cli_user_no_username() {
+        let err = Cli::parse_from_args(vec!["git-insights".to_string(), "user".to_string()])
+            .expect_err("Expected an error for user command without username");
+        assert_eq!(err, "Usage: git-insights user <username> [--ownership] [--by-email|-e] [--top N] [--sort loc|pct]");
+    }
+
+    #[test]
+    fn test_cli_timeline_default() {
+        let cli = Cli::parse_from_args(vec!["git-insights".to_string(), "timeline".to_string()])
+            .expect("parse");
+        match cli.command {
+            Commands::Timeline { weeks, color, .. } => {
+                assert!(weeks.is_none());
+                assert_eq!(color, ColorChoice::Auto);
+            }
+            _ => panic!("Expected Timeline command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_timeline_weeks_flags() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "timeline".to_string(),
+            "--weeks".to_string(),
+            "12".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::Timeline { weeks, color, .. } => {
+                assert_eq!(weeks, Some(12));
+                assert_eq!(color, ColorChoice::Auto);
+            }
+            _ => panic!("Expected Timeline command"),
+        }
+        let cli2 = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "timeline".to_string(),
+            "--weeks=8".to_string(),
+        ])
+        .expect("parse");
+        match cli2.command {
+            Commands::Timeline { weeks, color, .. } => {
+                assert_eq!(weeks, Some(8));
+                assert_eq!(color, ColorChoice::Auto);
+            }
+            _ => panic!("Expected Timeline command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_heatmap() {
+        let cli = Cli::parse_from_args(vec!["git-insights".to_string(), "heatmap".to_string()])
+            .expect("parse");
+        match cli.command {
+            Commands::Heatmap { weeks, color, .. } => {
+                assert!(weeks.is_none());
+                assert_eq!(color, ColorChoice::Auto);
+            }
+            _ => panic!("Expected Heatmap"),
+        }
+    }
+
+    #[test]
+    fn test_cli_week_start() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "heatmap".to_string(),
+            "--week-start".to_string(),
+            "Mon".to_string(),
+        ])
+        .expect("parse");
+        assert_eq!(cli.filter.week_start, WeekStart::Monday);
+        assert!(Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "code-frequency".to_string(),
+            "--week-start=fri".to_string(),
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_cli_palette() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "timeline".to_string(),
+            "--palette".to_string(),
+            "viridis".to_string(),
+        ])
+        .expect("parse");
+        assert_eq!(cli.palette, Palette::Viridis);
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "heatmap".to_string(),
+            "--palette=#000000,#ff0000".to_string(),
+        ])
+        .expect("parse");
+        assert_eq!(cli.palette, Palette::Custom(vec![(0, 0, 0), (255, 0, 0)]));
+        assert!(Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "heatmap".to_string(),
+            "--palette".to_string(),
+            "rainbow".to_string(),
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_cli_heatmap_normalize() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "heatmap".to_string(),
+            "--normalize".to_string(),
+            "LOG".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::Heatmap { normalize, .. } => assert_eq!(normalize.as_deref(), Some("log")),
+            _ => panic!("Expected Heatmap"),
+        }
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "code-frequency".to_string(),
+            "--heatmap".to_string(),
+            "dow-hod".to_string(),
+            "--normalize=row".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::CodeFrequency { normalize, .. } => {
+                assert_eq!(normalize.as_deref(), Some("row"))
+            }
+            _ => panic!("Expected CodeFrequency"),
+        }
+        assert!(Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "heatmap".to_string(),
+            "--normalize=log".to_string(),
+            "-o".to_string(),
+            "cal.svg".to_string(),
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_cli_code_frequency_weight() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "code-frequency".to_string(),
+            "--group=dow".to_string(),
+            "--weight".to_string(),
+            "Lines".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::CodeFrequency { weight, .. } => assert_eq!(weight.as_deref(), Some("lines")),
+            _ => panic!("Expected CodeFrequency"),
+        }
+        assert!(Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "code-frequency".to_string(),
+            "--weight".to_string(),
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_cli_timeline_granularity() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "timeline".to_string(),
+            "--granularity=Month".to_string(),
+            "-8".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::Timeline {
+                weeks, granularity, ..
+            } => {
+                assert_eq!(weeks, Some(8));
+                assert_eq!(granularity.as_deref(), Some("month"));
+            }
+            _ => panic!("Expected Timeline command"),
+        }
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "timeline".to_string(),
+            "--style".to_string(),
+            "Braille".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::Timeline { style, .. } => assert_eq!(style.as_deref(), Some("braille")),
+            _ => panic!("Expected Timeline command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_timeline_numeric_shorthand() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "timeline".to_string(),
+            "--52".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::Timeline { weeks, color, .. } => {
+                assert_eq!(weeks, Some(52));
+                assert_eq!(color, ColorChoice::Auto);
+            }
+            _ => panic!("Expected Timeline command with numeric shorthand"),
+        }
 
-EXAMPLES:
-  git-insights stats
-  git-insights stats --by-email
-  git-insights json
-  git-insights user alice
+        let cli_hyphen = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "timeline".to_string(),
+            "-52".to_string(),
+        ])
+        .expect("parse");
+        match cli_hyphen.command {
+            Commands::Timeline { weeks, color, .. } => {
+                assert_eq!(weeks, Some(52));
+                assert_eq!(color, ColorChoice::Auto);
+            }
+            _ => panic!("Expected Timeline command with -NN shorthand"),
+        }
+    }
 
-See 'git-insights <COMMAND> --help' for command-specific options."
-            )
+    #[test]
+    fn test_cli_heatmap_weeks_and_color() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "heatmap".to_string(),
+            "--60".to_string(),
+            "--color".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::Heatmap { weeks, color, .. } => {
+                assert_eq!(weeks, Some(60));
+                assert_eq!(color, ColorChoice::Always);
+            }
+            _ => panic!("Expected Heatmap with weeks+color"),
         }
-        HelpTopic::Stats => {
-            "\
-git-insights stats
 
-Compute repository stats using a gitfame-like method:
-- Surviving LOC via git blame --line-porcelain HEAD
-- Commits via git shortlog -s -e HEAD
-- Only text files considered (git grep -I --name-only . HEAD AND ls-files)
-- Clean git commands (no pager), no dependencies
+        let cli_hyphen = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "heatmap".to_string(),
+            "-60".to_string(),
+        ])
+        .expect("parse");
+        match cli_hyphen.command {
+            Commands::Heatmap { weeks, color, .. } => {
+                assert_eq!(weeks, Some(60));
+                assert_eq!(color, ColorChoice::Auto);
+            }
+            _ => panic!("Expected Heatmap with -NN shorthand"),
+        }
+    }
 
-USAGE:
-  git-insights stats [OPTIONS]
+    #[test]
+    fn test_cli_code_frequency_defaults_and_flags() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "code-frequency".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::CodeFrequency {
+                group,
+                heatmap,
+                weeks,
+                color,
+                table,
+                ..
+            } => {
+                assert!(group.is_none());
+                assert!(heatmap.is_none());
+                assert!(weeks.is_none());
+                assert_eq!(color, ColorChoice::Auto);
+                assert!(!table);
+            }
+            _ => panic!("Expected CodeFrequency"),
+        }
 
-OPTIONS:
-  -e, --by-email  Group by \"Name <email>\" (default groups by name only)
-  -h, --help      Show this help
+        let cli2 = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "code-frequency".to_string(),
+            "--group=dom".to_string(),
+            "--heatmap".to_string(),
+            "dow-hod".to_string(),
+            "--weeks=26".to_string(),
+            "--no-color".to_string(),
+            "--table".to_string(),
+        ])
+        .expect("parse");
+        match cli2.command {
+            Commands::CodeFrequency {
+                group,
+                heatmap,
+                weeks,
+                color,
+                table,
+                ..
+            } => {
+                assert_eq!(group.as_deref(), Some("dom"));
+                assert_eq!(heatmap.as_deref(), Some("dow-hod"));
+                assert_eq!(weeks, Some(26));
+                assert_eq!(color, ColorChoice::Never);
+                assert!(table);
+            }
+            _ => panic!("Expected CodeFrequency with flags"),
+        }
 
-EXAMPLES:
-  git-insights stats
-  git-insights stats --by-email"
-                .to_string()
+        let cli3 = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "code-frequency".to_string(),
+            "--52".to_string(),
+        ])
+        .expect("parse");
+        match cli3.command {
+            Commands::CodeFrequency {
+                group,
+                heatmap,
+                weeks,
+                color,
+                table,
+                ..
+            } => {
+                assert!(group.is_none());
+                assert!(heatmap.is_none());
+                assert_eq!(weeks, Some(52));
+                assert_eq!(color, ColorChoice::Auto);
+                assert!(!table);
+            }
+            _ => panic!("Expected CodeFrequency with shorthand weeks"),
+        }
+    }
+
+    #[test]
+    fn test_cli_timeline_capacity() {
+        let args = vec![
+            "git-insights".to_string(),
+            "timeline".to_string(),
+            "--capacity".to_string(),
+            "cap.csv".to_string(),
+            "-12".to_string(),
+        ];
+        match Cli::parse_from_args(args).unwrap().command {
+            Commands::Timeline {
+                weeks, capacity, ..
+            } => {
+                assert_eq!(weeks, Some(12));
+                assert_eq!(capacity.as_deref(), Some("cap.csv"));
+            }
+            _ => panic!("Expected Timeline"),
+        }
+
+        let args = vec![
+            "git-insights".to_string(),
+            "timeline".to_string(),
+            "--capacity=team.csv".to_string(),
+        ];
+        match Cli::parse_from_args(args).unwrap().command {
+            Commands::Timeline { capacity, .. } => {
+                assert_eq!(capacity.as_deref(), Some("team.csv"))
+            }
+            _ => panic!("Expected Timeline"),
+        }
+    }
+
+    #[test]
+    fn test_cli_svg_output_flags() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "heatmap".to_string(),
+            "--output".to_string(),
+            "cal.svg".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::Heatmap { output, .. } => assert_eq!(output.as_deref(), Some("cal.svg")),
+            _ => panic!("Expected Heatmap with output"),
+        }
+
+        let cli2 = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "code-frequency".to_string(),
+            "--heatmap=dow-hod".to_string(),
+            "--output=Freq.SVG".to_string(),
+        ])
+        .expect("parse");
+        match cli2.command {
+            Commands::CodeFrequency {
+                heatmap, output, ..
+            } => {
+                assert_eq!(heatmap.as_deref(), Some("dow-hod"));
+                assert_eq!(output.as_deref(), Some("Freq.SVG"));
+            }
+            _ => panic!("Expected CodeFrequency with output"),
+        }
+
+        let cli3 = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "timeline".to_string(),
+            "-o".to_string(),
+            "weeks.png".to_string(),
+            "--52".to_string(),
+        ])
+        .expect("parse");
+        match cli3.command {
+            Commands::Timeline { output, weeks, .. } => {
+                assert_eq!(output.as_deref(), Some("weeks.png"));
+                assert_eq!(weeks, Some(52));
+            }
+            _ => panic!("Expected Timeline with output"),
+        }
+        let err = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "timeline".to_string(),
+            "--output=t.png".to_string(),
+            "--style".to_string(),
+            "braille".to_string(),
+        ])
+        .expect_err("style with output");
+        assert!(err.contains("--output cannot be combined"));
+    }
+
+    #[test]
+    fn test_cli_reachable_from() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "timeline".to_string(),
+            "--reachable-from".to_string(),
+            "v*".to_string(),
+            "--12".to_string(),
+        ])
+        .expect("parse");
+        assert_eq!(cli.filter.reachable_from.as_deref(), Some("v*"));
+        match cli.command {
+            Commands::Timeline { weeks, .. } => assert_eq!(weeks, Some(12)),
+            _ => panic!("Expected Timeline"),
+        }
+
+        let cli2 = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "stats".to_string(),
+            "--reachable-from=release-*".to_string(),
+        ])
+        .expect("parse");
+        assert_eq!(cli2.filter.reachable_from.as_deref(), Some("release-*"));
+
+        let err = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "stats".to_string(),
+            "--reachable-from".to_string(),
+        ])
+        .expect_err("missing glob");
+        assert!(err.contains("requires a tag glob"));
+    }
+
+    #[test]
+    fn test_cli_report_format() {
+        let cli = Cli::parse_from_args(vec!["git-insights".to_string(), "report".to_string()])
+            .expect("parse");
+        match cli.command {
+            Commands::Report { format, .. } => assert!(format.is_none()),
+            _ => panic!("Expected Report"),
+        }
+
+        let cli2 = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "report".to_string(),
+            "--format=HTML".to_string(),
+        ])
+        .expect("parse");
+        match cli2.command {
+            Commands::Report { format, .. } => assert_eq!(format.as_deref(), Some("html")),
+            _ => panic!("Expected Report with format"),
+        }
+    }
+
+    #[test]
+    fn test_cli_export_prometheus() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "export".to_string(),
+            "--prometheus".to_string(),
+            "git.prom".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::Export { prometheus, .. } => {
+                assert_eq!(prometheus.as_deref(), Some("git.prom"))
+            }
+            _ => panic!("Expected Export"),
+        }
+
+        let cli2 = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "export".to_string(),
+            "--help".to_string(),
+        ])
+        .expect("parse");
+        assert!(matches!(
+            cli2.command,
+            Commands::Help {
+                topic: HelpTopic::Export
+            }
+        ));
+    }
+
+    #[test]
+    fn test_cli_export_xlsx() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "export".to_string(),
+            "--xlsx=insights.xlsx".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::Export { prometheus, xlsx } => {
+                assert_eq!(prometheus, None);
+                assert_eq!(xlsx.as_deref(), Some("insights.xlsx"));
+            }
+            _ => panic!("Expected Export"),
         }
-        HelpTopic::Json => {
-            "\
-git-insights json
+    }
 
-Export stats to a JSON file (git-insights.json) mapping:
-  author -> { loc, commits, files[] }
+    #[test]
+    fn test_cli_view() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "view".to_string(),
+            "--format=HTML".to_string(),
+            "data.json".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::View { path, format, .. } => {
+                assert_eq!(path, "data.json");
+                assert_eq!(format.as_deref(), Some("html"));
+            }
+            _ => panic!("Expected View"),
+        }
 
-USAGE:
-  git-insights json
+        let err = Cli::parse_from_args(vec!["git-insights".to_string(), "view".to_string()])
+            .expect_err("file required");
+        assert!(err.starts_with("Usage: git-insights view"));
+    }
 
-EXAMPLES:
-  git-insights json"
-                .to_string()
+    #[test]
+    fn test_cli_badge_metric() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "badge".to_string(),
+            "--metric".to_string(),
+            "Bus-Factor".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::Badge { metric } => assert_eq!(metric.as_deref(), Some("bus-factor")),
+            _ => panic!("Expected Badge"),
         }
-        HelpTopic::User => {
-            "\
-git-insights user
-
-Show insights for a specific user.
+    }
 
-Default behavior:
-- Merged pull request count (via commit message heuristics)
-- Tags where the user authored commits
+    #[test]
+    fn test_cli_text_detection() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "stats".to_string(),
+            "--text-detection".to_string(),
+            "sniff".to_string(),
+        ])
+        .expect("parse");
+        assert_eq!(cli.text_detection, TextDetection::Sniff);
 
-Ownership mode (per-file \"ownership\" list):
-- Computes surviving LOC per file attributed to this user at HEAD via blame
-- Shows file path, user LOC, file LOC, and ownership percentage
+        let err = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "stats".to_string(),
+            "--text-detection=magic".to_string(),
+        ])
+        .expect_err("unknown mode");
+        assert!(err.contains("unknown --text-detection"));
+    }
 
-USAGE:
-  git-insights user <username> [--ownership] [--by-email|-e] [--top N] [--sort loc|pct]
+    #[test]
+    fn test_cli_shared_output_flag() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "json".to_string(),
+            "-o".to_string(),
+            "-".to_string(),
+        ])
+        .expect("parse");
+        assert!(matches!(cli.command, Commands::Json { output: Some(ref o) } if o == "-"));
 
-OPTIONS:
-  --ownership       Show per-file ownership table for this user
-  -e, --by-email    Match by email (author-mail) instead of author name
-  --top N           Limit to top N rows (default: 10)
-  --sort loc|pct    Sort by user LOC (loc, default) or percentage (pct)
-  -h, --help        Show this help
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "view".to_string(),
+            "--output".to_string(),
+            "out.html".to_string(),
+            "data.json".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::View { path, output, .. } => {
+                assert_eq!(path, "data.json");
+                assert_eq!(output.as_deref(), Some("out.html"));
+            }
+            _ => panic!("Expected View"),
+        }
 
-EXAMPLES:
-  git-insights user alice
-  git-insights user alice --ownership
-  git-insights user \"alice@example.com\" --ownership --by-email --top 5 --sort pct"
-                .to_string()
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "report".to_string(),
+            "--output=site/index.html".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::Report { output, .. } => {
+                assert_eq!(output.as_deref(), Some("site/index.html"))
+            }
+            _ => panic!("Expected Report"),
         }
-        HelpTopic::Timeline => {
-            "\
-git-insights timeline
+    }
 
-Show weekly commit activity as a multi-row sparkline (ASCII/Unicode).
-Color output is ON by default; use --no-color to disable.
+    #[test]
+    fn test_cli_author_scope_flags() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "timeline".to_string(),
+            "--only-me".to_string(),
+        ])
+        .expect("parse");
+        assert_eq!(cli.filter.author_scope, AuthorScope::OnlyMe);
 
-USAGE:
-  git-insights timeline [--weeks N|--NN|-NN] [--no-color] [-c|--color]
+        let err = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "stats".to_string(),
+            "--only-me".to_string(),
+            "--exclude-me".to_string(),
+        ])
+        .expect_err("conflicting scopes");
+        assert!(err.contains("cannot be combined"));
+    }
 
-OPTIONS:
-  --weeks N     Number of weeks to display (default: 26). Shorthand: --52 or -52
-  -c, --color   Force ANSI colors (default: ON)
-  --no-color    Disable ANSI colors
-  -h, --help    Show this help
+    #[test]
+    fn test_cli_log_json() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "log-json".to_string(),
+            "--output=-".to_string(),
+        ])
+        .expect("parse");
+        assert!(matches!(cli.command, Commands::LogJson { output: Some(ref o) } if o == "-"));
+    }
 
-EXAMPLES:
-  git-insights timeline
-  git-insights timeline --weeks 12
-  git-insights timeline --52
-  git-insights timeline -52 --no-color"
-                .to_string()
+    #[test]
+    fn test_cli_hotspots() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "hotspots".to_string(),
+            "--top=5".to_string(),
+            "--size".to_string(),
+            "Indent".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::Hotspots { top, size } => {
+                assert_eq!(top, Some(5));
+                assert_eq!(size.as_deref(), Some("indent"));
+            }
+            _ => panic!("Expected Hotspots"),
         }
-        HelpTopic::Heatmap => {
-            "\
-git-insights heatmap
+    }
 
-Show a UTC commit heatmap (weekday x hour).
-Color output is ON by default; use --no-color to disable.
+    #[test]
+    fn test_cli_interactive() {
+        let cli = Cli::parse_from_args(vec!["git-insights".to_string(), "interactive".to_string()])
+            .expect("parse");
+        assert!(matches!(cli.command, Commands::Interactive));
+    }
 
-USAGE:
-  git-insights heatmap [--weeks N|--NN|-NN] [--no-color] [-c|--color]
+    #[test]
+    fn test_cli_codeowners_check() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "codeowners-check".to_string(),
+            "--min-pct=25".to_string(),
+            "--stale-days".to_string(),
+            "0".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::CodeownersCheck {
+                file,
+                min_pct,
+                stale_days,
+            } => {
+                assert!(file.is_none());
+                assert_eq!(min_pct, Some(25.0));
+                assert_eq!(stale_days, Some(0));
+            }
+            _ => panic!("Expected CodeownersCheck"),
+        }
+    }
 
-OPTIONS:
-  --weeks N     Limit to the last N weeks (default: all history). Shorthand: --60 or -60
-  -c, --color   Force ANSI colors (default: ON)
-  --no-color    Disable ANSI colors
-  -h, --help    Show this help
+    #[test]
+    fn test_cli_merge_lines() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "stats".to_string(),
+            "--merge-lines=Synthetic".to_string(),
+        ])
+        .expect("parse");
+        assert_eq!(cli.filter.merge_lines, MergeLines::Synthetic);
+        assert!(Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "stats".to_string(),
+            "--merge-lines".to_string(),
+            "octopus".to_string(),
+        ])
+        .is_err());
+    }
 
-EXAMPLES:
-  git-insights heatmap
-  git-insights heatmap --60
-  git-insights heatmap -60 --no-color"
-                .to_string()
-        }
-        HelpTopic::CodeFrequency => {
-            "\
-git-insights code-frequency
+    #[test]
+    fn test_cli_blame_opts() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "stats".to_string(),
+            "--blame-opts=-w -C -C".to_string(),
+        ])
+        .expect("parse");
+        assert_eq!(cli.filter.blame.args(), ["-w", "-C", "-C"]);
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "ownership-matrix".to_string(),
+            "--blame-opts".to_string(),
+            "none".to_string(),
+        ])
+        .expect("parse");
+        assert!(cli.filter.blame.args().is_empty());
+        assert!(Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "stats".to_string(),
+            "--blame-opts=w,C,C,C,C".to_string(),
+        ])
+        .is_err());
+    }
 
-Show code-frequency histograms and heatmaps derived from commit timestamps (UTC), no dependencies.
+    #[test]
+    fn test_cli_ignore_rev() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "stats".to_string(),
+            "--ignore-rev".to_string(),
+            "abc123".to_string(),
+            "--ignore-rev=def456".to_string(),
+            "--no-ignore-revs-file".to_string(),
+        ])
+        .expect("parse");
+        assert_eq!(cli.filter.ignore_revs, ["abc123", "def456"]);
+        assert!(cli.filter.skip_ignore_revs_file);
+        assert!(Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "stats".to_string(),
+            "--ignore-rev".to_string(),
+        ])
+        .is_err());
+    }
 
-Notes:
-- UTC: Hour-of-day and day-of-week are computed in UTC.
-- Windowing: When --weeks is provided, the window aligns to the end of the current week (Sun..Sat),
-  mirroring 'timeline' and 'heatmap' behavior.
-- Style/Color: Uses the same ASCII/ANSI ramps and color levels as other visualizations.
-  Disable colors with --no-color.
+    #[test]
+    fn test_cli_messages() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "messages".to_string(),
+            "--by=Month".to_string(),
+        ])
+        .expect("parse");
+        assert!(
+            matches!(cli.command, Commands::Messages { by: Some(ref b), keywords: false, .. } if b == "month")
+        );
+        let cli = Cli::parse_from_args(
+            [
+                "git-insights",
+                "messages",
+                "--keywords",
+                "--top",
+                "5",
+                "--under=72",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+        )
+        .expect("parse");
+        match cli.command {
+            Commands::Messages {
+                keywords,
+                top,
+                under,
+                ..
+            } => assert_eq!((keywords, top, under), (true, Some(5), Some(72))),
+            _ => panic!("Expected Messages"),
+        }
+        assert!(Cli::parse_from_args(
+            ["git-insights", "messages", "--top", "5"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect()
+        )
+        .is_err());
+    }
 
-Groupings and views:
-- Histograms: --group hod|dow|dom
-    hod = hour-of-day (00..23)
-    dow = day-of-week (Sun..Sat)
-    dom = day-of-month (01..31)
-- Heatmaps: --heatmap dow-hod|dom-hod
-    dow-hod = day-of-week x hour-of-day (7x24)
-    dom-hod = day-of-month x hour-of-day (31x24)
+    #[test]
+    fn test_cli_daemon() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "daemon".to_string(),
+            "--interval".to_string(),
+            "30m".to_string(),
+            "--repos-file=repos.txt".to_string(),
+            "--export-dir".to_string(),
+            "out/".to_string(),
+            "--notify=https://hooks.example.com/x".to_string(),
+            "--once".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::Daemon {
+                interval,
+                repos_file,
+                export_dir,
+                analyses,
+                notify,
+                once,
+            } => {
+                assert_eq!(interval.as_deref(), Some("30m"));
+                assert_eq!(repos_file.as_deref(), Some("repos.txt"));
+                assert_eq!(export_dir.as_deref(), Some("out/"));
+                assert!(analyses.is_none());
+                assert_eq!(notify.as_deref(), Some("https://hooks.example.com/x"));
+                assert!(once);
+            }
+            _ => panic!("Expected Daemon"),
+        }
+    }
 
-USAGE:
-  git-insights code-frequency [--group X | --heatmap Y] [--weeks N|--NN|-NN] [--no-color] [-c|--color]
+    #[test]
+    fn test_cli_owners() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "owners".to_string(),
+            "--file".to_string(),
+            "src/main.rs".to_string(),
+            "--line=42".to_string(),
+            "--format".to_string(),
+            "JSON".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::Owners {
+                file,
+                line,
+                format,
+                top,
+            } => {
+                assert_eq!(file.as_deref(), Some("src/main.rs"));
+                assert_eq!(line, Some(42));
+                assert_eq!(format.as_deref(), Some("json"));
+                assert!(top.is_none());
+            }
+            _ => panic!("Expected Owners"),
+        }
+    }
 
-OPTIONS:
-  --group X       Histogram grouping: hod|dow|dom (default: hod if no --heatmap)
-  --heatmap Y     Heatmap kind: dow-hod|dom-hod
-  --weeks N       Limit to the last N weeks (default: all history). Shorthand: --52 or -52
-  -c, --color     Force ANSI colors (default: ON)
-  --no-color      Disable ANSI colors
-  --table         Render numeric table instead of shaded chart (heatmaps and histograms)
-  -h, --help      Show this help
+    #[test]
+    fn test_cli_releases() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "releases".to_string(),
+            "--tags=v*".to_string(),
+            "--top".to_string(),
+            "5".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::Releases { tags, top } => {
+                assert_eq!(tags.as_deref(), Some("v*"));
+                assert_eq!(top, Some(5));
+            }
+            _ => panic!("Expected Releases"),
+        }
+    }
 
-EXAMPLES:
-  git-insights code-frequency
-  git-insights code-frequency --group dow
-  git-insights code-frequency --heatmap dow-hod --weeks 26
-  git-insights code-frequency --heatmap dom-hod -26 --no-color"
-                .to_string()
+    #[test]
+    fn test_cli_branches() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "branches".to_string(),
+            "--base".to_string(),
+            "origin/develop".to_string(),
+            "--stale-days=30".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::Branches { base, stale_days } => {
+                assert_eq!(base.as_deref(), Some("origin/develop"));
+                assert_eq!(stale_days, Some(30));
+            }
+            _ => panic!("Expected Branches"),
+        }
+    }
+
+    #[test]
+    fn test_cli_chart_size_flags() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "timeline".to_string(),
+            "--height".to_string(),
+            "12".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::Timeline { height, .. } => assert_eq!(height, Some(12)),
+            _ => panic!("Expected Timeline"),
+        }
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "code-frequency".to_string(),
+            "--width=80".to_string(),
+            "--table".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::CodeFrequency { width, table, .. } => {
+                assert_eq!(width, Some(80));
+                assert!(table);
+            }
+            _ => panic!("Expected CodeFrequency"),
         }
     }
-}
 
-pub fn channel_string() -> &'static str {
-    if cfg!(feature = "python") {
-        "pip"
-    } else {
-        "cargo"
+    #[test]
+    fn test_cli_normalize_names() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "stats".to_string(),
+            "--normalize-names".to_string(),
+            "NFC".to_string(),
+        ])
+        .expect("parse");
+        assert_eq!(cli.filter.normalize_names, NameNormalization::Nfc);
+        assert!(Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "stats".to_string(),
+            "--normalize-names=nfkd".to_string(),
+        ])
+        .is_err());
     }
-}
 
-pub fn version_string() -> String {
-    format!("{} ({})", env!("CARGO_PKG_VERSION"), channel_string())
-}
+    #[test]
+    fn test_cli_tz() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "heatmap".to_string(),
+            "--tz".to_string(),
+            "Author".to_string(),
+        ])
+        .expect("parse");
+        assert_eq!(cli.filter.time_zone, TimeZoneMode::Author);
+        assert!(Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "heatmap".to_string(),
+            "--tz=pst".to_string(),
+        ])
+        .is_err());
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_cli_file() {
+        let parse = |v: &[&str]| {
+            let mut args = vec!["git-insights".to_string()];
+            args.extend(v.iter().map(|s| s.to_string()));
+            Cli::parse_from_args(args)
+        };
+        match parse(&["file", "src/", "--format=JSON", "--top", "0"])
+            .expect("parse")
+            .command
+        {
+            Commands::File { path, format, top } => {
+                assert_eq!(path, "src/");
+                assert_eq!(format.as_deref(), Some("json"));
+                assert_eq!(top, Some(0));
+            }
+            _ => panic!("Expected File"),
+        }
+        assert!(parse(&["file"]).is_err());
+        assert!(parse(&["file", "--top", "3"]).is_err());
+        assert!(parse(&["file", "a.rs", "b.rs"]).is_err());
+    }
 
     #[test]
-    fn test_cli_stats_default_by_name() {
-        let cli = Cli::parse_from_args(vec!["git-insights".to_string(), "stats".to_string()])
-            .expect("Failed to parse args");
+    fn test_cli_classes() {
+        let parse = |v: &[&str]| {
+            let mut args = vec!["git-insights".to_string()];
+            args.extend(v.iter().map(|s| s.to_string()));
+            Cli::parse_from_args(args)
+        };
+        let cli =
+            parse(&["classes", "--class", "tests=tests/", "--class=docs=*.md"]).expect("parse");
         match cli.command {
-            Commands::Stats { by_name } => assert!(by_name),
-            _ => panic!("Expected Stats command"),
+            Commands::Classes { classes } => assert_eq!(
+                classes,
+                vec![
+                    ("tests".to_string(), "tests/".to_string()),
+                    ("docs".to_string(), "*.md".to_string())
+                ]
+            ),
+            _ => panic!("Expected Classes"),
         }
+        assert!(parse(&["classes", "--class", "tests"]).is_err());
+        assert!(parse(&["classes", "--top", "3"]).is_err());
     }
 
     #[test]
-    fn test_cli_stats_by_email_flag() {
+    fn test_cli_languages() {
         let cli = Cli::parse_from_args(vec![
             "git-insights".to_string(),
-            "stats".to_string(),
-            "--by-email".to_string(),
+            "languages".to_string(),
+            "--top=5".to_string(),
         ])
-        .expect("Failed to parse args");
+        .expect("parse");
         match cli.command {
-            Commands::Stats { by_name } => assert!(!by_name),
-            _ => panic!("Expected Stats command"),
+            Commands::Languages { top } => assert_eq!(top, Some(5)),
+            _ => panic!("Expected Languages"),
         }
     }
 
     #[test]
-    fn test_cli_stats_short_e_flag() {
+    fn test_cli_risk() {
         let cli = Cli::parse_from_args(vec![
             "git-insights".to_string(),
-            "stats".to_string(),
-            "-e".to_string(),
+            "risk".to_string(),
+            "--weights".to_string(),
+            "Churn=2,size=0".to_string(),
+            "--format=json".to_string(),
         ])
-        .expect("Failed to parse args");
+        .expect("parse");
         match cli.command {
-            Commands::Stats { by_name } => assert!(!by_name),
-            _ => panic!("Expected Stats command"),
+            Commands::Risk {
+                weights,
+                top,
+                format,
+            } => {
+                assert_eq!(weights.as_deref(), Some("churn=2,size=0"));
+                assert!(top.is_none());
+                assert_eq!(format.as_deref(), Some("json"));
+            }
+            _ => panic!("Expected Risk"),
         }
     }
 
     #[test]
-    fn test_cli_json() {
-        let cli = Cli::parse_from_args(vec!["git-insights".to_string(), "json".to_string()])
-            .expect("Failed to parse args");
-        assert!(matches!(cli.command, Commands::Json));
+    fn test_cli_time_audit_and_exclude_bad_times() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "time-audit".to_string(),
+            "--max-skew=6".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::TimeAudit { max_skew_hours } => assert_eq!(max_skew_hours, Some(6)),
+            _ => panic!("Expected TimeAudit"),
+        }
+
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "timeline".to_string(),
+            "--exclude-bad-times".to_string(),
+        ])
+        .expect("parse");
+        assert!(cli.filter.exclude_bad_times);
     }
 
     #[test]
-    fn test_cli_user() {
+    fn test_cli_stats_incremental() {
         let cli = Cli::parse_from_args(vec![
             "git-insights".to_string(),
-            "user".to_string(),
-            "testuser".to_string(),
+            "stats".to_string(),
+            "--incremental".to_string(),
         ])
-        .expect("Failed to parse args");
+        .expect("parse");
         match cli.command {
-            Commands::User {
-                username,
-                ownership,
-                by_email,
-                top,
-                sort,
-            } => {
-                assert_eq!(username, "testuser");
-                assert!(!ownership);
-                assert!(!by_email);
-                assert!(top.is_none());
-                assert!(sort.is_none());
-            }
-            _ => panic!("Expected User command"),
+            Commands::Stats {
+                by_name,
+                incremental,
+                ..
+            } => assert!(by_name && incremental),
+            _ => panic!("Expected Stats command"),
         }
     }
 
     #[test]
-    fn test_cli_user_ownership_flags() {
+    fn test_cli_encoding_audit() {
         let cli = Cli::parse_from_args(vec![
             "git-insights".to_string(),
-            "user".to_string(),
-            "palash".to_string(),
-            "--ownership".to_string(),
-            "--by-email".to_string(),
-            "--top".to_string(),
-            "5".to_string(),
+            "encoding-audit".to_string(),
+        ])
+        .expect("parse");
+        assert!(matches!(cli.command, Commands::EncodingAudit));
+    }
+
+    #[test]
+    fn test_cli_contributors() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "contributors".to_string(),
             "--sort".to_string(),
-            "pct".to_string(),
+            "Last".to_string(),
         ])
-        .expect("Failed to parse args");
+        .expect("parse");
         match cli.command {
-            Commands::User {
-                username,
-                ownership,
-                by_email,
-                top,
-                sort,
-            } => {
-                assert_eq!(username, "palash");
-                assert!(ownership);
-                assert!(by_email);
-                assert_eq!(top, Some(5));
-                assert_eq!(sort.as_deref(), Some("pct"));
-            }
-            _ => panic!("Expected User command with ownership flags"),
+            Commands::Contributors { sort } => assert_eq!(sort.as_deref(), Some("last")),
+            _ => panic!("Expected Contributors"),
         }
+    }
 
-        let cli2 = Cli::parse_from_args(vec![
+    #[test]
+    fn test_cli_timeline_bursts() {
+        let cli = Cli::parse_from_args(vec![
             "git-insights".to_string(),
-            "user".to_string(),
-            "palash".to_string(),
-            "--ownership".to_string(),
-            "-e".to_string(),
-            "--top=3".to_string(),
-            "--sort=loc".to_string(),
+            "timeline".to_string(),
+            "--bursts=2.5".to_string(),
+            "--burst-commits".to_string(),
+            "3".to_string(),
         ])
-        .expect("Failed to parse args");
-        match cli2.command {
-            Commands::User {
-                username,
-                ownership,
-                by_email,
-                top,
-                sort,
+        .expect("parse");
+        match cli.command {
+            Commands::Timeline {
+                bursts,
+                burst_commits,
+                ..
             } => {
-                assert_eq!(username, "palash");
-                assert!(ownership);
-                assert!(by_email);
-                assert_eq!(top, Some(3));
-                assert_eq!(sort.as_deref(), Some("loc"));
+                assert_eq!(bursts, Some(2.5));
+                assert_eq!(burst_commits, Some(3));
             }
-            _ => panic!("Expected User command with equals-style flags"),
+            _ => panic!("Expected Timeline"),
         }
     }
 
     #[test]
-    fn test_cli_no_args_yields_help() {
-        let cli = Cli::parse_from_args(vec!["git-insights".to_string()]).expect("parse");
+    fn test_cli_growth() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "growth".to_string(),
+            "--format=JSON".to_string(),
+            "--no-color".to_string(),
+        ])
+        .expect("parse");
         match cli.command {
-            Commands::Help { topic } => match topic {
-                HelpTopic::Top => {}
-                _ => panic!("Expected top-level help"),
-            },
-            _ => panic!("Expected Help command"),
+            Commands::Growth { color, format, .. } => {
+                assert_eq!(color, ColorChoice::Never);
+                assert_eq!(format.as_deref(), Some("json"));
+            }
+            _ => panic!("Expected Growth"),
         }
     }
 
     #[test]
-    fn test_cli_top_help_flag() {
-        let cli = Cli::parse_from_args(vec!["git-insights".to_string(), "--help".to_string()])
+    fn test_cli_quality() {
+        let cli = Cli::parse_from_args(vec!["git-insights".to_string(), "quality".to_string()])
             .expect("parse");
-        match cli.command {
-            Commands::Help { topic } => match topic {
-                HelpTopic::Top => {}
-                _ => panic!("Expected top-level help"),
-            },
-            _ => panic!("Expected Help command"),
-        }
+        assert!(matches!(cli.command, Commands::Quality));
+        let cli = Cli::parse_from_args(vec!["git-insights".to_string(), "signing".to_string()])
+            .expect("parse");
+        assert!(matches!(cli.command, Commands::Signing));
+        let cli = Cli::parse_from_args(
+            [
+                "git-insights",
+                "churn",
+                "--by-ext",
+                "--weeks=4",
+                "--top",
+                "5",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+        )
+        .expect("parse");
+        assert!(matches!(
+            cli.command,
+            Commands::Churn {
+                weeks: Some(4),
+                by_ext: true,
+                top: Some(5)
+            }
+        ));
+        assert!(Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "quality".to_string(),
+            "--top".to_string(),
+            "3".to_string(),
+        ])
+        .is_err());
     }
 
     #[test]
-    fn test_cli_stats_help_flag() {
+    fn test_cli_ownership_by_tenure() {
         let cli = Cli::parse_from_args(vec![
             "git-insights".to_string(),
-            "stats".to_string(),
-            "--help".to_string(),
+            "ownership-by-tenure".to_string(),
         ])
         .expect("parse");
+        assert!(matches!(cli.command, Commands::OwnershipByTenure));
+    }
+
+    #[test]
+    fn test_cli_ownership_history() {
+        let cli = Cli::parse_from_args(
+            [
+                "git-insights",
+                "ownership-history",
+                "ann@x.io",
+                "-e",
+                "--samples=8",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+        )
+        .expect("parse");
         match cli.command {
-            Commands::Help { topic } => match topic {
-                HelpTopic::Stats => {}
-                _ => panic!("Expected stats help"),
-            },
-            _ => panic!("Expected Help command"),
+            Commands::OwnershipHistory {
+                username,
+                by_email,
+                samples,
+                ..
+            } => {
+                assert_eq!(username, "ann@x.io");
+                assert!(by_email);
+                assert_eq!(samples, Some(8));
+            }
+            _ => panic!("expected ownership-history"),
         }
+        assert!(Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "ownership-history".to_string(),
+        ])
+        .is_err());
     }
 
     #[test]
-    fn test_cli_version_flag() {
-        let cli = Cli::parse_from_args(vec!["git-insights".to_string(), "--version".to_string()])
-            .expect("parse");
-        assert!(matches!(cli.command, Commands::Version));
+    fn test_cli_stdin_commits() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "messages".to_string(),
+            "--stdin-commits".to_string(),
+        ])
+        .expect("parse");
+        assert_eq!(cli.filter.only_commits, Some(Vec::new()));
+
+        let err = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "stats".to_string(),
+            "--stdin-commits".to_string(),
+            "--reachable-from=v*".to_string(),
+        ])
+        .expect_err("conflicting selections");
+        assert!(err.contains("cannot be combined"));
     }
 
     #[test]
-    fn test_version_string_includes_channel_and_version() {
-        let v = super::version_string();
-        assert!(v.ends_with(" (cargo)"));
-        assert!(v.starts_with(env!("CARGO_PKG_VERSION")));
+    fn test_cli_velocity() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "velocity".to_string(),
+            "--iteration".to_string(),
+            "3w".to_string(),
+            "--start=2024-01-08".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::Velocity {
+                iteration,
+                start,
+                color,
+            } => {
+                assert_eq!(iteration.as_deref(), Some("3w"));
+                assert_eq!(start.as_deref(), Some("2024-01-08"));
+                assert_eq!(color, ColorChoice::Auto);
+            }
+            _ => panic!("Expected Velocity"),
+        }
     }
 
     #[test]
-    fn test_cli_unknown_command() {
-        let err = Cli::parse_from_args(vec!["git-insights".to_string(), "invalid".to_string()])
-            .expect_err("Expected an error for unknown command");
-        assert!(err.contains("Unknown command: invalid"));
+    fn test_cli_code_frequency_per_author() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "code-frequency".to_string(),
+            "--heatmap".to_string(),
+            "dow-hod".to_string(),
+            "--per-author".to_string(),
+            "--top=4".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::CodeFrequency {
+                heatmap,
+                per_author,
+                top,
+                ..
+            } => {
+                assert_eq!(heatmap.as_deref(), Some("dow-hod"));
+                assert!(per_author);
+                assert_eq!(top, Some(4));
+            }
+            _ => panic!("Expected CodeFrequency"),
+        }
     }
 
     #[test]
-    fn test_cli_user_no_username() {
-        let err = Cli::parse_from_args(vec!["git-insights".to_string(), "user".to_string()])
-            .expect_err("Expected an error for user command without username");
-        assert_eq!(err, "Usage: git-insights user <username> [--ownership] [--by-email|-e] [--top N] [--sort loc|pct]");
+    fn test_cli_stats_max_rows_and_output() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "stats".to_string(),
+            "--max-rows=0".to_string(),
+            "-o".to_string(),
+            "authors.md".to_string(),
+        ])
+        .expect("parse");
+        match cli.command {
+            Commands::Stats {
+                max_rows, output, ..
+            } => {
+                assert_eq!(max_rows, Some(0));
+                assert_eq!(output.as_deref(), Some("authors.md"));
+            }
+            _ => panic!("Expected Stats command"),
+        }
     }
 
     #[test]
-    fn test_cli_timeline_default() {
-        let cli = Cli::parse_from_args(vec!["git-insights".to_string(), "timeline".to_string()])
-            .expect("parse");
+    fn test_cli_code_frequency_summary() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "code-frequency".to_string(),
+            "--summary".to_string(),
+            "--top=3".to_string(),
+        ])
+        .expect("parse");
         match cli.command {
-            Commands::Timeline { weeks, color } => {
-                assert!(weeks.is_none());
-                assert!(color);
+            Commands::CodeFrequency { summary, top, .. } => {
+                assert!(summary);
+                assert_eq!(top, Some(3));
             }
-            _ => panic!("Expected Timeline command"),
+            _ => panic!("Expected CodeFrequency"),
         }
     }
 
     #[test]
-    fn test_cli_timeline_weeks_flags() {
+    fn test_cli_ownership_matrix() {
         let cli = Cli::parse_from_args(vec![
             "git-insights".to_string(),
-            "timeline".to_string(),
-            "--weeks".to_string(),
-            "12".to_string(),
+            "ownership-matrix".to_string(),
+            "--min-pct".to_string(),
+            "40".to_string(),
+            "--format=JSON".to_string(),
+            "-o".to_string(),
+            "m.json".to_string(),
         ])
         .expect("parse");
         match cli.command {
-            Commands::Timeline { weeks, color } => {
-                assert_eq!(weeks, Some(12));
-                assert!(color);
+            Commands::OwnershipMatrix {
+                min_pct,
+                format,
+                by_name,
+                output,
+            } => {
+                assert_eq!(min_pct, Some(40.0));
+                assert_eq!(format.as_deref(), Some("json"));
+                assert!(by_name);
+                assert_eq!(output.as_deref(), Some("m.json"));
             }
-            _ => panic!("Expected Timeline command"),
+            _ => panic!("Expected OwnershipMatrix"),
         }
-        let cli2 = Cli::parse_from_args(vec![
+    }
+
+    #[test]
+    fn test_cli_compare() {
+        let cli = Cli::parse_from_args(vec![
             "git-insights".to_string(),
-            "timeline".to_string(),
-            "--weeks=8".to_string(),
+            "compare".to_string(),
+            "v1.0".to_string(),
+            "v2.0".to_string(),
+            "--no-color".to_string(),
+            "-e".to_string(),
         ])
         .expect("parse");
-        match cli2.command {
-            Commands::Timeline { weeks, color } => {
-                assert_eq!(weeks, Some(8));
-                assert!(color);
+        match cli.command {
+            Commands::Compare {
+                ref_a,
+                ref_b,
+                by_name,
+                color,
+            } => {
+                assert_eq!((ref_a.as_str(), ref_b.as_str()), ("v1.0", "v2.0"));
+                assert!(!by_name);
+                assert_eq!(color, ColorChoice::Never);
             }
-            _ => panic!("Expected Timeline command"),
+            _ => panic!("Expected Compare"),
         }
+        assert!(Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "compare".to_string(),
+            "v1.0".to_string(),
+        ])
+        .is_err());
     }
 
     #[test]
-    fn test_cli_heatmap() {
-        let cli = Cli::parse_from_args(vec!["git-insights".to_string(), "heatmap".to_string()])
-            .expect("parse");
+    fn test_cli_stats_compare_with() {
+        let cli = Cli::parse_from_args(vec![
+            "git-insights".to_string(),
+            "stats".to_string(),
+            "--compare-with".to_string(),
+            "2024-01-01..2024-03-31".to_string(),
+        ])
+        .expect("parse");
         match cli.command {
-            Commands::Heatmap { weeks, color } => {
-                assert!(weeks.is_none());
-                assert!(color);
+            Commands::Stats { compare_with, .. } => {
+                assert_eq!(compare_with.as_deref(), Some("2024-01-01..2024-03-31"));
             }
-            _ => panic!("Expected Heatmap"),
+            _ => panic!("Expected Stats command"),
         }
     }
 
     #[test]
-    fn test_cli_timeline_numeric_shorthand() {
+    fn test_cli_growth_loc() {
         let cli = Cli::parse_from_args(vec![
             "git-insights".to_string(),
-            "timeline".to_string(),
-            "--52".to_string(),
+            "growth".to_string(),
+            "--loc".to_string(),
+            "--samples=12".to_string(),
         ])
         .expect("parse");
         match cli.command {
-            Commands::Timeline { weeks, color } => {
-                assert_eq!(weeks, Some(52));
-                assert!(color);
+            Commands::Growth { loc, samples, .. } => {
+                assert!(loc);
+                assert_eq!(samples, Some(12));
             }
-            _ => panic!("Expected Timeline command with numeric shorthand"),
+            _ => panic!("Expected Growth"),
         }
+    }
 
-        let cli_hyphen = Cli::parse_from_args(vec![
+    #[test]
+    fn test_cli_timeline_metric() {
+        let cli = Cli::parse_from_args(vec![
             "git-insights".to_string(),
             "timeline".to_string(),
-            "-52".to_string(),
+            "--metric".to_string(),
+            "Contributors".to_string(),
+            "--52".to_string(),
         ])
         .expect("parse");
-        match cli_hyphen.command {
-            Commands::Timeline { weeks, color } => {
+        match cli.command {
+            Commands::Timeline { weeks, metric, .. } => {
                 assert_eq!(weeks, Some(52));
-                assert!(color);
+                assert_eq!(metric.as_deref(), Some("contributors"));
             }
-            _ => panic!("Expected Timeline command with -NN shorthand"),
+            _ => panic!("Expected Timeline"),
         }
     }
 
     #[test]
-    fn test_cli_heatmap_weeks_and_color() {
+    fn test_cli_survival() {
         let cli = Cli::parse_from_args(vec![
             "git-insights".to_string(),
-            "heatmap".to_string(),
-            "--60".to_string(),
-            "--color".to_string(),
+            "survival".to_string(),
+            "--samples".to_string(),
+            "9".to_string(),
         ])
         .expect("parse");
         match cli.command {
-            Commands::Heatmap { weeks, color } => {
-                assert_eq!(weeks, Some(60));
-                assert!(color);
-            }
-            _ => panic!("Expected Heatmap with weeks+color"),
+            Commands::Survival { samples } => assert_eq!(samples, Some(9)),
+            _ => panic!("Expected Survival"),
         }
+    }
 
-        let cli_hyphen = Cli::parse_from_args(vec![
+    #[test]
+    fn test_cli_top() {
+        let cli = Cli::parse_from_args(vec![
             "git-insights".to_string(),
-            "heatmap".to_string(),
-            "-60".to_string(),
+            "top".to_string(),
+            "--metric=Churn".to_string(),
+            "--limit".to_string(),
+            "5".to_string(),
+            "--no-color".to_string(),
         ])
         .expect("parse");
-        match cli_hyphen.command {
-            Commands::Heatmap { weeks, color } => {
-                assert_eq!(weeks, Some(60));
-                assert!(color);
+        match cli.command {
+            Commands::Top {
+                metric,
+                limit,
+                color,
+            } => {
+                assert_eq!(metric.as_deref(), Some("churn"));
+                assert_eq!(limit, Some(5));
+                assert_eq!(color, ColorChoice::Never);
             }
-            _ => panic!("Expected Heatmap with -NN shorthand"),
+            _ => panic!("Expected Top"),
         }
     }
 
     #[test]
-    fn test_cli_code_frequency_defaults_and_flags() {
+    fn test_cli_stats_fast() {
         let cli = Cli::parse_from_args(vec![
             "git-insights".to_string(),
-            "code-frequency".to_string(),
+            "stats".to_string(),
+            "--fast".to_string(),
         ])
         .expect("parse");
         match cli.command {
-            Commands::CodeFrequency {
-                group,
-                heatmap,
-                weeks,
-                color,
-                table,
+            Commands::Stats {
+                fast, incremental, ..
             } => {
-                assert!(group.is_none());
-                assert!(heatmap.is_none());
-                assert!(weeks.is_none());
-                assert!(color);
-                assert!(!table);
+                assert!(fast);
+                assert!(!incremental);
             }
-            _ => panic!("Expected CodeFrequency"),
+            _ => panic!("Expected Stats"),
         }
+    }
 
-        let cli2 = Cli::parse_from_args(vec![
-            "git-insights".to_string(),
-            "code-frequency".to_string(),
-            "--group=dom".to_string(),
-            "--heatmap".to_string(),
-            "dow-hod".to_string(),
-            "--weeks=26".to_string(),
-            "--no-color".to_string(),
-            "--table".to_string(),
-        ])
-        .expect("parse");
-        match cli2.command {
-            Commands::CodeFrequency {
-                group,
-                heatmap,
-                weeks,
-                color,
-                table,
+    #[test]
+    fn test_cli_stats_sort_and_top() {
+        let args = |extra: &[&str]| {
+            let mut v = vec!["git-insights".to_string(), "stats".to_string()];
+            v.extend(extra.iter().map(|s| s.to_string()));
+            v
+        };
+        let cli =
+            Cli::parse_from_args(args(&["--sort", "Commits", "--asc", "--top=5"])).expect("parse");
+        match cli.command {
+            Commands::Stats {
+                sort,
+                ascending,
+                top,
+                ..
             } => {
-                assert_eq!(group.as_deref(), Some("dom"));
-                assert_eq!(heatmap.as_deref(), Some("dow-hod"));
-                assert_eq!(weeks, Some(26));
-                assert!(!color);
-                assert!(table);
+                assert_eq!(sort.as_deref(), Some("commits"));
+                assert_eq!(ascending, Some(true));
+                assert_eq!(top, Some(5));
             }
-            _ => panic!("Expected CodeFrequency with flags"),
+            _ => panic!("Expected Stats"),
         }
+        match Cli::parse_from_args(args(&[])).expect("parse").command {
+            Commands::Stats {
+                sort,
+                ascending,
+                top,
+                ..
+            } => assert_eq!((sort, ascending, top), (None, None, None)),
+            _ => panic!("Expected Stats"),
+        }
+        assert!(Cli::parse_from_args(args(&["--asc", "--desc"])).is_err());
+        match Cli::parse_from_args(args(&["--by-domain"]))
+            .expect("parse")
+            .command
+        {
+            Commands::Stats {
+                by_name, by_domain, ..
+            } => assert!(by_domain && !by_name),
+            _ => panic!("Expected Stats"),
+        }
+        assert!(Cli::parse_from_args(args(&["--top", "3", "--compare-with", "a..b"])).is_err());
+    }
 
-        let cli3 = Cli::parse_from_args(vec![
-            "git-insights".to_string(),
-            "code-frequency".to_string(),
-            "--52".to_string(),
+    #[test]
+    fn test_cli_jobs() {
+        let args = |extra: &[&str]| {
+            let mut v = vec!["git-insights".to_string(), "stats".to_string()];
+            v.extend(extra.iter().map(|s| s.to_string()));
+            v
+        };
+        assert_eq!(Cli::parse_from_args(args(&[])).expect("parse").jobs, None);
+        assert_eq!(
+            Cli::parse_from_args(args(&["-j", "4"]))
+                .expect("parse")
+                .jobs,
+            Some(4)
+        );
+        assert_eq!(
+            Cli::parse_from_args(args(&["--jobs=2"]))
+                .expect("parse")
+                .jobs,
+            Some(2)
+        );
+        let err = Cli::parse_from_args(args(&["--jobs", "0"])).expect_err("zero jobs");
+        assert!(err.contains("invalid --jobs"));
+    }
+
+    #[test]
+    fn test_cli_strict_validation() {
+        let parse = |v: &[&str]| {
+            let mut args = vec!["git-insights".to_string()];
+            args.extend(v.iter().map(|s| s.to_string()));
+            Cli::parse_from_args(args)
+        };
+
+        let err = parse(&["stats", "--bogus"]).expect_err("unknown option");
+        assert!(err.starts_with("unknown option '--bogus' for 'stats'"));
+        assert!(err.contains("USAGE:\n  git-insights stats [OPTIONS]"));
+        assert!(err.ends_with("Run 'git-insights stats --help' for all options."));
+
+        let err = parse(&["user", "ann", "--top", "abc"]).expect_err("bad count");
+        assert!(err.starts_with("invalid value 'abc' for '--top': expected a whole number"));
+        let err = parse(&["timeline", "--bursts=high"]).expect_err("bad number");
+        assert!(err.starts_with("invalid value 'high' for '--bursts': expected a number"));
+        let err = parse(&["top", "--limit"]).expect_err("missing value");
+        assert!(err.starts_with("option '--limit' requires a value"));
+        let err = parse(&["survival", "--samples=3", "--no-color"]).expect_err("not for survival");
+        assert!(err.starts_with("unknown option '--no-color' for 'survival'"));
+        let err = parse(&["stats", "--fast=yes"]).expect_err("flag with value");
+        assert!(err.starts_with("option '--fast' does not take a value"));
+        let err = parse(&["compare", "v1", "v2", "v3"]).expect_err("extra argument");
+        assert!(err.starts_with("unexpected argument 'v3' for 'compare'"));
+        let err = parse(&["stats", "--sort", "bogus"]).expect_err("bad keyword");
+        assert!(err
+            .starts_with("invalid value 'bogus' for '--sort': expected loc, commits, files, name"));
+        assert!(err.contains("USAGE:\n  git-insights stats [OPTIONS]"));
+        let err = parse(&["owners", "--file", "a", "--format=xml"]).expect_err("bad format");
+        assert!(err.starts_with("invalid value 'xml' for '--format': expected text, json"));
+        assert!(parse(&["heatmap", "--normalize", "x"]).is_err());
+        assert!(parse(&["timeline", "--granularity", "year"]).is_err());
+        assert!(parse(&["stats", "--sort", "Commits"]).is_ok());
+
+        // Week shorthands, global options and help still parse.
+        assert!(parse(&["timeline", "-12", "--tz", "utc", "-j", "2"]).is_ok());
+        assert!(parse(&["code-frequency", "--8", "--top=3", "--no-color"]).is_ok());
+        assert!(parse(&["compare", "v1", "v2", "-e"]).is_ok());
+        assert!(matches!(
+            parse(&["stats", "--bogus", "--help"]).map(|c| c.command),
+            Ok(Commands::Help { .. })
+        ));
+    }
+
+    #[test]
+    fn test_cli_man() {
+        let args = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        match Cli::parse_from_args(args(&["git-insights", "man", "stats"]))
+            .expect("parse")
+            .command
+        {
+            Commands::Man { command, dir } => {
+                assert_eq!(command.as_deref(), Some("stats"));
+                assert_eq!(dir, None);
+            }
+            _ => panic!("Expected Man"),
+        }
+        match Cli::parse_from_args(args(&["git-insights", "man", "--dir=out/man1"]))
+            .expect("parse")
+            .command
+        {
+            Commands::Man { command, dir } => {
+                assert_eq!(command, None);
+                assert_eq!(dir.as_deref(), Some("out/man1"));
+            }
+            _ => panic!("Expected Man"),
+        }
+        assert!(matches!(help_topic("man"), Some(HelpTopic::Man)));
+    }
+
+    #[test]
+    fn test_cli_exclude_and_mailmap() {
+        let parse = |v: &[&str]| {
+            let mut args = vec!["git-insights".to_string()];
+            args.extend(v.iter().map(|s| s.to_string()));
+            Cli::parse_from_args(args)
+        };
+        let cli = parse(&[
+            "stats",
+            "--exclude",
+            "vendor/",
+            "--exclude=*.lock",
+            "--mailmap",
+            "people.mailmap",
         ])
         .expect("parse");
-        match cli3.command {
-            Commands::CodeFrequency {
-                group,
-                heatmap,
-                weeks,
-                color,
-                table,
-            } => {
-                assert!(group.is_none());
-                assert!(heatmap.is_none());
-                assert_eq!(weeks, Some(52));
-                assert!(color);
-                assert!(!table);
+        assert_eq!(cli.filter.exclude_paths, vec!["vendor/", "*.lock"]);
+        assert!(cli.filter.excludes_path("vendor/lib/a.rs"));
+        assert!(cli.filter.excludes_path("sub/Cargo.lock"));
+        assert!(!cli.filter.excludes_path("src/main.rs"));
+        let mailmap = cli.mailmap.expect("mailmap");
+        assert!(mailmap.is_absolute() && mailmap.ends_with("people.mailmap"));
+        assert!(parse(&["stats", "--exclude"]).is_err());
+        assert!(!cli.filter.include_generated);
+        let cli = parse(&["stats", "--include-generated"]).expect("parse");
+        assert!(cli.filter.include_generated);
+        assert_eq!(cli.filter.history_args(), ["--no-merges"]);
+        let cli = parse(&["timeline", "--include-merges", "--first-parent"]).expect("parse");
+        assert!(cli.filter.include_merges && cli.filter.first_parent);
+        assert_eq!(cli.filter.history_args(), ["--first-parent"]);
+    }
+
+    #[test]
+    fn test_cli_color_choice() {
+        let color = |v: &[&str]| {
+            let mut args = vec!["git-insights".to_string(), "timeline".to_string()];
+            args.extend(v.iter().map(|s| s.to_string()));
+            match Cli::parse_from_args(args)?.command {
+                Commands::Timeline { color, .. } => Ok(color),
+                _ => panic!("Expected Timeline"),
+            }
+        };
+        assert_eq!(color(&[]), Ok(ColorChoice::Auto));
+        assert_eq!(color(&["-c"]), Ok(ColorChoice::Always));
+        assert_eq!(color(&["--color=never"]), Ok(ColorChoice::Never));
+        assert_eq!(color(&["--color", "auto", "-12"]), Ok(ColorChoice::Auto));
+        assert_eq!(color(&["--no-color", "--color"]), Ok(ColorChoice::Always));
+        let err: String = color(&["--color=sometimes"]).expect_err("bad value");
+        assert!(err
+            .starts_with("invalid value 'sometimes' for '--color': expected auto, always, never"));
+        assert!(color(&["--color", "sometimes"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_verbosity() {
+        let parse = |v: &[&str]| {
+            let mut args = vec!["git-insights".to_string()];
+            args.extend(v.iter().map(|s| s.to_string()));
+            Cli::parse_from_args(args).map(|cli| cli.verbosity)
+        };
+        assert_eq!(parse(&["stats"]), Ok(Verbosity::Normal));
+        assert_eq!(parse(&["stats", "-q"]), Ok(Verbosity::Quiet));
+        assert_eq!(
+            parse(&["timeline", "--quiet", "--verbose"]),
+            Ok(Verbosity::Verbose)
+        );
+        assert!(parse(&["stats", "--quiet=yes"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_watch() {
+        let parse = |v: &[&str]| {
+            let mut args = vec!["git-insights".to_string()];
+            args.extend(v.iter().map(|s| s.to_string()));
+            Cli::parse_from_args(args).map(|cli| cli.command)
+        };
+        match parse(&["timeline", "--watch", "30", "-12"]).expect("parse") {
+            Commands::Timeline { weeks, watch, .. } => {
+                assert_eq!((weeks, watch), (Some(12), Some(30)));
+            }
+            _ => panic!("Expected Timeline"),
+        }
+        match parse(&["heatmap", "--watch"]).expect("parse") {
+            Commands::Heatmap { watch, .. } => assert_eq!(watch, Some(DEFAULT_WATCH_SECS)),
+            _ => panic!("Expected Heatmap"),
+        }
+        match parse(&["stats", "--watch=5", "--incremental"]).expect("parse") {
+            Commands::Stats { watch, .. } => assert_eq!(watch, Some(5)),
+            _ => panic!("Expected Stats"),
+        }
+        assert!(parse(&["stats", "--watch", "--output", "a.md"]).is_err());
+        assert!(parse(&["heatmap", "--watch=0"]).is_err());
+        assert!(parse(&["stats", "--watch=soon"]).is_err());
+        assert!(parse(&["top", "--watch"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_serve() {
+        let parse = |v: &[&str]| {
+            let mut args = vec!["git-insights".to_string()];
+            args.extend(v.iter().map(|s| s.to_string()));
+            Cli::parse_from_args(args).map(|cli| cli.command)
+        };
+        match parse(&["serve", "--port", "9000", "--host=0.0.0.0"]).expect("parse") {
+            Commands::Serve { port, host } => {
+                assert_eq!(port, Some(9000));
+                assert_eq!(host.as_deref(), Some("0.0.0.0"));
+            }
+            _ => panic!("Expected Serve"),
+        }
+        match parse(&["serve"]).expect("parse") {
+            Commands::Serve { port, host } => assert_eq!((port, host), (None, None)),
+            _ => panic!("Expected Serve"),
+        }
+        let err = parse(&["serve", "--port=70000"]).expect_err("port range");
+        assert!(err.contains("expected a port number"));
+        assert!(parse(&["serve", "--weeks", "4"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_user_github() {
+        let parse = |v: &[&str]| {
+            let mut args = vec!["git-insights".to_string()];
+            args.extend(v.iter().map(|s| s.to_string()));
+            Cli::parse_from_args(args).map(|cli| cli.command)
+        };
+        match parse(&["user", "alice", "--github"]).expect("parse") {
+            Commands::User { github, .. } => assert_eq!(github.as_deref(), Some("alice")),
+            _ => panic!("Expected User"),
+        }
+        match parse(&["user", "Alice Smith", "--github-login=alice-s"]).expect("parse") {
+            Commands::User { github, .. } => assert_eq!(github.as_deref(), Some("alice-s")),
+            _ => panic!("Expected User"),
+        }
+        assert!(parse(&["user", "a@x.io", "-e", "--github"]).is_err());
+        assert!(parse(&["user", "alice", "--github", "--ownership"]).is_err());
+
+        match parse(&[
+            "user",
+            "alice",
+            "--pr-pattern",
+            "^Land ",
+            "--pr-pattern=D\\d+",
+        ])
+        .expect("parse")
+        {
+            Commands::User { pr_patterns, .. } => {
+                let patterns: Vec<&str> = pr_patterns.iter().map(|r| r.as_str()).collect();
+                assert_eq!(patterns, ["^Land ", "D\\d+"]);
+            }
+            _ => panic!("Expected User"),
+        }
+        let err = parse(&["user", "alice", "--pr-pattern", "(unclosed"]).expect_err("regex");
+        assert!(err.starts_with("invalid value '(unclosed' for '--pr-pattern'"));
+    }
+
+    #[test]
+    fn test_cli_user_ownership_thresholds() {
+        let parse = |v: &[&str]| {
+            let mut args = vec!["git-insights".to_string()];
+            args.extend(v.iter().map(|s| s.to_string()));
+            Cli::parse_from_args(args).map(|cli| cli.command)
+        };
+        match parse(&[
+            "user",
+            "ann",
+            "--ownership",
+            "--min-pct",
+            "12.5",
+            "--min-loc=3",
+        ])
+        .expect("parse")
+        {
+            Commands::User {
+                min_pct, min_loc, ..
+            } => assert_eq!((min_pct, min_loc), (Some(12.5), Some(3))),
+            _ => panic!("Expected User"),
+        }
+        assert!(parse(&["user", "ann", "--min-loc", "3"]).is_err());
+        assert!(parse(&["user", "ann", "--ownership", "--min-pct", "lots"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_codeowners_audit() {
+        let parse = |v: &[&str]| {
+            let mut args = vec!["git-insights".to_string()];
+            args.extend(v.iter().map(|s| s.to_string()));
+            Cli::parse_from_args(args).map(|cli| cli.command)
+        };
+        match parse(&["codeowners-audit", "--min-pct=2.5", "--file", "CO"]).expect("parse") {
+            Commands::CodeownersAudit { file, min_pct } => {
+                assert_eq!(file.as_deref(), Some("CO"));
+                assert_eq!(min_pct, Some(2.5));
+            }
+            _ => panic!("Expected CodeownersAudit"),
+        }
+        assert!(parse(&["codeowners-audit", "--stale-days", "3"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_recurse_submodules() {
+        let parse = |v: &[&str]| {
+            let mut args = vec!["git-insights".to_string()];
+            args.extend(v.iter().map(|s| s.to_string()));
+            Cli::parse_from_args(args)
+        };
+        assert!(
+            parse(&["stats", "--recurse-submodules"])
+                .expect("parse")
+                .recurse_submodules
+        );
+        assert!(
+            parse(&["heatmap", "--recurse-submodules"])
+                .expect("parse")
+                .recurse_submodules
+        );
+        assert!(!parse(&["timeline"]).expect("parse").recurse_submodules);
+        assert!(parse(&["hotspots", "--recurse-submodules"]).is_err());
+    }
+
+    /// Every keyword `command_spec` accepts must also be accepted where the
+    /// value is finally parsed.
+    #[test]
+    fn test_command_spec_choices_parse() {
+        let choices = |command: &str, option: &str| -> &'static [&'static str] {
+            let spec = command_spec(command).expect("spec");
+            match spec
+                .options
+                .iter()
+                .copied()
+                .flatten()
+                .find(|(n, _)| *n == option)
+            {
+                Some((_, Arg::Choice(values))) => values,
+                other => panic!("{} {} is {:?}, not a choice", command, option, other),
+            }
+        };
+        let check = |command: &str, option: &str, parse: &dyn Fn(&str) -> bool| {
+            for value in choices(command, option) {
+                assert!(
+                    parse(value),
+                    "{} {} {} does not parse",
+                    command,
+                    option,
+                    value
+                );
+            }
+        };
+        check("stats", "--sort", &|v| {
+            crate::stats::StatsSort::parse(v).is_ok()
+        });
+        check("timeline", "--metric", &|v| {
+            crate::visualize::TimelineMetric::parse(v).is_ok()
+        });
+        check("timeline", "--granularity", &|v| {
+            crate::visualize::TimelineGranularity::parse(v).is_ok()
+        });
+        check("timeline", "--style", &|v| {
+            crate::visualize::TimelineStyle::parse(v).is_ok()
+        });
+        for command in ["heatmap", "code-frequency"] {
+            check(command, "--normalize", &|v| {
+                crate::visualize::HeatmapScale::parse(v).is_ok()
+            });
+        }
+        check("code-frequency", "--weight", &|v| {
+            crate::code_frequency::FrequencyWeight::parse(v).is_ok()
+        });
+        check("messages", "--by", &|v| {
+            crate::messages::MessageGroup::parse(v).is_ok()
+        });
+        check("contributors", "--sort", &|v| {
+            crate::contributors::ContributorSort::parse(v).is_ok()
+        });
+        check("top", "--metric", &|v| {
+            crate::top::TopMetric::parse(v).is_ok()
+        });
+        check("hotspots", "--size", &|v| {
+            crate::hotspots::HotspotSize::parse(v).is_ok()
+        });
+        check("badge", "--metric", &|v| {
+            crate::badge::BadgeMetric::parse(v).is_ok()
+        });
+    }
+
+    /// `command_spec` is written separately from the parsers, so check the
+    /// two against each other: every flag a command's parser reads must be
+    /// in its spec (or strict validation would reject it), and every flag in
+    /// its spec must be read by the parser or a shared helper it calls (or it
+    /// would be accepted and silently ignored).
+    #[test]
+    fn test_command_spec_matches_parsers() {
+        let source = include_str!("cli.rs");
+        let start = source
+            .find("let command = match command_str.as_str() {")
+            .unwrap();
+        let end = start + source[start..].find("let filter = parse_filter(").unwrap();
+        let arm = regex::Regex::new(r#"(?m)^            "([a-z-]+)" => \{$"#).unwrap();
+        let literal = regex::Regex::new(r#""(--?[A-Za-z][A-Za-z0-9-]*)=?""#).unwrap();
+        let names = |options: &[Options]| -> HashSet<&str> {
+            options
+                .iter()
+                .flat_map(|o| o.iter().map(|(n, _)| *n))
+                .collect()
+        };
+        // Parsed for every command after the match.
+        let everywhere = names(&[GLOBAL_OPTIONS, SUBMODULE_OPTIONS]);
+        let helpers: [(&str, Options); 3] = [
+            ("output_flag(", OUTPUT_OPTIONS),
+            ("parse_watch(", WATCH_OPTIONS),
+            ("parse_color(", COLOR_OPTIONS),
+        ];
+
+        let arms: Vec<(&str, usize)> = arm
+            .captures_iter(&source[start..end])
+            .map(|c| (c.get(1).unwrap().as_str(), start + c.get(0).unwrap().end()))
+            .collect();
+        assert!(arms.len() > 30, "parser arms not found");
+        let specs = &source[source.find("fn command_spec(").unwrap()..];
+        let specs = &specs[..specs.find("\n}\n").unwrap()];
+        let spec_arm = regex::Regex::new(r#"(?m)^        "([a-z-]+)" => spec\("#).unwrap();
+        for c in spec_arm.captures_iter(specs) {
+            let command = c.get(1).unwrap().as_str();
+            assert!(
+                arms.iter().any(|(name, _)| *name == command),
+                "'{}' has a command_spec but no parser",
+                command
+            );
+        }
+        for (i, (command, from)) in arms.iter().enumerate() {
+            let body = &source[*from..arms.get(i + 1).map_or(end, |a| a.1)];
+            let spec = command_spec(command)
+                .unwrap_or_else(|| panic!("'{}' has a parser but no command_spec", command));
+            let mut parsed: HashSet<&str> = literal
+                .captures_iter(body)
+                .map(|c| c.get(1).unwrap().as_str())
+                .collect();
+            for flag in &parsed {
+                assert!(
+                    accepts_option(command, flag),
+                    "'{}' parses {} but command_spec does not list it",
+                    command,
+                    flag
+                );
+            }
+            for (call, options) in helpers {
+                if body.contains(call) {
+                    parsed.extend(names(&[options]));
+                }
+            }
+            for flag in names(spec.options) {
+                assert!(
+                    parsed.contains(flag) || everywhere.contains(flag),
+                    "command_spec lists {} for '{}' but its parser ignores it",
+                    flag,
+                    command
+                );
             }
-            _ => panic!("Expected CodeFrequency with shorthand weeks"),
         }
     }
 }
@@ -1,102 +1,349 @@
-use crate::visualize::collect_commit_timestamps;
+use crate::error::GitInsightsError;
+use crate::palette::level_color;
+use crate::visualize::{
+    collect_author_timestamps, collect_commit_timestamps, ChartSize, HeatmapScale, HISTOGRAM_WIDTH,
+    MONTH_NAMES, TABLE_BAR_WIDTH,
+};
+use std::io::{self, Write};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Code-frequency visualizations.
+#[non_exhaustive]
 pub enum Group {
     HourOfDay,
     DayOfWeek,
     DayOfMonth,
+    /// Seasonal pattern, Jan..Dec.
+    MonthOfYear,
+    /// Long-term trend, one bar per calendar year.
+    Year,
 }
 
+/// Two-dimensional code-frequency heatmaps.
+#[non_exhaustive]
 pub enum HeatmapKind {
     DowByHod,
     DomByHod,
+    /// Conventional-commit type x hour-of-day.
+    TypeByHod,
+    /// Lines added and lines deleted, each day-of-week x hour-of-day.
+    AddDelByHod,
+}
+
+/// Whether `t` falls in the last `weeks` weeks (aligned to the end of the current week).
+fn in_week_window(t: u64, weeks: Option<usize>, now: u64) -> bool {
+    let Some(w) = weeks else {
+        return true;
+    };
+    if w == 0 {
+        return false;
+    }
+    const DAY: u64 = 86_400;
+    const WEEK: u64 = 7 * DAY;
+    let start_of_week = now - (now % WEEK);
+    let aligned_end = start_of_week.saturating_add(WEEK - 1);
+    let span = (w as u64).saturating_mul(WEEK);
+    let min_ts = aligned_end.saturating_sub(span.saturating_sub(1));
+    t <= aligned_end && t >= min_ts
 }
 
 /// Filter to last N weeks.
 fn filter_by_weeks(timestamps: &[u64], weeks: Option<usize>, now: u64) -> Vec<u64> {
-    if let Some(w) = weeks {
-        if w == 0 {
-            return Vec::new();
+    timestamps
+        .iter()
+        .copied()
+        .filter(|&t| in_week_window(t, weeks, now))
+        .collect()
+}
+
+// Histograms.
+
+/// What each commit adds to its code-frequency bucket (`--weight`).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[non_exhaustive]
+pub enum FrequencyWeight {
+    /// One per commit.
+    #[default]
+    Commits,
+    /// Lines added plus deleted (`--numstat`), so one massive commit
+    /// outweighs a typo fix.
+    Lines,
+}
+
+impl FrequencyWeight {
+    pub fn parse(s: &str) -> Result<FrequencyWeight, String> {
+        match s {
+            "commits" => Ok(FrequencyWeight::Commits),
+            "lines" => Ok(FrequencyWeight::Lines),
+            other => Err(format!(
+                "unknown --weight '{}'. Expected commits or lines.",
+                other
+            )),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FrequencyWeight::Commits => "commits",
+            FrequencyWeight::Lines => "lines",
         }
-        const DAY: u64 = 86_400;
-        const WEEK: u64 = 7 * DAY;
-        let start_of_week = now - (now % WEEK);
-        let aligned_end = start_of_week.saturating_add(WEEK - 1);
-        let span = (w as u64).saturating_mul(WEEK);
-        let min_ts = aligned_end.saturating_sub(span.saturating_sub(1));
-        timestamps
-            .iter()
-            .copied()
-            .filter(|&t| t <= aligned_end && t >= min_ts)
-            .collect()
-    } else {
-        timestamps.to_vec()
     }
 }
 
-/// Histograms.
+/// Every timestamp with weight 1.
+fn unweighted(timestamps: &[u64]) -> impl Iterator<Item = (u64, usize)> + '_ {
+    timestamps.iter().map(|&t| (t, 1))
+}
 
-pub fn histogram_hour_of_day(timestamps: &[u64]) -> [usize; 24] {
-    let mut bins = [0usize; 24];
-    for &t in timestamps {
-        let hour = ((t / 3_600) % 24) as usize;
-        bins[hour] += 1;
+/// Sum the weight of each `(time, weight)` point into the bin `index` picks.
+fn weighted_bins<const N: usize>(
+    points: impl Iterator<Item = (u64, usize)>,
+    index: impl Fn(u64) -> Option<usize>,
+) -> [usize; N] {
+    let mut bins = [0usize; N];
+    for (t, w) in points {
+        if let Some(i) = index(t).filter(|&i| i < N) {
+            bins[i] += w;
+        }
     }
     bins
 }
 
+fn hour_of(t: u64) -> Option<usize> {
+    Some(((t / 3_600) % 24) as usize)
+}
+
+fn weekday_of(t: u64) -> Option<usize> {
+    Some(((t / 86_400 + 4) % 7) as usize) // 1970-01-01 = Thu (4)
+}
+
+fn day_of_month_of(t: u64) -> Option<usize> {
+    let (_, _, d) = ymd_from_unix(t);
+    (1..=31).contains(&d).then(|| (d - 1) as usize)
+}
+
+fn month_of(t: u64) -> Option<usize> {
+    let (_, m, _) = ymd_from_unix(t);
+    (m as usize).checked_sub(1)
+}
+
+pub fn histogram_hour_of_day(timestamps: &[u64]) -> [usize; 24] {
+    weighted_bins(unweighted(timestamps), hour_of)
+}
+
 pub fn histogram_day_of_week(timestamps: &[u64]) -> [usize; 7] {
-    let mut bins = [0usize; 7];
-    for &t in timestamps {
-        let day = t / 86_400;
-        let weekday = ((day + 4) % 7) as usize; // 1970-01-01 = Thu (4)
-        bins[weekday] += 1;
-    }
-    bins
+    weighted_bins(unweighted(timestamps), weekday_of)
 }
 
 /// Day-of-month histogram.
 pub fn histogram_day_of_month(timestamps: &[u64]) -> [usize; 31] {
-    let mut bins = [0usize; 31];
-    for &t in timestamps {
-        let (_, _, d) = ymd_from_unix(t);
-        if d >= 1 && d <= 31 {
-            bins[(d - 1) as usize] += 1;
-        }
+    weighted_bins(unweighted(timestamps), day_of_month_of)
+}
+
+/// Month-of-year histogram, Jan..Dec.
+pub fn histogram_month_of_year(timestamps: &[u64]) -> [usize; 12] {
+    weighted_bins(unweighted(timestamps), month_of)
+}
+
+/// Commits per calendar year from the first to the last one with commits,
+/// quiet years included.
+pub fn histogram_year(timestamps: &[u64]) -> Vec<(i32, usize)> {
+    weighted_years(unweighted(timestamps))
+}
+
+fn weighted_years(points: impl Iterator<Item = (u64, usize)>) -> Vec<(i32, usize)> {
+    let years: Vec<(i32, usize)> = points.map(|(t, w)| (ymd_from_unix(t).0, w)).collect();
+    let (Some(first), Some(last)) = (
+        years.iter().map(|(y, _)| *y).min(),
+        years.iter().map(|(y, _)| *y).max(),
+    ) else {
+        return Vec::new();
+    };
+    let mut bins: Vec<(i32, usize)> = (first..=last).map(|y| (y, 0)).collect();
+    for (y, w) in years {
+        bins[(y - first) as usize].1 += w;
     }
     bins
 }
 
-/// Heatmaps
+// Heatmaps
 
 /// 7x24 heatmap.
 pub fn heatmap_dow_by_hod(timestamps: &[u64]) -> [[usize; 24]; 7] {
-    let mut grid = [[0usize; 24]; 7];
-    for &t in timestamps {
-        let day = t / 86_400;
-        let weekday = ((day + 4) % 7) as usize;
-        let hour = ((t / 3_600) % 24) as usize;
-        grid[weekday][hour] += 1;
+    weighted_grid(unweighted(timestamps), weekday_of)
+}
+
+/// Sum the weight of each point into the `row` picked for its time, by hour.
+fn weighted_grid<const R: usize>(
+    points: impl Iterator<Item = (u64, usize)>,
+    row: impl Fn(u64) -> Option<usize>,
+) -> [[usize; 24]; R] {
+    let mut grid = [[0usize; 24]; R];
+    for (t, w) in points {
+        if let (Some(r), Some(h)) = (row(t).filter(|&r| r < R), hour_of(t)) {
+            grid[r][h] += w;
+        }
     }
     grid
 }
 
-/// 31x24 heatmap.
-pub fn heatmap_dom_by_hod(timestamps: &[u64]) -> [[usize; 24]; 31] {
-    let mut grid = [[0usize; 24]; 31];
-    for &t in timestamps {
-        let (_, _, d) = ymd_from_unix(t);
-        if d >= 1 && d <= 31 {
-            let row = (d - 1) as usize;
-            let hour = ((t / 3_600) % 24) as usize;
-            grid[row][hour] += 1;
+/// Conventional-commit type x 24 heatmap from `(author time, subject)` pairs.
+///
+/// Only types that occur get a row, in [`crate::messages::COMMIT_TYPES`]
+/// order with `other` last.
+pub fn heatmap_type_by_hod(commits: &[(u64, String)]) -> (Vec<String>, Vec<Vec<usize>>) {
+    use crate::messages::{classify_subject, COMMIT_TYPES, OTHER_TYPE};
+    let order: Vec<&str> = COMMIT_TYPES
+        .iter()
+        .copied()
+        .chain(std::iter::once(OTHER_TYPE))
+        .collect();
+    let mut grid = vec![vec![0usize; 24]; order.len()];
+    for (t, subject) in commits {
+        let kind = classify_subject(subject);
+        if let Some(row) = order.iter().position(|k| *k == kind) {
+            grid[row][((t / 3_600) % 24) as usize] += 1;
         }
     }
-    grid
+    order
+        .into_iter()
+        .zip(grid)
+        .filter(|(_, row)| row.iter().any(|&c| c > 0))
+        .map(|(k, row)| (k.to_string(), row))
+        .unzip()
+}
+
+/// Commits in the week window as `(author time, subject)`, shifted to the
+/// `--tz` wall clock; merges only with `--include-merges`.
+fn commit_subjects(weeks: Option<usize>, now: u64) -> Result<Vec<(u64, String)>, GitInsightsError> {
+    let tz = crate::filter::active().time_zone;
+    let offsets = commit_offsets()?;
+    let keep_merges = crate::filter::active().include_merges;
+    Ok(crate::commits::collect_commits()?
+        .into_iter()
+        .filter(|c| keep_merges || !c.is_merge())
+        .map(|c| {
+            let offset = offsets.get(&c.sha).map_or("", String::as_str);
+            (
+                tz.wall_clock(c.author_time, c.author_time, offset),
+                c.subject,
+            )
+        })
+        .filter(|(t, _)| in_week_window(*t, weeks, now))
+        .collect())
+}
+
+/// `--tz` UTC offset per full sha (empty in UTC mode).
+fn commit_offsets() -> Result<std::collections::HashMap<String, String>, GitInsightsError> {
+    let Some((date, field)) = crate::filter::active().time_zone.offset_format() else {
+        return Ok(Default::default());
+    };
+    let revs = crate::filter::revision_args()?;
+    let format = format!("--format=%H%x09{}", field);
+    let mut args = vec!["--no-pager", "log", date, &format];
+    args.extend(revs.iter().map(|s| s.as_str()));
+    let mut offsets = std::collections::HashMap::new();
+    crate::git::run_command_lines(&args, |l| {
+        if let Some((sha, off)) = l.split_once('\t') {
+            offsets.insert(sha.to_string(), off.to_string());
+        }
+    })?;
+    Ok(offsets)
 }
 
-/// Rendering.
+/// `(time, lines added, lines deleted)` per commit from `git log --numstat
+/// --format=%x1f%ct%x1f%at%x1f%aN%x1f%aE[%x1f<offset>]` output. `time` maps
+/// committer time, author time and offset to a wall clock, or `None` to drop
+/// the commit; binary and excluded files add nothing.
+pub fn line_changes_from_numstat<S: AsRef<str>>(
+    lines: impl Iterator<Item = S>,
+    allow: impl Fn(&str, &str) -> bool,
+    exclude: impl Fn(&str) -> bool,
+    time: impl Fn(u64, u64, &str) -> Option<u64>,
+) -> Vec<(u64, usize, usize)> {
+    let mut points: Vec<(u64, usize, usize)> = Vec::new();
+    let mut counting = false;
+    for line in lines {
+        let line = line.as_ref();
+        if let Some(header) = line.strip_prefix('\x1f') {
+            let mut fields = header.split('\x1f');
+            let (Some(ct), Some(at), Some(name), email, offset) = (
+                fields.next().and_then(|v| v.parse::<u64>().ok()),
+                fields.next().and_then(|v| v.parse::<u64>().ok()),
+                fields.next(),
+                fields.next().unwrap_or(""),
+                fields.next().unwrap_or(""),
+            ) else {
+                counting = false;
+                continue;
+            };
+            let t = allow(name, email).then(|| time(ct, at, offset)).flatten();
+            counting = t.is_some();
+            points.extend(t.map(|t| (t, 0, 0)));
+        } else if counting {
+            let mut parts = line.splitn(3, '\t');
+            let (Some(a), Some(d), Some(path)) = (parts.next(), parts.next(), parts.next()) else {
+                continue;
+            };
+            let (Ok(a), Ok(d)) = (a.parse::<usize>(), d.parse::<usize>()) else {
+                continue;
+            };
+            if let Some(last) = points.last_mut().filter(|_| !exclude(path)) {
+                last.1 += a;
+                last.2 += d;
+            }
+        }
+    }
+    points
+}
+
+/// [`line_changes_from_numstat`] of the active filter, shifted to the `--tz`
+/// wall clock, with initialized submodules under `--recurse-submodules`.
+fn collect_line_changes() -> Result<Vec<(u64, usize, usize)>, GitInsightsError> {
+    if crate::submodules::recurse_submodules() {
+        return Ok(crate::submodules::each_repo(collect_repo_line_changes)?
+            .into_iter()
+            .flat_map(|(_, points)| points)
+            .collect());
+    }
+    collect_repo_line_changes()
+}
+
+fn collect_repo_line_changes() -> Result<Vec<(u64, usize, usize)>, GitInsightsError> {
+    let revs = crate::filter::revision_args()?;
+    let authors = crate::filter::author_matcher()?;
+    let filter = crate::filter::active();
+    let tz = filter.time_zone;
+    let mut format = "--format=%x1f%ct%x1f%at%x1f%aN%x1f%aE".to_string();
+    let mut args = vec!["--no-pager", "log", "--numstat", "--no-renames"];
+    args.extend(filter.history_args());
+    if let Some((date, field)) = tz.offset_format() {
+        format.push_str("%x1f");
+        format.push_str(field);
+        args.push(date);
+    }
+    args.push(&format);
+    args.extend(revs.iter().map(|s| s.as_str()));
+    let sanity = crate::time_audit::active_sanity()?;
+    crate::git::run_command_line_iter(&args, |lines| {
+        line_changes_from_numstat(
+            lines,
+            |n, e| authors.allows(n, e),
+            |p| filter.excludes_path(p),
+            |ct, at, offset| {
+                let ok = sanity.as_ref().is_none_or(|s| s.allows(at, ct));
+                ok.then(|| tz.wall_clock(ct, at, offset))
+            },
+        )
+    })
+}
+
+/// 31x24 heatmap.
+pub fn heatmap_dom_by_hod(timestamps: &[u64]) -> [[usize; 24]; 31] {
+    weighted_grid(unweighted(timestamps), day_of_month_of)
+}
+
+// Rendering.
 
 const ANSI_RESET: &str = "\x1b[0m";
 
@@ -109,80 +356,56 @@ fn intensity_index(v: usize, max: usize, levels: usize) -> usize {
     let idx = ((v - 1) * l) / max + 1;
     idx.min(l)
 }
-
-/// 12-step color palette.
-fn color_for_level_rich(idx: usize, levels: usize) -> &'static str {
-    // 12-color ramp from dim through cool to warm hues.
-    // Using standard ANSI codes (widely supported); bright/dim variants to increase steps.
-    const PALETTE: [&str; 12] = [
-        "\x1b[90m", // 0: dim (should not be used for non-zero, but safe fallback)
-        "\x1b[34m", // blue
-        "\x1b[94m", // bright blue
-        "\x1b[36m", // cyan
-        "\x1b[96m", // bright cyan
-        "\x1b[32m", // green
-        "\x1b[92m", // bright green
-        "\x1b[33m", // yellow
-        "\x1b[93m", // bright yellow
-        "\x1b[35m", // magenta
-        "\x1b[95m", // bright magenta
-        "\x1b[91m", // bright red
-    ];
-    let n = PALETTE.len();
-    if levels <= 1 {
-        return PALETTE[0];
-    }
-    // Scale idx (0..levels-1) into PALETTE indices (0..n-1)
-    let k = if idx >= levels - 1 {
-        n - 1
-    } else {
-        (idx * (n - 1)) / (levels - 1)
-    };
-    PALETTE[k]
-}
-
 /// Legend (rich palette).
-fn print_ramp_legend_rich(color: bool, unit: &str) {
+fn print_ramp_legend_rich(out: &mut impl Write, color: bool, unit: &str) -> io::Result<()> {
     if color {
-        print!("\x1b[90mLegend (low→high, blank=0 {}):\x1b[0m ", unit);
+        write!(out, "\x1b[90mLegend (low→high, blank=0 {}):\x1b[0m ", unit)?;
         let levels = 10;
         for lvl in 1..levels {
-            let code = color_for_level_rich(lvl, levels);
-            print!(" {}█{}", code, ANSI_RESET);
+            let code = level_color(lvl, levels);
+            write!(out, " {}█{}", code, ANSI_RESET)?;
         }
-        println!();
+        writeln!(out)?;
     } else {
         // ASCII legend consistent with existing ramp
-        println!("Legend (low→high, blank=' ' 0 {}):  .:-=+*#%@", unit);
+        writeln!(out, "Legend (low→high, blank=' ' 0 {}):  .:-=+*#%@", unit)?;
     }
+    Ok(())
 }
 
-fn render_histogram_labeled(labels: &[&str], counts: &[usize], color: bool, unit: &str) {
+pub(crate) fn render_histogram_labeled(
+    out: &mut impl Write,
+    labels: &[&str],
+    counts: &[usize],
+    color: bool,
+    unit: &str,
+    size: ChartSize,
+) -> io::Result<()> {
     let max = counts.iter().copied().max().unwrap_or(0);
     let label_width = labels.iter().map(|s| s.len()).max().unwrap_or(0).max(3);
     if color {
-        print!("\x1b[90m");
+        write!(out, "\x1b[90m")?;
     }
-    println!("Histogram — unit: {}", unit);
+    writeln!(out, "Histogram — unit: {}", unit)?;
     if color {
-        print!("\x1b[0m");
+        write!(out, "\x1b[0m")?;
     }
-    print_ramp_legend_rich(color, unit);
+    print_ramp_legend_rich(out, color, unit)?;
 
     if max == 0 {
-        println!("(no commits)");
-        return;
+        writeln!(out, "(no commits)")?;
+        return Ok(());
     }
 
-    // Target bar width
-    let width = 40usize;
+    // Target bar width: label, " | ", bar, " " and the count
+    let width = size.bar_width(HISTOGRAM_WIDTH, label_width + 4 + max.to_string().len());
     for (i, &c) in counts.iter().enumerate() {
-        let bar_len = (c * width + max - 1) / max; // ceil
+        let bar_len = (c * width).div_ceil(max); // ceil
         let mut line = String::new();
         line.push_str(&format!("{:>width$} | ", labels[i], width = label_width));
         if color {
             let idx = intensity_index(c, max, 10);
-            line.push_str(color_for_level_rich(idx, 10));
+            line.push_str(&level_color(idx, 10));
             for _ in 0..bar_len {
                 line.push('█');
             }
@@ -194,12 +417,13 @@ fn render_histogram_labeled(labels: &[&str], counts: &[usize], color: bool, unit
             }
             line.push_str(&format!(" {}", c));
         }
-        println!("{}", line);
+        writeln!(out, "{}", line)?;
     }
+    Ok(())
 }
 
 /// Build histogram table.
-fn build_histogram_table(labels: &[&str], counts: &[usize]) -> String {
+pub(crate) fn build_histogram_table(labels: &[&str], counts: &[usize], size: ChartSize) -> String {
     use std::fmt::Write as _;
     let n = counts.len().min(labels.len());
     let max_count = counts.iter().copied().max().unwrap_or(0);
@@ -213,7 +437,6 @@ fn build_histogram_table(labels: &[&str], counts: &[usize]) -> String {
         .unwrap_or(0)
         .max(3);
     let count_w_data = max_count.to_string().len().max(1);
-    let bar_w_data = 20usize;
 
     // Ensure headers fit within column widths (avoid lines of different lengths)
     let label_hdr = "Label".len();
@@ -222,6 +445,7 @@ fn build_histogram_table(labels: &[&str], counts: &[usize]) -> String {
 
     let label_w = label_w_data.max(label_hdr);
     let count_w = count_w_data.max(count_hdr);
+    let bar_w_data = size.bar_width(TABLE_BAR_WIDTH, label_w + count_w + 10);
     let bar_w = bar_w_data.max(bar_hdr);
 
     let mut out = String::new();
@@ -244,9 +468,9 @@ fn build_histogram_table(labels: &[&str], counts: &[usize]) -> String {
 
     // Header
     push_sep(&mut out);
-    let _ = write!(
+    let _ = writeln!(
         out,
-        "| {:>lw$} | {:>cw$} | {:>bw$} |\n",
+        "| {:>lw$} | {:>cw$} | {:>bw$} |",
         "Label",
         "Count",
         "Bar",
@@ -263,7 +487,7 @@ fn build_histogram_table(labels: &[&str], counts: &[usize]) -> String {
         let filled = if max_count == 0 {
             0
         } else {
-            (c * bar_w + max_count - 1) / max_count
+            (c * bar_w).div_ceil(max_count)
         }; // ceil
         let mut bar = String::with_capacity(bar_w);
         for _ in 0..filled {
@@ -272,9 +496,9 @@ fn build_histogram_table(labels: &[&str], counts: &[usize]) -> String {
         for _ in filled..bar_w {
             bar.push(' ');
         }
-        let _ = write!(
+        let _ = writeln!(
             out,
-            "| {:>lw$} | {:>cw$} | {} |\n",
+            "| {:>lw$} | {:>cw$} | {} |",
             lab,
             c,
             bar,
@@ -287,11 +511,17 @@ fn build_histogram_table(labels: &[&str], counts: &[usize]) -> String {
 }
 
 /// Render histogram table.
-fn render_histogram_table(labels: &[&str], counts: &[usize], color: bool) {
+fn render_histogram_table(
+    out: &mut impl Write,
+    labels: &[&str],
+    counts: &[usize],
+    color: bool,
+    size: ChartSize,
+) -> io::Result<()> {
     if !color {
-        let s = build_histogram_table(labels, counts);
-        print!("{}", s);
-        return;
+        let s = build_histogram_table(labels, counts, size);
+        write!(out, "{}", s)?;
+        return Ok(());
     }
 
     use std::fmt::Write as _;
@@ -308,9 +538,9 @@ fn render_histogram_table(labels: &[&str], counts: &[usize], color: bool) {
         .unwrap_or(0)
         .max(3);
     let count_w_data = max_count.to_string().len().max(1);
-    let bar_w_data = 20usize;
     let label_w = label_w_data.max("Label".len());
     let count_w = count_w_data.max("Count".len());
+    let bar_w_data = size.bar_width(TABLE_BAR_WIDTH, label_w + count_w + 10);
     let bar_w = bar_w_data.max("Bar".len());
 
     let push_sep = |s: &mut String| {
@@ -329,13 +559,13 @@ fn render_histogram_table(labels: &[&str], counts: &[usize], color: bool) {
         s.push_str("+\n");
     };
 
-    let mut out = String::new();
+    let mut buf = String::new();
     // Top border
-    push_sep(&mut out);
+    push_sep(&mut buf);
     // Header (plain text)
-    let _ = write!(
-        out,
-        "| {:>lw$} | {:>cw$} | {:>bw$} |\n",
+    let _ = writeln!(
+        buf,
+        "| {:>lw$} | {:>cw$} | {:>bw$} |",
         "Label",
         "Count",
         "Bar",
@@ -344,9 +574,9 @@ fn render_histogram_table(labels: &[&str], counts: &[usize], color: bool) {
         bw = bar_w
     );
     // Header separator
-    push_sep(&mut out);
-    print!("{}", out);
-    out.clear();
+    push_sep(&mut buf);
+    write!(out, "{}", buf)?;
+    buf.clear();
 
     // Data rows
     for i in 0..n {
@@ -357,7 +587,7 @@ fn render_histogram_table(labels: &[&str], counts: &[usize], color: bool) {
         let filled = if max_count == 0 {
             0
         } else {
-            (c * bar_w + max_count - 1) / max_count
+            (c * bar_w).div_ceil(max_count)
         }; // ceil
         let mut bar = String::with_capacity(bar_w);
         for _ in 0..filled {
@@ -373,21 +603,18 @@ fn render_histogram_table(labels: &[&str], counts: &[usize], color: bool) {
         } else {
             intensity_index(c, max_count, 10)
         };
-        let code = if shade == 0 {
-            "\x1b[90m"
-        } else {
-            color_for_level_rich(shade, 10)
-        };
+        let code = level_color(shade, 10);
 
         // Print row: keep widths applied to digits only, wrap with ANSI to preserve alignment
-        print!("| {:>lw$} ", lab, lw = label_w);
-        print!("| {}{:>cw$}{} ", code, c, ANSI_RESET, cw = count_w);
-        print!("| {}{}{} |\n", code, bar, ANSI_RESET);
+        write!(out, "| {:>lw$} ", lab, lw = label_w)?;
+        write!(out, "| {}{:>cw$}{} ", code, c, ANSI_RESET, cw = count_w)?;
+        writeln!(out, "| {}{}{} |", code, bar, ANSI_RESET)?;
 
-        out.clear();
-        push_sep(&mut out);
-        print!("{}", out);
+        buf.clear();
+        push_sep(&mut buf);
+        write!(out, "{}", buf)?;
     }
+    Ok(())
 }
 
 /// Build hour axis (24 columns).
@@ -405,7 +632,13 @@ pub(super) fn build_hour_axis_24(indent: usize, cell_w: usize) -> String {
 }
 
 /// Render heatmap grid (rows x 24).
-fn render_heatmap_rows_x_24(rows: &[Vec<usize>], row_labels: &[String], color: bool) {
+fn render_heatmap_rows_x_24(
+    out: &mut impl Write,
+    rows: &[Vec<usize>],
+    row_labels: &[String],
+    color: bool,
+    scale: HeatmapScale,
+) -> io::Result<()> {
     let cols = 24usize;
     // Compute global max
     let mut max = 0usize;
@@ -417,36 +650,42 @@ fn render_heatmap_rows_x_24(rows: &[Vec<usize>], row_labels: &[String], color: b
         }
     }
     // Header (centered hours per fixed-width column)
-    println!("{}", build_hour_axis_24(4, 3));
+    let label_w = row_labels
+        .iter()
+        .map(|l| l.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(3);
+    writeln!(out, "{}", build_hour_axis_24(label_w + 1, 3))?;
     for (ri, lab) in row_labels.iter().enumerate() {
-        print!("{:<3} ", &lab);
-        for h in 0..cols {
-            let v = rows[ri][h];
+        write!(out, "{:<label_w$} ", &lab)?;
+        let row_max = rows[ri].iter().take(cols).copied().max().unwrap_or(0);
+        for &v in rows[ri].iter().take(cols) {
+            let (v, max) = scale.scale(v, row_max, max);
             if color {
                 if max == 0 || v == 0 {
                     // 3 spaces for an empty cell (width 3)
-                    print!("   ");
+                    write!(out, "   ")?;
                 } else {
                     // Double bricks for clearer alignment: two blocks + one space (width 3)
                     let idx = intensity_index(v, max, 10);
-                    let code = color_for_level_rich(idx, 10);
-                    print!("{}██{} ", code, ANSI_RESET);
+                    let code = level_color(idx, 10);
+                    write!(out, "{}██{} ", code, ANSI_RESET)?;
                 }
             } else {
                 // ASCII: double the ramp char for same width (2 chars + 1 space)
-                let ch = if max == 0 {
-                    ' '
-                } else {
-                    let ramp: &[u8] = b" .:-=+*#%@";
-                    let idx = (v.saturating_mul(ramp.len() - 1)) / max;
-                    ramp[idx] as char
+                let ramp: &[u8] = b" .:-=+*#%@";
+                let ch = match v.saturating_mul(ramp.len() - 1).checked_div(max) {
+                    Some(idx) => ramp[idx] as char,
+                    None => ' ',
                 };
-                print!("{}{} ", ch, ch);
+                write!(out, "{}{} ", ch, ch)?;
             }
         }
-        println!();
+        writeln!(out)?;
     }
-    println!("{}", build_hour_axis_24(4, 3));
+    writeln!(out, "{}", build_hour_axis_24(label_w + 1, 3))?;
+    Ok(())
 }
 
 /// Build heatmap table.
@@ -497,8 +736,7 @@ fn build_heatmap_table_rows_x_24(rows: &[Vec<usize>], row_labels: &[String]) ->
     // Data rows
     for (ri, lab) in row_labels.iter().enumerate() {
         let _ = write!(out, "| {:>rlw$} ", lab, rlw = rlw);
-        for h in 0..24 {
-            let v = rows[ri][h];
+        for &v in rows[ri].iter().take(24) {
             let _ = write!(out, "| {:>w$} ", v, w = cell_w);
         }
         out.push_str("|\n");
@@ -509,17 +747,23 @@ fn build_heatmap_table_rows_x_24(rows: &[Vec<usize>], row_labels: &[String]) ->
 }
 
 /// Render heatmap table.
-fn render_heatmap_table_rows_x_24(rows: &[Vec<usize>], row_labels: &[String]) {
+fn render_heatmap_table_rows_x_24(
+    out: &mut impl Write,
+    rows: &[Vec<usize>],
+    row_labels: &[String],
+) -> io::Result<()> {
     let s = build_heatmap_table_rows_x_24(rows, row_labels);
-    print!("{}", s);
+    write!(out, "{}", s)?;
+    Ok(())
 }
 
 /// Render colored heatmap table.
 fn render_heatmap_table_rows_x_24_colored(
+    out: &mut impl Write,
     rows: &[Vec<usize>],
     row_labels: &[String],
-    _color: bool,
-) {
+    scale: HeatmapScale,
+) -> io::Result<()> {
     use std::fmt::Write as _;
 
     // Compute max to determine widths and intensities
@@ -534,7 +778,7 @@ fn render_heatmap_table_rows_x_24_colored(
     let cell_w = max_val.to_string().len().max(2);
     let rlw = row_labels.iter().map(|s| s.len()).max().unwrap_or(3).max(3);
 
-    let mut out = String::new();
+    let mut buf = String::new();
 
     // Separator
     let push_sep = |s: &mut String| {
@@ -552,48 +796,422 @@ fn render_heatmap_table_rows_x_24_colored(
     };
 
     // Top border
-    push_sep(&mut out);
+    push_sep(&mut buf);
 
     // Header row
-    let _ = write!(out, "| {:>rlw$} ", "", rlw = rlw);
+    let _ = write!(buf, "| {:>rlw$} ", "", rlw = rlw);
     for h in 0..24 {
-        let _ = write!(out, "| {:>w$} ", format!("{:02}", h), w = cell_w);
+        let _ = write!(buf, "| {:>w$} ", format!("{:02}", h), w = cell_w);
     }
-    out.push_str("|\n");
+    buf.push_str("|\n");
 
     // Header separator
-    push_sep(&mut out);
-    print!("{}", out);
-    out.clear();
+    push_sep(&mut buf);
+    write!(out, "{}", buf)?;
+    buf.clear();
 
     // Data rows with colored counts
     for (ri, lab) in row_labels.iter().enumerate() {
         // Row label
-        print!("| {:>rlw$} ", lab, rlw = rlw);
+        write!(out, "| {:>rlw$} ", lab, rlw = rlw)?;
 
-        for h in 0..24 {
-            let v = rows[ri][h];
+        let row_max = rows[ri].iter().take(24).copied().max().unwrap_or(0);
+        for &v in rows[ri].iter().take(24) {
             let shade = if v == 0 || max_val == 0 {
                 0
             } else {
-                intensity_index(v, max_val, 10)
+                let (scaled, max) = scale.scale(v, row_max, max_val);
+                intensity_index(scaled, max, 10)
             };
-            let code = if shade == 0 {
-                "\x1b[90m"
+            let code = level_color(shade, 10);
+            write!(out, "| {}{:>w$}{} ", code, v, ANSI_RESET, w = cell_w)?;
+        }
+        writeln!(out, "|")?;
+
+        buf.clear();
+        push_sep(&mut buf);
+        write!(out, "{}", buf)?;
+    }
+    Ok(())
+}
+
+// Per-author small multiples.
+
+/// Day-of-week x hour-of-day grid for one author.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthorHeatmap {
+    pub name: String,
+    pub commits: usize,
+    pub grid: [[usize; 24]; 7],
+}
+
+/// Grids for the `top` authors with the most commits (ties by name).
+pub fn heatmaps_per_author(pairs: &[(String, u64)], top: usize) -> Vec<AuthorHeatmap> {
+    let mut by_author: std::collections::BTreeMap<&str, Vec<u64>> = Default::default();
+    for (name, t) in pairs {
+        by_author.entry(name).or_default().push(*t);
+    }
+    let mut panels: Vec<AuthorHeatmap> = by_author
+        .into_iter()
+        .map(|(name, ts)| AuthorHeatmap {
+            name: name.to_string(),
+            commits: ts.len(),
+            grid: heatmap_dow_by_hod(&ts),
+        })
+        .collect();
+    panels.sort_by(|a, b| b.commits.cmp(&a.commits).then_with(|| a.name.cmp(&b.name)));
+    panels.truncate(top);
+    panels
+}
+
+const PANEL_LABELS: [&str; 7] = ["Su", "Mo", "Tu", "We", "Th", "Fr", "Sa"];
+/// Row label plus one column per hour.
+const PANEL_WIDTH: usize = 3 + 24;
+const PANEL_GAP: usize = 3;
+
+/// Compact hour axis under a panel: 0, 6, 12 and 18 at their columns.
+fn panel_hour_axis() -> String {
+    let mut axis = format!("{:<3}", "");
+    for h in (0..24).step_by(6) {
+        axis.push_str(&format!("{:<6}", h));
+    }
+    axis
+}
+
+/// One-column cell of a compact panel: blank for 0, else a shaded block (or
+/// an ASCII ramp character without color).
+fn push_panel_cell(line: &mut String, v: usize, max: usize, color: bool) {
+    if v == 0 || max == 0 {
+        line.push(' ');
+    } else if color {
+        line.push_str(&level_color(intensity_index(v, max, 10), 10));
+        line.push('█');
+        line.push_str(ANSI_RESET);
+    } else {
+        let ramp: &[u8] = b" .:-=+*#%@";
+        line.push(ramp[v * (ramp.len() - 1) / max] as char);
+    }
+}
+
+/// Render panels side by side, as many per row as fit in `columns` (80 when
+/// unknown). Each panel is shaded relative to its own busiest hour, so
+/// rhythms stay comparable between prolific and occasional authors.
+fn render_small_multiples(
+    out: &mut impl Write,
+    panels: &[AuthorHeatmap],
+    color: bool,
+    columns: Option<usize>,
+) -> io::Result<()> {
+    let per_row = ((columns.unwrap_or(80) + PANEL_GAP) / (PANEL_WIDTH + PANEL_GAP)).max(1);
+    let gap = " ".repeat(PANEL_GAP);
+    for chunk in panels.chunks(per_row) {
+        let titles: Vec<String> = chunk
+            .iter()
+            .map(|p| {
+                let title = format!("{} ({})", p.name, p.commits);
+                format!(
+                    "{:<w$}",
+                    crate::output::truncate(&title, PANEL_WIDTH),
+                    w = PANEL_WIDTH
+                )
+            })
+            .collect();
+        writeln!(out, "{}", titles.join(&gap).trim_end())?;
+        let week_start = crate::filter::active().week_start;
+        for row in week_start.day_order() {
+            let label = PANEL_LABELS[row];
+            let cells: Vec<String> = chunk
+                .iter()
+                .map(|p| {
+                    let max = p.grid.iter().flatten().copied().max().unwrap_or(0);
+                    let mut line = format!("{:<3}", label);
+                    for &v in &p.grid[row] {
+                        push_panel_cell(&mut line, v, max, color);
+                    }
+                    line
+                })
+                .collect();
+            writeln!(out, "{}", cells.join(&gap))?;
+        }
+        let axes: Vec<String> = chunk.iter().map(|_| panel_hour_axis()).collect();
+        writeln!(out, "{}", axes.join(&gap).trim_end())?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// `--heatmap dow-hod --per-author`: one mini-heatmap per top author.
+pub fn run_code_frequency_per_author(
+    weeks: Option<usize>,
+    color: bool,
+    top: usize,
+) -> Result<(), GitInsightsError> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| format!("clock error: {e}"))?
+        .as_secs();
+    let pairs: Vec<(String, u64)> = collect_author_timestamps()?
+        .into_iter()
+        .filter(|(_, t)| in_week_window(*t, weeks, now))
+        .collect();
+    let panels = heatmaps_per_author(&pairs, top);
+    Ok(render_per_author(
+        &mut io::stdout().lock(),
+        &panels,
+        color,
+        top,
+    )?)
+}
+
+fn render_per_author(
+    out: &mut impl Write,
+    panels: &[AuthorHeatmap],
+    color: bool,
+    top: usize,
+) -> io::Result<()> {
+    if color {
+        write!(out, "\x1b[90m")?;
+    }
+    writeln!(out,
+        "Heatmap: Day-of-Week x Hour-of-Day ({}) per author, top {}; each panel scaled to its own max",
+        crate::filter::active().time_zone.label(),
+        top
+    )?;
+    if color {
+        write!(out, "\x1b[0m")?;
+    }
+    if panels.is_empty() {
+        writeln!(out, "(no commits)")?;
+        return Ok(());
+    }
+    print_ramp_legend_rich(out, color, "commits/hour")?;
+    writeln!(out)?;
+    render_small_multiples(out, panels, color, crate::visualize::terminal_columns())?;
+    Ok(())
+}
+
+// Weekday/weekend and working/off-hours summary.
+
+/// Hours (start inclusive, on the `--tz` clock) counted as working hours by
+/// `--summary`.
+pub const WORK_HOURS: std::ops::Range<u64> = 9..18;
+
+/// Commits split by weekday vs weekend and working vs off hours.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ActivitySplit {
+    pub commits: usize,
+    /// Saturday and Sunday.
+    pub weekend: usize,
+    /// Outside [`WORK_HOURS`], on any day.
+    pub off_hours: usize,
+}
+
+impl ActivitySplit {
+    fn add(&mut self, t: u64) {
+        let weekday = (t / 86_400 + 4) % 7; // 0 = Sun
+        self.commits += 1;
+        if weekday == 0 || weekday == 6 {
+            self.weekend += 1;
+        }
+        if !WORK_HOURS.contains(&((t / 3_600) % 24)) {
+            self.off_hours += 1;
+        }
+    }
+
+    fn pct(&self, part: usize) -> f64 {
+        part as f64 * 100.0 / self.commits.max(1) as f64
+    }
+}
+
+/// Overall split plus one per author for the `top` authors by commits (ties
+/// by name).
+pub fn activity_summary(
+    pairs: &[(String, u64)],
+    top: usize,
+) -> (ActivitySplit, Vec<(String, ActivitySplit)>) {
+    let mut overall = ActivitySplit::default();
+    let mut by_author: std::collections::BTreeMap<&str, ActivitySplit> = Default::default();
+    for (name, t) in pairs {
+        overall.add(*t);
+        by_author.entry(name).or_default().add(*t);
+    }
+    let mut authors: Vec<(String, ActivitySplit)> = by_author
+        .into_iter()
+        .map(|(name, split)| (name.to_string(), split))
+        .collect();
+    authors.sort_by(|a, b| b.1.commits.cmp(&a.1.commits).then_with(|| a.0.cmp(&b.0)));
+    authors.truncate(top);
+    (overall, authors)
+}
+
+fn print_activity_row(out: &mut impl Write, label: &str, split: &ActivitySplit) -> io::Result<()> {
+    writeln!(
+        out,
+        "| {:<24} | {:>7} | {:>6.1}% | {:>6.1}% | {:>6.1}% | {:>6.1}% |",
+        crate::output::truncate(label, 24),
+        split.commits,
+        split.pct(split.commits - split.weekend),
+        split.pct(split.weekend),
+        split.pct(split.commits - split.off_hours),
+        split.pct(split.off_hours)
+    )?;
+    Ok(())
+}
+
+/// `--summary`: weekday/weekend and working/off-hours shares, overall and for
+/// the top authors.
+pub fn run_code_frequency_summary(
+    weeks: Option<usize>,
+    top: usize,
+) -> Result<(), GitInsightsError> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| format!("clock error: {e}"))?
+        .as_secs();
+    let pairs: Vec<(String, u64)> = collect_author_timestamps()?
+        .into_iter()
+        .filter(|(_, t)| in_week_window(*t, weeks, now))
+        .collect();
+    Ok(render_activity_summary(
+        &mut io::stdout().lock(),
+        &pairs,
+        top,
+    )?)
+}
+
+fn render_activity_summary(
+    out: &mut impl Write,
+    pairs: &[(String, u64)],
+    top: usize,
+) -> io::Result<()> {
+    if pairs.is_empty() {
+        writeln!(out, "(no commits)")?;
+        return Ok(());
+    }
+    let (overall, authors) = activity_summary(pairs, top);
+    writeln!(
+        out,
+        "| {:<24} | {:>7} | {:>7} | {:>7} | {:>7} | {:>7} |",
+        "author", "commits", "weekday", "weekend", "work", "off"
+    )?;
+    writeln!(
+        out,
+        "|:{:-<25}|{:->9}|{:->9}|{:->9}|{:->9}|{:->9}|",
+        "", "", "", "", "", ""
+    )?;
+    print_activity_row(out, "(all)", &overall)?;
+    for (name, split) in &authors {
+        print_activity_row(out, name, split)?;
+    }
+    writeln!(out)?;
+    writeln!(
+        out,
+        "Weekend = Sat/Sun; work = {:02}:00-{:02}:00, any day. Times: {}.",
+        WORK_HOURS.start,
+        WORK_HOURS.end,
+        crate::filter::active().time_zone.label()
+    )?;
+    Ok(())
+}
+
+// Additions vs deletions.
+
+/// Lines added and deleted side by side, both shaded against the busiest
+/// cell of either panel (or of the weekday with `--normalize row`) so the
+/// two are directly comparable. With `table`, one numeric table each.
+fn render_added_deleted(
+    out: &mut impl Write,
+    row_labels: &[String],
+    added: &[Vec<usize>],
+    deleted: &[Vec<usize>],
+    color: bool,
+    table: bool,
+    scale: HeatmapScale,
+) -> io::Result<()> {
+    let dim = color && !table;
+    if dim {
+        write!(out, "\x1b[90m")?;
+    }
+    let shading = match scale {
+        HeatmapScale::Global => String::new(),
+        other => format!(", shading: {}", other.name()),
+    };
+    writeln!(
+        out,
+        "Heatmap: Lines added vs deleted, Day-of-Week x Hour-of-Day ({}), unit: lines/hour{}",
+        crate::filter::active().time_zone.label(),
+        shading
+    )?;
+    if dim {
+        write!(out, "\x1b[0m")?;
+    }
+    let total = |rows: &[Vec<usize>]| rows.iter().flatten().sum::<usize>();
+    let (total_added, total_deleted) = (total(added), total(deleted));
+    if total_added + total_deleted == 0 {
+        writeln!(out, "(no line changes)")?;
+        return Ok(());
+    }
+    if table {
+        for (title, rows) in [("Added", added), ("Deleted", deleted)] {
+            writeln!(out, "{}", title)?;
+            if color {
+                render_heatmap_table_rows_x_24_colored(out, rows, row_labels, scale)?;
             } else {
-                color_for_level_rich(shade, 10)
-            };
-            print!("| {}{:>w$}{} ", code, v, ANSI_RESET, w = cell_w);
+                render_heatmap_table_rows_x_24(out, rows, row_labels)?;
+            }
         }
-        println!("|");
+        return Ok(());
+    }
+    print_ramp_legend_rich(out, color, "lines/hour")?;
+    writeln!(out)?;
 
-        out.clear();
-        push_sep(&mut out);
-        print!("{}", out);
+    let max = added
+        .iter()
+        .chain(deleted)
+        .flatten()
+        .copied()
+        .max()
+        .unwrap_or(0);
+    let label_w = row_labels
+        .iter()
+        .map(|l| l.chars().count())
+        .max()
+        .unwrap_or(0)
+        + 1;
+    let panel_w = label_w + 24;
+    let gap = " ".repeat(PANEL_GAP);
+    let added_title = format!("added (+{})", total_added);
+    writeln!(
+        out,
+        "{:<w$}{}deleted (-{})",
+        added_title,
+        gap,
+        total_deleted,
+        w = panel_w
+    )?;
+    for (r, label) in row_labels.iter().enumerate() {
+        let row_max = added[r]
+            .iter()
+            .chain(&deleted[r])
+            .copied()
+            .max()
+            .unwrap_or(0);
+        let panel = |rows: &[Vec<usize>]| {
+            let mut line = format!("{:<w$}", label, w = label_w);
+            for &v in &rows[r] {
+                let (v, max) = scale.scale(v, row_max, max);
+                push_panel_cell(&mut line, v, max, color);
+            }
+            line
+        };
+        writeln!(out, "{}{}{}", panel(added), gap, panel(deleted))?;
     }
+    let axis = format!("{:<w$}{}", "", &panel_hour_axis()[3..], w = label_w);
+    writeln!(out, "{}{}{}", axis, gap, axis.trim_end())?;
+    Ok(())
 }
 
-/// Runner.
+// Runner.
 
 pub fn run_code_frequency_with_options(
     group: Option<Group>,
@@ -601,107 +1219,384 @@ pub fn run_code_frequency_with_options(
     weeks: Option<usize>,
     color: bool,
     table: bool,
-) -> Result<(), String> {
+) -> Result<(), GitInsightsError> {
+    run_code_frequency_sized(
+        group,
+        heatmap,
+        weeks,
+        color,
+        table,
+        ChartSize::default(),
+        HeatmapScale::default(),
+    )
+}
+
+/// Code-frequency counts for one histogram or heatmap, ready to render.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum CodeFrequencyData {
+    /// One count per label (hour, weekday, day of month, month or year).
+    Histogram {
+        labels: Vec<String>,
+        counts: Vec<usize>,
+        /// e.g. `commits/hour` or `lines/day`.
+        unit: String,
+    },
+    /// One row of 24 hourly counts per label.
+    Heatmap {
+        /// e.g. `Day-of-Week x Hour-of-Day`.
+        title: &'static str,
+        row_labels: Vec<String>,
+        rows: Vec<Vec<usize>>,
+        /// `commits/hour` or `lines/hour`.
+        unit: String,
+    },
+    /// Lines added and lines deleted per day-of-week x hour-of-day.
+    AddedDeleted {
+        row_labels: Vec<String>,
+        added: Vec<Vec<usize>>,
+        deleted: Vec<Vec<usize>>,
+    },
+}
+
+/// Title, row labels and 24-column rows of one heatmap.
+type HeatmapRows = (&'static str, Vec<String>, Vec<Vec<usize>>);
+
+/// Row labels, lines added and lines deleted of the add-del heatmap.
+pub type AddedDeletedRows = (Vec<String>, Vec<Vec<usize>>, Vec<Vec<usize>>);
+
+fn heatmap_rows(
+    kind: HeatmapKind,
+    points: &[(u64, usize)],
+    weeks: Option<usize>,
+    now: u64,
+) -> Result<HeatmapRows, GitInsightsError> {
+    let points = || points.iter().copied();
+    Ok(match kind {
+        HeatmapKind::DowByHod => {
+            let week_start = crate::filter::active().week_start;
+            (
+                "Day-of-Week x Hour-of-Day",
+                week_start
+                    .day_names()
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+                week_start
+                    .reorder(&weighted_grid::<7>(points(), weekday_of))
+                    .iter()
+                    .map(|r| r.to_vec())
+                    .collect(),
+            )
+        }
+        HeatmapKind::DomByHod => (
+            "Day-of-Month x Hour-of-Day",
+            (1..=31).map(|d| format!("{:02}", d)).collect(),
+            weighted_grid::<31>(points(), day_of_month_of)
+                .iter()
+                .map(|r| r.to_vec())
+                .collect(),
+        ),
+        HeatmapKind::TypeByHod => {
+            let (labels, rows) = heatmap_type_by_hod(&commit_subjects(weeks, now)?);
+            ("Commit type x Hour-of-Day", labels, rows)
+        }
+        HeatmapKind::AddDelByHod => {
+            // Stacked for single-grid output: added rows, then deleted rows.
+            let (days, added, deleted) = added_deleted_rows(weeks, now)?;
+            let labels = days
+                .iter()
+                .map(|d| format!("+{}", d))
+                .chain(days.iter().map(|d| format!("-{}", d)))
+                .collect();
+            (
+                "Lines added (+) and deleted (-) x Hour-of-Day",
+                labels,
+                added.into_iter().chain(deleted).collect(),
+            )
+        }
+    })
+}
+
+/// Day-of-week labels with the lines added and the lines deleted per
+/// weekday x hour in the week window, in `--week-start` order.
+fn added_deleted_rows(
+    weeks: Option<usize>,
+    now: u64,
+) -> Result<AddedDeletedRows, GitInsightsError> {
+    let changes: Vec<(u64, usize, usize)> = collect_line_changes()?
+        .into_iter()
+        .filter(|&(t, _, _)| in_week_window(t, weeks, now))
+        .collect();
+    Ok(added_deleted_grids(
+        &changes,
+        crate::filter::active().week_start,
+    ))
+}
+
+/// Day-of-week x hour grids of lines added and lines deleted, rows in
+/// `week_start` order.
+pub fn added_deleted_grids(
+    changes: &[(u64, usize, usize)],
+    week_start: crate::filter::WeekStart,
+) -> AddedDeletedRows {
+    let grid = |pick: fn(&(u64, usize, usize)) -> usize| -> Vec<Vec<usize>> {
+        let points = changes.iter().map(|c| (c.0, pick(c)));
+        week_start
+            .reorder(&weighted_grid::<7>(points, weekday_of))
+            .iter()
+            .map(|r| r.to_vec())
+            .collect()
+    };
+    (
+        week_start
+            .day_names()
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+        grid(|c| c.1),
+        grid(|c| c.2),
+    )
+}
+
+/// Counts behind `code-frequency` without printing: the `heatmap` if given,
+/// else the `group` histogram (hour of day by default).
+pub fn compute_code_frequency(
+    group: Option<Group>,
+    heatmap: Option<HeatmapKind>,
+    weeks: Option<usize>,
+) -> Result<CodeFrequencyData, GitInsightsError> {
+    compute_code_frequency_weighted(group, heatmap, weeks, FrequencyWeight::Commits)
+}
+
+/// Like [`compute_code_frequency`] with each commit counted by `weight`.
+/// The commit-type heatmap only counts commits and the add-del heatmap
+/// always counts lines.
+pub fn compute_code_frequency_weighted(
+    group: Option<Group>,
+    heatmap: Option<HeatmapKind>,
+    weeks: Option<usize>,
+    weight: FrequencyWeight,
+) -> Result<CodeFrequencyData, GitInsightsError> {
+    if weight == FrequencyWeight::Lines {
+        let unweightable = match heatmap {
+            Some(HeatmapKind::TypeByHod) => Some("type-hod"),
+            Some(HeatmapKind::AddDelByHod) => Some("add-del"),
+            _ => None,
+        };
+        if let Some(kind) = unweightable {
+            return Err(
+                format!("--weight lines cannot be combined with --heatmap {}", kind).into(),
+            );
+        }
+    }
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .map_err(|e| format!("clock error: {e}"))?
         .as_secs();
-    let ts_all = collect_commit_timestamps()?;
-    let ts = filter_by_weeks(&ts_all, weeks, now);
-
-    match heatmap {
-        Some(HeatmapKind::DowByHod) => {
-            let grid = heatmap_dow_by_hod(&ts);
-            let labels = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
-            if color && !table {
-                print!("\x1b[90m");
-            }
-            println!("Heatmap: Day-of-Week x Hour-of-Day (UTC), unit: commits/hour");
-            if color && !table {
-                print!("\x1b[0m");
+    if let Some(HeatmapKind::AddDelByHod) = heatmap {
+        let (row_labels, added, deleted) = added_deleted_rows(weeks, now)?;
+        return Ok(CodeFrequencyData::AddedDeleted {
+            row_labels,
+            added,
+            deleted,
+        });
+    }
+    let all: Vec<(u64, usize)> = match weight {
+        FrequencyWeight::Commits => unweighted(&collect_commit_timestamps()?).collect(),
+        FrequencyWeight::Lines => collect_line_changes()?
+            .into_iter()
+            .map(|(t, added, deleted)| (t, added + deleted))
+            .collect(),
+    };
+    let points: Vec<(u64, usize)> = all
+        .into_iter()
+        .filter(|&(t, _)| in_week_window(t, weeks, now))
+        .collect();
+    let unit = |per: &str| format!("{}/{}", weight.name(), per);
+
+    if let Some(kind) = heatmap {
+        let (title, row_labels, rows) = heatmap_rows(kind, &points, weeks, now)?;
+        return Ok(CodeFrequencyData::Heatmap {
+            title,
+            row_labels,
+            rows,
+            unit: unit("hour"),
+        });
+    }
+    let points = || points.iter().copied();
+    let (labels, counts, per): (Vec<String>, Vec<usize>, _) =
+        match group.unwrap_or(Group::HourOfDay) {
+            Group::HourOfDay => (
+                (0..24).map(|h| format!("{:02}", h)).collect(),
+                weighted_bins::<24>(points(), hour_of).to_vec(),
+                "hour",
+            ),
+            Group::DayOfWeek => {
+                let week_start = crate::filter::active().week_start;
+                (
+                    week_start
+                        .day_names()
+                        .iter()
+                        .map(|s| s.to_string())
+                        .collect(),
+                    week_start.reorder(&weighted_bins::<7>(points(), weekday_of)),
+                    "day",
+                )
             }
-            if !table {
-                print_ramp_legend_rich(color, "commits/hour");
-                println!();
+            Group::DayOfMonth => (
+                (1..=31).map(|d| format!("{:02}", d)).collect(),
+                weighted_bins::<31>(points(), day_of_month_of).to_vec(),
+                "day",
+            ),
+            Group::MonthOfYear => (
+                MONTH_NAMES.iter().map(|m| m.to_string()).collect(),
+                weighted_bins::<12>(points(), month_of).to_vec(),
+                "month",
+            ),
+            Group::Year => {
+                let (years, counts) = weighted_years(points())
+                    .into_iter()
+                    .map(|(y, c)| (y.to_string(), c))
+                    .unzip();
+                (years, counts, "year")
             }
+        };
+    Ok(CodeFrequencyData::Histogram {
+        labels,
+        counts,
+        unit: unit(per),
+    })
+}
 
-            let rows: Vec<Vec<usize>> = (0..7).map(|r| grid[r].to_vec()).collect();
-            let row_labels: Vec<String> = labels.iter().map(|s| s.to_string()).collect();
-            if table {
-                if color {
-                    render_heatmap_table_rows_x_24_colored(&rows, &row_labels, true);
-                } else {
-                    render_heatmap_table_rows_x_24(&rows, &row_labels);
-                }
-            } else {
-                render_heatmap_rows_x_24(&rows, &row_labels, color);
-            }
-        }
-        Some(HeatmapKind::DomByHod) => {
-            let grid = heatmap_dom_by_hod(&ts);
+/// Like [`run_code_frequency_with_options`] with an explicit histogram bar
+/// width and heatmap shading.
+pub fn run_code_frequency_sized(
+    group: Option<Group>,
+    heatmap: Option<HeatmapKind>,
+    weeks: Option<usize>,
+    color: bool,
+    table: bool,
+    size: ChartSize,
+    scale: HeatmapScale,
+) -> Result<(), GitInsightsError> {
+    let data = compute_code_frequency(group, heatmap, weeks)?;
+    Ok(render_code_frequency(
+        &mut io::stdout().lock(),
+        &data,
+        color,
+        table,
+        size,
+        scale,
+    )?)
+}
+
+/// Print `data` as `code-frequency` does: bars or a table for a histogram, a
+/// shaded grid or a table for a heatmap, with cells shaded by `scale`.
+pub fn render_code_frequency(
+    out: &mut impl Write,
+    data: &CodeFrequencyData,
+    color: bool,
+    table: bool,
+    size: ChartSize,
+    scale: HeatmapScale,
+) -> io::Result<()> {
+    match data {
+        CodeFrequencyData::Heatmap {
+            title,
+            row_labels,
+            rows,
+            unit,
+        } => {
             if color && !table {
-                print!("\x1b[90m");
+                write!(out, "\x1b[90m")?;
             }
-            println!("Heatmap: Day-of-Month x Hour-of-Day (UTC), unit: commits/hour");
+            let shading = match scale {
+                HeatmapScale::Global => String::new(),
+                other => format!(", shading: {}", other.name()),
+            };
+            writeln!(
+                out,
+                "Heatmap: {} ({}), unit: {}{}",
+                title,
+                crate::filter::active().time_zone.label(),
+                unit,
+                shading
+            )?;
             if color && !table {
-                print!("\x1b[0m");
+                write!(out, "\x1b[0m")?;
+            }
+            if rows.is_empty() {
+                writeln!(out, "(no commits)")?;
+                return Ok(());
             }
             if !table {
-                print_ramp_legend_rich(color, "commits/hour");
-                println!();
+                print_ramp_legend_rich(out, color, unit)?;
+                writeln!(out)?;
             }
 
-            let rows: Vec<Vec<usize>> = (0..31).map(|r| grid[r].to_vec()).collect();
-            let row_labels: Vec<String> = (1..=31).map(|d| format!("{:02}", d)).collect();
             if table {
                 if color {
-                    render_heatmap_table_rows_x_24_colored(&rows, &row_labels, true);
+                    render_heatmap_table_rows_x_24_colored(out, rows, row_labels, scale)?;
                 } else {
-                    render_heatmap_table_rows_x_24(&rows, &row_labels);
+                    render_heatmap_table_rows_x_24(out, rows, row_labels)?;
                 }
             } else {
-                render_heatmap_rows_x_24(&rows, &row_labels, color);
+                render_heatmap_rows_x_24(out, rows, row_labels, color, scale)?;
             }
         }
-        None => {
-            // Histogram mode
-            let grp = group.unwrap_or(Group::HourOfDay);
-            match grp {
-                Group::HourOfDay => {
-                    let bins = histogram_hour_of_day(&ts);
-                    let labels: Vec<String> = (0..24).map(|h| format!("{:02}", h)).collect();
-                    let lab_refs: Vec<&str> = labels.iter().map(|s| s.as_str()).collect();
-                    if table {
-                        render_histogram_table(&lab_refs, &bins, color);
-                    } else {
-                        render_histogram_labeled(&lab_refs, &bins, color, "commits/hour");
-                    }
-                }
-                Group::DayOfWeek => {
-                    let bins = histogram_day_of_week(&ts);
-                    let labels = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
-                    if table {
-                        render_histogram_table(&labels, &bins, color);
-                    } else {
-                        render_histogram_labeled(&labels, &bins, color, "commits/day");
-                    }
-                }
-                Group::DayOfMonth => {
-                    let bins = histogram_day_of_month(&ts);
-                    let labels: Vec<String> = (1..=31).map(|d| format!("{:02}", d)).collect();
-                    let lab_refs: Vec<&str> = labels.iter().map(|s| s.as_str()).collect();
-                    if table {
-                        render_histogram_table(&lab_refs, &bins, color);
-                    } else {
-                        render_histogram_labeled(&lab_refs, &bins, color, "commits/day");
-                    }
-                }
+        CodeFrequencyData::AddedDeleted {
+            row_labels,
+            added,
+            deleted,
+        } => {
+            render_added_deleted(out, row_labels, added, deleted, color, table, scale)?;
+        }
+        CodeFrequencyData::Histogram {
+            labels,
+            counts,
+            unit,
+        } => {
+            let lab_refs: Vec<&str> = labels.iter().map(|s| s.as_str()).collect();
+            if table {
+                render_histogram_table(out, &lab_refs, counts, color, size)?;
+            } else {
+                render_histogram_labeled(out, &lab_refs, counts, color, unit, size)?;
             }
         }
     }
     Ok(())
 }
 
+/// Export a code-frequency heatmap as an SVG file, or PNG for `.png` paths
+/// with the `charts` feature.
+pub fn export_code_frequency_heatmap_svg(
+    heatmap: HeatmapKind,
+    weeks: Option<usize>,
+    path: &str,
+) -> Result<(), GitInsightsError> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| format!("clock error: {e}"))?
+        .as_secs();
+    let unit = match heatmap {
+        HeatmapKind::AddDelByHod => "lines/hour",
+        _ => "commits/hour",
+    };
+    let ts_all = collect_commit_timestamps()?;
+    let points: Vec<(u64, usize)> = unweighted(&filter_by_weeks(&ts_all, weeks, now)).collect();
+
+    let (kind, row_labels, rows) = heatmap_rows(heatmap, &points, weeks, now)?;
+    let title = format!(
+        "{} ({}), {}",
+        kind,
+        crate::filter::active().time_zone.label(),
+        unit
+    );
+    let col_labels: Vec<String> = (0..24).map(|h| format!("{:02}", h)).collect();
+    crate::svg::write_heatmap_chart(path, &rows, &row_labels, &col_labels, &title)
+}
+
 /// Convert Unix seconds to (y,m,d) UTC.
 pub fn ymd_from_unix(t: u64) -> (i32, u32, u32) {
     let days = (t / 86_400) as i64;
@@ -724,9 +1619,26 @@ fn civil_from_days(z: i64) -> (i32, u32, u32) {
     (y, m as u32, d)
 }
 
+/// Convert a UTC calendar date to Unix seconds at 00:00.
+pub fn unix_from_ymd(y: i32, m: u32, d: u32) -> u64 {
+    days_from_civil(y, m, d).max(0) as u64 * 86_400
+}
+
+/// Inverse of `civil_from_days` (same source).
+fn days_from_civil(y: i32, m: u32, d: u32) -> i64 {
+    let y = y as i64 - (m <= 2) as i64;
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400; // [0, 399]
+    let mp = (m as i64 + 9) % 12; // [0, 11]
+    let doy = (153 * mp + 2) / 5 + d as i64 - 1; // [0, 365]
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy; // [0, 146096]
+    era * 146_097 + doe - 719_468
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::filter::WeekStart;
     use std::env;
     use std::fs;
     use std::io::Write;
@@ -866,15 +1778,7 @@ mod tests {
                 .arg("--allow-empty")
                 .arg("-m")
                 .arg("test");
-            let _day = ts / 86_400;
-            let h = (ts / 3_600) % 24;
-            let min = (ts / 60) % 60;
-            let sec = ts % 60;
-            let (y, mth, d) = super::ymd_from_unix(ts);
-            let date = format!(
-                "{:04}-{:02}-{:02} {:02}:{:02}:{:02} +0000",
-                y, mth, d, h, min, sec
-            );
+            let date = git_date(ts);
             c.env("GIT_AUTHOR_NAME", name);
             c.env("GIT_AUTHOR_EMAIL", email);
             c.env("GIT_COMMITTER_NAME", name);
@@ -891,6 +1795,40 @@ mod tests {
         }
     }
 
+    impl TempRepo {
+        /// Re-date the initial commit so wall-clock-sensitive tests do not
+        /// depend on when the suite runs.
+        fn backdate_init(&self, ts: u64) {
+            let date = git_date(ts);
+            let out = Command::new("git")
+                .current_dir(&self.path)
+                .args(["-c", "commit.gpgsign=false"])
+                .args(["commit", "--amend", "--no-edit", "--no-verify", "-q"])
+                .env("GIT_AUTHOR_DATE", &date)
+                .env("GIT_COMMITTER_DATE", &date)
+                .output()
+                .expect("git commit spawn failed");
+            assert!(
+                out.status.success(),
+                "git commit --amend failed: {}",
+                String::from_utf8_lossy(&out.stderr)
+            );
+        }
+    }
+
+    fn git_date(ts: u64) -> String {
+        let (y, mth, d) = super::ymd_from_unix(ts);
+        format!(
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02} +0000",
+            y,
+            mth,
+            d,
+            (ts / 3_600) % 24,
+            (ts / 60) % 60,
+            ts % 60
+        )
+    }
+
     impl Drop for TempRepo {
         fn drop(&mut self) {
             let _ = env::set_current_dir(&self.old_dir);
@@ -927,6 +1865,14 @@ mod tests {
         assert_eq!((m, d), (1, 31));
     }
 
+    #[test]
+    fn test_unix_from_ymd_roundtrip() {
+        assert_eq!(unix_from_ymd(1970, 1, 1), 0);
+        assert_eq!(unix_from_ymd(2020, 1, 1), 1_577_836_800);
+        let t = unix_from_ymd(2024, 2, 29);
+        assert_eq!(ymd_from_unix(t), (2024, 2, 29));
+    }
+
     #[test]
     fn test_histogram_day_of_month_basic() {
         // 1970-01-01 is day 1, 1970-01-31 is day 31
@@ -937,6 +1883,19 @@ mod tests {
         assert_eq!(bins[30], 2); // day 31
     }
 
+    #[test]
+    fn test_histogram_month_of_year_and_year() {
+        let jan_2021 = unix_from_ymd(2021, 1, 15);
+        let dec_2023 = unix_from_ymd(2023, 12, 31) + 86_399;
+        let bins = histogram_month_of_year(&[jan_2021, dec_2023, dec_2023]);
+        assert_eq!((bins[0], bins[11], bins.iter().sum::<usize>()), (1, 2, 3));
+        assert_eq!(
+            histogram_year(&[dec_2023, jan_2021, dec_2023]),
+            vec![(2021, 1), (2022, 0), (2023, 2)]
+        );
+        assert!(histogram_year(&[]).is_empty());
+    }
+
     #[test]
     fn test_end_to_end_from_temp_repo_histogram_hod() {
         let repo = TempRepo::new("git-insights-freq");
@@ -947,7 +1906,7 @@ mod tests {
             "alice@test_git_insights.com",
             "a.txt",
             "a\n",
-            base_day + 0,
+            base_day,
         );
         repo.commit_with_epoch(
             "Bob",
@@ -961,6 +1920,171 @@ mod tests {
             .expect("ok");
     }
 
+    #[test]
+    fn test_compute_code_frequency_from_temp_repo() {
+        let repo = TempRepo::new("git-insights-freq-data");
+        let base_day = 20 * 86_400;
+        // Keep the init commit out of every cell asserted on below.
+        repo.backdate_init(base_day - 86_400 + 7 * 3_600);
+        repo.commit_with_epoch("Alice", "alice@x.io", "a.txt", "a\n", base_day);
+        repo.commit_with_epoch(
+            "Bob",
+            "bob@x.io",
+            "b.txt",
+            "b\nc\nd\n",
+            base_day + 13 * 3_600,
+        );
+
+        let total = match compute_code_frequency(None, None, None).expect("histogram") {
+            CodeFrequencyData::Histogram {
+                labels,
+                counts,
+                unit,
+            } => {
+                assert_eq!((labels.len(), unit.as_str()), (24, "commits/hour"));
+                assert_eq!(counts[13], 1);
+                counts.iter().sum::<usize>()
+            }
+            other => panic!("Expected a histogram, got {:?}", other),
+        };
+        match compute_code_frequency(None, Some(HeatmapKind::DowByHod), None).expect("heatmap") {
+            CodeFrequencyData::Heatmap { title, rows, .. } => {
+                assert_eq!(title, "Day-of-Week x Hour-of-Day");
+                assert_eq!(rows.iter().flatten().sum::<usize>(), total);
+            }
+            other => panic!("Expected a heatmap, got {:?}", other),
+        }
+        let lines = compute_code_frequency_weighted(None, None, None, FrequencyWeight::Lines);
+        match lines.expect("weighted histogram") {
+            CodeFrequencyData::Histogram { counts, unit, .. } => {
+                assert_eq!(unit, "lines/hour");
+                assert_eq!((counts[0], counts[13]), (1, 3));
+            }
+            other => panic!("Expected a histogram, got {:?}", other),
+        }
+        assert!(compute_code_frequency_weighted(
+            None,
+            Some(HeatmapKind::TypeByHod),
+            None,
+            FrequencyWeight::Lines
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_add_del_heatmap_from_temp_repo() {
+        let repo = TempRepo::new("git-insights-freq-add-del");
+        let base_day = 20 * 86_400;
+        repo.backdate_init(base_day - 86_400 + 7 * 3_600);
+        // Bob adds three lines on Wednesday at 13h.
+        repo.commit_with_epoch(
+            "Bob",
+            "bob@x.io",
+            "b.txt",
+            "b\nc\nd\n",
+            base_day + 13 * 3_600,
+        );
+        // Bob trims b.txt on Thursday at 05h: two lines deleted.
+        fs::write(repo.path.join("b.txt"), "").unwrap();
+        repo.commit_with_epoch(
+            "Bob",
+            "bob@x.io",
+            "b.txt",
+            "b\n",
+            base_day + 86_400 + 5 * 3_600,
+        );
+        let data = compute_code_frequency(None, Some(HeatmapKind::AddDelByHod), None);
+        let data = data.expect("add-del heatmap");
+        match &data {
+            CodeFrequencyData::AddedDeleted {
+                row_labels,
+                added,
+                deleted,
+            } => {
+                assert_eq!(row_labels[4], "Thu");
+                assert_eq!(deleted.iter().flatten().sum::<usize>(), 2);
+                assert_eq!((added[3][13], deleted[4][5]), (3, 2));
+            }
+            other => panic!("Expected added/deleted grids, got {:?}", other),
+        }
+        let mut buf = Vec::new();
+        let (size, scale) = (Default::default(), Default::default());
+        render_code_frequency(&mut buf, &data, false, false, size, scale).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert!(text.starts_with("Heatmap: Lines added vs deleted"));
+        let thu = text
+            .lines()
+            .find(|l| l.starts_with("Thu "))
+            .expect("Thu row");
+        // Left panel empty; 2 of the shared peak of 3 in the right 05h column.
+        let right = format!("Thu{}Thu{}*", " ".repeat(1 + 24 + 3), " ".repeat(1 + 5));
+        assert_eq!(thu.trim_end(), right);
+        assert!(text.contains("deleted (-2)"));
+        assert!(compute_code_frequency_weighted(
+            None,
+            Some(HeatmapKind::AddDelByHod),
+            None,
+            FrequencyWeight::Lines
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_added_deleted_grids() {
+        let lines = [
+            "\x1f100\x1f100\x1fAnn\x1fann@x.io",
+            "",
+            "4\t1\tsrc/lib.rs",
+            "2\t0\tREADME.md",
+            // 1970-01-05 05:00 UTC, a Monday.
+            "\x1f363600\x1f363600\x1fBob\x1fbob@x.io",
+            "0\t3\tsrc/lib.rs",
+        ];
+        let changes =
+            line_changes_from_numstat(lines.iter(), |_, _| true, |_| false, |ct, _, _| Some(ct));
+        assert_eq!(changes, vec![(100, 6, 1), (363_600, 0, 3)]);
+
+        let (labels, added, deleted) = added_deleted_grids(&changes, WeekStart::Sunday);
+        assert_eq!(labels[0], "Sun");
+        // 1970-01-01 00:01:40 UTC was a Thursday.
+        assert_eq!((added[4][0], deleted[4][0]), (6, 1));
+        assert_eq!((added[1][5], deleted[1][5]), (0, 3));
+        assert_eq!(added.iter().flatten().sum::<usize>(), 6);
+        assert_eq!(deleted.iter().flatten().sum::<usize>(), 4);
+
+        let (labels, added, deleted) = added_deleted_grids(&changes, WeekStart::Monday);
+        assert_eq!((labels[0].as_str(), labels[3].as_str()), ("Mon", "Thu"));
+        assert_eq!((added[3][0], deleted[0][5]), (6, 3));
+    }
+
+    #[test]
+    fn test_line_weights_from_numstat() {
+        let lines = [
+            "\x1f100\x1f90\x1fAnn\x1fann@x.io",
+            "",
+            "10\t2\tsrc/lib.rs",
+            "-\t-\tlogo.png",
+            "5\t5\tvendor/x.rs",
+            "\x1f200\x1f190\x1fBot\x1fbot@x.io",
+            "100\t0\tsrc/lib.rs",
+            "\x1f300\x1f290\x1fBob\x1fbob@x.io\x1f+0100",
+            "\x1f400\x1f390\x1fBob\x1fbob@x.io",
+            "1\t1\tREADME.md",
+        ];
+        let points = line_changes_from_numstat(
+            lines.iter(),
+            |n, _| n != "Bot",
+            |p| p.starts_with("vendor/"),
+            |ct, _, offset| (ct != 400).then(|| ct + offset.len() as u64),
+        );
+        // Bob's empty commit weighs nothing; his commit at 400 is dropped.
+        assert_eq!(points, vec![(100, 10, 2), (305, 0, 0)]);
+        assert_eq!(FrequencyWeight::parse("lines"), Ok(FrequencyWeight::Lines));
+        assert!(FrequencyWeight::parse("bytes")
+            .unwrap_err()
+            .contains("Expected commits or lines"));
+    }
+
     #[test]
     fn test_heatmap_dow_by_hod_known_points() {
         // Verify exact binning without premature rounding:
@@ -1006,14 +2130,61 @@ mod tests {
             },
         ];
         let labels = vec!["A".to_string(), "B".to_string(), "C".to_string()];
-        super::render_heatmap_table_rows_x_24(&rows, &labels);
+        let mut buf = Vec::new();
+        super::render_heatmap_table_rows_x_24(&mut buf, &rows, &labels).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            super::build_heatmap_table_rows_x_24(&rows, &labels)
+        );
+    }
+
+    #[test]
+    fn test_render_code_frequency_to_buffer() {
+        let data = CodeFrequencyData::Histogram {
+            labels: vec!["Sun".to_string(), "Mon".to_string()],
+            counts: vec![1, 4],
+            unit: "commits/day".to_string(),
+        };
+        let mut buf = Vec::new();
+        render_code_frequency(
+            &mut buf,
+            &data,
+            false,
+            false,
+            Default::default(),
+            Default::default(),
+        )
+        .unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert!(text.starts_with("Histogram — unit: commits/day\n"));
+        let mon = text
+            .lines()
+            .find(|l| l.starts_with("Mon | "))
+            .expect("Mon bar");
+        assert!(mon.ends_with("# 4"));
+
+        let mut table = Vec::new();
+        render_code_frequency(
+            &mut table,
+            &data,
+            false,
+            true,
+            Default::default(),
+            Default::default(),
+        )
+        .unwrap();
+        let labels = ["Sun", "Mon"];
+        assert_eq!(
+            String::from_utf8(table).unwrap(),
+            build_histogram_table(&labels, &[1, 4], Default::default())
+        );
     }
 
     #[test]
     fn test_build_histogram_table_alignment() {
         let labels: Vec<&str> = vec!["A", "BB", "CCC"];
         let counts = vec![0usize, 3, 10];
-        let s = super::build_histogram_table(&labels, &counts);
+        let s = super::build_histogram_table(&labels, &counts, Default::default());
         let lines: Vec<&str> = s.lines().collect();
         // Collect data lines starting with '|' (including header)
         let pipe_lines: Vec<&str> = lines
@@ -1034,6 +2205,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_heatmap_type_by_hod() {
+        let commits = vec![
+            (23 * 3_600, "fix: late".to_string()),
+            (86_400 + 23 * 3_600, "fix(cli): later".to_string()),
+            (9 * 3_600, "feat: morning".to_string()),
+            (10 * 3_600, "Update docs".to_string()),
+        ];
+        let (labels, rows) = super::heatmap_type_by_hod(&commits);
+        assert_eq!(labels, vec!["feat", "fix", "other"]);
+        assert_eq!(rows[1][23], 2);
+        assert_eq!(rows[0][9], 1);
+        assert_eq!(rows[2].iter().sum::<usize>(), 1);
+        assert!(rows.iter().all(|r| r.len() == 24));
+    }
+
+    #[test]
+    fn test_build_histogram_table_width() {
+        let size = crate::visualize::ChartSize {
+            width: Some(30),
+            height: None,
+        };
+        let s = super::build_histogram_table(&["A", "B"], &[1, 2], size);
+        let row = s.lines().find(|l| l.starts_with("|     B")).expect("row B");
+        let bar = row.rsplit('|').nth(1).expect("bar cell");
+        assert_eq!(bar.len(), 32);
+        assert_eq!(bar.trim(), "#".repeat(30));
+    }
+
     #[test]
     fn test_build_heatmap_table_alignment() {
         let mut rows = vec![vec![0usize; 24]; 2];
@@ -1065,7 +2265,7 @@ mod tests {
         let _repo = TempRepo::new("git-insights-freq-table-hod");
         // two commits at different hours
         let base_day = 30 * 86_400;
-        _repo.commit_with_epoch("A", "a@test_git_insights.com", "a.txt", "a\n", base_day + 0);
+        _repo.commit_with_epoch("A", "a@test_git_insights.com", "a.txt", "a\n", base_day);
         _repo.commit_with_epoch(
             "B",
             "b@test_git_insights.com",
@@ -1116,6 +2316,30 @@ mod tests {
         assert_eq!(dom[0].len(), 24);
     }
 
+    #[test]
+    fn test_export_heatmap_svg_from_temp_repo() {
+        let repo = TempRepo::new("git-insights-freq-svg");
+        let base_day = 50 * 86_400;
+        repo.commit_with_epoch(
+            "E",
+            "e@test_git_insights.com",
+            "e.txt",
+            "e\n",
+            base_day + 9 * 3_600,
+        );
+        let out = repo.path.join("cf.svg");
+        super::export_code_frequency_heatmap_svg(
+            HeatmapKind::DomByHod,
+            None,
+            out.to_str().unwrap(),
+        )
+        .expect("ok");
+        let svg = fs::read_to_string(&out).expect("svg written");
+        assert!(svg.contains("Day-of-Month x Hour-of-Day"));
+        assert!(svg.contains(">31</text>"));
+        assert!(svg.contains(">23</text>"));
+    }
+
     #[test]
     fn test_filter_by_weeks_empty_when_zero() {
         let now = 10 * 7 * 86_400;
@@ -1123,4 +2347,49 @@ mod tests {
         let out = filter_by_weeks(&ts, Some(0), now);
         assert!(out.is_empty());
     }
+
+    #[test]
+    fn test_heatmaps_per_author() {
+        // 2024-01-01 was a Monday.
+        let monday_9 = unix_from_ymd(2024, 1, 1) + 9 * 3_600;
+        let pairs = vec![
+            ("Bob".to_string(), monday_9),
+            ("Ann".to_string(), monday_9),
+            ("Ann".to_string(), monday_9 + 86_400 + 3_600),
+            ("Cat".to_string(), monday_9),
+        ];
+        let panels = heatmaps_per_author(&pairs, 2);
+        let names: Vec<&str> = panels.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Ann", "Bob"]);
+        assert_eq!(panels[0].commits, 2);
+        assert_eq!(panels[0].grid[1][9], 1);
+        assert_eq!(panels[0].grid[2][10], 1);
+        assert_eq!(panel_hour_axis().len(), PANEL_WIDTH);
+    }
+
+    #[test]
+    fn test_activity_summary() {
+        // 2024-01-06 is a Saturday; 2024-01-08 a Monday.
+        let sat = unix_from_ymd(2024, 1, 6);
+        let mon = unix_from_ymd(2024, 1, 8);
+        let pairs = vec![
+            ("Ann".to_string(), mon + 10 * 3_600),
+            ("Ann".to_string(), mon + 17 * 3_600 + 3_599),
+            ("Ann".to_string(), mon + 18 * 3_600),
+            ("Bob".to_string(), sat + 11 * 3_600),
+        ];
+        let (overall, authors) = activity_summary(&pairs, 1);
+        assert_eq!(
+            overall,
+            ActivitySplit {
+                commits: 4,
+                weekend: 1,
+                off_hours: 1
+            }
+        );
+        assert_eq!(authors.len(), 1);
+        assert_eq!(authors[0].0, "Ann");
+        assert_eq!((authors[0].1.weekend, authors[0].1.off_hours), (0, 1));
+        assert_eq!(overall.pct(overall.weekend), 25.0);
+    }
 }
@@ -0,0 +1,625 @@
+use crate::error::GitInsightsError;
+use crate::git::{run_command, run_command_lines};
+use crate::merges::{blame_line_authors, run_blame_head, MergeResolver};
+use crate::output::{finish_progress, print_progress, truncate};
+use crate::stats::tracked_text_files_head;
+use std::collections::HashMap;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Locations GitHub searches for a CODEOWNERS file, in priority order.
+pub const CODEOWNERS_PATHS: &[&str] = &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+/// One `pattern owner...` line of a CODEOWNERS file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeownersRule {
+    /// 1-based line number in the CODEOWNERS file.
+    pub line: usize,
+    pub pattern: String,
+    pub owners: Vec<String>,
+}
+
+/// Parse CODEOWNERS text, skipping blank lines and comments.
+pub fn parse_codeowners(text: &str) -> Vec<CodeownersRule> {
+    let mut rules = Vec::new();
+    for (idx, raw) in text.lines().enumerate() {
+        let line = match raw.find(" #") {
+            Some(pos) => &raw[..pos],
+            None => raw,
+        };
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        let pattern = parts.next().unwrap_or_default().to_string();
+        rules.push(CodeownersRule {
+            line: idx + 1,
+            pattern,
+            owners: parts.map(str::to_string).collect(),
+        });
+    }
+    rules
+}
+
+/// Glob match where `*` and `?` stay within a path segment and `**` spans segments.
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some(b'*') if pattern.get(1) == Some(&b'*') => {
+            let mut rest = &pattern[2..];
+            if rest.first() == Some(&b'/') {
+                rest = &rest[1..];
+                // `**/` also matches zero directories.
+                if glob_match(rest, text) {
+                    return true;
+                }
+            }
+            (0..=text.len()).any(|i| {
+                (i == 0 || text[i - 1] == b'/' || rest.is_empty()) && glob_match(rest, &text[i..])
+            })
+        }
+        Some(b'*') => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != b'/')
+            .any(|i| glob_match(&pattern[1..], &text[i..])),
+        Some(b'?') => !text.is_empty() && text[0] != b'/' && glob_match(&pattern[1..], &text[1..]),
+        Some(&c) => !text.is_empty() && text[0] == c && glob_match(&pattern[1..], &text[1..]),
+    }
+}
+
+/// Whether a CODEOWNERS (gitignore-style) pattern covers `path`.
+///
+/// Patterns without an inner `/` match at any depth; a pattern that matches a
+/// directory covers everything beneath it; a trailing `/` only matches directories.
+pub fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let dir_only = pattern.ends_with('/');
+    let trimmed = pattern.trim_end_matches('/');
+    let anchored = trimmed.contains('/');
+    let trimmed = trimmed.trim_start_matches('/');
+    let full = if anchored {
+        trimmed.to_string()
+    } else {
+        format!("**/{}", trimmed)
+    };
+
+    let mut candidates: Vec<&str> = path.match_indices('/').map(|(i, _)| &path[..i]).collect();
+    if !dir_only {
+        candidates.push(path);
+    }
+    candidates
+        .iter()
+        .any(|c| glob_match(full.as_bytes(), c.as_bytes()))
+}
+
+/// The rule that owns `path`: the last matching line wins, as on GitHub.
+pub fn owning_rule<'a>(rules: &'a [CodeownersRule], path: &str) -> Option<&'a CodeownersRule> {
+    rules
+        .iter()
+        .rev()
+        .find(|r| pattern_matches(&r.pattern, path))
+}
+
+/// Teams (`@org/team`) cannot be mapped to commit identities.
+fn is_team(owner: &str) -> bool {
+    owner.starts_with('@') && owner.contains('/')
+}
+
+/// Whether a commit identity plausibly belongs to a CODEOWNERS owner.
+///
+/// `@handle` matches the author name, the email local part, or a GitHub
+/// noreply address (`123+handle@users.noreply.github.com`); plain emails
+/// match the author email. All comparisons are case-insensitive.
+pub fn owner_matches(owner: &str, name: &str, email: &str) -> bool {
+    let email = email
+        .trim_matches(|c| c == '<' || c == '>')
+        .to_ascii_lowercase();
+    if let Some(handle) = owner.strip_prefix('@') {
+        if is_team(owner) {
+            return false;
+        }
+        let handle = handle.to_ascii_lowercase();
+        let local = email.split('@').next().unwrap_or("");
+        let local = local.split_once('+').map(|(_, h)| h).unwrap_or(local);
+        name.eq_ignore_ascii_case(&handle) || local == handle
+    } else {
+        owner.to_ascii_lowercase() == email
+    }
+}
+
+/// Blame and activity summary for one CODEOWNERS rule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleReport {
+    pub rule: CodeownersRule,
+    pub files: usize,
+    pub lines: usize,
+    pub owner_lines: usize,
+    /// Commits by a listed owner inside the staleness window.
+    pub recent_commits: usize,
+}
+
+/// Outcome of checking one rule.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum RuleStatus {
+    Ok,
+    /// Listed owners hold less than the minimum share of surviving lines.
+    LowOwnership,
+    /// Listed owners have no commits to the covered files in the window.
+    Stale,
+    /// The rule covers no tracked text files.
+    NoFiles,
+    /// Every owner is a team, so ownership can't be measured.
+    Unresolved,
+}
+
+impl RuleStatus {
+    pub fn is_drift(self) -> bool {
+        matches!(self, RuleStatus::LowOwnership | RuleStatus::Stale)
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            RuleStatus::Ok => "ok",
+            RuleStatus::LowOwnership => "DRIFT: low ownership",
+            RuleStatus::Stale => "DRIFT: no recent commits",
+            RuleStatus::NoFiles => "no files",
+            RuleStatus::Unresolved => "team only",
+        }
+    }
+}
+
+impl RuleReport {
+    pub fn owner_pct(&self) -> f64 {
+        if self.lines == 0 {
+            0.0
+        } else {
+            self.owner_lines as f64 * 100.0 / self.lines as f64
+        }
+    }
+
+    /// Classify against `min_pct`; `check_recent` is false when staleness is disabled.
+    pub fn status(&self, min_pct: f64, check_recent: bool) -> RuleStatus {
+        if self.rule.owners.is_empty() || self.rule.owners.iter().all(|o| is_team(o)) {
+            RuleStatus::Unresolved
+        } else if self.files == 0 {
+            RuleStatus::NoFiles
+        } else if self.owner_pct() < min_pct {
+            RuleStatus::LowOwnership
+        } else if check_recent && self.recent_commits == 0 {
+            RuleStatus::Stale
+        } else {
+            RuleStatus::Ok
+        }
+    }
+}
+
+/// Read CODEOWNERS from `path` on disk, or from the first standard location at HEAD.
+pub fn load_codeowners(path: Option<&str>) -> Result<(String, String), GitInsightsError> {
+    if let Some(p) = path {
+        let text =
+            std::fs::read_to_string(p).map_err(|e| format!("failed to read {}: {}", p, e))?;
+        return Ok((p.to_string(), text));
+    }
+    for p in CODEOWNERS_PATHS {
+        if let Ok(text) = run_command(&["--no-pager", "show", &format!("HEAD:{}", p)]) {
+            return Ok((p.to_string(), text));
+        }
+    }
+    Err(format!(
+        "no CODEOWNERS file found at HEAD (looked in {})",
+        CODEOWNERS_PATHS.join(", ")
+    )
+    .into())
+}
+
+/// Surviving lines at HEAD per author of the files one rule owns.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct RuleBlame {
+    files: usize,
+    /// `(name, <email>)` -> lines.
+    authors: HashMap<(String, String), usize>,
+}
+
+/// Blame every tracked text file and credit its lines to the owning rule.
+fn blame_rules(rules: &[CodeownersRule]) -> Result<Vec<RuleBlame>, GitInsightsError> {
+    let authors = crate::filter::author_matcher()?;
+    let merges = MergeResolver::load()?;
+    let mut blames = vec![RuleBlame::default(); rules.len()];
+    let files = tracked_text_files_head()?;
+    let total = files.len();
+    let start = Instant::now();
+    for (n, file) in files.iter().enumerate() {
+        print_progress(n + 1, total, start);
+        let Some(idx) = rules
+            .iter()
+            .rposition(|r| pattern_matches(&r.pattern, file))
+        else {
+            continue;
+        };
+        let blame = &mut blames[idx];
+        blame.files += 1;
+        let Ok(output) = run_blame_head(file) else {
+            continue;
+        };
+        for (name, mail) in blame_line_authors(&output, file, &merges) {
+            if authors.allows(&name, &mail) {
+                *blame.authors.entry((name, mail)).or_insert(0) += 1;
+            }
+        }
+    }
+    if total > 0 {
+        finish_progress();
+    }
+    Ok(blames)
+}
+
+/// Blame every covered file and count recent owner commits, one report per rule.
+pub fn check_codeowners(
+    rules: &[CodeownersRule],
+    stale_days: u64,
+) -> Result<Vec<RuleReport>, GitInsightsError> {
+    let authors = crate::filter::author_matcher()?;
+    let mut reports: Vec<RuleReport> = rules
+        .iter()
+        .zip(blame_rules(rules)?)
+        .map(|(r, blame)| RuleReport {
+            rule: r.clone(),
+            files: blame.files,
+            lines: blame.authors.values().sum(),
+            owner_lines: blame
+                .authors
+                .iter()
+                .filter(|((name, mail), _)| r.owners.iter().any(|o| owner_matches(o, name, mail)))
+                .map(|(_, n)| n)
+                .sum(),
+            recent_commits: 0,
+        })
+        .collect();
+    let index_of = |path: &str| {
+        rules
+            .iter()
+            .rposition(|r| pattern_matches(&r.pattern, path))
+    };
+
+    if stale_days > 0 {
+        // Filter on %at ourselves: approxidate mis-parses large "N days ago" values.
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| format!("clock error: {e}"))?
+            .as_secs();
+        let cutoff = now.saturating_sub(stale_days.saturating_mul(86_400));
+        let revs = crate::filter::revision_args()?;
+        let mut args = vec![
+            "--no-pager",
+            "log",
+            "--no-merges",
+            "--no-renames",
+            "--format=\x1f%at\x1f%aN\x1f%aE",
+            "--name-only",
+        ];
+        args.extend(revs.iter().map(|s| s.as_str()));
+        let mut author: Option<(String, String)> = None;
+        let mut counted: Vec<bool> = vec![false; reports.len()];
+        run_command_lines(&args, |line| {
+            if let Some(header) = line.strip_prefix('\x1f') {
+                let mut f = header.splitn(3, '\x1f');
+                let ts: u64 = f.next().and_then(|t| t.parse().ok()).unwrap_or(0);
+                let (n, e) = (f.next().unwrap_or(""), f.next().unwrap_or(""));
+                author =
+                    (ts >= cutoff && authors.allows(n, e)).then(|| (n.to_string(), e.to_string()));
+                counted.iter_mut().for_each(|c| *c = false);
+            } else if let (Some((n, e)), false) = (&author, line.trim().is_empty()) {
+                let Some(idx) = index_of(line.trim()) else {
+                    return;
+                };
+                let report = &mut reports[idx];
+                if !counted[idx] && report.rule.owners.iter().any(|o| owner_matches(o, n, e)) {
+                    report.recent_commits += 1;
+                    counted[idx] = true;
+                }
+            }
+        })?;
+    }
+    Ok(reports)
+}
+
+/// Declared owner against blame for one CODEOWNERS rule (`codeowners-audit`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleAudit {
+    pub rule: CodeownersRule,
+    pub files: usize,
+    pub lines: usize,
+    /// Surviving lines of each declared owner, in CODEOWNERS order.
+    pub owner_lines: Vec<(String, usize)>,
+    /// The author holding the most surviving lines, and how many.
+    pub top_author: Option<(String, usize)>,
+}
+
+impl RuleAudit {
+    fn pct(&self, lines: usize) -> f64 {
+        if self.lines == 0 {
+            0.0
+        } else {
+            lines as f64 * 100.0 / self.lines as f64
+        }
+    }
+
+    /// Share of the rule's surviving lines held by `owner`'s entry.
+    pub fn owner_pct(&self, owner: &str) -> f64 {
+        let lines = self
+            .owner_lines
+            .iter()
+            .find(|(o, _)| o == owner)
+            .map_or(0, |(_, n)| *n);
+        self.pct(lines)
+    }
+
+    /// Declared owners holding less than `min_pct` of the surviving lines.
+    pub fn flagged(&self, min_pct: f64) -> Vec<&str> {
+        if self.files == 0 {
+            return Vec::new();
+        }
+        self.owner_lines
+            .iter()
+            .filter(|(o, n)| !is_team(o) && self.pct(*n) < min_pct)
+            .map(|(o, _)| o.as_str())
+            .collect()
+    }
+}
+
+/// Per declared owner, the surviving lines they hold in each rule's files.
+pub fn audit_codeowners(rules: &[CodeownersRule]) -> Result<Vec<RuleAudit>, GitInsightsError> {
+    Ok(rules
+        .iter()
+        .zip(blame_rules(rules)?)
+        .map(|(rule, blame)| {
+            let owner_lines = rule
+                .owners
+                .iter()
+                .map(|o| {
+                    let lines = blame
+                        .authors
+                        .iter()
+                        .filter(|((name, mail), _)| owner_matches(o, name, mail))
+                        .map(|(_, n)| n)
+                        .sum();
+                    (o.clone(), lines)
+                })
+                .collect();
+            let mut by_name: HashMap<&str, usize> = HashMap::new();
+            for ((name, _), n) in &blame.authors {
+                *by_name.entry(name).or_insert(0) += n;
+            }
+            let top_author = by_name
+                .into_iter()
+                .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+                .map(|(name, n)| (name.to_string(), n));
+            RuleAudit {
+                rule: rule.clone(),
+                files: blame.files,
+                lines: blame.authors.values().sum(),
+                owner_lines,
+                top_author,
+            }
+        })
+        .collect())
+}
+
+/// Print one row per declared owner and return how many were flagged.
+pub fn run_codeowners_audit(path: Option<&str>, min_pct: f64) -> Result<usize, GitInsightsError> {
+    let (source, text) = load_codeowners(path)?;
+    let rules = parse_codeowners(&text);
+    let audits = audit_codeowners(&rules)?;
+
+    println!("CODEOWNERS: {} ({} rules)", source, rules.len());
+    println!(
+        "| {:>5} | {:<30} | {:<20} | {:>6} | {:<20} | {:>6} | {:<10} |",
+        "line", "pattern", "declared owner", "%own", "top author", "%top", "status"
+    );
+    println!(
+        "|{:->7}|:{:-<31}|:{:-<21}|{:->8}|:{:-<21}|{:->8}|:{:-<11}|",
+        "", "", "", "", "", "", ""
+    );
+    let mut flagged = 0;
+    for audit in &audits {
+        let low = audit.flagged(min_pct);
+        let (top, top_pct) = match &audit.top_author {
+            Some((name, n)) => (name.as_str(), format!("{:.1}", audit.pct(*n))),
+            None => ("-", "-".to_string()),
+        };
+        for owner in &audit.rule.owners {
+            let status = if is_team(owner) {
+                "team"
+            } else if audit.files == 0 {
+                "no files"
+            } else if low.contains(&owner.as_str()) {
+                flagged += 1;
+                "FLAGGED"
+            } else {
+                "ok"
+            };
+            println!(
+                "| {:>5} | {:<30} | {:<20} | {:>6.1} | {:<20} | {:>6} | {:<10} |",
+                audit.rule.line,
+                truncate(&audit.rule.pattern, 30),
+                truncate(owner, 20),
+                audit.owner_pct(owner),
+                truncate(top, 20),
+                top_pct,
+                status
+            );
+        }
+    }
+    println!();
+    println!(
+        "{} of {} declared owners hold less than {:.1}% of their rule's surviving lines.",
+        flagged,
+        audits.iter().map(|a| a.rule.owners.len()).sum::<usize>(),
+        min_pct
+    );
+    Ok(flagged)
+}
+
+/// Print the drift table and return how many rules drifted.
+pub fn run_codeowners_check(
+    path: Option<&str>,
+    min_pct: f64,
+    stale_days: u64,
+) -> Result<usize, GitInsightsError> {
+    let (source, text) = load_codeowners(path)?;
+    let rules = parse_codeowners(&text);
+    let reports = check_codeowners(&rules, stale_days)?;
+
+    println!("CODEOWNERS: {} ({} rules)", source, rules.len());
+    println!(
+        "| {:>5} | {:<30} | {:<24} | {:>6} | {:>7} | {:>6} | {:>6} | {:<24} |",
+        "line", "pattern", "owners", "files", "lines", "%own", "recent", "status"
+    );
+    println!(
+        "|{:->7}|:{:-<31}|:{:-<25}|{:->8}|{:->9}|{:->8}|{:->8}|:{:-<25}|",
+        "", "", "", "", "", "", "", ""
+    );
+    let mut drift = 0;
+    for r in &reports {
+        let status = r.status(min_pct, stale_days > 0);
+        if status.is_drift() {
+            drift += 1;
+        }
+        println!(
+            "| {:>5} | {:<30} | {:<24} | {:>6} | {:>7} | {:>6.1} | {:>6} | {:<24} |",
+            r.rule.line,
+            truncate(&r.rule.pattern, 30),
+            truncate(&r.rule.owners.join(" "), 24),
+            r.files,
+            r.lines,
+            r.owner_pct(),
+            r.recent_commits,
+            status.label()
+        );
+    }
+    println!();
+    println!(
+        "{} of {} rules drifted (min ownership {:.1}%, window {}).",
+        drift,
+        reports.len(),
+        min_pct,
+        if stale_days > 0 {
+            format!("{} days", stale_days)
+        } else {
+            "disabled".to_string()
+        }
+    );
+    Ok(drift)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_codeowners() {
+        let text =
+            "# owners\n\n*       @alice\n/docs/  @bob dev@x.io # docs team\n*.rs @org/rust\n";
+        let rules = parse_codeowners(text);
+        assert_eq!(rules.len(), 3);
+        assert_eq!(rules[1].line, 4);
+        assert_eq!(rules[1].pattern, "/docs/");
+        assert_eq!(rules[1].owners, vec!["@bob", "dev@x.io"]);
+    }
+
+    #[test]
+    fn test_pattern_matches() {
+        assert!(pattern_matches("*", "src/a.rs"));
+        assert!(pattern_matches("*.rs", "src/deep/a.rs"));
+        assert!(!pattern_matches("*.rs", "src/a.rsx"));
+        assert!(pattern_matches("/docs/", "docs/guide/intro.md"));
+        assert!(!pattern_matches("/docs/", "src/docs/x.md"));
+        assert!(pattern_matches("docs/", "src/docs/x.md"));
+        assert!(!pattern_matches("docs/", "docs"));
+        assert!(pattern_matches("apps/*.js", "apps/a.js"));
+        assert!(!pattern_matches("apps/*.js", "apps/x/a.js"));
+        assert!(pattern_matches("src/**/mod.rs", "src/mod.rs"));
+        assert!(pattern_matches("src/**/mod.rs", "src/a/b/mod.rs"));
+        assert!(pattern_matches("Makefile", "tools/Makefile"));
+
+        let rules = parse_codeowners("* @a\n*.md @b\n/docs/ @c\n");
+        assert_eq!(owning_rule(&rules, "docs/x.md").unwrap().owners, vec!["@c"]);
+        assert_eq!(owning_rule(&rules, "README.md").unwrap().owners, vec!["@b"]);
+    }
+
+    #[test]
+    fn test_owner_matches() {
+        assert!(owner_matches("@alice", "Alice", "<a@x.io>"));
+        assert!(owner_matches("@alice", "A. Smith", "<alice@corp.io>"));
+        assert!(owner_matches(
+            "@alice",
+            "A",
+            "<123+Alice@users.noreply.github.com>"
+        ));
+        assert!(owner_matches("dev@x.io", "Dev", "<DEV@x.io>"));
+        assert!(!owner_matches("@org/team", "team", "<team@x.io>"));
+        assert!(!owner_matches("@bob", "Alice", "<alice@x.io>"));
+    }
+
+    #[test]
+    fn test_codeowners_check_e2e() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        repo.commit_with_epoch("ann", "ann@x.io", "src/a.rs", "fn a() {}", 1_000_000_000)
+            .expect("commit");
+        repo.commit_with_epoch("Bob", "bob@x.io", "docs/guide.md", "# guide", 1_700_000_000)
+            .expect("commit");
+        repo.commit_with_epoch("Bob", "bob@x.io", "docs/guide.md", "more", 1_700_000_100)
+            .expect("commit");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        let rules = parse_codeowners("/src/ @ann\n/docs/ @ann @org/docs\n/empty/ @bob\n");
+        let reports = check_codeowners(&rules, 36_500);
+        std::env::set_current_dir(old).ok();
+
+        let reports = reports.expect("check");
+        assert_eq!(
+            (reports[0].files, reports[0].lines, reports[0].owner_lines),
+            (1, 1, 1)
+        );
+        assert_eq!(reports[0].recent_commits, 1);
+        assert_eq!(reports[0].status(10.0, true), RuleStatus::Ok);
+        assert_eq!(reports[1].owner_lines, 0);
+        assert_eq!(reports[1].status(10.0, true), RuleStatus::LowOwnership);
+        assert_eq!(reports[2].status(10.0, true), RuleStatus::NoFiles);
+    }
+
+    #[test]
+    fn test_codeowners_audit_e2e() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        repo.commit_with_epoch("ann", "ann@x.io", "src/a.rs", "fn a() {}", 1_000_000_000)
+            .expect("commit");
+        repo.commit_with_epoch("Bob", "bob@x.io", "src/b.rs", "fn b() {}", 1_000_000_100)
+            .expect("commit");
+        repo.commit_with_epoch("Bob", "bob@x.io", "src/c.rs", "fn c() {}", 1_000_000_200)
+            .expect("commit");
+
+        let rules = parse_codeowners(
+            "/src/ @ann @bob @org/core
+/empty/ @bob
+",
+        );
+        let scoped = crate::git::Repo::open(&repo.path).expect("open");
+        let audits = scoped.scope(|| audit_codeowners(&rules)).expect("audit");
+
+        assert_eq!((audits[0].files, audits[0].lines), (3, 3));
+        assert_eq!(
+            audits[0].owner_lines,
+            vec![
+                ("@ann".to_string(), 1),
+                ("@bob".to_string(), 2),
+                ("@org/core".to_string(), 0)
+            ]
+        );
+        assert_eq!(audits[0].top_author, Some(("Bob".to_string(), 2)));
+        assert!(audits[0].flagged(30.0).is_empty());
+        assert_eq!(audits[0].flagged(50.0), vec!["@ann"]);
+        assert!(audits[1].flagged(50.0).is_empty());
+    }
+}
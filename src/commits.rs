@@ -0,0 +1,187 @@
+use crate::error::GitInsightsError;
+use crate::git::run_command_records;
+use crate::json;
+use crate::output::write_export;
+
+/// One commit from the history stream, including topology.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Commit {
+    pub sha: String,
+    /// Parent shas in order (first parent first; empty for root commits).
+    pub parents: Vec<String>,
+    /// Ref decorations pointing at this commit (e.g. `HEAD -> main`, `tag: v1.0`).
+    pub refs: Vec<String>,
+    pub author_name: String,
+    pub author_email: String,
+    pub author_time: u64,
+    pub committer_time: u64,
+    pub subject: String,
+}
+
+impl Commit {
+    pub fn is_merge(&self) -> bool {
+        self.parents.len() > 1
+    }
+
+    pub fn to_json(&self) -> String {
+        let list = |items: &[String]| {
+            items
+                .iter()
+                .map(|s| format!("\"{}\"", json::escape(s)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        format!(
+            "{{\"sha\": \"{}\", \"parents\": [{}], \"refs\": [{}], \"author\": \"{}\", \"email\": \"{}\", {}, {}, \"subject\": \"{}\"}}",
+            self.sha,
+            list(&self.parents),
+            list(&self.refs),
+            json::escape(&self.author_name),
+            json::escape(&self.author_email),
+            json::time_fields("author_time", self.author_time),
+            json::time_fields("committer_time", self.committer_time),
+            json::escape(&self.subject)
+        )
+    }
+}
+
+/// Field/record separators that cannot appear in the requested fields.
+const FIELD_SEP: char = '\x1f';
+const RECORD_SEP: char = '\x1e';
+const LOG_FORMAT: &str = "--format=%H%x1f%P%x1f%D%x1f%aN%x1f%aE%x1f%at%x1f%ct%x1f%s%x1e";
+
+/// Parse `git log` output produced with [`LOG_FORMAT`].
+pub fn parse_log_records(out: &str) -> Vec<Commit> {
+    out.split(RECORD_SEP).filter_map(parse_log_record).collect()
+}
+
+/// One [`LOG_FORMAT`] record, `None` for the empty tail after the last one.
+fn parse_log_record(rec: &str) -> Option<Commit> {
+    let rec = rec.trim_start_matches('\n');
+    let f: Vec<&str> = rec.split(FIELD_SEP).collect();
+    if f.len() < 8 || f[0].is_empty() {
+        return None;
+    }
+    Some(Commit {
+        sha: f[0].to_string(),
+        parents: f[1].split_whitespace().map(str::to_string).collect(),
+        refs: f[2]
+            .split(", ")
+            .filter(|r| !r.is_empty())
+            .map(str::to_string)
+            .collect(),
+        author_name: f[3].to_string(),
+        author_email: f[4].to_string(),
+        author_time: f[5].parse().unwrap_or(0),
+        committer_time: f[6].parse().unwrap_or(0),
+        subject: f[7].to_string(),
+    })
+}
+
+/// Commits selected by the active filter (merges included), newest first.
+///
+/// With `--exclude-bad-times`, commits with implausible timestamps are dropped.
+pub fn collect_commits() -> Result<Vec<Commit>, GitInsightsError> {
+    let commits = collect_all_commits()?;
+    Ok(match crate::time_audit::active_sanity()? {
+        Some(sanity) => commits
+            .into_iter()
+            .filter(|c| sanity.allows(c.author_time, c.committer_time))
+            .collect(),
+        None => commits,
+    })
+}
+
+/// Like [`collect_commits`], but keeps commits regardless of their timestamps.
+pub fn collect_all_commits() -> Result<Vec<Commit>, GitInsightsError> {
+    let revs = crate::filter::revision_args()?;
+    let authors = crate::filter::author_matcher()?;
+    let mut args = vec!["--no-pager", "log", "--decorate=short", LOG_FORMAT];
+    if crate::filter::active().first_parent {
+        args.push("--first-parent");
+    }
+    args.extend(revs.iter().map(|s| s.as_str()));
+    let names = crate::identity::active_normalization();
+    let mut commits = Vec::new();
+    run_command_records(&args, RECORD_SEP as u8, |rec| {
+        if let Some(mut c) = parse_log_record(rec) {
+            if authors.allows(&c.author_name, &c.author_email) {
+                c.author_name = names.apply(c.author_name);
+                c.author_email = names.apply(c.author_email);
+                commits.push(c);
+            }
+        }
+    })?;
+    Ok(commits)
+}
+
+/// Default file name for `git-insights log-json`.
+pub const LOG_JSON_PATH: &str = "git-insights-log.jsonl";
+
+/// Write the commit stream as JSON Lines (one commit object per line).
+pub fn export_log_json(output: Option<&str>) -> Result<(), GitInsightsError> {
+    let mut text = String::new();
+    for c in collect_commits()? {
+        text.push_str(&c.to_json());
+        text.push('\n');
+    }
+    write_export(output, LOG_JSON_PATH, text.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_records() {
+        let out = "b2\x1fa1 c3\x1fHEAD -> main, tag: v1\x1fAnn\x1fann@x.io\x1f100\x1f101\x1fMerge \"x\"\x1e\n\
+                   a1\x1f\x1f\x1fBob\x1fbob@x.io\x1f50\x1f50\x1finit\x1e";
+        let commits = parse_log_records(out);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].parents, vec!["a1", "c3"]);
+        assert_eq!(commits[0].refs, vec!["HEAD -> main", "tag: v1"]);
+        assert!(commits[0].is_merge());
+        assert!(commits[1].parents.is_empty() && commits[1].refs.is_empty());
+        let doc = crate::json::parse(&commits[0].to_json()).expect("valid JSON");
+        assert_eq!(
+            doc.get("subject").and_then(|s| s.as_str()),
+            Some("Merge \"x\"")
+        );
+        assert_eq!(
+            doc.get("parents")
+                .and_then(|p| p.as_array())
+                .map(|p| p.len()),
+            Some(2)
+        );
+        assert_eq!(doc.get("author_time").and_then(|t| t.as_usize()), Some(100));
+        assert_eq!(
+            doc.get("committer_time_utc").and_then(|t| t.as_str()),
+            Some("1970-01-01T00:01:41Z")
+        );
+    }
+
+    #[test]
+    fn test_collect_commits_topology() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", "a", 1_700_000_000)
+            .expect("commit");
+        let p = repo.path.to_str().unwrap();
+        crate::git::run_command(&["-C", p, "tag", "v1"]).expect("tag");
+        repo.commit_with_epoch("Bob", "bob@x.io", "b.txt", "b", 1_700_000_100)
+            .expect("commit");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        let commits = collect_commits();
+        std::env::set_current_dir(old).ok();
+
+        let commits = commits.expect("commits");
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].author_name, "Bob");
+        assert_eq!(commits[0].parents, vec![commits[1].sha.clone()]);
+        assert!(commits[0].refs.iter().any(|r| r.starts_with("HEAD")));
+        assert!(commits[1].refs.contains(&"tag: v1".to_string()));
+        assert_eq!(commits[1].author_time, 1_700_000_000);
+    }
+}
@@ -0,0 +1,510 @@
+//! `compare <refA> <refB>`: author stats at two refs and the change between them,
+//! and `stats --compare-with`: activity in two consecutive time windows.
+
+use crate::code_frequency::ymd_from_unix;
+use crate::error::GitInsightsError;
+use crate::git::{run_command, run_command_line_iter};
+use crate::merges::MergeResolver;
+use crate::output::{finish_progress, print_progress, truncate};
+use crate::stats::{
+    blame_file_authors_at, gather_commit_stats_for, prepare_blame, tracked_text_files_at, StatsMap,
+};
+use std::collections::{BTreeMap, HashSet};
+use std::time::Instant;
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// One author's surviving lines, commits and files at both refs.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuthorDelta {
+    pub author: String,
+    pub loc: (usize, usize),
+    pub commits: (usize, usize),
+    pub files: (usize, usize),
+    /// Share of all surviving lines, in percent, at each ref.
+    pub share: (f64, f64),
+}
+
+impl AuthorDelta {
+    pub fn loc_change(&self) -> i64 {
+        self.loc.1 as i64 - self.loc.0 as i64
+    }
+
+    /// Ownership change in percentage points.
+    pub fn share_change(&self) -> f64 {
+        self.share.1 - self.share.0
+    }
+}
+
+/// Full commit sha for `rev`, or an error naming the bad ref.
+pub fn resolve_ref(rev: &str) -> Result<String, GitInsightsError> {
+    run_command(&[
+        "rev-parse",
+        "--verify",
+        "--quiet",
+        &format!("{}^{{commit}}", rev),
+    ])
+    .map(|s| s.trim().to_string())
+    .map_err(|_| format!("unknown ref '{}'", rev).into())
+}
+
+/// Stats as `stats` computes them, but at `rev`: lines surviving in its tree
+/// and commits reachable from it.
+pub fn gather_stats_at(rev: &str, by_name: bool) -> Result<StatsMap, GitInsightsError> {
+    let files = tracked_text_files_at(rev)?;
+    prepare_blame();
+    let authors = crate::filter::author_matcher()?;
+    let merges = MergeResolver::load_at(rev)?;
+    let names = crate::identity::active_normalization();
+    let mut stats = gather_commit_stats_for(by_name, &[rev.to_string()])?;
+    let total = files.len();
+    let start = Instant::now();
+    for (n, file) in files.iter().enumerate() {
+        print_progress(n + 1, total, start);
+        let Some(lines) = blame_file_authors_at(rev, file, by_name, &authors, &merges, names)
+        else {
+            continue;
+        };
+        for (key, loc) in lines {
+            let entry = stats.entry(key).or_default();
+            entry.loc += loc;
+            entry.files.insert(file.clone());
+        }
+    }
+    if total > 0 {
+        finish_progress();
+    }
+    Ok(stats)
+}
+
+/// Per-author deltas between two stats maps, largest ownership change first
+/// (ties by name). Authors present at only one ref count as zero at the other.
+pub fn compare_stats(before: &StatsMap, after: &StatsMap) -> Vec<AuthorDelta> {
+    let total = |m: &StatsMap| m.values().map(|s| s.loc).sum::<usize>().max(1) as f64;
+    let (total_before, total_after) = (total(before), total(after));
+    let mut authors: Vec<&String> = before.keys().chain(after.keys()).collect();
+    authors.sort();
+    authors.dedup();
+    let mut rows: Vec<AuthorDelta> = authors
+        .into_iter()
+        .map(|author| {
+            let a = before.get(author).cloned().unwrap_or_default();
+            let b = after.get(author).cloned().unwrap_or_default();
+            AuthorDelta {
+                author: author.clone(),
+                loc: (a.loc, b.loc),
+                commits: (a.commits, b.commits),
+                files: (a.files.len(), b.files.len()),
+                share: (
+                    a.loc as f64 * 100.0 / total_before,
+                    b.loc as f64 * 100.0 / total_after,
+                ),
+            }
+        })
+        .collect();
+    rows.sort_by(|x, y| {
+        y.share_change()
+            .abs()
+            .total_cmp(&x.share_change().abs())
+            .then_with(|| y.loc_change().abs().cmp(&x.loc_change().abs()))
+            .then_with(|| x.author.cmp(&y.author))
+    });
+    rows
+}
+
+fn signed(v: i64) -> String {
+    format!("{:+}", v)
+}
+
+/// Green for gains, red for losses.
+fn paint(text: String, change: f64, color: bool) -> String {
+    match (color, change.partial_cmp(&0.0)) {
+        (true, Some(std::cmp::Ordering::Greater)) => format!("\x1b[32m{}\x1b[0m", text),
+        (true, Some(std::cmp::Ordering::Less)) => format!("\x1b[31m{}\x1b[0m", text),
+        _ => text,
+    }
+}
+
+/// Print the delta table and the biggest ownership gain and loss.
+pub fn render_compare(rows: &[AuthorDelta], ref_a: &str, ref_b: &str, color: bool) {
+    println!("Comparing {} -> {}", ref_a, ref_b);
+    println!();
+    println!(
+        "| {:<24} | {:>8} | {:>8} | {:>8} | {:>7} | {:>7} | {:>16} |",
+        "author", "loc A", "loc B", "Δloc", "Δcoms", "Δfils", "ownership"
+    );
+    println!(
+        "|:{:-<25}|{:->10}|{:->10}|{:->10}|{:->9}|{:->9}|{:->18}|",
+        "", "", "", "", "", "", ""
+    );
+    for r in rows {
+        let ownership = format!("{:.1}% -> {:.1}%", r.share.0, r.share.1);
+        println!(
+            "| {:<24} | {:>8} | {:>8} | {} | {:>7} | {:>7} | {} |",
+            truncate(&r.author, 24),
+            r.loc.0,
+            r.loc.1,
+            paint(
+                format!("{:>8}", signed(r.loc_change())),
+                r.loc_change() as f64,
+                color
+            ),
+            signed(r.commits.1 as i64 - r.commits.0 as i64),
+            signed(r.files.1 as i64 - r.files.0 as i64),
+            paint(format!("{:>16}", ownership), r.share_change(), color),
+        );
+    }
+    let gain = rows
+        .iter()
+        .filter(|r| r.share_change() > 0.0)
+        .max_by(|x, y| x.share_change().total_cmp(&y.share_change()));
+    let loss = rows
+        .iter()
+        .filter(|r| r.share_change() < 0.0)
+        .min_by(|x, y| x.share_change().total_cmp(&y.share_change()));
+    println!();
+    for (label, row) in [
+        ("Biggest ownership gain", gain),
+        ("Biggest ownership loss", loss),
+    ] {
+        if let Some(r) = row {
+            println!(
+                "{}: {} ({:+.1} pp, {} lines)",
+                label,
+                r.author,
+                r.share_change(),
+                signed(r.loc_change())
+            );
+        }
+    }
+}
+
+/// Compute stats at both refs and print per-author deltas.
+pub fn run_compare(
+    ref_a: &str,
+    ref_b: &str,
+    by_name: bool,
+    color: bool,
+) -> Result<(), GitInsightsError> {
+    let (sha_a, sha_b) = (resolve_ref(ref_a)?, resolve_ref(ref_b)?);
+    let before = gather_stats_at(&sha_a, by_name)?;
+    let after = gather_stats_at(&sha_b, by_name)?;
+    render_compare(&compare_stats(&before, &after), ref_a, ref_b, color);
+    Ok(())
+}
+
+/// Previous window `[start, end)` from `--compare-with SINCE..UNTIL` (UTC
+/// dates, both days included); the current window is the same length
+/// starting at `end`.
+pub fn parse_window(s: &str) -> Result<(u64, u64), String> {
+    let err = || {
+        format!(
+            "invalid --compare-with '{}'. Expected YYYY-MM-DD..YYYY-MM-DD.",
+            s
+        )
+    };
+    let (since, until) = s.split_once("..").ok_or_else(err)?;
+    let start = crate::capacity::parse_date(since.trim()).ok_or_else(err)?;
+    let end = crate::capacity::parse_date(until.trim()).ok_or_else(err)? + DAY;
+    if end <= start {
+        return Err(format!("--compare-with '{}' ends before it starts", s));
+    }
+    Ok((start, end))
+}
+
+/// Activity in one time window.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WindowStats {
+    pub commits: usize,
+    pub added: usize,
+    pub deleted: usize,
+    pub files: HashSet<String>,
+}
+
+impl WindowStats {
+    pub fn lines(&self) -> usize {
+        self.added + self.deleted
+    }
+}
+
+/// Per-author `(previous, current)` activity.
+pub type WindowPair = (WindowStats, WindowStats);
+
+/// Parse `log --numstat --format=%x1f%ct%x1f%aN%x1f%aE` into per-author
+/// activity for `[start, end)` and the equally long window after it.
+pub fn bucket_windows(
+    out: &str,
+    start: u64,
+    end: u64,
+    by_name: bool,
+    allow: impl Fn(&str, &str) -> bool,
+) -> BTreeMap<String, WindowPair> {
+    windows_from_lines(out.lines(), start, end, by_name, allow)
+}
+
+/// [`bucket_windows`] over lines as git streams them.
+fn windows_from_lines<S: AsRef<str>>(
+    lines: impl Iterator<Item = S>,
+    start: u64,
+    end: u64,
+    by_name: bool,
+    allow: impl Fn(&str, &str) -> bool,
+) -> BTreeMap<String, WindowPair> {
+    let len = end - start;
+    let mut authors: BTreeMap<String, WindowPair> = BTreeMap::new();
+    let mut slot: Option<(String, bool)> = None;
+    for line in lines {
+        let line = line.as_ref();
+        if let Some(header) = line.strip_prefix('\x1f') {
+            let f: Vec<&str> = header.splitn(3, '\x1f').collect();
+            slot = None;
+            if f.len() < 3 || !allow(f[1], f[2]) {
+                continue;
+            }
+            let t: u64 = f[0].parse().unwrap_or(0);
+            if t < start || t >= end + len {
+                continue;
+            }
+            let key = if by_name {
+                f[1].to_string()
+            } else {
+                format!("{} <{}>", f[1], f[2])
+            };
+            let current = t >= end;
+            let pair = authors.entry(key.clone()).or_default();
+            let w = if current { &mut pair.1 } else { &mut pair.0 };
+            w.commits += 1;
+            slot = Some((key, current));
+        } else if let Some((key, current)) = &slot {
+            let mut parts = line.split('\t');
+            if let (Some(a), Some(d), Some(file)) = (parts.next(), parts.next(), parts.next()) {
+                let pair = authors.entry(key.clone()).or_default();
+                let w = if *current { &mut pair.1 } else { &mut pair.0 };
+                w.added += a.parse::<usize>().unwrap_or(0);
+                w.deleted += d.parse::<usize>().unwrap_or(0);
+                w.files.insert(file.to_string());
+            }
+        }
+    }
+    authors
+}
+
+/// `+25.0%`, `-40.0%`, `new` (nothing before) or `-` (nothing in either).
+pub fn pct_change(prev: usize, cur: usize) -> String {
+    match (prev, cur) {
+        (0, 0) => "-".to_string(),
+        (0, _) => "new".to_string(),
+        _ => format!("{:+.1}%", (cur as f64 - prev as f64) * 100.0 / prev as f64),
+    }
+}
+
+/// Activity per author (by committer date) in the window starting at `start`
+/// and the one after it, for the active filter. Merges count as in `stats`.
+pub fn gather_window_stats(
+    start: u64,
+    end: u64,
+    by_name: bool,
+) -> Result<BTreeMap<String, WindowPair>, GitInsightsError> {
+    let revs = crate::filter::revision_args()?;
+    let authors = crate::filter::author_matcher()?;
+    let since = format!("--since=@{}", start);
+    let until = format!("--until=@{}", end + (end - start));
+    let mut args = vec![
+        "--no-pager",
+        "log",
+        "--numstat",
+        "--format=\x1f%ct\x1f%aN\x1f%aE",
+        &since,
+        &until,
+    ];
+    args.extend(crate::filter::history_args());
+    args.extend(revs.iter().map(|s| s.as_str()));
+    let windows = run_command_line_iter(&args, |lines| {
+        windows_from_lines(lines, start, end, by_name, |n, e| authors.allows(n, e))
+    })?;
+    let names = crate::identity::active_normalization();
+    let mut merged: BTreeMap<String, WindowPair> = BTreeMap::new();
+    for (key, (prev, cur)) in windows {
+        let entry = merged.entry(names.apply(key)).or_default();
+        for (into, from) in [(&mut entry.0, prev), (&mut entry.1, cur)] {
+            into.commits += from.commits;
+            into.added += from.added;
+            into.deleted += from.deleted;
+            into.files.extend(from.files);
+        }
+    }
+    Ok(merged)
+}
+
+fn format_day(t: u64) -> String {
+    let (y, m, d) = ymd_from_unix(t);
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+/// `stats --compare-with`: previous- and current-window columns per author
+/// with the percentage change.
+pub fn run_stats_window_compare(
+    start: u64,
+    end: u64,
+    by_name: bool,
+) -> Result<(), GitInsightsError> {
+    let rows = gather_window_stats(start, end, by_name)?;
+    let current_end = end + (end - start);
+    println!(
+        "Previous: {} .. {}   Current: {} .. {}",
+        format_day(start),
+        format_day(end - DAY),
+        format_day(end),
+        format_day(current_end - DAY)
+    );
+    println!();
+    println!(
+        "| {:<24} | {:>6} | {:>6} | {:>7} | {:>7} | {:>7} | {:>7} | {:>5} | {:>5} | {:>7} |",
+        "author", "coms", "coms", "Δ", "lines", "lines", "Δ", "fils", "fils", "Δ"
+    );
+    println!(
+        "| {:<24} | {:>6} | {:>6} | {:>7} | {:>7} | {:>7} | {:>7} | {:>5} | {:>5} | {:>7} |",
+        "", "prev", "cur", "", "prev", "cur", "", "prev", "cur", ""
+    );
+    println!(
+        "|:{:-<25}|{:->8}|{:->8}|{:->9}|{:->9}|{:->9}|{:->9}|{:->7}|{:->7}|{:->9}|",
+        "", "", "", "", "", "", "", "", "", ""
+    );
+    let mut sorted: Vec<(&String, &WindowPair)> = rows.iter().collect();
+    sorted.sort_by(|a, b| {
+        (b.1 .1.commits + b.1 .0.commits)
+            .cmp(&(a.1 .1.commits + a.1 .0.commits))
+            .then_with(|| a.0.cmp(b.0))
+    });
+    let mut total = WindowPair::default();
+    for (author, (prev, cur)) in sorted {
+        print_window_row(author, prev, cur);
+        for (into, from) in [(&mut total.0, prev), (&mut total.1, cur)] {
+            into.commits += from.commits;
+            into.added += from.added;
+            into.deleted += from.deleted;
+            into.files.extend(from.files.iter().cloned());
+        }
+    }
+    print_window_row("(total)", &total.0, &total.1);
+    Ok(())
+}
+
+fn print_window_row(label: &str, prev: &WindowStats, cur: &WindowStats) {
+    println!(
+        "| {:<24} | {:>6} | {:>6} | {:>7} | {:>7} | {:>7} | {:>7} | {:>5} | {:>5} | {:>7} |",
+        truncate(label, 24),
+        prev.commits,
+        cur.commits,
+        pct_change(prev.commits, cur.commits),
+        prev.lines(),
+        cur.lines(),
+        pct_change(prev.lines(), cur.lines()),
+        prev.files.len(),
+        cur.files.len(),
+        pct_change(prev.files.len(), cur.files.len())
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::AuthorStats;
+
+    #[test]
+    fn test_compare_stats() {
+        let stats = |rows: &[(&str, usize, usize)]| -> StatsMap {
+            rows.iter()
+                .map(|(a, loc, commits)| {
+                    (
+                        a.to_string(),
+                        AuthorStats {
+                            loc: *loc,
+                            commits: *commits,
+                            files: (0..*loc.min(&3)).map(|i| i.to_string()).collect(),
+                            ..Default::default()
+                        },
+                    )
+                })
+                .collect()
+        };
+        let before = stats(&[("Ann", 75, 3), ("Bob", 25, 1)]);
+        let after = stats(&[("Ann", 50, 4), ("Cat", 50, 2)]);
+        let rows = compare_stats(&before, &after);
+        let order: Vec<&str> = rows.iter().map(|r| r.author.as_str()).collect();
+        assert_eq!(order, vec!["Cat", "Ann", "Bob"]);
+        assert_eq!(rows[0].loc_change(), 50);
+        assert_eq!(rows[1].share_change(), -25.0);
+        assert_eq!(rows[1].commits, (3, 4));
+        assert_eq!(rows[2].loc, (25, 0));
+        assert_eq!(rows[2].files, (3, 0));
+    }
+
+    #[test]
+    fn test_gather_stats_at_tag() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", "one", 1_700_000_000)
+            .expect("commit");
+        let p = repo.path.to_str().unwrap();
+        run_command(&["-C", p, "tag", "v1"]).expect("tag");
+        repo.commit_with_epoch("Bob", "bob@x.io", "b.txt", "two", 1_700_000_100)
+            .expect("commit");
+        repo.commit_with_epoch("Bob", "bob@x.io", "a.txt", "three", 1_700_000_200)
+            .expect("commit");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        let v1 = resolve_ref("v1").and_then(|sha| gather_stats_at(&sha, true));
+        let head = gather_stats_at("HEAD", true);
+        let missing = resolve_ref("no-such-ref");
+        std::env::set_current_dir(old).ok();
+
+        let v1 = v1.expect("stats at v1");
+        assert_eq!(v1.len(), 1);
+        assert_eq!((v1["Ann"].loc, v1["Ann"].commits), (1, 1));
+        let head = head.expect("stats at HEAD");
+        assert_eq!(
+            (
+                head["Bob"].loc,
+                head["Bob"].commits,
+                head["Bob"].files.len()
+            ),
+            (2, 2, 2)
+        );
+        assert!(missing.unwrap_err().to_string().contains("no-such-ref"));
+    }
+
+    #[test]
+    fn test_window_compare() {
+        assert_eq!(parse_window("1970-01-01..1970-01-02"), Ok((0, 2 * DAY)));
+        assert!(parse_window("1970-01-02..1970-01-01").is_err());
+        assert!(parse_window("1970-01-01").is_err());
+
+        let out = format!(
+            "\x1f{}\x1fAnn\x1fann@x.io\n\n3\t1\ta.rs\n\
+             \x1f{}\x1fAnn\x1fann@x.io\n\n2\t0\ta.rs\n-\t-\tlogo.png\n\
+             \x1f{}\x1fBob\x1fbob@x.io\n\n5\t5\tb.rs\n\
+             \x1f{}\x1fBob\x1fbob@x.io\n\n1\t1\tc.rs\n",
+            10 * DAY,
+            DAY,
+            DAY + 1,
+            4 * DAY
+        );
+        let rows = bucket_windows(&out, DAY, 2 * DAY, true, |_, _| true);
+        let (prev, cur) = &rows["Ann"];
+        assert_eq!((prev.commits, prev.lines(), prev.files.len()), (1, 2, 2));
+        assert_eq!(cur.commits, 0);
+        let (prev, cur) = &rows["Bob"];
+        assert_eq!((prev.commits, prev.lines()), (1, 10));
+        assert_eq!(cur, &WindowStats::default());
+        let by_email = bucket_windows(&out, 0, DAY, false, |n, _| n == "Ann");
+        assert!(by_email.contains_key("Ann <ann@x.io>"));
+        assert_eq!(by_email.len(), 1);
+
+        assert_eq!(pct_change(4, 5), "+25.0%");
+        assert_eq!(pct_change(5, 0), "-100.0%");
+        assert_eq!(pct_change(0, 3), "new");
+        assert_eq!(pct_change(0, 0), "-");
+    }
+}
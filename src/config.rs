@@ -0,0 +1,961 @@
+//! Config files: flag defaults, identity aliases and named profiles.
+//!
+//! Defaults come from `~/.config/git-insights/config.toml` (or
+//! `$XDG_CONFIG_HOME/git-insights/config.toml`) and then the repo-local
+//! `.git-insights.toml`, so the repo wins over the user file:
+//!
+//! ```toml
+//! [defaults]                 # every command that takes the option
+//! color = false              # --no-color
+//! exclude = ["vendor/"]      # --exclude vendor/
+//! blame-opts = "w,M,C,C"    # --blame-opts w,M,C,C
+//!
+//! [defaults.timeline]
+//! weeks = 26                 # --weeks 26
+//!
+//! [aliases]                  # written to a generated mailmap (--mailmap)
+//! "Ann Lee <ann@corp.io>" = ["ann <ann@home.net>", "<alee@old.io>"]
+//!
+//! [classes]                  # path classes for `classes` (--class)
+//! tests = ["tests/", "**/*_test.rs"]
+//!
+//! [palettes]                 # color ramps for --palette ocean
+//! ocean = ["#03045e", "#0077b6", "#90e0ef"]
+//! ```
+//!
+//! Profiles live in `.git-insights.toml` and are applied with `--profile`:
+//!
+//! ```toml
+//! [profile.ci]
+//! command = "stats"          # optional: run when no command is given
+//! reachable-from = "v*"      # applies to every command
+//!
+//! [profile.ci.stats]
+//! by-email = true            # --by-email
+//!
+//! [profile.weekly-report.timeline]
+//! weeks = 12                 # --weeks 12
+//! color = false              # --no-color
+//! ```
+//!
+//! Both expand to ordinary CLI flags placed before the user's own flags
+//! (defaults before profiles), so anything given explicitly on the command
+//! line still wins.
+
+use crate::git::{repo_relative, run_command};
+use std::path::{Path, PathBuf};
+
+/// Repo-local config file name.
+pub const CONFIG_FILE: &str = ".git-insights.toml";
+
+/// A scalar or array value from the supported TOML subset.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum TomlValue {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Bool(bool),
+    Array(Vec<TomlValue>),
+}
+
+/// One `key = value` entry together with the dotted table it belongs to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TomlEntry {
+    pub table: Vec<String>,
+    pub key: String,
+    pub value: TomlValue,
+}
+
+/// Parse the TOML subset used by config files: `[dotted.tables]`, bare or
+/// quoted keys, strings, integers, floats, booleans and single-line arrays.
+pub fn parse_toml(text: &str) -> Result<Vec<TomlEntry>, String> {
+    let mut entries = Vec::new();
+    let mut table: Vec<String> = Vec::new();
+    for (lineno, raw) in text.lines().enumerate() {
+        let err = |msg: &str| format!("line {}: {}", lineno + 1, msg);
+        let line = strip_comment(raw).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(inner) = line.strip_prefix('[') {
+            let inner = inner
+                .strip_suffix(']')
+                .ok_or_else(|| err("unterminated table header"))?;
+            table = split_dotted(inner).map_err(|e| err(&e))?;
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| err("expected 'key = value'"))?;
+        let key = unquote_key(key.trim()).map_err(|e| err(&e))?;
+        let value = parse_value(value.trim()).map_err(|e| err(&e))?;
+        entries.push(TomlEntry {
+            table: table.clone(),
+            key,
+            value,
+        });
+    }
+    Ok(entries)
+}
+
+/// Drop a trailing `# comment`, ignoring `#` inside strings.
+fn strip_comment(line: &str) -> &str {
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match quote {
+            Some('"') if escaped => escaped = false,
+            Some('"') if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '#' => return &line[..i],
+            None => {}
+        }
+    }
+    line
+}
+
+fn split_dotted(s: &str) -> Result<Vec<String>, String> {
+    let mut parts = Vec::new();
+    let mut rest = s.trim();
+    while !rest.is_empty() {
+        let (part, tail) = if let Some(q) = rest.strip_prefix('"') {
+            let end = q.find('"').ok_or("unterminated quoted key")?;
+            (q[..end].to_string(), q[end + 1..].trim_start())
+        } else {
+            let end = rest.find('.').unwrap_or(rest.len());
+            (unquote_key(rest[..end].trim())?, &rest[end..])
+        };
+        parts.push(part);
+        rest = match tail.strip_prefix('.') {
+            Some(t) => t.trim_start(),
+            None if tail.trim().is_empty() => "",
+            None => return Err(format!("unexpected '{}' in table name", tail)),
+        };
+    }
+    if parts.is_empty() {
+        return Err("empty table name".to_string());
+    }
+    Ok(parts)
+}
+
+fn unquote_key(k: &str) -> Result<String, String> {
+    if let Some(q) = k.strip_prefix('"').and_then(|q| q.strip_suffix('"')) {
+        return Ok(q.to_string());
+    }
+    if !k.is_empty()
+        && k.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        Ok(k.to_string())
+    } else {
+        Err(format!("invalid key '{}'", k))
+    }
+}
+
+fn parse_value(v: &str) -> Result<TomlValue, String> {
+    let (value, rest) = parse_value_prefix(v)?;
+    if !rest.trim().is_empty() {
+        return Err(format!("unexpected trailing '{}'", rest.trim()));
+    }
+    Ok(value)
+}
+
+/// Parse one value from the start of `v`, returning it and the unparsed rest.
+fn parse_value_prefix(v: &str) -> Result<(TomlValue, &str), String> {
+    if let Some(body) = v.strip_prefix('"') {
+        let mut out = String::new();
+        let mut chars = body.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => return Ok((TomlValue::String(out), &body[i + 1..])),
+                '\\' => match chars.next().map(|(_, e)| e) {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some('"') => out.push('"'),
+                    Some('\\') => out.push('\\'),
+                    _ => return Err("unsupported escape in string".to_string()),
+                },
+                c => out.push(c),
+            }
+        }
+        return Err("unterminated string".to_string());
+    }
+    if let Some(body) = v.strip_prefix('\'') {
+        let end = body.find('\'').ok_or("unterminated string")?;
+        return Ok((TomlValue::String(body[..end].to_string()), &body[end + 1..]));
+    }
+    if let Some(mut body) = v.strip_prefix('[') {
+        let mut items = Vec::new();
+        loop {
+            body = body.trim_start();
+            if let Some(rest) = body.strip_prefix(']') {
+                return Ok((TomlValue::Array(items), rest));
+            }
+            let (item, rest) = parse_value_prefix(body)?;
+            items.push(item);
+            body = rest.trim_start();
+            if let Some(rest) = body.strip_prefix(',') {
+                body = rest;
+            } else if !body.starts_with(']') {
+                return Err("expected ',' or ']' in array".to_string());
+            }
+        }
+    }
+    let end = v.find([',', ']']).unwrap_or(v.len());
+    let (token, rest) = (v[..end].trim(), &v[end..]);
+    let value = match token {
+        "true" => TomlValue::Bool(true),
+        "false" => TomlValue::Bool(false),
+        _ => {
+            let digits = token.replace('_', "");
+            if let Ok(i) = digits.parse::<i64>() {
+                TomlValue::Integer(i)
+            } else if let Ok(f) = digits.parse::<f64>() {
+                TomlValue::Float(f)
+            } else {
+                return Err(format!("unsupported value '{}'", token));
+            }
+        }
+    };
+    Ok((value, rest))
+}
+
+/// Turn a config entry into CLI flags: `true` -> `--key`, `false` -> `--no-key`,
+/// scalars -> `--key value`, arrays -> one `--key value` per element.
+pub fn entry_to_args(key: &str, value: &TomlValue) -> Vec<String> {
+    match value {
+        TomlValue::Bool(true) => vec![format!("--{}", key)],
+        TomlValue::Bool(false) => vec![format!("--no-{}", key)],
+        TomlValue::String(s) => vec![format!("--{}", key), s.clone()],
+        TomlValue::Integer(i) => vec![format!("--{}", key), i.to_string()],
+        TomlValue::Float(f) => vec![format!("--{}", key), f.to_string()],
+        TomlValue::Array(items) => items.iter().flat_map(|v| entry_to_args(key, v)).collect(),
+    }
+}
+
+/// Remove `--profile NAME` / `--profile=NAME` from `args`, returning the name.
+fn take_profile_flag(args: &mut Vec<String>) -> Result<Option<String>, String> {
+    let mut name = None;
+    let mut i = 1;
+    while i < args.len() {
+        if args[i] == "--profile" {
+            if i + 1 >= args.len() {
+                return Err("--profile requires a profile name".to_string());
+            }
+            name = Some(args.remove(i + 1));
+            args.remove(i);
+        } else if let Some(eq) = args[i].strip_prefix("--profile=") {
+            name = Some(eq.to_string());
+            args.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+    Ok(name)
+}
+
+/// Expand profile `name` from `config` into `args` (`args[0]` is the program).
+pub fn apply_profile(
+    mut args: Vec<String>,
+    name: &str,
+    config: &str,
+) -> Result<Vec<String>, String> {
+    let entries = parse_toml(config).map_err(|e| format!("{} {}", CONFIG_FILE, e))?;
+    let in_profile: Vec<&TomlEntry> = entries
+        .iter()
+        .filter(|e| e.table.len() >= 2 && e.table[0] == "profile" && e.table[1] == name)
+        .collect();
+    if in_profile.is_empty() {
+        return Err(format!("profile '{}' not found in {}", name, CONFIG_FILE));
+    }
+
+    let has_command = args.get(1).map(|a| !a.starts_with('-')).unwrap_or(false);
+    if !has_command {
+        let command = in_profile
+            .iter()
+            .find(|e| e.table.len() == 2 && e.key == "command")
+            .and_then(|e| match &e.value {
+                TomlValue::String(s) => Some(s.clone()),
+                _ => None,
+            })
+            .ok_or_else(|| {
+                format!(
+                    "no command given and profile '{}' does not set 'command'",
+                    name
+                )
+            })?;
+        args.insert(1, command);
+    }
+    let command = args[1].clone();
+
+    let mut injected = Vec::new();
+    for e in &in_profile {
+        let applies = match e.table.len() {
+            2 => e.key != "command",
+            3 => e.table[2] == command,
+            _ => false,
+        };
+        if !applies {
+            continue;
+        }
+        let flags = entry_to_args(&e.key, &e.value);
+        // `key = false` for a switch without a `--no-` form is just its default.
+        let plain_default = e.value == TomlValue::Bool(false)
+            && !flags
+                .first()
+                .is_some_and(|f| crate::cli::accepts_option(&command, f))
+            && crate::cli::accepts_option(&command, &format!("--{}", e.key));
+        if !plain_default {
+            injected.extend(flags);
+        }
+    }
+    let at = injection_point(&args);
+    args.splice(at..at, injected);
+    Ok(args)
+}
+
+/// Where injected flags go: after the command and its leading positional
+/// arguments (user <name>, compare <refA> <refB>), before every flag.
+fn injection_point(args: &[String]) -> usize {
+    2 + args[2..].iter().take_while(|a| !a.starts_with('-')).count()
+}
+
+/// Location of the repo-local config: the work-tree root, or the CWD outside a repo.
+pub fn repo_config_path() -> PathBuf {
+    run_command(&["rev-parse", "--show-toplevel"])
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("."))
+        .join(CONFIG_FILE)
+}
+
+/// Apply `--profile` if present; other invocations are returned unchanged.
+pub fn expand_profile_args(mut args: Vec<String>) -> Result<Vec<String>, String> {
+    let Some(name) = take_profile_flag(&mut args)? else {
+        return Ok(args);
+    };
+    let path = repo_config_path();
+    let text = read_config(&path)?;
+    apply_profile(args, &name, &text)
+}
+
+/// Location of the per-user config: `$XDG_CONFIG_HOME/git-insights/config.toml`,
+/// falling back to `~/.config`; `None` without a home directory.
+pub fn user_config_path() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME").filter(|v| !v.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("git-insights").join("config.toml"))
+}
+
+/// Flags from the `[defaults]` and `[defaults.<command>]` tables of one
+/// config file. Top-level defaults are skipped by commands that do not take
+/// the option; a command table naming an unknown option is an error.
+pub fn default_args(command: &str, config: &str) -> Result<Vec<String>, String> {
+    let mut injected = Vec::new();
+    for e in parse_toml(config)? {
+        let for_command = match e.table.as_slice() {
+            [t] if t == "defaults" => false,
+            [t, c] if t == "defaults" && c == command => true,
+            _ => continue,
+        };
+        let flags = entry_to_args(&e.key, &e.value);
+        let Some(flag) = flags.first() else {
+            continue;
+        };
+        // `key = false` for a switch without a `--no-` form is just its default.
+        let plain_default = e.value == TomlValue::Bool(false)
+            && crate::cli::accepts_option(command, &format!("--{}", e.key));
+        if crate::cli::accepts_option(command, flag) {
+            injected.extend(flags);
+        } else if for_command && !plain_default {
+            return Err(format!(
+                "[defaults.{}]: '{}' is not an option of '{}'",
+                command, e.key, command
+            ));
+        }
+    }
+    Ok(injected)
+}
+
+/// Mailmap lines (`Proper Name <proper@email> Commit Name <commit@email>`)
+/// for the `[aliases]` table: each key is the canonical identity and its
+/// value one alias or an array of them, either `Name <email>` or `<email>`.
+pub fn alias_mailmap(config: &str) -> Result<Vec<String>, String> {
+    let is_identity = |s: &str| {
+        let s = s.trim();
+        s.ends_with('>') && s.find('<').is_some_and(|i| i + 2 < s.len())
+    };
+    let mut lines = Vec::new();
+    for e in parse_toml(config)? {
+        if e.table != ["aliases"] {
+            continue;
+        }
+        if !is_identity(&e.key) {
+            return Err(format!(
+                "[aliases]: '{}' must be an identity like 'Name <email>'",
+                e.key
+            ));
+        }
+        let aliases = match &e.value {
+            TomlValue::Array(items) => items.clone(),
+            v => vec![v.clone()],
+        };
+        for alias in aliases {
+            match alias {
+                TomlValue::String(a) if is_identity(&a) => {
+                    lines.push(format!("{} {}", e.key.trim(), a.trim()))
+                }
+                other => {
+                    return Err(format!(
+                        "[aliases]: '{}' maps to {:?}, expected 'Name <email>' or '<email>'",
+                        e.key, other
+                    ))
+                }
+            }
+        }
+    }
+    Ok(lines)
+}
+
+/// `--class NAME=PATTERN` flags for the `[classes]` table: each key is a
+/// class name and its value one pattern or an array of them.
+pub fn class_args(config: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    for e in parse_toml(config)? {
+        if e.table != ["classes"] {
+            continue;
+        }
+        let patterns = match &e.value {
+            TomlValue::Array(items) => items.clone(),
+            v => vec![v.clone()],
+        };
+        for pattern in patterns {
+            match pattern {
+                TomlValue::String(p) if !p.trim().is_empty() => {
+                    args.push("--class".to_string());
+                    args.push(format!("{}={}", e.key, p));
+                }
+                other => {
+                    return Err(format!(
+                        "[classes]: '{}' maps to {:?}, expected a path pattern",
+                        e.key, other
+                    ))
+                }
+            }
+        }
+    }
+    Ok(args)
+}
+
+/// `(name, "#rrggbb,...")` for each entry of the `[palettes]` table, whose
+/// values are arrays of at least two hex colors, low to high.
+pub fn palette_ramps(config: &str) -> Result<Vec<(String, String)>, String> {
+    let mut ramps = Vec::new();
+    for e in parse_toml(config)? {
+        if e.table != ["palettes"] {
+            continue;
+        }
+        let stops: Option<Vec<String>> = match &e.value {
+            TomlValue::Array(items) => items
+                .iter()
+                .map(|v| match v {
+                    TomlValue::String(c) => Some(c.trim().to_string()),
+                    _ => None,
+                })
+                .collect(),
+            _ => None,
+        };
+        match stops.map(|s| s.join(",")) {
+            Some(ramp) if crate::palette::Palette::parse(&ramp).is_ok() => {
+                ramps.push((e.key, ramp))
+            }
+            _ => {
+                return Err(format!(
+                    "[palettes]: '{}' maps to {:?}, expected at least two '#rrggbb' colors",
+                    e.key, e.value
+                ))
+            }
+        }
+    }
+    Ok(ramps)
+}
+
+/// Replace `--palette NAME` values naming a configured ramp with its colors;
+/// later entries (the repo file) win.
+fn resolve_palette_args(args: &mut [String], ramps: &[(String, String)]) {
+    let lookup = |v: &str| {
+        ramps
+            .iter()
+            .rev()
+            .find(|(name, _)| name == v)
+            .map(|(_, ramp)| ramp.clone())
+    };
+    let mut i = 0;
+    while i < args.len() {
+        if args[i] == "--palette" {
+            if let Some(ramp) = args.get(i + 1).and_then(|v| lookup(v)) {
+                args[i + 1] = ramp;
+            }
+            i += 1;
+        } else if let Some(ramp) = args[i].strip_prefix("--palette=").and_then(lookup) {
+            args[i] = format!("--palette={}", ramp);
+        }
+        i += 1;
+    }
+}
+
+/// Write alias lines to `.git/git-insights/aliases.mailmap` and return its
+/// absolute path for `--mailmap`.
+fn write_alias_mailmap(lines: &[String]) -> Result<PathBuf, String> {
+    let git_path = run_command(&["rev-parse", "--git-path", "git-insights/aliases.mailmap"])?;
+    let path = std::path::absolute(repo_relative(&git_path))
+        .map_err(|e| format!("{}: {}", git_path, e))?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    }
+    let text = format!(
+        "# Generated by git-insights from [aliases] and --mailmap; edit those instead.\n{}\n",
+        lines.join("\n")
+    );
+    std::fs::write(&path, text).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// Remove every `--mailmap FILE` / `--mailmap=FILE` from `args`, returning
+/// the last file given.
+fn take_mailmap_flag(args: &mut Vec<String>) -> Option<String> {
+    let mut file = None;
+    let mut i = 2;
+    while i < args.len() {
+        if args[i] == "--mailmap" && i + 1 < args.len() {
+            file = Some(args.remove(i + 1));
+            args.remove(i);
+        } else if let Some(eq) = args[i].strip_prefix("--mailmap=") {
+            file = Some(eq.to_string());
+            args.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+    file
+}
+
+/// Insert the defaults of the user and repo config files (in that order)
+/// before the command's own flags. Invocations without a known command are
+/// returned unchanged, and missing files are fine.
+pub fn expand_config_defaults(mut args: Vec<String>) -> Result<Vec<String>, String> {
+    let command = match args.get(1) {
+        // Every known command takes `--help`.
+        Some(c) if crate::cli::accepts_option(c, "--help") => c.clone(),
+        _ => return Ok(args),
+    };
+    let mut injected = Vec::new();
+    let mut aliases = Vec::new();
+    let mut ramps = Vec::new();
+    // Classes given on the command line replace the configured ones.
+    let explicit_classes = args[2..]
+        .iter()
+        .any(|a| a == "--class" || a.starts_with("--class="));
+    for path in user_config_path().into_iter().chain([repo_config_path()]) {
+        if !path.is_file() {
+            continue;
+        }
+        let text = read_config(&path)?;
+        let in_file = |e: String| format!("{} {}", path.display(), e);
+        injected.extend(default_args(&command, &text).map_err(in_file)?);
+        aliases.extend(alias_mailmap(&text).map_err(in_file)?);
+        ramps.extend(palette_ramps(&text).map_err(in_file)?);
+        if crate::cli::accepts_option(&command, "--class") && !explicit_classes {
+            injected.extend(class_args(&text).map_err(in_file)?);
+        }
+    }
+    if !aliases.is_empty() {
+        // A `--mailmap` of the user's own is merged in after the aliases, so
+        // both apply and its entries win where they overlap.
+        if let Some(own) = take_mailmap_flag(&mut args) {
+            let text = std::fs::read_to_string(&own)
+                .map_err(|e| format!("failed to read --mailmap {}: {}", own, e))?;
+            aliases.push(text.trim_end().to_string());
+        }
+        let mailmap = write_alias_mailmap(&aliases)?;
+        injected.push("--mailmap".to_string());
+        injected.push(mailmap.to_string_lossy().into_owned());
+    }
+    let at = injection_point(&args);
+    args.splice(at..at, injected);
+    resolve_palette_args(&mut args, &ramps);
+    Ok(args)
+}
+
+/// Apply `--profile`, then the config-file defaults, to a raw command line
+/// before it is parsed. Reads the config files and may write the alias
+/// mailmap, so the binary and Python `run()` call it once up front.
+pub fn expand_args(args: Vec<String>) -> Result<Vec<String>, String> {
+    expand_config_defaults(expand_profile_args(args)?)
+}
+
+fn read_config(path: &Path) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(v: &[&str]) -> Vec<String> {
+        v.iter().map(|s| s.to_string()).collect()
+    }
+
+    const CONFIG: &str = r#"
+# shared settings
+[profile.ci]
+command = "stats"
+reachable-from = "v*"   # only shipped history
+
+[profile.ci.stats]
+by-email = true
+
+[profile.weekly-report.timeline]
+weeks = 12
+color = false
+
+[profile."weekly-report".user]
+top = 5
+sort = 'pct'
+"#;
+
+    #[test]
+    fn test_parse_toml_subset() {
+        let entries = parse_toml(CONFIG).expect("parse");
+        assert_eq!(entries.len(), 7);
+        assert_eq!(entries[1].table, vec!["profile", "ci"]);
+        assert_eq!(entries[1].value, TomlValue::String("v*".to_string()));
+        assert_eq!(entries[6].table, vec!["profile", "weekly-report", "user"]);
+
+        let arr = parse_toml("excludes = [\"a#b\", 'c', 3, true]").expect("array");
+        assert_eq!(
+            arr[0].value,
+            TomlValue::Array(vec![
+                TomlValue::String("a#b".to_string()),
+                TomlValue::String("c".to_string()),
+                TomlValue::Integer(3),
+                TomlValue::Bool(true),
+            ])
+        );
+        assert!(parse_toml("[profile.ci\n").is_err());
+        assert!(parse_toml("weeks = twelve").is_err());
+    }
+
+    #[test]
+    fn test_apply_profile_injects_flags_before_user_args() {
+        let out = apply_profile(
+            args(&["gi", "timeline", "--weeks", "4"]),
+            "weekly-report",
+            CONFIG,
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            args(&[
+                "gi",
+                "timeline",
+                "--weeks",
+                "12",
+                "--no-color",
+                "--weeks",
+                "4"
+            ])
+        );
+
+        let out = apply_profile(args(&["gi"]), "ci", CONFIG).unwrap();
+        assert_eq!(
+            out,
+            args(&["gi", "stats", "--reachable-from", "v*", "--by-email"])
+        );
+
+        let out = apply_profile(args(&["gi", "user", "alice"]), "weekly-report", CONFIG).unwrap();
+        assert_eq!(
+            out,
+            args(&["gi", "user", "alice", "--top", "5", "--sort", "pct"])
+        );
+
+        // `by-email` has no `--no-` form, so false leaves it off; `color` has one.
+        let config =
+            "[profile.quiet.stats]\nby-email = false\n[profile.quiet.timeline]\ncolor = false\n";
+        let out = apply_profile(args(&["gi", "stats"]), "quiet", config).unwrap();
+        assert_eq!(out, args(&["gi", "stats"]));
+        let out = apply_profile(args(&["gi", "timeline"]), "quiet", config).unwrap();
+        assert_eq!(out, args(&["gi", "timeline", "--no-color"]));
+        let out = crate::cli::Cli::parse_from_args(out);
+        assert!(out.is_ok(), "{:?}", out.err());
+
+        assert!(apply_profile(args(&["gi", "stats"]), "nope", CONFIG)
+            .unwrap_err()
+            .contains("not found"));
+        assert!(apply_profile(args(&["gi"]), "weekly-report", CONFIG).is_err());
+    }
+
+    #[test]
+    fn test_take_profile_flag() {
+        let mut a = args(&["gi", "stats", "--profile", "ci", "-e"]);
+        assert_eq!(take_profile_flag(&mut a).unwrap().as_deref(), Some("ci"));
+        assert_eq!(a, args(&["gi", "stats", "-e"]));
+
+        let mut a = args(&["gi", "--profile=ci"]);
+        assert_eq!(take_profile_flag(&mut a).unwrap().as_deref(), Some("ci"));
+        assert_eq!(a, args(&["gi"]));
+    }
+
+    #[test]
+    fn test_default_args_per_command() {
+        let config = r#"
+[defaults]
+color = false
+weeks = 26
+exclude = ["vendor/", "*.lock"]
+
+[defaults.user]
+sort = "pct"
+by-email = false
+"#;
+        assert_eq!(
+            default_args("timeline", config).unwrap(),
+            args(&[
+                "--no-color",
+                "--weeks",
+                "26",
+                "--exclude",
+                "vendor/",
+                "--exclude",
+                "*.lock"
+            ])
+        );
+        // `user` takes neither --weeks nor --no-color.
+        assert_eq!(
+            default_args("user", config).unwrap(),
+            args(&[
+                "--exclude",
+                "vendor/",
+                "--exclude",
+                "*.lock",
+                "--sort",
+                "pct"
+            ])
+        );
+        let err = default_args("stats", "[defaults.stats]\nweeks = 3").unwrap_err();
+        assert!(
+            err.contains("'weeks' is not an option of 'stats'"),
+            "{}",
+            err
+        );
+
+        let out = {
+            let mut a = args(&["gi", "compare", "v1", "v2", "-e"]);
+            let at = injection_point(&a);
+            a.splice(at..at, args(&["--no-color"]));
+            a
+        };
+        assert_eq!(
+            out,
+            args(&["gi", "compare", "v1", "v2", "--no-color", "-e"])
+        );
+    }
+
+    #[test]
+    fn test_alias_mailmap_lines() {
+        let config = r#"
+[aliases]
+"Ann Lee <ann@corp.io>" = ["ann <ann@home.net>", "<alee@old.io>"]
+"Bob <bob@corp.io>" = "bobby <bob@laptop>"
+"#;
+        assert_eq!(
+            alias_mailmap(config).unwrap(),
+            args(&[
+                "Ann Lee <ann@corp.io> ann <ann@home.net>",
+                "Ann Lee <ann@corp.io> <alee@old.io>",
+                "Bob <bob@corp.io> bobby <bob@laptop>",
+            ])
+        );
+        assert!(alias_mailmap("[aliases]\nAnn = \"ann <a@x>\"").is_err());
+        assert!(alias_mailmap("[aliases]\n\"Ann <a@x>\" = [\"ann\"]").is_err());
+    }
+
+    #[test]
+    fn test_class_args() {
+        let config = r#"
+[classes]
+tests = ["tests/", "**/*_test.rs"]
+docs = "*.md"
+"#;
+        assert_eq!(
+            class_args(config).unwrap(),
+            args(&[
+                "--class",
+                "tests=tests/",
+                "--class",
+                "tests=**/*_test.rs",
+                "--class",
+                "docs=*.md",
+            ])
+        );
+        assert!(class_args("[classes]\ntests = 3").is_err());
+    }
+
+    #[test]
+    fn test_palette_ramps() {
+        let config = r##"
+[palettes]
+ocean = ["#03045e", "#0077b6", "#90e0ef"]
+"##;
+        let ramps = palette_ramps(config).unwrap();
+        assert_eq!(
+            ramps,
+            vec![("ocean".to_string(), "#03045e,#0077b6,#90e0ef".to_string())]
+        );
+        assert!(palette_ramps("[palettes]\nocean = \"#03045e\"").is_err());
+        assert!(palette_ramps("[palettes]\nocean = [\"blue\", \"#0077b6\"]").is_err());
+
+        let mut cli_args = args(&["gi", "heatmap", "--palette", "ocean"]);
+        resolve_palette_args(&mut cli_args, &ramps);
+        assert_eq!(cli_args[3], "#03045e,#0077b6,#90e0ef");
+        let mut cli_args = args(&["gi", "heatmap", "--palette=ocean", "--palette=mono"]);
+        resolve_palette_args(&mut cli_args, &ramps);
+        assert_eq!(cli_args[2], "--palette=#03045e,#0077b6,#90e0ef");
+        assert_eq!(cli_args[3], "--palette=mono");
+    }
+
+    #[test]
+    fn test_parse_from_args_ignores_config_files() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        let user_dir = repo.path.join("xdg").join("git-insights");
+        std::fs::create_dir_all(&user_dir).expect("mkdir");
+        std::fs::write(user_dir.join("config.toml"), "[defaults]\nweeks = 3\n").expect("write");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        let old_xdg = std::env::var_os("XDG_CONFIG_HOME");
+        std::env::set_var("XDG_CONFIG_HOME", repo.path.join("xdg"));
+        let plain = crate::cli::Cli::parse_from_args(args(&["gi", "timeline"]));
+        let expanded = expand_args(args(&["gi", "timeline"]));
+        std::env::set_current_dir(old).ok();
+        match old_xdg {
+            Some(v) => std::env::set_var("XDG_CONFIG_HOME", v),
+            None => std::env::remove_var("XDG_CONFIG_HOME"),
+        }
+
+        assert!(matches!(
+            plain.expect("parse").command,
+            crate::cli::Commands::Timeline { weeks: None, .. }
+        ));
+        assert_eq!(
+            expanded.expect("expand"),
+            args(&["gi", "timeline", "--weeks", "3"])
+        );
+    }
+
+    #[test]
+    fn test_repo_config_defaults_and_aliases() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        repo.commit_with_epoch("Ann Lee", "ann@corp.io", "a.txt", "one", 1_600_000_000)
+            .expect("commit");
+        repo.commit_with_epoch("ann", "ann@home.net", "b.txt", "two", 1_600_000_100)
+            .expect("commit");
+        std::fs::write(
+            repo.path.join(CONFIG_FILE),
+            "[defaults.timeline]\nweeks = 8\n\n[aliases]\n\"Ann Lee <ann@corp.io>\" = \"ann <ann@home.net>\"\n",
+        )
+        .expect("write config");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        let parse = |v: &[&str]| expand_args(args(v)).and_then(crate::cli::Cli::parse_from_args);
+        let cli = parse(&["gi", "timeline", "--weeks", "4"]);
+        let defaulted = parse(&["gi", "timeline"]);
+        let shortlog = cli.as_ref().ok().map(|cli| {
+            crate::git::set_mailmap_file(cli.mailmap.clone());
+            let out = run_command(&["shortlog", "-sne", "HEAD"]);
+            crate::git::set_mailmap_file(None);
+            out
+        });
+        std::env::set_current_dir(old).ok();
+
+        let cli = cli.expect("parse");
+        assert!(matches!(
+            cli.command,
+            crate::cli::Commands::Timeline { weeks: Some(4), .. }
+        ));
+        assert!(matches!(
+            defaulted.expect("parse").command,
+            crate::cli::Commands::Timeline { weeks: Some(8), .. }
+        ));
+        let mailmap = cli.mailmap.expect("generated mailmap");
+        assert!(mailmap.ends_with("git-insights/aliases.mailmap"));
+        let shortlog = shortlog.unwrap().expect("shortlog");
+        assert_eq!(shortlog.lines().count(), 1, "{}", shortlog);
+        assert!(
+            shortlog.contains("2\tAnn Lee <ann@corp.io>"),
+            "{}",
+            shortlog
+        );
+    }
+
+    #[test]
+    fn test_aliases_merge_with_explicit_mailmap() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        let people = [
+            ("Ann Lee", "ann@corp.io"),
+            ("ann", "ann@home.net"),
+            ("Bob", "bob@old.io"),
+            ("Robert", "bob@new.io"),
+        ];
+        for (i, (name, email)) in people.iter().enumerate() {
+            repo.commit_with_epoch(name, email, "a.txt", name, 1_600_000_000 + i as u64)
+                .expect("commit");
+        }
+        std::fs::write(
+            repo.path.join(CONFIG_FILE),
+            "[aliases]\n\"Ann Lee <ann@corp.io>\" = \"ann <ann@home.net>\"\n",
+        )
+        .expect("write config");
+        std::fs::write(
+            repo.path.join("people.mailmap"),
+            "Robert <bob@new.io> Bob <bob@old.io>\n",
+        )
+        .expect("write mailmap");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        let expanded = expand_args(args(&["gi", "stats", "--mailmap", "people.mailmap"]));
+        let shortlog = expanded.as_ref().ok().map(|a| {
+            let cli = crate::cli::Cli::parse_from_args(a.clone()).expect("parse");
+            crate::git::set_mailmap_file(cli.mailmap.clone());
+            let out = run_command(&["shortlog", "-sne", "HEAD"]);
+            crate::git::set_mailmap_file(None);
+            out
+        });
+        let missing = expand_args(args(&["gi", "stats", "--mailmap=nope.mailmap"]));
+        std::env::set_current_dir(old).ok();
+
+        let expanded = expanded.expect("expand");
+        assert_eq!(expanded.iter().filter(|a| *a == "--mailmap").count(), 1);
+        assert!(!expanded.iter().any(|a| a == "people.mailmap"));
+        let shortlog = shortlog.unwrap().expect("shortlog");
+        assert_eq!(shortlog.lines().count(), 2, "{}", shortlog);
+        assert!(
+            shortlog.contains("2\tAnn Lee <ann@corp.io>"),
+            "{}",
+            shortlog
+        );
+        assert!(shortlog.contains("2\tRobert <bob@new.io>"), "{}", shortlog);
+        assert!(missing
+            .unwrap_err()
+            .contains("failed to read --mailmap nope.mailmap"));
+    }
+}
@@ -0,0 +1,193 @@
+use crate::code_frequency::ymd_from_unix;
+use crate::commits::{collect_commits, Commit};
+use crate::error::GitInsightsError;
+use crate::output::truncate;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// One author's span of activity.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Contributor {
+    pub name: String,
+    /// Author time of the earliest and latest commit.
+    pub first: u64,
+    pub last: u64,
+    /// Distinct `(year, month)` pairs with at least one commit.
+    pub active_months: usize,
+    pub commits: usize,
+}
+
+/// Row order for the contributors table.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[non_exhaustive]
+pub enum ContributorSort {
+    /// Onboarding order: earliest first commit first.
+    #[default]
+    First,
+    /// Most recently active first.
+    Last,
+    Commits,
+}
+
+impl ContributorSort {
+    pub fn parse(s: &str) -> Result<ContributorSort, String> {
+        match s {
+            "first" => Ok(ContributorSort::First),
+            "last" => Ok(ContributorSort::Last),
+            "commits" => Ok(ContributorSort::Commits),
+            other => Err(format!(
+                "unknown --sort '{}'. Expected first, last or commits.",
+                other
+            )),
+        }
+    }
+}
+
+/// Fold commits into per-author lifecycles, ordered by `sort`.
+pub fn contributor_lifecycles(commits: &[Commit], sort: ContributorSort) -> Vec<Contributor> {
+    let mut by_author: BTreeMap<&str, Contributor> = BTreeMap::new();
+    let mut months: BTreeSet<(&str, i32, u32)> = BTreeSet::new();
+    for c in commits {
+        let entry = by_author
+            .entry(&c.author_name)
+            .or_insert_with(|| Contributor {
+                name: c.author_name.clone(),
+                first: c.author_time,
+                last: c.author_time,
+                ..Default::default()
+            });
+        entry.first = entry.first.min(c.author_time);
+        entry.last = entry.last.max(c.author_time);
+        entry.commits += 1;
+        let (y, m, _) = ymd_from_unix(c.author_time);
+        months.insert((&c.author_name, y, m));
+    }
+    for (name, _, _) in months {
+        if let Some(row) = by_author.get_mut(name) {
+            row.active_months += 1;
+        }
+    }
+    let mut rows: Vec<Contributor> = by_author.into_values().collect();
+    match sort {
+        ContributorSort::First => rows.sort_by_key(|r| r.first),
+        ContributorSort::Last => rows.sort_by_key(|r| std::cmp::Reverse(r.last)),
+        ContributorSort::Commits => rows.sort_by_key(|r| std::cmp::Reverse(r.commits)),
+    }
+    rows
+}
+
+/// Authors whose first and last commits fall in each year: `year -> (joined, last seen)`.
+pub fn joined_and_left_by_year(rows: &[Contributor]) -> BTreeMap<i32, (usize, usize)> {
+    let mut years: BTreeMap<i32, (usize, usize)> = BTreeMap::new();
+    for r in rows {
+        years.entry(ymd_from_unix(r.first).0).or_default().0 += 1;
+        years.entry(ymd_from_unix(r.last).0).or_default().1 += 1;
+    }
+    years
+}
+
+/// Lifecycles for the active filter. Merge commits are counted the same way
+/// as in `stats`: only with `--include-merges`.
+pub fn gather_contributors(sort: ContributorSort) -> Result<Vec<Contributor>, GitInsightsError> {
+    let keep_merges = crate::filter::active().include_merges;
+    let commits: Vec<Commit> = collect_commits()?
+        .into_iter()
+        .filter(|c| keep_merges || !c.is_merge())
+        .collect();
+    Ok(contributor_lifecycles(&commits, sort))
+}
+
+fn format_date(t: u64) -> String {
+    let (y, m, d) = ymd_from_unix(t);
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+/// Print one row per author, then how many authors joined and went quiet per year.
+pub fn run_contributors(sort: ContributorSort) -> Result<(), GitInsightsError> {
+    let rows = gather_contributors(sort)?;
+    println!(
+        "| {:<28} | {:<10} | {:<10} | {:>6} | {:>7} |",
+        "author", "first", "last", "months", "commits"
+    );
+    println!(
+        "|:{:-<29}|:{:-<11}|:{:-<11}|{:->8}|{:->9}|",
+        "", "", "", "", ""
+    );
+    for r in &rows {
+        println!(
+            "| {:<28} | {:<10} | {:<10} | {:>6} | {:>7} |",
+            truncate(&r.name, 28),
+            format_date(r.first),
+            format_date(r.last),
+            r.active_months,
+            r.commits
+        );
+    }
+
+    println!();
+    println!("| {:<4} | {:>6} | {:>9} |", "year", "joined", "last seen");
+    println!("|:{:-<5}|{:->8}|{:->11}|", "", "", "");
+    for (year, (joined, left)) in joined_and_left_by_year(&rows) {
+        println!("| {:<4} | {:>6} | {:>9} |", year, joined, left);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contributor_lifecycles() {
+        let commit = |name: &str, t: u64| Commit {
+            author_name: name.to_string(),
+            author_time: t,
+            ..Default::default()
+        };
+        // 2023-11-14, 2023-11-20, 2024-01-10 and 2024-03-01 (UTC).
+        let commits = vec![
+            commit("Bob", 1_709_251_200),
+            commit("Ann", 1_704_844_800),
+            commit("Ann", 1_700_438_400),
+            commit("Ann", 1_699_920_000),
+        ];
+        let rows = contributor_lifecycles(&commits, ContributorSort::First);
+        let names: Vec<&str> = rows.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["Ann", "Bob"]);
+        assert_eq!(
+            (rows[0].first, rows[0].last),
+            (1_699_920_000, 1_704_844_800)
+        );
+        assert_eq!((rows[0].active_months, rows[0].commits), (2, 3));
+
+        let by_last = contributor_lifecycles(&commits, ContributorSort::Last);
+        assert_eq!(by_last[0].name, "Bob");
+
+        let years = joined_and_left_by_year(&rows);
+        assert_eq!(years[&2023], (1, 0));
+        assert_eq!(years[&2024], (1, 2));
+        assert!(ContributorSort::parse("tenure").is_err());
+    }
+
+    #[test]
+    fn test_gather_contributors_e2e() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", "a", 1_699_920_000)
+            .expect("commit");
+        repo.commit_with_epoch("Bob", "bob@x.io", "a.txt", "b", 1_704_844_800)
+            .expect("commit");
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", "c", 1_709_251_200)
+            .expect("commit");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        let rows = gather_contributors(ContributorSort::Commits);
+        std::env::set_current_dir(old).ok();
+
+        let rows = rows.expect("contributors");
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].name, "Ann");
+        assert_eq!((rows[0].commits, rows[0].active_months), (2, 2));
+        assert_eq!(rows[1].first, 1_704_844_800);
+    }
+}
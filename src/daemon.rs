@@ -0,0 +1,500 @@
+use crate::code_frequency::ymd_from_unix;
+use crate::error::GitInsightsError;
+use crate::git::{run_command, short_hash, Repo};
+use crate::json;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Analyses the daemon can export on each cycle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum DaemonAnalysis {
+    Json,
+    Report,
+    LogJson,
+    Prometheus,
+}
+
+impl DaemonAnalysis {
+    pub fn parse(s: &str) -> Result<DaemonAnalysis, String> {
+        match s {
+            "json" => Ok(DaemonAnalysis::Json),
+            "report" => Ok(DaemonAnalysis::Report),
+            "log-json" => Ok(DaemonAnalysis::LogJson),
+            "prometheus" => Ok(DaemonAnalysis::Prometheus),
+            other => Err(format!(
+                "unknown analysis '{}'. Expected json, report, log-json or prometheus.",
+                other
+            )),
+        }
+    }
+
+    /// Parse a comma-separated list such as `json,report`.
+    pub fn parse_list(s: &str) -> Result<Vec<DaemonAnalysis>, String> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|a| !a.is_empty())
+            .map(|a| DaemonAnalysis::parse(&a.to_lowercase()))
+            .collect()
+    }
+
+    /// Export file name stem and extension.
+    fn file_parts(self) -> (&'static str, &'static str) {
+        match self {
+            DaemonAnalysis::Json => ("stats", "json"),
+            DaemonAnalysis::Report => ("report", "html"),
+            DaemonAnalysis::LogJson => ("log", "jsonl"),
+            DaemonAnalysis::Prometheus => ("metrics", "prom"),
+        }
+    }
+
+    fn export(self, path: &str) -> Result<(), GitInsightsError> {
+        match self {
+            DaemonAnalysis::Json => Ok(crate::stats::export_json(Some(path))?),
+            DaemonAnalysis::Report => {
+                crate::report::run_report(crate::report::ReportFormat::Html, Some(path))
+            }
+            DaemonAnalysis::LogJson => crate::commits::export_log_json(Some(path)),
+            DaemonAnalysis::Prometheus => crate::prometheus::export_prometheus(path),
+        }
+    }
+}
+
+/// Settings for `git-insights daemon`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DaemonConfig {
+    pub interval: Duration,
+    pub repos_file: String,
+    pub export_dir: String,
+    pub analyses: Vec<DaemonAnalysis>,
+    /// Webhook URL each cycle's digest is POSTed to (`--notify`).
+    pub notify: Option<String>,
+    /// Run a single cycle and exit (useful under an external scheduler or in tests).
+    pub once: bool,
+}
+
+/// Validate CLI values and fill in defaults (1h interval, json + report).
+pub fn build_daemon_config(
+    interval: Option<&str>,
+    repos_file: Option<&str>,
+    export_dir: Option<&str>,
+    analyses: Option<&str>,
+    notify: Option<&str>,
+    once: bool,
+) -> Result<DaemonConfig, GitInsightsError> {
+    let usage = |msg: &str| GitInsightsError::Usage(msg.to_string());
+    Ok(DaemonConfig {
+        interval: parse_interval(interval.unwrap_or("1h")).map_err(GitInsightsError::Usage)?,
+        repos_file: repos_file
+            .ok_or_else(|| usage("daemon requires --repos-file FILE"))?
+            .to_string(),
+        export_dir: export_dir
+            .ok_or_else(|| usage("daemon requires --export-dir DIR"))?
+            .to_string(),
+        analyses: DaemonAnalysis::parse_list(analyses.unwrap_or("json,report"))
+            .map_err(GitInsightsError::Usage)?,
+        notify: notify.map(str::to_string),
+        once,
+    })
+}
+
+/// Parse `90`, `45s`, `30m`, `1h` or `2d` into a duration.
+pub fn parse_interval(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let (num, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(pos) => s.split_at(pos),
+        None => (s, "s"),
+    };
+    let invalid = || format!("invalid --interval '{}'. Use e.g. 30m, 1h or 2d.", s);
+    let n: u64 = num.parse().map_err(|_| invalid())?;
+    let unit_secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3_600,
+        "d" => 86_400,
+        _ => return Err(invalid()),
+    };
+    let secs = n.checked_mul(unit_secs).ok_or_else(invalid)?;
+    if secs == 0 {
+        return Err("--interval must be greater than zero".to_string());
+    }
+    Ok(Duration::from_secs(secs))
+}
+
+/// Repository paths from a repos file: one per line, `#` comments allowed.
+pub fn parse_repos_file(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|l| l.split_once('#').map(|(p, _)| p).unwrap_or(l).trim())
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Compact UTC stamp used in export names, e.g. `20240131T235959Z`.
+pub fn timestamp_slug(t: u64) -> String {
+    let (y, m, d) = ymd_from_unix(t);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        y,
+        m,
+        d,
+        (t / 3_600) % 24,
+        (t / 60) % 60,
+        t % 60
+    )
+}
+
+/// Directory name for a repo's exports: its final path component plus a hash
+/// of its full path, so same-named repos do not share exports.
+fn repo_slug(repo: &str) -> String {
+    let name = Path::new(repo.trim_end_matches('/'))
+        .file_name()
+        .map(|n| n.to_string_lossy().trim_end_matches(".git").to_string())
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| "repo".to_string());
+    let full = fs::canonicalize(repo)
+        .or_else(|_| std::path::absolute(repo))
+        .unwrap_or_else(|_| PathBuf::from(repo));
+    let hash = short_hash(full.to_string_lossy().as_bytes());
+    format!("{}-{}", name, &hash[..8])
+}
+
+fn log(msg: &str) {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    eprintln!("[{}] {}", timestamp_slug(now), msg);
+}
+
+/// Worktree the daemon analyses upstream in, inside the repo's git directory.
+const WORKTREE_PATH: &str = "git-insights/daemon-worktree";
+
+/// Fetch `repo` and return the tree to analyse. When the checked-out branch
+/// has an upstream, that is a detached worktree at `@{upstream}` kept in the
+/// git directory, so the user's working tree and branch are never touched;
+/// otherwise (e.g. a bare mirror, which the fetch updates) `repo` itself.
+/// Failures are logged; the repo is still analysed.
+fn refresh(repo: &str) -> String {
+    if let Err(e) = run_command(&["-C", repo, "fetch", "--quiet", "--all", "--prune"]) {
+        log(&format!("{}: fetch failed: {}", repo, e));
+    }
+    let Ok(upstream) = run_command(&["-C", repo, "rev-parse", "--verify", "@{upstream}"]) else {
+        return repo.to_string();
+    };
+    let worktree = match run_command(&[
+        "-C",
+        repo,
+        "rev-parse",
+        "--path-format=absolute",
+        "--git-path",
+        WORKTREE_PATH,
+    ]) {
+        Ok(path) => path,
+        Err(e) => {
+            log(&format!("{}: cannot locate the git directory: {}", repo, e));
+            return repo.to_string();
+        }
+    };
+    let checkout = if Path::new(&worktree).join(".git").exists() {
+        run_command(&[
+            "-C", &worktree, "checkout", "--quiet", "--detach", "--force", &upstream,
+        ])
+    } else {
+        let _ = run_command(&["-C", repo, "worktree", "prune"]);
+        run_command(&[
+            "-C", repo, "worktree", "add", "--quiet", "--detach", "--force", &worktree, &upstream,
+        ])
+    };
+    match checkout {
+        Ok(_) => worktree,
+        Err(e) => {
+            log(&format!("{}: cannot check out upstream: {}", repo, e));
+            repo.to_string()
+        }
+    }
+}
+
+/// What one cycle did for one repo, for the log and the digest.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RepoCycle {
+    pub repo: String,
+    /// Commit the analyses ran at, when it could be resolved.
+    pub head: Option<String>,
+    pub written: Vec<PathBuf>,
+    /// Analyses (`stats`, `report`, ...) that failed.
+    pub failed: Vec<String>,
+}
+
+/// Webhook payload summarising a cycle: `{"text": ...}`, which Slack,
+/// Mattermost and similar incoming webhooks accept as is.
+pub fn digest_json(cycles: &[RepoCycle], now: u64) -> String {
+    let written: usize = cycles.iter().map(|c| c.written.len()).sum();
+    let failed: usize = cycles.iter().map(|c| c.failed.len()).sum();
+    let mut text = format!(
+        "git-insights {}: {} repo(s), {} export(s) written, {} failed",
+        timestamp_slug(now),
+        cycles.len(),
+        written,
+        failed
+    );
+    for cycle in cycles {
+        text.push_str(&format!("\n- {}", cycle.repo));
+        if let Some(head) = &cycle.head {
+            text.push_str(&format!(" @ {}", &head[..head.len().min(12)]));
+        }
+        let names: Vec<String> = cycle
+            .written
+            .iter()
+            .filter_map(|p| p.file_name())
+            .map(|n| n.to_string_lossy().into_owned())
+            .collect();
+        if !names.is_empty() {
+            text.push_str(&format!(": {}", names.join(", ")));
+        }
+        if !cycle.failed.is_empty() {
+            text.push_str(&format!(" (failed: {})", cycle.failed.join(", ")));
+        }
+    }
+    format!("{{\"text\": \"{}\"}}", json::escape(&text))
+}
+
+/// POST `body` as JSON to `url` with curl, like `--github`, so no TLS stack
+/// is linked in. The body goes on stdin, never on the command line.
+fn post_json(url: &str, body: &str) -> Result<(), String> {
+    let mut child = Command::new("curl")
+        .args([
+            "--silent",
+            "--show-error",
+            "--fail",
+            "--header",
+            "Content-Type: application/json",
+            "--data-binary",
+            "@-",
+            url,
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("--notify needs curl on PATH: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(body.as_bytes())
+            .map_err(|e| format!("Failed to pass the digest to curl: {}", e))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("curl failed: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "curl failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Refresh and export every repo once.
+pub fn run_cycle(config: &DaemonConfig, repos: &[String], now: u64) -> Vec<RepoCycle> {
+    let stamp = timestamp_slug(now);
+    let export_root = std::path::absolute(&config.export_dir)
+        .unwrap_or_else(|_| PathBuf::from(&config.export_dir));
+    let mut cycles = Vec::new();
+
+    for repo in repos {
+        let mut cycle = RepoCycle {
+            repo: repo.clone(),
+            ..Default::default()
+        };
+        let tree = refresh(repo);
+        let dir = export_root.join(repo_slug(repo));
+        if let Err(e) = fs::create_dir_all(&dir) {
+            log(&format!("{}: cannot create {}: {}", repo, dir.display(), e));
+            cycles.push(cycle);
+            continue;
+        }
+        let scoped = match Repo::open(&tree) {
+            Ok(scoped) => scoped,
+            Err(e) => {
+                log(&format!("{}: cannot enter repo: {}", repo, e));
+                cycles.push(cycle);
+                continue;
+            }
+        };
+        cycle.head = scoped.run_command(&["rev-parse", "HEAD"]).ok();
+        for analysis in &config.analyses {
+            let (stem, ext) = analysis.file_parts();
+            let path = dir.join(format!("{}-{}.{}", stem, stamp, ext));
+            match scoped.scope(|| analysis.export(&path.to_string_lossy())) {
+                Ok(()) => cycle.written.push(path),
+                Err(e) => {
+                    log(&format!("{}: {} failed: {}", repo, stem, e));
+                    cycle.failed.push(stem.to_string());
+                }
+            }
+        }
+        cycles.push(cycle);
+    }
+    cycles
+}
+
+/// Run cycles forever (or once), sleeping `interval` between them.
+pub fn run_daemon(config: &DaemonConfig) -> Result<(), GitInsightsError> {
+    if config.analyses.is_empty() {
+        return Err("no analyses selected".into());
+    }
+    loop {
+        let text = fs::read_to_string(&config.repos_file)
+            .map_err(|e| format!("failed to read {}: {}", config.repos_file, e))?;
+        let repos = parse_repos_file(&text);
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| format!("clock error: {e}"))?
+            .as_secs();
+        log(&format!("refreshing {} repo(s)", repos.len()));
+        let cycles = run_cycle(config, &repos, now);
+        let written: usize = cycles.iter().map(|c| c.written.len()).sum();
+        log(&format!("cycle done, {} export(s) written", written));
+        if let Some(url) = &config.notify {
+            if let Err(e) = post_json(url, &digest_json(&cycles, now)) {
+                log(&format!("digest to {} failed: {}", url, e));
+            }
+        }
+        if config.once {
+            return Ok(());
+        }
+        std::thread::sleep(config.interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_interval_and_repos() {
+        assert_eq!(parse_interval("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_interval("30m").unwrap(), Duration::from_secs(1_800));
+        assert_eq!(parse_interval("1h").unwrap(), Duration::from_secs(3_600));
+        assert_eq!(parse_interval("2d").unwrap(), Duration::from_secs(172_800));
+        assert!(parse_interval("0h").is_err());
+        assert!(parse_interval("1w").is_err());
+        assert!(parse_interval("999999999999999999d").is_err());
+        assert_eq!(
+            parse_repos_file("# mirrors\n/srv/a\n\n ../b.git  # legacy\n"),
+            vec!["/srv/a", "../b.git"]
+        );
+        let slug = repo_slug("../b.git");
+        assert!(slug.starts_with("b-") && slug.len() == 10, "{}", slug);
+        assert_ne!(repo_slug("/srv/a/app"), repo_slug("/srv/b/app"));
+        assert_eq!(timestamp_slug(1_706_745_599), "20240131T235959Z");
+        assert_eq!(
+            DaemonAnalysis::parse_list("json, Log-Json").unwrap(),
+            vec![DaemonAnalysis::Json, DaemonAnalysis::LogJson]
+        );
+    }
+
+    #[test]
+    fn test_run_cycle_writes_timestamped_exports() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", "a", 1_700_000_000)
+            .expect("commit");
+        let out = repo.path.join("exports");
+        let config = DaemonConfig {
+            interval: Duration::from_secs(60),
+            repos_file: String::new(),
+            export_dir: out.to_string_lossy().into_owned(),
+            analyses: vec![DaemonAnalysis::LogJson],
+            notify: None,
+            once: true,
+        };
+        let repos = vec![repo.path.to_string_lossy().into_owned()];
+
+        let old = std::env::current_dir().unwrap();
+        let cycles = run_cycle(&config, &repos, 1_706_745_599);
+        assert_eq!(std::env::current_dir().unwrap(), old);
+
+        let name = repo_slug(&repos[0]);
+        let expected = out.join(name).join("log-20240131T235959Z.jsonl");
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].written, vec![expected.clone()]);
+        assert!(cycles[0].failed.is_empty());
+        assert!(cycles[0].head.is_some());
+        let text = fs::read_to_string(expected).expect("export");
+        assert!(text.contains("\"author\": \"Ann\""));
+    }
+
+    #[test]
+    fn test_run_cycle_analyses_upstream_without_touching_clone() {
+        let _guard = crate::test_sync::test_lock();
+        let upstream = crate::test_repo::TestRepo::init().expect("init repo");
+        upstream
+            .commit_with_epoch("Ann", "ann@x.io", "a.txt", "a", 1_700_000_000)
+            .expect("commit");
+        let clone = PathBuf::from(format!("{}-clone", upstream.path.display()));
+        let clone_str = clone.to_string_lossy().into_owned();
+        run_command(&[
+            "clone",
+            "--quiet",
+            &upstream.path.to_string_lossy(),
+            &clone_str,
+        ])
+        .expect("clone");
+        let before = run_command(&["-C", &clone_str, "rev-parse", "HEAD"]).expect("head");
+        upstream
+            .commit_with_epoch("Bob", "bob@x.io", "b.txt", "b", 1_700_100_000)
+            .expect("commit");
+
+        let out = upstream.path.join("exports");
+        let config = DaemonConfig {
+            interval: Duration::from_secs(60),
+            repos_file: String::new(),
+            export_dir: out.to_string_lossy().into_owned(),
+            analyses: vec![DaemonAnalysis::LogJson],
+            notify: None,
+            once: true,
+        };
+        let repos = vec![clone_str.clone()];
+        // The second cycle reuses the worktree the first one created.
+        run_cycle(&config, &repos, 1_706_745_599);
+        let cycles = run_cycle(&config, &repos, 1_706_745_600);
+
+        let text = fs::read_to_string(&cycles[0].written[0]).expect("export");
+        assert!(text.contains("\"author\": \"Bob\""));
+        let after = run_command(&["-C", &clone_str, "rev-parse", "HEAD"]).expect("head");
+        assert_eq!(before, after);
+        assert!(!clone.join("b.txt").exists());
+        assert_eq!(
+            run_command(&["-C", &clone_str, "status", "--porcelain"]).expect("status"),
+            ""
+        );
+        let _ = fs::remove_dir_all(&clone);
+    }
+
+    #[test]
+    fn test_digest_json() {
+        let cycles = vec![
+            RepoCycle {
+                repo: "/srv/app".to_string(),
+                head: Some("0123456789abcdef0123".to_string()),
+                written: vec![PathBuf::from("out/app-1/stats-20240131T235959Z.json")],
+                failed: vec!["report".to_string()],
+            },
+            RepoCycle {
+                repo: "/srv/gone".to_string(),
+                ..Default::default()
+            },
+        ];
+        assert_eq!(
+            digest_json(&cycles, 1_706_745_599),
+            "{\"text\": \"git-insights 20240131T235959Z: 2 repo(s), 1 export(s) written, 1 failed\
+             \\n- /srv/app @ 0123456789ab: stats-20240131T235959Z.json (failed: report)\
+             \\n- /srv/gone\"}"
+        );
+    }
+}
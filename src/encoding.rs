@@ -0,0 +1,253 @@
+use crate::error::GitInsightsError;
+use crate::git::read_head_blobs;
+use crate::merges::{blame_line_authors, run_blame_head, MergeResolver};
+use crate::output::{finish_progress, print_progress, truncate};
+use crate::stats::tracked_text_files_head;
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Instant;
+
+/// Line endings and UTF-8 validity of one file at HEAD.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FileEncoding {
+    pub path: String,
+    pub lines: usize,
+    pub lf: usize,
+    pub crlf: usize,
+    /// 0-based lines using the file's minority line ending (empty unless mixed).
+    pub mixed_lines: Vec<usize>,
+    /// 0-based lines that are not valid UTF-8.
+    pub invalid_lines: Vec<usize>,
+}
+
+impl FileEncoding {
+    pub fn is_mixed(&self) -> bool {
+        self.lf > 0 && self.crlf > 0
+    }
+
+    pub fn has_issues(&self) -> bool {
+        !self.mixed_lines.is_empty() || !self.invalid_lines.is_empty()
+    }
+}
+
+/// Classify every line of a blob. In a mixed file the less common ending is
+/// flagged; on a tie CRLF is, since LF is what git stores canonically.
+pub fn scan_blob(path: &str, bytes: &[u8]) -> FileEncoding {
+    let mut scan = FileEncoding {
+        path: path.to_string(),
+        ..Default::default()
+    };
+    let body = bytes.strip_suffix(b"\n").unwrap_or(bytes);
+    if bytes.is_empty() {
+        return scan;
+    }
+    let mut crlf_lines = Vec::new();
+    let mut lf_lines = Vec::new();
+    let mut pieces = body.split(|&b| b == b'\n').peekable();
+    let mut n = 0;
+    while let Some(line) = pieces.next() {
+        let terminated = pieces.peek().is_some() || body.len() < bytes.len();
+        if terminated {
+            if line.ends_with(b"\r") {
+                crlf_lines.push(n);
+            } else {
+                lf_lines.push(n);
+            }
+        }
+        if std::str::from_utf8(line).is_err() {
+            scan.invalid_lines.push(n);
+        }
+        n += 1;
+    }
+    scan.lines = n;
+    scan.lf = lf_lines.len();
+    scan.crlf = crlf_lines.len();
+    if scan.is_mixed() {
+        scan.mixed_lines = if scan.crlf <= scan.lf {
+            crlf_lines
+        } else {
+            lf_lines
+        };
+    }
+    scan
+}
+
+/// Flagged lines attributed to one author.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EncodingAuthor {
+    pub mixed: usize,
+    pub invalid: usize,
+    pub files: BTreeSet<String>,
+}
+
+/// Files with mixed line endings or non-UTF-8 lines, and who wrote those lines.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EncodingAudit {
+    pub scanned: usize,
+    pub files: Vec<FileEncoding>,
+    pub authors: BTreeMap<String, EncodingAuthor>,
+}
+
+/// Scan every text file at HEAD and blame the files that have issues.
+pub fn gather_encoding_audit() -> Result<EncodingAudit, GitInsightsError> {
+    let paths = tracked_text_files_head()?;
+    let mut audit = EncodingAudit {
+        scanned: paths.len(),
+        ..Default::default()
+    };
+    audit.files = paths
+        .iter()
+        .zip(read_head_blobs(&paths)?)
+        .filter_map(|(path, body)| Some(scan_blob(path, &body?)))
+        .filter(FileEncoding::has_issues)
+        .collect();
+    if audit.files.is_empty() {
+        return Ok(audit);
+    }
+
+    let authors = crate::filter::author_matcher()?;
+    let merges = MergeResolver::load()?;
+    let names = crate::identity::active_normalization();
+    let total = audit.files.len();
+    let start = Instant::now();
+    for (n, file) in audit.files.iter().enumerate() {
+        print_progress(n + 1, total, start);
+        let Ok(blame) = run_blame_head(&file.path) else {
+            continue;
+        };
+        let line_authors = blame_line_authors(&blame, &file.path, &merges);
+        let mut credit = |line: usize, invalid: bool| {
+            let Some((name, mail)) = line_authors.get(line) else {
+                return;
+            };
+            if !authors.allows(name, mail) {
+                return;
+            }
+            let entry = audit.authors.entry(names.apply(name.clone())).or_default();
+            if invalid {
+                entry.invalid += 1;
+            } else {
+                entry.mixed += 1;
+            }
+            entry.files.insert(file.path.clone());
+        };
+        for &line in &file.mixed_lines {
+            credit(line, false);
+        }
+        for &line in &file.invalid_lines {
+            credit(line, true);
+        }
+    }
+    finish_progress();
+    Ok(audit)
+}
+
+/// Print flagged files, then the authors of the flagged lines.
+pub fn run_encoding_audit() -> Result<(), GitInsightsError> {
+    let audit = gather_encoding_audit()?;
+    if audit.files.is_empty() {
+        println!(
+            "No mixed line endings or non-UTF-8 content in {} text files.",
+            audit.scanned
+        );
+        return Ok(());
+    }
+    println!(
+        "| {:<48} | {:>7} | {:>7} | {:>7} | {:>9} |",
+        "file", "lines", "LF", "CRLF", "non-UTF-8"
+    );
+    println!(
+        "|:{:-<49}|{:->9}|{:->9}|{:->9}|{:->11}|",
+        "", "", "", "", ""
+    );
+    for f in &audit.files {
+        println!(
+            "| {:<48} | {:>7} | {:>7} | {:>7} | {:>9} |",
+            truncate(&f.path, 48),
+            f.lines,
+            f.lf,
+            f.crlf,
+            f.invalid_lines.len()
+        );
+    }
+
+    let mut authors: Vec<(&String, &EncodingAuthor)> = audit.authors.iter().collect();
+    authors.sort_by_key(|(name, a)| (std::cmp::Reverse(a.mixed + a.invalid), *name));
+    println!();
+    println!(
+        "| {:<28} | {:>10} | {:>9} | {:>6} |",
+        "author", "mixed EOL", "non-UTF-8", "files"
+    );
+    println!("|:{:-<29}|{:->12}|{:->11}|{:->8}|", "", "", "", "");
+    for (name, a) in authors {
+        println!(
+            "| {:<28} | {:>10} | {:>9} | {:>6} |",
+            truncate(name, 28),
+            a.mixed,
+            a.invalid,
+            a.files.len()
+        );
+    }
+    println!();
+    println!(
+        "{} of {} text files need attention. To enforce LF, add '* text=auto' to .gitattributes and run 'git add --renormalize .'.",
+        audit.files.len(),
+        audit.scanned
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_blob() {
+        let clean = scan_blob("a", b"one\ntwo\nlast");
+        assert_eq!((clean.lines, clean.lf, clean.crlf), (3, 2, 0));
+        assert!(!clean.has_issues());
+
+        let mixed = scan_blob("b", b"one\r\ntwo\nthree\n\xff\xfe\r\n");
+        assert_eq!((mixed.lines, mixed.lf, mixed.crlf), (4, 2, 2));
+        assert!(mixed.is_mixed());
+        assert_eq!(mixed.mixed_lines, vec![0, 3]);
+        assert_eq!(mixed.invalid_lines, vec![3]);
+
+        let windows = scan_blob("c", b"one\r\ntwo\r\nthree\n");
+        assert_eq!(windows.mixed_lines, vec![2]);
+        assert_eq!(scan_blob("d", b"").lines, 0);
+    }
+
+    #[test]
+    fn test_gather_encoding_audit_e2e() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", "one", 1_700_000_000)
+            .expect("commit");
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", "two", 1_700_000_100)
+            .expect("commit");
+        repo.commit_with_epoch("Bob", "bob@x.io", "a.txt", "three\r", 1_700_000_200)
+            .expect("commit");
+        std::fs::write(repo.path.join("latin1.txt"), b"caf\xe9\nok\n").expect("write");
+        repo.commit_with_epoch("Cat", "cat@x.io", "clean.txt", "fine", 1_700_000_300)
+            .expect("commit");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        let audit = gather_encoding_audit();
+        std::env::set_current_dir(old).ok();
+
+        let audit = audit.expect("audit");
+        assert_eq!(audit.scanned, 3);
+        let flagged: Vec<&str> = audit.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(flagged, vec!["a.txt", "latin1.txt"]);
+        assert_eq!(
+            (audit.authors["Bob"].mixed, audit.authors["Bob"].invalid),
+            (1, 0)
+        );
+        assert_eq!(
+            (audit.authors["Cat"].mixed, audit.authors["Cat"].invalid),
+            (0, 1)
+        );
+        assert!(!audit.authors.contains_key("Ann"));
+    }
+}
@@ -0,0 +1,195 @@
+//! Error type of the git, stats and visualization layers.
+
+use std::fmt;
+use std::io;
+
+/// Why an analysis failed.
+///
+/// Modules that still report errors as `String` convert both ways, so `?`
+/// works across the boundary; the message is the `Display` output.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum GitInsightsError {
+    /// A git command ran and exited unsuccessfully.
+    Git {
+        /// The arguments after `git`, space separated.
+        command: String,
+        /// Exit code, `None` if git was killed by a signal.
+        code: Option<i32>,
+        stderr: String,
+    },
+    /// git could not be started or its pipes failed.
+    Spawn { command: String, source: io::Error },
+    /// Writing the rendered output failed, e.g. a closed pipe.
+    Io(io::Error),
+    /// A GitHub API request failed: no token, curl missing or failing, or an
+    /// error response (`--github`).
+    GitHub(String),
+    /// An option value the command does not accept, e.g. `--sort bogus`.
+    Usage(String),
+    /// Anything else, e.g. invalid input or unexpected git output.
+    Other(String),
+}
+
+impl GitInsightsError {
+    pub(crate) fn git(args: &[&str], code: Option<i32>, stderr: &[u8]) -> GitInsightsError {
+        GitInsightsError::Git {
+            command: args.join(" "),
+            code,
+            stderr: String::from_utf8_lossy(stderr).trim().to_string(),
+        }
+    }
+
+    pub(crate) fn spawn(args: &[&str], source: io::Error) -> GitInsightsError {
+        GitInsightsError::Spawn {
+            command: args.join(" "),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for GitInsightsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GitInsightsError::Git {
+                command,
+                code,
+                stderr,
+            } => {
+                write!(f, "'git {}' failed", command)?;
+                if let Some(code) = code {
+                    write!(f, " with exit code {}", code)?;
+                }
+                if !stderr.is_empty() {
+                    write!(f, ": {}", stderr)?;
+                }
+                Ok(())
+            }
+            GitInsightsError::Spawn { command, source } => {
+                write!(f, "Failed to execute 'git {}': {}", command, source)
+            }
+            GitInsightsError::Io(e) => write!(f, "Failed to write output: {}", e),
+            GitInsightsError::GitHub(msg) | GitInsightsError::Usage(msg) => f.write_str(msg),
+            GitInsightsError::Other(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for GitInsightsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GitInsightsError::Spawn { source, .. } | GitInsightsError::Io(source) => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl GitInsightsError {
+    /// How the binary exits when this error ends a run.
+    pub fn exit_kind(&self) -> ExitKind {
+        match self {
+            GitInsightsError::Git { .. } | GitInsightsError::Spawn { .. } => ExitKind::GitFailed,
+            GitInsightsError::Usage(_) => ExitKind::Usage,
+            _ => ExitKind::Failure,
+        }
+    }
+}
+
+/// Exit status of the `git-insights` binary and the Python `run()`, so
+/// scripts can branch on the kind of failure (0 is success).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ExitKind {
+    /// The analysis failed, or a check such as `codeowners-check` found problems.
+    Failure = 1,
+    /// The working directory is not inside a git repository.
+    NotARepo = 2,
+    /// `git` is not installed or not on `PATH`.
+    GitMissing = 3,
+    /// Unknown command or option, or an invalid value.
+    Usage = 4,
+    /// A git command failed or could not be started.
+    GitFailed = 5,
+}
+
+impl ExitKind {
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+}
+
+impl From<io::Error> for GitInsightsError {
+    fn from(e: io::Error) -> GitInsightsError {
+        GitInsightsError::Io(e)
+    }
+}
+
+impl From<String> for GitInsightsError {
+    fn from(msg: String) -> GitInsightsError {
+        GitInsightsError::Other(msg)
+    }
+}
+
+impl From<&str> for GitInsightsError {
+    fn from(msg: &str) -> GitInsightsError {
+        GitInsightsError::Other(msg.to_string())
+    }
+}
+
+impl From<GitInsightsError> for String {
+    fn from(e: GitInsightsError) -> String {
+        e.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_display_and_conversions() {
+        let e = GitInsightsError::git(&["log", "nope"], Some(128), b"fatal: bad revision\n");
+        assert_eq!(
+            e.to_string(),
+            "'git log nope' failed with exit code 128: fatal: bad revision"
+        );
+        assert!(matches!(
+            e,
+            GitInsightsError::Git {
+                code: Some(128),
+                ..
+            }
+        ));
+
+        let missing = io::Error::new(io::ErrorKind::NotFound, "no git");
+        let e = GitInsightsError::spawn(&["status"], missing);
+        assert!(std::error::Error::source(&e).is_some());
+
+        let closed = io::Error::new(io::ErrorKind::BrokenPipe, "closed");
+        let e = GitInsightsError::from(closed);
+        assert!(e.to_string().starts_with("Failed to write output: "));
+
+        let e = GitInsightsError::GitHub("GitHub API returned 401: Bad credentials".to_string());
+        assert_eq!(e.to_string(), "GitHub API returned 401: Bad credentials");
+        assert_eq!(e.exit_kind(), ExitKind::Failure);
+
+        let s: String = GitInsightsError::from("boom").into();
+        assert_eq!(s, "boom");
+    }
+
+    #[test]
+    fn test_exit_kinds() {
+        let e = GitInsightsError::git(&["log", "nope"], Some(128), b"fatal: bad revision\n");
+        assert_eq!(e.exit_kind(), ExitKind::GitFailed);
+        let spawn = GitInsightsError::spawn(&["status"], io::Error::other("no git"));
+        assert_eq!(spawn.exit_kind(), ExitKind::GitFailed);
+        // Only the variant counts, not wording that looks like a git failure.
+        let other = GitInsightsError::from("'git log' failed");
+        assert_eq!(other.exit_kind(), ExitKind::Failure);
+        assert_eq!(GitInsightsError::from("boom").exit_kind().code(), 1);
+        let usage = GitInsightsError::Usage("unknown --sort 'x'".to_string());
+        assert_eq!(usage.to_string(), "unknown --sort 'x'");
+        assert_eq!(usage.exit_kind().code(), 4);
+        assert_eq!(ExitKind::Usage.code(), 4);
+    }
+}
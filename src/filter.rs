@@ -0,0 +1,798 @@
+use crate::error::GitInsightsError;
+use crate::git::{run_command, run_command_with_stdin};
+use crate::identity::NameNormalization;
+use std::sync::{OnceLock, RwLock};
+
+/// Commit selection shared by every history-based analysis.
+///
+/// The CLI installs one filter per invocation via [`set_active`]; log
+/// consumers ask for [`revision_args`] instead of hard-coding `HEAD`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommitFilter {
+    /// Only consider commits reachable from tags matching this glob.
+    pub reachable_from: Option<String>,
+    /// Keep or drop the current git identity's commits and lines.
+    pub author_scope: AuthorScope,
+    /// Who gets credit for lines written while resolving merge conflicts.
+    pub merge_lines: MergeLines,
+    /// Whitespace and move/copy detection of every blame (`--blame-opts`).
+    pub blame: BlameOptions,
+    /// Commits blame looks past (`--ignore-rev`); full shas once resolved.
+    pub ignore_revs: Vec<String>,
+    /// Do not pass the repository's `.git-blame-ignore-revs` to blame
+    /// (`--no-ignore-revs-file`).
+    pub skip_ignore_revs_file: bool,
+    /// How author identities are normalized before grouping.
+    pub normalize_names: NameNormalization,
+    /// Drop commits with implausible timestamps from time-based analyses.
+    pub exclude_bad_times: bool,
+    /// Exactly these commits (`--stdin-commits`); full shas once resolved.
+    pub only_commits: Option<Vec<String>>,
+    /// Clock that hour/day buckets use (`--tz`).
+    pub time_zone: TimeZoneMode,
+    /// First row of day-of-week charts (`--week-start`).
+    pub week_start: WeekStart,
+    /// Gitignore-style patterns of files left out of line ownership (`--exclude`).
+    pub exclude_paths: Vec<String>,
+    /// Keep files `.gitattributes` marks `linguist-generated` or `-diff` in
+    /// line ownership (`--include-generated`).
+    pub include_generated: bool,
+    /// Count merge commits too (`--include-merges`); left out by default.
+    pub include_merges: bool,
+    /// Follow only the first parent of merges (`--first-parent`).
+    pub first_parent: bool,
+}
+
+/// Which authors an analysis covers, relative to the configured git identity.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[non_exhaustive]
+pub enum AuthorScope {
+    #[default]
+    All,
+    /// `--exclude-me`: everyone except `user.name`/`user.email`.
+    ExcludeMe,
+    /// `--only-me`: just `user.name`/`user.email`.
+    OnlyMe,
+}
+
+/// Attribution of blame lines introduced by merge commits (`--merge-lines`).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[non_exhaustive]
+pub enum MergeLines {
+    /// Whoever committed the merge (plain `git blame`).
+    #[default]
+    Merger,
+    /// The latest author of the file on the merged-in branch.
+    Branch,
+    /// A synthetic `(merge)` identity.
+    Synthetic,
+}
+
+impl MergeLines {
+    pub fn parse(s: &str) -> Result<MergeLines, String> {
+        match s {
+            "merger" => Ok(MergeLines::Merger),
+            "branch" => Ok(MergeLines::Branch),
+            "synthetic" => Ok(MergeLines::Synthetic),
+            other => Err(format!(
+                "unknown --merge-lines '{}'. Expected merger, branch or synthetic.",
+                other
+            )),
+        }
+    }
+}
+
+/// Whitespace and move/copy detection passed to `git blame` (`--blame-opts`).
+///
+/// Each level keeps more lines with their original author at the cost of
+/// slower blames: `-M` is cheap, every `-C` searches more commits for the
+/// origin of copied lines, and the third `-C` can be slow on large histories.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlameOptions {
+    /// `-w`: whitespace-only edits keep the line's previous author.
+    pub ignore_whitespace: bool,
+    /// `-M`: follow lines moved within a file.
+    pub moves: bool,
+    /// Number of `-C` flags (0 to 3): follow lines moved or copied from files
+    /// changed in the same commit, also from the commit that created the
+    /// file, or from any commit.
+    pub copies: u8,
+}
+
+impl Default for BlameOptions {
+    /// `-M -C`: renames and files split within a commit keep their authors.
+    fn default() -> BlameOptions {
+        BlameOptions {
+            ignore_whitespace: false,
+            moves: true,
+            copies: 1,
+        }
+    }
+}
+
+impl BlameOptions {
+    /// `none`, or blame flags separated by commas or spaces, with or without
+    /// the dash: `w,M,C,C`, `-w -C -C` or `w,CC`.
+    pub fn parse(s: &str) -> Result<BlameOptions, String> {
+        let err = || {
+            format!(
+                "invalid --blame-opts '{}'. Expected none or a list of w, M and up to three C, e.g. 'w,M,C'.",
+                s
+            )
+        };
+        let mut opts = BlameOptions {
+            ignore_whitespace: false,
+            moves: false,
+            copies: 0,
+        };
+        if s.trim().eq_ignore_ascii_case("none") {
+            return Ok(opts);
+        }
+        let tokens = s.split([',', ' ']).filter(|t| !t.is_empty());
+        for token in tokens {
+            let flags = token.strip_prefix('-').unwrap_or(token);
+            if flags.is_empty() {
+                return Err(err());
+            }
+            for flag in flags.chars() {
+                match flag.to_ascii_lowercase() {
+                    'w' => opts.ignore_whitespace = true,
+                    'm' => opts.moves = true,
+                    'c' if opts.copies < 3 => opts.copies += 1,
+                    _ => return Err(err()),
+                }
+            }
+        }
+        Ok(opts)
+    }
+
+    /// The flags for `git blame`, e.g. `["-w", "-M", "-C"]`.
+    pub fn args(self) -> Vec<&'static str> {
+        let mut args = Vec::new();
+        if self.ignore_whitespace {
+            args.push("-w");
+        }
+        if self.moves {
+            args.push("-M");
+        }
+        args.extend(std::iter::repeat_n("-C", self.copies as usize));
+        args
+    }
+}
+
+/// Whose wall clock time-of-day and day-of-week buckets follow (`--tz`).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[non_exhaustive]
+pub enum TimeZoneMode {
+    /// Committer time in UTC.
+    #[default]
+    Utc,
+    /// Author time in the author's own offset (`%ad`).
+    Author,
+    /// Committer time in this machine's time zone.
+    Local,
+}
+
+impl TimeZoneMode {
+    pub fn parse(s: &str) -> Result<TimeZoneMode, String> {
+        match s {
+            "utc" => Ok(TimeZoneMode::Utc),
+            "author" => Ok(TimeZoneMode::Author),
+            "local" => Ok(TimeZoneMode::Local),
+            other => Err(format!(
+                "unknown --tz '{}'. Expected author, utc or local.",
+                other
+            )),
+        }
+    }
+
+    /// Chart title suffix, e.g. `Heatmap (UTC)`.
+    pub fn label(self) -> &'static str {
+        match self {
+            TimeZoneMode::Utc => "UTC",
+            TimeZoneMode::Author => "author time zones",
+            TimeZoneMode::Local => "local time",
+        }
+    }
+
+    /// `git log` arguments that print each commit's UTC offset (`+0200`) for
+    /// the `%x` placeholder they return, or `None` in UTC mode.
+    pub fn offset_format(self) -> Option<(&'static str, &'static str)> {
+        match self {
+            TimeZoneMode::Utc => None,
+            TimeZoneMode::Author => Some(("--date=format:%z", "%ad")),
+            TimeZoneMode::Local => Some(("--date=format-local:%z", "%cd")),
+        }
+    }
+
+    /// Shift a commit's times to wall-clock seconds: committer time for UTC
+    /// and local, author time for author, plus `offset` (`+0530`, `-0800`).
+    pub fn wall_clock(self, committed: u64, authored: u64, offset: &str) -> u64 {
+        let base = match self {
+            TimeZoneMode::Author => authored,
+            _ => committed,
+        };
+        base.saturating_add_signed(parse_utc_offset(offset).unwrap_or(0))
+    }
+}
+
+/// Short day names, Sunday first like every day-of-week bucket (0 = Sun).
+pub const DAY_NAMES: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+/// Day that day-of-week charts start with (`--week-start`). Buckets stay
+/// Sunday-first; only the displayed row order changes.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[non_exhaustive]
+pub enum WeekStart {
+    #[default]
+    Sunday,
+    Monday,
+}
+
+impl WeekStart {
+    pub fn parse(s: &str) -> Result<WeekStart, String> {
+        match s {
+            "sun" | "sunday" => Ok(WeekStart::Sunday),
+            "mon" | "monday" => Ok(WeekStart::Monday),
+            other => Err(format!(
+                "unknown --week-start '{}'. Expected sun or mon.",
+                other
+            )),
+        }
+    }
+
+    /// Sunday-first bucket indices in display order.
+    pub fn day_order(self) -> [usize; 7] {
+        match self {
+            WeekStart::Sunday => [0, 1, 2, 3, 4, 5, 6],
+            WeekStart::Monday => [1, 2, 3, 4, 5, 6, 0],
+        }
+    }
+
+    /// [`DAY_NAMES`] in display order.
+    pub fn day_names(self) -> [&'static str; 7] {
+        self.day_order().map(|d| DAY_NAMES[d])
+    }
+
+    /// Sunday-first rows (or counts) in display order.
+    pub fn reorder<T: Clone>(self, rows: &[T]) -> Vec<T> {
+        self.day_order().iter().map(|&d| rows[d].clone()).collect()
+    }
+}
+
+/// Seconds east of UTC for `+HHMM`/`-HHMM`.
+pub fn parse_utc_offset(s: &str) -> Option<i64> {
+    let s = s.trim();
+    let (sign, digits) = match s.split_at_checked(1)? {
+        ("+", d) => (1, d),
+        ("-", d) => (-1, d),
+        _ => return None,
+    };
+    if digits.len() != 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i64 = digits[..2].parse().ok()?;
+    let minutes: i64 = digits[2..].parse().ok()?;
+    Some(sign * (hours * 3_600 + minutes * 60))
+}
+
+/// Resolved author predicate; build once per analysis via [`author_matcher`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuthorMatcher {
+    scope: AuthorScope,
+    name: Option<String>,
+    email: Option<String>,
+}
+
+impl AuthorMatcher {
+    /// Matcher for `scope` against the given identity (names compare
+    /// case-insensitively; emails may include angle brackets).
+    pub fn new(scope: AuthorScope, name: Option<&str>, email: Option<&str>) -> AuthorMatcher {
+        AuthorMatcher {
+            scope,
+            name: name.map(|n| n.trim().to_lowercase()),
+            email: email.map(normalize_email),
+        }
+    }
+
+    fn is_me(&self, name: &str, email: &str) -> bool {
+        self.name.as_deref() == Some(name.trim().to_lowercase().as_str())
+            || (!email.is_empty() && self.email.as_deref() == Some(normalize_email(email).as_str()))
+    }
+
+    /// Whether commits/lines by this author are counted.
+    pub fn allows(&self, name: &str, email: &str) -> bool {
+        match self.scope {
+            AuthorScope::All => true,
+            AuthorScope::ExcludeMe => !self.is_me(name, email),
+            AuthorScope::OnlyMe => self.is_me(name, email),
+        }
+    }
+
+    /// Same as [`allows`](Self::allows) for `Name <email>` strings.
+    pub fn allows_ident(&self, ident: &str) -> bool {
+        match ident.rsplit_once(" <") {
+            Some((name, email)) => self.allows(name, email),
+            None => self.allows(ident, ""),
+        }
+    }
+}
+
+fn normalize_email(e: &str) -> String {
+    e.trim()
+        .trim_matches(|c| c == '<' || c == '>')
+        .to_ascii_lowercase()
+}
+
+static ACTIVE_FILTER: OnceLock<RwLock<CommitFilter>> = OnceLock::new();
+
+fn slot() -> &'static RwLock<CommitFilter> {
+    ACTIVE_FILTER.get_or_init(|| RwLock::new(CommitFilter::default()))
+}
+
+/// Install the filter used by subsequent analyses.
+pub fn set_active(filter: CommitFilter) {
+    *slot().write().unwrap_or_else(|e| e.into_inner()) = filter;
+}
+
+/// Snapshot of the currently active filter.
+pub fn active() -> CommitFilter {
+    slot().read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+impl CommitFilter {
+    /// Revision arguments for `git log`/`shortlog` (defaults to `HEAD`).
+    pub fn revision_args(&self) -> Result<Vec<String>, GitInsightsError> {
+        if let Some(shas) = &self.only_commits {
+            if shas.is_empty() {
+                return Err("--stdin-commits read no commit ids".into());
+            }
+            let mut args = vec!["--no-walk".to_string()];
+            args.extend(shas.iter().cloned());
+            return Ok(args);
+        }
+        match &self.reachable_from {
+            Some(glob) => {
+                let tags = run_command(&["tag", "--list", glob])?;
+                if tags.trim().is_empty() {
+                    return Err(format!("no tags match --reachable-from '{}'", glob).into());
+                }
+                Ok(vec![format!("--tags={}", glob)])
+            }
+            None => Ok(vec!["HEAD".to_string()]),
+        }
+    }
+}
+
+impl CommitFilter {
+    /// `git log`/`shortlog` options choosing which commits count: merges only
+    /// with `--include-merges`, the mainline only with `--first-parent`.
+    pub fn history_args(&self) -> Vec<&'static str> {
+        let mut args = Vec::new();
+        if !self.include_merges {
+            args.push("--no-merges");
+        }
+        if self.first_parent {
+            args.push("--first-parent");
+        }
+        args
+    }
+
+    /// Whether `path` matches one of the `--exclude` patterns.
+    pub fn excludes_path(&self, path: &str) -> bool {
+        self.exclude_paths
+            .iter()
+            .any(|p| crate::codeowners::pattern_matches(p, path))
+    }
+
+    /// Resolve the author scope against `git config user.name`/`user.email`.
+    pub fn author_matcher(&self) -> Result<AuthorMatcher, GitInsightsError> {
+        if self.author_scope == AuthorScope::All {
+            return Ok(AuthorMatcher::default());
+        }
+        let name = run_command(&["config", "user.name"]).ok();
+        let email = run_command(&["config", "user.email"]).ok();
+        let name = name.filter(|n| !n.is_empty());
+        let email = email.filter(|e| !e.is_empty());
+        if name.is_none() && email.is_none() {
+            return Err("--exclude-me/--only-me need user.name or user.email in git config".into());
+        }
+        Ok(AuthorMatcher::new(
+            self.author_scope,
+            name.as_deref(),
+            email.as_deref(),
+        ))
+    }
+}
+
+impl CommitFilter {
+    /// With `--stdin-commits`, read and resolve the commit list from stdin.
+    pub fn read_stdin_commits(mut self) -> Result<CommitFilter, GitInsightsError> {
+        if self.only_commits.is_some() {
+            let mut text = String::new();
+            std::io::Read::read_to_string(&mut std::io::stdin(), &mut text)
+                .map_err(|e| format!("failed to read commits from stdin: {}", e))?;
+            self.only_commits = Some(resolve_commits(
+                &parse_commit_list(&text),
+                "--stdin-commits",
+            )?);
+        }
+        Ok(self)
+    }
+
+    /// Expand the `--ignore-rev` ids to full shas, so a typo fails up front
+    /// rather than every blame.
+    pub fn resolve_ignore_revs(mut self) -> Result<CommitFilter, GitInsightsError> {
+        self.ignore_revs = resolve_commits(&self.ignore_revs, "--ignore-rev")?;
+        Ok(self)
+    }
+}
+
+/// First token of every non-blank, non-`#` line, so both `git rev-list` and
+/// `git log --oneline` output can be piped in.
+pub fn parse_commit_list(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .filter_map(|l| l.split_whitespace().next())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Expand (possibly abbreviated) ids to full commit shas, in input order with
+/// duplicates removed; any id that does not name a commit is an error
+/// reported against `flag`.
+pub fn resolve_commits(ids: &[String], flag: &str) -> Result<Vec<String>, GitInsightsError> {
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let input: String = ids
+        .iter()
+        .map(|id| format!("{}^{{commit}}\n", id))
+        .collect();
+    let out = run_command_with_stdin(&["cat-file", "--batch-check"], &input)?;
+    let mut seen = std::collections::HashSet::new();
+    let mut shas = Vec::new();
+    for (id, line) in ids.iter().zip(out.lines()) {
+        match line.split_once(' ') {
+            Some((sha, kind)) if kind.starts_with("commit") => {
+                if seen.insert(sha.to_string()) {
+                    shas.push(sha.to_string());
+                }
+            }
+            _ => return Err(format!("{}: '{}' is not a commit", flag, id).into()),
+        }
+    }
+    Ok(shas)
+}
+
+/// Author predicate for the active filter.
+pub fn author_matcher() -> Result<AuthorMatcher, GitInsightsError> {
+    active().author_matcher()
+}
+
+/// Revision arguments for the active filter.
+pub fn revision_args() -> Result<Vec<String>, GitInsightsError> {
+    active().revision_args()
+}
+
+/// [`CommitFilter::history_args`] of the active filter.
+pub fn history_args() -> Vec<&'static str> {
+    active().history_args()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_repo::{Author, TestRepo};
+    use crate::test_sync::test_lock;
+
+    #[test]
+    fn test_default_filter_uses_head() {
+        let f = CommitFilter::default();
+        assert_eq!(f.revision_args().unwrap(), vec!["HEAD".to_string()]);
+    }
+
+    #[test]
+    fn test_reachable_from_limits_to_tagged_history() {
+        let _guard = test_lock();
+        let repo = TestRepo::init().expect("init repo");
+        repo.seed_commits(2, &[Author::new("Rel", "rel@test_git_insights.com")], 1)
+            .expect("seed");
+        run_command(&["-C", repo.path.to_str().unwrap(), "tag", "v1.0"]).expect("tag");
+        repo.commit_with_epoch(
+            "Wip",
+            "wip@test_git_insights.com",
+            "wip.txt",
+            "x",
+            1_700_000_000,
+        )
+        .expect("commit");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        set_active(CommitFilter {
+            reachable_from: Some("v*".to_string()),
+            ..Default::default()
+        });
+        let stats = crate::stats::gather_commit_statsx(true);
+        let missing = CommitFilter {
+            reachable_from: Some("nope*".to_string()),
+            ..Default::default()
+        }
+        .revision_args();
+        set_active(CommitFilter::default());
+        std::env::set_current_dir(old).ok();
+
+        let stats = stats.expect("commit stats");
+        assert!(stats.contains_key("Rel"));
+        assert!(!stats.contains_key("Wip"), "untagged commit leaked in");
+        assert!(missing.unwrap_err().to_string().contains("no tags match"));
+    }
+
+    #[test]
+    fn test_author_matcher_scopes() {
+        let me = |scope| AuthorMatcher::new(scope, Some("Ann Lee"), Some("ann@x.io"));
+        assert!(me(AuthorScope::All).allows("Bob", "bob@x.io"));
+        assert!(!me(AuthorScope::ExcludeMe).allows("ann lee", "other@x.io"));
+        assert!(!me(AuthorScope::ExcludeMe).allows("Ann L.", "<ANN@x.io>"));
+        assert!(me(AuthorScope::ExcludeMe).allows("Bob", "<bob@x.io>"));
+        assert!(me(AuthorScope::OnlyMe).allows_ident("Ann Lee <ann@x.io>"));
+        assert!(!me(AuthorScope::OnlyMe).allows_ident("Bob <bob@x.io>"));
+    }
+
+    #[test]
+    fn test_exclude_me_and_only_me_filter_history() {
+        let _guard = test_lock();
+        let repo = TestRepo::init().expect("init repo");
+        // TestRepo configures user.name "Temp User" / temp@test_git_insights.com.
+        repo.commit_with_epoch(
+            "Temp User",
+            "temp@test_git_insights.com",
+            "me.txt",
+            "mine",
+            1_700_000_000,
+        )
+        .expect("commit");
+        repo.commit_with_epoch(
+            "Other",
+            "other@test_git_insights.com",
+            "other.txt",
+            "theirs",
+            1_700_000_100,
+        )
+        .expect("commit");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        set_active(CommitFilter {
+            author_scope: AuthorScope::ExcludeMe,
+            ..Default::default()
+        });
+        let others = crate::stats::gather_author_statsx(true);
+        let other_ts = crate::visualize::collect_commit_timestamps();
+        set_active(CommitFilter {
+            author_scope: AuthorScope::OnlyMe,
+            ..Default::default()
+        });
+        let mine = crate::stats::gather_commit_stats();
+        let ownership = crate::stats::get_user_file_ownership("Temp User", false, 10, false);
+        set_active(CommitFilter::default());
+        std::env::set_current_dir(old).ok();
+
+        let others = others.expect("stats");
+        assert!(others.contains_key("Other"));
+        assert!(!others.contains_key("Temp User"));
+        assert_eq!(other_ts.expect("timestamps"), vec![1_700_000_100]);
+        let mine = mine.expect("commit stats");
+        assert_eq!(mine.len(), 1);
+        assert_eq!(mine.get("Temp User").map(|s| s.commits), Some(1));
+        let ownership = ownership.expect("ownership");
+        assert_eq!(ownership.len(), 1);
+        assert_eq!(ownership[0].0, "me.txt");
+    }
+
+    #[test]
+    fn test_merges_and_first_parent_history() {
+        let _guard = test_lock();
+        let repo = TestRepo::init().expect("init repo");
+        let p = repo.path.to_str().unwrap().to_string();
+        let git = |args: &[&str]| {
+            let mut full = vec!["-C", p.as_str()];
+            full.extend_from_slice(args);
+            run_command(&full)
+        };
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", "base", 1_700_000_000)
+            .expect("commit");
+        git(&["checkout", "-q", "-b", "topic"]).expect("branch");
+        repo.commit_with_epoch("Bob", "bob@x.io", "b.txt", "bob", 1_700_000_100)
+            .expect("commit");
+        git(&["checkout", "-q", "main"]).expect("checkout");
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", "ann", 1_700_000_200)
+            .expect("commit");
+        git(&[
+            "-c",
+            "user.name=Max",
+            "-c",
+            "user.email=max@x.io",
+            "merge",
+            "-q",
+            "--no-ff",
+            "--no-edit",
+            "topic",
+        ])
+        .expect("merge");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        let mut results = Vec::new();
+        for (include_merges, first_parent) in
+            [(false, false), (true, false), (false, true), (true, true)]
+        {
+            set_active(CommitFilter {
+                include_merges,
+                first_parent,
+                ..Default::default()
+            });
+            let stats = crate::stats::gather_commit_statsx(true).expect("stats");
+            let mut counts: Vec<(String, usize)> =
+                stats.into_iter().map(|(a, s)| (a, s.commits)).collect();
+            counts.sort();
+            let ts = crate::visualize::collect_commit_timestamps().expect("timestamps");
+            results.push((counts, ts.len()));
+        }
+        set_active(CommitFilter::default());
+        std::env::set_current_dir(old).ok();
+
+        let counts = |v: &[(&str, usize)]| -> Vec<(String, usize)> {
+            v.iter().map(|(a, n)| (a.to_string(), *n)).collect()
+        };
+        assert_eq!(results[0], (counts(&[("Ann", 2), ("Bob", 1)]), 3));
+        assert_eq!(
+            results[1],
+            (counts(&[("Ann", 2), ("Bob", 1), ("Max", 1)]), 4)
+        );
+        assert_eq!(results[2], (counts(&[("Ann", 2)]), 2));
+        assert_eq!(results[3], (counts(&[("Ann", 2), ("Max", 1)]), 3));
+    }
+
+    #[test]
+    fn test_stdin_commit_list_limits_history() {
+        let _guard = test_lock();
+        let repo = TestRepo::init().expect("init repo");
+        for (name, ts) in [
+            ("Ann", 1_700_000_000),
+            ("Bob", 1_700_000_100),
+            ("Cat", 1_700_000_200),
+        ] {
+            repo.commit_with_epoch(name, "x@x.io", "a.txt", name, ts)
+                .expect("commit");
+        }
+        let p = repo.path.to_str().unwrap();
+        let bob = run_command(&["-C", p, "log", "--format=%h %s", "--author=Bob"]).expect("log");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        let ids = parse_commit_list(&format!("# picked\n{}\n\n{}\n", bob, bob));
+        let shas = resolve_commits(&ids, "--stdin-commits");
+        let bogus = resolve_commits(&["HEAD^{tree}".to_string()], "--stdin-commits");
+        set_active(CommitFilter {
+            only_commits: shas.as_ref().ok().cloned(),
+            ..Default::default()
+        });
+        let stats = crate::stats::gather_commit_statsx(true);
+        let timestamps = crate::visualize::collect_commit_timestamps();
+        set_active(CommitFilter::default());
+        std::env::set_current_dir(old).ok();
+
+        assert_eq!(shas.expect("resolve").len(), 1);
+        assert!(bogus.is_err());
+        let stats = stats.expect("stats");
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats["Bob"].commits, 1);
+        assert_eq!(timestamps.expect("timestamps"), vec![1_700_000_100]);
+        let empty = CommitFilter {
+            only_commits: Some(Vec::new()),
+            ..Default::default()
+        };
+        assert!(empty.revision_args().is_err());
+    }
+
+    #[test]
+    fn test_time_zone_mode_shifts_to_author_clock() {
+        assert_eq!(parse_utc_offset("+0530"), Some(19_800));
+        assert_eq!(parse_utc_offset("-0800"), Some(-28_800));
+        assert_eq!(parse_utc_offset("0800"), None);
+        assert_eq!(parse_utc_offset(""), None);
+        assert_eq!(TimeZoneMode::Utc.wall_clock(100, 50, ""), 100);
+        assert_eq!(
+            TimeZoneMode::Author.wall_clock(100_000, 90_000, "-0100"),
+            86_400
+        );
+
+        let _guard = test_lock();
+        let repo = TestRepo::init().expect("init repo");
+        // 22:00 UTC, authored in UTC+05:30 (03:30 the next day on their clock).
+        let ts = 1_700_000_000 - 1_700_000_000 % 86_400 + 22 * 3_600;
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", "one", ts)
+            .expect("commit");
+        let p = repo.path.to_str().unwrap();
+        run_command(&[
+            "-C",
+            p,
+            "commit",
+            "-q",
+            "--amend",
+            "--no-edit",
+            &format!("--date=@{} +0530", ts),
+        ])
+        .expect("amend");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        set_active(CommitFilter {
+            time_zone: TimeZoneMode::Author,
+            ..Default::default()
+        });
+        let author = crate::visualize::collect_commit_timestamps();
+        set_active(CommitFilter::default());
+        let utc = crate::visualize::collect_commit_timestamps();
+        std::env::set_current_dir(old).ok();
+
+        let author = author.expect("timestamps");
+        assert_eq!(author, vec![ts + 19_800]);
+        assert_eq!(crate::code_frequency::histogram_hour_of_day(&author)[3], 1);
+        assert_eq!(utc.expect("timestamps").len(), 1);
+    }
+
+    #[test]
+    fn test_blame_options_parse() {
+        assert_eq!(BlameOptions::default().args(), ["-M", "-C"]);
+        let opts = BlameOptions::parse("w, CC").expect("parse");
+        assert_eq!(
+            opts,
+            BlameOptions {
+                ignore_whitespace: true,
+                moves: false,
+                copies: 2,
+            }
+        );
+        assert_eq!(BlameOptions::parse("-M -C -C -C").unwrap().copies, 3);
+        assert_eq!(BlameOptions::parse("None"), BlameOptions::parse(""));
+        assert!(BlameOptions::parse("C,CCC").is_err());
+        assert!(BlameOptions::parse("w,x")
+            .unwrap_err()
+            .contains("Expected none or a list of w, M"));
+        assert!(BlameOptions::parse("-").is_err());
+    }
+
+    #[test]
+    fn test_week_start_reorders_day_rows() {
+        assert_eq!(WeekStart::parse("mon"), Ok(WeekStart::Monday));
+        assert_eq!(WeekStart::parse("sunday"), Ok(WeekStart::Sunday));
+        assert!(WeekStart::parse("sat").is_err());
+        assert_eq!(WeekStart::Sunday.day_names(), DAY_NAMES);
+        assert_eq!(WeekStart::Monday.day_names()[0], "Mon");
+        assert_eq!(WeekStart::Monday.day_names()[6], "Sun");
+        assert_eq!(
+            WeekStart::Monday.reorder(&[0, 1, 2, 3, 4, 5, 6]),
+            vec![1, 2, 3, 4, 5, 6, 0]
+        );
+
+        let _guard = test_lock();
+        let mut grid = [[0usize; 24]; 7];
+        grid[0][0] = 1; // Sun 00
+        set_active(CommitFilter {
+            week_start: WeekStart::Monday,
+            ..Default::default()
+        });
+        let mut buf = Vec::new();
+        let rendered = crate::visualize::render_heatmap_ascii(&mut buf, grid);
+        set_active(CommitFilter::default());
+        rendered.expect("render");
+        let text = String::from_utf8(buf).unwrap();
+        let rows: Vec<&str> = text.lines().skip(1).take(7).collect();
+        assert!(rows[0].starts_with("Mon     "));
+        assert!(rows[6].starts_with("Sun  @ "));
+    }
+}
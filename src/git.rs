@@ -1,17 +1,320 @@
+use crate::error::GitInsightsError;
+use std::cell::RefCell;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::sync::RwLock;
+use std::time::Instant;
+
+/// Extra mailmap (`--mailmap`) every git command reads besides `.mailmap`.
+static MAILMAP_FILE: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+thread_local! {
+    static CURRENT_REPO: RefCell<Option<Repo>> = const { RefCell::new(None) };
+}
+
+/// A git work tree that commands run in, so callers need not change the
+/// process working directory.
+///
+/// ```no_run
+/// use git_insights::git::Repo;
+///
+/// let repo = Repo::open("../other-project").unwrap();
+/// let stats = repo.scope(|| git_insights::stats::compute_stats(true)).unwrap();
+/// println!("{} lines", stats.total_loc);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repo {
+    path: PathBuf,
+}
+
+impl Repo {
+    /// The work tree containing `path` (any directory inside it).
+    pub fn open(path: impl AsRef<Path>) -> Result<Repo, GitInsightsError> {
+        let dir = path.as_ref().to_string_lossy();
+        let args = ["-C", &dir, "rev-parse", "--show-toplevel"];
+        let output = Command::new("git")
+            .args(args)
+            .output()
+            .map_err(|e| GitInsightsError::spawn(&args, e))?;
+        if !output.status.success() {
+            return Err(GitInsightsError::git(
+                &args,
+                output.status.code(),
+                &output.stderr,
+            ));
+        }
+        let top = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok(Repo {
+            path: PathBuf::from(top),
+        })
+    }
+
+    /// Top-level directory of the work tree.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The repository selected by the innermost [`Repo::scope`] on this
+    /// thread; `None` means the process working directory.
+    pub fn current() -> Option<Repo> {
+        CURRENT_REPO.with(|c| c.borrow().clone())
+    }
+
+    /// Run `f` with every git command it issues on this thread (and on the
+    /// blame workers it starts) going to this repository.
+    pub fn scope<T>(&self, f: impl FnOnce() -> T) -> T {
+        struct Restore(Option<Repo>);
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                CURRENT_REPO.with(|c| *c.borrow_mut() = self.0.take());
+            }
+        }
+        let _restore = Restore(CURRENT_REPO.with(|c| c.replace(Some(self.clone()))));
+        f()
+    }
+
+    /// [`run_command`] in this repository.
+    pub fn run_command(&self, args: &[&str]) -> Result<String, GitInsightsError> {
+        self.scope(|| run_command(args))
+    }
+}
+
+/// Merge the identities mapped in `path` into every later git command's
+/// author names and emails, on top of the repository's own `.mailmap`.
+pub fn set_mailmap_file(path: Option<PathBuf>) {
+    *MAILMAP_FILE.write().unwrap_or_else(|e| e.into_inner()) = path;
+}
+
+/// `git`, started in the current [`Repo`] if one is in scope.
+fn git_command() -> Command {
+    let mut cmd = Command::new("git");
+    if let Some(repo) = Repo::current() {
+        cmd.current_dir(repo.path);
+    }
+    if let Some(path) = MAILMAP_FILE
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+    {
+        cmd.arg("-c")
+            .arg(format!("mailmap.file={}", path.display()));
+    }
+    cmd
+}
+
+/// Top of the work tree git commands run in, found without starting git:
+/// the scoped [`Repo`], else the nearest directory at or above the current
+/// one that holds a `.git` entry.
+pub(crate) fn work_tree_root() -> Option<PathBuf> {
+    if let Some(repo) = Repo::current() {
+        return Some(repo.path);
+    }
+    let cwd = std::env::current_dir().ok()?;
+    cwd.ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// A path git printed relative to its working directory (e.g. from
+/// `rev-parse --git-path`), usable from this process.
+pub(crate) fn repo_relative(path: &str) -> PathBuf {
+    match Repo::current() {
+        Some(repo) => repo.path.join(path),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Everything git reads to map identities here: the work tree's `.mailmap`,
+/// then the `mailmap.file` (`--mailmap` when given) and `mailmap.blob` ones.
+pub fn mailmap_contents() -> String {
+    let mut text = work_tree_root()
+        .and_then(|root| std::fs::read_to_string(root.join(".mailmap")).ok())
+        .unwrap_or_default();
+    if let Ok(file) = run_command(&["config", "--path", "mailmap.file"]) {
+        text.push('\0');
+        text.push_str(&std::fs::read_to_string(repo_relative(&file)).unwrap_or_default());
+    }
+    if let Ok(blob) = run_command(&["config", "mailmap.blob"]) {
+        text.push('\0');
+        text.push_str(&run_command(&["cat-file", "blob", &blob]).unwrap_or_default());
+    }
+    text
+}
+
+/// Short, stable (FNV-1a) hex digest of `data`, for cache keys and file
+/// names; not for anything security related.
+pub fn short_hash(data: &[u8]) -> String {
+    let hash = data.iter().fold(0xcbf2_9ce4_8422_2325u64, |h, &b| {
+        (h ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{:016x}", hash)
+}
 
 /// Executes a Git command and returns its stdout if successful.
-pub fn run_command(args: &[&str]) -> Result<String, String> {
-    let output = Command::new("git").args(args).output();
-    match output {
-        Ok(output) => {
-            if output.status.success() {
-                Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
-            } else {
-                Err(String::from_utf8_lossy(&output.stderr).to_string())
+pub fn run_command(args: &[&str]) -> Result<String, GitInsightsError> {
+    let start = Instant::now();
+    let output = git_command()
+        .args(args)
+        .output()
+        .map_err(|e| GitInsightsError::spawn(args, e))?;
+    crate::log::git_command(args, start);
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(GitInsightsError::git(
+            args,
+            output.status.code(),
+            &output.stderr,
+        ))
+    }
+}
+
+/// Executes a Git command feeding `input` on stdin and returns its stdout if successful.
+pub fn run_command_with_stdin(args: &[&str], input: &str) -> Result<String, GitInsightsError> {
+    let stdout = run_command_bytes_with_stdin(args, input)?;
+    Ok(String::from_utf8_lossy(&stdout).trim().to_string())
+}
+
+/// Like [`run_command_with_stdin`] but returns raw stdout bytes (for blob contents).
+pub fn run_command_bytes_with_stdin(
+    args: &[&str],
+    input: &str,
+) -> Result<Vec<u8>, GitInsightsError> {
+    let start = Instant::now();
+    let mut child = git_command()
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| GitInsightsError::spawn(args, e))?;
+    // Feed stdin from a separate thread so large outputs cannot deadlock on full pipes.
+    let writer = child.stdin.take().map(|mut stdin| {
+        let input = input.to_string();
+        std::thread::spawn(move || stdin.write_all(input.as_bytes()))
+    });
+    let output = child
+        .wait_with_output()
+        .map_err(|e| GitInsightsError::spawn(args, e))?;
+    crate::log::git_command(args, start);
+    if !output.status.success() {
+        return Err(GitInsightsError::git(
+            args,
+            output.status.code(),
+            &output.stderr,
+        ));
+    }
+    if let Some(handle) = writer {
+        handle
+            .join()
+            .map_err(|_| GitInsightsError::from("Failed to write to git stdin"))?
+            .map_err(|e| GitInsightsError::spawn(args, e))?;
+    }
+    Ok(output.stdout)
+}
+
+/// Executes a Git command and hands `f` each stdout line (without its line
+/// ending) as it arrives, so long `git log` output is never held in memory
+/// whole.
+pub fn run_command_lines(args: &[&str], mut f: impl FnMut(&str)) -> Result<(), GitInsightsError> {
+    run_command_line_iter(args, |lines| lines.for_each(|l| f(&l)))
+}
+
+/// [`run_command_lines`] for parsers that consume an iterator of lines;
+/// returns what `f` returns once git has exited successfully.
+pub fn run_command_line_iter<T>(
+    args: &[&str],
+    f: impl FnOnce(&mut dyn Iterator<Item = String>) -> T,
+) -> Result<T, GitInsightsError> {
+    stream_records(args, b'\n', |records| {
+        f(&mut records.map(|mut line| {
+            if line.ends_with('\r') {
+                line.pop();
+            }
+            line
+        }))
+    })
+}
+
+/// [`run_command_lines`] for output split on `separator` instead of newlines,
+/// e.g. `%x1e`-terminated commits whose messages span several lines.
+pub fn run_command_records(
+    args: &[&str],
+    separator: u8,
+    mut f: impl FnMut(&str),
+) -> Result<(), GitInsightsError> {
+    stream_records(args, separator, |records| records.for_each(|r| f(&r)))
+}
+
+/// Run git and give `f` its stdout split on `separator` while it is read.
+fn stream_records<T>(
+    args: &[&str],
+    separator: u8,
+    f: impl FnOnce(&mut dyn Iterator<Item = String>) -> T,
+) -> Result<T, GitInsightsError> {
+    let start = Instant::now();
+    let mut child = git_command()
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| GitInsightsError::spawn(args, e))?;
+    // Drain stderr concurrently so a chatty git cannot block on a full pipe.
+    let stderr = child.stderr.take().map(|mut stderr| {
+        std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            let _ = stderr.read_to_end(&mut buf);
+            buf
+        })
+    });
+    let mut reader = BufReader::new(child.stdout.take().expect("stdout is piped"));
+    let mut read_error = None;
+    let mut at_end = false;
+    let mut records = std::iter::from_fn(|| {
+        let mut record = Vec::new();
+        match reader.read_until(separator, &mut record) {
+            Ok(0) => {
+                at_end = true;
+                None
+            }
+            Ok(_) => {
+                if record.last() == Some(&separator) {
+                    record.pop();
+                } else if record.iter().all(u8::is_ascii_whitespace) {
+                    // The newline git prints after the last terminated record.
+                    at_end = true;
+                    return None;
+                }
+                Some(String::from_utf8_lossy(&record).into_owned())
+            }
+            Err(e) => {
+                read_error = Some(e);
+                None
             }
         }
-        Err(e) => Err(format!("Failed to execute git command: {}", e)),
+    });
+    let value = f(&mut records);
+    drop(reader);
+    if let Some(e) = read_error {
+        let _ = child.kill();
+        let _ = child.wait();
+        return Err(GitInsightsError::spawn(args, e));
+    }
+    if !at_end {
+        // `f` stopped early; git would only die of the closed pipe.
+        let _ = child.kill();
+        let _ = child.wait();
+        crate::log::git_command(args, start);
+        return Ok(value);
+    }
+    let status = child.wait().map_err(|e| GitInsightsError::spawn(args, e))?;
+    let stderr = stderr.and_then(|h| h.join().ok()).unwrap_or_default();
+    crate::log::git_command(args, start);
+    if status.success() {
+        Ok(value)
+    } else {
+        Err(GitInsightsError::git(args, status.code(), &stderr))
     }
 }
 
@@ -22,45 +325,302 @@ pub fn is_git_installed() -> bool {
         .stdout(Stdio::null())
         .stderr(Stdio::null())
         .status()
-        .map_or(false, |s| s.success())
+        .is_ok_and(|s| s.success())
 }
 
-/// Counts the number of merged pull requests for a given author.
-pub fn count_pull_requests(author: &str) -> Result<usize, String> {
-    let log_output = run_command(&["log", "--merges", "--author", author, "--pretty=format:%s"])?;
+/// Where a commit's pull/merge request was detected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PullRequestSource {
+    /// Matched a `--pr-pattern` regex.
+    Custom,
+    /// `Merge pull request #N` merge commits.
+    GitHub,
+    /// `See merge request !N` in the message.
+    GitLab,
+    /// Bitbucket `Merged in` merge commits.
+    Bitbucket,
+    /// A `Change-Id: I...` trailer.
+    Gerrit,
+    /// Squash merges: a non-merge subject ending in `(#N)`.
+    Squash,
+    /// Any other `Merge branch '...'` merge commit.
+    BranchMerge,
+}
 
-    let pr_merges = log_output
-        .lines()
-        .filter(|line| {
-            line.starts_with("Merge pull request #")
-                || line.starts_with("Merge branch '")
-                || line.starts_with("Merged in")
-        })
-        .count();
+/// Merged pull requests per [`PullRequestSource`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PullRequestSources {
+    pub custom: usize,
+    pub github: usize,
+    pub gitlab: usize,
+    pub bitbucket: usize,
+    pub gerrit: usize,
+    pub squash: usize,
+    pub branch_merges: usize,
+}
+
+impl PullRequestSources {
+    pub fn add(&mut self, source: PullRequestSource) {
+        *match source {
+            PullRequestSource::Custom => &mut self.custom,
+            PullRequestSource::GitHub => &mut self.github,
+            PullRequestSource::GitLab => &mut self.gitlab,
+            PullRequestSource::Bitbucket => &mut self.bitbucket,
+            PullRequestSource::Gerrit => &mut self.gerrit,
+            PullRequestSource::Squash => &mut self.squash,
+            PullRequestSource::BranchMerge => &mut self.branch_merges,
+        } += 1;
+    }
+
+    /// `(label, count)` for every source, in detection order.
+    pub fn rows(&self) -> [(&'static str, usize); 7] {
+        [
+            ("custom", self.custom),
+            ("github", self.github),
+            ("gitlab", self.gitlab),
+            ("bitbucket", self.bitbucket),
+            ("gerrit", self.gerrit),
+            ("squash", self.squash),
+            ("branch_merges", self.branch_merges),
+        ]
+    }
+
+    pub fn total(&self) -> usize {
+        self.rows().iter().map(|(_, n)| n).sum()
+    }
 
-    Ok(pr_merges)
+    pub fn to_json(&self) -> String {
+        let members: Vec<String> = self
+            .rows()
+            .iter()
+            .map(|(label, n)| format!("\"{}\": {}", label, n))
+            .collect();
+        format!("{{{}}}", members.join(", "))
+    }
+}
+
+/// Classify one commit message; each commit counts for at most one source,
+/// custom patterns first.
+pub fn classify_pull_request(
+    message: &str,
+    is_merge: bool,
+    custom: &[regex::Regex],
+) -> Option<PullRequestSource> {
+    let subject = message.lines().next().unwrap_or("").trim();
+    if custom.iter().any(|re| re.is_match(message)) {
+        return Some(PullRequestSource::Custom);
+    }
+    if is_merge && subject.starts_with("Merge pull request #") {
+        return Some(PullRequestSource::GitHub);
+    }
+    let gitlab = message.lines().any(|l| {
+        l.trim()
+            .strip_prefix("See merge request ")
+            .is_some_and(|r| r.split_once('!').is_some_and(|(_, n)| is_number(n)))
+    });
+    if gitlab {
+        return Some(PullRequestSource::GitLab);
+    }
+    if is_merge && subject.starts_with("Merged in") {
+        return Some(PullRequestSource::Bitbucket);
+    }
+    let gerrit = message.lines().any(|l| {
+        l.trim()
+            .strip_prefix("Change-Id: I")
+            .is_some_and(|id| id.len() == 40 && id.bytes().all(|b| b.is_ascii_hexdigit()))
+    });
+    if gerrit {
+        return Some(PullRequestSource::Gerrit);
+    }
+    let squash = subject
+        .strip_suffix(')')
+        .and_then(|s| s.rsplit_once("(#"))
+        .is_some_and(|(_, n)| is_number(n));
+    if !is_merge && squash {
+        return Some(PullRequestSource::Squash);
+    }
+    if is_merge && subject.starts_with("Merge branch '") {
+        return Some(PullRequestSource::BranchMerge);
+    }
+    None
+}
+
+fn is_number(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Merged pull requests of `author` by where they were detected: GitHub,
+/// GitLab, Bitbucket and branch merge commits, Gerrit `Change-Id` trailers,
+/// squash-merge subjects and `custom` patterns matched against the message.
+pub fn pull_request_sources(
+    author: &str,
+    custom: &[regex::Regex],
+) -> Result<PullRequestSources, GitInsightsError> {
+    let args = ["log", "--author", author, "--pretty=format:%P%x1f%B%x1e"];
+    let mut sources = PullRequestSources::default();
+    run_command_records(&args, b'\x1e', |record| {
+        let Some((parents, message)) = record.trim_start_matches('\n').split_once('\x1f') else {
+            return;
+        };
+        let is_merge = parents.split_whitespace().count() > 1;
+        if let Some(source) = classify_pull_request(message, is_merge, custom) {
+            sources.add(source);
+        }
+    })?;
+    Ok(sources)
+}
+
+/// Counts the number of merged pull requests for a given author.
+pub fn count_pull_requests(author: &str) -> Result<usize, GitInsightsError> {
+    Ok(pull_request_sources(author, &[])?.total())
 }
 
-/// Checks if the current directory is within a Git repository.
+/// Checks if the current directory (or [`Repo`]) is within a Git repository.
 pub fn is_in_git_repo() -> bool {
-    Command::new("git")
+    git_command()
         .arg("rev-parse")
         .arg("--is-inside-work-tree")
         .stdout(Stdio::null())
         .stderr(Stdio::null())
         .status()
-        .map_or(false, |s| s.success())
+        .is_ok_and(|s| s.success())
+}
+
+/// Remotes configured as promisors (i.e. the repo is a partial clone).
+pub fn promisor_remotes() -> Vec<String> {
+    let out =
+        run_command(&["config", "--get-regexp", r"^remote\..*\.promisor$"]).unwrap_or_default();
+    out.lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(' ')?;
+            if value.trim() != "true" {
+                return None;
+            }
+            let name = key.strip_prefix("remote.")?.strip_suffix(".promisor")?;
+            Some(name.to_string())
+        })
+        .collect()
+}
+
+/// Blob ids reachable from `rev` that are not present locally.
+pub fn missing_blobs(rev: &str) -> Result<Vec<String>, GitInsightsError> {
+    let out = run_command(&["rev-list", "--objects", "--missing=print", rev])?;
+    Ok(out
+        .lines()
+        .filter_map(|l| l.strip_prefix('?'))
+        .map(|oid| oid.trim().to_string())
+        .filter(|oid| !oid.is_empty())
+        .collect())
+}
+
+/// Fetch missing objects from a promisor remote in batches of `batch_size`.
+///
+/// Mirrors git's own lazy-fetch invocation, but requests many objects per round
+/// trip instead of one per blame lookup. Returns the number of objects requested.
+pub fn prefetch_missing_blobs(
+    remote: &str,
+    rev: &str,
+    batch_size: usize,
+) -> Result<usize, GitInsightsError> {
+    let missing = missing_blobs(rev)?;
+    for chunk in missing.chunks(batch_size.max(1)) {
+        let mut input = chunk.join("\n");
+        input.push('\n');
+        run_command_with_stdin(
+            &[
+                "-c",
+                "fetch.negotiationAlgorithm=noop",
+                "fetch",
+                remote,
+                "--no-tags",
+                "--no-write-fetch-head",
+                "--recurse-submodules=no",
+                "--filter=blob:none",
+                "--stdin",
+            ],
+            &input,
+        )?;
+    }
+    Ok(missing.len())
+}
+
+/// Contents of each file at HEAD, read with one `cat-file --batch` call.
+///
+/// Entries are `None` for paths that are missing from HEAD or are not blobs.
+pub fn read_head_blobs(files: &[String]) -> Result<Vec<Option<Vec<u8>>>, GitInsightsError> {
+    if files.is_empty() {
+        return Ok(Vec::new());
+    }
+    let input: String = files.iter().map(|f| format!("HEAD:{}\n", f)).collect();
+    let out = run_command_bytes_with_stdin(&["cat-file", "--batch"], &input)?;
+    let mut blobs = Vec::with_capacity(files.len());
+    let mut pos = 0;
+    while blobs.len() < files.len() {
+        let nl = out[pos..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .ok_or("truncated cat-file output")?;
+        let header = String::from_utf8_lossy(&out[pos..pos + nl]).to_string();
+        pos += nl + 1;
+        if header.ends_with(" missing") || header.ends_with(" ambiguous") {
+            blobs.push(None);
+            continue;
+        }
+        let mut parts = header.split(' ');
+        let kind = parts.nth(1).unwrap_or("");
+        let len: usize = parts
+            .next()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| format!("unexpected cat-file header '{}'", header))?;
+        let body = out.get(pos..pos + len).ok_or("truncated cat-file output")?;
+        blobs.push((kind == "blob").then(|| body.to_vec()));
+        pos += len + 1; // trailing LF after each object
+    }
+    Ok(blobs)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_short_hash_is_stable() {
+        assert_eq!(short_hash(b""), "cbf29ce484222325");
+        assert_ne!(short_hash(b"a"), short_hash(b"b"));
+    }
+
     #[test]
     fn test_is_git_installed() {
         assert!(is_git_installed());
     }
 
+    #[test]
+    fn test_repo_scope_without_changing_cwd() {
+        // No test lock: nothing here touches the process working directory.
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        repo.commit_with_epoch("Ann", "ann@x.io", "src/a.txt", "a", 1_700_000_000)
+            .expect("commit");
+        let cwd = std::env::current_dir().unwrap();
+
+        let opened = Repo::open(repo.path.join("src")).expect("open");
+        assert_eq!(
+            opened.path().canonicalize().unwrap(),
+            repo.path.canonicalize().unwrap()
+        );
+        assert_eq!(opened.run_command(&["log", "--format=%an"]).unwrap(), "Ann");
+        let files = opened.scope(|| {
+            assert_eq!(Repo::current().as_ref(), Some(&opened));
+            run_command(&["ls-files"])
+        });
+        assert_eq!(files.unwrap(), "src/a.txt");
+        assert_eq!(Repo::current(), None);
+        assert_eq!(std::env::current_dir().unwrap(), cwd);
+
+        let outside = std::env::temp_dir();
+        assert!(Repo::open(outside.join("no-such-dir-for-git-insights")).is_err());
+    }
+
     #[test]
     fn test_is_in_git_repo() {
         let _guard = crate::test_sync::test_lock();
@@ -77,6 +637,148 @@ mod tests {
     #[test]
     fn test_run_command_failure() {
         let result = run_command(&["invalid-command"]);
-        assert!(result.is_err());
+        match result {
+            Err(GitInsightsError::Git { command, code, .. }) => {
+                assert_eq!(command, "invalid-command");
+                assert!(code.is_some_and(|c| c != 0));
+            }
+            other => panic!("Expected a git failure, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_run_command_with_stdin() {
+        let out = run_command_with_stdin(&["hash-object", "--stdin"], "hello\n").unwrap();
+        assert_eq!(out, "ce013625030ba8dba906f756967f9e9ca394464a");
+    }
+
+    #[test]
+    fn test_prefetch_missing_blobs_in_partial_clone() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        let authors = vec![crate::test_repo::Author::new(
+            "Alice",
+            "alice@test_git_insights.com",
+        )];
+        repo.seed_commits(6, &authors, 2).expect("seed");
+        for key in ["uploadpack.allowFilter", "uploadpack.allowAnySHA1InWant"] {
+            run_command(&["-C", repo.path.to_str().unwrap(), "config", key, "true"])
+                .expect("config");
+        }
+
+        let clone = repo.path.join("partial");
+        let url = format!("file://{}", repo.path.display());
+        run_command(&[
+            "clone",
+            "-q",
+            "--filter=blob:none",
+            &url,
+            clone.to_str().unwrap(),
+        ])
+        .expect("partial clone");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&clone).expect("cd");
+        let remotes = promisor_remotes();
+        let before = missing_blobs("HEAD").unwrap_or_default();
+        let fetched = prefetch_missing_blobs("origin", "HEAD", 2);
+        let after = missing_blobs("HEAD").unwrap_or_default();
+        std::env::set_current_dir(old).ok();
+
+        assert_eq!(remotes, vec!["origin".to_string()]);
+        assert!(!before.is_empty(), "historical blobs should be missing");
+        assert_eq!(fetched.expect("prefetch ok"), before.len());
+        assert!(after.is_empty(), "still missing: {:?}", after);
+    }
+
+    #[test]
+    fn test_classify_pull_request() {
+        use PullRequestSource::*;
+        let none: &[regex::Regex] = &[];
+        let cases = [
+            ("Merge pull request #12 from a/b\n\nFix", true, Some(GitHub)),
+            (
+                "Merge branch 'fix' into 'main'\n\nSee merge request group/app!34",
+                true,
+                Some(GitLab),
+            ),
+            ("Merged in feature (pull request #3)", true, Some(Bitbucket)),
+            (
+                "Fix parser\n\nChange-Id: I0123456789abcdef0123456789abcdef01234567",
+                false,
+                Some(Gerrit),
+            ),
+            ("Fix parser (#123)", false, Some(Squash)),
+            ("Merge branch 'main' into fix", true, Some(BranchMerge)),
+            ("Fix parser (#12a)", false, None),
+            ("Change-Id: Ishort", false, None),
+            ("Merge remote-tracking branch 'origin/main'", true, None),
+        ];
+        for (message, is_merge, expected) in cases {
+            assert_eq!(
+                classify_pull_request(message, is_merge, none),
+                expected,
+                "{}",
+                message
+            );
+        }
+        let custom = [regex::Regex::new(r"(?m)^Reviewed-on: ").unwrap()];
+        assert_eq!(
+            classify_pull_request("Fix (#1)\n\nReviewed-on: x", false, &custom),
+            Some(Custom)
+        );
+
+        let mut sources = PullRequestSources::default();
+        sources.add(Squash);
+        sources.add(GitHub);
+        assert_eq!(sources.total(), 2);
+        assert!(sources.to_json().contains("\"github\": 1, \"gitlab\": 0"));
+    }
+
+    #[test]
+    fn test_run_command_lines_streams_log() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", "one", 1_600_000_000)
+            .expect("commit");
+        repo.commit_with_epoch("Bob", "bob@x.io", "a.txt", "two", 1_600_000_100)
+            .expect("commit");
+        let opened = Repo::open(&repo.path).expect("open");
+
+        let mut lines = Vec::new();
+        opened
+            .scope(|| {
+                run_command_lines(&["log", "--format=%an%x09%at"], |l| {
+                    lines.push(l.to_string())
+                })
+            })
+            .expect("stream");
+        assert_eq!(lines, vec!["Bob\t1600000100", "Ann\t1600000000"]);
+
+        let mut records = Vec::new();
+        opened
+            .scope(|| {
+                run_command_records(&["log", "--format=%an%x1e"], b'\x1e', |r| {
+                    records.push(r.trim().to_string())
+                })
+            })
+            .expect("records");
+        assert_eq!(records, vec!["Bob", "Ann"]);
+
+        let first = opened
+            .scope(|| run_command_line_iter(&["log", "--format=%an"], |lines| lines.next()))
+            .expect("early stop is not an error");
+        assert_eq!(first.as_deref(), Some("Bob"));
+
+        let err = opened
+            .scope(|| run_command_lines(&["log", "no-such-rev"], |_| {}))
+            .expect_err("bad revision");
+        assert!(matches!(
+            err,
+            GitInsightsError::Git {
+                code: Some(128),
+                ..
+            }
+        ));
     }
 }
@@ -0,0 +1,253 @@
+//! Opt-in GitHub API enrichment for `user --github`: merged pull requests,
+//! reviews and issue activity from the GitHub search API instead of merge
+//! commit messages.
+//!
+//! Requests go through `curl`, like git, so the tool stays free of TLS
+//! dependencies. The token comes from `GITHUB_TOKEN` or `GH_TOKEN` and is
+//! passed to curl on stdin, never on the command line.
+
+use crate::error::GitInsightsError;
+use crate::git::run_command;
+use crate::json;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Environment variables checked for the API token, in order.
+pub const TOKEN_VARS: [&str; 2] = ["GITHUB_TOKEN", "GH_TOKEN"];
+
+const DEFAULT_API_URL: &str = "https://api.github.com";
+
+/// What a user did on the repository according to GitHub.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GitHubActivity {
+    /// `owner/name` the counts are for.
+    pub repo: String,
+    pub login: String,
+    pub merged_prs: usize,
+    /// Pull requests the user reviewed.
+    pub reviews: usize,
+    pub issues_opened: usize,
+    /// Issues the user commented on.
+    pub issues_commented: usize,
+}
+
+impl GitHubActivity {
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"repo\": \"{}\", \"login\": \"{}\", \"merged_prs\": {}, \"reviews\": {}, \"issues_opened\": {}, \"issues_commented\": {}}}",
+            json::escape(&self.repo),
+            json::escape(&self.login),
+            self.merged_prs,
+            self.reviews,
+            self.issues_opened,
+            self.issues_commented
+        )
+    }
+}
+
+/// `owner/name` of a GitHub remote URL (`git@github.com:o/r.git`,
+/// `https://github.com/o/r`, `ssh://git@github.com/o/r.git`).
+pub fn parse_remote_slug(url: &str) -> Option<String> {
+    let url = url.trim();
+    let rest = url
+        .strip_prefix("git@github.com:")
+        .or_else(|| url.split_once("github.com/").map(|(_, r)| r))?;
+    let rest = rest.trim_end_matches('/');
+    let rest = rest.strip_suffix(".git").unwrap_or(rest);
+    let (owner, name) = rest.split_once('/')?;
+    if owner.is_empty() || name.is_empty() || name.contains('/') {
+        return None;
+    }
+    Some(format!("{}/{}", owner, name))
+}
+
+/// The repository to query: `GITHUB_REPOSITORY` (set in GitHub Actions), else
+/// the `origin` remote.
+fn repo_slug() -> Result<String, String> {
+    if let Ok(slug) = std::env::var("GITHUB_REPOSITORY") {
+        if !slug.trim().is_empty() {
+            return Ok(slug.trim().to_string());
+        }
+    }
+    let url = run_command(&["remote", "get-url", "origin"])
+        .map_err(|_| "no 'origin' remote; set GITHUB_REPOSITORY=owner/name".to_string())?;
+    parse_remote_slug(&url).ok_or_else(|| {
+        format!(
+            "origin ({}) is not a GitHub remote; set GITHUB_REPOSITORY=owner/name",
+            url.trim()
+        )
+    })
+}
+
+fn token() -> Result<String, String> {
+    TOKEN_VARS
+        .iter()
+        .filter_map(|v| std::env::var(v).ok())
+        .find(|t| !t.trim().is_empty())
+        .map(|t| t.trim().to_string())
+        .ok_or_else(|| format!("--github needs a token in {}", TOKEN_VARS.join(" or ")))
+}
+
+/// Percent-encode a search query for the URL.
+fn encode_query(q: &str) -> String {
+    let mut out = String::with_capacity(q.len());
+    for b in q.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(b as char)
+            }
+            b' ' => out.push('+'),
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+/// Search URL counting issues/PRs matching `qualifiers` in `repo`.
+pub fn search_url(api: &str, repo: &str, qualifiers: &str) -> String {
+    format!(
+        "{}/search/issues?q={}&per_page=1",
+        api.trim_end_matches('/'),
+        encode_query(&format!("repo:{} {}", repo, qualifiers))
+    )
+}
+
+/// `total_count` of a search response, or the API's error message.
+pub fn parse_total_count(status: u16, body: &str) -> Result<usize, String> {
+    let doc = json::parse(body).ok();
+    if status != 200 {
+        let message = doc
+            .as_ref()
+            .and_then(|d| d.get("message"))
+            .and_then(|m| m.as_str())
+            .unwrap_or("unexpected response");
+        return Err(format!("GitHub API returned {}: {}", status, message));
+    }
+    doc.as_ref()
+        .and_then(|d| d.get("total_count"))
+        .and_then(|c| c.as_usize())
+        .ok_or_else(|| "GitHub API response has no total_count".to_string())
+}
+
+/// GET `url` with curl; returns the HTTP status and body.
+fn get(url: &str, token: &str) -> Result<(u16, String), String> {
+    let mut child = Command::new("curl")
+        .args([
+            "--silent",
+            "--show-error",
+            "--header",
+            "@-",
+            "--header",
+            "Accept: application/vnd.github+json",
+            "--header",
+            "X-GitHub-Api-Version: 2022-11-28",
+            "--user-agent",
+            "git-insights",
+            "--write-out",
+            "\n%{http_code}",
+            url,
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("--github needs curl on PATH: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        writeln!(stdin, "Authorization: Bearer {}", token)
+            .map_err(|e| format!("Failed to pass the token to curl: {}", e))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("curl failed: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "curl failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    let (body, status) = text.rsplit_once('\n').unwrap_or(("", &text));
+    let status = status
+        .trim()
+        .parse()
+        .map_err(|_| "curl returned no HTTP status".to_string())?;
+    Ok((status, body.to_string()))
+}
+
+/// Query GitHub for `login`'s activity in the current repository.
+pub fn fetch_github_activity(login: &str) -> Result<GitHubActivity, GitInsightsError> {
+    fetch_activity(login).map_err(GitInsightsError::GitHub)
+}
+
+fn fetch_activity(login: &str) -> Result<GitHubActivity, String> {
+    let token = token()?;
+    let repo = repo_slug()?;
+    let api = std::env::var("GITHUB_API_URL").unwrap_or_else(|_| DEFAULT_API_URL.to_string());
+    let count = |qualifiers: String| -> Result<usize, String> {
+        let url = search_url(&api, &repo, &qualifiers);
+        crate::log::debug(format_args!("GET {}", url));
+        let (status, body) = get(&url, &token)?;
+        parse_total_count(status, &body)
+    };
+    Ok(GitHubActivity {
+        merged_prs: count(format!("type:pr is:merged author:{}", login))?,
+        reviews: count(format!("type:pr reviewed-by:{} -author:{}", login, login))?,
+        issues_opened: count(format!("type:issue author:{}", login))?,
+        issues_commented: count(format!("type:issue commenter:{}", login))?,
+        repo,
+        login: login.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remote_slug_and_search_url() {
+        for url in [
+            "git@github.com:Magnus167/git-insights.git",
+            "https://github.com/Magnus167/git-insights",
+            "ssh://git@github.com/Magnus167/git-insights.git/\n",
+        ] {
+            assert_eq!(
+                parse_remote_slug(url).as_deref(),
+                Some("Magnus167/git-insights"),
+                "{}",
+                url
+            );
+        }
+        assert_eq!(parse_remote_slug("https://gitlab.com/a/b.git"), None);
+        assert_eq!(parse_remote_slug("https://github.com/a"), None);
+
+        assert_eq!(
+            search_url("https://api.github.com/", "o/r", "type:pr author:ann-l"),
+            "https://api.github.com/search/issues?q=repo%3Ao%2Fr+type%3Apr+author%3Aann-l&per_page=1"
+        );
+    }
+
+    #[test]
+    fn test_parse_total_count() {
+        assert_eq!(
+            parse_total_count(200, "{\"total_count\": 12, \"items\": []}"),
+            Ok(12)
+        );
+        let err = parse_total_count(401, "{\"message\": \"Bad credentials\"}").unwrap_err();
+        assert_eq!(err, "GitHub API returned 401: Bad credentials");
+        assert!(parse_total_count(200, "<html>").is_err());
+    }
+
+    #[test]
+    fn test_missing_token_is_github_error() {
+        let has_token = TOKEN_VARS
+            .iter()
+            .any(|v| std::env::var(v).is_ok_and(|t| !t.trim().is_empty()));
+        if has_token {
+            return;
+        }
+        match fetch_github_activity("ann-l") {
+            Err(GitInsightsError::GitHub(msg)) => assert!(msg.contains("needs a token")),
+            other => panic!("Expected a GitHub error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,427 @@
+use crate::code_frequency::ymd_from_unix;
+use crate::commits::{collect_commits, Commit};
+use crate::error::GitInsightsError;
+use crate::git::{run_command, run_command_bytes_with_stdin, run_command_lines};
+use crate::json;
+use crate::output::{finish_progress, print_progress};
+use crate::stats::SNIFF_BYTES;
+use crate::visualize::{render_timeline_multiline, ChartSize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::{self, Write};
+use std::time::Instant;
+
+const BAR_WIDTH: usize = 40;
+
+/// Default number of commits sampled by `growth --loc`.
+pub const DEFAULT_LOC_SAMPLES: usize = 40;
+
+/// Distinct authors active in one calendar month (UTC).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MonthGrowth {
+    pub year: i32,
+    pub month: u32,
+    /// Authors whose first-ever commit falls in this month.
+    pub new: usize,
+    /// Authors with an earlier commit who committed again this month.
+    pub returning: usize,
+}
+
+impl MonthGrowth {
+    pub fn label(&self) -> String {
+        format!("{:04}-{:02}", self.year, self.month)
+    }
+}
+
+/// New vs returning authors per month, from the first to the last active
+/// month with no gaps (quiet months have zero of both).
+pub fn monthly_growth(commits: &[Commit]) -> Vec<MonthGrowth> {
+    let mut first: BTreeMap<&str, u64> = BTreeMap::new();
+    let mut active: BTreeSet<((i32, u32), &str)> = BTreeSet::new();
+    for c in commits {
+        let t = first.entry(&c.author_name).or_insert(c.author_time);
+        *t = (*t).min(c.author_time);
+        let (y, m, _) = ymd_from_unix(c.author_time);
+        active.insert(((y, m), &c.author_name));
+    }
+    let (Some(&(start, _)), Some(&(end, _))) = (active.first(), active.last()) else {
+        return Vec::new();
+    };
+
+    let mut months: BTreeMap<(i32, u32), MonthGrowth> = BTreeMap::new();
+    let (mut y, mut m) = start;
+    while (y, m) <= end {
+        months.insert(
+            (y, m),
+            MonthGrowth {
+                year: y,
+                month: m,
+                ..Default::default()
+            },
+        );
+        (y, m) = if m == 12 { (y + 1, 1) } else { (y, m + 1) };
+    }
+    for (ym, author) in active {
+        let (fy, fm, _) = ymd_from_unix(first[author]);
+        if let Some(row) = months.get_mut(&ym) {
+            if (fy, fm) == ym {
+                row.new += 1;
+            } else {
+                row.returning += 1;
+            }
+        }
+    }
+    months.into_values().collect()
+}
+
+/// Monthly growth for the active filter, counting merges like `contributors`.
+pub fn gather_growth() -> Result<Vec<MonthGrowth>, GitInsightsError> {
+    let keep_merges = crate::filter::active().include_merges;
+    let commits: Vec<Commit> = collect_commits()?
+        .into_iter()
+        .filter(|c| keep_merges || !c.is_merge())
+        .collect();
+    Ok(monthly_growth(&commits))
+}
+
+/// `[{"month": "2024-01", "new": 1, "returning": 2}, ...]`
+pub fn render_growth_json(rows: &[MonthGrowth]) -> String {
+    let items: Vec<String> = rows
+        .iter()
+        .map(|r| {
+            format!(
+                "{{\"month\": \"{}\", \"new\": {}, \"returning\": {}}}",
+                json::escape(&r.label()),
+                r.new,
+                r.returning
+            )
+        })
+        .collect();
+    format!("[{}]", items.join(", "))
+}
+
+/// Two-series bar per month: new authors (`+`, green) stacked before
+/// returning authors (`=`, blue), scaled to the busiest month.
+pub fn render_growth_bars(rows: &[MonthGrowth], color: bool) {
+    let max = rows.iter().map(|r| r.new + r.returning).max().unwrap_or(0);
+    if max == 0 {
+        println!("(no commits)");
+        return;
+    }
+    let (new_glyph, ret_glyph, new_on, ret_on, dim, off) = if color {
+        ("█", "█", "\x1b[32m", "\x1b[34m", "\x1b[90m", "\x1b[0m")
+    } else {
+        ("+", "=", "", "", "", "")
+    };
+    println!(
+        "{}Authors per month: {}{}{} new, {}{}{} returning (max={}){}",
+        dim, new_on, new_glyph, dim, ret_on, ret_glyph, dim, max, off
+    );
+    for r in rows {
+        let total = r.new + r.returning;
+        let total_len = (total * BAR_WIDTH).div_ceil(max);
+        let new_len = (r.new * BAR_WIDTH).div_ceil(max).min(total_len);
+        println!(
+            "{} | {}{}{}{}{}{}{} {} new, {} returning",
+            r.label(),
+            new_on,
+            new_glyph.repeat(new_len),
+            off,
+            ret_on,
+            ret_glyph.repeat(total_len - new_len),
+            off,
+            " ".repeat(BAR_WIDTH - total_len),
+            r.new,
+            r.returning
+        );
+    }
+}
+
+/// Print the histogram (or JSON).
+pub fn run_growth(color: bool, as_json: bool) -> Result<(), GitInsightsError> {
+    let rows = gather_growth()?;
+    if as_json {
+        println!("{}", render_growth_json(&rows));
+    } else {
+        render_growth_bars(&rows, color);
+    }
+    Ok(())
+}
+
+// Codebase size over time (`--loc`).
+
+/// Total lines in the text blobs of one sampled commit's tree.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LocSample {
+    pub sha: String,
+    /// Committer time.
+    pub time: u64,
+    pub lines: usize,
+}
+
+/// `n` indices spread evenly over `0..len`, always including both ends.
+pub fn sample_indices(len: usize, n: usize) -> Vec<usize> {
+    match (len, n) {
+        (0, _) | (_, 0) => Vec::new(),
+        (_, 1) => vec![len - 1],
+        _ => {
+            let mut idx: Vec<usize> = (0..n).map(|i| i * (len - 1) / (n - 1)).collect();
+            idx.dedup();
+            idx
+        }
+    }
+}
+
+/// Line counts from `cat-file --batch` output, one per requested object:
+/// `None` for binary blobs (a NUL in the first [`SNIFF_BYTES`]) and for
+/// anything that is not a blob. A final line without a newline counts.
+pub fn parse_batch_line_counts(out: &[u8], expected: usize) -> Result<Vec<Option<usize>>, String> {
+    let mut counts = Vec::with_capacity(expected);
+    let mut pos = 0;
+    while counts.len() < expected {
+        let nl = out[pos..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .ok_or("truncated cat-file output")?;
+        let header = String::from_utf8_lossy(&out[pos..pos + nl]).to_string();
+        pos += nl + 1;
+        if header.ends_with(" missing") || header.ends_with(" ambiguous") {
+            counts.push(None);
+            continue;
+        }
+        let mut parts = header.split(' ');
+        let kind = parts.nth(1).unwrap_or("");
+        let size: usize = parts
+            .next()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| format!("unexpected cat-file header '{}'", header))?;
+        let body = out
+            .get(pos..pos + size)
+            .ok_or("truncated cat-file output")?;
+        let text = kind == "blob" && !body[..size.min(SNIFF_BYTES)].contains(&0);
+        counts.push(text.then(|| {
+            let newlines = body.iter().filter(|&&b| b == b'\n').count();
+            newlines + usize::from(body.last().is_some_and(|&b| b != b'\n'))
+        }));
+        pos += size + 1;
+    }
+    Ok(counts)
+}
+
+/// Blob ids in the tree of `sha` (one per file, so copies count twice).
+fn tree_blobs(sha: &str) -> Result<Vec<String>, GitInsightsError> {
+    let out = run_command(&["--no-pager", "ls-tree", "-r", sha])?;
+    Ok(out
+        .lines()
+        .filter_map(|l| {
+            let (meta, _path) = l.split_once('\t')?;
+            let mut f = meta.split(' ');
+            (f.nth(1) == Some("blob")).then(|| f.next().map(str::to_string))?
+        })
+        .collect())
+}
+
+/// `(sha, committer time)` of `samples` evenly spaced commits of the active
+/// filter, oldest first.
+pub(crate) fn sample_commits(samples: usize) -> Result<Vec<(String, u64)>, GitInsightsError> {
+    let revs = crate::filter::revision_args()?;
+    let mut args = vec!["--no-pager", "log", "--reverse", "--format=%H %ct"];
+    args.extend(revs.iter().map(|s| s.as_str()));
+    let mut commits: Vec<(String, u64)> = Vec::new();
+    run_command_lines(&args, |l| {
+        if let Some((sha, ct)) = l.trim().split_once(' ') {
+            commits.push((sha.to_string(), ct.parse().unwrap_or(0)));
+        }
+    })?;
+    Ok(sample_indices(commits.len(), samples)
+        .into_iter()
+        .map(|i| commits[i].clone())
+        .collect())
+}
+
+/// Total text lines at `samples` evenly spaced commits of the active filter,
+/// oldest first. Each distinct blob is read once.
+pub fn gather_loc_samples(samples: usize) -> Result<Vec<LocSample>, GitInsightsError> {
+    let picked = sample_commits(samples)?;
+    let mut lines_by_blob: HashMap<String, Option<usize>> = HashMap::new();
+    let mut rows = Vec::with_capacity(picked.len());
+    let start = Instant::now();
+    for (n, (sha, time)) in picked.iter().enumerate() {
+        print_progress(n + 1, picked.len(), start);
+        let blobs = tree_blobs(sha)?;
+        let mut fresh: Vec<&String> = blobs
+            .iter()
+            .filter(|b| !lines_by_blob.contains_key(*b))
+            .collect();
+        fresh.sort();
+        fresh.dedup();
+        if !fresh.is_empty() {
+            let input: String = fresh.iter().map(|b| format!("{}\n", b)).collect();
+            let out = run_command_bytes_with_stdin(&["cat-file", "--batch"], &input)?;
+            let counts = parse_batch_line_counts(&out, fresh.len())?;
+            for (blob, count) in fresh.into_iter().zip(counts) {
+                lines_by_blob.insert(blob.clone(), count);
+            }
+        }
+        rows.push(LocSample {
+            sha: sha.clone(),
+            time: *time,
+            lines: blobs.iter().filter_map(|b| lines_by_blob[b]).sum(),
+        });
+    }
+    if !picked.is_empty() {
+        finish_progress();
+    }
+    Ok(rows)
+}
+
+fn format_day(t: u64) -> String {
+    let (y, m, d) = ymd_from_unix(t);
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+/// `[{"sha": "...", "date": "YYYY-MM-DD", "lines": N}, ...]`
+pub fn render_loc_json(rows: &[LocSample]) -> String {
+    let items: Vec<String> = rows
+        .iter()
+        .map(|r| {
+            format!(
+                "{{\"sha\": \"{}\", \"date\": \"{}\", \"lines\": {}}}",
+                r.sha,
+                format_day(r.time),
+                r.lines
+            )
+        })
+        .collect();
+    format!("[{}]", items.join(", "))
+}
+
+/// Codebase size as a multiline timeline, one column per sampled commit.
+pub fn render_loc_timeline(
+    out: &mut impl Write,
+    rows: &[LocSample],
+    color: bool,
+) -> io::Result<()> {
+    let (Some(first), Some(last)) = (rows.first(), rows.last()) else {
+        writeln!(out, "(no commits)")?;
+        return Ok(());
+    };
+    let counts: Vec<usize> = rows.iter().map(|r| r.lines).collect();
+    let max = counts.iter().copied().max().unwrap_or(0);
+    let (dim, off) = if color {
+        ("\x1b[90m", "\x1b[0m")
+    } else {
+        ("", "")
+    };
+    writeln!(
+        out,
+        "{}Total lines in text files at {} sampled commits (old -> new), max={}{}",
+        dim,
+        rows.len(),
+        max,
+        off
+    )?;
+    writeln!(out)?;
+    render_timeline_multiline(out, &counts, ChartSize::default().timeline_height(), color)?;
+    let left_pad = max.to_string().len().max(3) + 2;
+    let (from, to) = (format_day(first.time), format_day(last.time));
+    let gap = rows.len().saturating_sub(from.len() + to.len()).max(1);
+    writeln!(
+        out,
+        "{}{}{}{}{}{}",
+        dim,
+        " ".repeat(left_pad),
+        from,
+        " ".repeat(gap),
+        to,
+        off
+    )?;
+    writeln!(out)?;
+    let change = last.lines as i64 - first.lines as i64;
+    writeln!(
+        out,
+        "{} lines at {} -> {} lines at {} ({:+})",
+        first.lines, from, last.lines, to, change
+    )?;
+    Ok(())
+}
+
+/// `growth --loc`: sample, then print the timeline (or JSON).
+pub fn run_growth_loc(samples: usize, color: bool, as_json: bool) -> Result<(), GitInsightsError> {
+    let rows = gather_loc_samples(samples)?;
+    if as_json {
+        println!("{}", render_loc_json(&rows));
+    } else {
+        render_loc_timeline(&mut io::stdout().lock(), &rows, color).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monthly_growth() {
+        let commit = |name: &str, t: u64| Commit {
+            author_name: name.to_string(),
+            author_time: t,
+            ..Default::default()
+        };
+        // 2023-11-14, 2023-11-20, 2024-01-10 and 2024-01-20 (UTC).
+        let commits = vec![
+            commit("Bob", 1_705_708_800),
+            commit("Ann", 1_704_844_800),
+            commit("Ann", 1_700_438_400),
+            commit("Ann", 1_699_920_000),
+        ];
+        let rows = monthly_growth(&commits);
+        let summary: Vec<(String, usize, usize)> = rows
+            .iter()
+            .map(|r| (r.label(), r.new, r.returning))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("2023-11".to_string(), 1, 0),
+                ("2023-12".to_string(), 0, 0),
+                ("2024-01".to_string(), 1, 1),
+            ]
+        );
+        assert_eq!(
+            render_growth_json(&rows[..1]),
+            "[{\"month\": \"2023-11\", \"new\": 1, \"returning\": 0}]"
+        );
+        assert!(monthly_growth(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_loc_sampling_helpers() {
+        assert_eq!(sample_indices(10, 4), vec![0, 3, 6, 9]);
+        assert_eq!(sample_indices(3, 40), vec![0, 1, 2]);
+        assert_eq!(sample_indices(5, 1), vec![4]);
+        assert!(sample_indices(0, 4).is_empty());
+
+        let out = b"aaa blob 6\nx\ny\nz\n\nbbb blob 3\na\0b\nccc missing\nddd blob 0\n\n";
+        assert_eq!(
+            parse_batch_line_counts(out, 4).expect("parse"),
+            vec![Some(3), None, None, Some(0)]
+        );
+    }
+
+    #[test]
+    fn test_gather_loc_samples() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        for (i, line) in ["one", "two", "three", "four"].iter().enumerate() {
+            repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", line, 1_700_000_000 + i as u64)
+                .expect("commit");
+        }
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        let rows = gather_loc_samples(2);
+        std::env::set_current_dir(old).ok();
+
+        let lines: Vec<usize> = rows.expect("samples").iter().map(|r| r.lines).collect();
+        assert_eq!(lines, vec![1, 4]);
+    }
+}
@@ -0,0 +1,246 @@
+use crate::error::GitInsightsError;
+use crate::git::{read_head_blobs, run_command_line_iter};
+use crate::output::truncate;
+use crate::stats::tracked_text_files_head;
+use std::collections::HashMap;
+
+/// How a file's "size" is measured when scoring hotspots.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum HotspotSize {
+    /// Lines of code at HEAD.
+    Loc,
+    /// Sum of indentation levels over non-blank lines, a cheap complexity proxy.
+    Indent,
+}
+
+impl HotspotSize {
+    pub fn parse(s: &str) -> Result<HotspotSize, String> {
+        match s {
+            "loc" => Ok(HotspotSize::Loc),
+            "indent" => Ok(HotspotSize::Indent),
+            other => Err(format!(
+                "unknown --size '{}'. Expected loc or indent.",
+                other
+            )),
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            HotspotSize::Loc => "loc",
+            HotspotSize::Indent => "indent",
+        }
+    }
+
+    fn measure(self, text: &str) -> usize {
+        match self {
+            HotspotSize::Loc => text.lines().count(),
+            HotspotSize::Indent => indent_complexity(text),
+        }
+    }
+}
+
+/// A file ranked by churn × size.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hotspot {
+    pub path: String,
+    pub commits: usize,
+    pub size: usize,
+    pub score: usize,
+}
+
+/// Sum of indentation levels (4 columns, tabs count as one level) over non-blank lines.
+pub fn indent_complexity(text: &str) -> usize {
+    text.lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| {
+            let cols: usize = l
+                .chars()
+                .take_while(|c| c.is_whitespace())
+                .map(|c| if c == '\t' { 4 } else { 1 })
+                .sum();
+            cols / 4
+        })
+        .sum()
+}
+
+/// Parse `git log --format=%x1f%aN%x1f%aE --name-only` output into per-file
+/// commit counts, keeping only commits whose author passes `allow`.
+pub fn parse_churn(out: &str, allow: impl Fn(&str, &str) -> bool) -> HashMap<String, usize> {
+    churn_from_lines(out.lines(), allow)
+}
+
+/// [`parse_churn`] over lines as git streams them.
+fn churn_from_lines<S: AsRef<str>>(
+    lines: impl Iterator<Item = S>,
+    allow: impl Fn(&str, &str) -> bool,
+) -> HashMap<String, usize> {
+    let mut churn = HashMap::new();
+    let mut counting = false;
+    for line in lines {
+        let line = line.as_ref();
+        if let Some(header) = line.strip_prefix('\x1f') {
+            let (name, email) = header.split_once('\x1f').unwrap_or((header, ""));
+            counting = allow(name, email);
+        } else if counting && !line.trim().is_empty() {
+            *churn.entry(line.trim().to_string()).or_insert(0) += 1;
+        }
+    }
+    churn
+}
+
+/// Rank files present in both maps by `commits * size`, highest first.
+pub fn rank_hotspots(
+    churn: &HashMap<String, usize>,
+    sizes: &HashMap<String, usize>,
+) -> Vec<Hotspot> {
+    let mut rows: Vec<Hotspot> = sizes
+        .iter()
+        .filter_map(|(path, &size)| {
+            let commits = *churn.get(path)?;
+            Some(Hotspot {
+                path: path.clone(),
+                commits,
+                size,
+                score: commits * size,
+            })
+        })
+        .collect();
+    rows.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+    rows
+}
+
+/// Measure each text file's HEAD blob in one `cat-file --batch` call.
+fn file_sizes(
+    files: &[String],
+    size: HotspotSize,
+) -> Result<HashMap<String, usize>, GitInsightsError> {
+    Ok(files
+        .iter()
+        .zip(read_head_blobs(files)?)
+        .filter_map(|(file, body)| {
+            let body = body?;
+            Some((file.clone(), size.measure(&String::from_utf8_lossy(&body))))
+        })
+        .collect())
+}
+
+/// Compute hotspots for the current repository under the active filter.
+pub fn gather_hotspots(size: HotspotSize) -> Result<Vec<Hotspot>, GitInsightsError> {
+    let churn = gather_churn()?;
+
+    let files: Vec<String> = tracked_text_files_head()?
+        .into_iter()
+        .filter(|f| churn.contains_key(f))
+        .collect();
+    if files.is_empty() {
+        return Ok(Vec::new());
+    }
+    let sizes = file_sizes(&files, size)?;
+    Ok(rank_hotspots(&churn, &sizes))
+}
+
+/// Non-merge commits per path for the active filter.
+pub(crate) fn gather_churn() -> Result<HashMap<String, usize>, GitInsightsError> {
+    let revs = crate::filter::revision_args()?;
+    let authors = crate::filter::author_matcher()?;
+    let mut args = vec![
+        "--no-pager",
+        "log",
+        "--no-merges",
+        "--no-renames",
+        "--format=%x1f%aN%x1f%aE",
+        "--name-only",
+    ];
+    args.extend(revs.iter().map(|s| s.as_str()));
+    run_command_line_iter(&args, |lines| {
+        churn_from_lines(lines, |n, e| authors.allows(n, e))
+    })
+}
+
+/// Print the top `top` hotspots as a table.
+pub fn run_hotspots(top: usize, size: HotspotSize) -> Result<(), GitInsightsError> {
+    let rows = gather_hotspots(size)?;
+    println!(
+        "| {:>4} | {:<60} | {:>7} | {:>7} | {:>9} |",
+        "No.",
+        "File",
+        "coms",
+        size.label(),
+        "score"
+    );
+    println!(
+        "|{:->6}|:{:-<60}|{:->9}|{:->9}|{:->11}|",
+        "", "", "", "", ""
+    );
+    for (i, h) in rows.iter().take(top).enumerate() {
+        println!(
+            "| {:>4} | {:<60} | {:>7} | {:>7} | {:>9} |",
+            i + 1,
+            truncate(&h.path, 60),
+            h.commits,
+            h.size,
+            h.score
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_indent_complexity() {
+        let src = "fn a() {\n    if x {\n        y();\n    }\n\n\tz();\n}\n";
+        // levels: 0 + 1 + 2 + 1 + (blank) + 1 + 0
+        assert_eq!(indent_complexity(src), 5);
+        assert_eq!(HotspotSize::Loc.measure(src), 7);
+        assert!(HotspotSize::parse("cyclomatic").is_err());
+    }
+
+    #[test]
+    fn test_parse_churn_and_rank() {
+        let out = "\x1fAnn\x1fann@x.io\n\na.rs\nb.rs\n\x1fBob\x1fbob@x.io\n\na.rs\n\x1fAnn\x1fann@x.io\n\na.rs\n";
+        let churn = parse_churn(out, |_, _| true);
+        assert_eq!(churn["a.rs"], 3);
+        assert_eq!(churn["b.rs"], 1);
+        let no_bob = parse_churn(out, |n, _| n != "Bob");
+        assert_eq!(no_bob["a.rs"], 2);
+
+        let sizes: HashMap<String, usize> = [
+            ("a.rs".to_string(), 10),
+            ("b.rs".to_string(), 50),
+            ("gone.rs".to_string(), 5),
+        ]
+        .into_iter()
+        .collect();
+        let rows = rank_hotspots(&churn, &sizes);
+        assert_eq!(rows.len(), 2);
+        assert_eq!((rows[0].path.as_str(), rows[0].score), ("b.rs", 50));
+        assert_eq!((rows[1].path.as_str(), rows[1].score), ("a.rs", 30));
+    }
+
+    #[test]
+    fn test_gather_hotspots_e2e() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        for (i, line) in ["one", "two", "three"].iter().enumerate() {
+            repo.commit_with_epoch("Ann", "ann@x.io", "hot.txt", line, 1_700_000_000 + i as u64)
+                .expect("commit");
+        }
+        repo.commit_with_epoch("Ann", "ann@x.io", "cold.txt", "x", 1_700_000_100)
+            .expect("commit");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        let rows = gather_hotspots(HotspotSize::Loc);
+        std::env::set_current_dir(old).ok();
+
+        let rows = rows.expect("hotspots");
+        assert_eq!(rows[0].path, "hot.txt");
+        assert_eq!((rows[0].commits, rows[0].size, rows[0].score), (3, 3, 9));
+        assert_eq!(rows[1].path, "cold.txt");
+    }
+}
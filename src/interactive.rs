@@ -0,0 +1,279 @@
+use std::io::{self, BufRead, Write};
+
+/// An analysis offered by the guided mode.
+struct Analysis {
+    label: &'static str,
+    command: &'static str,
+    /// Whether the command accepts `--weeks N`.
+    weeks: bool,
+    /// Default export path for commands that write a file.
+    export: Option<&'static str>,
+}
+
+const ANALYSES: &[Analysis] = &[
+    Analysis {
+        label: "Repository stats (LOC, commits, files per author)",
+        command: "stats",
+        weeks: false,
+        export: None,
+    },
+    Analysis {
+        label: "Weekly commit timeline",
+        command: "timeline",
+        weeks: true,
+        export: None,
+    },
+    Analysis {
+        label: "Weekday x hour heatmap",
+        command: "heatmap",
+        weeks: true,
+        export: None,
+    },
+    Analysis {
+        label: "Hotspots (files changed often and large)",
+        command: "hotspots",
+        weeks: false,
+        export: None,
+    },
+    Analysis {
+        label: "HTML report",
+        command: "report",
+        weeks: false,
+        export: Some(crate::report::REPORT_PATH),
+    },
+    Analysis {
+        label: "JSON export of author stats",
+        command: "json",
+        weeks: false,
+        export: Some(crate::stats::JSON_EXPORT_PATH),
+    },
+    Analysis {
+        label: "Commit stream as JSON Lines",
+        command: "log-json",
+        weeks: false,
+        export: Some(crate::commits::LOG_JSON_PATH),
+    },
+];
+
+/// Read one trimmed answer; EOF aborts the session.
+fn read_answer<R: BufRead, W: Write>(
+    input: &mut R,
+    out: &mut W,
+    question: &str,
+) -> Result<String, String> {
+    write!(out, "{} ", question)
+        .and_then(|_| out.flush())
+        .map_err(|e| e.to_string())?;
+    let mut line = String::new();
+    let n = input.read_line(&mut line).map_err(|e| e.to_string())?;
+    if n == 0 {
+        return Err("interactive mode aborted (no input)".to_string());
+    }
+    Ok(line.trim().to_string())
+}
+
+/// Ask a free-form question; a blank answer yields `default`.
+fn ask<R: BufRead, W: Write>(
+    input: &mut R,
+    out: &mut W,
+    question: &str,
+    default: &str,
+) -> Result<String, String> {
+    let answer = read_answer(input, out, &format!("{} [{}]:", question, default))?;
+    Ok(if answer.is_empty() {
+        default.to_string()
+    } else {
+        answer
+    })
+}
+
+/// Ask until the answer is a number in `1..=options.len()`; blank picks the first.
+fn choose<R: BufRead, W: Write>(
+    input: &mut R,
+    out: &mut W,
+    question: &str,
+    options: &[&str],
+) -> Result<usize, String> {
+    writeln!(out, "{}", question).map_err(|e| e.to_string())?;
+    for (i, o) in options.iter().enumerate() {
+        writeln!(out, "  {}) {}", i + 1, o).map_err(|e| e.to_string())?;
+    }
+    loop {
+        let answer = read_answer(input, out, "Choice [1]:")?;
+        if answer.is_empty() {
+            return Ok(0);
+        }
+        match answer.parse::<usize>() {
+            Ok(n) if (1..=options.len()).contains(&n) => return Ok(n - 1),
+            _ => writeln!(
+                out,
+                "Please enter a number between 1 and {}.",
+                options.len()
+            )
+            .map_err(|e| e.to_string())?,
+        }
+    }
+}
+
+/// Walk through analysis, window and output choices and return the
+/// equivalent command-line arguments (without the program name).
+pub fn prompt_args<R: BufRead, W: Write>(
+    input: &mut R,
+    out: &mut W,
+) -> Result<Vec<String>, String> {
+    let labels: Vec<&str> = ANALYSES.iter().map(|a| a.label).collect();
+    let analysis = &ANALYSES[choose(input, out, "What would you like to see?", &labels)?];
+    let mut args = vec![analysis.command.to_string()];
+
+    if analysis.weeks {
+        loop {
+            let weeks = ask(
+                input,
+                out,
+                "How many weeks of history? ('all' for everything)",
+                "all",
+            )?;
+            if weeks == "all" {
+                break;
+            }
+            if weeks.parse::<usize>().map(|n| n > 0).unwrap_or(false) {
+                args.push("--weeks".to_string());
+                args.push(weeks);
+                break;
+            }
+            writeln!(out, "Please enter a positive number or 'all'.").map_err(|e| e.to_string())?;
+        }
+    }
+
+    let window = choose(
+        input,
+        out,
+        "Which commits should count?",
+        &[
+            "All history",
+            "Only what shipped (reachable from release tags)",
+            "Only my commits",
+            "Everyone except me",
+        ],
+    )?;
+    match window {
+        1 => {
+            args.push("--reachable-from".to_string());
+            args.push(ask(input, out, "Release tag glob", "v*")?);
+        }
+        2 => args.push("--only-me".to_string()),
+        3 => args.push("--exclude-me".to_string()),
+        _ => {}
+    }
+
+    if let Some(default) = analysis.export {
+        let path = ask(input, out, "Write to ('-' for the terminal)", default)?;
+        if path != default {
+            args.push("--output".to_string());
+            args.push(path);
+        }
+    } else if analysis.command == "heatmap" {
+        let format = choose(
+            input,
+            out,
+            "Output format?",
+            &["Terminal (ANSI colors)", "SVG file"],
+        )?;
+        if format == 1 {
+            args.push("--output".to_string());
+            args.push(ask(input, out, "SVG path", "heatmap.svg")?);
+        }
+    }
+    Ok(args)
+}
+
+/// Quote an argument for POSIX shells when needed.
+fn shell_quote(arg: &str) -> String {
+    let safe = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:@,+".contains(c));
+    if safe {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// Render `args` as a copy-pasteable `git-insights ...` command line.
+pub fn shell_command(args: &[String]) -> String {
+    let mut cmd = String::from("git-insights");
+    for a in args {
+        cmd.push(' ');
+        cmd.push_str(&shell_quote(a));
+    }
+    cmd
+}
+
+/// Run the guided prompts on stdin/stderr and return full argv for the chosen
+/// command. The equivalent non-interactive command is printed before it runs.
+pub fn run_interactive() -> Result<Vec<String>, String> {
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let mut out = io::stderr();
+    let args = prompt_args(&mut input, &mut out)?;
+    eprintln!();
+    eprintln!("Equivalent command:");
+    eprintln!("  {}", shell_command(&args));
+    eprintln!();
+    let mut argv = vec!["git-insights".to_string()];
+    argv.extend(args);
+    Ok(argv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(answers: &str) -> (Result<Vec<String>, String>, String) {
+        let mut input = answers.as_bytes();
+        let mut out = Vec::new();
+        let args = prompt_args(&mut input, &mut out);
+        (args, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_prompt_args_timeline_shipped() {
+        let (args, transcript) = run("2\nx\n12\n2\n\n");
+        assert_eq!(
+            args.unwrap(),
+            vec!["timeline", "--weeks", "12", "--reachable-from", "v*"]
+        );
+        assert!(transcript.contains("Please enter a positive number"));
+    }
+
+    #[test]
+    fn test_prompt_args_defaults_and_export() {
+        assert_eq!(run("\n\n").0.unwrap(), vec!["stats"]);
+        assert_eq!(
+            run("5\n3\n-\n").0.unwrap(),
+            vec!["report", "--only-me", "--output", "-"]
+        );
+        assert_eq!(run("6\n9\n4\n\n").0.unwrap(), vec!["json", "--exclude-me"]);
+        assert!(run("3\n").0.is_err());
+    }
+
+    #[test]
+    fn test_shell_command_roundtrips_through_cli() {
+        let args = vec![
+            "heatmap".to_string(),
+            "--output".to_string(),
+            "my map.svg".to_string(),
+            "--reachable-from".to_string(),
+            "v*".to_string(),
+        ];
+        assert_eq!(
+            shell_command(&args),
+            "git-insights heatmap --output 'my map.svg' --reachable-from 'v*'"
+        );
+        let mut argv = vec!["git-insights".to_string()];
+        argv.extend(args);
+        let cli = crate::cli::Cli::parse_from_args(argv).expect("parse");
+        assert_eq!(cli.filter.reachable_from.as_deref(), Some("v*"));
+    }
+}
@@ -0,0 +1,253 @@
+use crate::error::GitInsightsError;
+use crate::merges::{blame_line_authors, run_blame_head, MergeResolver};
+use crate::output::{finish_progress, print_progress, truncate};
+use crate::stats::tracked_text_files_head;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Instant;
+
+/// Bucket for files whose extension is not in [`EXTENSIONS`].
+pub const OTHER_LANGUAGE: &str = "Other";
+
+/// Built-in extension -> language map (lowercase extensions, no dot).
+pub const EXTENSIONS: &[(&str, &str)] = &[
+    ("rs", "Rust"),
+    ("py", "Python"),
+    ("pyi", "Python"),
+    ("js", "JavaScript"),
+    ("mjs", "JavaScript"),
+    ("cjs", "JavaScript"),
+    ("jsx", "JavaScript"),
+    ("ts", "TypeScript"),
+    ("tsx", "TypeScript"),
+    ("go", "Go"),
+    ("java", "Java"),
+    ("kt", "Kotlin"),
+    ("kts", "Kotlin"),
+    ("scala", "Scala"),
+    ("swift", "Swift"),
+    ("c", "C"),
+    ("h", "C"),
+    ("cc", "C++"),
+    ("cpp", "C++"),
+    ("cxx", "C++"),
+    ("hh", "C++"),
+    ("hpp", "C++"),
+    ("cs", "C#"),
+    ("rb", "Ruby"),
+    ("php", "PHP"),
+    ("pl", "Perl"),
+    ("lua", "Lua"),
+    ("r", "R"),
+    ("jl", "Julia"),
+    ("hs", "Haskell"),
+    ("ex", "Elixir"),
+    ("exs", "Elixir"),
+    ("erl", "Erlang"),
+    ("clj", "Clojure"),
+    ("dart", "Dart"),
+    ("zig", "Zig"),
+    ("sh", "Shell"),
+    ("bash", "Shell"),
+    ("zsh", "Shell"),
+    ("ps1", "PowerShell"),
+    ("sql", "SQL"),
+    ("html", "HTML"),
+    ("htm", "HTML"),
+    ("css", "CSS"),
+    ("scss", "CSS"),
+    ("sass", "CSS"),
+    ("vue", "Vue"),
+    ("svelte", "Svelte"),
+    ("md", "Markdown"),
+    ("markdown", "Markdown"),
+    ("rst", "reStructuredText"),
+    ("txt", "Text"),
+    ("json", "JSON"),
+    ("yml", "YAML"),
+    ("yaml", "YAML"),
+    ("toml", "TOML"),
+    ("ini", "INI"),
+    ("cfg", "INI"),
+    ("xml", "XML"),
+    ("proto", "Protocol Buffers"),
+    ("tf", "Terraform"),
+    ("nix", "Nix"),
+    ("cmake", "CMake"),
+    ("mk", "Makefile"),
+];
+
+/// Language of a path by extension, with a few well-known file names.
+pub fn language_for_path(path: &str) -> &'static str {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    match name {
+        "Makefile" | "GNUmakefile" | "makefile" => return "Makefile",
+        "Dockerfile" | "Containerfile" => return "Dockerfile",
+        "CMakeLists.txt" => return "CMake",
+        _ => {}
+    }
+    let Some((stem, ext)) = name.rsplit_once('.') else {
+        return OTHER_LANGUAGE;
+    };
+    if stem.is_empty() {
+        return OTHER_LANGUAGE;
+    }
+    let ext = ext.to_ascii_lowercase();
+    EXTENSIONS
+        .iter()
+        .find(|(e, _)| *e == ext)
+        .map(|(_, lang)| *lang)
+        .unwrap_or(OTHER_LANGUAGE)
+}
+
+/// Surviving lines and files for one language.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LanguageStats {
+    pub files: usize,
+    pub loc: usize,
+    /// Surviving lines per author.
+    pub authors: HashMap<String, usize>,
+}
+
+/// Per-language totals keyed by language name.
+pub type LanguageMap = BTreeMap<&'static str, LanguageStats>;
+
+/// Add one blamed file's per-line authors to `map`.
+pub fn tally_file(map: &mut LanguageMap, path: &str, line_authors: Vec<String>) {
+    let entry = map.entry(language_for_path(path)).or_default();
+    entry.files += 1;
+    entry.loc += line_authors.len();
+    for author in line_authors {
+        *entry.authors.entry(author).or_insert(0) += 1;
+    }
+}
+
+/// Blame every text file at HEAD and group surviving lines by language.
+pub fn gather_languages() -> Result<LanguageMap, GitInsightsError> {
+    let files = tracked_text_files_head()?;
+    let authors = crate::filter::author_matcher()?;
+    let merges = MergeResolver::load()?;
+    let names = crate::identity::active_normalization();
+    let mut map = LanguageMap::new();
+    let total = files.len();
+    let start = Instant::now();
+    for (n, file) in files.iter().enumerate() {
+        print_progress(n + 1, total, start);
+        let Ok(blame) = run_blame_head(file) else {
+            continue;
+        };
+        let line_authors: Vec<String> = blame_line_authors(&blame, file, &merges)
+            .into_iter()
+            .filter(|(name, mail)| authors.allows(name, mail))
+            .map(|(name, _)| names.apply(name))
+            .collect();
+        tally_file(&mut map, file, line_authors);
+    }
+    if total > 0 {
+        finish_progress();
+    }
+    Ok(map)
+}
+
+/// Print the language table, then each author's top `top` languages.
+pub fn run_languages(top: usize) -> Result<(), GitInsightsError> {
+    let map = gather_languages()?;
+    let total_loc: usize = map.values().map(|s| s.loc).sum();
+    let mut langs: Vec<(&&str, &LanguageStats)> = map.iter().collect();
+    langs.sort_by(|a, b| b.1.loc.cmp(&a.1.loc).then_with(|| a.0.cmp(b.0)));
+
+    println!(
+        "| {:<20} | {:>6} | {:>8} | {:>6} | {:>7} |",
+        "language", "files", "loc", "loc %", "authors"
+    );
+    println!(
+        "|:{:-<21}|{:->8}|{:->10}|{:->8}|{:->9}|",
+        "", "", "", "", ""
+    );
+    for (lang, s) in &langs {
+        println!(
+            "| {:<20} | {:>6} | {:>8} | {:>6.1} | {:>7} |",
+            truncate(lang, 20),
+            s.files,
+            s.loc,
+            s.loc as f64 * 100.0 / total_loc.max(1) as f64,
+            s.authors.len()
+        );
+    }
+
+    let mut per_author: HashMap<&str, Vec<(&str, usize)>> = HashMap::new();
+    for (lang, s) in &langs {
+        for (author, loc) in &s.authors {
+            per_author.entry(author).or_default().push((lang, *loc));
+        }
+    }
+    let mut authors: Vec<(&str, Vec<(&str, usize)>)> = per_author.into_iter().collect();
+    authors.sort_by_key(|(a, l)| (std::cmp::Reverse(l.iter().map(|x| x.1).sum::<usize>()), *a));
+
+    println!();
+    println!("| {:<28} | {:>8} | {:<50} |", "author", "loc", "languages");
+    println!("|:{:-<29}|{:->10}|:{:-<51}|", "", "", "");
+    for (author, mut langs) in authors {
+        let loc: usize = langs.iter().map(|x| x.1).sum();
+        langs.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        let summary = langs
+            .iter()
+            .take(top)
+            .map(|(lang, n)| format!("{} {:.0}%", lang, *n as f64 * 100.0 / loc.max(1) as f64))
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!(
+            "| {:<28} | {:>8} | {:<50} |",
+            truncate(author, 28),
+            loc,
+            truncate(&summary, 50)
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_for_path() {
+        assert_eq!(language_for_path("src/main.rs"), "Rust");
+        assert_eq!(language_for_path(".github/workflows/ci.YML"), "YAML");
+        assert_eq!(language_for_path("docker/Dockerfile"), "Dockerfile");
+        assert_eq!(language_for_path("CMakeLists.txt"), "CMake");
+        assert_eq!(language_for_path(".gitignore"), OTHER_LANGUAGE);
+        assert_eq!(language_for_path("LICENSE"), OTHER_LANGUAGE);
+    }
+
+    #[test]
+    fn test_gather_languages_e2e() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        repo.commit_with_epoch("Ann", "ann@x.io", "src/lib.rs", "fn a() {}", 1_700_000_000)
+            .expect("commit");
+        repo.commit_with_epoch("Ann", "ann@x.io", "src/lib.rs", "fn b() {}", 1_700_000_100)
+            .expect("commit");
+        repo.commit_with_epoch("Bob", "bob@x.io", "ci.yml", "on: push", 1_700_000_200)
+            .expect("commit");
+        repo.commit_with_epoch(
+            "Bob",
+            "bob@x.io",
+            "src/main.rs",
+            "fn main() {}",
+            1_700_000_300,
+        )
+        .expect("commit");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        let map = gather_languages();
+        std::env::set_current_dir(old).ok();
+
+        let map = map.expect("languages");
+        let rust = &map["Rust"];
+        assert_eq!((rust.files, rust.loc), (2, 3));
+        assert_eq!(rust.authors["Ann"], 2);
+        assert_eq!(rust.authors["Bob"], 1);
+        assert_eq!(map["YAML"].authors["Bob"], 1);
+    }
+}
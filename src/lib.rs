@@ -1,11 +1,64 @@
+pub mod app;
+pub mod badge;
+pub mod branches;
+pub mod bursts;
+pub mod capacity;
+pub mod churn;
+pub mod classes;
 pub mod cli;
 pub mod code_frequency;
+pub mod codeowners;
+pub mod commits;
+pub mod compare;
+pub mod config;
+pub mod contributors;
+pub mod daemon;
+pub mod encoding;
+pub mod error;
+pub mod filter;
 pub mod git;
+pub mod github;
+pub mod growth;
+pub mod hotspots;
+pub mod identity;
+pub mod interactive;
+pub mod json;
+pub mod languages;
+pub mod log;
+pub mod man;
+pub mod matrix;
+pub mod merges;
+pub mod messages;
 pub mod output;
+pub mod owners;
+pub mod ownership_history;
+pub mod palette;
+pub mod prelude;
+pub mod prometheus;
+pub mod quality;
+pub mod releases;
+pub mod report;
+pub mod risk;
+pub mod serve;
+pub mod signing;
+pub mod snapshot;
 pub mod stats;
+pub mod submodules;
+pub mod survival;
+pub mod svg;
+pub mod tenure;
 pub mod test_repo;
 pub mod test_sync;
+pub mod time_audit;
+pub mod top;
+pub mod velocity;
+pub mod view;
 pub mod visualize;
+pub mod watch;
+pub mod xlsx;
+
+#[cfg(feature = "charts")]
+pub mod png;
 
 #[cfg(feature = "python")]
 pub mod py;
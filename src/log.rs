@@ -0,0 +1,81 @@
+//! Diagnostics on stderr, so stdout stays clean for piping.
+//!
+//! `--quiet` drops progress bars and notes, `--verbose` adds every git command
+//! run, timings and snapshot reuse. Warnings are always shown.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Instant;
+
+/// How much goes to stderr (`--quiet`, `--verbose`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum Verbosity {
+    /// Warnings only.
+    Quiet,
+    /// Progress and notes.
+    #[default]
+    Normal,
+    /// Also git commands, timings and cache use.
+    Verbose,
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+/// Select how chatty subsequent analyses are on stderr.
+pub fn set_verbosity(level: Verbosity) {
+    VERBOSITY.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn verbosity() -> Verbosity {
+    match VERBOSITY.load(Ordering::Relaxed) {
+        0 => Verbosity::Quiet,
+        2 => Verbosity::Verbose,
+        _ => Verbosity::Normal,
+    }
+}
+
+/// Whether progress bars are drawn (not with `--quiet`).
+pub fn show_progress() -> bool {
+    verbosity() >= Verbosity::Normal
+}
+
+/// A note for the user, e.g. what an approximation means.
+pub fn info(args: fmt::Arguments) {
+    if verbosity() >= Verbosity::Normal {
+        eprintln!("{}", args);
+    }
+}
+
+/// Something went wrong but the analysis continues.
+pub fn warn(args: fmt::Arguments) {
+    eprintln!("Warning: {}", args);
+}
+
+/// Details for `--verbose`.
+pub fn debug(args: fmt::Arguments) {
+    if verbosity() >= Verbosity::Verbose {
+        let mut err = io::stderr().lock();
+        let _ = writeln!(err, "[debug] {}", args);
+    }
+}
+
+/// A finished git command and how long it took, for `--verbose`.
+pub(crate) fn git_command(args: &[&str], start: Instant) {
+    if verbosity() >= Verbosity::Verbose {
+        debug(format_args!(
+            "git {} ({:.1?})",
+            args.join(" "),
+            start.elapsed()
+        ));
+    }
+}
+
+/// Run `f`, reporting how long `what` took with `--verbose`.
+pub fn timed<T>(what: &str, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let value = f();
+    debug(format_args!("{} took {:.2?}", what, start.elapsed()));
+    value
+}
@@ -0,0 +1,278 @@
+//! roff manual pages generated from the `--help` texts, so the two never
+//! drift apart.
+
+use crate::cli::{help_topic, render_help, HelpTopic};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Escape text for roff: backslashes, hyphens and control characters at the
+/// start of a line.
+fn escape(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        format!("\\&{}", escaped)
+    } else {
+        escaped
+    }
+}
+
+/// `USAGE:`-style headings of the help texts.
+fn is_heading(line: &str) -> bool {
+    line.strip_suffix(':')
+        .is_some_and(|h| !h.is_empty() && h.chars().all(|c| c.is_ascii_uppercase() || c == ' '))
+}
+
+/// The help text split into the description and `(heading, lines)` sections.
+fn split_sections(help: &str) -> (Vec<&str>, Vec<(&str, Vec<&str>)>) {
+    let mut lines = help.lines().skip(1);
+    let mut description = Vec::new();
+    let mut sections: Vec<(&str, Vec<&str>)> = Vec::new();
+    for line in lines.by_ref() {
+        if is_heading(line) {
+            sections.push((line.trim_end_matches(':'), Vec::new()));
+            break;
+        }
+        description.push(line);
+    }
+    for line in lines {
+        if is_heading(line) {
+            sections.push((line.trim_end_matches(':'), Vec::new()));
+        } else if let Some((_, body)) = sections.last_mut() {
+            body.push(line);
+        }
+    }
+    (description, sections)
+}
+
+/// `  --term  description` lines (continuations indented deeper) as `.TP` items.
+fn render_items(out: &mut String, lines: &[&str]) {
+    for line in lines {
+        let text = line.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let is_term = line.starts_with("  ") && !line[2..].starts_with(' ');
+        if is_term {
+            let (term, desc) = match text.split_once("  ") {
+                Some((term, desc)) => (term, desc.trim()),
+                None => (text, ""),
+            };
+            out.push_str(".TP\n");
+            out.push_str(&format!("\\fB{}\\fR\n", escape(term)));
+            if !desc.is_empty() {
+                out.push_str(&escape(desc));
+                out.push('\n');
+            }
+        } else {
+            out.push_str(&escape(text));
+            out.push('\n');
+        }
+    }
+}
+
+/// Verbatim lines (usage, examples), without the help's two-space indent.
+fn render_literal(out: &mut String, lines: &[&str]) {
+    out.push_str(".nf\n");
+    for line in lines.iter().filter(|l| !l.trim().is_empty()) {
+        out.push_str(&escape(line.strip_prefix("  ").unwrap_or(line)));
+        out.push('\n');
+    }
+    out.push_str(".fi\n");
+}
+
+/// Prose with `- ` bullets; blank lines start new paragraphs.
+fn render_prose(out: &mut String, lines: &[&str]) {
+    let (mut started, mut paragraph) = (false, false);
+    for line in lines {
+        let text = line.trim();
+        if text.is_empty() {
+            paragraph = started;
+            continue;
+        }
+        if paragraph {
+            out.push_str(".PP\n");
+            paragraph = false;
+        }
+        if let Some(item) = text.strip_prefix("- ") {
+            out.push_str(".IP \\(bu 2\n");
+            out.push_str(&escape(item));
+        } else {
+            out.push_str(&escape(text));
+        }
+        out.push('\n');
+        started = true;
+    }
+}
+
+/// One-line summaries of every command from the top-level COMMANDS section.
+fn command_summaries() -> Vec<(String, String)> {
+    let help = render_help(HelpTopic::Top);
+    let (_, sections) = split_sections(&help);
+    let Some((_, lines)) = sections.iter().find(|(h, _)| *h == "COMMANDS") else {
+        return Vec::new();
+    };
+    let mut summaries: Vec<(String, String)> = Vec::new();
+    for line in lines {
+        let text = line.trim();
+        if line.starts_with("  ") && !line[2..].starts_with(' ') {
+            let (term, desc) = text.split_once("  ").unwrap_or((text, ""));
+            let name = term.split_whitespace().next().unwrap_or(term);
+            summaries.push((name.to_string(), desc.trim().to_string()));
+        } else if let Some((_, desc)) = summaries.last_mut() {
+            if desc.is_empty() {
+                *desc = text.to_string();
+            }
+        }
+    }
+    summaries
+}
+
+/// Commands that have their own page (everything with a help topic).
+pub fn man_commands() -> Vec<String> {
+    command_summaries()
+        .into_iter()
+        .map(|(name, _)| name)
+        .filter(|name| help_topic(name).is_some())
+        .collect()
+}
+
+/// File name of the page for `command` (`None` for the main page).
+pub fn man_file_name(command: Option<&str>) -> String {
+    match command {
+        Some(c) => format!("git-insights-{}.1", c),
+        None => "git-insights.1".to_string(),
+    }
+}
+
+/// The roff page for `command`, or for the tool itself when `None`.
+pub fn render_man(command: Option<&str>) -> Result<String, String> {
+    let (topic, page, summary) = match command {
+        Some(c) => {
+            let topic = help_topic(c).ok_or_else(|| format!("no manual page for '{}'", c))?;
+            let summary = command_summaries()
+                .into_iter()
+                .find(|(name, _)| name == c)
+                .map(|(_, desc)| desc)
+                .unwrap_or_default();
+            (topic, format!("git-insights-{}", c), summary)
+        }
+        None => (
+            HelpTopic::Top,
+            "git-insights".to_string(),
+            "Git repository stats and insights".to_string(),
+        ),
+    };
+    let help = render_help(topic);
+    let (description, sections) = split_sections(&help);
+
+    let mut out = format!(
+        ".TH \"{}\" \"1\" \"\" \"git-insights {}\" \"git-insights manual\"\n",
+        page.to_uppercase(),
+        env!("CARGO_PKG_VERSION")
+    );
+    out.push_str(".SH NAME\n");
+    out.push_str(&format!("{} \\- {}\n", escape(&page), escape(&summary)));
+    for (heading, lines) in &sections {
+        match *heading {
+            "USAGE" => {
+                out.push_str(".SH SYNOPSIS\n");
+                render_literal(&mut out, lines);
+                if !description.iter().all(|l| l.trim().is_empty()) {
+                    out.push_str(".SH DESCRIPTION\n");
+                    render_prose(&mut out, &description);
+                }
+            }
+            "EXAMPLES" => {
+                out.push_str(".SH EXAMPLES\n");
+                render_literal(&mut out, lines);
+            }
+            _ => {
+                out.push_str(&format!(".SH \"{}\"\n", heading));
+                if lines.iter().any(|l| l.starts_with("  ")) {
+                    render_items(&mut out, lines);
+                } else {
+                    render_prose(&mut out, lines);
+                }
+            }
+        }
+    }
+    out.push_str(".SH \"SEE ALSO\"\n");
+    let others: Vec<String> = match command {
+        Some(_) => vec!["\\fBgit\\-insights\\fR(1)".to_string()],
+        None => man_commands()
+            .iter()
+            .map(|c| format!("\\fBgit\\-insights\\-{}\\fR(1)", escape(c)))
+            .collect(),
+    };
+    out.push_str(&others.join(",\n"));
+    out.push('\n');
+    Ok(out)
+}
+
+/// Write the main page and one page per command into `dir`; returns the files.
+pub fn write_man_pages(dir: &str) -> Result<Vec<PathBuf>, String> {
+    let dir = Path::new(dir);
+    fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let mut pages: Vec<Option<String>> = vec![None];
+    pages.extend(man_commands().into_iter().map(Some));
+    let mut written = Vec::new();
+    for command in pages {
+        let path = dir.join(man_file_name(command.as_deref()));
+        let page = render_man(command.as_deref())?;
+        fs::write(&path, page).map_err(|e| format!("{}: {}", path.display(), e))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Print one page, or write all of them with `--dir`.
+pub fn run_man(command: Option<&str>, dir: Option<&str>) -> Result<(), String> {
+    match dir {
+        Some(dir) => {
+            let written = write_man_pages(dir)?;
+            println!("Wrote {} manual pages to {}", written.len(), dir);
+        }
+        None => print!("{}", render_man(command)?),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_man_command_page() {
+        let page = render_man(Some("timeline")).expect("page");
+        assert!(page.starts_with(".TH \"GIT-INSIGHTS-TIMELINE\" \"1\""));
+        assert!(page.contains(
+            ".SH NAME\ngit\\-insights\\-timeline \\- Show weekly commit activity as ASCII/Unicode sparkline\n"
+        ));
+        assert!(page.contains(".SH SYNOPSIS\n.nf\ngit\\-insights timeline [\\-\\-weeks N"));
+        assert!(page.contains(".TP\n\\fB\\-\\-height N\\fR\nRows in the chart (default: 7)\n"));
+        // A term on its own line takes the indented lines below it.
+        assert!(page.contains(".TP\n\\fB\\-\\-bursts FACTOR\\fR\nMark weeks"));
+        assert!(page.contains(".SH EXAMPLES\n.nf\ngit\\-insights timeline\n"));
+        assert!(render_man(Some("nope")).is_err());
+    }
+
+    #[test]
+    fn test_man_pages_cover_every_command() {
+        let commands = man_commands();
+        assert!(commands.contains(&"stats".to_string()));
+        assert!(commands.contains(&"compare".to_string()));
+        assert!(!commands.contains(&"help".to_string()));
+        let main = render_man(None).expect("main page");
+        assert!(main.contains(".SH \"GLOBAL OPTIONS\"\n"));
+        for c in &commands {
+            assert!(main.contains(&format!("\\fBgit\\-insights\\-{}\\fR(1)", escape(c))));
+            assert!(render_man(Some(c)).is_ok(), "{}", c);
+        }
+
+        let dir = std::env::temp_dir().join(format!("gi-man-{}", std::process::id()));
+        let written = write_man_pages(dir.to_str().unwrap()).expect("write");
+        assert_eq!(written.len(), commands.len() + 1);
+        assert!(dir.join("git-insights-stats.1").is_file());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
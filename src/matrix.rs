@@ -0,0 +1,237 @@
+//! Ownership matrix: for every author, the files and directories they
+//! significantly own at HEAD, as a document for handover packets.
+
+use crate::error::GitInsightsError;
+use crate::json;
+use crate::output::write_export;
+use crate::snapshot::{blame_head_files, FileAuthors};
+use std::collections::{BTreeMap, HashMap};
+
+/// Default share of a path's surviving lines an author needs to own it.
+pub const DEFAULT_MIN_PCT: f64 = 30.0;
+
+/// A file, or a directory (path ending in `/`), owned by one author.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OwnedEntry {
+    pub path: String,
+    /// Lines in `path` (recursively, for directories) written by the author.
+    pub lines: usize,
+    /// All surviving lines in `path`.
+    pub total: usize,
+}
+
+impl OwnedEntry {
+    pub fn is_dir(&self) -> bool {
+        self.path.ends_with('/')
+    }
+
+    pub fn pct(&self) -> f64 {
+        self.lines as f64 * 100.0 / self.total.max(1) as f64
+    }
+}
+
+/// One author's surviving lines and the paths they own, in path order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OwnerSection {
+    pub author: String,
+    pub lines: usize,
+    pub entries: Vec<OwnedEntry>,
+}
+
+/// Directories containing `file`, outermost first (`a/`, `a/b/`).
+fn parent_dirs(file: &str) -> impl Iterator<Item = &str> {
+    file.match_indices('/').map(move |(i, _)| &file[..=i])
+}
+
+/// Every author with surviving lines (most lines first, ties by name) and the
+/// files and directories where they wrote at least `min_pct`% of the lines.
+pub fn ownership_matrix(files: &FileAuthors, min_pct: f64) -> Vec<OwnerSection> {
+    // Path -> (total lines, lines per author); directories aggregate their files.
+    let mut paths: BTreeMap<String, (usize, HashMap<&str, usize>)> = BTreeMap::new();
+    for (file, authors) in files {
+        let file_total: usize = authors.values().sum();
+        if file_total == 0 {
+            continue;
+        }
+        let targets = std::iter::once(file.as_str()).chain(parent_dirs(file));
+        for path in targets {
+            let entry = paths.entry(path.to_string()).or_default();
+            entry.0 += file_total;
+            for (author, &lines) in authors {
+                *entry.1.entry(author).or_insert(0) += lines;
+            }
+        }
+    }
+
+    let mut sections: BTreeMap<&str, OwnerSection> = BTreeMap::new();
+    for authors in files.values() {
+        for (author, &lines) in authors {
+            sections
+                .entry(author)
+                .or_insert_with(|| OwnerSection {
+                    author: author.clone(),
+                    lines: 0,
+                    entries: Vec::new(),
+                })
+                .lines += lines;
+        }
+    }
+    for (path, (total, by_author)) in &paths {
+        for (author, &lines) in by_author {
+            let entry = OwnedEntry {
+                path: path.clone(),
+                lines,
+                total: *total,
+            };
+            if lines > 0 && entry.pct() >= min_pct {
+                if let Some(section) = sections.get_mut(author) {
+                    section.entries.push(entry);
+                }
+            }
+        }
+    }
+    let mut sections: Vec<OwnerSection> = sections.into_values().filter(|s| s.lines > 0).collect();
+    sections.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.author.cmp(&b.author)));
+    sections
+}
+
+/// Nesting depth of each entry: the number of owned directories above it.
+fn entry_depths(entries: &[OwnedEntry]) -> Vec<usize> {
+    let mut open: Vec<&str> = Vec::new();
+    entries
+        .iter()
+        .map(|e| {
+            while open.last().is_some_and(|dir| !e.path.starts_with(dir)) {
+                open.pop();
+            }
+            let depth = open.len();
+            if e.is_dir() {
+                open.push(&e.path);
+            }
+            depth
+        })
+        .collect()
+}
+
+/// One `##` section per author with a nested list of owned paths.
+pub fn render_matrix_markdown(sections: &[OwnerSection], min_pct: f64) -> String {
+    let mut out = String::from("# Ownership matrix\n\n");
+    out.push_str(&format!(
+        "Files and directories (ending in `/`) where each author wrote at least {}% of the surviving lines at HEAD.\n",
+        min_pct
+    ));
+    for s in sections {
+        out.push_str(&format!("\n## {} ({} lines)\n\n", s.author, s.lines));
+        if s.entries.is_empty() {
+            out.push_str(&format!("- _no path at or above {}%_\n", min_pct));
+            continue;
+        }
+        for (e, depth) in s.entries.iter().zip(entry_depths(&s.entries)) {
+            out.push_str(&format!(
+                "{}- `{}` {:.1}% ({}/{} lines)\n",
+                "  ".repeat(depth),
+                e.path,
+                e.pct(),
+                e.lines,
+                e.total
+            ));
+        }
+    }
+    out
+}
+
+/// `[{"author": ..., "lines": N, "owns": [{"path": ..., "type": "dir"|"file", ...}]}]`
+pub fn render_matrix_json(sections: &[OwnerSection]) -> String {
+    let items: Vec<String> = sections
+        .iter()
+        .map(|s| {
+            let owns: Vec<String> = s
+                .entries
+                .iter()
+                .map(|e| {
+                    format!(
+                        "{{\"path\": \"{}\", \"type\": \"{}\", \"lines\": {}, \"total\": {}, \"pct\": {:.1}}}",
+                        json::escape(&e.path),
+                        if e.is_dir() { "dir" } else { "file" },
+                        e.lines,
+                        e.total,
+                        e.pct()
+                    )
+                })
+                .collect();
+            format!(
+                "{{\"author\": \"{}\", \"lines\": {}, \"owns\": [{}]}}",
+                json::escape(&s.author),
+                s.lines,
+                owns.join(", ")
+            )
+        })
+        .collect();
+    format!("[{}]\n", items.join(", "))
+}
+
+/// Blame HEAD and write the matrix as markdown (or JSON) to `output` (stdout
+/// when `None`).
+pub fn run_ownership_matrix(
+    min_pct: f64,
+    as_json: bool,
+    by_name: bool,
+    output: Option<&str>,
+) -> Result<(), GitInsightsError> {
+    let sections = ownership_matrix(&blame_head_files(by_name)?, min_pct);
+    let doc = if as_json {
+        render_matrix_json(&sections)
+    } else {
+        render_matrix_markdown(&sections, min_pct)
+    };
+    write_export(output, "-", doc.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ownership_matrix() {
+        let mut files = FileAuthors::new();
+        let file = |pairs: &[(&str, usize)]| -> BTreeMap<String, usize> {
+            pairs.iter().map(|(a, n)| (a.to_string(), *n)).collect()
+        };
+        files.insert("src/a.rs".to_string(), file(&[("Ann", 8), ("Bob", 2)]));
+        files.insert("src/net/b.rs".to_string(), file(&[("Bob", 10)]));
+        files.insert("README".to_string(), file(&[("Ann", 1), ("Cat", 9)]));
+
+        let sections = ownership_matrix(&files, 30.0);
+        let authors: Vec<&str> = sections.iter().map(|s| s.author.as_str()).collect();
+        assert_eq!(authors, vec!["Bob", "Ann", "Cat"]);
+
+        let bob: Vec<&str> = sections[0]
+            .entries
+            .iter()
+            .map(|e| e.path.as_str())
+            .collect();
+        assert_eq!(bob, vec!["src/", "src/net/", "src/net/b.rs"]);
+        assert_eq!(
+            (sections[0].entries[0].lines, sections[0].entries[0].total),
+            (12, 20)
+        );
+        assert_eq!(entry_depths(&sections[0].entries), vec![0, 1, 2]);
+
+        let ann: Vec<&str> = sections[1]
+            .entries
+            .iter()
+            .map(|e| e.path.as_str())
+            .collect();
+        assert_eq!(ann, vec!["src/", "src/a.rs"]);
+
+        let md = render_matrix_markdown(&sections, 30.0);
+        assert!(md.contains("## Bob (12 lines)\n\n- `src/` 60.0% (12/20 lines)\n  - `src/net/`"));
+        assert!(md.contains("## Cat (9 lines)\n\n- `README` 90.0% (9/10 lines)\n"));
+
+        let high = ownership_matrix(&files, 95.0);
+        assert!(render_matrix_markdown(&high, 95.0).contains("## Ann (9 lines)\n\n- _no path"));
+        assert!(render_matrix_json(&high).starts_with(
+            "[{\"author\": \"Bob\", \"lines\": 12, \"owns\": [{\"path\": \"src/net/\", \"type\": \"dir\""
+        ));
+    }
+}
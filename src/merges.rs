@@ -0,0 +1,356 @@
+use crate::error::GitInsightsError;
+use crate::filter::{CommitFilter, MergeLines};
+use crate::git::{run_command, work_tree_root};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Synthetic identity credited with conflict-resolution lines.
+pub const MERGE_AUTHOR_NAME: &str = "(merge)";
+pub const MERGE_AUTHOR_MAIL: &str = "<merge>";
+
+/// Re-attributes blame lines that were introduced by merge commits.
+///
+/// Blame only points at a merge when a line differs from every parent, i.e.
+/// it was written while resolving a conflict (or as an "evil" merge).
+#[derive(Debug, Default)]
+pub struct MergeResolver {
+    mode: MergeLines,
+    merges: HashSet<String>,
+    /// `(merge sha, file)` -> resolved `(name, mail)` for [`MergeLines::Branch`].
+    branch_authors: Mutex<HashMap<(String, String), (String, String)>>,
+}
+
+impl MergeResolver {
+    /// Resolver for the active filter; only lists merges when re-attribution is on.
+    pub fn load() -> Result<MergeResolver, GitInsightsError> {
+        MergeResolver::load_at("HEAD")
+    }
+
+    /// Like [`load`](Self::load) for blames at `rev`.
+    pub fn load_at(rev: &str) -> Result<MergeResolver, GitInsightsError> {
+        let mode = crate::filter::active().merge_lines;
+        if mode == MergeLines::Merger {
+            return Ok(MergeResolver::default());
+        }
+        let out = run_command(&["rev-list", "--merges", rev])?;
+        Ok(MergeResolver::new(
+            mode,
+            out.lines().map(|s| s.trim().to_string()),
+        ))
+    }
+
+    pub fn new(mode: MergeLines, merges: impl IntoIterator<Item = String>) -> MergeResolver {
+        MergeResolver {
+            mode,
+            merges: merges.into_iter().collect(),
+            branch_authors: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Author to credit for a line blamed on `sha` in `file`.
+    pub fn resolve(&self, sha: &str, file: &str, name: &str, mail: &str) -> (String, String) {
+        if self.mode == MergeLines::Merger || !self.merges.contains(sha) {
+            return (name.to_string(), mail.to_string());
+        }
+        if self.mode == MergeLines::Branch {
+            let key = (sha.to_string(), file.to_string());
+            if let Some(found) = self.branch_authors.lock().unwrap().get(&key) {
+                return found.clone();
+            }
+            let found = branch_author(sha, file);
+            self.branch_authors
+                .lock()
+                .unwrap()
+                .insert(key, found.clone());
+            return found;
+        }
+        synthetic()
+    }
+}
+
+fn synthetic() -> (String, String) {
+    (MERGE_AUTHOR_NAME.to_string(), MERGE_AUTHOR_MAIL.to_string())
+}
+
+/// Latest non-merge author of `file` on the side(s) brought in by `merge`,
+/// falling back to the synthetic identity.
+fn branch_author(merge: &str, file: &str) -> (String, String) {
+    let range = format!("{}^1..{}", merge, merge);
+    run_command(&[
+        "--no-pager",
+        "log",
+        "-1",
+        "--no-merges",
+        "--format=%aN%x09%aE",
+        &range,
+        "--",
+        file,
+    ])
+    .ok()
+    .and_then(|out| {
+        let (name, email) = out.trim().split_once('\t')?;
+        Some((name.to_string(), format!("<{}>", email)))
+    })
+    .unwrap_or_else(synthetic)
+}
+
+/// File of commits blame skips by default, at the top of the work tree.
+pub const IGNORE_REVS_FILE: &str = ".git-blame-ignore-revs";
+
+/// The repository's [`IGNORE_REVS_FILE`], if it has one and
+/// `--no-ignore-revs-file` is not given.
+pub fn ignore_revs_file(filter: &CommitFilter) -> Option<PathBuf> {
+    if filter.skip_ignore_revs_file {
+        return None;
+    }
+    let path = work_tree_root()?.join(IGNORE_REVS_FILE);
+    path.is_file().then_some(path)
+}
+
+/// Flags of every blame: the active `--blame-opts` (`-M -C` by default, so
+/// lines keep their author when a file is renamed, or split into new files
+/// in the same commit), then the ignored revisions, so a mass reformat
+/// listed in [`IGNORE_REVS_FILE`] or passed with `--ignore-rev` does not
+/// take over the lines it touched.
+pub fn blame_flags() -> Vec<String> {
+    let filter = crate::filter::active();
+    let mut args: Vec<String> = filter.blame.args().iter().map(|s| s.to_string()).collect();
+    if let Some(path) = ignore_revs_file(&filter) {
+        args.push("--ignore-revs-file".to_string());
+        args.push(path.to_string_lossy().into_owned());
+    }
+    for rev in &filter.ignore_revs {
+        args.push("--ignore-rev".to_string());
+        args.push(rev.clone());
+    }
+    args
+}
+
+/// `git blame --line-porcelain` arguments for `file` at `rev`, with
+/// [`blame_flags`].
+pub fn blame_args(rev: &str, file: &str) -> Vec<String> {
+    let mut args: Vec<String> = ["--no-pager", "blame", "--line-porcelain"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    args.extend(blame_flags());
+    args.extend([rev.to_string(), "--".to_string(), file.to_string()]);
+    args
+}
+
+/// `git blame --line-porcelain` output for `file` at `rev`.
+pub fn run_blame(rev: &str, file: &str) -> Result<String, GitInsightsError> {
+    let args = blame_args(rev, file);
+    run_command(&args.iter().map(String::as_str).collect::<Vec<_>>())
+}
+
+/// `git blame --line-porcelain` output for `file` at HEAD.
+pub fn run_blame_head(file: &str) -> Result<String, GitInsightsError> {
+    run_blame("HEAD", file)
+}
+
+/// First token of a `--line-porcelain` group header, if `line` is one.
+fn porcelain_sha(line: &str) -> Option<&str> {
+    let sha = line.split(' ').next()?;
+    ((sha.len() == 40 || sha.len() == 64) && sha.bytes().all(|b| b.is_ascii_hexdigit()))
+        .then_some(sha)
+}
+
+/// `(name, mail)` per content line of `blame --line-porcelain` output, with
+/// merge-resolution lines re-attributed by `resolver`.
+pub fn blame_line_authors(
+    blame: &str,
+    file: &str,
+    resolver: &MergeResolver,
+) -> Vec<(String, String)> {
+    let mut lines = Vec::new();
+    let mut sha = String::new();
+    let mut name = String::new();
+    let mut mail = String::new();
+    for line in blame.lines() {
+        if line.starts_with('\t') {
+            if !name.is_empty() {
+                lines.push(resolver.resolve(&sha, file, &name, &mail));
+            }
+        } else if let Some(rest) = line.strip_prefix("author ") {
+            name = rest.trim().to_string();
+        } else if let Some(rest) = line.strip_prefix("author-mail ") {
+            mail = rest.trim().to_string();
+        } else if let Some(s) = porcelain_sha(line) {
+            sha = s.to_string();
+        }
+    }
+    lines
+}
+
+/// `author-time` per content line of `blame --line-porcelain` output, in the
+/// same order as [`blame_line_authors`].
+pub fn blame_line_times(blame: &str) -> Vec<u64> {
+    let mut times = Vec::new();
+    let mut time = 0u64;
+    for line in blame.lines() {
+        if let Some(rest) = line.strip_prefix("author-time ") {
+            time = rest.trim().parse().unwrap_or(0);
+        } else if line.starts_with('\t') {
+            times.push(time);
+        }
+    }
+    times
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MERGE: &str = "1111111111111111111111111111111111111111";
+    const PLAIN: &str = "2222222222222222222222222222222222222222";
+
+    fn porcelain() -> String {
+        format!(
+            "{PLAIN} 1 1 1\nauthor Ann\nauthor-mail <ann@x.io>\nsummary a\nfilename f\n\tone\n\
+             {MERGE} 2 2 1\nauthor Max\nauthor-mail <max@x.io>\nprevious {PLAIN} f\nfilename f\n\ttwo\n"
+        )
+    }
+
+    #[test]
+    fn test_blame_line_authors_modes() {
+        let blame = porcelain();
+        let merger = MergeResolver::new(MergeLines::Merger, [MERGE.to_string()]);
+        let lines = blame_line_authors(&blame, "f", &merger);
+        assert_eq!(lines[1].0, "Max");
+
+        let synthetic = MergeResolver::new(MergeLines::Synthetic, [MERGE.to_string()]);
+        let lines = blame_line_authors(&blame, "f", &synthetic);
+        assert_eq!(lines[0], ("Ann".to_string(), "<ann@x.io>".to_string()));
+        assert_eq!(lines[1].0, MERGE_AUTHOR_NAME);
+    }
+
+    #[test]
+    fn test_merge_resolution_lines_e2e() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        let p = repo.path.to_str().unwrap().to_string();
+        let git = |args: &[&str]| {
+            let mut full = vec!["-C", p.as_str()];
+            full.extend_from_slice(args);
+            run_command(&full)
+        };
+        repo.commit_with_epoch("Ann", "ann@x.io", "f.txt", "base", 1_700_000_000)
+            .expect("commit");
+        git(&["checkout", "-q", "-b", "topic"]).expect("branch");
+        repo.commit_with_epoch("Bob", "bob@x.io", "f.txt", "bob", 1_700_000_100)
+            .expect("commit");
+        git(&["checkout", "-q", "main"]).expect("checkout");
+        repo.commit_with_epoch("Ann", "ann@x.io", "f.txt", "ann", 1_700_000_200)
+            .expect("commit");
+        // Conflicting append; resolve with a line neither side wrote.
+        assert!(git(&["merge", "-q", "topic"]).is_err());
+        std::fs::write(repo.path.join("f.txt"), "base\nresolved\n").expect("resolve");
+        git(&["add", "f.txt"]).expect("add");
+        git(&[
+            "-c",
+            "user.name=Max",
+            "-c",
+            "user.email=max@x.io",
+            "commit",
+            "-q",
+            "--no-edit",
+        ])
+        .expect("merge commit");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        let mut results = Vec::new();
+        for mode in [
+            MergeLines::Merger,
+            MergeLines::Synthetic,
+            MergeLines::Branch,
+        ] {
+            crate::filter::set_active(crate::filter::CommitFilter {
+                merge_lines: mode,
+                ..Default::default()
+            });
+            let resolver = MergeResolver::load();
+            let blame = run_command(&["blame", "--line-porcelain", "HEAD", "--", "f.txt"]);
+            results.push(
+                resolver
+                    .and_then(|r| blame.map(|b| blame_line_authors(&b, "f.txt", &r)))
+                    .map(|lines| lines[1].0.clone()),
+            );
+        }
+        crate::filter::set_active(crate::filter::CommitFilter::default());
+        std::env::set_current_dir(old).ok();
+
+        let results: Vec<String> = results.into_iter().map(|r| r.expect("blame")).collect();
+        assert_eq!(results, vec!["Max", MERGE_AUTHOR_NAME, "Bob"]);
+    }
+
+    #[test]
+    fn test_blame_follows_split_files_e2e() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        let lines: Vec<String> = (0..6)
+            .map(|i| format!("fn helper_number_{i}() -> usize {{ {i} * 1000 + 42 }}"))
+            .collect();
+        for (i, line) in lines.iter().enumerate() {
+            repo.commit_with_epoch("Ann", "ann@x.io", "a.rs", line, 1_700_000_000 + i as u64)
+                .expect("commit");
+        }
+        // Bob moves the second half of a.rs into a new file.
+        std::fs::write(repo.path.join("a.rs"), lines[..3].join("\n") + "\n").expect("write");
+        std::fs::write(repo.path.join("b.rs"), lines[3..].join("\n") + "\n").expect("write");
+        repo.commit_with_epoch("Bob", "bob@x.io", "NOTES", "split", 1_700_000_100)
+            .expect("commit");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        let blame = run_blame_head("b.rs");
+        std::env::set_current_dir(old).ok();
+
+        let authors = blame_line_authors(&blame.expect("blame"), "b.rs", &MergeResolver::default());
+        assert_eq!(authors.len(), 3);
+        assert!(authors.iter().all(|(name, _)| name == "Ann"));
+    }
+
+    #[test]
+    fn test_blame_skips_ignored_revs_e2e() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.rs", "fn a(){1}", 1_700_000_000)
+            .expect("commit");
+        // Bob's formatter rewrites Ann's line.
+        std::fs::write(repo.path.join("a.rs"), "fn a() { 1 }\n").expect("write");
+        repo.commit_with_epoch("Bob", "bob@x.io", "NOTES", "fmt", 1_700_000_100)
+            .expect("commit");
+        let git = crate::git::Repo::open(&repo.path).expect("open");
+        let fmt = git.run_command(&["rev-parse", "HEAD"]).expect("rev-parse");
+        let author = |filter: CommitFilter| {
+            crate::filter::set_active(filter);
+            let blame = git.scope(|| run_blame_head("a.rs")).expect("blame");
+            crate::filter::set_active(CommitFilter::default());
+            blame_line_authors(&blame, "a.rs", &MergeResolver::default())[0]
+                .0
+                .clone()
+        };
+        assert_eq!(author(CommitFilter::default()), "Bob");
+        assert_eq!(
+            author(CommitFilter {
+                ignore_revs: vec![fmt.clone()],
+                ..Default::default()
+            }),
+            "Ann"
+        );
+
+        repo.commit_with_epoch("Cat", "cat@x.io", IGNORE_REVS_FILE, &fmt, 1_700_000_200)
+            .expect("commit");
+        assert_eq!(author(CommitFilter::default()), "Ann");
+        assert_eq!(
+            author(CommitFilter {
+                skip_ignore_revs_file: true,
+                ..Default::default()
+            }),
+            "Bob"
+        );
+    }
+}
@@ -0,0 +1,387 @@
+use crate::code_frequency::{build_histogram_table, ymd_from_unix};
+use crate::commits::{collect_commits, Commit};
+use crate::error::GitInsightsError;
+use crate::output::truncate;
+use std::collections::BTreeMap;
+
+/// Conventional-commit types, in display order. Anything else is `other`.
+pub const COMMIT_TYPES: &[&str] = &[
+    "feat", "fix", "docs", "style", "refactor", "perf", "test", "build", "ci", "chore", "revert",
+];
+
+/// Bucket for subjects without a recognised `type(scope)!:` prefix.
+pub const OTHER_TYPE: &str = "other";
+
+/// Conventional-commit type of a subject line (`feat(cli)!: ...` -> `feat`).
+pub fn classify_subject(subject: &str) -> &'static str {
+    let Some((head, _)) = subject.split_once(':') else {
+        return OTHER_TYPE;
+    };
+    let head = head.trim_end_matches('!');
+    let kind = match head.split_once('(') {
+        Some((kind, scope)) if scope.ends_with(')') => kind,
+        Some(_) => return OTHER_TYPE,
+        None => head,
+    };
+    let kind = kind.trim().to_ascii_lowercase();
+    COMMIT_TYPES
+        .iter()
+        .find(|t| **t == kind)
+        .copied()
+        .unwrap_or(OTHER_TYPE)
+}
+
+/// How the breakdown table is grouped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum MessageGroup {
+    Author,
+    Month,
+}
+
+impl MessageGroup {
+    pub fn parse(s: &str) -> Result<MessageGroup, String> {
+        match s {
+            "author" => Ok(MessageGroup::Author),
+            "month" => Ok(MessageGroup::Month),
+            other => Err(format!(
+                "unknown --by '{}'. Expected author or month.",
+                other
+            )),
+        }
+    }
+
+    fn key(self, c: &Commit) -> String {
+        match self {
+            MessageGroup::Author => c.author_name.clone(),
+            MessageGroup::Month => {
+                let (y, m, _) = ymd_from_unix(c.author_time);
+                format!("{:04}-{:02}", y, m)
+            }
+        }
+    }
+}
+
+/// Type counts per group key, plus overall totals.
+#[derive(Debug, Default, PartialEq)]
+pub struct MessageBreakdown {
+    pub rows: BTreeMap<String, BTreeMap<&'static str, usize>>,
+    pub totals: BTreeMap<&'static str, usize>,
+}
+
+impl MessageBreakdown {
+    /// Types with at least one commit, in [`COMMIT_TYPES`] order with `other` last.
+    pub fn types(&self) -> Vec<&'static str> {
+        COMMIT_TYPES
+            .iter()
+            .copied()
+            .chain(std::iter::once(OTHER_TYPE))
+            .filter(|t| self.totals.contains_key(t))
+            .collect()
+    }
+}
+
+/// Classify non-merge commits and count them per group.
+pub fn breakdown(commits: &[Commit], group: MessageGroup) -> MessageBreakdown {
+    let mut out = MessageBreakdown::default();
+    for c in commits.iter().filter(|c| !c.is_merge()) {
+        let kind = classify_subject(&c.subject);
+        *out.rows
+            .entry(group.key(c))
+            .or_default()
+            .entry(kind)
+            .or_insert(0) += 1;
+        *out.totals.entry(kind).or_insert(0) += 1;
+    }
+    out
+}
+
+/// Print the overall type histogram and the per-group table.
+pub fn run_messages(group: MessageGroup) -> Result<(), GitInsightsError> {
+    let commits = collect_commits()?;
+    let b = breakdown(&commits, group);
+    let types = b.types();
+    if types.is_empty() {
+        println!("No commits.");
+        return Ok(());
+    }
+
+    let counts: Vec<usize> = types.iter().map(|t| b.totals[t]).collect();
+    println!("Commit types (non-merge commits):");
+    print!(
+        "{}",
+        build_histogram_table(&types, &counts, Default::default())
+    );
+    println!();
+
+    let label = match group {
+        MessageGroup::Author => "Author",
+        MessageGroup::Month => "Month",
+    };
+    let mut header = format!("| {:<28} |", label);
+    let mut sep = format!("|:{:-<28}|", "");
+    for t in &types {
+        header.push_str(&format!(" {:>8} |", t));
+        sep.push_str(&format!("{:->10}|", ""));
+    }
+    header.push_str(&format!(" {:>7} |", "total"));
+    sep.push_str(&format!("{:->9}|", ""));
+    println!("{}", header);
+    println!("{}", sep);
+
+    let mut rows: Vec<(&String, &BTreeMap<&str, usize>)> = b.rows.iter().collect();
+    if group == MessageGroup::Author {
+        rows.sort_by_key(|(_, c)| std::cmp::Reverse(c.values().sum::<usize>()));
+    }
+    for (key, per_type) in rows {
+        let mut line = format!("| {:<28} |", truncate(key, 28));
+        for t in &types {
+            line.push_str(&format!(" {:>8} |", per_type.get(t).copied().unwrap_or(0)));
+        }
+        line.push_str(&format!(" {:>7} |", per_type.values().sum::<usize>()));
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// Terms listed per table for `--keywords` unless `--top` says otherwise.
+pub const DEFAULT_KEYWORDS: usize = 10;
+
+/// Subjects shorter than this many characters count as short (`--under`).
+pub const DEFAULT_SHORT_SUBJECT: usize = 50;
+
+/// Words too common to say anything about a change.
+pub const STOPWORDS: &[&str] = &[
+    "a", "about", "after", "all", "also", "an", "and", "any", "are", "as", "at", "be", "been",
+    "before", "but", "by", "can", "do", "does", "don't", "for", "from", "has", "have", "if", "in",
+    "into", "is", "it", "it's", "its", "more", "no", "not", "now", "of", "on", "only", "or",
+    "other", "out", "over", "so", "some", "than", "that", "the", "their", "then", "there", "these",
+    "this", "to", "too", "up", "use", "via", "was", "we", "were", "what", "when", "which", "while",
+    "will", "with", "without", "would", "you",
+];
+
+/// Lowercased words of a subject without its conventional-commit prefix,
+/// stopwords, numbers and one-letter words; each word once, in order.
+pub fn subject_terms(subject: &str) -> Vec<String> {
+    let text = match subject.split_once(':') {
+        Some((_, rest)) if classify_subject(subject) != OTHER_TYPE => rest,
+        _ => subject,
+    };
+    let mut terms: Vec<String> = Vec::new();
+    for word in text.split(|c: char| !(c.is_alphanumeric() || c == '\'' || c == '_' || c == '-')) {
+        let word = word
+            .trim_matches(|c: char| !c.is_alphanumeric())
+            .to_lowercase();
+        if word.chars().count() < 2
+            || word.chars().all(|c| c.is_ascii_digit())
+            || STOPWORDS.contains(&word.as_str())
+            || terms.contains(&word)
+        {
+            continue;
+        }
+        terms.push(word);
+    }
+    terms
+}
+
+/// Subject counts, lengths and term frequencies of one group of commits.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SubjectStats {
+    pub subjects: usize,
+    /// Characters over all subjects.
+    pub chars: usize,
+    /// Subjects shorter than the `--under` limit.
+    pub short: usize,
+    /// Subjects each term appears in.
+    pub terms: BTreeMap<String, usize>,
+}
+
+impl SubjectStats {
+    fn add(&mut self, subject: &str, under: usize) {
+        let len = subject.chars().count();
+        self.subjects += 1;
+        self.chars += len;
+        if len < under {
+            self.short += 1;
+        }
+        for term in subject_terms(subject) {
+            *self.terms.entry(term).or_insert(0) += 1;
+        }
+    }
+
+    pub fn average_length(&self) -> f64 {
+        self.chars as f64 / self.subjects.max(1) as f64
+    }
+
+    pub fn short_pct(&self) -> f64 {
+        self.short as f64 * 100.0 / self.subjects.max(1) as f64
+    }
+
+    /// The `top` terms in the most subjects, ties A to Z.
+    pub fn top_terms(&self, top: usize) -> Vec<(&str, usize)> {
+        let mut terms: Vec<(&str, usize)> =
+            self.terms.iter().map(|(t, n)| (t.as_str(), *n)).collect();
+        terms.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        terms.truncate(top);
+        terms
+    }
+}
+
+/// [`SubjectStats`] of all non-merge commits and per group key.
+pub fn keyword_stats(
+    commits: &[Commit],
+    group: MessageGroup,
+    under: usize,
+) -> (SubjectStats, BTreeMap<String, SubjectStats>) {
+    let mut overall = SubjectStats::default();
+    let mut groups: BTreeMap<String, SubjectStats> = BTreeMap::new();
+    for c in commits.iter().filter(|c| !c.is_merge()) {
+        overall.add(&c.subject, under);
+        groups
+            .entry(group.key(c))
+            .or_default()
+            .add(&c.subject, under);
+    }
+    (overall, groups)
+}
+
+/// `messages --keywords`: top subject terms overall and per group, with
+/// subject length figures.
+pub fn run_message_keywords(
+    group: MessageGroup,
+    top: usize,
+    under: usize,
+) -> Result<(), GitInsightsError> {
+    let commits = collect_commits()?;
+    let (overall, groups) = keyword_stats(&commits, group, under);
+    if overall.subjects == 0 {
+        println!("No commits.");
+        return Ok(());
+    }
+    println!(
+        "Subjects: {} non-merge commits, {:.1} characters on average, {:.1}% under {} characters",
+        overall.subjects,
+        overall.average_length(),
+        overall.short_pct(),
+        under
+    );
+    println!();
+    println!("| {:<28} | {:>8} | {:>6} |", "term", "subjects", "share");
+    println!("|:{:-<29}|{:->10}|{:->8}|", "", "", "");
+    for (term, n) in overall.top_terms(top) {
+        println!(
+            "| {:<28} | {:>8} | {:>5.1}% |",
+            truncate(term, 28),
+            n,
+            n as f64 * 100.0 / overall.subjects as f64
+        );
+    }
+
+    let label = match group {
+        MessageGroup::Author => "author",
+        MessageGroup::Month => "month",
+    };
+    let mut rows: Vec<(&String, &SubjectStats)> = groups.iter().collect();
+    if group == MessageGroup::Author {
+        rows.sort_by_key(|(_, s)| std::cmp::Reverse(s.subjects));
+    }
+    println!();
+    println!(
+        "| {:<28} | {:>8} | {:>7} | {:>7} | {:<50} |",
+        label, "subjects", "avg len", "short %", "top terms"
+    );
+    println!(
+        "|:{:-<29}|{:->10}|{:->9}|{:->9}|:{:-<51}|",
+        "", "", "", "", ""
+    );
+    for (key, s) in rows {
+        let terms = s
+            .top_terms(top)
+            .iter()
+            .map(|(t, n)| format!("{} ({})", t, n))
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!(
+            "| {:<28} | {:>8} | {:>7.1} | {:>7.1} | {:<50} |",
+            truncate(key, 28),
+            s.subjects,
+            s.average_length(),
+            s.short_pct(),
+            truncate(&terms, 50)
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_subject() {
+        assert_eq!(classify_subject("feat: add x"), "feat");
+        assert_eq!(classify_subject("fix(cli)!: parse flags"), "fix");
+        assert_eq!(classify_subject("Docs(readme): typo"), "docs");
+        assert_eq!(classify_subject("feat!: breaking"), "feat");
+        assert_eq!(classify_subject("Update README"), OTHER_TYPE);
+        assert_eq!(classify_subject("wip: stuff"), OTHER_TYPE);
+        assert_eq!(classify_subject("fix(oops: nope"), OTHER_TYPE);
+    }
+
+    #[test]
+    fn test_breakdown_by_author_and_month() {
+        let commit = |name: &str, ts: u64, subject: &str, parents: usize| Commit {
+            author_name: name.to_string(),
+            author_time: ts,
+            subject: subject.to_string(),
+            parents: vec!["p".to_string(); parents],
+            ..Default::default()
+        };
+        let commits = vec![
+            commit("Ann", 1_704_067_200, "feat: a", 1), // 2024-01-01
+            commit("Ann", 1_706_745_600, "fix: b", 1),  // 2024-02-01
+            commit("Bob", 1_706_745_600, "chore: c", 1),
+            commit("Bob", 1_706_745_600, "Merge branch 'x'", 2),
+        ];
+        let by_author = breakdown(&commits, MessageGroup::Author);
+        assert_eq!(by_author.rows["Ann"]["feat"], 1);
+        assert_eq!(by_author.rows["Bob"].values().sum::<usize>(), 1);
+        assert_eq!(by_author.types(), vec!["feat", "fix", "chore"]);
+
+        let by_month = breakdown(&commits, MessageGroup::Month);
+        assert_eq!(by_month.rows["2024-02"]["fix"], 1);
+        assert_eq!(by_month.rows["2024-02"]["chore"], 1);
+        assert!(MessageGroup::parse("week").is_err());
+    }
+
+    #[test]
+    fn test_subject_terms_and_keyword_stats() {
+        assert_eq!(
+            subject_terms("fix(cli): Don't crash on the empty --top value 42"),
+            vec!["crash", "empty", "top", "value"]
+        );
+        assert_eq!(
+            subject_terms("Parser: parser handles UTF-8"),
+            vec!["parser", "handles", "utf-8"]
+        );
+
+        let commit = |name: &str, subject: &str| Commit {
+            author_name: name.to_string(),
+            subject: subject.to_string(),
+            parents: vec!["p".to_string()],
+            ..Default::default()
+        };
+        let commits = vec![
+            commit("Ann", "feat: parser for config files"),
+            commit("Ann", "fix: parser crash"),
+            commit("Bob", "Update config docs with a much longer explanation"),
+        ];
+        let (overall, groups) = keyword_stats(&commits, MessageGroup::Author, 20);
+        assert_eq!(overall.subjects, 3);
+        assert_eq!(overall.short, 1);
+        assert_eq!(overall.top_terms(2), vec![("config", 2), ("parser", 2)]);
+        assert_eq!(groups["Ann"].top_terms(1), vec![("parser", 2)]);
+        assert_eq!(groups["Ann"].average_length(), 23.0);
+        assert_eq!(groups["Bob"].short_pct(), 0.0);
+    }
+}
@@ -1,24 +1,139 @@
-use crate::stats::{AuthorStats, UserStats};
-use std::io::{self, Write};
+use crate::error::GitInsightsError;
+use crate::stats::{AuthorStats, StatsSort, UserOwnership, UserStats};
+use std::fmt::{self, Write as _};
+use std::io::{self, IsTerminal, Write};
 use std::time::Instant;
 
-/// Print author stats table.
+/// Unix seconds as an RFC 3339 UTC timestamp, e.g. `2023-11-14T22:13:20Z`.
+///
+/// Every export that carries a time uses this, next to the raw epoch.
+pub fn rfc3339_utc(t: u64) -> String {
+    let (y, m, d) = crate::code_frequency::ymd_from_unix(t);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        y,
+        m,
+        d,
+        (t / 3_600) % 24,
+        (t / 60) % 60,
+        t % 60
+    )
+}
+
+/// When charts and tables use ANSI colors (`--color[=WHEN]`, `--no-color`).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[non_exhaustive]
+pub enum ColorChoice {
+    /// Color when stdout is a terminal and `NO_COLOR` is unset or empty.
+    #[default]
+    Auto,
+    /// `-c`, `--color`, `--color=always`: color even when piped.
+    Always,
+    /// `--no-color`, `--color=never`.
+    Never,
+}
+
+impl ColorChoice {
+    pub fn parse(s: &str) -> Result<ColorChoice, String> {
+        match s {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            other => Err(format!(
+                "unknown --color '{}'. Expected auto, always or never.",
+                other
+            )),
+        }
+    }
+
+    /// Whether output written to stdout gets colors.
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+                    && io::stdout().is_terminal()
+            }
+        }
+    }
+}
+
+/// Whether a `COLORTERM` value announces 24-bit color.
+pub fn is_truecolor(colorterm: Option<&str>) -> bool {
+    colorterm.is_some_and(|v| {
+        let v = v.trim().to_ascii_lowercase();
+        v == "truecolor" || v == "24bit"
+    })
+}
+
+/// Whether the terminal takes 24-bit colors (`COLORTERM=truecolor|24bit`);
+/// see [`crate::palette`].
+pub fn truecolor_enabled() -> bool {
+    is_truecolor(std::env::var("COLORTERM").ok().as_deref())
+}
+
+/// Author rows `stats` prints before summarizing the rest (`--max-rows`).
+pub const DEFAULT_MAX_ROWS: usize = 500;
+
+/// Where, and how much of, the author table is written.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TableOutput {
+    /// Stop after this many rows (`None` prints all).
+    pub max_rows: Option<usize>,
+    /// Write the full table to this file instead of stdout.
+    pub path: Option<String>,
+    /// Row order (`--sort`); the default is most lines first.
+    pub sort: StatsSort,
+    /// Smallest first (`--asc`) instead of largest first.
+    pub ascending: bool,
+    /// Only the first N rows after sorting (`--top`); `None` keeps all.
+    pub top: Option<usize>,
+    /// One row per email domain instead of per author (`--by-domain`).
+    pub by_domain: bool,
+}
+
+/// Print author stats table. Fails if stdout does, e.g. when a pager quits
+/// early.
 pub fn print_table(
     data: Vec<(String, AuthorStats)>,
     total_loc: usize,
     total_commits: usize,
     total_files: usize,
-) {
-    println!(
-        "| {:<28} | {:>7} | {:>7} | {:>7} | {:<15} |",
-        "Author", "loc", "coms", "fils", "distribution"
-    );
-    println!(
-        "|:{:-<28}|{:->8}|{:->8}|{:->8}|:{:-<16}|",
-        "", "", "", "", ""
-    );
+) -> io::Result<()> {
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    write_table(
+        &mut out,
+        &data,
+        (total_loc, total_commits, total_files),
+        None,
+    )?;
+    out.flush()
+}
+
+/// Stream the author stats table to `out` one row at a time, stopping after
+/// `max_rows` rows with a notice about the remainder. `totals` is
+/// `(loc, commits, files)`.
+pub fn write_table<W: Write>(
+    out: &mut W,
+    data: &[(String, AuthorStats)],
+    totals: (usize, usize, usize),
+    max_rows: Option<usize>,
+) -> io::Result<()> {
+    let (total_loc, total_commits, total_files) = totals;
+    writeln!(
+        out,
+        "| {:<28} | {:>7} | {:>8} | {:>8} | {:>8} | {:>7} | {:>7} | {:<15} | {:<12} |",
+        "Author", "loc", "added", "deleted", "net", "coms", "fils", "distribution", "last 12w"
+    )?;
+    writeln!(
+        out,
+        "|:{:-<28}|{:->8}|{:->9}|{:->9}|{:->9}|{:->8}|{:->8}|:{:-<16}|:{:-<13}|",
+        "", "", "", "", "", "", "", "", ""
+    )?;
 
-    for (author, stats) in &data {
+    let shown = max_rows.unwrap_or(data.len()).min(data.len());
+    for (author, stats) in &data[..shown] {
         let loc_dist = if total_loc > 0 {
             (stats.loc as f32 / total_loc as f32) * 100.0
         } else {
@@ -37,60 +152,266 @@ pub fn print_table(
 
         let distribution_str = format!("{:.1}/{:.1}/{:.1}", loc_dist, coms_dist, fils_dist);
 
-        println!(
-            "| {:<28} | {:>7} | {:>7} | {:>7} | {:<15} |",
-            author,
+        writeln!(
+            out,
+            "| {:<28} | {:>7} | {:>8} | {:>8} | {:>+8} | {:>7} | {:>7} | {:<15} | {:<12} |",
+            cell(author),
             stats.loc,
+            stats.added,
+            stats.deleted,
+            stats.net(),
             stats.commits,
             stats.files.len(),
-            distribution_str
-        );
+            distribution_str,
+            sparkline(&stats.weekly)
+        )?;
     }
+    if shown < data.len() {
+        writeln!(
+            out,
+            "... {} more rows. Use --max-rows N (0 = all) or --output FILE for the full table.",
+            data.len() - shown
+        )?;
+    }
+    Ok(())
+}
+
+/// One block per count, scaled to the largest; zero is a blank.
+pub fn sparkline(counts: &[usize]) -> String {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = counts.iter().copied().max().unwrap_or(0);
+    counts
+        .iter()
+        .map(|&c| match c {
+            0 => ' ',
+            _ => BLOCKS[(c * BLOCKS.len()).div_ceil(max) - 1],
+        })
+        .collect()
 }
 
 /// Print user file ownership table.
-pub fn print_user_ownership(rows: &[(String, usize, usize, f32)]) {
-    println!(
-        "| {:>4} | {:<60} | {:>7} | {:>7} | {:>6} |",
-        "No.", "File", "userLOC", "fileLOC", "%own"
-    );
-    println!("|{:->6}|:{:-<60}|{:->9}|{:->9}|{:->8}|", "", "", "", "", "");
-    for (i, (file, u, f, pct)) in rows.iter().enumerate() {
-        println!(
-            "| {:>4} | {:<60} | {:>7} | {:>7} | {:>5.1} |",
+pub fn print_user_ownership(report: &UserOwnership) -> io::Result<()> {
+    write_user_ownership(&mut io::stdout().lock(), report)
+}
+
+/// Write the user file ownership table to `out`, with each file's share of
+/// the user's lines and a summary line.
+pub fn write_user_ownership<W: Write>(out: &mut W, report: &UserOwnership) -> io::Result<()> {
+    writeln!(
+        out,
+        "| {:>4} | {:<60} | {:>7} | {:>7} | {:>6} | {:>6} |",
+        "No.", "File", "userLOC", "fileLOC", "%own", "%user"
+    )?;
+    writeln!(
+        out,
+        "|{:->6}|:{:-<60}|{:->9}|{:->9}|{:->8}|{:->8}|",
+        "", "", "", "", "", ""
+    )?;
+    for (i, (file, u, f, pct)) in report.rows.iter().enumerate() {
+        writeln!(
+            out,
+            "| {:>4} | {:<60} | {:>7} | {:>7} | {:>5.1} | {:>5.1} |",
             i + 1,
             truncate(file, 60),
             u,
             f,
-            pct
-        );
+            pct,
+            report.share_of_user(*u)
+        )?;
+    }
+    write!(
+        out,
+        "\nTotal: {} lines in {} files",
+        report.total_loc, report.files
+    )?;
+    match &report.top_dir {
+        Some((dir, loc)) => writeln!(
+            out,
+            "; top directory: {} ({} lines, {:.1}%)",
+            dir,
+            loc,
+            report.share_of_user(*loc)
+        ),
+        None => writeln!(out),
     }
 }
 
-/// Truncate long paths for display.
-fn truncate(s: &str, max: usize) -> String {
-    if s.len() <= max {
-        s.to_string()
-    } else if max > 3 {
-        let cut = max - 3;
-        let mut end = cut;
-        if end > 0 && end < s.len() {
-            let prev = &s[end.saturating_sub(1)..end];
-            let cur = &s[end..end + 1];
-            if prev != "-" && cur == "-" {
-                end += 1;
-            }
+/// Terminal columns `c` takes: 0 for combining marks, joiners, variation
+/// selectors and control characters, 2 for East Asian wide and fullwidth
+/// characters and emoji, 1 otherwise. An approximation of Unicode's
+/// East Asian Width that covers the scripts found in names and paths.
+pub(crate) fn char_width(c: char) -> usize {
+    match c as u32 {
+        0x0300..=0x036F
+        | 0x0483..=0x0489
+        | 0x0591..=0x05BD
+        | 0x0610..=0x061A
+        | 0x064B..=0x065F
+        | 0x0E31
+        | 0x0E34..=0x0E3A
+        | 0x0E47..=0x0E4E
+        | 0x1AB0..=0x1AFF
+        | 0x1DC0..=0x1DFF
+        | 0x200B..=0x200F
+        | 0x202A..=0x202E
+        | 0x2060..=0x2064
+        | 0x20D0..=0x20FF
+        | 0x3099..=0x309A
+        | 0xFE00..=0xFE0F
+        | 0xFE20..=0xFE2F
+        | 0xFEFF
+        | 0xE0100..=0xE01EF => 0,
+        _ if c.is_control() => 0,
+        0x1100..=0x115F
+        | 0x231A..=0x231B
+        | 0x2329..=0x232A
+        | 0x23E9..=0x23EC
+        | 0x2614..=0x2615
+        | 0x26A1
+        | 0x26AA..=0x26AB
+        | 0x26BD..=0x26BE
+        | 0x26C4..=0x26C5
+        | 0x26D4
+        | 0x26EA
+        | 0x26F2..=0x26F5
+        | 0x26FA
+        | 0x26FD
+        | 0x2705
+        | 0x270A..=0x270B
+        | 0x2728
+        | 0x274C
+        | 0x2753..=0x2755
+        | 0x2757
+        | 0x2795..=0x2797
+        | 0x27B0
+        | 0x27BF
+        | 0x2B1B..=0x2B1C
+        | 0x2B50
+        | 0x2B55
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xA960..=0xA97F
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE10..=0xFE19
+        | 0xFE30..=0xFE6F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F004
+        | 0x1F0CF
+        | 0x1F18E
+        | 0x1F191..=0x1F19A
+        | 0x1F200..=0x1F251
+        | 0x1F300..=0x1F64F
+        | 0x1F680..=0x1F6FF
+        | 0x1F7E0..=0x1F7EB
+        | 0x1F90C..=0x1F9FF
+        | 0x1FA70..=0x1FAFF
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+/// Terminal columns `s` takes; see [`char_width`].
+pub(crate) fn display_width(s: &str) -> usize {
+    s.chars().map(char_width).sum()
+}
+
+/// Text for a table column. Unlike a `String`, `{:<N}` and `{:>N}` pad it to
+/// N terminal columns, so rows with accented, CJK or emoji names line up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Cell(String);
+
+impl PartialEq<&str> for Cell {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl fmt::Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pad = f
+            .width()
+            .unwrap_or(0)
+            .saturating_sub(display_width(&self.0));
+        let (before, after) = match f.align() {
+            Some(fmt::Alignment::Right) => (pad, 0),
+            Some(fmt::Alignment::Center) => (pad / 2, pad - pad / 2),
+            _ => (0, pad),
+        };
+        let fill = f.fill();
+        for _ in 0..before {
+            f.write_char(fill)?;
         }
-        let mut out = s[..end].to_string();
-        out.push_str("...");
-        out
-    } else {
-        s[..max].to_string()
+        f.write_str(&self.0)?;
+        for _ in 0..after {
+            f.write_char(fill)?;
+        }
+        Ok(())
+    }
+}
+
+/// `s` as a [`Cell`], without truncation.
+pub(crate) fn cell(s: &str) -> Cell {
+    Cell(s.to_string())
+}
+
+/// Truncate long names and paths to `max` terminal columns for display,
+/// ending in `...`. Cuts fall on character boundaries, and combining marks
+/// stay with the character they belong to.
+pub(crate) fn truncate(s: &str, max: usize) -> Cell {
+    if display_width(s) <= max {
+        return cell(s);
+    }
+    let (budget, ellipsis) = if max > 3 { (max - 3, "...") } else { (max, "") };
+    let mut end = 0;
+    let mut width = 0;
+    for (i, c) in s.char_indices() {
+        let w = char_width(c);
+        if width + w > budget {
+            break;
+        }
+        width += w;
+        end = i + c.len_utf8();
+    }
+    // Keep a dash right after the cut, so `this-is-long` reads `this-is-...`.
+    if max > 3 && end > 0 && s[end..].starts_with('-') && !s[..end].ends_with('-') {
+        end += 1;
     }
+    Cell(format!("{}{}", &s[..end], ellipsis))
+}
+
+/// Write an export to `output`, falling back to `default_path`.
+///
+/// `-` writes to stdout; the confirmation message is only printed for files so
+/// piped output stays clean.
+pub fn write_export(
+    output: Option<&str>,
+    default_path: &str,
+    contents: &[u8],
+) -> Result<(), GitInsightsError> {
+    let path = output.unwrap_or(default_path);
+    if path == "-" {
+        let mut stdout = io::stdout().lock();
+        stdout
+            .write_all(contents)
+            .and_then(|_| stdout.flush())
+            .map_err(|e| format!("failed to write to stdout: {}", e))?;
+        return Ok(());
+    }
+    std::fs::write(path, contents).map_err(|e| format!("failed to write {}: {}", path, e))?;
+    println!("Successfully exported to {}", path);
+    Ok(())
 }
 
 /// Print progress bar.
 pub fn print_progress(processed: usize, total: usize, start_time: Instant) {
+    if !crate::log::show_progress() {
+        return;
+    }
     const BAR_WIDTH: usize = 50;
     let percentage = processed as f32 / total as f32;
     let filled_width = (percentage * BAR_WIDTH as f32) as usize;
@@ -103,7 +424,7 @@ pub fn print_progress(processed: usize, total: usize, start_time: Instant) {
     let bar: String = (0..BAR_WIDTH)
         .map(|i| if i < filled_width { '#' } else { ' ' })
         .collect();
-    print!(
+    eprint!(
         "\rProcessing: {:3.0}%|{}| {}/{} [{:.2} file/s]",
         percentage * 100.0,
         bar,
@@ -111,37 +432,78 @@ pub fn print_progress(processed: usize, total: usize, start_time: Instant) {
         total,
         files_per_second
     );
-    io::stdout().flush().unwrap();
+    io::stderr().flush().unwrap();
+}
+
+/// End the progress line drawn by [`print_progress`].
+pub fn finish_progress() {
+    if crate::log::show_progress() {
+        eprintln!();
+    }
 }
 
 /// Print user stats.
-pub fn print_user_stats(username: &str, stats: &UserStats) {
-    println!("\nStatistics for user: {}", username);
-    println!("---------------------------------");
-    println!("Merged Pull Requests: {}", stats.pull_requests);
+pub fn print_user_stats(username: &str, stats: &UserStats) -> io::Result<()> {
+    write_user_stats(&mut io::stdout().lock(), username, stats)
+}
+
+/// Write the user stats summary to `out`.
+pub fn write_user_stats<W: Write>(
+    out: &mut W,
+    username: &str,
+    stats: &UserStats,
+) -> io::Result<()> {
+    writeln!(out, "\nStatistics for user: {}", username)?;
+    writeln!(out, "---------------------------------")?;
+    match &stats.github {
+        Some(g) => {
+            writeln!(
+                out,
+                "Merged Pull Requests: {} (GitHub, {} as {})",
+                g.merged_prs, g.repo, g.login
+            )?;
+            writeln!(out, "Pull Requests Reviewed: {}", g.reviews)?;
+            writeln!(out, "Issues Opened: {}", g.issues_opened)?;
+            writeln!(out, "Issues Commented On: {}", g.issues_commented)?;
+        }
+        None => {
+            writeln!(out, "Merged Pull Requests: {}", stats.pull_requests)?;
+            let sources: Vec<String> = stats
+                .pull_request_sources
+                .rows()
+                .iter()
+                .filter(|(_, n)| *n > 0)
+                .map(|(label, n)| format!("{} {}", label.replace('_', " "), n))
+                .collect();
+            if !sources.is_empty() {
+                writeln!(out, "  by source: {}", sources.join(", "))?;
+            }
+        }
+    }
 
     if !stats.tags.is_empty() {
-        println!("\nAuthored in the following tags:");
+        writeln!(out, "\nAuthored in the following tags:")?;
         let mut sorted_tags: Vec<_> = stats.tags.iter().collect();
         sorted_tags.sort();
 
         let tag_count = sorted_tags.len();
         if tag_count <= 6 {
             for tag in sorted_tags {
-                println!("  - {}", tag);
+                writeln!(out, "  - {}", tag)?;
             }
         } else {
             for tag in sorted_tags.iter().take(5) {
-                println!("  - {}", tag);
+                writeln!(out, "  - {}", tag)?;
             }
-            println!("  ... ({} more tags)", tag_count - 6);
+            writeln!(out, "  ... ({} more tags)", tag_count - 6)?;
             if let Some(last_tag) = sorted_tags.last() {
-                println!("  - {}", last_tag);
+                writeln!(out, "  - {}", last_tag)?;
             }
         }
     } else {
-        println!("\nNo tags found where this user is an author.");
+        writeln!(out, "\nNo tags found where this user is an author.")?;
     }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -151,6 +513,14 @@ mod tests {
     use std::collections::HashSet;
     use std::time::Instant;
 
+    #[test]
+    fn test_is_truecolor() {
+        assert!(is_truecolor(Some("truecolor")));
+        assert!(is_truecolor(Some("24bit")));
+        assert!(!is_truecolor(Some("yes")));
+        assert!(!is_truecolor(None));
+    }
+
     #[test]
     fn test_print_table() {
         let mut data = Vec::new();
@@ -162,9 +532,67 @@ mod tests {
                 loc: 100,
                 commits: 10,
                 files,
+                ..Default::default()
             },
         ));
-        print_table(data, 100, 10, 1);
+        print_table(data, 100, 10, 1).expect("print");
+    }
+
+    #[test]
+    fn test_write_errors_are_returned() {
+        struct ClosedPipe;
+        impl Write for ClosedPipe {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::ErrorKind::BrokenPipe.into())
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+        let data = vec![("a".to_string(), AuthorStats::default())];
+        let err = write_table(&mut ClosedPipe, &data, (0, 0, 0), None).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+        let err = write_user_stats(&mut ClosedPipe, "a", &UserStats::default()).unwrap_err();
+        let err = crate::error::GitInsightsError::from(err);
+        assert_ne!(err.exit_kind().code(), 0);
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[0, 1, 2, 4, 8]), " ▁▂▄█");
+        assert_eq!(sparkline(&[3, 0, 3]), "█ █");
+        assert_eq!(sparkline(&[0; 3]), "   ");
+        assert_eq!(sparkline(&[]), "");
+    }
+
+    #[test]
+    fn test_write_table_caps_rows() {
+        let data: Vec<(String, AuthorStats)> = (0..5)
+            .map(|i| {
+                (
+                    format!("author{}", i),
+                    AuthorStats {
+                        loc: 10 - i,
+                        commits: 1,
+                        files: HashSet::new(),
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect();
+        let mut out = Vec::new();
+        write_table(&mut out, &data, (40, 5, 0), Some(2)).expect("write");
+        let text = String::from_utf8(out).expect("utf8");
+        assert_eq!(text.lines().count(), 2 + 2 + 1);
+        assert!(text.contains("| author1 "));
+        assert!(!text.contains("author2"));
+        assert!(text.ends_with(
+            "... 3 more rows. Use --max-rows N (0 = all) or --output FILE for the full table.\n"
+        ));
+
+        let mut all = Vec::new();
+        write_table(&mut all, &data, (40, 5, 0), None).expect("write");
+        assert_eq!(String::from_utf8(all).expect("utf8").lines().count(), 7);
     }
 
     #[test]
@@ -181,8 +609,9 @@ mod tests {
         let stats = UserStats {
             pull_requests: 5,
             tags,
+            ..Default::default()
         };
-        print_user_stats("test_user", &stats);
+        print_user_stats("test_user", &stats).expect("print");
     }
 
     #[test]
@@ -190,17 +619,77 @@ mod tests {
         let stats = UserStats {
             pull_requests: 2,
             tags: HashSet::new(),
+            ..Default::default()
         };
-        print_user_stats("test_user_no_tags", &stats);
+        print_user_stats("test_user_no_tags", &stats).expect("print");
+    }
+
+    #[test]
+    fn test_write_user_stats_collapses_tags() {
+        let tags: HashSet<String> = (1..=8).map(|i| format!("v{}", i)).collect();
+        let stats = UserStats {
+            pull_requests: 3,
+            tags,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        write_user_stats(&mut out, "ann", &stats).expect("write");
+        let text = String::from_utf8(out).expect("utf8");
+        assert!(text.contains("Statistics for user: ann\n"));
+        assert!(text.contains("Merged Pull Requests: 3\n"));
+        assert!(!text.contains("by source"));
+        assert!(text.contains("  ... (2 more tags)\n  - v8\n"));
+        assert!(!text.contains("v6"));
+
+        let stats = UserStats {
+            pull_requests: 7,
+            github: Some(crate::github::GitHubActivity {
+                repo: "o/r".to_string(),
+                login: "ann-l".to_string(),
+                merged_prs: 7,
+                reviews: 4,
+                issues_opened: 2,
+                issues_commented: 9,
+            }),
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        write_user_stats(&mut out, "ann", &stats).expect("write");
+        let text = String::from_utf8(out).expect("utf8");
+        assert!(text.contains("Merged Pull Requests: 7 (GitHub, o/r as ann-l)\n"));
+        assert!(text.contains("Pull Requests Reviewed: 4\nIssues Opened: 2\n"));
+
+        let mut stats = UserStats::default();
+        stats.pull_request_sources.gitlab = 2;
+        stats.pull_request_sources.branch_merges = 1;
+        stats.pull_requests = 3;
+        let mut out = Vec::new();
+        write_user_stats(&mut out, "ann", &stats).expect("write");
+        let text = String::from_utf8(out).expect("utf8");
+        assert!(text.contains("Merged Pull Requests: 3\n  by source: gitlab 2, branch merges 1\n"));
     }
 
     #[test]
     fn test_print_user_ownership() {
-        let rows = vec![
-            ("src/lib.rs".to_string(), 10, 20, 50.0),
-            ("README.md".to_string(), 5, 5, 100.0),
-        ];
-        super::print_user_ownership(&rows);
+        let report = UserOwnership {
+            rows: vec![
+                ("src/lib.rs".to_string(), 10, 20, 50.0),
+                ("README.md".to_string(), 5, 5, 100.0),
+            ],
+            total_loc: 20,
+            files: 3,
+            top_dir: Some(("src".to_string(), 15)),
+        };
+        super::print_user_ownership(&report).expect("print");
+
+        let mut out = Vec::new();
+        write_user_ownership(&mut out, &report).expect("write");
+        let text = String::from_utf8(out).expect("utf8");
+        assert!(text.contains("|      10 |      20 |  50.0 |  50.0 |"));
+        assert!(text.contains("|       5 |       5 | 100.0 |  25.0 |"));
+        assert!(
+            text.ends_with("Total: 20 lines in 3 files; top directory: src (15 lines, 75.0%)\n")
+        );
     }
 
     #[test]
@@ -208,5 +697,47 @@ mod tests {
         assert_eq!(super::truncate("short", 10), "short");
         assert_eq!(super::truncate("exactlyten", 10), "exactlyten");
         assert_eq!(super::truncate("this-is-long", 10), "this-is-...");
+        assert_eq!(
+            super::truncate("Jürgen Müller-Lüdenscheid", 12),
+            "Jürgen Mü..."
+        );
+        assert_eq!(super::truncate("文档/设计说明书.md", 10), "文档/设...");
+        assert_eq!(
+            super::truncate("e\u{301}e\u{301}e\u{301}e\u{301}x", 4),
+            "e\u{301}..."
+        );
+        assert_eq!(super::truncate("日本語", 2), "日");
+    }
+
+    #[test]
+    fn test_cells_pad_by_display_width() {
+        assert_eq!(display_width("Zoë"), 3);
+        assert_eq!(display_width("Zoe\u{308}"), 3);
+        assert_eq!(display_width("李小龙"), 6);
+        assert_eq!(display_width("🚀 ship"), 7);
+        assert_eq!(format!("|{:<8}|", cell("李小龙")), "|李小龙  |");
+        assert_eq!(format!("|{:>5}|", cell("Zoë")), "|  Zoë|");
+        assert_eq!(format!("|{:<6}|", truncate("李小龙", 5)), "|李... |");
+
+        let mut rows = Vec::new();
+        for name in ["Ann", "Zoë", "李小龙"] {
+            rows.push((name.to_string(), AuthorStats::default()));
+        }
+        let mut out = Vec::new();
+        write_table(&mut out, &rows, (0, 0, 0), None).expect("write");
+        let text = String::from_utf8(out).expect("utf8");
+        let widths: Vec<usize> = text
+            .lines()
+            .filter(|l| !l.starts_with("|:"))
+            .map(display_width)
+            .collect();
+        assert!(widths.iter().all(|w| *w == widths[0]), "{}", text);
+    }
+
+    #[test]
+    fn test_rfc3339_utc() {
+        assert_eq!(rfc3339_utc(0), "1970-01-01T00:00:00Z");
+        assert_eq!(rfc3339_utc(1_700_000_000), "2023-11-14T22:13:20Z");
+        assert_eq!(rfc3339_utc(1_709_251_199), "2024-02-29T23:59:59Z");
     }
 }
@@ -0,0 +1,512 @@
+use crate::codeowners::{load_codeowners, owning_rule, parse_codeowners, CodeownersRule};
+use crate::error::GitInsightsError;
+use crate::git::{run_command, run_command_lines, Repo};
+use crate::json;
+use crate::merges::{blame_args, blame_line_authors, run_blame_head, MergeResolver};
+use crate::output::truncate;
+use crate::stats::tracked_text_files_head;
+use std::collections::{HashMap, HashSet};
+
+/// The commit that last touched one line.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LineOwner {
+    pub sha: String,
+    pub name: String,
+    pub email: String,
+    pub time: u64,
+    pub summary: String,
+}
+
+/// Who owns a file (and optionally one line of it).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OwnersReport {
+    /// Path relative to the repository root.
+    pub file: String,
+    pub line: Option<(usize, LineOwner)>,
+    /// `(name, email, lines)`, most lines first.
+    pub owners: Vec<(String, String, usize)>,
+    pub total_lines: usize,
+    pub codeowners: Option<CodeownersRule>,
+}
+
+/// Commit details for the `n`th (1-based) content line of `blame --line-porcelain`.
+pub fn line_owner_from_porcelain(blame: &str, n: usize) -> Option<LineOwner> {
+    let mut current = LineOwner::default();
+    let mut seen = 0;
+    for line in blame.lines() {
+        if line.starts_with('\t') {
+            seen += 1;
+            if seen == n {
+                return Some(current);
+            }
+        } else if let Some(rest) = line.strip_prefix("author ") {
+            current.name = rest.to_string();
+        } else if let Some(rest) = line.strip_prefix("author-mail ") {
+            current.email = rest.trim_matches(|c| c == '<' || c == '>').to_string();
+        } else if let Some(rest) = line.strip_prefix("author-time ") {
+            current.time = rest.trim().parse().unwrap_or(0);
+        } else if let Some(rest) = line.strip_prefix("summary ") {
+            current.summary = rest.to_string();
+        } else if let Some(sha) = line.split(' ').next().filter(|s| s.len() >= 40) {
+            if sha.bytes().all(|b| b.is_ascii_hexdigit()) {
+                current.sha = sha.to_string();
+            }
+        }
+    }
+    None
+}
+
+/// Blame `path` at HEAD once and summarise its owners.
+///
+/// `path` may be absolute or relative to the current directory, as editors
+/// pass it; CODEOWNERS is consulted when the repository has one.
+pub fn gather_owners(path: &str, line: Option<usize>) -> Result<OwnersReport, GitInsightsError> {
+    let file = run_command(&["ls-files", "--full-name", "--", path])?
+        .lines()
+        .next()
+        .map(str::to_string)
+        .ok_or_else(|| format!("{} is not tracked by git", path))?;
+    let top = run_command(&["rev-parse", "--show-toplevel"])?;
+    let blame_args = blame_args("HEAD", &file);
+    let mut args = vec!["-C", top.trim()];
+    args.extend(blame_args.iter().map(String::as_str));
+    let blame = run_command(&args)?;
+    let merges = MergeResolver::load()?;
+    let authors = crate::filter::author_matcher()?;
+    let names = crate::identity::active_normalization();
+
+    let mut counts: HashMap<(String, String), usize> = HashMap::new();
+    let mut total_lines = 0;
+    for (name, mail) in blame_line_authors(&blame, &file, &merges) {
+        if authors.allows(&name, &mail) {
+            let email = mail.trim_matches(|c| c == '<' || c == '>').to_string();
+            *counts
+                .entry((names.apply(name), names.apply(email)))
+                .or_insert(0) += 1;
+            total_lines += 1;
+        }
+    }
+    let mut owners: Vec<(String, String, usize)> =
+        counts.into_iter().map(|((n, e), c)| (n, e, c)).collect();
+    owners.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+
+    let line = match line {
+        Some(n) => {
+            let mut owner = line_owner_from_porcelain(&blame, n)
+                .ok_or_else(|| format!("{} has no line {}", file, n))?;
+            let (name, mail) = merges.resolve(
+                &owner.sha,
+                &file,
+                &owner.name,
+                &format!("<{}>", owner.email),
+            );
+            owner.name = name;
+            owner.email = mail.trim_matches(|c| c == '<' || c == '>').to_string();
+            Some((n, owner))
+        }
+        None => None,
+    };
+
+    let codeowners = load_codeowners(None)
+        .ok()
+        .and_then(|(_, text)| owning_rule(&parse_codeowners(&text), &file).cloned());
+
+    Ok(OwnersReport {
+        file,
+        line,
+        owners,
+        total_lines,
+        codeowners,
+    })
+}
+
+/// One JSON object, keeping at most `top` file owners.
+pub fn render_owners_json(report: &OwnersReport, top: usize) -> String {
+    let line = match &report.line {
+        Some((n, o)) => format!(
+            "{{\"number\": {}, \"sha\": \"{}\", \"author\": \"{}\", \"email\": \"{}\", {}, \"summary\": \"{}\"}}",
+            n,
+            o.sha,
+            json::escape(&o.name),
+            json::escape(&o.email),
+            json::time_fields("time", o.time),
+            json::escape(&o.summary)
+        ),
+        None => "null".to_string(),
+    };
+    let owners: Vec<String> = report
+        .owners
+        .iter()
+        .take(top)
+        .map(|(n, e, c)| {
+            format!(
+                "{{\"author\": \"{}\", \"email\": \"{}\", \"lines\": {}, \"pct\": {:.1}}}",
+                json::escape(n),
+                json::escape(e),
+                c,
+                *c as f64 * 100.0 / report.total_lines.max(1) as f64
+            )
+        })
+        .collect();
+    let codeowners = match &report.codeowners {
+        Some(rule) => format!(
+            "[{}]",
+            rule.owners
+                .iter()
+                .map(|o| format!("\"{}\"", json::escape(o)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        None => "[]".to_string(),
+    };
+    format!(
+        "{{\"file\": \"{}\", \"line\": {}, \"owners\": [{}], \"total_lines\": {}, \"codeowners\": {}}}",
+        json::escape(&report.file),
+        line,
+        owners.join(", "),
+        report.total_lines,
+        codeowners
+    )
+}
+
+/// Print owners as text or JSON.
+pub fn run_owners(
+    path: &str,
+    line: Option<usize>,
+    top: usize,
+    as_json: bool,
+) -> Result<(), GitInsightsError> {
+    let report = gather_owners(path, line)?;
+    if as_json {
+        println!("{}", render_owners_json(&report, top));
+        return Ok(());
+    }
+    if let Some((n, o)) = &report.line {
+        let (y, m, d) = crate::code_frequency::ymd_from_unix(o.time);
+        println!(
+            "{}:{}  {} <{}>  {} {:04}-{:02}-{:02}  {}",
+            report.file,
+            n,
+            o.name,
+            o.email,
+            &o.sha[..o.sha.len().min(8)],
+            y,
+            m,
+            d,
+            o.summary
+        );
+    }
+    println!(
+        "Top owners of {} ({} lines):",
+        report.file, report.total_lines
+    );
+    for (name, email, lines) in report.owners.iter().take(top) {
+        println!(
+            "  {:<28} {:>6} lines {:>5.1}%  <{}>",
+            crate::output::cell(name),
+            lines,
+            *lines as f64 * 100.0 / report.total_lines.max(1) as f64,
+            email
+        );
+    }
+    if let Some(rule) = &report.codeowners {
+        println!(
+            "CODEOWNERS: {} (line {}: {})",
+            rule.owners.join(" "),
+            rule.line,
+            rule.pattern
+        );
+    }
+    Ok(())
+}
+
+/// One author's share of a file or directory (`file <path>`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PathOwner {
+    pub name: String,
+    pub email: String,
+    /// Surviving lines at HEAD.
+    pub lines: usize,
+    /// Author time of their latest non-merge commit to the path (0 if none).
+    pub last_touch: u64,
+}
+
+/// Who owns the text files under a path, the inverse of `user --ownership`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PathOwnership {
+    /// The path as given.
+    pub path: String,
+    /// Text files blamed.
+    pub files: usize,
+    pub total_lines: usize,
+    /// Most lines first; authors whose lines are all gone come last.
+    pub owners: Vec<PathOwner>,
+}
+
+/// Blame every text file under `path` (a file or directory, relative to the
+/// current directory) and add each author's latest commit to it.
+pub fn gather_path_ownership(path: &str) -> Result<PathOwnership, GitInsightsError> {
+    let listed: HashSet<String> = run_command(&["ls-files", "--full-name", "--", path])?
+        .lines()
+        .map(str::to_string)
+        .collect();
+    if listed.is_empty() {
+        return Err(format!("{} is not tracked by git", path).into());
+    }
+    let authors = crate::filter::author_matcher()?;
+    let names = crate::identity::active_normalization();
+    let mut by_author: HashMap<(String, String), PathOwner> = HashMap::new();
+
+    let revs = crate::filter::revision_args()?;
+    let mut args = vec![
+        "--no-pager",
+        "log",
+        "--no-merges",
+        "--format=%aN%x09%aE%x09%at",
+    ];
+    args.extend(revs.iter().map(|s| s.as_str()));
+    args.extend(["--", path]);
+    run_command_lines(&args, |line| {
+        let mut f = line.splitn(3, '\t');
+        let (Some(name), Some(email), Some(time)) = (f.next(), f.next(), f.next()) else {
+            return;
+        };
+        if !authors.allows(name, email) {
+            return;
+        }
+        let key = (
+            names.apply(name.to_string()),
+            names.apply(email.to_string()),
+        );
+        let owner = by_author.entry(key).or_default();
+        owner.last_touch = owner.last_touch.max(time.parse().unwrap_or(0));
+    })?;
+
+    // Blame from the top level, where the listed paths are relative to.
+    let top = match Repo::current() {
+        Some(repo) => repo,
+        None => Repo::open(".")?,
+    };
+    let (files, total_lines) = top.scope(|| -> Result<(usize, usize), String> {
+        let files: Vec<String> = tracked_text_files_head()?
+            .into_iter()
+            .filter(|f| listed.contains(f))
+            .collect();
+        let merges = MergeResolver::load()?;
+        let mut total_lines = 0;
+        for file in &files {
+            let Ok(blame) = run_blame_head(file) else {
+                continue;
+            };
+            for (name, mail) in blame_line_authors(&blame, file, &merges) {
+                if authors.allows(&name, &mail) {
+                    let email = mail.trim_matches(|c| c == '<' || c == '>').to_string();
+                    by_author
+                        .entry((names.apply(name), names.apply(email)))
+                        .or_default()
+                        .lines += 1;
+                    total_lines += 1;
+                }
+            }
+        }
+        Ok((files.len(), total_lines))
+    })?;
+
+    let mut owners: Vec<PathOwner> = by_author
+        .into_iter()
+        .map(|((name, email), o)| PathOwner { name, email, ..o })
+        .collect();
+    owners.sort_by(|a, b| {
+        b.lines
+            .cmp(&a.lines)
+            .then_with(|| b.last_touch.cmp(&a.last_touch))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    Ok(PathOwnership {
+        path: path.to_string(),
+        files,
+        total_lines,
+        owners,
+    })
+}
+
+fn date(t: u64) -> String {
+    if t == 0 {
+        return "-".to_string();
+    }
+    let (y, m, d) = crate::code_frequency::ymd_from_unix(t);
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+/// One JSON object, keeping at most `top` owners (0 = all).
+pub fn render_path_ownership_json(report: &PathOwnership, top: usize) -> String {
+    let limit = if top == 0 { usize::MAX } else { top };
+    let owners: Vec<String> = report
+        .owners
+        .iter()
+        .take(limit)
+        .map(|o| {
+            format!(
+                "{{\"author\": \"{}\", \"email\": \"{}\", \"lines\": {}, \"pct\": {:.1}, {}}}",
+                json::escape(&o.name),
+                json::escape(&o.email),
+                o.lines,
+                o.lines as f64 * 100.0 / report.total_lines.max(1) as f64,
+                json::time_fields("last_touch", o.last_touch)
+            )
+        })
+        .collect();
+    format!(
+        "{{\"path\": \"{}\", \"files\": {}, \"total_lines\": {}, \"owners\": [{}]}}",
+        json::escape(&report.path),
+        report.files,
+        report.total_lines,
+        owners.join(", ")
+    )
+}
+
+/// Print the per-author breakdown of `path` as a table or JSON.
+pub fn run_file(path: &str, top: usize, as_json: bool) -> Result<(), GitInsightsError> {
+    let report = gather_path_ownership(path)?;
+    if as_json {
+        println!("{}", render_path_ownership_json(&report, top));
+        return Ok(());
+    }
+    println!(
+        "Ownership of {} ({} files, {} lines):",
+        report.path, report.files, report.total_lines
+    );
+    println!(
+        "| {:<28} | {:<30} | {:>8} | {:>6} | {:<10} |",
+        "author", "email", "loc", "loc %", "last touch"
+    );
+    println!(
+        "|:{:-<29}|:{:-<31}|{:->10}|{:->8}|:{:-<11}|",
+        "", "", "", "", ""
+    );
+    let limit = if top == 0 { usize::MAX } else { top };
+    for o in report.owners.iter().take(limit) {
+        println!(
+            "| {:<28} | {:<30} | {:>8} | {:>6.1} | {:<10} |",
+            truncate(&o.name, 28),
+            truncate(&o.email, 30),
+            o.lines,
+            o.lines as f64 * 100.0 / report.total_lines.max(1) as f64,
+            date(o.last_touch)
+        );
+    }
+    if report.owners.len() > limit {
+        println!("... {} more authors", report.owners.len() - limit);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_owner_from_porcelain() {
+        let sha_a = "a".repeat(40);
+        let sha_b = "b".repeat(40);
+        let blame = format!(
+            "{sha_a} 1 1 1\nauthor Ann\nauthor-mail <ann@x.io>\nauthor-time 100\nsummary first\nfilename f\n\tone\n\
+             {sha_b} 2 2 1\nauthor Bob\nauthor-mail <bob@x.io>\nauthor-time 200\nsummary second\nfilename f\n\ttwo\n"
+        );
+        let owner = line_owner_from_porcelain(&blame, 2).expect("line 2");
+        assert_eq!(owner.sha, sha_b);
+        assert_eq!(
+            (owner.name.as_str(), owner.email.as_str()),
+            ("Bob", "bob@x.io")
+        );
+        assert_eq!((owner.time, owner.summary.as_str()), (200, "second"));
+        assert!(line_owner_from_porcelain(&blame, 3).is_none());
+    }
+
+    #[test]
+    fn test_gather_owners_e2e() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        repo.commit_with_epoch("Ann", "ann@x.io", "src/a.rs", "one", 1_700_000_000)
+            .expect("commit");
+        repo.commit_with_epoch("Bob", "bob@x.io", "src/a.rs", "two", 1_700_000_100)
+            .expect("commit");
+        repo.commit_with_epoch("Bob", "bob@x.io", "src/a.rs", "three", 1_700_000_200)
+            .expect("commit");
+        repo.commit_with_epoch("Ann", "ann@x.io", "CODEOWNERS", "/src/ @ann", 1_700_000_300)
+            .expect("commit");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(repo.path.join("src")).expect("cd");
+        let report = gather_owners("a.rs", Some(1));
+        std::env::set_current_dir(old).ok();
+
+        let report = report.expect("owners");
+        assert_eq!(report.file, "src/a.rs");
+        assert_eq!(report.total_lines, 3);
+        assert_eq!(
+            report.owners[0],
+            ("Bob".to_string(), "bob@x.io".to_string(), 2)
+        );
+        let (n, owner) = report.line.as_ref().expect("line owner");
+        assert_eq!((*n, owner.name.as_str()), (1, "Ann"));
+        assert_eq!(report.codeowners.as_ref().unwrap().owners, vec!["@ann"]);
+
+        let doc = crate::json::parse(&render_owners_json(&report, 1)).expect("valid JSON");
+        assert_eq!(
+            doc.get("owners")
+                .and_then(|o| o.as_array())
+                .map(|o| o.len()),
+            Some(1)
+        );
+        assert_eq!(
+            doc.get("line")
+                .and_then(|l| l.get("author"))
+                .and_then(|a| a.as_str()),
+            Some("Ann")
+        );
+        assert_eq!(
+            doc.get("line")
+                .and_then(|l| l.get("time_utc"))
+                .and_then(|t| t.as_str()),
+            Some("2023-11-14T22:13:20Z")
+        );
+    }
+
+    #[test]
+    fn test_gather_path_ownership_e2e() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        repo.commit_with_epoch("Ann", "ann@x.io", "src/a.rs", "one", 1_700_000_000)
+            .expect("commit");
+        repo.commit_with_epoch("Bob", "bob@x.io", "src/b.rs", "two", 1_700_000_100)
+            .expect("commit");
+        repo.commit_with_epoch("Bob", "bob@x.io", "src/b.rs", "three", 1_700_086_400)
+            .expect("commit");
+        repo.commit_with_epoch("Cy", "cy@x.io", "README.md", "hi", 1_700_000_300)
+            .expect("commit");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(repo.path.join("src")).expect("cd");
+        let report = gather_path_ownership(".");
+        let missing = gather_path_ownership("nope.rs");
+        std::env::set_current_dir(old).ok();
+
+        let report = report.expect("ownership");
+        assert!(missing.is_err());
+        assert_eq!((report.files, report.total_lines), (2, 3));
+        let names: Vec<&str> = report.owners.iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(names, vec!["Bob", "Ann"]);
+        assert_eq!(report.owners[0].lines, 2);
+        assert_eq!(report.owners[0].last_touch, 1_700_086_400);
+        assert_eq!(date(report.owners[0].last_touch), "2023-11-15");
+
+        let doc = crate::json::parse(&render_path_ownership_json(&report, 1)).expect("JSON");
+        let owners = doc.get("owners").and_then(|o| o.as_array()).unwrap();
+        assert_eq!(owners.len(), 1);
+        assert_eq!(
+            owners[0].get("last_touch_utc").and_then(|t| t.as_str()),
+            Some("2023-11-15T22:13:20Z")
+        );
+    }
+}
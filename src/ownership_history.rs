@@ -0,0 +1,231 @@
+//! `ownership-history <user>`: the user's surviving lines at evenly spaced
+//! commits, drawn as a timeline to show their footprint growing or eroding.
+
+use crate::code_frequency::ymd_from_unix;
+use crate::error::GitInsightsError;
+use crate::growth::sample_commits;
+use crate::merges::{blame_line_authors, run_blame, MergeResolver};
+use crate::output::{finish_progress, print_progress};
+use crate::stats::{is_user_line, prepare_blame, tracked_text_files_at};
+use crate::visualize::{render_timeline_multiline, ChartSize};
+use std::io::{self, Write};
+use std::time::Instant;
+
+/// Default number of sampled commits; each one blames the whole tree.
+pub const DEFAULT_HISTORY_SAMPLES: usize = 12;
+
+/// The user's lines in the tree of one sampled commit.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OwnershipSample {
+    pub sha: String,
+    /// Committer time.
+    pub time: u64,
+    /// Lines blamed to the user.
+    pub lines: usize,
+    /// Lines blamed to anyone allowed by the filter.
+    pub total: usize,
+}
+
+impl OwnershipSample {
+    pub fn share_pct(&self) -> f64 {
+        self.lines as f64 * 100.0 / self.total.max(1) as f64
+    }
+}
+
+/// Blame every text file at `samples` evenly spaced commits, oldest first,
+/// counting the lines that belong to `username`.
+pub fn gather_ownership_history(
+    username: &str,
+    by_email: bool,
+    samples: usize,
+) -> Result<Vec<OwnershipSample>, GitInsightsError> {
+    let picked = sample_commits(samples)?;
+    prepare_blame();
+    let authors = crate::filter::author_matcher()?;
+    let names = crate::identity::active_normalization();
+    let mut rows = Vec::with_capacity(picked.len());
+    let start = Instant::now();
+    for (k, (sha, time)) in picked.iter().enumerate() {
+        let merges = MergeResolver::load_at(sha)?;
+        let mut row = OwnershipSample {
+            sha: sha.clone(),
+            time: *time,
+            ..Default::default()
+        };
+        for file in tracked_text_files_at(sha)? {
+            let Ok(blame) = run_blame(sha, &file) else {
+                continue;
+            };
+            for (name, mail) in blame_line_authors(&blame, &file, &merges) {
+                if !authors.allows(&name, &mail) {
+                    continue;
+                }
+                row.total += 1;
+                if is_user_line(&name, &mail, username, by_email, names) {
+                    row.lines += 1;
+                }
+            }
+        }
+        rows.push(row);
+        print_progress(k + 1, picked.len(), start);
+    }
+    if !picked.is_empty() {
+        finish_progress();
+    }
+    Ok(rows)
+}
+
+fn format_day(t: u64) -> String {
+    let (y, m, d) = ymd_from_unix(t);
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+/// `[{"sha": "...", "date": "YYYY-MM-DD", "lines": N, "total": N}, ...]`
+pub fn render_ownership_history_json(rows: &[OwnershipSample]) -> String {
+    let items: Vec<String> = rows
+        .iter()
+        .map(|r| {
+            format!(
+                "{{\"sha\": \"{}\", \"date\": \"{}\", \"lines\": {}, \"total\": {}}}",
+                r.sha,
+                format_day(r.time),
+                r.lines,
+                r.total
+            )
+        })
+        .collect();
+    format!("[{}]", items.join(", "))
+}
+
+/// The timeline of the user's lines, the peak and the change from the first
+/// sample, then one row per sample.
+pub fn render_ownership_history(
+    out: &mut impl Write,
+    username: &str,
+    rows: &[OwnershipSample],
+    color: bool,
+) -> io::Result<()> {
+    let (Some(first), Some(last)) = (rows.first(), rows.last()) else {
+        writeln!(out, "(no commits)")?;
+        return Ok(());
+    };
+    let counts: Vec<usize> = rows.iter().map(|r| r.lines).collect();
+    let max = counts.iter().copied().max().unwrap_or(0);
+    let (dim, off) = if color {
+        ("\x1b[90m", "\x1b[0m")
+    } else {
+        ("", "")
+    };
+    writeln!(
+        out,
+        "{}Surviving lines of {} at {} sampled commits (old -> new), max={}{}",
+        dim,
+        username,
+        rows.len(),
+        max,
+        off
+    )?;
+    writeln!(out)?;
+    render_timeline_multiline(out, &counts, ChartSize::default().timeline_height(), color)?;
+    let left_pad = max.to_string().len().max(3) + 2;
+    let (from, to) = (format_day(first.time), format_day(last.time));
+    let gap = rows.len().saturating_sub(from.len() + to.len()).max(1);
+    writeln!(
+        out,
+        "{}{}{}{}{}{}",
+        dim,
+        " ".repeat(left_pad),
+        from,
+        " ".repeat(gap),
+        to,
+        off
+    )?;
+    writeln!(out)?;
+    let peak = rows.iter().rev().max_by_key(|r| r.lines).unwrap_or(last);
+    writeln!(
+        out,
+        "{} lines at {} -> {} lines at {} ({:+}); peak {} at {}",
+        first.lines,
+        from,
+        last.lines,
+        to,
+        last.lines as i64 - first.lines as i64,
+        peak.lines,
+        format_day(peak.time)
+    )?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "| {:<10} | {:<10} | {:>8} | {:>8} | {:>7} |",
+        "date", "commit", "lines", "total", "share"
+    )?;
+    writeln!(
+        out,
+        "|:{:-<11}|:{:-<11}|{:->10}|{:->10}|{:->9}|",
+        "", "", "", "", ""
+    )?;
+    for r in rows {
+        writeln!(
+            out,
+            "| {:<10} | {:<10} | {:>8} | {:>8} | {:>6.1}% |",
+            format_day(r.time),
+            &r.sha[..r.sha.len().min(10)],
+            r.lines,
+            r.total,
+            r.share_pct()
+        )?;
+    }
+    Ok(())
+}
+
+/// Sample, then print the timeline (or JSON).
+pub fn run_ownership_history(
+    username: &str,
+    by_email: bool,
+    samples: usize,
+    color: bool,
+    as_json: bool,
+) -> Result<(), GitInsightsError> {
+    let rows = gather_ownership_history(username, by_email, samples)?;
+    if as_json {
+        println!("{}", render_ownership_history_json(&rows));
+    } else {
+        render_ownership_history(&mut io::stdout().lock(), username, &rows, color)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ownership_history_e2e() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", "one", 1_700_000_000)
+            .expect("commit");
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", "two", 1_700_086_400)
+            .expect("commit");
+        repo.commit_with_epoch("Bob", "bob@x.io", "b.txt", "three", 1_700_172_800)
+            .expect("commit");
+        let git = crate::git::Repo::open(&repo.path).expect("open");
+        let rows = git
+            .scope(|| gather_ownership_history("Ann", false, 3))
+            .expect("history");
+        let lines: Vec<(usize, usize)> = rows.iter().map(|r| (r.lines, r.total)).collect();
+        assert_eq!(lines, vec![(1, 1), (2, 2), (2, 3)]);
+        let by_email = git
+            .scope(|| gather_ownership_history("<BOB@x.io>", true, 2))
+            .expect("history");
+        assert_eq!(by_email[1].lines, 1);
+
+        let mut out = Vec::new();
+        render_ownership_history(&mut out, "Ann", &rows, false).expect("render");
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("1 lines at 2023-11-14 -> 2 lines at 2023-11-16 (+1)"));
+        assert!(text.contains("peak 2 at 2023-11-15"));
+        assert!(render_ownership_history_json(&rows[..1]).contains("\"lines\": 1, \"total\": 1"));
+    }
+}
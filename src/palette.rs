@@ -0,0 +1,242 @@
+//! Color ramps of shaded charts (`--palette`), shared by the heatmaps,
+//! timelines and histograms.
+//!
+//! Named ramps and custom ones (`--palette '#0e4429,#39d353'`, or a
+//! `[palettes]` entry in the config) are drawn in 24-bit color when the
+//! terminal announces it and in the nearest of the 256 xterm colors
+//! otherwise.
+
+use crate::output::truecolor_enabled;
+use std::borrow::Cow;
+use std::sync::RwLock;
+
+/// An RGB color.
+pub type Rgb = (u8, u8, u8);
+
+/// Escape of level 0, used for zero cells and as the dim fallback.
+pub const DIM: &str = "\x1b[90m";
+
+/// 12-step ANSI ramp from dim through cool to warm hues; widely supported.
+const ANSI_PALETTE: [&str; 12] = [
+    DIM,        // 0: dim (should not be used for non-zero, but safe fallback)
+    "\x1b[34m", // blue
+    "\x1b[94m", // bright blue
+    "\x1b[36m", // cyan
+    "\x1b[96m", // bright cyan
+    "\x1b[32m", // green
+    "\x1b[92m", // bright green
+    "\x1b[33m", // yellow
+    "\x1b[93m", // bright yellow
+    "\x1b[35m", // magenta
+    "\x1b[95m", // bright magenta
+    "\x1b[91m", // bright red
+];
+
+const GITHUB: &[Rgb] = &[(14, 68, 41), (0, 109, 50), (38, 166, 65), (57, 211, 83)];
+const HEAT: &[Rgb] = &[(46, 160, 67), (240, 200, 40), (215, 48, 39)];
+const VIRIDIS: &[Rgb] = &[
+    (68, 1, 84),
+    (59, 82, 139),
+    (33, 145, 140),
+    (94, 201, 98),
+    (253, 231, 37),
+];
+const MONO: &[Rgb] = &[(88, 88, 88), (238, 238, 238)];
+
+/// Color ramp of shaded cells and bars (`--palette`).
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub enum Palette {
+    /// The 12-step ANSI ramp, or `heat` when the terminal takes 24-bit color.
+    #[default]
+    Auto,
+    /// GitHub's contribution greens (dark theme).
+    Github,
+    /// Green -> yellow -> red.
+    Heat,
+    /// Perceptually uniform purple -> teal -> yellow.
+    Viridis,
+    /// Gray -> white.
+    Mono,
+    /// Low-to-high stops from a hex list.
+    Custom(Vec<Rgb>),
+}
+
+impl Palette {
+    /// A palette name, or comma-separated `#rrggbb` stops (at least two).
+    pub fn parse(s: &str) -> Result<Palette, String> {
+        match s.trim().to_lowercase().as_str() {
+            "auto" => Ok(Palette::Auto),
+            "github" => Ok(Palette::Github),
+            "heat" => Ok(Palette::Heat),
+            "viridis" => Ok(Palette::Viridis),
+            "mono" => Ok(Palette::Mono),
+            list if list.starts_with('#') => {
+                let stops = list
+                    .split(',')
+                    .map(parse_hex)
+                    .collect::<Option<Vec<Rgb>>>()
+                    .filter(|stops| stops.len() >= 2)
+                    .ok_or_else(|| {
+                        format!(
+                            "invalid --palette '{}'. Expected at least two #rrggbb colors, e.g. '#0e4429,#39d353'.",
+                            s
+                        )
+                    })?;
+                Ok(Palette::Custom(stops))
+            }
+            _ => Err(format!(
+                "unknown --palette '{}'. Expected auto, github, heat, viridis, mono, a [palettes] name or '#rrggbb,...'.",
+                s
+            )),
+        }
+    }
+
+    /// Gradient stops, low to high; `None` for the ANSI ramp of `auto`.
+    fn stops(&self, truecolor: bool) -> Option<&[Rgb]> {
+        match self {
+            Palette::Auto if truecolor => Some(HEAT),
+            Palette::Auto => None,
+            Palette::Github => Some(GITHUB),
+            Palette::Heat => Some(HEAT),
+            Palette::Viridis => Some(VIRIDIS),
+            Palette::Mono => Some(MONO),
+            Palette::Custom(stops) => Some(stops),
+        }
+    }
+
+    /// Escape for shade `idx` of `levels` (0 is [`DIM`]), in 24-bit color
+    /// with `truecolor` and in the 256-color cube otherwise.
+    pub fn level(&self, idx: usize, levels: usize, truecolor: bool) -> Cow<'static, str> {
+        if idx == 0 || levels <= 1 {
+            return Cow::Borrowed(DIM);
+        }
+        let Some(stops) = self.stops(truecolor) else {
+            let n = ANSI_PALETTE.len();
+            let k = if idx >= levels - 1 {
+                n - 1
+            } else {
+                (idx * (n - 1)) / (levels - 1)
+            };
+            return Cow::Borrowed(ANSI_PALETTE[k]);
+        };
+        let t = match levels {
+            2 => 1.0,
+            _ => (idx.min(levels - 1) - 1) as f64 / (levels - 2) as f64,
+        };
+        let (r, g, b) = interpolate(stops, t);
+        Cow::Owned(if truecolor {
+            format!("\x1b[38;2;{};{};{}m", r, g, b)
+        } else {
+            format!("\x1b[38;5;{}m", xterm_256((r, g, b)))
+        })
+    }
+}
+
+fn parse_hex(s: &str) -> Option<Rgb> {
+    let hex = s.trim().strip_prefix('#')?;
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// The color at `t` (0..=1) along evenly spaced `stops`.
+fn interpolate(stops: &[Rgb], t: f64) -> Rgb {
+    if stops.len() == 1 {
+        return stops[0];
+    }
+    let pos = t.clamp(0.0, 1.0) * (stops.len() - 1) as f64;
+    let i = (pos.floor() as usize).min(stops.len() - 2);
+    let f = pos - i as f64;
+    let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * f).round() as u8;
+    let (a, b) = (stops[i], stops[i + 1]);
+    (mix(a.0, b.0), mix(a.1, b.1), mix(a.2, b.2))
+}
+
+/// Nearest color of the 6x6x6 cube or the gray ramp of the xterm palette.
+fn xterm_256((r, g, b): Rgb) -> u8 {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let nearest = |v: u8| {
+        (0..6)
+            .min_by_key(|&i| (LEVELS[i] as i32 - v as i32).abs())
+            .unwrap_or(0)
+    };
+    let (ri, gi, bi) = (nearest(r), nearest(g), nearest(b));
+    let cube = (LEVELS[ri], LEVELS[gi], LEVELS[bi]);
+    let gray_i = ((r as u32 + g as u32 + b as u32) / 3).saturating_sub(8) / 10;
+    let gray_i = gray_i.min(23) as u8;
+    let gray_v = 8 + gray_i * 10;
+    let dist = |(x, y, z): Rgb| {
+        let d = |p: u8, q: u8| (p as i32 - q as i32).pow(2);
+        d(x, r) + d(y, g) + d(z, b)
+    };
+    if dist((gray_v, gray_v, gray_v)) < dist(cube) {
+        232 + gray_i
+    } else {
+        16 + 36 * ri as u8 + 6 * gi as u8 + bi as u8
+    }
+}
+
+static ACTIVE_PALETTE: RwLock<Palette> = RwLock::new(Palette::Auto);
+
+/// Select the palette of later charts.
+pub fn set_palette(palette: Palette) {
+    *ACTIVE_PALETTE.write().unwrap_or_else(|e| e.into_inner()) = palette;
+}
+
+pub fn palette() -> Palette {
+    ACTIVE_PALETTE
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+/// Escape for shade `idx` of `levels` in the active palette.
+pub fn level_color(idx: usize, levels: usize) -> Cow<'static, str> {
+    ACTIVE_PALETTE
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .level(idx, levels, truecolor_enabled())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palette_parse() {
+        assert_eq!(Palette::parse("Viridis"), Ok(Palette::Viridis));
+        assert_eq!(
+            Palette::parse("#0E4429, #39d353"),
+            Ok(Palette::Custom(vec![(14, 68, 41), (57, 211, 83)]))
+        );
+        assert!(Palette::parse("#0e4429").is_err());
+        assert!(Palette::parse("#0e44,#39d353").is_err());
+        assert!(Palette::parse("rainbow")
+            .unwrap_err()
+            .contains("Expected auto, github"));
+    }
+
+    #[test]
+    fn test_palette_levels() {
+        assert_eq!(Palette::Auto.level(0, 10, true), DIM);
+        assert_eq!(Palette::Auto.level(9, 10, false), "\x1b[91m");
+        assert_eq!(Palette::Auto.level(1, 10, false), "\x1b[34m");
+        assert_eq!(Palette::Heat.level(1, 10, true), "\x1b[38;2;46;160;67m");
+        assert_eq!(Palette::Heat.level(9, 10, true), "\x1b[38;2;215;48;39m");
+        assert_eq!(Palette::Auto.level(9, 10, true), "\x1b[38;2;215;48;39m");
+        assert_eq!(Palette::Mono.level(9, 10, true), "\x1b[38;2;238;238;238m");
+        // Halfway between two custom stops.
+        let custom = Palette::Custom(vec![(0, 0, 0), (200, 100, 50)]);
+        assert_eq!(custom.level(6, 12, true), "\x1b[38;2;100;50;25m");
+        // 256-color fallback: pure colors land in the cube, grays on the ramp.
+        assert_eq!(xterm_256((255, 0, 0)), 196);
+        assert_eq!(xterm_256((238, 238, 238)), 255);
+        assert_eq!(
+            Palette::Custom(vec![(0, 0, 0), (255, 0, 0)]).level(9, 10, false),
+            "\x1b[38;5;196m"
+        );
+    }
+}
@@ -0,0 +1,382 @@
+//! PNG charts (`--output chart.png`, `charts` feature) for slide decks: the
+//! SVG heatmap and bar layouts rasterized onto a pixel canvas, with a built-in
+//! 3x5 bitmap font and an uncompressed (stored) PNG encoder.
+
+use crate::error::GitInsightsError;
+use crate::output::write_export;
+use crate::palette::Rgb;
+use crate::svg::level_for;
+use crate::xlsx::crc32;
+
+/// [`crate::svg::RAMP`] as RGB.
+pub const RAMP: [Rgb; 5] = [
+    (235, 237, 240),
+    (155, 233, 168),
+    (64, 196, 99),
+    (48, 161, 78),
+    (33, 110, 57),
+];
+
+const BACKGROUND: Rgb = (255, 255, 255);
+const AXIS: Rgb = (87, 96, 106);
+const TITLE: Rgb = (36, 41, 47);
+
+/// Glyphs are drawn at this many pixels per font dot.
+const TEXT_SCALE: usize = 2;
+/// Horizontal advance of one character in pixels.
+const CHAR_W: usize = 4 * TEXT_SCALE;
+/// Height of a line of text in pixels.
+const CHAR_H: usize = 5 * TEXT_SCALE;
+
+/// An RGB raster, row-major from the top left.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Canvas {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Rgb>,
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize) -> Canvas {
+        Canvas {
+            width,
+            height,
+            pixels: vec![BACKGROUND; width * height],
+        }
+    }
+
+    /// Fill a rectangle, clipped to the canvas.
+    pub fn fill_rect(&mut self, x: usize, y: usize, w: usize, h: usize, color: Rgb) {
+        for row in y..(y + h).min(self.height) {
+            for col in x..(x + w).min(self.width) {
+                self.pixels[row * self.width + col] = color;
+            }
+        }
+    }
+
+    /// Draw `text` with its top left corner at `(x, y)`. Letters are shown
+    /// in upper case and characters without a glyph as `?`.
+    pub fn text(&mut self, x: usize, y: usize, text: &str, color: Rgb) {
+        for (i, ch) in text.chars().enumerate() {
+            let glyph = glyph(ch.to_ascii_uppercase());
+            let left = x + i * CHAR_W;
+            for (row, bits) in glyph.iter().enumerate() {
+                for col in 0..3 {
+                    if bits & (0b100 >> col) != 0 {
+                        self.fill_rect(
+                            left + col * TEXT_SCALE,
+                            y + row * TEXT_SCALE,
+                            TEXT_SCALE,
+                            TEXT_SCALE,
+                            color,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// The image as a PNG file: 8-bit RGB, filter 0 and stored deflate blocks.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut raw = Vec::with_capacity((self.width * 3 + 1) * self.height);
+        for row in self.pixels.chunks(self.width.max(1)) {
+            raw.push(0);
+            for &(r, g, b) in row {
+                raw.extend([r, g, b]);
+            }
+        }
+        let mut ihdr = Vec::with_capacity(13);
+        ihdr.extend((self.width as u32).to_be_bytes());
+        ihdr.extend((self.height as u32).to_be_bytes());
+        ihdr.extend([8, 2, 0, 0, 0]);
+
+        let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+        push_chunk(&mut out, b"IHDR", &ihdr);
+        push_chunk(&mut out, b"IDAT", &zlib_stored(&raw));
+        push_chunk(&mut out, b"IEND", &[]);
+        out
+    }
+}
+
+fn push_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+/// A zlib stream of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    if data.is_empty() {
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    let mut blocks = data.chunks(u16::MAX as usize).peekable();
+    while let Some(block) = blocks.next() {
+        out.push(blocks.peek().is_none() as u8);
+        let len = block.len() as u16;
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65_521;
+        b = (b + a) % 65_521;
+    }
+    (b << 16) | a
+}
+
+/// Rows of a 3x5 glyph, top to bottom, leftmost dot in the high bit.
+fn glyph(ch: char) -> [u8; 5] {
+    match ch {
+        ' ' => [0, 0, 0, 0, 0],
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '-' => [0, 0, 0b111, 0, 0],
+        '+' => [0, 0b010, 0b111, 0b010, 0],
+        '=' => [0, 0b111, 0, 0b111, 0],
+        '~' => [0, 0b011, 0b110, 0, 0],
+        '_' => [0, 0, 0, 0, 0b111],
+        ':' => [0, 0b010, 0, 0b010, 0],
+        '.' => [0, 0, 0, 0, 0b010],
+        ',' => [0, 0, 0, 0b010, 0b100],
+        '\'' => [0b010, 0b010, 0, 0, 0],
+        '(' => [0b001, 0b010, 0b010, 0b010, 0b001],
+        ')' => [0b100, 0b010, 0b010, 0b010, 0b100],
+        '<' => [0b001, 0b010, 0b100, 0b010, 0b001],
+        '>' => [0b100, 0b010, 0b001, 0b010, 0b100],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+        '#' => [0b101, 0b111, 0b101, 0b111, 0b101],
+        _ => [0b111, 0b001, 0b010, 0, 0b010],
+    }
+}
+
+fn text_width(text: &str) -> usize {
+    text.chars().count() * CHAR_W
+}
+
+/// Draw the labels left to right at `y`, skipping any that would overlap the
+/// previous one.
+fn axis_labels(canvas: &mut Canvas, labels: impl Iterator<Item = (usize, String)>, y: usize) {
+    let mut free_x = 0;
+    for (x, label) in labels {
+        if label.is_empty() || x < free_x {
+            continue;
+        }
+        canvas.text(x, y, &label, AXIS);
+        free_x = x + text_width(&label) + CHAR_W;
+    }
+}
+
+/// Rasterize a grid of cells like [`crate::svg::render_heatmap`], with axes
+/// and a color scale.
+pub fn render_heatmap(
+    rows: &[Vec<usize>],
+    row_labels: &[String],
+    col_labels: &[String],
+    title: Option<&str>,
+) -> Canvas {
+    const CELL: usize = 14;
+    const GAP: usize = 2;
+    const PAD: usize = 8;
+    let left = PAD + row_labels.iter().map(|l| text_width(l)).max().unwrap_or(0) + CHAR_W;
+    let title_h = if title.is_some() { CHAR_H + 10 } else { 0 };
+    let top = PAD + title_h + CHAR_H + 6;
+    let cols = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    let max = rows.iter().flatten().copied().max().unwrap_or(0);
+    let grid_bottom = top + rows.len() * (CELL + GAP);
+    let legend = format!("More (max={})", max);
+    let legend_w = text_width("Less") + 6 + RAMP.len() * 14 + 4 + text_width(&legend);
+    let width = (left + cols * (CELL + GAP))
+        .max(left + legend_w)
+        .max(PAD + title.map(text_width).unwrap_or(0))
+        + PAD;
+    let height = grid_bottom + 8 + 12 + PAD;
+
+    let mut canvas = Canvas::new(width, height);
+    if let Some(t) = title {
+        canvas.text(PAD, PAD, t, TITLE);
+    }
+    let labels = col_labels
+        .iter()
+        .take(cols)
+        .enumerate()
+        .map(|(c, l)| (left + c * (CELL + GAP), l.clone()));
+    axis_labels(&mut canvas, labels, top - CHAR_H - 4);
+    for (r, row) in rows.iter().enumerate() {
+        let y = top + r * (CELL + GAP);
+        if let Some(label) = row_labels.get(r) {
+            canvas.text(PAD, y + (CELL - CHAR_H) / 2, label, AXIS);
+        }
+        for (c, &v) in row.iter().enumerate() {
+            let color = RAMP[level_for(v, max, RAMP.len())];
+            canvas.fill_rect(left + c * (CELL + GAP), y, CELL, CELL, color);
+        }
+    }
+
+    // Color scale: "Less [][][][][] More (max=N)"
+    let ly = grid_bottom + 8;
+    canvas.text(left, ly + 1, "Less", AXIS);
+    let swatch_x = left + text_width("Less") + 6;
+    for (i, &color) in RAMP.iter().enumerate() {
+        canvas.fill_rect(swatch_x + i * 14, ly, 12, 12, color);
+    }
+    canvas.text(swatch_x + RAMP.len() * 14 + 4, ly + 1, &legend, AXIS);
+    canvas
+}
+
+/// Rasterize a vertical bar chart, one bar per count, with the peak on the
+/// y axis and `labels` under the bars.
+pub fn render_bars(labels: &[String], counts: &[usize], title: Option<&str>) -> Canvas {
+    const PLOT_H: usize = 160;
+    const PAD: usize = 8;
+    let bar = (800 / counts.len().max(1)).clamp(2, 16);
+    let gap = (bar / 4).max(1);
+    let max = counts.iter().copied().max().unwrap_or(0);
+    let left = PAD + text_width(&max.to_string()) + CHAR_W;
+    let title_h = if title.is_some() { CHAR_H + 10 } else { 0 };
+    let top = PAD + title_h;
+    let width =
+        (left + counts.len() * (bar + gap)).max(PAD + title.map(text_width).unwrap_or(0)) + PAD;
+    let height = top + PLOT_H + 6 + CHAR_H + PAD;
+
+    let mut canvas = Canvas::new(width, height);
+    if let Some(t) = title {
+        canvas.text(PAD, PAD, t, TITLE);
+    }
+    canvas.text(PAD, top, &max.to_string(), AXIS);
+    canvas.text(PAD, top + PLOT_H - CHAR_H, "0", AXIS);
+    canvas.fill_rect(left - 2, top, 1, PLOT_H, AXIS);
+    for (i, &c) in counts.iter().enumerate() {
+        let bar_h = if max == 0 {
+            0
+        } else {
+            (c * PLOT_H).div_ceil(max)
+        };
+        let color = RAMP[level_for(c, max, RAMP.len())];
+        let x = left + i * (bar + gap);
+        canvas.fill_rect(x, top + PLOT_H - bar_h, bar, bar_h, color);
+    }
+    let labels = labels
+        .iter()
+        .take(counts.len())
+        .enumerate()
+        .map(|(i, l)| (left + i * (bar + gap), l.clone()));
+    axis_labels(&mut canvas, labels, top + PLOT_H + 6);
+    canvas
+}
+
+/// Write `canvas` as a PNG file to `path` (`-` for stdout).
+pub fn write_png(path: &str, canvas: &Canvas) -> Result<(), GitInsightsError> {
+    write_export(Some(path), path, &canvas.encode())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_png_structure() {
+        let mut canvas = Canvas::new(3, 2);
+        canvas.fill_rect(1, 1, 5, 5, (255, 0, 0));
+        assert_eq!(canvas.pixels[4], (255, 0, 0));
+        assert_eq!(canvas.pixels[2], BACKGROUND);
+        let png = canvas.encode();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[16..24], &[0, 0, 0, 3, 0, 0, 0, 2]);
+        let ihdr_crc = u32::from_be_bytes(png[29..33].try_into().unwrap());
+        assert_eq!(ihdr_crc, crc32(&png[12..29]));
+        assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82]));
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+
+    #[test]
+    fn test_zlib_stored_blocks() {
+        let data = vec![7u8; 70_000];
+        let z = zlib_stored(&data);
+        // Header, two blocks of 5 header bytes each, checksum.
+        assert_eq!(z.len(), 2 + 5 + 5 + data.len() + 4);
+        assert_eq!(&z[2..7], &[0, 0xff, 0xff, 0, 0]);
+        assert_eq!(z[7 + 65_535], 1);
+        assert_eq!(
+            zlib_stored(&[]),
+            [0x78, 0x01, 1, 0, 0, 0xff, 0xff, 0, 0, 0, 1]
+        );
+    }
+
+    #[test]
+    fn test_render_heatmap_and_bars() {
+        let rows = vec![vec![0, 1, 2], vec![3, 0, 0]];
+        let labels = vec!["Sun".to_string(), "Mon".to_string()];
+        let cols = vec!["00".to_string(), String::new(), "02".to_string()];
+        let canvas = render_heatmap(&rows, &labels, &cols, Some("Title"));
+        assert_eq!(canvas.pixels.len(), canvas.width * canvas.height);
+        assert!(canvas.pixels.contains(&RAMP[4]));
+        assert!(canvas.pixels.contains(&TITLE));
+
+        let bars = render_bars(&["-1w".to_string(), "now".to_string()], &[1, 4], None);
+        assert_eq!(bars.height, 8 + 160 + 6 + CHAR_H + 8);
+        assert!(bars.pixels.contains(&RAMP[4]) && bars.pixels.contains(&RAMP[1]));
+    }
+
+    #[test]
+    fn test_axis_labels_skip_overlaps() {
+        let mut canvas = Canvas::new(100, 20);
+        let labels = [
+            (0, "abc".to_string()),
+            (20, "x".to_string()),
+            (40, "y".to_string()),
+        ];
+        axis_labels(&mut canvas, labels.into_iter(), 0);
+        // "x" would overlap "abc"; "y" is drawn.
+        let inked = |x0: usize, x1: usize| {
+            (0..CHAR_H).any(|y| (x0..x1).any(|x| canvas.pixels[y * 100 + x] == AXIS))
+        };
+        assert!(inked(40, 40 + CHAR_W));
+        assert!(!inked(3 * CHAR_W, 40));
+    }
+}
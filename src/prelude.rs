@@ -0,0 +1,40 @@
+//! Convenience re-exports for library users.
+//!
+//! ```no_run
+//! use git_insights::prelude::*;
+//!
+//! set_active(CommitFilter::default());
+//! let stats: StatsMap = gather_loc_and_file_statsx(true).unwrap();
+//! let repo: RepoStats = compute_stats(true).unwrap();
+//! println!("{} lines, top author {:?}", repo.total_loc, repo.authors.first());
+//! ```
+//!
+//! Public enums are `#[non_exhaustive]`, so match them with a wildcard arm;
+//! new variants (e.g. more code-frequency groups) are not breaking changes.
+
+pub use crate::badge::BadgeMetric;
+pub use crate::cli::{Cli, Commands, HelpTopic};
+pub use crate::code_frequency::{
+    compute_code_frequency, compute_code_frequency_weighted, render_code_frequency,
+    CodeFrequencyData, FrequencyWeight, Group, HeatmapKind,
+};
+pub use crate::codeowners::{CodeownersRule, RuleReport, RuleStatus};
+pub use crate::commits::{collect_commits, Commit};
+pub use crate::error::{ExitKind, GitInsightsError};
+pub use crate::filter::{set_active, AuthorScope, CommitFilter};
+pub use crate::git::Repo;
+pub use crate::hotspots::{gather_hotspots, Hotspot, HotspotSize};
+pub use crate::identity::NameNormalization;
+pub use crate::json::JsonValue;
+pub use crate::output::ColorChoice;
+pub use crate::palette::{set_palette, Palette};
+pub use crate::report::{ActivityData, ReportData, ReportFormat};
+pub use crate::stats::{
+    compute_stats, gather_commit_statsx, gather_loc_and_file_statsx, set_text_detection,
+    AuthorStats, RepoStats, StatsMap, StatsSort, TextDetection, UserOwnership, UserStats,
+};
+pub use crate::view::ViewFormat;
+pub use crate::visualize::{
+    compute_timeline, compute_timeline_by, ChartSize, HeatmapScale, TimelineData,
+    TimelineGranularity, TimelineMetric, TimelineStyle, TimelineView,
+};
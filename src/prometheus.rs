@@ -0,0 +1,180 @@
+use crate::error::GitInsightsError;
+use crate::output::write_export;
+use crate::stats::{gather_author_statsx, AuthorStats};
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::fs;
+
+/// Escape a label value per the Prometheus text exposition format.
+fn escape_label(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+fn write_header(out: &mut String, name: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+}
+
+fn write_author_gauge(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    authors: &[(String, AuthorStats)],
+    value: impl Fn(&AuthorStats) -> usize,
+) {
+    write_header(out, name, help);
+    for (author, stats) in authors {
+        let _ = writeln!(
+            out,
+            "{}{{author=\"{}\"}} {}",
+            name,
+            escape_label(author),
+            value(stats)
+        );
+    }
+}
+
+/// Render author stats as Prometheus gauges (text exposition format).
+pub fn render_prometheus(authors: &[(String, AuthorStats)]) -> String {
+    let mut out = String::new();
+    write_author_gauge(
+        &mut out,
+        "git_insights_author_loc",
+        "Surviving lines of code attributed to the author.",
+        authors,
+        |s| s.loc,
+    );
+    write_author_gauge(
+        &mut out,
+        "git_insights_author_commits",
+        "Commits authored.",
+        authors,
+        |s| s.commits,
+    );
+    write_author_gauge(
+        &mut out,
+        "git_insights_author_files",
+        "Files the author owns surviving lines in.",
+        authors,
+        |s| s.files.len(),
+    );
+
+    let files: HashSet<&String> = authors.iter().flat_map(|(_, s)| s.files.iter()).collect();
+    let totals = [
+        (
+            "git_insights_total_loc",
+            "Surviving lines of code in HEAD.",
+            authors.iter().map(|(_, s)| s.loc).sum::<usize>(),
+        ),
+        (
+            "git_insights_total_commits",
+            "Commits in the analyzed history.",
+            authors.iter().map(|(_, s)| s.commits).sum(),
+        ),
+        (
+            "git_insights_total_files",
+            "Text files with surviving lines.",
+            files.len(),
+        ),
+        ("git_insights_authors", "Distinct authors.", authors.len()),
+    ];
+    for (name, help, value) in totals {
+        write_header(&mut out, name, help);
+        let _ = writeln!(out, "{} {}", name, value);
+    }
+    out
+}
+
+/// Write metrics for the current repository to `path`.
+///
+/// The file is written to a temporary sibling and renamed into place, so a
+/// node_exporter textfile collector never scrapes a half-written file.
+/// `-` writes to stdout.
+pub fn export_prometheus(path: &str) -> Result<(), GitInsightsError> {
+    let mut authors: Vec<(String, AuthorStats)> = gather_author_statsx(true)?.into_iter().collect();
+    authors.sort_by(|a, b| a.0.cmp(&b.0));
+    let text = render_prometheus(&authors);
+    if path == "-" {
+        return write_export(Some(path), path, text.as_bytes());
+    }
+
+    let tmp = format!("{}.tmp", path);
+    fs::write(&tmp, text).map_err(|e| format!("failed to write {}: {}", tmp, e))?;
+    fs::rename(&tmp, path).map_err(|e| format!("failed to rename {} to {}: {}", tmp, path, e))?;
+    println!("Successfully exported to {}", path);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_prometheus_gauges() {
+        let mut files = HashSet::new();
+        files.insert("a.rs".to_string());
+        files.insert("b.rs".to_string());
+        let authors = vec![
+            (
+                "Alice \"Al\"".to_string(),
+                AuthorStats {
+                    loc: 10,
+                    commits: 3,
+                    files: files.clone(),
+                    ..Default::default()
+                },
+            ),
+            (
+                "Bob".to_string(),
+                AuthorStats {
+                    loc: 5,
+                    commits: 1,
+                    files,
+                    ..Default::default()
+                },
+            ),
+        ];
+        let text = render_prometheus(&authors);
+        assert!(text.contains("# TYPE git_insights_author_loc gauge\n"));
+        assert!(text.contains("git_insights_author_loc{author=\"Alice \\\"Al\\\"\"} 10\n"));
+        assert!(text.contains("git_insights_author_commits{author=\"Bob\"} 1\n"));
+        assert!(text.contains("\ngit_insights_total_loc 15\n"));
+        assert!(text.contains("\ngit_insights_total_commits 4\n"));
+        assert!(text.contains("\ngit_insights_total_files 2\n"));
+        assert!(text.contains("\ngit_insights_authors 2\n"));
+    }
+
+    #[test]
+    fn test_export_prometheus_writes_file() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        repo.commit_with_epoch(
+            "Alice",
+            "alice@test_git_insights.com",
+            "a.txt",
+            "a\nb\n",
+            1_700_000_000,
+        )
+        .expect("commit");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        let result = export_prometheus("metrics.prom");
+        std::env::set_current_dir(old).ok();
+
+        result.expect("export ok");
+        let text = fs::read_to_string(repo.path.join("metrics.prom")).expect("metrics written");
+        assert!(text.contains("git_insights_author_loc{author=\"Alice\"} 2\n"));
+        assert!(text.contains("git_insights_total_commits 1\n"));
+        assert!(!repo.path.join("metrics.prom.tmp").exists());
+    }
+}
@@ -0,0 +1,193 @@
+//! `quality`: revert and fixup rates per author and per month, since a
+//! rising share of reverts is an early warning sign.
+
+use crate::code_frequency::ymd_from_unix;
+use crate::commits::{collect_commits, Commit};
+use crate::error::GitInsightsError;
+use crate::messages::classify_subject;
+use crate::output::truncate;
+use std::collections::BTreeMap;
+
+/// What a commit subject says about the commit it follows up on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FollowUp {
+    /// `Revert "..."` as written by `git revert`, or a `revert:` type.
+    Revert,
+    /// `fixup!`, `amend!` or `squash!` from `git commit --fixup/--squash`.
+    Fixup,
+}
+
+/// The follow-up kind of a subject, if any.
+pub fn follow_up_kind(subject: &str) -> Option<FollowUp> {
+    let subject = subject.trim_start();
+    if subject.starts_with("Revert \"") || classify_subject(subject) == "revert" {
+        Some(FollowUp::Revert)
+    } else if ["fixup!", "amend!", "squash!"]
+        .iter()
+        .any(|p| subject.starts_with(p))
+    {
+        Some(FollowUp::Fixup)
+    } else {
+        None
+    }
+}
+
+/// Commit, revert and fixup counts of one author or month.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QualityCounts {
+    pub commits: usize,
+    pub reverts: usize,
+    pub fixups: usize,
+}
+
+impl QualityCounts {
+    fn add(&mut self, kind: Option<FollowUp>) {
+        self.commits += 1;
+        match kind {
+            Some(FollowUp::Revert) => self.reverts += 1,
+            Some(FollowUp::Fixup) => self.fixups += 1,
+            None => {}
+        }
+    }
+
+    pub fn revert_pct(&self) -> f64 {
+        self.reverts as f64 * 100.0 / self.commits.max(1) as f64
+    }
+
+    pub fn fixup_pct(&self) -> f64 {
+        self.fixups as f64 * 100.0 / self.commits.max(1) as f64
+    }
+}
+
+/// Counts over all commits, per author and per `YYYY-MM` of author time.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QualityReport {
+    pub total: QualityCounts,
+    pub authors: BTreeMap<String, QualityCounts>,
+    pub months: BTreeMap<String, QualityCounts>,
+}
+
+/// Tally reverts and fixups; merges count only with `--include-merges`.
+pub fn quality_report(commits: &[Commit], keep_merges: bool) -> QualityReport {
+    let mut report = QualityReport::default();
+    for c in commits.iter().filter(|c| keep_merges || !c.is_merge()) {
+        let kind = follow_up_kind(&c.subject);
+        let (y, m, _) = ymd_from_unix(c.author_time);
+        report.total.add(kind);
+        report
+            .authors
+            .entry(c.author_name.clone())
+            .or_default()
+            .add(kind);
+        report
+            .months
+            .entry(format!("{:04}-{:02}", y, m))
+            .or_default()
+            .add(kind);
+    }
+    report
+}
+
+fn print_counts_table(label: &str, rows: &[(&String, &QualityCounts)]) {
+    println!(
+        "| {:<28} | {:>7} | {:>7} | {:>8} | {:>6} | {:>7} |",
+        label, "commits", "reverts", "revert %", "fixups", "fixup %"
+    );
+    println!(
+        "|:{:-<29}|{:->9}|{:->9}|{:->10}|{:->8}|{:->9}|",
+        "", "", "", "", "", ""
+    );
+    for (key, c) in rows {
+        println!(
+            "| {:<28} | {:>7} | {:>7} | {:>8.1} | {:>6} | {:>7.1} |",
+            truncate(key, 28),
+            c.commits,
+            c.reverts,
+            c.revert_pct(),
+            c.fixups,
+            c.fixup_pct()
+        );
+    }
+}
+
+/// Print the overall rates, then one table per month and one per author.
+pub fn run_quality() -> Result<(), GitInsightsError> {
+    let keep_merges = crate::filter::active().include_merges;
+    let report = quality_report(&collect_commits()?, keep_merges);
+    if report.total.commits == 0 {
+        println!("No commits.");
+        return Ok(());
+    }
+    println!(
+        "Reverts: {} of {} commits ({:.1}%); fixup!/squash!: {} ({:.1}%)",
+        report.total.reverts,
+        report.total.commits,
+        report.total.revert_pct(),
+        report.total.fixups,
+        report.total.fixup_pct()
+    );
+    println!();
+    let months: Vec<(&String, &QualityCounts)> = report.months.iter().collect();
+    print_counts_table("month", &months);
+    println!();
+    let mut authors: Vec<(&String, &QualityCounts)> = report.authors.iter().collect();
+    authors.sort_by(|a, b| b.1.commits.cmp(&a.1.commits).then_with(|| a.0.cmp(b.0)));
+    print_counts_table("author", &authors);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_follow_up_kind() {
+        assert_eq!(
+            follow_up_kind("Revert \"feat: add cache\""),
+            Some(FollowUp::Revert)
+        );
+        assert_eq!(
+            follow_up_kind("revert(cli): drop --fast"),
+            Some(FollowUp::Revert)
+        );
+        assert_eq!(follow_up_kind("fixup! fix: typo"), Some(FollowUp::Fixup));
+        assert_eq!(follow_up_kind("squash! docs"), Some(FollowUp::Fixup));
+        assert_eq!(follow_up_kind("amend! feat: x"), Some(FollowUp::Fixup));
+        assert_eq!(follow_up_kind("Reverted the cache change"), None);
+        assert_eq!(follow_up_kind("fix: fixup! parsing"), None);
+    }
+
+    #[test]
+    fn test_quality_report() {
+        let commit = |name: &str, ts: u64, subject: &str, parents: usize| Commit {
+            author_name: name.to_string(),
+            author_time: ts,
+            subject: subject.to_string(),
+            parents: vec!["p".to_string(); parents],
+            ..Default::default()
+        };
+        let commits = vec![
+            commit("Ann", 1_704_067_200, "feat: cache", 1), // 2024-01-01
+            commit("Ann", 1_706_745_600, "Revert \"feat: cache\"", 1), // 2024-02-01
+            commit("Bob", 1_706_745_600, "fixup! feat: cache", 1),
+            commit("Bob", 1_706_745_600, "docs: readme", 1),
+            commit("Max", 1_706_745_600, "Revert \"Merge branch 'x'\"", 2),
+        ];
+        let report = quality_report(&commits, false);
+        assert_eq!(
+            report.total,
+            QualityCounts {
+                commits: 4,
+                reverts: 1,
+                fixups: 1
+            }
+        );
+        assert_eq!(report.authors["Ann"].revert_pct(), 50.0);
+        assert_eq!(report.authors["Bob"].fixups, 1);
+        assert_eq!(report.months["2024-01"].reverts, 0);
+        assert_eq!(report.months["2024-02"].commits, 3);
+        assert!(!report.authors.contains_key("Max"));
+        assert_eq!(quality_report(&commits, true).total.reverts, 2);
+    }
+}
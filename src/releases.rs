@@ -0,0 +1,250 @@
+use crate::code_frequency::ymd_from_unix;
+use crate::error::GitInsightsError;
+use crate::git::{run_command, run_command_line_iter};
+use crate::identity::NameNormalization;
+use crate::output::truncate;
+use std::collections::BTreeMap;
+
+/// Label for commits after the newest tag.
+pub const UNRELEASED: &str = "(unreleased)";
+
+/// Activity between one tag and the previous one.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReleaseStats {
+    pub name: String,
+    /// Previous tag; `None` for the first release (counted from the root).
+    pub from: Option<String>,
+    /// Tag creation time (0 for the unreleased row).
+    pub date: u64,
+    pub commits: usize,
+    /// Commits per author name.
+    pub authors: BTreeMap<String, usize>,
+    pub added: usize,
+    pub deleted: usize,
+}
+
+impl ReleaseStats {
+    /// Authors by commit count, most active first.
+    pub fn top_authors(&self) -> Vec<(&str, usize)> {
+        let mut v: Vec<(&str, usize)> =
+            self.authors.iter().map(|(a, c)| (a.as_str(), *c)).collect();
+        v.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        v
+    }
+}
+
+/// Fold `git log --numstat --format=%x1f%aN%x1f%aE` output into `stats`,
+/// skipping commits whose author fails `allow` and normalizing author names.
+/// Binary files count as 0 lines.
+pub fn accumulate_numstat(
+    out: &str,
+    stats: &mut ReleaseStats,
+    allow: impl Fn(&str, &str) -> bool,
+    names: NameNormalization,
+) {
+    accumulate_numstat_lines(out.lines(), stats, allow, names)
+}
+
+/// [`accumulate_numstat`] over lines as git streams them.
+fn accumulate_numstat_lines<S: AsRef<str>>(
+    lines: impl Iterator<Item = S>,
+    stats: &mut ReleaseStats,
+    allow: impl Fn(&str, &str) -> bool,
+    names: NameNormalization,
+) {
+    let mut counting = false;
+    for line in lines {
+        let line = line.as_ref();
+        if let Some(header) = line.strip_prefix('\x1f') {
+            let (name, email) = header.split_once('\x1f').unwrap_or((header, ""));
+            counting = allow(name, email);
+            if counting {
+                stats.commits += 1;
+                *stats
+                    .authors
+                    .entry(names.apply(name.to_string()))
+                    .or_insert(0) += 1;
+            }
+        } else if counting {
+            let mut parts = line.split('\t');
+            if let (Some(a), Some(d), Some(_)) = (parts.next(), parts.next(), parts.next()) {
+                stats.added += a.parse::<usize>().unwrap_or(0);
+                stats.deleted += d.parse::<usize>().unwrap_or(0);
+            }
+        }
+    }
+}
+
+/// Tags matching `glob` (all when `None`), oldest first by creation date.
+pub fn list_tags(glob: Option<&str>) -> Result<Vec<(String, u64)>, GitInsightsError> {
+    let pattern = format!("refs/tags/{}", glob.unwrap_or(""));
+    let out = run_command(&[
+        "for-each-ref",
+        "--sort=creatordate",
+        "--format=%(refname:short)%09%(creatordate:unix)",
+        &pattern,
+    ])?;
+    Ok(out
+        .lines()
+        .filter_map(|l| {
+            let (name, ts) = l.split_once('\t')?;
+            Some((name.to_string(), ts.trim().parse().unwrap_or(0)))
+        })
+        .collect())
+}
+
+/// Per-release stats for consecutive tag pairs, plus unreleased work on HEAD.
+pub fn gather_releases(glob: Option<&str>) -> Result<Vec<ReleaseStats>, GitInsightsError> {
+    let authors = crate::filter::author_matcher()?;
+    let names = crate::identity::active_normalization();
+    let tags = list_tags(glob)?;
+    if tags.is_empty() {
+        return Err(match glob {
+            Some(g) => format!("no tags match '{}'", g),
+            None => "repository has no tags".to_string(),
+        }
+        .into());
+    }
+
+    let mut targets: Vec<(String, String, u64)> = tags
+        .iter()
+        .map(|(t, ts)| (t.clone(), t.clone(), *ts))
+        .collect();
+    targets.push((UNRELEASED.to_string(), "HEAD".to_string(), 0));
+
+    let mut releases = Vec::new();
+    let mut prev: Option<String> = None;
+    for (name, rev, date) in targets {
+        let range = match &prev {
+            Some(p) => format!("{}..{}", p, rev),
+            None => rev.clone(),
+        };
+        let mut stats = ReleaseStats {
+            name,
+            from: prev.clone(),
+            date,
+            ..Default::default()
+        };
+        run_command_line_iter(
+            &[
+                "--no-pager",
+                "log",
+                "--no-merges",
+                "--numstat",
+                "--format=\x1f%aN\x1f%aE",
+                &range,
+            ],
+            |lines| accumulate_numstat_lines(lines, &mut stats, |n, e| authors.allows(n, e), names),
+        )?;
+        if rev != "HEAD" || stats.commits > 0 {
+            releases.push(stats);
+        }
+        prev = Some(rev);
+    }
+    Ok(releases)
+}
+
+/// Print one row per release, newest last.
+pub fn run_releases(glob: Option<&str>, top: usize) -> Result<(), GitInsightsError> {
+    let releases = gather_releases(glob)?;
+    println!(
+        "| {:<20} | {:<10} | {:>6} | {:>7} | {:>8} | {:>8} | {:<40} |",
+        "release", "date", "coms", "authors", "added", "deleted", "top contributors"
+    );
+    println!(
+        "|:{:-<21}|:{:-<11}|{:->8}|{:->9}|{:->10}|{:->10}|:{:-<41}|",
+        "", "", "", "", "", "", ""
+    );
+    for r in &releases {
+        let date = if r.date == 0 {
+            "-".to_string()
+        } else {
+            let (y, m, d) = ymd_from_unix(r.date);
+            format!("{:04}-{:02}-{:02}", y, m, d)
+        };
+        let top_list = r
+            .top_authors()
+            .iter()
+            .take(top)
+            .map(|(a, c)| format!("{} ({})", a, c))
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!(
+            "| {:<20} | {:<10} | {:>6} | {:>7} | {:>8} | {:>8} | {:<40} |",
+            truncate(&r.name, 20),
+            date,
+            r.commits,
+            r.authors.len(),
+            r.added,
+            r.deleted,
+            truncate(&top_list, 40)
+        );
+    }
+    if let Some(heaviest) = releases
+        .iter()
+        .filter(|r| r.name != UNRELEASED)
+        .max_by_key(|r| r.added + r.deleted)
+    {
+        println!();
+        println!(
+            "Heaviest release: {} ({} lines changed)",
+            heaviest.name,
+            heaviest.added + heaviest.deleted
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accumulate_numstat() {
+        let out = "\x1fAnn\x1fann@x.io\n\n3\t1\ta.rs\n-\t-\tlogo.png\n\x1fBob\x1fbob@x.io\n\n10\t0\tb.rs\n\x1fAnn\x1fann@x.io\n\n1\t1\ta.rs\n";
+        let mut all = ReleaseStats::default();
+        accumulate_numstat(out, &mut all, |_, _| true, NameNormalization::None);
+        assert_eq!((all.commits, all.added, all.deleted), (3, 14, 2));
+        assert_eq!(all.top_authors(), vec![("Ann", 2), ("Bob", 1)]);
+
+        let mut no_bob = ReleaseStats::default();
+        accumulate_numstat(
+            out,
+            &mut no_bob,
+            |n, _| n != "Bob",
+            NameNormalization::Casefold,
+        );
+        assert_eq!((no_bob.commits, no_bob.added), (2, 4));
+        assert_eq!(no_bob.top_authors(), vec![("ann", 2)]);
+    }
+
+    #[test]
+    fn test_gather_releases_e2e() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        let p = repo.path.to_str().unwrap().to_string();
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", "a", 1_700_000_000)
+            .expect("commit");
+        run_command(&["-C", &p, "tag", "v1.0"]).expect("tag");
+        repo.commit_with_epoch("Bob", "bob@x.io", "b.txt", "b", 1_700_000_100)
+            .expect("commit");
+        repo.commit_with_epoch("Ann", "ann@x.io", "b.txt", "c", 1_700_000_200)
+            .expect("commit");
+        run_command(&["-C", &p, "tag", "v1.1"]).expect("tag");
+        repo.commit_with_epoch("Cat", "cat@x.io", "c.txt", "d", 1_700_000_300)
+            .expect("commit");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        let releases = gather_releases(Some("v*"));
+        std::env::set_current_dir(old).ok();
+
+        let releases = releases.expect("releases");
+        let names: Vec<&str> = releases.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["v1.0", "v1.1", UNRELEASED]);
+        assert_eq!(releases[1].from.as_deref(), Some("v1.0"));
+        assert_eq!((releases[1].commits, releases[1].authors.len()), (2, 2));
+        assert_eq!(releases[1].added, 2);
+        assert_eq!(releases[2].top_authors(), vec![("Cat", 1)]);
+    }
+}
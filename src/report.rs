@@ -0,0 +1,326 @@
+use crate::code_frequency::{
+    heatmap_dow_by_hod, histogram_day_of_week, histogram_hour_of_day, ymd_from_unix,
+};
+use crate::error::GitInsightsError;
+use crate::git::run_command;
+use crate::output::{rfc3339_utc, write_export};
+use crate::stats::{gather_author_statsx, AuthorStats};
+use crate::svg::{render_bars, render_heatmap, xml_escape};
+use crate::visualize::{
+    collect_commit_timestamps, compute_calendar_heatmap, compute_timeline_weeks,
+};
+use std::fmt::Write as _;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Report output formats.
+#[non_exhaustive]
+pub enum ReportFormat {
+    Html,
+}
+
+/// Everything a report renders, gathered up-front.
+pub struct ReportData {
+    pub repo_name: String,
+    pub generated_at: u64,
+    pub authors: Vec<(String, AuthorStats)>,
+    /// Commit-time charts; `None` when only author stats are available
+    /// (e.g. a report rendered from an exported JSON file).
+    pub activity: Option<ActivityData>,
+}
+
+/// Commit timestamp aggregates shown as charts.
+pub struct ActivityData {
+    pub timeline: Vec<usize>,
+    pub calendar: Vec<Vec<usize>>,
+    pub hour_of_day: [usize; 24],
+    pub day_of_week: [usize; 7],
+    pub dow_by_hod: [[usize; 24]; 7],
+}
+
+const TIMELINE_WEEKS: usize = 26;
+const CALENDAR_WEEKS: usize = 52;
+
+/// Collect stats and timestamp aggregates for the current repository.
+pub fn gather_report_data(now: u64) -> Result<ReportData, GitInsightsError> {
+    let toplevel = run_command(&["rev-parse", "--show-toplevel"])?;
+    let repo_name = toplevel
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or(&toplevel)
+        .to_string();
+
+    let mut authors: Vec<(String, AuthorStats)> = gather_author_statsx(true)?.into_iter().collect();
+    authors.sort_by(|a, b| b.1.loc.cmp(&a.1.loc).then_with(|| a.0.cmp(&b.0)));
+
+    let ts = collect_commit_timestamps()?;
+    Ok(ReportData {
+        repo_name,
+        generated_at: now,
+        authors,
+        activity: Some(ActivityData {
+            timeline: compute_timeline_weeks(&ts, TIMELINE_WEEKS, now),
+            calendar: compute_calendar_heatmap(&ts, CALENDAR_WEEKS, now),
+            hour_of_day: histogram_hour_of_day(&ts),
+            day_of_week: histogram_day_of_week(&ts),
+            dow_by_hod: heatmap_dow_by_hod(&ts),
+        }),
+    })
+}
+
+/// Format Unix seconds as "YYYY-MM-DD HH:MM UTC".
+fn format_utc(t: u64) -> String {
+    let (y, m, d) = ymd_from_unix(t);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+        y,
+        m,
+        d,
+        (t / 3_600) % 24,
+        (t / 60) % 60
+    )
+}
+
+fn render_stats_table(out: &mut String, authors: &[(String, AuthorStats)]) {
+    let total_loc: usize = authors.iter().map(|(_, s)| s.loc).sum();
+    let total_commits: usize = authors.iter().map(|(_, s)| s.commits).sum();
+    let pct = |v: usize, total: usize| {
+        if total > 0 {
+            (v as f32 / total as f32) * 100.0
+        } else {
+            0.0
+        }
+    };
+
+    out.push_str("<table>\n<thead><tr><th>Author</th><th>LOC</th><th>Commits</th><th>Files</th><th>LOC %</th><th>Commits %</th></tr></thead>\n<tbody>\n");
+    for (author, s) in authors {
+        let _ = writeln!(
+            out,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{:.1}</td><td>{:.1}</td></tr>",
+            xml_escape(author),
+            s.loc,
+            s.commits,
+            s.files.len(),
+            pct(s.loc, total_loc),
+            pct(s.commits, total_commits)
+        );
+    }
+    out.push_str("</tbody>\n</table>\n");
+}
+
+fn render_activity(out: &mut String, activity: &ActivityData) {
+    let week_start = crate::filter::active().week_start;
+    let dow_labels: Vec<String> = week_start
+        .day_names()
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    let hour_labels: Vec<String> = (0..24)
+        .map(|h| {
+            if h % 3 == 0 {
+                format!("{:02}", h)
+            } else {
+                String::new()
+            }
+        })
+        .collect();
+
+    let _ = writeln!(
+        out,
+        "<h2>Weekly commits (last {} weeks, old &rarr; new)</h2>",
+        activity.timeline.len()
+    );
+    let _ = writeln!(
+        out,
+        "<div class=\"chart\">{}</div>",
+        render_bars(
+            &vec![String::new(); activity.timeline.len()],
+            &activity.timeline
+        )
+    );
+
+    let _ = writeln!(
+        out,
+        "<h2>Calendar (last {} weeks, {})</h2>",
+        activity.calendar.first().map(|r| r.len()).unwrap_or(0),
+        crate::filter::active().time_zone.label()
+    );
+    let _ = writeln!(
+        out,
+        "<div class=\"chart\">{}</div>",
+        render_heatmap(
+            &week_start.reorder(&activity.calendar),
+            &dow_labels,
+            &[],
+            None
+        )
+    );
+
+    let _ = writeln!(
+        out,
+        "<h2>Code frequency ({})</h2>\n<h3>Hour of day</h3>",
+        crate::filter::active().time_zone.label()
+    );
+    let _ = writeln!(
+        out,
+        "<div class=\"chart\">{}</div>",
+        render_bars(&hour_labels, &activity.hour_of_day)
+    );
+    out.push_str("<h3>Day of week</h3>\n");
+    let _ = writeln!(
+        out,
+        "<div class=\"chart\">{}</div>",
+        render_bars(&dow_labels, &week_start.reorder(&activity.day_of_week))
+    );
+    out.push_str("<h3>Day of week &times; hour of day</h3>\n");
+    let grid: Vec<Vec<usize>> = week_start
+        .reorder(&activity.dow_by_hod)
+        .iter()
+        .map(|r| r.to_vec())
+        .collect();
+    let _ = writeln!(
+        out,
+        "<div class=\"chart\">{}</div>",
+        render_heatmap(&grid, &dow_labels, &hour_labels, None)
+    );
+}
+
+/// Build a self-contained HTML document (inline CSS/SVG, no external assets).
+pub fn render_html_report(data: &ReportData) -> String {
+    let total_loc: usize = data.authors.iter().map(|(_, s)| s.loc).sum();
+    let total_commits: usize = data.authors.iter().map(|(_, s)| s.commits).sum();
+
+    let mut out = String::new();
+    let title = format!("git-insights: {}", xml_escape(&data.repo_name));
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{}\n</style>\n</head>\n<body>\n",
+        title, REPORT_CSS
+    );
+    let _ = writeln!(out, "<h1>{}</h1>", title);
+    let _ = writeln!(
+        out,
+        "<p class=\"meta\">Generated <time datetime=\"{}\">{}</time> &middot; {} commits &middot; {} surviving LOC &middot; {} authors</p>",
+        rfc3339_utc(data.generated_at),
+        format_utc(data.generated_at),
+        total_commits,
+        total_loc,
+        data.authors.len()
+    );
+
+    out.push_str("<h2>Authors</h2>\n");
+    render_stats_table(&mut out, &data.authors);
+
+    if let Some(activity) = &data.activity {
+        render_activity(&mut out, activity);
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+const REPORT_CSS: &str = "\
+body { font-family: -apple-system, 'Segoe UI', Helvetica, Arial, sans-serif; margin: 2em auto; max-width: 1100px; color: #24292f; }
+h1 { font-size: 1.6em; }
+h2 { font-size: 1.25em; border-bottom: 1px solid #d0d7de; padding-bottom: .3em; margin-top: 1.6em; }
+h3 { font-size: 1em; color: #57606a; }
+.meta { color: #57606a; }
+table { border-collapse: collapse; }
+th, td { padding: 4px 10px; border: 1px solid #d0d7de; }
+th { background: #f6f8fa; text-align: left; }
+td:not(:first-child) { text-align: right; font-variant-numeric: tabular-nums; }
+.chart { overflow-x: auto; }";
+
+/// Generate a report and write it next to the current directory.
+pub fn run_report(format: ReportFormat, output: Option<&str>) -> Result<(), GitInsightsError> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| format!("clock error: {e}"))?
+        .as_secs();
+    let data = gather_report_data(now)?;
+    match format {
+        ReportFormat::Html => write_html_report(&data, output),
+    }
+}
+
+/// Default file name for HTML reports.
+pub const REPORT_PATH: &str = "git-insights-report.html";
+
+/// Render `data` as HTML and write it to `output` (default: [`REPORT_PATH`]).
+pub fn write_html_report(data: &ReportData, output: Option<&str>) -> Result<(), GitInsightsError> {
+    write_export(output, REPORT_PATH, render_html_report(data).as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn sample_data() -> ReportData {
+        let mut files = HashSet::new();
+        files.insert("src/lib.rs".to_string());
+        let mut dow_by_hod = [[0usize; 24]; 7];
+        dow_by_hod[1][9] = 3;
+        ReportData {
+            repo_name: "demo".to_string(),
+            generated_at: 1_700_000_000,
+            authors: vec![(
+                "Alice <a&b>".to_string(),
+                AuthorStats {
+                    loc: 10,
+                    commits: 2,
+                    files,
+                    ..Default::default()
+                },
+            )],
+            activity: Some(ActivityData {
+                timeline: vec![0, 1, 4, 2],
+                calendar: vec![vec![0, 1, 2]; 7],
+                hour_of_day: [1; 24],
+                day_of_week: [0, 1, 2, 3, 4, 5, 6],
+                dow_by_hod,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_render_html_report_is_self_contained() {
+        let html = render_html_report(&sample_data());
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("Alice &lt;a&amp;b&gt;"));
+        assert_eq!(html.matches("<svg").count(), 5);
+        assert_eq!(html.matches("<svg").count(), html.matches("</svg>").count());
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("<link"));
+        assert!(!html.contains("src="));
+        assert!(html.contains("2023-11-14 22:13 UTC"));
+
+        let mut stats_only = sample_data();
+        stats_only.activity = None;
+        let html = render_html_report(&stats_only);
+        assert!(html.contains("<h2>Authors</h2>"));
+        assert!(!html.contains("<svg"));
+    }
+
+    #[test]
+    fn test_run_report_writes_file_in_repo() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        repo.commit_with_epoch(
+            "Alice",
+            "alice@test_git_insights.com",
+            "a.txt",
+            "a",
+            1_700_000_000,
+        )
+        .expect("commit");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        let result = run_report(ReportFormat::Html, None);
+        let html = std::fs::read_to_string(repo.path.join("git-insights-report.html"));
+        std::env::set_current_dir(old).ok();
+
+        result.expect("report ok");
+        assert!(html.expect("report written").contains("<td>Alice</td>"));
+    }
+}
@@ -0,0 +1,321 @@
+use crate::error::GitInsightsError;
+use crate::hotspots::gather_churn;
+use crate::json;
+use crate::merges::{blame_line_authors, blame_line_times, run_blame_head, MergeResolver};
+use crate::output::{finish_progress, print_progress, truncate};
+use crate::stats::tracked_text_files_head;
+use std::collections::HashMap;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Relative weight of each signal in the composite score.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RiskWeights {
+    pub churn: f64,
+    pub ownership: f64,
+    pub age: f64,
+    pub size: f64,
+}
+
+impl Default for RiskWeights {
+    fn default() -> Self {
+        RiskWeights {
+            churn: 1.0,
+            ownership: 1.0,
+            age: 1.0,
+            size: 1.0,
+        }
+    }
+}
+
+impl RiskWeights {
+    /// Parse `churn=2,age=0.5`; unnamed signals keep their default weight of 1.
+    pub fn parse(s: &str) -> Result<RiskWeights, String> {
+        let mut w = RiskWeights::default();
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("invalid weight '{}'. Use e.g. churn=2,age=0.5.", part))?;
+            let value: f64 = value
+                .trim()
+                .parse()
+                .ok()
+                .filter(|v: &f64| v.is_finite() && *v >= 0.0)
+                .ok_or_else(|| format!("invalid weight value in '{}'", part))?;
+            match key.trim() {
+                "churn" => w.churn = value,
+                "ownership" => w.ownership = value,
+                "age" => w.age = value,
+                "size" => w.size = value,
+                other => {
+                    return Err(format!(
+                        "unknown signal '{}'. Expected churn, ownership, age or size.",
+                        other
+                    ))
+                }
+            }
+        }
+        if w.churn + w.ownership + w.age + w.size == 0.0 {
+            return Err("at least one weight must be greater than zero".to_string());
+        }
+        Ok(w)
+    }
+}
+
+/// Raw per-file inputs to the risk score.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FileSignals {
+    pub path: String,
+    /// Non-merge commits touching the file.
+    pub commits: usize,
+    /// Surviving lines (blame at HEAD).
+    pub loc: usize,
+    pub top_owner: String,
+    /// Share of surviving lines held by `top_owner` (0..=1).
+    pub top_share: f64,
+    /// Mean age of surviving lines, in days.
+    pub age_days: f64,
+}
+
+/// A file with its normalized signals and composite score (0..=100).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RiskRow {
+    pub signals: FileSignals,
+    /// Churn, ownership, age and size, each scaled to 0..=1.
+    pub parts: [f64; 4],
+    pub score: f64,
+}
+
+/// Lines, top owner, owner share and mean line age from `blame --line-porcelain`.
+pub fn blame_signals(
+    blame: &str,
+    file: &str,
+    resolver: &MergeResolver,
+    allow: impl Fn(&str, &str) -> bool,
+    now: u64,
+) -> (usize, String, f64, f64) {
+    let times = blame_line_times(blame);
+    let names = crate::identity::active_normalization();
+    let mut owners: HashMap<String, usize> = HashMap::new();
+    let mut age_sum = 0u64;
+    let mut loc = 0usize;
+    for ((name, mail), t) in blame_line_authors(blame, file, resolver)
+        .into_iter()
+        .zip(times)
+    {
+        if !allow(&name, &mail) {
+            continue;
+        }
+        loc += 1;
+        age_sum += now.saturating_sub(t);
+        *owners.entry(names.apply(name)).or_insert(0) += 1;
+    }
+    let (top_owner, top_lines) = owners
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+        .unwrap_or_default();
+    let share = top_lines as f64 / loc.max(1) as f64;
+    let age_days = age_sum as f64 / loc.max(1) as f64 / 86_400.0;
+    (loc, top_owner, share, age_days)
+}
+
+/// Scale each signal by its maximum across files and combine with `weights`.
+pub fn score_files(files: Vec<FileSignals>, weights: RiskWeights) -> Vec<RiskRow> {
+    let max_commits = files.iter().map(|f| f.commits).max().unwrap_or(0).max(1) as f64;
+    let max_loc = files.iter().map(|f| f.loc).max().unwrap_or(0).max(1) as f64;
+    let max_age = files.iter().map(|f| f.age_days).fold(0.0, f64::max);
+    let total = weights.churn + weights.ownership + weights.age + weights.size;
+    let mut rows: Vec<RiskRow> = files
+        .into_iter()
+        .map(|f| {
+            let parts = [
+                f.commits as f64 / max_commits,
+                f.top_share,
+                if max_age > 0.0 {
+                    f.age_days / max_age
+                } else {
+                    0.0
+                },
+                f.loc as f64 / max_loc,
+            ];
+            let score = (weights.churn * parts[0]
+                + weights.ownership * parts[1]
+                + weights.age * parts[2]
+                + weights.size * parts[3])
+                / total
+                * 100.0;
+            RiskRow {
+                signals: f,
+                parts,
+                score,
+            }
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.signals.path.cmp(&b.signals.path))
+    });
+    rows
+}
+
+/// Blame every changed text file at HEAD and rank them by composite risk.
+pub fn gather_risk(weights: RiskWeights) -> Result<Vec<RiskRow>, GitInsightsError> {
+    let churn = gather_churn()?;
+    let files: Vec<String> = tracked_text_files_head()?
+        .into_iter()
+        .filter(|f| churn.contains_key(f))
+        .collect();
+    let authors = crate::filter::author_matcher()?;
+    let merges = MergeResolver::load()?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| format!("clock error: {e}"))?
+        .as_secs();
+
+    let total = files.len();
+    let start = Instant::now();
+    let mut signals = Vec::new();
+    for (n, file) in files.into_iter().enumerate() {
+        print_progress(n + 1, total, start);
+        let Ok(blame) = run_blame_head(&file) else {
+            continue;
+        };
+        let (loc, top_owner, top_share, age_days) =
+            blame_signals(&blame, &file, &merges, |n, e| authors.allows(n, e), now);
+        if loc == 0 {
+            continue;
+        }
+        signals.push(FileSignals {
+            commits: churn[&file],
+            path: file,
+            loc,
+            top_owner,
+            top_share,
+            age_days,
+        });
+    }
+    if total > 0 {
+        finish_progress();
+    }
+    Ok(score_files(signals, weights))
+}
+
+/// JSON array of the top `top` rows.
+pub fn render_risk_json(rows: &[RiskRow], top: usize) -> String {
+    let items: Vec<String> = rows
+        .iter()
+        .take(top)
+        .map(|r| {
+            let f = &r.signals;
+            format!(
+                "{{\"file\": \"{}\", \"score\": {:.1}, \"commits\": {}, \"loc\": {}, \"top_owner\": \"{}\", \"owner_share\": {:.3}, \"age_days\": {:.1}, \"signals\": {{\"churn\": {:.3}, \"ownership\": {:.3}, \"age\": {:.3}, \"size\": {:.3}}}}}",
+                json::escape(&f.path),
+                r.score,
+                f.commits,
+                f.loc,
+                json::escape(&f.top_owner),
+                f.top_share,
+                f.age_days,
+                r.parts[0],
+                r.parts[1],
+                r.parts[2],
+                r.parts[3]
+            )
+        })
+        .collect();
+    format!("[{}]", items.join(", "))
+}
+
+/// Print the ranked table (or JSON).
+pub fn run_risk(weights: RiskWeights, top: usize, as_json: bool) -> Result<(), GitInsightsError> {
+    let rows = gather_risk(weights)?;
+    if as_json {
+        println!("{}", render_risk_json(&rows, top));
+        return Ok(());
+    }
+    println!(
+        "| {:>4} | {:<48} | {:>6} | {:>5} | {:>7} | {:<20} | {:>6} | {:>6} |",
+        "No.", "File", "score", "coms", "loc", "top owner", "own %", "age d"
+    );
+    println!(
+        "|{:->6}|:{:-<49}|{:->8}|{:->7}|{:->9}|:{:-<21}|{:->8}|{:->8}|",
+        "", "", "", "", "", "", "", ""
+    );
+    for (i, r) in rows.iter().take(top).enumerate() {
+        let f = &r.signals;
+        println!(
+            "| {:>4} | {:<48} | {:>6.1} | {:>5} | {:>7} | {:<20} | {:>6.1} | {:>6.0} |",
+            i + 1,
+            truncate(&f.path, 48),
+            r.score,
+            f.commits,
+            f.loc,
+            truncate(&f.top_owner, 20),
+            f.top_share * 100.0,
+            f.age_days
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_risk_weights_and_scoring() {
+        let w = RiskWeights::parse("churn=2, size=0").expect("weights");
+        assert_eq!((w.churn, w.ownership, w.size), (2.0, 1.0, 0.0));
+        assert!(RiskWeights::parse("bus=1").is_err());
+        assert!(RiskWeights::parse("churn=-1").is_err());
+        assert!(RiskWeights::parse("churn=0,ownership=0,age=0,size=0").is_err());
+
+        let file = |path: &str, commits, loc, share, age| FileSignals {
+            path: path.to_string(),
+            commits,
+            loc,
+            top_owner: "Ann".to_string(),
+            top_share: share,
+            age_days: age,
+        };
+        let rows = score_files(
+            vec![
+                file("calm.rs", 1, 10, 0.5, 10.0),
+                file("hot.rs", 10, 100, 1.0, 100.0),
+            ],
+            RiskWeights::default(),
+        );
+        assert_eq!(rows[0].signals.path, "hot.rs");
+        assert!((rows[0].score - 100.0).abs() < 1e-9);
+        assert!((rows[1].score - (0.1 + 0.5 + 0.1 + 0.1) / 4.0 * 100.0).abs() < 1e-9);
+
+        let doc = crate::json::parse(&render_risk_json(&rows, 1)).expect("valid JSON");
+        assert_eq!(doc.as_array().map(|a| a.len()), Some(1));
+    }
+
+    #[test]
+    fn test_gather_risk_e2e() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        for i in 0..3 {
+            repo.commit_with_epoch("Ann", "ann@x.io", "hot.rs", "x", 1_700_000_000 + i)
+                .expect("commit");
+        }
+        repo.commit_with_epoch("Ann", "ann@x.io", "calm.rs", "a", 1_700_000_100)
+            .expect("commit");
+        repo.commit_with_epoch("Bob", "bob@x.io", "calm.rs", "b", 1_700_000_200)
+            .expect("commit");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        let rows = gather_risk(RiskWeights::default());
+        std::env::set_current_dir(old).ok();
+
+        let rows = rows.expect("risk");
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].signals.path, "hot.rs");
+        assert_eq!(rows[0].signals.commits, 3);
+        assert_eq!(rows[0].signals.top_owner, "Ann");
+        assert!((rows[1].signals.top_share - 0.5).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,449 @@
+//! `serve`: a small HTTP server with a JSON API and an HTML dashboard, so
+//! teammates can browse a repository's insights from a browser.
+//!
+//! Requests are handled one at a time and every response is computed fresh
+//! from the repository the server was started in.
+
+use crate::error::GitInsightsError;
+use crate::json;
+use crate::stats::{compute_stats, get_user_file_ownership, RepoStats};
+use crate::visualize::{
+    collect_commit_timestamps, compute_calendar_heatmap, compute_timeline, TimelineMetric,
+};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const DEFAULT_SERVE_PORT: u16 = 8080;
+
+/// Loopback only unless `--host` says otherwise.
+pub const DEFAULT_SERVE_HOST: &str = "127.0.0.1";
+
+/// Largest `weeks` the timeline and heatmap endpoints accept (ten years).
+pub const MAX_WEEKS: usize = 520;
+
+/// How long a client may stall while sending its request or reading the
+/// response; requests are served one at a time, so a silent client would
+/// otherwise block everyone else.
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Status, content type and body of one response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl Response {
+    fn json(body: String) -> Response {
+        Response {
+            status: 200,
+            content_type: "application/json",
+            body,
+        }
+    }
+
+    fn error(status: u16, msg: &str) -> Response {
+        Response {
+            status,
+            content_type: "application/json",
+            body: format!("{{\"error\": \"{}\"}}", json::escape(msg)),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+/// Decode `%XX` escapes and `+` (as space) in a URL component.
+pub fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => match std::str::from_utf8(&bytes[i + 1..i + 3])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                Some(b) => {
+                    out.push(b);
+                    i += 3;
+                    continue;
+                }
+                None => out.push(b'%'),
+            },
+            b'+' => out.push(b' '),
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Split `/path?a=1&b=2` into the decoded path and query pairs.
+fn split_target(target: &str) -> (String, Vec<(String, String)>) {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let params = query
+        .split('&')
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            let (k, v) = p.split_once('=').unwrap_or((p, ""));
+            (percent_decode(k), percent_decode(v))
+        })
+        .collect();
+    (path.to_string(), params)
+}
+
+fn param<'a>(params: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    params
+        .iter()
+        .rev()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.as_str())
+}
+
+fn count_param(params: &[(String, String)], key: &str, default: usize) -> Result<usize, Response> {
+    match param(params, key) {
+        None => Ok(default),
+        Some(v) => v.parse().map_err(|_| {
+            Response::error(
+                400,
+                &format!("'{}' must be a whole number, not '{}'", key, v),
+            )
+        }),
+    }
+}
+
+/// [`count_param`] for `weeks`, rejecting values above [`MAX_WEEKS`].
+fn weeks_param(params: &[(String, String)], default: usize) -> Result<usize, Response> {
+    let weeks = count_param(params, "weeks", default)?;
+    if weeks > MAX_WEEKS {
+        return Err(Response::error(
+            400,
+            &format!("'weeks' must be at most {}, not {}", MAX_WEEKS, weeks),
+        ));
+    }
+    Ok(weeks)
+}
+
+fn failed(e: GitInsightsError) -> Response {
+    Response::error(500, &e.to_string())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// `/stats`: totals and one row per author, most lines first.
+pub fn stats_json(stats: &RepoStats) -> String {
+    let authors: Vec<String> = stats
+        .authors
+        .iter()
+        .map(|(author, s)| {
+            format!(
+                "{{\"author\": \"{}\", \"loc\": {}, \"commits\": {}, \"files\": {}}}",
+                json::escape(author),
+                s.loc,
+                s.commits,
+                s.files.len()
+            )
+        })
+        .collect();
+    format!(
+        "{{\"total_loc\": {}, \"total_commits\": {}, \"total_files\": {}, \"authors\": [{}]}}",
+        stats.total_loc,
+        stats.total_commits,
+        stats.total_files,
+        authors.join(", ")
+    )
+}
+
+fn counts_json(counts: &[usize]) -> String {
+    let items: Vec<String> = counts.iter().map(|c| c.to_string()).collect();
+    format!("[{}]", items.join(", "))
+}
+
+/// `/ownership/<user>`: `(file, user lines, file lines, share)` rows.
+pub fn ownership_json(user: &str, rows: &[(String, usize, usize, f32)]) -> String {
+    let files: Vec<String> = rows
+        .iter()
+        .map(|(file, loc, total, pct)| {
+            format!(
+                "{{\"file\": \"{}\", \"loc\": {}, \"file_loc\": {}, \"pct\": {:.1}}}",
+                json::escape(file),
+                loc,
+                total,
+                pct
+            )
+        })
+        .collect();
+    format!(
+        "{{\"user\": \"{}\", \"files\": [{}]}}",
+        json::escape(user),
+        files.join(", ")
+    )
+}
+
+/// The JSON body for an API path, `None` for unknown paths.
+fn api(path: &str, params: &[(String, String)]) -> Result<Option<String>, Response> {
+    let body = match path {
+        "/stats" => {
+            stats_json(&compute_stats(param(params, "by") != Some("email")).map_err(failed)?)
+        }
+        "/timeline" => {
+            let weeks = weeks_param(params, 26)?;
+            let metric = TimelineMetric::parse(param(params, "metric").unwrap_or("commits"))
+                .map_err(|e| Response::error(400, &e.to_string()))?;
+            let data = compute_timeline(weeks, metric).map_err(failed)?;
+            format!(
+                "{{\"unit\": \"{}\", {}, \"counts\": {}}}",
+                data.metric.unit(),
+                json::time_fields("now", data.now),
+                counts_json(&data.counts)
+            )
+        }
+        "/heatmap" => {
+            let weeks = weeks_param(params, 52)?;
+            let now = now();
+            let grid =
+                compute_calendar_heatmap(&collect_commit_timestamps().map_err(failed)?, weeks, now);
+            let rows: Vec<String> = grid.iter().map(|r| counts_json(r)).collect();
+            format!(
+                "{{\"weeks\": {}, {}, \"rows\": \"Sun..Sat\", \"grid\": [{}]}}",
+                weeks,
+                json::time_fields("now", now),
+                rows.join(", ")
+            )
+        }
+        _ => match path.strip_prefix("/ownership/") {
+            Some(user) if !user.is_empty() => {
+                let user = percent_decode(user);
+                let top = count_param(params, "top", 10)?;
+                let by_email = param(params, "by") == Some("email");
+                let sort_pct = param(params, "sort") == Some("pct");
+                let rows =
+                    get_user_file_ownership(&user, by_email, top, sort_pct).map_err(failed)?;
+                ownership_json(&user, &rows)
+            }
+            _ => return Ok(None),
+        },
+    };
+    Ok(Some(body))
+}
+
+/// Answer one request; `target` is the path with its query string.
+pub fn route(method: &str, target: &str) -> Response {
+    if method != "GET" && method != "HEAD" {
+        return Response::error(405, "only GET is supported");
+    }
+    let (path, params) = split_target(target);
+    if path == "/" || path == "/index.html" {
+        return Response {
+            status: 200,
+            content_type: "text/html; charset=utf-8",
+            body: DASHBOARD_HTML.to_string(),
+        };
+    }
+    match api(&path, &params) {
+        Ok(Some(body)) => Response::json(body),
+        Ok(None) => Response::error(404, &format!("no route for '{}'", path)),
+        Err(response) => response,
+    }
+}
+
+/// Read one request from `stream` and write the response.
+fn handle(stream: &mut TcpStream) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    // Drain the headers; request bodies are not used.
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+    let mut parts = line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or("/"));
+    let response = route(method, target);
+    crate::log::info(format_args!("{} {} -> {}", method, target, response.status));
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.reason(),
+        response.content_type,
+        response.body.len()
+    )?;
+    if method != "HEAD" {
+        stream.write_all(response.body.as_bytes())?;
+    }
+    stream.flush()
+}
+
+/// Serve requests from `listener`, stopping after `limit` when given.
+pub fn serve_on(listener: &TcpListener, limit: Option<usize>) -> Result<(), GitInsightsError> {
+    for (n, stream) in listener.incoming().enumerate() {
+        match stream {
+            Ok(mut stream) => {
+                if let Err(e) = handle(&mut stream) {
+                    crate::log::warn(format_args!("request failed: {}", e));
+                }
+            }
+            Err(e) => crate::log::warn(format_args!("connection failed: {}", e)),
+        }
+        if limit.is_some_and(|l| n + 1 >= l) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Listen on `host:port` until interrupted.
+pub fn run_serve(host: &str, port: u16) -> Result<(), GitInsightsError> {
+    let listener = TcpListener::bind((host, port))
+        .map_err(|e| format!("cannot listen on {}:{}: {}", host, port, e))?;
+    let addr = listener.local_addr().map_err(|e| e.to_string())?;
+    println!("Serving git-insights on http://{} (Ctrl-C to stop)", addr);
+    println!("API: /stats /timeline?weeks=N /heatmap?weeks=N /ownership/<user>");
+    serve_on(&listener, None)
+}
+
+const DASHBOARD_HTML: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>git-insights</title>
+<style>
+body { font-family: system-ui, sans-serif; margin: 2em; color: #222; }
+table { border-collapse: collapse; }
+th, td { padding: 2px 10px; text-align: right; }
+th:first-child, td:first-child { text-align: left; }
+tr:nth-child(even) { background: #f4f4f4; }
+.bars { display: flex; align-items: flex-end; height: 120px; gap: 2px; }
+.bars div { background: #40a060; width: 12px; }
+.grid td { width: 12px; height: 12px; padding: 0; border: 1px solid #fff; }
+</style>
+</head>
+<body>
+<h1>git-insights</h1>
+<p id="totals">Loading...</p>
+<h2>Commits per week</h2>
+<div class="bars" id="timeline"></div>
+<h2>Calendar heatmap</h2>
+<table class="grid" id="heatmap"></table>
+<h2>Authors</h2>
+<table id="authors"><tr><th>author</th><th>loc</th><th>commits</th><th>files</th></tr></table>
+<script>
+const get = (url) => fetch(url).then((r) => r.json());
+const esc = (s) => String(s).replace(/[&<>"]/g, (c) => "&#" + c.charCodeAt(0) + ";");
+get("/timeline").then((t) => {
+  const max = Math.max(1, ...t.counts);
+  document.getElementById("timeline").innerHTML = t.counts
+    .map((c) => `<div title="${c}" style="height:${(100 * c) / max}%"></div>`).join("");
+});
+get("/heatmap").then((h) => {
+  const max = Math.max(1, ...h.grid.flat());
+  document.getElementById("heatmap").innerHTML = h.grid.map((row) => "<tr>" + row
+    .map((c) => `<td title="${c}" style="background:rgba(64,160,96,${c ? 0.2 + (0.8 * c) / max : 0.05})"></td>`)
+    .join("") + "</tr>").join("");
+});
+get("/stats").then((s) => {
+  document.getElementById("totals").textContent =
+    `${s.total_loc} lines, ${s.total_commits} commits, ${s.total_files} files`;
+  document.getElementById("authors").innerHTML += s.authors.map((a) =>
+    `<tr><td><a href="/ownership/${encodeURIComponent(a.author)}">${esc(a.author)}</a></td>` +
+    `<td>${a.loc}</td><td>${a.commits}</td><td>${a.files}</td></tr>`).join("");
+});
+</script>
+</body>
+</html>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_split_target_and_decode() {
+        assert_eq!(percent_decode("Ann%20Lee+x%2"), "Ann Lee x%2");
+        let (path, params) = split_target("/timeline?weeks=8&metric=contributors");
+        assert_eq!(path, "/timeline");
+        assert_eq!(param(&params, "weeks"), Some("8"));
+        assert_eq!(count_param(&params, "weeks", 26), Ok(8));
+        let bad = count_param(&[("weeks".into(), "x".into())], "weeks", 26);
+        assert_eq!(bad.unwrap_err().status, 400);
+        assert_eq!(weeks_param(&params, 26), Ok(8));
+        let max = MAX_WEEKS.to_string();
+        assert_eq!(weeks_param(&[("weeks".into(), max)], 26), Ok(MAX_WEEKS));
+        let huge = weeks_param(&[("weeks".into(), "100000000".into())], 26);
+        assert_eq!(huge.unwrap_err().status, 400);
+        assert_eq!(route("GET", "/heatmap?weeks=521").status, 400);
+        assert_eq!(route("POST", "/stats").status, 405);
+        assert_eq!(route("GET", "/").content_type, "text/html; charset=utf-8");
+    }
+
+    #[test]
+    fn test_serve_json_api_e2e() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        repo.commit_with_epoch("Ann Lee", "ann@x.io", "a.txt", "one", 1_600_000_000)
+            .expect("commit");
+        repo.commit_with_epoch("Bob", "bob@x.io", "b.txt", "two", 1_600_000_100)
+            .expect("commit");
+        let listener = TcpListener::bind(("127.0.0.1", 0)).expect("bind");
+        let addr = listener.local_addr().unwrap();
+        let scoped = crate::git::Repo::open(&repo.path).expect("open");
+
+        let client = std::thread::spawn(move || {
+            [
+                "/stats",
+                "/ownership/Ann%20Lee",
+                "/nope",
+                "/timeline?weeks=x",
+            ]
+            .iter()
+            .map(|path| {
+                let mut conn = TcpStream::connect(addr).expect("connect");
+                write!(conn, "GET {} HTTP/1.1\r\nHost: x\r\n\r\n", path).unwrap();
+                let mut text = String::new();
+                conn.read_to_string(&mut text).unwrap();
+                text
+            })
+            .collect::<Vec<String>>()
+        });
+        scoped
+            .scope(|| serve_on(&listener, Some(4)))
+            .expect("serve");
+        let replies = client.join().unwrap();
+
+        assert!(replies[0].starts_with("HTTP/1.1 200 OK\r\n"));
+        let body = replies[0].split("\r\n\r\n").nth(1).unwrap();
+        let stats = json::parse(body).expect("json");
+        assert_eq!(stats.get("total_loc").and_then(|v| v.as_usize()), Some(2));
+        let authors = stats.get("authors").and_then(|a| a.as_array()).unwrap();
+        assert_eq!(authors.len(), 2);
+
+        let body = replies[1].split("\r\n\r\n").nth(1).unwrap();
+        let owned = json::parse(body).expect("json");
+        assert_eq!(owned.get("user").and_then(|u| u.as_str()), Some("Ann Lee"));
+        let files = owned.get("files").and_then(|f| f.as_array()).unwrap();
+        assert_eq!(files[0].get("file").and_then(|f| f.as_str()), Some("a.txt"));
+
+        assert!(replies[2].starts_with("HTTP/1.1 404 Not Found"));
+        assert!(replies[3].starts_with("HTTP/1.1 400 Bad Request"));
+    }
+}
@@ -0,0 +1,186 @@
+//! `signing`: share of commits carrying a GPG, SSH or X.509 signature,
+//! overall and per author, for projects with a signing policy.
+
+use crate::error::GitInsightsError;
+use crate::git::run_command_lines;
+use crate::output::truncate;
+use std::collections::BTreeMap;
+
+/// A commit's signature as reported by `git log --format=%G?`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SignatureStatus {
+    /// `G`/`U`: a good signature (of unknown validity for `U`).
+    Good,
+    /// `B`, `X`, `Y`, `R`: bad, expired, made by an expired key, or revoked.
+    Bad,
+    /// `E`: signed, but git cannot check it, e.g. the key is missing.
+    Unchecked,
+    /// `N`: not signed.
+    Unsigned,
+}
+
+impl SignatureStatus {
+    pub fn from_code(code: &str) -> SignatureStatus {
+        match code.trim() {
+            "G" | "U" => SignatureStatus::Good,
+            "B" | "X" | "Y" | "R" => SignatureStatus::Bad,
+            "E" => SignatureStatus::Unchecked,
+            _ => SignatureStatus::Unsigned,
+        }
+    }
+
+    pub fn is_signed(self) -> bool {
+        self != SignatureStatus::Unsigned
+    }
+}
+
+/// Signature counts of one author, or of everyone.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SigningCounts {
+    pub commits: usize,
+    /// Commits with any signature, checked or not.
+    pub signed: usize,
+    /// Signatures git verified as good.
+    pub good: usize,
+    pub bad: usize,
+}
+
+impl SigningCounts {
+    pub fn add(&mut self, status: SignatureStatus) {
+        self.commits += 1;
+        if status.is_signed() {
+            self.signed += 1;
+        }
+        match status {
+            SignatureStatus::Good => self.good += 1,
+            SignatureStatus::Bad => self.bad += 1,
+            _ => {}
+        }
+    }
+
+    pub fn signed_pct(&self) -> f64 {
+        self.signed as f64 * 100.0 / self.commits.max(1) as f64
+    }
+
+    pub fn good_pct(&self) -> f64 {
+        self.good as f64 * 100.0 / self.commits.max(1) as f64
+    }
+}
+
+/// Overall and per-author counts for the active filter.
+pub fn gather_signing() -> Result<(SigningCounts, BTreeMap<String, SigningCounts>), GitInsightsError>
+{
+    let revs = crate::filter::revision_args()?;
+    let authors = crate::filter::author_matcher()?;
+    let names = crate::identity::active_normalization();
+    let mut args = vec!["--no-pager", "log", "--format=%G?%x09%aN%x09%aE"];
+    args.extend(crate::filter::history_args());
+    args.extend(revs.iter().map(|s| s.as_str()));
+    let mut total = SigningCounts::default();
+    let mut per_author: BTreeMap<String, SigningCounts> = BTreeMap::new();
+    run_command_lines(&args, |line| {
+        let mut fields = line.splitn(3, '\t');
+        let (Some(code), Some(name), email) = (fields.next(), fields.next(), fields.next()) else {
+            return;
+        };
+        if !authors.allows(name, email.unwrap_or("")) {
+            return;
+        }
+        let status = SignatureStatus::from_code(code);
+        total.add(status);
+        per_author
+            .entry(names.apply(name.to_string()))
+            .or_default()
+            .add(status);
+    })?;
+    Ok((total, per_author))
+}
+
+/// Print overall coverage and one row per author, least covered first.
+pub fn run_signing() -> Result<(), GitInsightsError> {
+    let (total, per_author) = gather_signing()?;
+    if total.commits == 0 {
+        println!("No commits.");
+        return Ok(());
+    }
+    println!(
+        "Signed: {} of {} commits ({:.1}%); verified good: {} ({:.1}%); bad, expired or revoked: {}",
+        total.signed,
+        total.commits,
+        total.signed_pct(),
+        total.good,
+        total.good_pct(),
+        total.bad
+    );
+    println!();
+    let mut rows: Vec<(&String, &SigningCounts)> = per_author.iter().collect();
+    rows.sort_by(|a, b| {
+        a.1.signed_pct()
+            .total_cmp(&b.1.signed_pct())
+            .then_with(|| b.1.commits.cmp(&a.1.commits))
+            .then_with(|| a.0.cmp(b.0))
+    });
+    println!(
+        "| {:<28} | {:>7} | {:>6} | {:>8} | {:>6} | {:>4} |",
+        "author", "commits", "signed", "signed %", "good %", "bad"
+    );
+    println!(
+        "|:{:-<29}|{:->9}|{:->8}|{:->10}|{:->8}|{:->6}|",
+        "", "", "", "", "", ""
+    );
+    for (author, c) in rows {
+        println!(
+            "| {:<28} | {:>7} | {:>6} | {:>8.1} | {:>6.1} | {:>4} |",
+            truncate(author, 28),
+            c.commits,
+            c.signed,
+            c.signed_pct(),
+            c.good_pct(),
+            c.bad
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_status_counts() {
+        let mut c = SigningCounts::default();
+        for code in ["G", "U", "E", "B", "N", ""] {
+            c.add(SignatureStatus::from_code(code));
+        }
+        assert_eq!(
+            c,
+            SigningCounts {
+                commits: 6,
+                signed: 4,
+                good: 2,
+                bad: 1
+            }
+        );
+        assert!((c.signed_pct() - 66.666).abs() < 0.01);
+        assert!(!SignatureStatus::from_code("N").is_signed());
+        assert!(SignatureStatus::from_code("Y") == SignatureStatus::Bad);
+    }
+
+    #[test]
+    fn test_gather_signing_e2e() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", "one", 1_700_000_000)
+            .expect("commit");
+        repo.commit_with_epoch("Bob", "bob@x.io", "a.txt", "two", 1_700_000_100)
+            .expect("commit");
+        let (total, per_author) = crate::git::Repo::open(&repo.path)
+            .expect("open")
+            .scope(gather_signing)
+            .expect("signing");
+        assert_eq!((total.commits, total.signed), (2, 0));
+        assert_eq!(per_author["Ann"].commits, 1);
+        assert_eq!(per_author["Bob"].signed_pct(), 0.0);
+    }
+}
@@ -0,0 +1,343 @@
+//! Incremental `stats`: surviving lines per file are stored alongside the HEAD
+//! they were blamed at, and later runs only re-blame files changed since then.
+
+use crate::error::GitInsightsError;
+use crate::filter::{parse_commit_list, CommitFilter};
+use crate::git::{mailmap_contents, repo_relative, run_command, short_hash};
+use crate::json::{self, JsonValue};
+use crate::merges::{ignore_revs_file, MergeResolver};
+use crate::output::{finish_progress, print_progress, TableOutput};
+use crate::stats::{
+    blame_file_authors, gather_commit_statsx, prepare_blame, print_author_stats, text_detection,
+    tracked_text_files_head, StatsMap,
+};
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Snapshot location, relative to the git directory.
+pub const SNAPSHOT_PATH: &str = "git-insights/stats-snapshot.json";
+
+/// Surviving lines per author key, per file.
+pub type FileAuthors = BTreeMap<String, BTreeMap<String, usize>>;
+
+/// Blame results computed at `head` under the settings described by `key`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatsSnapshot {
+    pub head: String,
+    /// When the snapshot was written (Unix seconds).
+    pub created_at: u64,
+    /// Settings that change blame output; a mismatch forces a full rebuild.
+    pub key: String,
+    pub files: FileAuthors,
+}
+
+impl StatsSnapshot {
+    pub fn to_json(&self) -> String {
+        let files: Vec<String> = self
+            .files
+            .iter()
+            .map(|(file, authors)| {
+                let authors: Vec<String> = authors
+                    .iter()
+                    .map(|(a, n)| format!("\"{}\": {}", json::escape(a), n))
+                    .collect();
+                format!("\"{}\": {{{}}}", json::escape(file), authors.join(", "))
+            })
+            .collect();
+        format!(
+            "{{\"head\": \"{}\", {}, \"key\": \"{}\", \"files\": {{\n{}\n}}}}\n",
+            json::escape(&self.head),
+            json::time_fields("created_at", self.created_at),
+            json::escape(&self.key),
+            files.join(",\n")
+        )
+    }
+
+    pub fn from_json(text: &str) -> Result<StatsSnapshot, String> {
+        let doc = json::parse(text)?;
+        let field = |name: &str| {
+            doc.get(name)
+                .and_then(JsonValue::as_str)
+                .map(str::to_string)
+                .ok_or_else(|| format!("snapshot is missing '{}'", name))
+        };
+        let mut snapshot = StatsSnapshot {
+            head: field("head")?,
+            created_at: doc
+                .get("created_at")
+                .and_then(JsonValue::as_usize)
+                .unwrap_or(0) as u64,
+            key: field("key")?,
+            files: FileAuthors::new(),
+        };
+        let Some(JsonValue::Object(files)) = doc.get("files") else {
+            return Err("snapshot is missing 'files'".to_string());
+        };
+        for (file, authors) in files {
+            let JsonValue::Object(authors) = authors else {
+                return Err(format!("snapshot entry for '{}' is not an object", file));
+            };
+            let counts = authors
+                .iter()
+                .map(|(a, n)| {
+                    n.as_usize()
+                        .map(|n| (a.clone(), n))
+                        .ok_or_else(|| format!("bad line count for '{}' in '{}'", a, file))
+                })
+                .collect::<Result<_, String>>()?;
+            snapshot.files.insert(file.clone(), counts);
+        }
+        Ok(snapshot)
+    }
+
+    /// Fold per-file counts into the per-author map used by `stats`.
+    pub fn to_stats(&self) -> StatsMap {
+        stats_from_files(&self.files)
+    }
+}
+
+/// Fold per-file line counts into the per-author map used by `stats`.
+pub fn stats_from_files(files: &FileAuthors) -> StatsMap {
+    let mut stats = StatsMap::new();
+    for (file, authors) in files {
+        for (author, loc) in authors {
+            let entry = stats.entry(author.clone()).or_default();
+            entry.loc += loc;
+            entry.files.insert(file.clone());
+        }
+    }
+    stats
+}
+
+/// Blame every tracked text file at HEAD, without a snapshot.
+pub fn blame_head_files(by_name: bool) -> Result<FileAuthors, GitInsightsError> {
+    let paths = tracked_text_files_head()?;
+    prepare_blame();
+    let authors = crate::filter::author_matcher()?;
+    let merges = MergeResolver::load()?;
+    let names = crate::identity::active_normalization();
+    let mut files = FileAuthors::new();
+    let total = paths.len();
+    let start = Instant::now();
+    for (n, file) in paths.iter().enumerate() {
+        print_progress(n + 1, total, start);
+        if let Some(lines) = blame_file_authors(file, by_name, &authors, &merges, names) {
+            files.insert(file.clone(), lines.into_iter().collect());
+        }
+    }
+    if total > 0 {
+        finish_progress();
+    }
+    Ok(files)
+}
+
+/// Commits every blame skips: those listed in `.git-blame-ignore-revs`, then
+/// the `--ignore-rev` ones.
+fn ignored_revs(filter: &CommitFilter) -> Vec<String> {
+    let mut revs = ignore_revs_file(filter)
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .map(|text| parse_commit_list(&text))
+        .unwrap_or_default();
+    revs.extend(filter.ignore_revs.iter().cloned());
+    revs
+}
+
+/// Everything besides HEAD that changes what blame reports per file.
+fn settings_key(by_name: bool) -> Result<String, GitInsightsError> {
+    let filter = crate::filter::active();
+    Ok(format!(
+        "v1 by_name={} authors={:?} merge_lines={:?} blame={:?} ignore_revs={:?} names={:?} text={:?} mailmap={}",
+        by_name,
+        filter.author_matcher()?,
+        filter.merge_lines,
+        filter.blame.args(),
+        ignored_revs(&filter),
+        filter.normalize_names,
+        text_detection(),
+        short_hash(mailmap_contents().as_bytes())
+    ))
+}
+
+fn snapshot_path() -> Result<PathBuf, GitInsightsError> {
+    let path = run_command(&["rev-parse", "--git-path", SNAPSHOT_PATH])?;
+    Ok(repo_relative(&path))
+}
+
+/// Files changed between `base` and HEAD, or `None` when `base` is not an
+/// ancestor of HEAD (history was rewritten) and everything must be re-blamed.
+pub fn changed_since(base: &str) -> Result<Option<HashSet<String>>, GitInsightsError> {
+    if run_command(&["merge-base", "--is-ancestor", base, "HEAD"]).is_err() {
+        return Ok(None);
+    }
+    let range = format!("{}..HEAD", base);
+    let out = run_command(&["--no-pager", "diff", "--name-only", "--no-renames", &range])?;
+    Ok(Some(out.lines().map(str::to_string).collect()))
+}
+
+/// Surviving LOC per author, re-blaming only files changed since the stored
+/// snapshot (or everything when there is none), then refreshing the snapshot.
+pub fn gather_loc_stats_incremental(by_name: bool) -> Result<StatsMap, GitInsightsError> {
+    let head = run_command(&["rev-parse", "HEAD"])?;
+    let key = settings_key(by_name)?;
+    let path = snapshot_path()?;
+    let previous = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|text| StatsSnapshot::from_json(&text).ok())
+        .filter(|s| s.key == key);
+
+    let current = tracked_text_files_head()?;
+    let mut files = FileAuthors::new();
+    if let Some(prev) = previous {
+        if let Some(changed) = changed_since(&prev.head)? {
+            let current: HashSet<&String> = current.iter().collect();
+            files = prev
+                .files
+                .into_iter()
+                .filter(|(f, _)| current.contains(f) && !changed.contains(f))
+                .collect();
+        }
+        crate::log::debug(format_args!(
+            "snapshot {}: reusing {} of {} files",
+            path.display(),
+            files.len(),
+            current.len()
+        ));
+    } else {
+        crate::log::debug(format_args!(
+            "no usable snapshot at {}; blaming every file",
+            path.display()
+        ));
+    }
+
+    let stale: Vec<&String> = current.iter().filter(|f| !files.contains_key(*f)).collect();
+    if !stale.is_empty() {
+        prepare_blame();
+        let authors = crate::filter::author_matcher()?;
+        let merges = MergeResolver::load()?;
+        let names = crate::identity::active_normalization();
+        let total = stale.len();
+        let start = Instant::now();
+        for (n, file) in stale.into_iter().enumerate() {
+            print_progress(n + 1, total, start);
+            if let Some(lines) = blame_file_authors(file, by_name, &authors, &merges, names) {
+                files.insert(file.clone(), lines.into_iter().collect());
+            }
+        }
+        finish_progress();
+    }
+
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| format!("clock error: {e}"))?
+        .as_secs();
+    let snapshot = StatsSnapshot {
+        head,
+        created_at,
+        key,
+        files,
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    }
+    std::fs::write(&path, snapshot.to_json()).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(snapshot.to_stats())
+}
+
+/// `stats --incremental`: fresh commit counts plus snapshot-backed LOC.
+pub fn run_stats_incremental(by_name: bool, table: &TableOutput) -> Result<(), GitInsightsError> {
+    let mut final_stats = gather_loc_stats_incremental(by_name)?;
+    for (author, data) in gather_commit_statsx(by_name)? {
+        final_stats.entry(author).or_default().set_history(data);
+    }
+    print_author_stats(final_stats, table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_json_roundtrip() {
+        let mut files = FileAuthors::new();
+        files.insert(
+            "src/a \"b\".rs".to_string(),
+            BTreeMap::from([("Ann".to_string(), 3), ("Bob".to_string(), 1)]),
+        );
+        files.insert(
+            "c.txt".to_string(),
+            BTreeMap::from([("Ann".to_string(), 2)]),
+        );
+        let snap = StatsSnapshot {
+            head: "abc".to_string(),
+            created_at: 1_700_000_000,
+            key: "v1".to_string(),
+            files,
+        };
+        let back = StatsSnapshot::from_json(&snap.to_json()).expect("parse");
+        assert_eq!(back, snap);
+        let stats = back.to_stats();
+        assert_eq!((stats["Ann"].loc, stats["Ann"].files.len()), (5, 2));
+        assert_eq!(stats["Bob"].loc, 1);
+        assert!(StatsSnapshot::from_json("{\"head\": \"x\"}").is_err());
+    }
+
+    #[test]
+    fn test_incremental_stats_reuses_snapshot_e2e() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", "one", 1_700_000_000)
+            .expect("commit");
+        repo.commit_with_epoch("Bob", "bob@x.io", "b.txt", "two", 1_700_000_100)
+            .expect("commit");
+
+        let scoped = crate::git::Repo::open(&repo.path).expect("open");
+        let first = scoped.scope(|| gather_loc_stats_incremental(true));
+        // Tamper with the cached count for the untouched file: an incremental
+        // run must keep it, proving b.txt was not re-blamed.
+        let path = scoped.scope(snapshot_path).expect("path");
+        assert!(path.starts_with(scoped.path()));
+        let text = std::fs::read_to_string(&path).expect("snapshot");
+        std::fs::write(&path, text.replace("\"Bob\": 1", "\"Bob\": 7")).expect("write");
+        repo.commit_with_epoch("Cat", "cat@x.io", "a.txt", "three", 1_700_000_200)
+            .expect("commit");
+        let second = scoped.scope(|| gather_loc_stats_incremental(true));
+        let full = scoped.scope(|| crate::stats::gather_loc_and_file_statsx(true));
+
+        let first = first.expect("first run");
+        assert_eq!((first["Ann"].loc, first["Bob"].loc), (1, 1));
+        let second = second.expect("second run");
+        assert_eq!(second["Bob"].loc, 7);
+        assert_eq!((second["Ann"].loc, second["Cat"].loc), (1, 1));
+        assert_eq!(full.expect("full")["Bob"].loc, 1);
+    }
+
+    #[test]
+    fn test_incremental_stats_rebuilds_on_mailmap_change_e2e() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", "one", 1_700_000_000)
+            .expect("commit");
+        repo.commit_with_epoch("Zed", "zed@x.io", "b.txt", "two", 1_700_000_100)
+            .expect("commit");
+
+        let scoped = crate::git::Repo::open(&repo.path).expect("open");
+        let first = scoped.scope(|| gather_loc_stats_incremental(true));
+        repo.commit_with_epoch(
+            "Zed",
+            "zed@x.io",
+            ".mailmap",
+            "Zed <zed@x.io> Ann <ann@x.io>\n",
+            1_700_000_200,
+        )
+        .expect("commit");
+        let second = scoped.scope(|| gather_loc_stats_incremental(true));
+
+        let first = first.expect("first run");
+        assert_eq!((first["Ann"].loc, first["Zed"].loc), (1, 1));
+        let second = second.expect("second run");
+        // a.txt is unchanged but must be re-blamed under the new mailmap.
+        assert!(!second.contains_key("Ann"));
+        assert_eq!(second["Zed"].loc, 3);
+    }
+}
@@ -0,0 +1,213 @@
+//! `--recurse-submodules`: fold initialized submodules into `stats`,
+//! `timeline` and `heatmap`, with a subtotal per repository.
+
+use crate::error::GitInsightsError;
+use crate::git::{run_command, Repo};
+use crate::stats::{AuthorStats, RepoStats, StatsMap};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Label of the superproject in subtotals.
+pub const SUPERPROJECT: &str = ".";
+
+static RECURSE: AtomicBool = AtomicBool::new(false);
+
+/// Include initialized submodules in later analyses.
+pub fn set_recurse_submodules(on: bool) {
+    RECURSE.store(on, Ordering::Relaxed);
+}
+
+pub fn recurse_submodules() -> bool {
+    RECURSE.load(Ordering::Relaxed)
+}
+
+/// A checked-out submodule, nested ones included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Submodule {
+    /// Path from the superproject, e.g. `vendor/lib`.
+    pub path: String,
+    pub repo: Repo,
+}
+
+/// `(display path, absolute work tree)` pairs printed by the
+/// `submodule foreach` in [`initialized_submodules`].
+pub fn parse_submodule_list(out: &str) -> Vec<(String, PathBuf)> {
+    out.lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(path, dir)| (path.to_string(), PathBuf::from(dir)))
+        .collect()
+}
+
+/// Initialized submodules, recursively; uninitialized ones are skipped since
+/// they have no history to read.
+pub fn initialized_submodules() -> Result<Vec<Submodule>, GitInsightsError> {
+    let out = run_command(&[
+        "submodule",
+        "foreach",
+        "--quiet",
+        "--recursive",
+        r#"printf '%s\t%s\n' "$displaypath" "$toplevel/$sm_path""#,
+    ])?;
+    parse_submodule_list(&out)
+        .into_iter()
+        .map(|(path, dir)| {
+            Ok(Submodule {
+                path,
+                repo: Repo::open(dir)?,
+            })
+        })
+        .collect()
+}
+
+/// Run `f` in the superproject and then in every initialized submodule.
+/// A submodule that fails (e.g. a revision range it lacks) is skipped with a
+/// warning; a failing superproject fails the whole run.
+pub fn each_repo<T>(
+    f: impl Fn() -> Result<T, GitInsightsError>,
+) -> Result<Vec<(String, T)>, GitInsightsError> {
+    let mut results = vec![(SUPERPROJECT.to_string(), f()?)];
+    for sub in initialized_submodules()? {
+        match sub.repo.scope(&f) {
+            Ok(value) => results.push((sub.path, value)),
+            Err(e) => crate::log::warn(format_args!("skipping submodule {}: {}", sub.path, e)),
+        }
+    }
+    Ok(results)
+}
+
+/// Add `part` to `into`, prefixing its files with the submodule `path`.
+pub fn merge_stats(into: &mut StatsMap, part: StatsMap, path: &str) {
+    for (author, stats) in part {
+        let entry: &mut AuthorStats = into.entry(author).or_default();
+        entry.loc += stats.loc;
+        entry.commits += stats.commits;
+        entry.added += stats.added;
+        entry.deleted += stats.deleted;
+        entry.add_weekly(&stats.weekly);
+        entry.files.extend(stats.files.into_iter().map(|f| {
+            if path == SUPERPROJECT {
+                f
+            } else {
+                format!("{}/{}", path, f)
+            }
+        }));
+    }
+}
+
+/// The `stats` subtotal table, one row per repository.
+pub fn write_stats_subtotals<W: Write>(
+    out: &mut W,
+    parts: &[(String, RepoStats)],
+) -> io::Result<()> {
+    writeln!(out, "\nBy repository:")?;
+    writeln!(
+        out,
+        "| {:<30} | {:>9} | {:>8} | {:>6} | {:>7} |",
+        "repository", "loc", "commits", "files", "authors"
+    )?;
+    writeln!(
+        out,
+        "|:{:-<31}|{:->11}|{:->10}|{:->8}|{:->9}|",
+        "", "", "", "", ""
+    )?;
+    for (path, s) in parts {
+        writeln!(
+            out,
+            "| {:<30} | {:>9} | {:>8} | {:>6} | {:>7} |",
+            crate::output::truncate(path, 30),
+            s.total_loc,
+            s.total_commits,
+            s.total_files,
+            s.authors.len()
+        )?;
+    }
+    Ok(())
+}
+
+/// One `path: N commits` line per repository under a chart.
+pub fn write_commit_subtotals<W: Write>(out: &mut W, parts: &[(String, usize)]) -> io::Result<()> {
+    let width = parts
+        .iter()
+        .map(|(p, _)| crate::output::display_width(p))
+        .max()
+        .unwrap_or(0);
+    writeln!(out, "\nCommits in window by repository:")?;
+    for (path, n) in parts {
+        writeln!(out, "  {:<width$}  {}", crate::output::cell(path), n)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_list_and_merge_stats() {
+        let list = parse_submodule_list("vendor/lib\t/src/app/vendor/lib\nbad line\n");
+        assert_eq!(
+            list,
+            vec![(
+                "vendor/lib".to_string(),
+                PathBuf::from("/src/app/vendor/lib")
+            )]
+        );
+
+        let mut all = StatsMap::new();
+        let mut part = StatsMap::new();
+        part.entry("ann".to_string()).or_default().loc = 3;
+        part.get_mut("ann")
+            .unwrap()
+            .files
+            .insert("a.rs".to_string());
+        merge_stats(&mut all, part.clone(), SUPERPROJECT);
+        merge_stats(&mut all, part, "vendor/lib");
+        assert_eq!(all["ann"].loc, 6);
+        assert!(all["ann"].files.contains("vendor/lib/a.rs"));
+        assert!(all["ann"].files.contains("a.rs"));
+    }
+
+    #[test]
+    fn test_recurse_submodules_e2e() {
+        let _guard = crate::test_sync::test_lock();
+        let sub = crate::test_repo::TestRepo::init().expect("init sub");
+        sub.commit_with_epoch("Bob", "bob@x.io", "lib.rs", "fn lib() {}", 1_600_000_000)
+            .expect("commit");
+        let top = crate::test_repo::TestRepo::init().expect("init top");
+        top.commit_with_epoch("Ann", "ann@x.io", "main.rs", "fn main() {}", 1_600_000_100)
+            .expect("commit");
+        let top_repo = Repo::open(&top.path).expect("open");
+        let sub_path = sub.path.to_string_lossy().to_string();
+        top_repo
+            .run_command(&[
+                "-c",
+                "protocol.file.allow=always",
+                "submodule",
+                "--quiet",
+                "add",
+                &sub_path,
+                "vendor/lib",
+            ])
+            .expect("submodule add");
+
+        let subs = top_repo.scope(initialized_submodules).expect("list");
+        assert_eq!(subs.len(), 1);
+        assert_eq!(subs[0].path, "vendor/lib");
+
+        set_recurse_submodules(true);
+        let result = top_repo.scope(|| crate::stats::compute_stats_by_repo(true));
+        set_recurse_submodules(false);
+        let (all, parts) = result.expect("stats");
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[1].0, "vendor/lib");
+        assert_eq!(parts[1].1.total_loc, 1);
+        let bob = all.authors.iter().find(|(a, _)| a == "Bob").expect("bob");
+        assert!(bob.1.files.contains("vendor/lib/lib.rs"));
+
+        let mut out = Vec::new();
+        write_stats_subtotals(&mut out, &parts).expect("write");
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("| vendor/lib "));
+    }
+}
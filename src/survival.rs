@@ -0,0 +1,318 @@
+//! Line survival: how long lines live before they are rewritten or deleted,
+//! estimated from blame at evenly spaced commits.
+
+use crate::error::GitInsightsError;
+use crate::growth::sample_commits;
+use crate::merges::{blame_line_authors, run_blame, MergeResolver};
+use crate::output::{finish_progress, print_progress};
+use crate::stats::{prepare_blame, tracked_text_files_at};
+use std::collections::{BTreeMap, HashMap};
+use std::time::Instant;
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// Default number of sampled commits (the newest is always one of them).
+pub const DEFAULT_SURVIVAL_SAMPLES: usize = 6;
+
+/// One blamed line: where it was introduced and when.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlamedLine {
+    /// `(commit, file in that commit, line number in that file)`, stable
+    /// across later blames as long as the line survives.
+    pub origin: (String, String, usize),
+    /// Author time of the introducing commit.
+    pub written: u64,
+}
+
+/// `--line-porcelain` output reduced to one [`BlamedLine`] per content line.
+pub fn parse_blame_origins(blame: &str) -> Vec<BlamedLine> {
+    let mut lines = Vec::new();
+    let mut sha = String::new();
+    let mut orig_line = 0usize;
+    let mut file = String::new();
+    let mut written = 0u64;
+    for line in blame.lines() {
+        if line.starts_with('\t') {
+            lines.push(BlamedLine {
+                origin: (sha.clone(), file.clone(), orig_line),
+                written,
+            });
+        } else if let Some(rest) = line.strip_prefix("author-time ") {
+            written = rest.trim().parse().unwrap_or(0);
+        } else if let Some(rest) = line.strip_prefix("filename ") {
+            file = rest.to_string();
+        } else {
+            let mut parts = line.split(' ');
+            let first = parts.next().unwrap_or("");
+            if first.len() >= 40 && first.bytes().all(|b| b.is_ascii_hexdigit()) {
+                sha = first.to_string();
+                orig_line = parts.next().and_then(|n| n.parse().ok()).unwrap_or(0);
+            }
+        }
+    }
+    lines
+}
+
+/// A line seen in at least one sample.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineLife {
+    pub author: String,
+    /// Top-level directory (`src/`) of the path it was last seen at, or `.`.
+    pub dir: String,
+    pub written: u64,
+    /// Sample time it was first missing from, or `None` if it survives to the
+    /// newest sample.
+    pub removed: Option<u64>,
+    /// Time of the newest sample, the end of observation for survivors.
+    pub observed_until: u64,
+}
+
+impl LineLife {
+    /// Observed lifetime in seconds and whether it ended in removal.
+    pub fn lifetime(&self) -> (u64, bool) {
+        match self.removed {
+            Some(t) => (t.saturating_sub(self.written), true),
+            None => (self.observed_until.saturating_sub(self.written), false),
+        }
+    }
+}
+
+fn top_dir(path: &str) -> String {
+    match path.split_once('/') {
+        Some((dir, _)) => format!("{}/", dir),
+        None => ".".to_string(),
+    }
+}
+
+/// Kaplan-Meier median of `(lifetime, removed)` observations: the first
+/// lifetime at which the estimated share of surviving lines drops to half.
+/// `None` when more than half outlive the observation window.
+pub fn median_lifetime(observations: &[(u64, bool)]) -> Option<u64> {
+    let mut sorted = observations.to_vec();
+    sorted.sort_unstable();
+    let mut at_risk = sorted.len();
+    let mut survival = 1.0f64;
+    let mut i = 0;
+    while i < sorted.len() {
+        let t = sorted[i].0;
+        let same: Vec<&(u64, bool)> = sorted[i..].iter().take_while(|o| o.0 == t).collect();
+        let removed = same.iter().filter(|o| o.1).count();
+        if removed > 0 {
+            survival *= 1.0 - removed as f64 / at_risk as f64;
+            if survival <= 0.5 {
+                return Some(t);
+            }
+        }
+        at_risk -= same.len();
+        i += same.len();
+    }
+    None
+}
+
+/// Blame every text file at each sampled commit and follow lines by origin.
+/// Lines that appear and vanish between two samples are not seen.
+pub fn gather_line_lives(samples: usize) -> Result<Vec<LineLife>, GitInsightsError> {
+    let picked = sample_commits(samples)?;
+    let Some((_, newest)) = picked.last().cloned() else {
+        return Ok(Vec::new());
+    };
+    prepare_blame();
+    let authors = crate::filter::author_matcher()?;
+    let names = crate::identity::active_normalization();
+    // origin -> (life, index of the last sample it was seen in)
+    let mut seen: HashMap<(String, String, usize), (LineLife, usize)> = HashMap::new();
+    let start = Instant::now();
+    for (k, (sha, _)) in picked.iter().enumerate() {
+        let files = tracked_text_files_at(sha)?;
+        let merges = MergeResolver::load_at(sha)?;
+        for file in &files {
+            let Ok(blame) = run_blame(sha, file) else {
+                continue;
+            };
+            let who = blame_line_authors(&blame, file, &merges);
+            for (line, (name, mail)) in parse_blame_origins(&blame).into_iter().zip(who) {
+                if !authors.allows(&name, &mail) {
+                    continue;
+                }
+                let entry = seen.entry(line.origin).or_insert_with(|| {
+                    let life = LineLife {
+                        author: names.apply(name),
+                        dir: String::new(),
+                        written: line.written,
+                        removed: None,
+                        observed_until: newest,
+                    };
+                    (life, k)
+                });
+                entry.0.dir = top_dir(file);
+                entry.1 = k;
+            }
+        }
+        print_progress(k + 1, picked.len(), start);
+    }
+    finish_progress();
+    let last = picked.len() - 1;
+    Ok(seen
+        .into_values()
+        .map(|(mut life, k)| {
+            if k < last {
+                life.removed = Some(picked[k + 1].1);
+            }
+            life
+        })
+        .collect())
+}
+
+/// Lines, removals and median lifetime for one group.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SurvivalRow {
+    pub key: String,
+    pub lines: usize,
+    pub removed: usize,
+    pub median: Option<u64>,
+    /// Longest observed lifetime, the lower bound when `median` is `None`.
+    pub longest: u64,
+}
+
+/// One row per group (by `key`), most lines first, ties by name.
+pub fn survival_rows(lives: &[LineLife], key: impl Fn(&LineLife) -> &str) -> Vec<SurvivalRow> {
+    let mut groups: BTreeMap<&str, Vec<(u64, bool)>> = BTreeMap::new();
+    for life in lives {
+        groups.entry(key(life)).or_default().push(life.lifetime());
+    }
+    let mut rows: Vec<SurvivalRow> = groups
+        .into_iter()
+        .map(|(k, obs)| SurvivalRow {
+            key: k.to_string(),
+            lines: obs.len(),
+            removed: obs.iter().filter(|o| o.1).count(),
+            median: median_lifetime(&obs),
+            longest: obs.iter().map(|o| o.0).max().unwrap_or(0),
+        })
+        .collect();
+    rows.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.key.cmp(&b.key)));
+    rows
+}
+
+fn format_median(row: &SurvivalRow) -> String {
+    match row.median {
+        Some(t) => format!("{}d", t / DAY),
+        None => format!(">{}d", row.longest / DAY),
+    }
+}
+
+fn print_survival_table(heading: &str, rows: &[SurvivalRow]) {
+    let width = rows
+        .iter()
+        .map(|r| crate::output::display_width(&r.key))
+        .max()
+        .unwrap_or(0)
+        .max(heading.len());
+    println!(
+        "| {:<width$} | {:>8} | {:>8} | {:>7} | {:>11} |",
+        heading, "lines", "removed", "share", "median life"
+    );
+    println!(
+        "|:{:-<w$}|{:->10}|{:->10}|{:->9}|{:->13}|",
+        "",
+        "",
+        "",
+        "",
+        "",
+        w = width + 1
+    );
+    for r in rows {
+        println!(
+            "| {:<width$} | {:>8} | {:>8} | {:>6.1}% | {:>11} |",
+            crate::output::cell(&r.key),
+            r.lines,
+            r.removed,
+            r.removed as f64 * 100.0 / r.lines.max(1) as f64,
+            format_median(r)
+        );
+    }
+}
+
+/// Print median line lifetime overall, per top-level directory and per author.
+pub fn run_survival(samples: usize) -> Result<(), GitInsightsError> {
+    let lives = gather_line_lives(samples)?;
+    if lives.is_empty() {
+        println!("(no lines)");
+        return Ok(());
+    }
+    let all = survival_rows(&lives, |_| "all");
+    print_survival_table("overall", &all);
+    println!();
+    print_survival_table("directory", &survival_rows(&lives, |l| &l.dir));
+    println!();
+    print_survival_table("author", &survival_rows(&lives, |l| &l.author));
+    println!();
+    println!(
+        "Lines are followed by origin across blames of {} sampled commits; a removed line died by the first sample missing it.",
+        samples
+    );
+    println!("Median life is a Kaplan-Meier estimate; '>Nd' means more than half of the lines outlived the window.");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_blame_origins_and_median() {
+        let sha = "1".repeat(40);
+        let blame = format!(
+            "{sha} 3 1 1\nauthor Ann\nauthor-time 100\nfilename old.rs\n\tone\n\
+             {sha} 4 2\nauthor Ann\nauthor-time 100\nfilename old.rs\n\ttwo\n"
+        );
+        let lines = parse_blame_origins(&blame);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].origin, (sha.clone(), "old.rs".to_string(), 4));
+        assert_eq!(lines[1].written, 100);
+
+        // Removed at 10 and 20; two survivors observed for 30.
+        let obs = [(10, true), (20, true), (30, false), (30, false)];
+        assert_eq!(median_lifetime(&obs), Some(20));
+        assert_eq!(
+            median_lifetime(&[(10, true), (30, false), (30, false)]),
+            None
+        );
+        assert_eq!(median_lifetime(&[]), None);
+    }
+
+    #[test]
+    fn test_gather_line_lives() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        let t0 = 1_600_000_000;
+        repo.commit_with_epoch("Ann", "ann@x.io", "src/a.txt", "one", t0)
+            .expect("commit");
+        repo.commit_with_epoch("Bob", "bob@x.io", "b.txt", "two", t0 + 10 * DAY)
+            .expect("commit");
+        std::fs::write(repo.path.join("src/a.txt"), "uno\n").expect("rewrite");
+        repo.commit_with_epoch("Bob", "bob@x.io", "b.txt", "three", t0 + 20 * DAY)
+            .expect("commit");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        let lives = gather_line_lives(3);
+        std::env::set_current_dir(old).ok();
+
+        let lives = lives.expect("lives");
+        assert_eq!(lives.len(), 4);
+        let ann = lives.iter().find(|l| l.author == "Ann").expect("Ann");
+        assert_eq!(ann.dir, "src/");
+        assert_eq!(ann.removed, Some(t0 + 20 * DAY));
+        assert_eq!(ann.lifetime(), (20 * DAY, true));
+
+        let dirs = survival_rows(&lives, |l| &l.dir);
+        assert_eq!(dirs[0].key, ".");
+        assert_eq!(
+            (dirs[0].lines, dirs[0].removed, dirs[0].median),
+            (2, 0, None)
+        );
+        let src = &dirs[1];
+        assert_eq!((src.lines, src.removed, src.median), (2, 1, Some(20 * DAY)));
+    }
+}
@@ -0,0 +1,305 @@
+use crate::error::GitInsightsError;
+use crate::output::write_export;
+use std::fmt::Write as _;
+
+/// GitHub-like green ramp; index 0 is used for empty cells.
+pub const RAMP: [&str; 5] = ["#ebedf0", "#9be9a8", "#40c463", "#30a14e", "#216e39"];
+
+const AXIS_ATTRS: &str = "font-family=\"sans-serif\" font-size=\"10\" fill=\"#57606a\"";
+
+/// Escape text for XML/HTML content and attributes.
+pub fn xml_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// Map a value to a ramp color (0 -> empty color).
+pub fn color_for(v: usize, max: usize) -> &'static str {
+    RAMP[level_for(v, max, RAMP.len())]
+}
+
+/// Index into a ramp of `levels` colors; 0 only for empty values.
+pub fn level_for(v: usize, max: usize, levels: usize) -> usize {
+    if max == 0 || v == 0 {
+        return 0;
+    }
+    let l = levels - 1;
+    (((v - 1) * l) / max + 1).min(l)
+}
+
+/// Render a grid of cells as an SVG heatmap with axes and a color scale.
+///
+/// Empty labels are skipped, so callers can thin out dense axes.
+pub fn render_heatmap(
+    rows: &[Vec<usize>],
+    row_labels: &[String],
+    col_labels: &[String],
+    title: Option<&str>,
+) -> String {
+    const CELL: usize = 14;
+    const GAP: usize = 2;
+    const LEFT: usize = 34;
+    const LEGEND_H: usize = 26;
+    let title_h = if title.is_some() { 20 } else { 0 };
+    let top = title_h + 16;
+    let cols = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    let max = rows.iter().flatten().copied().max().unwrap_or(0);
+    let grid_w = cols * (CELL + GAP);
+    let width = (LEFT + grid_w).max(LEFT + 220);
+    let grid_bottom = top + rows.len() * (CELL + GAP);
+    let height = grid_bottom + LEGEND_H;
+
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">",
+        w = width,
+        h = height
+    );
+    if let Some(t) = title {
+        let _ = write!(
+            out,
+            "<text x=\"0\" y=\"14\" font-family=\"sans-serif\" font-size=\"13\" fill=\"#24292f\">{}</text>",
+            xml_escape(t)
+        );
+    }
+    for (c, lab) in col_labels.iter().enumerate().take(cols) {
+        if lab.is_empty() {
+            continue;
+        }
+        let _ = write!(
+            out,
+            "<text x=\"{}\" y=\"{}\" {}>{}</text>",
+            LEFT + c * (CELL + GAP),
+            top - 4,
+            AXIS_ATTRS,
+            xml_escape(lab)
+        );
+    }
+    for (r, row) in rows.iter().enumerate() {
+        let y = top + r * (CELL + GAP);
+        if let Some(lab) = row_labels.get(r).filter(|l| !l.is_empty()) {
+            let _ = write!(
+                out,
+                "<text x=\"0\" y=\"{}\" {}>{}</text>",
+                y + CELL - 3,
+                AXIS_ATTRS,
+                xml_escape(lab)
+            );
+        }
+        for (c, &v) in row.iter().enumerate() {
+            let _ = write!(
+                out,
+                "<rect x=\"{}\" y=\"{}\" width=\"{CELL}\" height=\"{CELL}\" rx=\"2\" fill=\"{}\"><title>{}</title></rect>",
+                LEFT + c * (CELL + GAP),
+                y,
+                color_for(v, max),
+                v
+            );
+        }
+    }
+
+    // Color scale: "Less [][][][][] More (max=N)"
+    let ly = grid_bottom + 6;
+    let _ = write!(
+        out,
+        "<text x=\"{}\" y=\"{}\" {}>Less</text>",
+        LEFT,
+        ly + 10,
+        AXIS_ATTRS
+    );
+    let swatch_x = LEFT + 30;
+    for (i, color) in RAMP.iter().enumerate() {
+        let _ = write!(
+            out,
+            "<rect x=\"{}\" y=\"{}\" width=\"12\" height=\"12\" rx=\"2\" fill=\"{}\"/>",
+            swatch_x + i * 14,
+            ly,
+            color
+        );
+    }
+    let _ = write!(
+        out,
+        "<text x=\"{}\" y=\"{}\" {}>More (max={})</text>",
+        swatch_x + RAMP.len() * 14 + 4,
+        ly + 10,
+        AXIS_ATTRS,
+        max
+    );
+    out.push_str("</svg>");
+    out
+}
+
+/// Render a vertical bar chart as SVG.
+pub fn render_bars(labels: &[String], counts: &[usize]) -> String {
+    const BAR: usize = 16;
+    const GAP: usize = 4;
+    const PLOT_H: usize = 120;
+    const BOTTOM: usize = 18;
+    let max = counts.iter().copied().max().unwrap_or(0);
+    let width = counts.len() * (BAR + GAP);
+    let height = PLOT_H + BOTTOM;
+
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">",
+        w = width,
+        h = height
+    );
+    for (i, &c) in counts.iter().enumerate() {
+        let bar_h = if max == 0 {
+            0
+        } else {
+            (c * PLOT_H).div_ceil(max)
+        };
+        let x = i * (BAR + GAP);
+        let _ = write!(
+            out,
+            "<rect x=\"{}\" y=\"{}\" width=\"{BAR}\" height=\"{}\" fill=\"{}\"><title>{}</title></rect>",
+            x,
+            PLOT_H - bar_h,
+            bar_h,
+            color_for(c, max),
+            c
+        );
+        if let Some(lab) = labels.get(i).filter(|l| !l.is_empty()) {
+            let _ = write!(
+                out,
+                "<text x=\"{}\" y=\"{}\" {}>{}</text>",
+                x,
+                height - 4,
+                AXIS_ATTRS,
+                xml_escape(lab)
+            );
+        }
+    }
+    out.push_str("</svg>");
+    out
+}
+
+fn is_png(path: &str) -> bool {
+    path.to_ascii_lowercase().ends_with(".png")
+}
+
+#[cfg(not(feature = "charts"))]
+fn png_unsupported(path: &str) -> String {
+    format!(
+        "unsupported output '{}': PNG charts need a build with `--features charts`",
+        path
+    )
+}
+
+/// Write a heatmap to `path`: PNG for `.png` paths (`charts` feature), SVG
+/// otherwise.
+pub fn write_heatmap_chart(
+    path: &str,
+    rows: &[Vec<usize>],
+    row_labels: &[String],
+    col_labels: &[String],
+    title: &str,
+) -> Result<(), GitInsightsError> {
+    if is_png(path) {
+        #[cfg(feature = "charts")]
+        return crate::png::write_png(
+            path,
+            &crate::png::render_heatmap(rows, row_labels, col_labels, Some(title)),
+        );
+        #[cfg(not(feature = "charts"))]
+        return Err(png_unsupported(path).into());
+    }
+    write_svg(
+        path,
+        &render_heatmap(rows, row_labels, col_labels, Some(title)),
+    )
+}
+
+/// Write a bar chart to `path`: PNG for `.png` paths (`charts` feature), SVG
+/// otherwise. Only the PNG carries the title.
+pub fn write_bars_chart(
+    path: &str,
+    labels: &[String],
+    counts: &[usize],
+    title: &str,
+) -> Result<(), GitInsightsError> {
+    if is_png(path) {
+        #[cfg(feature = "charts")]
+        return crate::png::write_png(path, &crate::png::render_bars(labels, counts, Some(title)));
+        #[cfg(not(feature = "charts"))]
+        return Err(png_unsupported(path).into());
+    }
+    let _ = title;
+    write_svg(path, &render_bars(labels, counts))
+}
+
+/// Write an SVG document to `path` (`-` for stdout).
+pub fn write_svg(path: &str, svg: &str) -> Result<(), GitInsightsError> {
+    if path != "-" && !path.to_ascii_lowercase().ends_with(".svg") {
+        return Err(format!(
+            "unsupported output '{}': only .svg and .png files are supported",
+            path
+        )
+        .into());
+    }
+    let doc = format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n{}\n", svg);
+    write_export(Some(path), path, doc.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xml_escape() {
+        assert_eq!(
+            xml_escape("<a href=\"x\">&'</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&#39;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_color_for_ramp() {
+        assert_eq!(color_for(0, 10), RAMP[0]);
+        assert_eq!(color_for(10, 10), RAMP[4]);
+        assert_ne!(color_for(1, 10), RAMP[0]);
+    }
+
+    #[test]
+    fn test_render_heatmap_axes_and_scale() {
+        let rows = vec![vec![0, 1, 2], vec![3, 0, 0]];
+        let row_labels = vec!["Sun".to_string(), "Mon".to_string()];
+        let col_labels = vec!["00".to_string(), String::new(), "02".to_string()];
+        let svg = render_heatmap(&rows, &row_labels, &col_labels, Some("A & B"));
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.ends_with("</svg>"));
+        assert!(svg.contains(">A &amp; B</text>"));
+        assert!(svg.contains(">Sun</text>") && svg.contains(">Mon</text>"));
+        assert!(svg.contains(">00</text>") && svg.contains(">02</text>"));
+        assert!(svg.contains("More (max=3)"));
+        // 6 cells + 5 legend swatches
+        assert_eq!(svg.matches("<rect").count(), 11);
+    }
+
+    #[test]
+    fn test_write_svg_rejects_other_extensions() {
+        let err = write_svg("chart.jpg", "<svg/>").expect_err("jpg not supported");
+        assert!(err.to_string().contains("only .svg"));
+    }
+
+    #[cfg(not(feature = "charts"))]
+    #[test]
+    fn test_png_chart_needs_feature() {
+        let err = write_bars_chart("chart.PNG", &[], &[1], "t").expect_err("no charts feature");
+        assert!(err.to_string().contains("--features charts"));
+    }
+}
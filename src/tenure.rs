@@ -0,0 +1,216 @@
+use crate::commits::{collect_commits, Commit};
+use crate::error::GitInsightsError;
+use crate::merges::{blame_line_authors, blame_line_times, run_blame_head, MergeResolver};
+use crate::output::{finish_progress, print_progress};
+use crate::stats::{prepare_blame, tracked_text_files_head};
+use std::collections::{BTreeSet, HashMap};
+use std::time::Instant;
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// How long an author had been contributing when they wrote a line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum TenureBand {
+    /// Under 3 months (90 days) since the author's first commit.
+    Newcomer,
+    /// 3 to 12 months.
+    Established,
+    /// Over a year (365 days).
+    Veteran,
+    /// No first-commit date for the author (e.g. synthetic merge lines).
+    Unknown,
+}
+
+impl TenureBand {
+    pub const ALL: [TenureBand; 4] = [
+        TenureBand::Newcomer,
+        TenureBand::Established,
+        TenureBand::Veteran,
+        TenureBand::Unknown,
+    ];
+
+    /// Band for a line written at `written` by an author first seen at `first`.
+    pub fn classify(first: u64, written: u64) -> TenureBand {
+        let days = written.saturating_sub(first) / DAY;
+        if days < 90 {
+            TenureBand::Newcomer
+        } else if days < 365 {
+            TenureBand::Established
+        } else {
+            TenureBand::Veteran
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TenureBand::Newcomer => "<3mo",
+            TenureBand::Established => "3-12mo",
+            TenureBand::Veteran => ">1y",
+            TenureBand::Unknown => "unknown",
+        }
+    }
+}
+
+/// Surviving lines and distinct authors in one tenure band.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BandTotals {
+    pub lines: usize,
+    pub authors: BTreeSet<String>,
+}
+
+/// Earliest author time per (normalized) author name.
+pub fn first_commit_times(commits: &[Commit]) -> HashMap<String, u64> {
+    let mut first: HashMap<String, u64> = HashMap::new();
+    for c in commits {
+        let t = first.entry(c.author_name.clone()).or_insert(c.author_time);
+        *t = (*t).min(c.author_time);
+    }
+    first
+}
+
+/// Credit every line of one file's blame to the band its author was in when
+/// the line was written.
+pub fn tally_blame(
+    blame: &str,
+    file: &str,
+    resolver: &MergeResolver,
+    allow: impl Fn(&str, &str) -> bool,
+    first: &HashMap<String, u64>,
+    bands: &mut HashMap<TenureBand, BandTotals>,
+) {
+    let names = crate::identity::active_normalization();
+    for ((name, mail), written) in blame_line_authors(blame, file, resolver)
+        .into_iter()
+        .zip(blame_line_times(blame))
+    {
+        if !allow(&name, &mail) {
+            continue;
+        }
+        let name = names.apply(name);
+        let band = match first.get(&name) {
+            Some(&f) => TenureBand::classify(f, written),
+            None => TenureBand::Unknown,
+        };
+        let entry = bands.entry(band).or_default();
+        entry.lines += 1;
+        entry.authors.insert(name);
+    }
+}
+
+/// Surviving lines at HEAD bucketed by author tenure at the time of writing.
+pub fn gather_ownership_by_tenure() -> Result<HashMap<TenureBand, BandTotals>, GitInsightsError> {
+    let keep_merges = crate::filter::active().include_merges;
+    let commits: Vec<Commit> = collect_commits()?
+        .into_iter()
+        .filter(|c| keep_merges || !c.is_merge())
+        .collect();
+    let first = first_commit_times(&commits);
+
+    prepare_blame();
+    let files = tracked_text_files_head()?;
+    let authors = crate::filter::author_matcher()?;
+    let merges = MergeResolver::load()?;
+    let mut bands: HashMap<TenureBand, BandTotals> = HashMap::new();
+    let total = files.len();
+    let start = Instant::now();
+    for (n, file) in files.iter().enumerate() {
+        print_progress(n + 1, total, start);
+        let Ok(blame) = run_blame_head(file) else {
+            continue;
+        };
+        tally_blame(
+            &blame,
+            file,
+            &merges,
+            |n, e| authors.allows(n, e),
+            &first,
+            &mut bands,
+        );
+    }
+    if total > 0 {
+        finish_progress();
+    }
+    Ok(bands)
+}
+
+/// Print lines, share and authors per tenure band.
+pub fn run_ownership_by_tenure() -> Result<(), GitInsightsError> {
+    let bands = gather_ownership_by_tenure()?;
+    let total: usize = bands.values().map(|b| b.lines).sum();
+    println!(
+        "| {:<8} | {:>10} | {:>7} | {:>7} |",
+        "tenure", "lines", "share", "authors"
+    );
+    println!("|:{:-<9}|{:->12}|{:->9}|{:->9}|", "", "", "", "");
+    let empty = BandTotals::default();
+    for band in TenureBand::ALL {
+        let b = bands.get(&band).unwrap_or(&empty);
+        if band == TenureBand::Unknown && b.lines == 0 {
+            continue;
+        }
+        println!(
+            "| {:<8} | {:>10} | {:>6.1}% | {:>7} |",
+            band.label(),
+            b.lines,
+            b.lines as f64 * 100.0 / total.max(1) as f64,
+            b.authors.len()
+        );
+    }
+    println!();
+    println!("Tenure is the time between an author's first commit and the commit that wrote each surviving line.");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tenure_classify() {
+        let t0 = 1_600_000_000;
+        assert_eq!(TenureBand::classify(t0, t0), TenureBand::Newcomer);
+        assert_eq!(
+            TenureBand::classify(t0, t0 + 89 * DAY),
+            TenureBand::Newcomer
+        );
+        assert_eq!(
+            TenureBand::classify(t0, t0 + 90 * DAY),
+            TenureBand::Established
+        );
+        assert_eq!(
+            TenureBand::classify(t0, t0 + 365 * DAY),
+            TenureBand::Veteran
+        );
+        // Rebased history can date a line before the author's first commit.
+        assert_eq!(TenureBand::classify(t0, t0 - DAY), TenureBand::Newcomer);
+    }
+
+    #[test]
+    fn test_ownership_by_tenure_e2e() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        let t0 = 1_600_000_000;
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", "one", t0)
+            .expect("commit");
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", "two", t0 + 200 * DAY)
+            .expect("commit");
+        repo.commit_with_epoch("Ann", "ann@x.io", "b.txt", "three", t0 + 400 * DAY)
+            .expect("commit");
+        repo.commit_with_epoch("Bob", "bob@x.io", "b.txt", "four", t0 + 401 * DAY)
+            .expect("commit");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        let bands = gather_ownership_by_tenure();
+        std::env::set_current_dir(old).ok();
+
+        let bands = bands.expect("bands");
+        let newcomer = &bands[&TenureBand::Newcomer];
+        assert_eq!(newcomer.lines, 2);
+        assert_eq!(newcomer.authors.len(), 2);
+        assert_eq!(bands[&TenureBand::Established].lines, 1);
+        assert_eq!(bands[&TenureBand::Veteran].lines, 1);
+        assert!(!bands.contains_key(&TenureBand::Unknown));
+    }
+}
@@ -568,8 +568,8 @@ mod tests {
         std::env::set_current_dir(&repo.path).expect("cd");
 
         let ts = crate::visualize::collect_commit_timestamps().expect("collect");
-        assert!(ts.iter().any(|&x| x == t1), "missing t1");
-        assert!(ts.iter().any(|&x| x == t2), "missing t2");
+        assert!(ts.contains(&t1), "missing t1");
+        assert!(ts.contains(&t2), "missing t2");
 
         std::env::set_current_dir(old).ok();
     }
@@ -667,6 +667,16 @@ mod tests {
             .expect("ownership sort pct failed");
         assert!(rows_top.len() <= 2);
 
+        let kept = crate::stats::get_user_file_ownership_with("Alice", false, 10, false, 100.0, 5)
+            .expect("ownership with thresholds failed");
+        assert_eq!(kept.rows.len(), 1);
+        assert_eq!((kept.total_loc, kept.files), (5, 1));
+        assert_eq!(kept.top_dir, Some((".".to_string(), 5)));
+        let hidden = crate::stats::get_user_file_ownership_with("Alice", false, 10, false, 0.0, 6)
+            .expect("ownership with min loc failed");
+        assert!(hidden.rows.is_empty());
+        assert_eq!(hidden.total_loc, 5);
+
         std::env::set_current_dir(old).ok();
     }
 
@@ -681,7 +691,7 @@ mod tests {
             "alice@test_git_insights.com",
             "a.txt",
             "a\n",
-            base_day + 0,
+            base_day,
         )
         .expect("c1");
         repo.commit_with_epoch(
@@ -707,7 +717,7 @@ mod tests {
         let repo = TestRepo::init().expect("init repo");
         let base_day = 30 * 86_400;
 
-        repo.commit_with_epoch("A", "a@test_git_insights.com", "a.txt", "a\n", base_day + 0)
+        repo.commit_with_epoch("A", "a@test_git_insights.com", "a.txt", "a\n", base_day)
             .expect("c1");
         repo.commit_with_epoch(
             "B",
@@ -0,0 +1,247 @@
+use crate::code_frequency::ymd_from_unix;
+use crate::commits::Commit;
+use crate::error::GitInsightsError;
+use crate::git::run_command_line_iter;
+use crate::output::truncate;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Default tolerance for clock skew (future dates, committer before author).
+pub const DEFAULT_MAX_SKEW: u64 = 24 * 60 * 60;
+
+/// Timestamps inside the first day of 1970 are treated as unset clocks.
+const EPOCH_ZERO_WINDOW: u64 = 24 * 60 * 60;
+
+/// Why a commit's timestamps are implausible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TimeIssue {
+    /// Author or committer time within a day of the Unix epoch.
+    EpochZero,
+    /// Author or committer time later than now (plus the skew tolerance).
+    Future,
+    /// Dated before the oldest root commit of the analysed history.
+    BeforeInception,
+    /// Committed more than the skew tolerance before it was authored.
+    CommitterBeforeAuthor,
+}
+
+impl TimeIssue {
+    pub fn label(self) -> &'static str {
+        match self {
+            TimeIssue::EpochZero => "epoch-zero",
+            TimeIssue::Future => "future",
+            TimeIssue::BeforeInception => "before-inception",
+            TimeIssue::CommitterBeforeAuthor => "committer-before-author",
+        }
+    }
+}
+
+/// Bounds a commit's author/committer times must fall within.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeSanity {
+    pub now: u64,
+    /// Author time of the oldest plausible root commit (0 when unknown).
+    pub inception: u64,
+    /// Seconds of clock skew tolerated before flagging.
+    pub max_skew: u64,
+}
+
+impl TimeSanity {
+    /// Every problem with one commit's timestamps (empty when plausible).
+    pub fn issues(&self, author_time: u64, committer_time: u64) -> Vec<TimeIssue> {
+        let mut issues = Vec::new();
+        let times = [author_time, committer_time];
+        if times.iter().any(|&t| t < EPOCH_ZERO_WINDOW) {
+            issues.push(TimeIssue::EpochZero);
+        } else if times.iter().any(|&t| t < self.inception) {
+            issues.push(TimeIssue::BeforeInception);
+        }
+        if times
+            .iter()
+            .any(|&t| t > self.now.saturating_add(self.max_skew))
+        {
+            issues.push(TimeIssue::Future);
+        }
+        if author_time > committer_time.saturating_add(self.max_skew) {
+            issues.push(TimeIssue::CommitterBeforeAuthor);
+        }
+        issues
+    }
+
+    pub fn allows(&self, author_time: u64, committer_time: u64) -> bool {
+        self.issues(author_time, committer_time).is_empty()
+    }
+}
+
+/// Author time of the oldest root commit that is not itself implausible.
+pub fn project_inception(now: u64) -> Result<u64, GitInsightsError> {
+    let revs = crate::filter::revision_args()?;
+    let mut args = vec!["--no-pager", "log", "--max-parents=0", "--format=%at"];
+    args.extend(revs.iter().map(|s| s.as_str()));
+    run_command_line_iter(&args, |lines| {
+        lines
+            .filter_map(|l| l.trim().parse::<u64>().ok())
+            .filter(|&t| t >= EPOCH_ZERO_WINDOW && t <= now)
+            .min()
+            .unwrap_or(0)
+    })
+}
+
+/// Sanity bounds for the current history and clock.
+pub fn load_sanity(max_skew: u64) -> Result<TimeSanity, GitInsightsError> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| format!("clock error: {e}"))?
+        .as_secs();
+    Ok(TimeSanity {
+        now,
+        inception: project_inception(now)?,
+        max_skew,
+    })
+}
+
+/// Bounds to drop commits by, when `--exclude-bad-times` is active.
+pub fn active_sanity() -> Result<Option<TimeSanity>, GitInsightsError> {
+    if !crate::filter::active().exclude_bad_times {
+        return Ok(None);
+    }
+    load_sanity(DEFAULT_MAX_SKEW).map(Some)
+}
+
+/// A commit and everything wrong with its timestamps.
+pub type SuspectCommit = (Commit, Vec<TimeIssue>);
+
+/// Commits with at least one [`TimeIssue`], newest first, plus the number checked.
+pub fn gather_time_audit(max_skew: u64) -> Result<(Vec<SuspectCommit>, usize), GitInsightsError> {
+    let sanity = load_sanity(max_skew)?;
+    let commits = crate::commits::collect_all_commits()?;
+    let total = commits.len();
+    let suspects = commits
+        .into_iter()
+        .filter_map(|c| {
+            let issues = sanity.issues(c.author_time, c.committer_time);
+            (!issues.is_empty()).then_some((c, issues))
+        })
+        .collect();
+    Ok((suspects, total))
+}
+
+fn format_date(t: u64) -> String {
+    let (y, m, d) = ymd_from_unix(t);
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+/// Print commits whose timestamps would distort time-based charts.
+pub fn run_time_audit(max_skew: u64) -> Result<(), GitInsightsError> {
+    let (suspects, total) = gather_time_audit(max_skew)?;
+    if suspects.is_empty() {
+        println!("All {} commits have plausible timestamps.", total);
+        return Ok(());
+    }
+    println!(
+        "| {:<10} | {:<10} | {:<10} | {:<20} | {:<32} | {:<40} |",
+        "commit", "authored", "committed", "author", "issues", "subject"
+    );
+    println!(
+        "|:{:-<11}|:{:-<11}|:{:-<11}|:{:-<21}|:{:-<33}|:{:-<41}|",
+        "", "", "", "", "", ""
+    );
+    for (c, issues) in &suspects {
+        let labels = issues
+            .iter()
+            .map(|i| i.label())
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!(
+            "| {:<10} | {:<10} | {:<10} | {:<20} | {:<32} | {:<40} |",
+            &c.sha[..c.sha.len().min(10)],
+            format_date(c.author_time),
+            format_date(c.committer_time),
+            truncate(&c.author_name, 20),
+            truncate(&labels, 32),
+            truncate(&c.subject, 40)
+        );
+    }
+    println!();
+    println!(
+        "{} of {} commits have implausible timestamps; pass --exclude-bad-times to leave them out of time-based analyses.",
+        suspects.len(),
+        total
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_sanity_issues() {
+        let s = TimeSanity {
+            now: 1_700_000_000,
+            inception: 1_600_000_000,
+            max_skew: DEFAULT_MAX_SKEW,
+        };
+        assert!(s.allows(1_650_000_000, 1_650_000_000));
+        assert!(s.allows(1_650_000_000, 1_650_000_000 - 3_600));
+        assert_eq!(s.issues(0, 1_650_000_000), vec![TimeIssue::EpochZero]);
+        assert_eq!(
+            s.issues(1_500_000_000, 1_650_000_000),
+            vec![TimeIssue::BeforeInception]
+        );
+        assert_eq!(
+            s.issues(1_800_000_000, 1_800_000_000),
+            vec![TimeIssue::Future]
+        );
+        assert_eq!(
+            s.issues(1_650_000_000, 1_640_000_000),
+            vec![TimeIssue::CommitterBeforeAuthor]
+        );
+        assert!(s.allows(1_700_000_000 + 3_600, 1_700_000_000 + 3_600));
+    }
+
+    #[test]
+    fn test_time_audit_and_exclusion_e2e() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", "root", 1_600_000_000)
+            .expect("commit");
+        repo.commit_with_epoch("Bob", "bob@x.io", "a.txt", "old", 1_500_000_000)
+            .expect("commit");
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", "ok", 1_650_000_000)
+            .expect("commit");
+        repo.commit_with_epoch("Cat", "cat@x.io", "a.txt", "late", 4_000_000_000)
+            .expect("commit");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        let audit = gather_time_audit(DEFAULT_MAX_SKEW);
+        crate::filter::set_active(crate::filter::CommitFilter {
+            exclude_bad_times: true,
+            ..Default::default()
+        });
+        let timestamps = crate::visualize::collect_commit_timestamps();
+        let commits = crate::commits::collect_commits();
+        crate::filter::set_active(crate::filter::CommitFilter::default());
+        std::env::set_current_dir(old).ok();
+
+        let (suspects, total) = audit.expect("audit");
+        assert_eq!(total, 4);
+        let flagged: Vec<(&str, Vec<TimeIssue>)> = suspects
+            .iter()
+            .map(|(c, i)| (c.author_name.as_str(), i.clone()))
+            .collect();
+        assert_eq!(
+            flagged,
+            vec![
+                ("Cat", vec![TimeIssue::Future]),
+                ("Bob", vec![TimeIssue::BeforeInception]),
+            ]
+        );
+        assert_eq!(
+            timestamps.expect("timestamps"),
+            vec![1_650_000_000, 1_600_000_000]
+        );
+        assert_eq!(commits.expect("commits").len(), 2);
+    }
+}
@@ -0,0 +1,155 @@
+use crate::bursts::collect_sized_commits;
+use crate::code_frequency::render_histogram_labeled;
+use crate::error::GitInsightsError;
+use crate::stats::{gather_commit_statsx, gather_loc_and_file_statsx};
+use crate::visualize::ChartSize;
+use std::collections::HashMap;
+use std::io;
+
+/// Default number of leaderboard rows.
+pub const DEFAULT_TOP_LIMIT: usize = 10;
+
+/// What the leaderboard ranks authors by (`top --metric`).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[non_exhaustive]
+pub enum TopMetric {
+    /// Surviving lines at HEAD (blame).
+    #[default]
+    Loc,
+    /// Commits in the analysed history.
+    Commits,
+    /// Files with at least one surviving line at HEAD (blame).
+    Files,
+    /// Lines added plus deleted by non-merge commits.
+    Churn,
+}
+
+impl TopMetric {
+    pub fn parse(s: &str) -> Result<TopMetric, String> {
+        match s {
+            "loc" => Ok(TopMetric::Loc),
+            "commits" => Ok(TopMetric::Commits),
+            "files" => Ok(TopMetric::Files),
+            "churn" => Ok(TopMetric::Churn),
+            other => Err(format!(
+                "unknown --metric '{}'. Expected loc, commits, files or churn.",
+                other
+            )),
+        }
+    }
+
+    /// Histogram unit, e.g. `lines`.
+    pub fn unit(self) -> &'static str {
+        match self {
+            TopMetric::Loc => "surviving lines",
+            TopMetric::Commits => "commits",
+            TopMetric::Files => "files",
+            TopMetric::Churn => "lines added + deleted",
+        }
+    }
+}
+
+/// Metric value per (normalized) author name.
+pub fn gather_metric(metric: TopMetric) -> Result<HashMap<String, usize>, GitInsightsError> {
+    Ok(match metric {
+        TopMetric::Loc => gather_loc_and_file_statsx(true)?
+            .into_iter()
+            .map(|(a, s)| (a, s.loc))
+            .collect(),
+        TopMetric::Files => gather_loc_and_file_statsx(true)?
+            .into_iter()
+            .map(|(a, s)| (a, s.files.len()))
+            .collect(),
+        TopMetric::Commits => gather_commit_statsx(true)?
+            .into_iter()
+            .map(|(a, s)| (a, s.commits))
+            .collect(),
+        TopMetric::Churn => {
+            let mut churn: HashMap<String, usize> = HashMap::new();
+            for c in collect_sized_commits()? {
+                *churn.entry(c.author).or_insert(0) += c.added + c.deleted;
+            }
+            churn
+        }
+    })
+}
+
+/// The `limit` highest non-zero values, ties by name (`0` keeps all).
+pub fn rank(values: HashMap<String, usize>, limit: usize) -> Vec<(String, usize)> {
+    let mut rows: Vec<(String, usize)> = values.into_iter().filter(|(_, v)| *v > 0).collect();
+    rows.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    if limit > 0 {
+        rows.truncate(limit);
+    }
+    rows
+}
+
+/// Print the leaderboard as a labeled bar chart.
+pub fn run_top(metric: TopMetric, limit: usize, color: bool) -> Result<(), GitInsightsError> {
+    let rows = rank(gather_metric(metric)?, limit);
+    if rows.is_empty() {
+        println!("(no authors)");
+        return Ok(());
+    }
+    println!("Top {} authors by {}:", rows.len(), metric.unit());
+    let labels: Vec<String> = rows
+        .iter()
+        .enumerate()
+        .map(|(i, (author, _))| format!("{}. {}", i + 1, author))
+        .collect();
+    let labels: Vec<&str> = labels.iter().map(|s| s.as_str()).collect();
+    let values: Vec<usize> = rows.iter().map(|(_, v)| *v).collect();
+    Ok(render_histogram_labeled(
+        &mut io::stdout().lock(),
+        &labels,
+        &values,
+        color,
+        metric.unit(),
+        ChartSize::default(),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank_and_parse() {
+        let values: HashMap<String, usize> = [("Ann", 5), ("Bob", 9), ("Cat", 5), ("Dan", 0)]
+            .into_iter()
+            .map(|(a, v)| (a.to_string(), v))
+            .collect();
+        let top = rank(values.clone(), 2);
+        assert_eq!(top, vec![("Bob".to_string(), 9), ("Ann".to_string(), 5)]);
+        assert_eq!(rank(values, 0).len(), 3);
+        assert_eq!(TopMetric::parse("churn"), Ok(TopMetric::Churn));
+        assert!(TopMetric::parse("lines").is_err());
+    }
+
+    #[test]
+    fn test_gather_metric_churn_and_commits() {
+        let _guard = crate::test_sync::test_lock();
+        let repo = crate::test_repo::TestRepo::init().expect("init repo");
+        let t0 = 1_600_000_000;
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", "one", t0)
+            .expect("commit");
+        repo.commit_with_epoch("Ann", "ann@x.io", "a.txt", "two", t0 + 60)
+            .expect("commit");
+        repo.commit_with_epoch("Bob", "bob@x.io", "b.txt", "three", t0 + 120)
+            .expect("commit");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&repo.path).expect("cd");
+        let churn = gather_metric(TopMetric::Churn);
+        let commits = gather_metric(TopMetric::Commits);
+        std::env::set_current_dir(old).ok();
+
+        let churn = churn.expect("churn");
+        assert_eq!(churn["Ann"], 2);
+        assert_eq!(churn["Bob"], 1);
+        assert_eq!(
+            rank(commits.expect("commits"), 1),
+            vec![("Ann".to_string(), 2)]
+        );
+    }
+}
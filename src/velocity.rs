@@ -0,0 +1,243 @@
+use crate::bursts::{collect_sized_commits, BurstCommit};
+use crate::code_frequency::ymd_from_unix;
+use crate::error::GitInsightsError;
+use std::collections::HashSet;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// Default iteration length (two weeks).
+pub const DEFAULT_ITERATION: u64 = 14 * DAY;
+
+/// Totals for one fixed-length iteration `[start, end)`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Iteration {
+    pub start: u64,
+    pub end: u64,
+    pub commits: usize,
+    pub added: usize,
+    pub deleted: usize,
+    pub authors: usize,
+}
+
+/// Parse an iteration length such as `2w`, `10d` or `1w` into seconds.
+pub fn parse_iteration(s: &str) -> Result<u64, String> {
+    let s = s.trim().to_lowercase();
+    let (num, unit) = match s.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+        Some((i, _)) => s.split_at(i),
+        None => (s.as_str(), "w"),
+    };
+    let n: u64 = num.parse().unwrap_or(0);
+    let unit_secs = match unit {
+        "d" => DAY,
+        "w" => 7 * DAY,
+        _ => 0,
+    };
+    if n == 0 || unit_secs == 0 {
+        return Err(format!(
+            "invalid --iteration '{}'. Expected a length like 2w or 10d.",
+            s
+        ));
+    }
+    Ok(n * unit_secs)
+}
+
+/// Parse `--start YYYY-MM-DD` as UTC midnight.
+pub fn parse_start(s: &str) -> Result<u64, String> {
+    crate::capacity::parse_date(s.trim())
+        .ok_or_else(|| format!("invalid --start '{}'. Expected YYYY-MM-DD.", s))
+}
+
+/// Midnight UTC of the Monday on or before `t`.
+pub fn monday_on_or_before(t: u64) -> u64 {
+    let days = t / DAY;
+    // 1970-01-01 was a Thursday.
+    (days - (days + 3) % 7) * DAY
+}
+
+/// Bucket commits (by committer time) into iterations of `length` seconds
+/// starting at `start`, up to the iteration containing `until`. Commits
+/// before `start` are ignored.
+pub fn bucket_iterations(
+    commits: &[BurstCommit],
+    start: u64,
+    length: u64,
+    until: u64,
+) -> Vec<Iteration> {
+    if until < start || length == 0 {
+        return Vec::new();
+    }
+    let count = ((until - start) / length + 1) as usize;
+    let mut rows: Vec<Iteration> = (0..count as u64)
+        .map(|i| Iteration {
+            start: start + i * length,
+            end: start + (i + 1) * length,
+            ..Default::default()
+        })
+        .collect();
+    let mut authors: Vec<HashSet<&str>> = vec![HashSet::new(); count];
+    for c in commits {
+        if c.time < start {
+            continue;
+        }
+        let idx = ((c.time - start) / length) as usize;
+        let Some(row) = rows.get_mut(idx) else {
+            continue;
+        };
+        row.commits += 1;
+        row.added += c.added;
+        row.deleted += c.deleted;
+        authors[idx].insert(&c.author);
+    }
+    for (row, set) in rows.iter_mut().zip(authors) {
+        row.authors = set.len();
+    }
+    rows
+}
+
+/// Change in commits versus the previous iteration: `▲ +25%`, `▼ -40%`, `= 0%`
+/// (ASCII `^`/`v` without color), or `new` when the previous one was empty.
+pub fn trend(prev: Option<&Iteration>, cur: &Iteration, unicode: bool) -> String {
+    let Some(prev) = prev else {
+        return String::new();
+    };
+    if prev.commits == 0 {
+        return if cur.commits == 0 {
+            "=".to_string()
+        } else {
+            "new".to_string()
+        };
+    }
+    let pct = (cur.commits as f64 - prev.commits as f64) * 100.0 / prev.commits as f64;
+    let arrow = match (cur.commits.cmp(&prev.commits), unicode) {
+        (std::cmp::Ordering::Equal, _) => return "= 0%".to_string(),
+        (std::cmp::Ordering::Greater, true) => "▲",
+        (std::cmp::Ordering::Greater, false) => "^",
+        (std::cmp::Ordering::Less, true) => "▼",
+        (std::cmp::Ordering::Less, false) => "v",
+    };
+    format!("{} {:+.0}%", arrow, pct)
+}
+
+fn format_date(t: u64) -> String {
+    let (y, m, d) = ymd_from_unix(t);
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+/// Iterations for the active filter. Without `start`, iterations begin on
+/// the Monday on or before the oldest commit.
+pub fn gather_velocity(
+    length: u64,
+    start: Option<u64>,
+) -> Result<Vec<Iteration>, GitInsightsError> {
+    let commits = collect_sized_commits()?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| format!("clock error: {e}"))?
+        .as_secs();
+    let start = match start {
+        Some(s) => s,
+        None => match commits.iter().map(|c| c.time).min() {
+            Some(oldest) => monday_on_or_before(oldest),
+            None => return Ok(Vec::new()),
+        },
+    };
+    Ok(bucket_iterations(&commits, start, length, now))
+}
+
+/// Print per-iteration totals with the change in commits from the previous one.
+pub fn run_velocity(length: u64, start: Option<u64>, color: bool) -> Result<(), GitInsightsError> {
+    let rows = gather_velocity(length, start)?;
+    if rows.is_empty() {
+        println!("(no commits)");
+        return Ok(());
+    }
+    println!(
+        "| {:>4} | {:<10} | {:<10} | {:>7} | {:>8} | {:>8} | {:>7} | {:<8} |",
+        "no.", "start", "end", "commits", "added", "deleted", "authors", "trend"
+    );
+    println!(
+        "|{:->6}|:{:-<11}|:{:-<11}|{:->9}|{:->10}|{:->10}|{:->9}|:{:-<9}|",
+        "", "", "", "", "", "", "", ""
+    );
+    for (i, row) in rows.iter().enumerate() {
+        let t = trend(i.checked_sub(1).map(|p| &rows[p]), row, color);
+        let t = match (color, t.chars().next()) {
+            (true, Some('▲')) => format!("\x1b[32m{:<8}\x1b[0m", t),
+            (true, Some('▼')) => format!("\x1b[31m{:<8}\x1b[0m", t),
+            _ => format!("{:<8}", t),
+        };
+        println!(
+            "| {:>4} | {:<10} | {:<10} | {:>7} | {:>8} | {:>8} | {:>7} | {} |",
+            i + 1,
+            format_date(row.start),
+            format_date(row.end - DAY),
+            row.commits,
+            format!("+{}", row.added),
+            format!("-{}", row.deleted),
+            row.authors,
+            t
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_iteration_and_monday() {
+        assert_eq!(parse_iteration("2w"), Ok(14 * DAY));
+        assert_eq!(parse_iteration("10D"), Ok(10 * DAY));
+        assert_eq!(parse_iteration("3"), Ok(21 * DAY));
+        assert!(parse_iteration("0w").is_err());
+        assert!(parse_iteration("2m").is_err());
+        assert_eq!(parse_start("1970-01-02"), Ok(DAY));
+        assert!(parse_start("01/02/1970").is_err());
+        // 2024-01-04 (Thursday) -> 2024-01-01 (Monday).
+        let monday = crate::code_frequency::unix_from_ymd(2024, 1, 1);
+        assert_eq!(monday_on_or_before(monday + 3 * DAY + 3_600), monday);
+        assert_eq!(monday_on_or_before(monday), monday);
+    }
+
+    #[test]
+    fn test_bucket_iterations_and_trend() {
+        let commit = |author: &str, time: u64, added: usize| BurstCommit {
+            author: author.to_string(),
+            time,
+            added,
+            deleted: 1,
+            ..Default::default()
+        };
+        let start = 1_000 * DAY;
+        let commits = vec![
+            commit("Ann", start - 1, 50),
+            commit("Ann", start, 10),
+            commit("Bob", start + 13 * DAY, 5),
+            commit("Ann", start + 14 * DAY, 7),
+            commit("Ann", start + 30 * DAY, 1),
+        ];
+        let rows = bucket_iterations(&commits, start, 14 * DAY, start + 29 * DAY);
+        assert_eq!(rows.len(), 3);
+        assert_eq!(
+            (
+                rows[0].commits,
+                rows[0].added,
+                rows[0].deleted,
+                rows[0].authors
+            ),
+            (2, 15, 2, 2)
+        );
+        assert_eq!(rows[1].commits, 1);
+        assert_eq!(rows[2].commits, 1);
+        assert_eq!(rows[2].end, start + 42 * DAY);
+
+        assert_eq!(trend(None, &rows[0], true), "");
+        assert_eq!(trend(Some(&rows[0]), &rows[1], true), "▼ -50%");
+        assert_eq!(trend(Some(&rows[1]), &rows[0], false), "^ +100%");
+        assert_eq!(trend(Some(&rows[1]), &rows[2], true), "= 0%");
+        let empty = Iteration::default();
+        assert_eq!(trend(Some(&empty), &rows[0], true), "new");
+    }
+}
@@ -0,0 +1,187 @@
+use crate::error::GitInsightsError;
+use crate::json::{self, JsonValue};
+use crate::output::print_table;
+use crate::report::{write_html_report, ReportData};
+use crate::stats::{AuthorStats, JSON_SCHEMA_VERSION};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Presentation targets for a previously exported stats file.
+#[non_exhaustive]
+pub enum ViewFormat {
+    Table,
+    Html,
+}
+
+/// Parse the author stats written by `git-insights json`: the versioned
+/// envelope, or the bare object keyed by author of older exports.
+pub fn parse_stats_json(text: &str) -> Result<Vec<(String, AuthorStats)>, String> {
+    let mut doc = json::parse(text)?;
+    if let Some(version) = doc.get("schema_version") {
+        match version.as_usize() {
+            Some(v) if v <= JSON_SCHEMA_VERSION => {}
+            Some(v) => {
+                return Err(format!(
+                    "unsupported schema_version {}; this build reads up to {}",
+                    v, JSON_SCHEMA_VERSION
+                ))
+            }
+            None => return Err("invalid 'schema_version'".to_string()),
+        }
+        doc = doc
+            .get("authors")
+            .cloned()
+            .ok_or_else(|| "missing 'authors'".to_string())?;
+    }
+    let JsonValue::Object(members) = doc else {
+        return Err("expected a JSON object keyed by author".to_string());
+    };
+    let mut authors = Vec::with_capacity(members.len());
+    for (author, v) in members {
+        let field = |name: &str| {
+            v.get(name)
+                .and_then(JsonValue::as_usize)
+                .ok_or_else(|| format!("author '{}': missing or invalid '{}'", author, name))
+        };
+        let loc = field("loc")?;
+        let commits = field("commits")?;
+        // Exports from before these columns existed lack them.
+        let added = field("added").unwrap_or(0);
+        let deleted = field("deleted").unwrap_or(0);
+        let files: HashSet<String> = v
+            .get("files")
+            .and_then(JsonValue::as_array)
+            .unwrap_or(&[])
+            .iter()
+            .filter_map(|f| f.as_str().map(str::to_string))
+            .collect();
+        authors.push((
+            author,
+            AuthorStats {
+                loc,
+                commits,
+                files,
+                added,
+                deleted,
+                ..Default::default()
+            },
+        ));
+    }
+    authors.sort_by(|a, b| b.1.loc.cmp(&a.1.loc).then_with(|| a.0.cmp(&b.0)));
+    Ok(authors)
+}
+
+/// Render an exported JSON file without running git.
+pub fn run_view(
+    path: &str,
+    format: ViewFormat,
+    output: Option<&str>,
+) -> Result<(), GitInsightsError> {
+    let text = fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path, e))?;
+    let authors = parse_stats_json(&text).map_err(|e| format!("{}: {}", path, e))?;
+    match format {
+        ViewFormat::Table => {
+            let total_loc: usize = authors.iter().map(|(_, s)| s.loc).sum();
+            let total_commits: usize = authors.iter().map(|(_, s)| s.commits).sum();
+            let total_files = authors
+                .iter()
+                .flat_map(|(_, s)| s.files.iter())
+                .collect::<HashSet<_>>()
+                .len();
+            println!("Total commits: {}", total_commits);
+            println!("Total files: {}", total_files);
+            println!("Total loc: {}", total_loc);
+            Ok(print_table(authors, total_loc, total_commits, total_files)?)
+        }
+        ViewFormat::Html => {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|e| format!("clock error: {e}"))?
+                .as_secs();
+            let repo_name = Path::new(path)
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.to_string());
+            let data = ReportData {
+                repo_name,
+                generated_at: now,
+                authors,
+                activity: None,
+            };
+            write_html_report(&data, output)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json;
+    use crate::report::REPORT_PATH;
+
+    #[test]
+    fn test_parse_stats_json_roundtrips_export() {
+        let mut files = HashSet::new();
+        files.insert("src/\"odd\".rs".to_string());
+        let stats = AuthorStats {
+            loc: 7,
+            commits: 2,
+            files,
+            ..Default::default()
+        };
+        let text = format!(
+            "{{\n\"{}\": {},\n\"Bob\": {{\"loc\": 9, \"commits\": 1, \"files\": []}}\n}}",
+            json::escape("Ann \"A\""),
+            stats.to_json()
+        );
+        let authors = parse_stats_json(&text).expect("parse");
+        assert_eq!(authors[0].0, "Bob");
+        assert_eq!(authors[1].0, "Ann \"A\"");
+        assert_eq!(authors[1].1.commits, 2);
+        assert!(authors[1].1.files.contains("src/\"odd\".rs"));
+
+        let envelope = format!(
+            "{{\"schema_version\": 1, \"head\": \"abc\", \"authors\": {}}}",
+            text
+        );
+        let wrapped = parse_stats_json(&envelope).expect("envelope");
+        let locs = |rows: &[(String, AuthorStats)]| -> Vec<(String, usize)> {
+            rows.iter().map(|(a, s)| (a.clone(), s.loc)).collect()
+        };
+        assert_eq!(locs(&wrapped), locs(&authors));
+        assert!(
+            parse_stats_json("{\"schema_version\": 99, \"authors\": {}}")
+                .unwrap_err()
+                .contains("unsupported schema_version 99")
+        );
+
+        assert!(parse_stats_json("[]").is_err());
+        assert!(parse_stats_json("{\"x\": {\"loc\": 1}}")
+            .unwrap_err()
+            .contains("'commits'"));
+    }
+
+    #[test]
+    fn test_run_view_html_outside_repo() {
+        let _guard = crate::test_sync::test_lock();
+        let dir = std::env::temp_dir().join(format!("gi_view_{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("mkdir");
+        fs::write(
+            dir.join("git-insights.json"),
+            "{\"Alice\": {\"loc\": 3, \"commits\": 1, \"files\": [\"a.txt\"]}}",
+        )
+        .expect("write json");
+
+        let old = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&dir).expect("cd");
+        let result = run_view("git-insights.json", ViewFormat::Html, None);
+        std::env::set_current_dir(old).ok();
+
+        result.expect("view ok");
+        let html = fs::read_to_string(dir.join(REPORT_PATH)).expect("report written");
+        fs::remove_dir_all(&dir).ok();
+        assert!(html.contains("<td>Alice</td>"));
+    }
+}
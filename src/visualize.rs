@@ -1,37 +1,349 @@
-use crate::git::run_command;
+use crate::bursts::{
+    detect_bursts, print_burst_commits, render_burst_row, BurstOptions, BURST_WINDOW,
+};
+use crate::capacity::{compute_capacity_weeks, load_capacity_file, render_capacity_overlay};
+use crate::error::GitInsightsError;
+use crate::git::run_command_lines;
+use crate::palette::level_color;
+use std::collections::HashSet;
+use std::io::{self, Write};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-/// Collect commit epochs (newest first).
-pub fn collect_commit_timestamps() -> Result<Vec<u64>, String> {
-    let out = run_command(&["--no-pager", "log", "--no-merges", "--format=%ct"])?;
-    let mut ts: Vec<u64> = Vec::new();
-    for line in out.lines() {
-        if let Ok(v) = line.trim().parse::<u64>() {
-            ts.push(v);
+/// Default bar width of labeled histograms.
+pub const HISTOGRAM_WIDTH: usize = 40;
+/// Default bar column width of histogram tables.
+pub const TABLE_BAR_WIDTH: usize = 20;
+/// Default number of rows in the multiline timeline.
+pub const TIMELINE_HEIGHT: usize = 7;
+
+/// Chart size overrides (`--width` / `--height`).
+///
+/// Unset widths fall back to the renderer's default, shrunk to fit `$COLUMNS`
+/// when it is set and narrower than the chart would be.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ChartSize {
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+}
+
+impl ChartSize {
+    /// Bar width for a chart whose labels, counts and borders take `overhead` columns.
+    pub fn bar_width(&self, default: usize, overhead: usize) -> usize {
+        match self.width {
+            Some(w) => w.max(1),
+            None => fit_width(default, overhead, terminal_columns()),
         }
     }
+
+    /// Rows for the multiline timeline.
+    pub fn timeline_height(&self) -> usize {
+        self.height.unwrap_or(TIMELINE_HEIGHT).max(1)
+    }
+}
+
+/// Shrink `default` so `overhead + width` fits in `columns` (never below 1).
+pub fn fit_width(default: usize, overhead: usize, columns: Option<usize>) -> usize {
+    match columns {
+        Some(c) => default.min(c.saturating_sub(overhead)).max(1),
+        None => default,
+    }
+}
+
+/// `$COLUMNS`, when set to a positive number.
+pub(crate) fn terminal_columns() -> Option<usize> {
+    std::env::var("COLUMNS")
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .filter(|c| *c > 0)
+}
+
+/// Collect commit epochs (newest first).
+pub fn collect_commit_timestamps() -> Result<Vec<u64>, GitInsightsError> {
+    Ok(collect_author_timestamps()?
+        .into_iter()
+        .map(|(_, t)| t)
+        .collect())
+}
+
+/// Collect `(normalized author name, commit epoch)` pairs (newest first).
+///
+/// Under `--tz author|local` the epoch is shifted to that wall clock, so the
+/// UTC bucketing helpers see the hour and day the author experienced.
+pub fn collect_author_timestamps() -> Result<Vec<(String, u64)>, GitInsightsError> {
+    if crate::submodules::recurse_submodules() {
+        return Ok(collect_author_timestamps_by_repo()?
+            .into_iter()
+            .flat_map(|(_, ts)| ts)
+            .collect());
+    }
+    collect_repo_author_timestamps()
+}
+
+/// `(author, commit time)` pairs of one repository.
+pub type AuthorTimestamps = Vec<(String, u64)>;
+
+/// [`collect_author_timestamps`] of the superproject and of each initialized
+/// submodule, separately.
+pub fn collect_author_timestamps_by_repo(
+) -> Result<Vec<(String, AuthorTimestamps)>, GitInsightsError> {
+    crate::submodules::each_repo(collect_repo_author_timestamps)
+}
+
+/// Commits per repository inside the chart window, for the subtotals under
+/// `timeline` and `heatmap` with `--recurse-submodules`.
+fn commit_subtotals(
+    count: impl Fn(&[u64]) -> usize,
+) -> Result<Vec<(String, usize)>, GitInsightsError> {
+    Ok(collect_author_timestamps_by_repo()?
+        .into_iter()
+        .map(|(path, pairs)| {
+            let ts: Vec<u64> = pairs.into_iter().map(|(_, t)| t).collect();
+            (path, count(&ts))
+        })
+        .collect())
+}
+
+fn collect_repo_author_timestamps() -> Result<Vec<(String, u64)>, GitInsightsError> {
+    let revs = crate::filter::revision_args()?;
+    let authors = crate::filter::author_matcher()?;
+    let tz = crate::filter::active().time_zone;
+    let mut format = "--format=%ct%x09%at%x09%aN%x09%aE".to_string();
+    let mut args = vec!["--no-pager", "log"];
+    args.extend(crate::filter::history_args());
+    if let Some((date, field)) = tz.offset_format() {
+        format.push_str("%x09");
+        format.push_str(field);
+        args.push(date);
+    }
+    args.push(&format);
+    args.extend(revs.iter().map(|s| s.as_str()));
+    let sanity = crate::time_audit::active_sanity()?;
+    let names = crate::identity::active_normalization();
+    let mut ts: Vec<(String, u64)> = Vec::new();
+    run_command_lines(&args, |line| {
+        let mut fields = line.trim().splitn(5, '\t');
+        let t = fields.next().unwrap_or("");
+        let authored = fields.next().unwrap_or("");
+        let name = fields.next().unwrap_or("");
+        let email = fields.next().unwrap_or("");
+        let offset = fields.next().unwrap_or("");
+        if !authors.allows(name, email) {
+            return;
+        }
+        if let Ok(v) = t.parse::<u64>() {
+            let authored = authored.parse().unwrap_or(0);
+            if let Some(s) = &sanity {
+                if !s.allows(authored, v) {
+                    return;
+                }
+            }
+            ts.push((
+                names.apply(name.to_string()),
+                tz.wall_clock(v, authored, offset),
+            ));
+        }
+    })?;
     Ok(ts)
 }
 
-/// Bucket commits by week; returns oldest->newest counts.
-pub fn compute_timeline_weeks(timestamps: &[u64], weeks: usize, now: u64) -> Vec<usize> {
-    let mut counts = vec![0usize; weeks];
-    if weeks == 0 {
-        return counts;
+/// Bucket size of the timeline (`timeline --granularity`).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[non_exhaustive]
+pub enum TimelineGranularity {
+    /// UTC days.
+    Day,
+    /// Weeks aligned like [`compute_timeline_weeks`].
+    #[default]
+    Week,
+    /// UTC calendar months.
+    Month,
+}
+
+impl TimelineGranularity {
+    pub fn parse(s: &str) -> Result<TimelineGranularity, GitInsightsError> {
+        match s {
+            "day" => Ok(TimelineGranularity::Day),
+            "week" => Ok(TimelineGranularity::Week),
+            "month" => Ok(TimelineGranularity::Month),
+            other => Err(GitInsightsError::Usage(format!(
+                "unknown --granularity '{}'. Expected day, week or month.",
+                other
+            ))),
+        }
     }
-    const WEEK: u64 = 7 * 24 * 60 * 60; // 604800
 
-    let start_of_week = now - (now % WEEK);
-    let aligned_end = start_of_week.saturating_add(WEEK - 1);
+    pub fn name(self) -> &'static str {
+        match self {
+            TimelineGranularity::Day => "day",
+            TimelineGranularity::Week => "week",
+            TimelineGranularity::Month => "month",
+        }
+    }
 
-    for &t in timestamps {
-        if t > aligned_end {
-            continue;
+    /// Chart title prefix, e.g. `Weekly`.
+    pub fn adjective(self) -> &'static str {
+        match self {
+            TimelineGranularity::Day => "Daily",
+            TimelineGranularity::Week => "Weekly",
+            TimelineGranularity::Month => "Monthly",
+        }
+    }
+
+    /// Window used when `--weeks` is not given: 4 weeks of days, 26 weeks,
+    /// or a year of months.
+    pub fn default_weeks(self) -> usize {
+        match self {
+            TimelineGranularity::Day => 4,
+            TimelineGranularity::Week => 26,
+            TimelineGranularity::Month => 52,
+        }
+    }
+
+    /// Buckets covering the last `weeks` weeks up to `now`.
+    pub fn buckets(self, weeks: usize, now: u64) -> usize {
+        match self {
+            TimelineGranularity::Day => weeks.saturating_mul(7),
+            TimelineGranularity::Week => weeks,
+            TimelineGranularity::Month if weeks == 0 => 0,
+            TimelineGranularity::Month => {
+                let start = now.saturating_sub((weeks as u64).saturating_mul(WEEK_SECS));
+                month_number(now) - month_number(start) + 1
+            }
+        }
+    }
+
+    /// Axis tick spacing in buckets: `(labeled major, minor)`.
+    fn axis_marks(self) -> (usize, usize) {
+        match self {
+            TimelineGranularity::Day => (14, 7),
+            TimelineGranularity::Week => (12, 4),
+            TimelineGranularity::Month => (12, 3),
+        }
+    }
+}
+
+const DAY_SECS: u64 = 24 * 60 * 60;
+const WEEK_SECS: u64 = 7 * DAY_SECS;
+
+/// Months since year 0 of the UTC date of `t`.
+fn month_number(t: u64) -> usize {
+    let (y, m, _) = crate::code_frequency::ymd_from_unix(t);
+    (y.max(0) as usize) * 12 + (m as usize - 1)
+}
+
+/// What the weekly timeline counts (`timeline --metric`).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[non_exhaustive]
+pub enum TimelineMetric {
+    #[default]
+    Commits,
+    /// Distinct authors with at least one commit in the week.
+    Contributors,
+}
+
+impl TimelineMetric {
+    pub fn parse(s: &str) -> Result<TimelineMetric, GitInsightsError> {
+        match s {
+            "commits" => Ok(TimelineMetric::Commits),
+            "contributors" => Ok(TimelineMetric::Contributors),
+            other => Err(GitInsightsError::Usage(format!(
+                "unknown --metric '{}'. Expected commits or contributors.",
+                other
+            ))),
+        }
+    }
+
+    /// Axis unit, e.g. `commits/week`.
+    pub fn unit(self) -> &'static str {
+        match self {
+            TimelineMetric::Commits => "commits/week",
+            TimelineMetric::Contributors => "authors/week",
+        }
+    }
+
+    /// Axis unit per bucket, e.g. `commits/day`.
+    pub fn unit_per(self, granularity: TimelineGranularity) -> String {
+        let noun = match self {
+            TimelineMetric::Commits => "commits",
+            TimelineMetric::Contributors => "authors",
+        };
+        format!("{}/{}", noun, granularity.name())
+    }
+
+    /// Oldest->newest weekly values from `(author, epoch)` pairs.
+    pub fn weekly_values(self, pairs: &[(String, u64)], weeks: usize, now: u64) -> Vec<usize> {
+        self.values(pairs, weeks, now, TimelineGranularity::Week)
+    }
+
+    /// Oldest->newest values of the last `buckets` days, weeks or months.
+    pub fn values(
+        self,
+        pairs: &[(String, u64)],
+        buckets: usize,
+        now: u64,
+        granularity: TimelineGranularity,
+    ) -> Vec<usize> {
+        match self {
+            TimelineMetric::Commits => {
+                let ts: Vec<u64> = pairs.iter().map(|(_, t)| *t).collect();
+                compute_timeline_buckets(&ts, buckets, now, granularity)
+            }
+            TimelineMetric::Contributors => {
+                let mut seen: Vec<HashSet<&str>> = vec![HashSet::new(); buckets];
+                for (author, t) in pairs {
+                    if let Some(idx) = bucket_index(*t, buckets, now, granularity) {
+                        seen[idx].insert(author);
+                    }
+                }
+                seen.iter().map(|s| s.len()).collect()
+            }
+        }
+    }
+}
+
+/// Oldest->newest bin of `t` among the `buckets` ending with the day, week
+/// or month of `now`, or `None` when it falls outside.
+fn bucket_index(
+    t: u64,
+    buckets: usize,
+    now: u64,
+    granularity: TimelineGranularity,
+) -> Option<usize> {
+    let bin = match granularity {
+        TimelineGranularity::Day | TimelineGranularity::Week => {
+            let span = if granularity == TimelineGranularity::Day {
+                DAY_SECS
+            } else {
+                WEEK_SECS
+            };
+            let aligned_end = (now - now % span).saturating_add(span - 1);
+            if t > aligned_end {
+                return None;
+            }
+            ((aligned_end - t) / span) as usize
         }
-        let diff = aligned_end - t;
-        let bin = (diff / WEEK) as usize;
-        if bin < weeks {
-            let idx = weeks - 1 - bin;
+        TimelineGranularity::Month => month_number(now).checked_sub(month_number(t))?,
+    };
+    (bin < buckets).then(|| buckets - 1 - bin)
+}
+
+/// Bucket commits by week; returns oldest->newest counts.
+pub fn compute_timeline_weeks(timestamps: &[u64], weeks: usize, now: u64) -> Vec<usize> {
+    compute_timeline_buckets(timestamps, weeks, now, TimelineGranularity::Week)
+}
+
+/// Bucket commits by day, week or month; returns oldest->newest counts.
+pub fn compute_timeline_buckets(
+    timestamps: &[u64],
+    buckets: usize,
+    now: u64,
+    granularity: TimelineGranularity,
+) -> Vec<usize> {
+    let mut counts = vec![0usize; buckets];
+    for &t in timestamps {
+        if let Some(idx) = bucket_index(t, buckets, now, granularity) {
             counts[idx] += 1;
         }
     }
@@ -81,84 +393,173 @@ pub fn compute_calendar_heatmap(timestamps: &[u64], weeks: usize, now: u64) -> V
     grid
 }
 
+/// How heatmap cells are shaded (`--normalize`).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[non_exhaustive]
+pub enum HeatmapScale {
+    /// Against the busiest cell of the whole grid.
+    #[default]
+    Global,
+    /// Against the busiest cell of the same row.
+    Row,
+    /// `ln(1 + n)` against the busiest cell, so one massive burst does not
+    /// wash out every other cell.
+    Log,
+}
+
+/// Fixed-point factor applied to logarithms so the integer ramps keep
+/// their resolution.
+const LOG_SCALE: f64 = 1000.0;
+
+impl HeatmapScale {
+    pub fn parse(s: &str) -> Result<HeatmapScale, GitInsightsError> {
+        match s {
+            "global" => Ok(HeatmapScale::Global),
+            "row" => Ok(HeatmapScale::Row),
+            "log" => Ok(HeatmapScale::Log),
+            other => Err(GitInsightsError::Usage(format!(
+                "unknown --normalize '{}'. Expected row, global or log.",
+                other
+            ))),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            HeatmapScale::Global => "global",
+            HeatmapScale::Row => "row",
+            HeatmapScale::Log => "log",
+        }
+    }
+
+    /// `(value, max)` to shade a cell holding `v` with, given the busiest
+    /// cell of its row and of the grid; zero stays zero.
+    pub fn scale(self, v: usize, row_max: usize, max: usize) -> (usize, usize) {
+        let log = |n: usize| ((n as f64).ln_1p() * LOG_SCALE).round() as usize;
+        match self {
+            HeatmapScale::Global => (v, max),
+            HeatmapScale::Row => (v, row_max),
+            HeatmapScale::Log => (log(v), log(max)),
+        }
+    }
+}
+
+/// Start (epoch seconds) of each calendar heatmap column, oldest first, in
+/// the same week alignment as [`compute_calendar_heatmap`].
+pub fn calendar_week_starts(weeks: usize, now: u64) -> Vec<u64> {
+    let start_of_week = now - (now % WEEK_SECS);
+    (0..weeks as u64)
+        .rev()
+        .map(|back| start_of_week.saturating_sub(back * WEEK_SECS))
+        .collect()
+}
+
+pub(crate) const MONTH_NAMES: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Month name over each column whose week starts in a new month; the first
+/// column is only named when the next one is in the same month, so two names
+/// never touch.
+pub fn calendar_month_labels(week_starts: &[u64]) -> Vec<&'static str> {
+    let months: Vec<u32> = week_starts
+        .iter()
+        .map(|&t| crate::code_frequency::ymd_from_unix(t).1)
+        .collect();
+    (0..months.len())
+        .map(|col| {
+            let starts_month = match col {
+                0 => months.get(1).is_none_or(|&next| next == months[0]),
+                _ => months[col] != months[col - 1],
+            };
+            if starts_month {
+                MONTH_NAMES[months[col] as usize - 1]
+            } else {
+                ""
+            }
+        })
+        .collect()
+}
+
+/// The month header above the calendar heatmap's 3-wide columns.
+fn write_calendar_month_row(out: &mut impl Write, week_starts: &[u64]) -> io::Result<()> {
+    let labels = calendar_month_labels(week_starts);
+    let row: String = labels.iter().map(|l| format!("{:<3}", l)).collect();
+    writeln!(out, "    {}", row.trim_end())
+}
+
 /// Render ASCII timeline.
-pub fn render_timeline_bars(counts: &[usize]) {
+pub fn render_timeline_bars(out: &mut impl Write, counts: &[usize]) -> io::Result<()> {
     let ramp: &[u8] = b" .:-=+*#%@"; // 10 levels
     let max = counts.iter().copied().max().unwrap_or(0);
     if max == 0 {
-        println!("(no commits in selected window)");
-        return;
+        writeln!(out, "(no commits in selected window)")?;
+        return Ok(());
     }
     let mut line = String::with_capacity(counts.len());
     for &c in counts {
         let idx = (c.saturating_mul(ramp.len() - 1)) / max;
         line.push(ramp[idx] as char);
     }
-    println!("{}", line);
+    writeln!(out, "{}", line)?;
+    Ok(())
 }
 
 /// Render 7x24 ASCII heatmap.
-pub fn render_heatmap_ascii(grid: [[usize; 24]; 7]) {
+pub fn render_heatmap_ascii(out: &mut impl Write, grid: [[usize; 24]; 7]) -> io::Result<()> {
     let ramp: &[u8] = b" .:-=+*#%@"; // 10 levels
-    let mut max = 0usize;
-    for r in 0..7 {
-        for h in 0..24 {
-            if grid[r][h] > max {
-                max = grid[r][h];
-            }
-        }
-    }
-    println!("    00  01  02  03  04  05  06  07  08  09  10  11  12  13  14  15  16  17  18  19  20  21  22  23");
-    let labels = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
-    for (r, lbl) in labels.iter().enumerate() {
-        print!("{:<3} ", lbl);
-        for h in 0..24 {
-            let c = grid[r][h];
-            let ch = if max == 0 {
-                ' '
-            } else {
-                let idx = (c.saturating_mul(ramp.len() - 1)) / max;
-                ramp[idx] as char
+    let max = grid.iter().flatten().copied().max().unwrap_or(0);
+    writeln!(out, "    00  01  02  03  04  05  06  07  08  09  10  11  12  13  14  15  16  17  18  19  20  21  22  23")?;
+    let week_start = crate::filter::active().week_start;
+    for (row, lbl) in week_start.reorder(&grid).iter().zip(week_start.day_names()) {
+        write!(out, "{:<3} ", lbl)?;
+        for &c in row {
+            let ch = match c.saturating_mul(ramp.len() - 1).checked_div(max) {
+                Some(idx) => ramp[idx] as char,
+                None => ' ',
             };
-            print!(" {} ", ch);
+            write!(out, " {} ", ch)?;
         }
-        println!();
+        writeln!(out)?;
     }
-    println!("    00  01  02  03  04  05  06  07  08  09  10  11  12  13  14  15  16  17  18  19  20  21  22  23");
+    writeln!(out, "    00  01  02  03  04  05  06  07  08  09  10  11  12  13  14  15  16  17  18  19  20  21  22  23")?;
+    Ok(())
 }
 
-/// Render GitHub-style calendar heatmap (ASCII ramp)
-pub fn render_calendar_heatmap_ascii(grid: &[Vec<usize>]) {
+/// Render ASCII calendar heatmap with month names over `week_starts`
+/// (from [`calendar_week_starts`]); an empty slice leaves the header out.
+pub fn render_calendar_heatmap_ascii(
+    out: &mut impl Write,
+    grid: &[Vec<usize>],
+    week_starts: &[u64],
+    scale: HeatmapScale,
+) -> io::Result<()> {
     let ramp: &[u8] = b" .:-=+*#%@"; // 10 levels
-    let mut max = 0usize;
-    for r in 0..7 {
-        for c in 0..grid[0].len() {
-            if grid[r][c] > max {
-                max = grid[r][c];
-            }
-        }
+    let max = grid.iter().flatten().copied().max().unwrap_or(0);
+    if !week_starts.is_empty() {
+        write_calendar_month_row(out, week_starts)?;
     }
-    let labels = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
-    for r in 0..7 {
-        print!("{:<3} ", labels[r]);
-        for c in 0..grid[0].len() {
-            let v = grid[r][c];
-            let ch = if max == 0 {
-                ' '
-            } else {
-                let idx = (v.saturating_mul(ramp.len() - 1)) / max;
-                ramp[idx] as char
+    let week_start = crate::filter::active().week_start;
+    for (row, lbl) in week_start.reorder(grid).iter().zip(week_start.day_names()) {
+        write!(out, "{:<3} ", lbl)?;
+        let row_max = row.iter().copied().max().unwrap_or(0);
+        for &v in row {
+            let (v, max) = scale.scale(v, row_max, max);
+            let ch = match v.saturating_mul(ramp.len() - 1).checked_div(max) {
+                Some(idx) => ramp[idx] as char,
+                None => ' ',
             };
-            print!(" {} ", ch);
+            write!(out, " {} ", ch)?;
         }
-        println!();
+        writeln!(out)?;
     }
     // bottom reference: week columns count
-    print!("    ");
+    write!(out, "    ")?;
     for _ in 0..grid[0].len() {
-        print!("^  ");
+        write!(out, "^  ")?;
     }
-    println!();
+    writeln!(out)?;
+    Ok(())
 }
 
 pub fn color_for_level(level: usize) -> &'static str {
@@ -182,154 +583,88 @@ fn intensity_index(v: usize, max: usize, levels: usize) -> usize {
     let idx = ((v - 1) * l) / max + 1;
     idx.min(l)
 }
-
-/// Rich color palette (12 steps).
-fn color_for_level_rich(idx: usize, levels: usize) -> &'static str {
-    const PALETTE: [&str; 12] = [
-        "\x1b[90m", // 0: dim (should not be used for non-zero, but safe fallback)
-        "\x1b[34m", // blue
-        "\x1b[94m", // bright blue
-        "\x1b[36m", // cyan
-        "\x1b[96m", // bright cyan
-        "\x1b[32m", // green
-        "\x1b[92m", // bright green
-        "\x1b[33m", // yellow
-        "\x1b[93m", // bright yellow
-        "\x1b[35m", // magenta
-        "\x1b[95m", // bright magenta
-        "\x1b[91m", // bright red
-    ];
-    let n = PALETTE.len();
-    if levels <= 1 {
-        return PALETTE[0];
-    }
-    let k = if idx >= levels - 1 {
-        n - 1
-    } else {
-        (idx * (n - 1)) / (levels - 1)
-    };
-    PALETTE[k]
-}
-
 /// Print legend (rich palette).
-fn print_ramp_legend_rich(color: bool, unit: &str) {
+fn print_ramp_legend_rich(out: &mut impl Write, color: bool, unit: &str) -> io::Result<()> {
     if color {
-        print!("\x1b[90mLegend (low→high, blank=0 {}):\x1b[0m ", unit);
+        write!(out, "\x1b[90mLegend (low→high, blank=0 {}):\x1b[0m ", unit)?;
         let levels = 10;
         for lvl in 1..levels {
-            let code = color_for_level_rich(lvl, levels);
-            print!(" {}█{}", code, ANSI_RESET);
+            let code = level_color(lvl, levels);
+            write!(out, " {}█{}", code, ANSI_RESET)?;
         }
-        println!();
+        writeln!(out)?;
     } else {
         let ramp = " .:-=+*#%@";
-        println!("Legend (low→high, blank=' ' 0 {}): {}", unit, ramp);
+        writeln!(out, "Legend (low→high, blank=' ' 0 {}): {}", unit, ramp)?;
     }
+    Ok(())
 }
 
 /// Print legend.
-pub fn print_ramp_legend(color: bool, unit: &str) {
+pub fn print_ramp_legend(out: &mut impl Write, color: bool, unit: &str) -> io::Result<()> {
     if color {
-        print!("\x1b[90mLegend (low→high, blank=0 {}):\x1b[0m ", unit);
+        write!(out, "\x1b[90mLegend (low→high, blank=0 {}):\x1b[0m ", unit)?;
         for lvl in 1..=5 {
-            print!(" {}█{}", color_for_level(lvl), ANSI_RESET);
+            write!(out, " {}█{}", color_for_level(lvl), ANSI_RESET)?;
         }
-        println!();
+        writeln!(out)?;
     } else {
         let ramp = " .:-=+*#%@";
-        println!("Legend (low→high, blank=' ' 0 {}): {}", unit, ramp);
+        writeln!(out, "Legend (low→high, blank=' ' 0 {}): {}", unit, ramp)?;
     }
+    Ok(())
 }
 
 /// Render colored timeline.
-pub fn render_timeline_bars_colored(counts: &[usize], color: bool) {
+pub fn render_timeline_bars_colored(
+    out: &mut impl Write,
+    counts: &[usize],
+    color: bool,
+) -> io::Result<()> {
     if !color {
-        render_timeline_bars(counts);
-        return;
+        return render_timeline_bars(out, counts);
     }
     let ramp: &[char] = &[' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█']; // 9 levels
     let max = counts.iter().copied().max().unwrap_or(0);
     if max == 0 {
-        println!("(no commits in selected window)");
-        return;
+        writeln!(out, "(no commits in selected window)")?;
+        return Ok(());
     }
-    let mut out = String::with_capacity(counts.len() * 6);
+    let mut line = String::with_capacity(counts.len() * 6);
     for &c in counts {
         let idx = (c.saturating_mul(ramp.len() - 1)) / max; // 0..=8 (shape)
         let shade = intensity_index(c, max, 10);
-        if shade == 0 {
-            out.push_str("\x1b[90m");
-        } else {
-            out.push_str(color_for_level_rich(shade, 10));
-        }
-        out.push(ramp[idx]);
+        line.push_str(&level_color(shade, 10));
+        line.push(ramp[idx]);
     }
-    out.push_str(ANSI_RESET);
-    println!("{}", out);
+    line.push_str(ANSI_RESET);
+    writeln!(out, "{}", line)?;
+    Ok(())
 }
 
 /// Render multiline timeline.
-pub fn render_timeline_multiline(counts: &[usize], height: usize, color: bool) {
+pub fn render_timeline_multiline(
+    out: &mut impl Write,
+    counts: &[usize],
+    height: usize,
+    color: bool,
+) -> io::Result<()> {
     let h = height.max(1);
     let max = counts.iter().copied().max().unwrap_or(0);
     if max == 0 || counts.is_empty() {
-        println!("(no commits in selected window)");
-        return;
+        writeln!(out, "(no commits in selected window)")?;
+        return Ok(());
     }
 
-    let top_label = max;
-    let mid_label = (max + 1) / 2;
-    let bottom_label = 0usize;
-    let label_width = top_label.to_string().len().max(3);
-    let axis_char = if color { '│' } else { '|' };
-    let dim_start = if color { "\x1b[90m" } else { "" };
-    let dim_end = if color { "\x1b[0m" } else { "" };
-
     for row in (1..=h).rev() {
-        let label_val = if row == h {
-            Some(top_label)
-        } else if row == ((h + 1) / 2) {
-            Some(mid_label)
-        } else if row == 1 {
-            Some(bottom_label)
-        } else {
-            None
-        };
-
-        let mut line = String::with_capacity(label_width + 2);
-        match label_val {
-            Some(v) => {
-                if color {
-                    line.push_str(dim_start);
-                }
-                line.push_str(&format!("{:>width$} {}", v, axis_char, width = label_width));
-                if color {
-                    line.push_str(dim_end);
-                }
-            }
-            None => {
-                if color {
-                    line.push_str(dim_start);
-                }
-                line.push_str(&format!(
-                    "{:>width$} {}",
-                    "",
-                    axis_char,
-                    width = label_width
-                ));
-                if color {
-                    line.push_str(dim_end);
-                }
-            }
-        }
-
+        let line = timeline_row_label(row, h, max, color);
         let mut bars = String::with_capacity(counts.len() * 6);
         for &c in counts {
-            let filled = ((c as usize) * h + max - 1) / max; // ceil to 1..=h
+            let filled = (c * h).div_ceil(max); // ceil to 1..=h
             if filled >= row {
                 if color {
                     let shade = intensity_index(c, max, 10);
-                    bars.push_str(color_for_level_rich(shade, 10));
+                    bars.push_str(&level_color(shade, 10));
                     bars.push('█');
                 } else {
                     bars.push('#');
@@ -342,39 +677,125 @@ pub fn render_timeline_multiline(counts: &[usize], height: usize, color: bool) {
             bars.push_str(ANSI_RESET);
         }
 
-        println!("{}{}", line, bars);
+        writeln!(out, "{}{}", line, bars)?;
+    }
+    Ok(())
+}
+
+/// The dimmed y-axis label and bar of chart line `row` (1 is the bottom) of
+/// `h`: `max` on top, about half of it in the middle and 0 at the bottom.
+fn timeline_row_label(row: usize, h: usize, max: usize, color: bool) -> String {
+    let label_width = max.to_string().len().max(3);
+    let axis_char = if color { '│' } else { '|' };
+    let label = if row == h {
+        max.to_string()
+    } else if row == h.div_ceil(2) {
+        max.div_ceil(2).to_string()
+    } else if row == 1 {
+        "0".to_string()
+    } else {
+        String::new()
+    };
+    let text = format!("{:>width$} {}", label, axis_char, width = label_width);
+    if color {
+        format!("\x1b[90m{}\x1b[0m", text)
+    } else {
+        text
+    }
+}
+
+/// Braille dots of the left and right column of a cell, bottom row first.
+const BRAILLE_LEFT: [u32; 4] = [0x40, 0x04, 0x02, 0x01];
+const BRAILLE_RIGHT: [u32; 4] = [0x80, 0x20, 0x10, 0x08];
+
+/// Render the timeline in braille: each character holds two buckets side by
+/// side and four dot rows, so `height` lines resolve `4 * height` levels. An
+/// odd bucket count is padded on the left so the newest bucket ends the chart.
+pub fn render_timeline_braille(
+    out: &mut impl Write,
+    counts: &[usize],
+    height: usize,
+    color: bool,
+) -> io::Result<()> {
+    let h = height.max(1);
+    let max = counts.iter().copied().max().unwrap_or(0);
+    if max == 0 || counts.is_empty() {
+        writeln!(out, "(no commits in selected window)")?;
+        return Ok(());
+    }
+    let mut padded = vec![0usize; counts.len() % 2];
+    padded.extend_from_slice(counts);
+    let dots = |c: usize| (c * 4 * h).div_ceil(max);
+
+    for row in (1..=h).rev() {
+        let mut line = timeline_row_label(row, h, max, color);
+        let base = (row - 1) * 4;
+        for pair in padded.chunks(2) {
+            let (left, right) = (dots(pair[0]), dots(pair[1]));
+            let mut bits = 0u32;
+            for d in 0..4 {
+                if left > base + d {
+                    bits |= BRAILLE_LEFT[d];
+                }
+                if right > base + d {
+                    bits |= BRAILLE_RIGHT[d];
+                }
+            }
+            if bits == 0 {
+                line.push(' ');
+                continue;
+            }
+            if color {
+                let shade = intensity_index(pair[0].max(pair[1]), max, 10);
+                line.push_str(&level_color(shade, 10));
+            }
+            line.push(char::from_u32(0x2800 + bits).unwrap_or(' '));
+        }
+        if color {
+            line.push_str(ANSI_RESET);
+        }
+        writeln!(out, "{}", line)?;
     }
+    Ok(())
 }
 
-/// Build timeline axis lines.
+/// Build timeline axis lines for `weeks` buckets drawn `per_column` to a
+/// column: a major tick labeled with the number of buckets ago every
+/// `marks.0` buckets, a minor tick every `marks.1`.
 fn build_timeline_axis_lines(
     weeks: usize,
     left_pad: usize,
-    major: char,
-    minor: char,
+    (major, minor): (char, char),
+    (major_every, minor_every): (usize, usize),
+    per_column: usize,
 ) -> (String, String) {
     if weeks == 0 {
         let s = " ".repeat(left_pad);
         return (s.clone(), s);
     }
+    let per_column = per_column.max(1);
+    let columns = weeks.div_ceil(per_column);
+    // The first bucket ago that is a multiple of `every` within column `col`.
+    let mark = |col: usize, every: usize| {
+        let newest = (columns - 1 - col) * per_column;
+        (newest..newest + per_column).find(|rel| rel.is_multiple_of(every))
+    };
 
-    let mut ticks = vec![' '; weeks];
-    for col in 0..weeks {
-        let rel = weeks - 1 - col;
-        if rel % 12 == 0 {
-            ticks[col] = major;
-        } else if rel % 4 == 0 {
-            ticks[col] = minor;
+    let mut ticks = vec![' '; columns];
+    for (col, tick) in ticks.iter_mut().enumerate() {
+        if mark(col, major_every).is_some() {
+            *tick = major;
+        } else if mark(col, minor_every).is_some() {
+            *tick = minor;
         }
     }
 
-    let mut labels = vec![' '; weeks];
-    let mut occupied = vec![false; weeks];
-    for col in 0..weeks {
-        let rel = weeks - 1 - col;
-        if rel % 12 == 0 {
+    let mut labels = vec![' '; columns];
+    let mut occupied = vec![false; columns];
+    for col in 0..columns {
+        if let Some(rel) = mark(col, major_every) {
             let s = rel.to_string();
-            if col + s.len() <= weeks && (col..col + s.len()).all(|i| !occupied[i]) {
+            if col + s.len() <= columns && (col..col + s.len()).all(|i| !occupied[i]) {
                 for (i, ch) in s.chars().enumerate() {
                     labels[col + i] = ch;
                     occupied[col + i] = true;
@@ -393,128 +814,329 @@ fn build_timeline_axis_lines(
 }
 
 /// Render timeline axis.
-fn render_timeline_axis(weeks: usize, color: bool, left_pad: usize) {
+fn render_timeline_axis(
+    out: &mut impl Write,
+    weeks: usize,
+    view: TimelineView,
+    color: bool,
+    left_pad: usize,
+) -> io::Result<()> {
     if weeks == 0 {
-        return;
+        return Ok(());
     }
     let major = if color { '┼' } else { '+' };
     let minor = if color { '│' } else { '|' };
-    let (ticks_line, labels_line) = build_timeline_axis_lines(weeks, left_pad, major, minor);
+    let (ticks_line, labels_line) = build_timeline_axis_lines(
+        weeks,
+        left_pad,
+        (major, minor),
+        view.granularity.axis_marks(),
+        view.style.per_column(),
+    );
 
     if color {
-        print!("\x1b[90m"); // dim
+        write!(out, "\x1b[90m")?; // dim
     }
-    println!("{}", ticks_line);
-    println!("{}", labels_line);
+    writeln!(out, "{}", ticks_line)?;
+    writeln!(out, "{}", labels_line)?;
     if color {
-        print!("\x1b[0m");
+        write!(out, "\x1b[0m")?;
     }
+    Ok(())
 }
 
 /// Render heatmap with optional color using '█' blocks (space for zero).
-pub fn render_heatmap_ascii_colored(grid: [[usize; 24]; 7], color: bool) {
+pub fn render_heatmap_ascii_colored(
+    out: &mut impl Write,
+    grid: [[usize; 24]; 7],
+    color: bool,
+) -> io::Result<()> {
     if !color {
-        render_heatmap_ascii(grid);
-        return;
+        return render_heatmap_ascii(out, grid);
     }
     // global max for scaling
-    let mut max = 0usize;
-    for r in 0..7 {
-        for h in 0..24 {
-            if grid[r][h] > max {
-                max = grid[r][h];
-            }
-        }
-    }
-    println!("    00  01  02  03  04  05  06  07  08  09  10  11  12  13  14  15  16  17  18  19  20  21  22  23");
-    let labels = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
-    for (r, lbl) in labels.iter().enumerate() {
-        print!("{:<3} ", lbl);
-        for h in 0..24 {
-            let c = grid[r][h];
+    let max = grid.iter().flatten().copied().max().unwrap_or(0);
+    writeln!(out, "    00  01  02  03  04  05  06  07  08  09  10  11  12  13  14  15  16  17  18  19  20  21  22  23")?;
+    let week_start = crate::filter::active().week_start;
+    for (row, lbl) in week_start.reorder(&grid).iter().zip(week_start.day_names()) {
+        write!(out, "{:<3} ", lbl)?;
+        for &c in row {
             if max == 0 || c == 0 {
-                print!("   ");
+                write!(out, "   ")?;
             } else {
                 // richer buckets for color with guaranteed non-zero shade
                 let idx = intensity_index(c, max, 10);
-                let code = color_for_level_rich(idx, 10);
-                print!(" {}█{} ", code, ANSI_RESET);
+                let code = level_color(idx, 10);
+                write!(out, " {}█{} ", code, ANSI_RESET)?;
             }
         }
-        println!();
+        writeln!(out)?;
     }
     // Bottom hour axis for reference
-    println!("    00  01  02  03  04  05  06  07  08  09  10  11  12  13  14  15  16  17  18  19  20  21  22  23");
+    writeln!(out, "    00  01  02  03  04  05  06  07  08  09  10  11  12  13  14  15  16  17  18  19  20  21  22  23")?;
+    Ok(())
 }
 
-/// Render GitHub-style calendar heatmap (colored)
-pub fn render_calendar_heatmap_colored(grid: &[Vec<usize>]) {
+/// Render GitHub-style calendar heatmap (colored), with month names over
+/// `week_starts` like [`render_calendar_heatmap_ascii`].
+pub fn render_calendar_heatmap_colored(
+    out: &mut impl Write,
+    grid: &[Vec<usize>],
+    week_starts: &[u64],
+    scale: HeatmapScale,
+) -> io::Result<()> {
     // global max
-    let mut max = 0usize;
-    for r in 0..7 {
-        for c in 0..grid[0].len() {
-            if grid[r][c] > max {
-                max = grid[r][c];
-            }
-        }
+    let max = grid.iter().flatten().copied().max().unwrap_or(0);
+    if !week_starts.is_empty() {
+        write_calendar_month_row(out, week_starts)?;
     }
-    let labels = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
-    for r in 0..7 {
-        print!("{:<3} ", labels[r]);
-        for c in 0..grid[0].len() {
-            let v = grid[r][c];
+    let week_start = crate::filter::active().week_start;
+    for (row, lbl) in week_start.reorder(grid).iter().zip(week_start.day_names()) {
+        write!(out, "{:<3} ", lbl)?;
+        let row_max = row.iter().copied().max().unwrap_or(0);
+        for &v in row {
             if max == 0 || v == 0 {
-                print!("   ");
+                write!(out, "   ")?;
             } else {
+                let (v, max) = scale.scale(v, row_max, max);
                 let idx = intensity_index(v, max, 10);
-                let code = color_for_level_rich(idx, 10);
-                print!(" {}█{} ", code, ANSI_RESET);
+                let code = level_color(idx, 10);
+                write!(out, " {}█{} ", code, ANSI_RESET)?;
             }
         }
-        println!();
+        writeln!(out)?;
     }
     // bottom week columns
-    print!("    ");
+    write!(out, "    ")?;
     for _ in 0..grid[0].len() {
-        print!("^  ");
+        write!(out, "^  ")?;
     }
-    println!();
+    writeln!(out)?;
+    Ok(())
 }
 
 /// Run the timeline visualization with options.
-pub fn run_timeline_with_options(weeks: usize, color: bool) -> Result<(), String> {
+pub fn run_timeline_with_options(weeks: usize, color: bool) -> Result<(), GitInsightsError> {
+    run_timeline_with_capacity(weeks, color, None)
+}
+
+/// Run the timeline, optionally overlaying weekly team capacity from a CSV file.
+pub fn run_timeline_with_capacity(
+    weeks: usize,
+    color: bool,
+    capacity: Option<&str>,
+) -> Result<(), GitInsightsError> {
+    run_timeline_sized(weeks, color, capacity, ChartSize::default())
+}
+
+/// Run the timeline with an explicit chart height.
+pub fn run_timeline_sized(
+    weeks: usize,
+    color: bool,
+    capacity: Option<&str>,
+    size: ChartSize,
+) -> Result<(), GitInsightsError> {
+    run_timeline_annotated(weeks, color, capacity, size, None)
+}
+
+/// Run the timeline, marking burst weeks and optionally listing their largest commits.
+pub fn run_timeline_annotated(
+    weeks: usize,
+    color: bool,
+    capacity: Option<&str>,
+    size: ChartSize,
+    bursts: Option<BurstOptions>,
+) -> Result<(), GitInsightsError> {
+    run_timeline_metric(
+        weeks,
+        color,
+        capacity,
+        size,
+        bursts,
+        TimelineView::default(),
+    )
+}
+
+/// How the timeline is drawn (`timeline --style`).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[non_exhaustive]
+pub enum TimelineStyle {
+    /// One column of blocks per bucket.
+    #[default]
+    Bars,
+    /// Braille dots: two buckets per column and four dot rows per line.
+    Braille,
+}
+
+impl TimelineStyle {
+    pub fn parse(s: &str) -> Result<TimelineStyle, GitInsightsError> {
+        match s {
+            "bars" => Ok(TimelineStyle::Bars),
+            "braille" => Ok(TimelineStyle::Braille),
+            other => Err(GitInsightsError::Usage(format!(
+                "unknown --style '{}'. Expected bars or braille.",
+                other
+            ))),
+        }
+    }
+
+    /// Buckets drawn in one terminal column.
+    pub fn per_column(self) -> usize {
+        match self {
+            TimelineStyle::Bars => 1,
+            TimelineStyle::Braille => 2,
+        }
+    }
+}
+
+/// What the timeline plots and how (`--metric`, `--granularity`, `--style`).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TimelineView {
+    pub metric: TimelineMetric,
+    pub granularity: TimelineGranularity,
+    pub style: TimelineStyle,
+}
+
+/// Values behind the timeline chart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineData {
+    pub metric: TimelineMetric,
+    pub granularity: TimelineGranularity,
+    /// When the data was computed; the last bucket is the one containing it.
+    pub now: u64,
+    /// One value per day, week or month, oldest first.
+    pub counts: Vec<usize>,
+}
+
+/// What `timeline` plots for the last `weeks` weeks, without printing it.
+pub fn compute_timeline(
+    weeks: usize,
+    metric: TimelineMetric,
+) -> Result<TimelineData, GitInsightsError> {
+    compute_timeline_by(weeks, metric, TimelineGranularity::Week)
+}
+
+/// Like [`compute_timeline`], bucketed by day, week or month.
+pub fn compute_timeline_by(
+    weeks: usize,
+    metric: TimelineMetric,
+    granularity: TimelineGranularity,
+) -> Result<TimelineData, GitInsightsError> {
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .map_err(|e| format!("clock error: {e}"))?
         .as_secs();
-    let ts = collect_commit_timestamps()?;
-    let counts = compute_timeline_weeks(&ts, weeks, now);
-    println!("Weekly commits (old -> new), weeks={weeks}:");
+    let pairs = collect_author_timestamps()?;
+    Ok(TimelineData {
+        metric,
+        granularity,
+        now,
+        counts: metric.values(&pairs, granularity.buckets(weeks, now), now, granularity),
+    })
+}
+
+/// Run the timeline of `metric` per day, week or month over the last `weeks`
+/// weeks. Capacity and bursts are weekly and need week granularity.
+pub fn run_timeline_metric(
+    weeks: usize,
+    color: bool,
+    capacity: Option<&str>,
+    size: ChartSize,
+    bursts: Option<BurstOptions>,
+    view: TimelineView,
+) -> Result<(), GitInsightsError> {
+    let TimelineView {
+        metric,
+        granularity,
+        style,
+    } = view;
+    if granularity != TimelineGranularity::Week && (capacity.is_some() || bursts.is_some()) {
+        return Err(format!(
+            "--capacity and --bursts cannot be combined with --granularity {}",
+            granularity.name()
+        )
+        .into());
+    }
+    if style != TimelineStyle::Bars && (capacity.is_some() || bursts.is_some()) {
+        return Err("--capacity and --bursts cannot be combined with --style braille".into());
+    }
+    let mut out = io::stdout().lock();
+    let capacity = capacity.map(load_capacity_file).transpose()?;
+    let TimelineData { now, counts, .. } = compute_timeline_by(weeks, metric, granularity)?;
+    let (adjective, unit, buckets) = (granularity.adjective(), granularity.name(), counts.len());
+    match metric {
+        TimelineMetric::Commits => {
+            writeln!(out, "{adjective} commits (old -> new), {unit}s={buckets}:")?
+        }
+        TimelineMetric::Contributors => writeln!(
+            out,
+            "{adjective} active authors (old -> new), {unit}s={buckets}:"
+        )?,
+    }
     let max = counts.iter().copied().max().unwrap_or(0);
-    let mid = (max + 1) / 2;
+    let mid = max.div_ceil(2);
     if color {
-        print!("\x1b[90m");
+        write!(out, "\x1b[90m")?;
     }
-    println!("Y-axis: commits/week (max={}, mid≈{})", max, mid);
+    let unit = metric.unit_per(granularity);
+    writeln!(out, "Y-axis: {} (max={}, mid≈{})", unit, max, mid)?;
     if color {
-        print!("\x1b[0m");
+        write!(out, "\x1b[0m")?;
+    }
+    print_ramp_legend_rich(&mut out, color, &unit)?;
+    writeln!(out)?;
+    match style {
+        TimelineStyle::Bars => {
+            render_timeline_multiline(&mut out, &counts, size.timeline_height(), color)?
+        }
+        TimelineStyle::Braille => {
+            render_timeline_braille(&mut out, &counts, size.timeline_height(), color)?
+        }
     }
-    print_ramp_legend_rich(color, "commits/week");
-    println!();
-    render_timeline_multiline(&counts, 7, color);
     let label_width = max.to_string().len().max(3);
     let left_pad = label_width + 2; // "{label:>width$} {axis}"
-    render_timeline_axis(weeks, color, left_pad);
+    let flagged = bursts.map(|b| detect_bursts(&counts, BURST_WINDOW, b.factor));
+    if let Some(flagged) = &flagged {
+        render_burst_row(flagged, left_pad, color);
+    }
+    if let Some(entries) = &capacity {
+        let cap = compute_capacity_weeks(entries, weeks, now);
+        render_capacity_overlay(&counts, &cap, left_pad, color);
+    }
+    render_timeline_axis(&mut out, buckets, view, color, left_pad)?;
+    if let (Some(opts), Some(flagged)) = (bursts, &flagged) {
+        if opts.top_commits > 0 {
+            print_burst_commits(&counts, flagged, now, opts)?;
+        }
+    }
+    if crate::submodules::recurse_submodules() {
+        let parts = commit_subtotals(|ts| {
+            compute_timeline_buckets(ts, buckets, now, granularity)
+                .iter()
+                .sum()
+        })?;
+        crate::submodules::write_commit_subtotals(&mut out, &parts)?;
+    }
     Ok(())
 }
 
 /// Run the timeline visualization end-to-end with default `weeks` if needed.
-pub fn run_timeline(weeks: usize) -> Result<(), String> {
+pub fn run_timeline(weeks: usize) -> Result<(), GitInsightsError> {
     run_timeline_with_options(weeks, false)
 }
 
 /// Run the heatmap visualization with options.
-pub fn run_heatmap_with_options(weeks: Option<usize>, color: bool) -> Result<(), String> {
+pub fn run_heatmap_with_options(weeks: Option<usize>, color: bool) -> Result<(), GitInsightsError> {
+    run_heatmap_scaled(weeks, color, HeatmapScale::default())
+}
+
+/// Like [`run_heatmap_with_options`] with cells shaded by `scale`.
+pub fn run_heatmap_scaled(
+    weeks: Option<usize>,
+    color: bool,
+    scale: HeatmapScale,
+) -> Result<(), GitInsightsError> {
+    let mut out = io::stdout().lock();
     let ts_all = collect_commit_timestamps()?;
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -524,34 +1146,117 @@ pub fn run_heatmap_with_options(weeks: Option<usize>, color: bool) -> Result<(),
     let w = weeks.unwrap_or(52);
     let grid = compute_calendar_heatmap(&ts_all, w, now);
 
-    let mut max = 0usize;
-    for r in 0..7 {
-        for c in 0..grid[0].len() {
-            if grid[r][c] > max {
-                max = grid[r][c];
-            }
-        }
-    }
+    let max = grid.iter().flatten().copied().max().unwrap_or(0);
     if color {
-        print!("\x1b[90m");
+        write!(out, "\x1b[90m")?;
     }
-    println!("Calendar heatmap (UTC) — rows: Sun..Sat, cols: weeks (old→new), unit: commits/day, window: last {} weeks, max={}", w, max);
+    let filter = crate::filter::active();
+    let days = filter.week_start.day_names();
+    let shading = match scale {
+        HeatmapScale::Global => String::new(),
+        other => format!(", shading: {}", other.name()),
+    };
+    writeln!(out,
+        "Calendar heatmap ({}) — rows: {}..{}, cols: weeks (old→new), unit: commits/day, window: last {} weeks, max={}{}",
+        filter.time_zone.label(),
+        days[0],
+        days[6],
+        w,
+        max,
+        shading
+    )?;
     if color {
-        print!("\x1b[0m");
+        write!(out, "\x1b[0m")?;
     }
-    print_ramp_legend_rich(color, "commits/day");
-    println!();
+    print_ramp_legend_rich(&mut out, color, "commits/day")?;
+    writeln!(out)?;
 
+    let week_starts = calendar_week_starts(w, now);
     if color {
-        render_calendar_heatmap_colored(&grid);
+        render_calendar_heatmap_colored(&mut out, &grid, &week_starts, scale)?;
     } else {
-        render_calendar_heatmap_ascii(&grid);
+        render_calendar_heatmap_ascii(&mut out, &grid, &week_starts, scale)?;
+    }
+    if crate::submodules::recurse_submodules() {
+        let parts =
+            commit_subtotals(|ts| compute_calendar_heatmap(ts, w, now).iter().flatten().sum())?;
+        crate::submodules::write_commit_subtotals(&mut out, &parts)?;
     }
     Ok(())
 }
 
+/// Export the calendar heatmap as an SVG file, or PNG for `.png` paths with
+/// the `charts` feature.
+pub fn export_heatmap_svg(weeks: Option<usize>, path: &str) -> Result<(), GitInsightsError> {
+    let ts_all = collect_commit_timestamps()?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| format!("clock error: {e}"))?
+        .as_secs();
+
+    let w = weeks.unwrap_or(52);
+    let grid = compute_calendar_heatmap(&ts_all, w, now);
+    let week_start = crate::filter::active().week_start;
+    let grid = week_start.reorder(&grid);
+    let row_labels: Vec<String> = week_start
+        .day_names()
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    // Label every 12th week counting back from the current one.
+    let col_labels: Vec<String> = (0..w)
+        .map(|col| match w - 1 - col {
+            0 => "now".to_string(),
+            rel if rel.is_multiple_of(12) => format!("-{}w", rel),
+            _ => String::new(),
+        })
+        .collect();
+    let title = format!(
+        "Calendar heatmap ({}), commits/day, last {} weeks",
+        crate::filter::active().time_zone.label(),
+        w
+    );
+    crate::svg::write_heatmap_chart(path, &grid, &row_labels, &col_labels, &title)
+}
+
+/// Export the timeline as SVG bars, or PNG for `.png` paths with the
+/// `charts` feature.
+pub fn export_timeline_chart(
+    weeks: usize,
+    view: TimelineView,
+    path: &str,
+) -> Result<(), GitInsightsError> {
+    let TimelineData {
+        metric,
+        granularity,
+        counts,
+        ..
+    } = compute_timeline_by(weeks, view.metric, view.granularity)?;
+    let (unit, buckets) = (granularity.name(), counts.len());
+    // Label every 12th bucket counting back from the current one.
+    let labels: Vec<String> = (0..buckets)
+        .map(|i| match buckets - 1 - i {
+            0 => "now".to_string(),
+            rel if rel.is_multiple_of(12) => format!("-{}{}", rel, &unit[..1]),
+            _ => String::new(),
+        })
+        .collect();
+    let what = match metric {
+        TimelineMetric::Commits => "commits",
+        TimelineMetric::Contributors => "active authors",
+    };
+    let title = format!(
+        "{} {} (old -> new), {}s={}",
+        granularity.adjective(),
+        what,
+        unit,
+        buckets
+    );
+    crate::svg::write_bars_chart(path, &labels, &counts, &title)
+}
+
 /// Run the heatmap visualization end-to-end.
-pub fn run_heatmap() -> Result<(), String> {
+pub fn run_heatmap() -> Result<(), GitInsightsError> {
     run_heatmap_with_options(None, false)
 }
 
@@ -564,15 +1269,73 @@ mod tests {
         let week = 604_800u64;
         let now = 10 * week; // arbitrary multiple
         let ts = vec![
-            now - (0 * week) + 1, // this week
-            now - (1 * week) + 2, // last week
-            now - (1 * week) + 3, // last week
-            now - (3 * week),     // 3 weeks ago
+            now + 1,          // this week
+            now - week + 2,   // last week
+            now - week + 3,   // last week
+            now - (3 * week), // 3 weeks ago
         ];
         let counts = compute_timeline_weeks(&ts, 4, now);
         assert_eq!(counts, vec![1, 0, 2, 1]);
     }
 
+    #[test]
+    fn test_timeline_metric_contributors() {
+        let week = 604_800u64;
+        let now = 10 * week;
+        let pair = |a: &str, t: u64| (a.to_string(), t);
+        let pairs = vec![
+            pair("Ann", now + 1),
+            pair("Ann", now + 2),
+            pair("Bob", now + 3),
+            pair("Ann", now - week),
+            pair("Cat", now - 5 * week),
+        ];
+        assert_eq!(
+            TimelineMetric::Contributors.weekly_values(&pairs, 3, now),
+            vec![0, 1, 2]
+        );
+        assert_eq!(
+            TimelineMetric::Commits.weekly_values(&pairs, 3, now),
+            vec![0, 1, 3]
+        );
+        assert!(matches!(
+            TimelineMetric::parse("contributors"),
+            Ok(TimelineMetric::Contributors)
+        ));
+        assert!(TimelineMetric::parse("lines").is_err());
+    }
+
+    #[test]
+    fn test_timeline_day_and_month_buckets() {
+        let day = 86_400u64;
+        // 2024-03-15 12:00 UTC.
+        let now = 1_710_504_000u64;
+        let ts = vec![now + 3_600, now - day, now - day - 60, now - 3 * day];
+        assert_eq!(
+            compute_timeline_buckets(&ts, 3, now, TimelineGranularity::Day),
+            vec![0, 2, 1]
+        );
+        // 2024-01-31, 2024-02-01 and 2023-12-31.
+        let months = vec![1_706_659_200, 1_706_745_600, 1_703_980_800, now];
+        assert_eq!(
+            compute_timeline_buckets(&months, 3, now, TimelineGranularity::Month),
+            vec![1, 1, 1]
+        );
+        assert_eq!(TimelineGranularity::Day.buckets(2, now), 14);
+        // 8 weeks back from mid-March is mid-January.
+        assert_eq!(TimelineGranularity::Month.buckets(8, now), 3);
+        assert_eq!(TimelineGranularity::Month.buckets(0, now), 0);
+        assert_eq!(
+            TimelineMetric::Contributors.unit_per(TimelineGranularity::Month),
+            "authors/month"
+        );
+        assert!(TimelineGranularity::parse("year").is_err());
+
+        let (ticks, labels) = build_timeline_axis_lines(15, 0, ('+', '|'), (14, 7), 1);
+        assert_eq!(ticks, "+      |      +");
+        assert_eq!(labels, "14            0");
+    }
+
     #[test]
     fn test_compute_heatmap_utc_known_points() {
         let sun_00 = 3 * 86_400;
@@ -586,9 +1349,16 @@ mod tests {
 
     #[test]
     fn test_render_timeline_no_panic() {
-        render_timeline_bars(&[0, 1, 2, 3, 0, 5, 5, 1]);
-        render_timeline_bars(&[]);
-        render_timeline_bars(&[0, 0, 0]);
+        let mut buf = Vec::new();
+        render_timeline_bars(&mut buf, &[0, 1, 2, 3, 0, 5, 5, 1]).unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap(), " .-+ @@.\n");
+        let mut buf = Vec::new();
+        render_timeline_bars(&mut buf, &[]).unwrap();
+        render_timeline_bars(&mut buf, &[0, 0, 0]).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "(no commits in selected window)\n".repeat(2)
+        );
     }
 
     #[test]
@@ -596,7 +1366,13 @@ mod tests {
         let mut grid = [[0usize; 24]; 7];
         grid[0][0] = 1;
         grid[6][23] = 5;
-        render_heatmap_ascii(grid);
+        let mut buf = Vec::new();
+        render_heatmap_ascii(&mut buf, grid).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 9);
+        assert!(lines[1].starts_with("Sun  .    "));
+        assert!(lines[7].starts_with("Sat ") && lines[7].ends_with(" @ "));
     }
 
     #[test]
@@ -608,7 +1384,7 @@ mod tests {
         let start_of_week = now - (now % WEEK);
         let aligned_end = start_of_week + WEEK - 1;
 
-        let t_curr1 = aligned_end - (1 * DAY); // within current week
+        let t_curr1 = aligned_end - DAY; // within current week
         let t_curr2 = aligned_end - (2 * DAY);
         let t_prev1 = aligned_end - (8 * DAY); // previous week
         let ts = vec![t_curr1, t_curr2, t_prev1];
@@ -619,9 +1395,9 @@ mod tests {
 
         let mut col0 = 0usize;
         let mut col1 = 0usize;
-        for r in 0..7 {
-            col0 += grid[r][0];
-            col1 += grid[r][1];
+        for row in &grid {
+            col0 += row[0];
+            col1 += row[1];
         }
         assert_eq!(col0, 1, "older week should have 1 commit");
         assert_eq!(col1, 2, "current week should have 2 commits");
@@ -634,21 +1410,96 @@ mod tests {
         grid[1][1] = 2;
         grid[2][2] = 3;
         grid[3][3] = 4;
-        super::render_calendar_heatmap_ascii(&grid);
-        super::render_calendar_heatmap_colored(&grid);
+        let mut buf = Vec::new();
+        super::render_calendar_heatmap_ascii(&mut buf, &grid, &[], HeatmapScale::Global).unwrap();
+        super::render_calendar_heatmap_colored(&mut buf, &grid, &[], HeatmapScale::Row).unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap().lines().count(), 16);
+    }
+
+    #[test]
+    fn test_calendar_month_labels() {
+        // 2024-03-15 12:00 UTC; the columns start on the Thursdays from
+        // 2024-02-15 to 2024-03-14 (2024-02-29 is the fourth).
+        let now = 1_710_504_000u64;
+        let starts = calendar_week_starts(5, now);
+        assert_eq!(starts.len(), 5);
+        assert_eq!(starts[4], now - now % WEEK_SECS);
+        assert_eq!(calendar_month_labels(&starts), ["Feb", "", "", "Mar", ""]);
+        // A lone last week of February stays unnamed next to March.
+        assert_eq!(calendar_month_labels(&starts[2..]), ["", "Mar", ""]);
+
+        let grid = vec![vec![1usize; 5]; 7];
+        let mut buf = Vec::new();
+        render_calendar_heatmap_ascii(&mut buf, &grid, &starts, HeatmapScale::Global).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert_eq!(text.lines().next(), Some("    Feb      Mar"));
+        assert_eq!(text.lines().count(), 9);
+    }
+
+    #[test]
+    fn test_heatmap_scale() {
+        assert_eq!(HeatmapScale::parse("log").unwrap(), HeatmapScale::Log);
+        let err = HeatmapScale::parse("max").unwrap_err().to_string();
+        assert!(err.contains("Expected row, global or log."));
+        assert_eq!(HeatmapScale::Global.scale(2, 4, 100), (2, 100));
+        assert_eq!(HeatmapScale::Row.scale(2, 4, 100), (2, 4));
+        assert_eq!(HeatmapScale::Log.scale(0, 4, 100).0, 0);
+
+        // One burst of 100 next to a row of single commits.
+        let mut grid = vec![vec![0usize; 3]; 7];
+        grid[0] = vec![1, 1, 100];
+        grid[1] = vec![1, 2, 0];
+        let row = |scale: HeatmapScale| {
+            let mut buf = Vec::new();
+            render_calendar_heatmap_ascii(&mut buf, &grid, &[], scale).unwrap();
+            String::from_utf8(buf)
+                .unwrap()
+                .lines()
+                .nth(1)
+                .unwrap()
+                .trim_end()
+                .to_string()
+        };
+        assert_eq!(row(HeatmapScale::Global), "Mon");
+        assert_eq!(row(HeatmapScale::Row), "Mon  =  @");
+        assert_eq!(row(HeatmapScale::Log), "Mon  .  :");
     }
 
     #[test]
     fn test_print_legends_no_panic() {
-        super::print_ramp_legend(false, "commits/week");
-        super::print_ramp_legend(true, "commits/day");
+        let mut buf = Vec::new();
+        super::print_ramp_legend(&mut buf, false, "commits/week").unwrap();
+        super::print_ramp_legend(&mut buf, true, "commits/day").unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert!(text.starts_with("Legend (low→high, blank=' ' 0 commits/week):"));
+        assert!(text.contains("\x1b[90mLegend"));
+    }
+
+    #[test]
+    fn test_render_timeline_braille() {
+        // Five buckets padded to (0, 4), (0, 2), (8, 1), newest on the right.
+        let mut buf = Vec::new();
+        render_timeline_braille(&mut buf, &[4, 0, 2, 8, 1], 2, false).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        // Top row: only the 8 reaches above half, in the left dot column.
+        assert_eq!(lines[0], "  8 |  \u{2847}");
+        // Bottom row (labeled with the midpoint on a 2-row chart).
+        assert_eq!(lines[1], "  4 |\u{28b8}\u{28a0}\u{28c7}");
+
+        let (ticks, labels) = build_timeline_axis_lines(104, 0, ('+', '|'), (12, 4), 2);
+        assert_eq!(ticks.chars().count(), 52);
+        assert!(ticks.ends_with("+ | | +"));
+        assert!(labels.trim_end().ends_with("12    0"));
     }
 
     #[test]
     fn test_build_timeline_axis_lines_alignment() {
         let weeks = 24usize;
         let left_pad = 5usize;
-        let (ticks, labels) = super::build_timeline_axis_lines(weeks, left_pad, '+', '|');
+        let (ticks, labels) =
+            super::build_timeline_axis_lines(weeks, left_pad, ('+', '|'), (12, 4), 1);
 
         assert!(ticks.starts_with(&" ".repeat(left_pad)));
         assert!(labels.starts_with(&" ".repeat(left_pad)));
@@ -660,9 +1511,9 @@ mod tests {
 
         for (col, tc) in t_body.chars().enumerate() {
             let rel = weeks - 1 - col;
-            let expected = if rel % 12 == 0 {
+            let expected = if rel.is_multiple_of(12) {
                 '+'
-            } else if rel % 4 == 0 {
+            } else if rel.is_multiple_of(4) {
                 '|'
             } else {
                 ' '
@@ -678,4 +1529,19 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_chart_size_widths() {
+        assert_eq!(fit_width(40, 10, None), 40);
+        assert_eq!(fit_width(40, 10, Some(200)), 40);
+        assert_eq!(fit_width(40, 10, Some(30)), 20);
+        assert_eq!(fit_width(40, 10, Some(5)), 1);
+        let size = ChartSize {
+            width: Some(80),
+            height: Some(0),
+        };
+        assert_eq!(size.bar_width(HISTOGRAM_WIDTH, 500), 80);
+        assert_eq!(size.timeline_height(), 1);
+        assert_eq!(ChartSize::default().timeline_height(), TIMELINE_HEIGHT);
+    }
 }
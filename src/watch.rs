@@ -0,0 +1,93 @@
+//! `--watch`: redraw a command's output on an interval, or as soon as HEAD
+//! moves, for a dashboard left running on a spare screen.
+
+use crate::git::run_command;
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Seconds between refreshes for a bare `--watch`.
+pub const DEFAULT_WATCH_SECS: u64 = 10;
+
+/// How often HEAD is checked for new commits while waiting.
+const POLL: Duration = Duration::from_secs(1);
+
+fn head() -> Option<String> {
+    run_command(&["rev-parse", "HEAD"]).ok()
+}
+
+/// Sleep until `interval` has passed or HEAD no longer points at `seen`.
+fn wait_for_change(interval: Duration, seen: &Option<String>) {
+    let start = Instant::now();
+    while start.elapsed() < interval {
+        thread::sleep(POLL.min(interval.saturating_sub(start.elapsed())));
+        if head() != *seen {
+            return;
+        }
+    }
+}
+
+/// `HH:MM:SS` UTC of `t`.
+fn clock(t: u64) -> String {
+    format!("{:02}:{:02}:{:02}", (t / 3_600) % 24, (t / 60) % 60, t % 60)
+}
+
+/// The status line under each refresh.
+pub fn watch_footer(now: u64, secs: u64, head: Option<&str>) -> String {
+    let head = head.map(|h| &h[..h.len().min(7)]).unwrap_or("-");
+    format!(
+        "Refreshed {} UTC at {}; again in {}s or on new commits. Ctrl-C to stop.",
+        clock(now),
+        head,
+        secs
+    )
+}
+
+/// Clear the screen and run `render` every `secs` seconds (or when HEAD
+/// moves) until interrupted. A failing first render ends the watch; later
+/// failures are shown and retried, e.g. while a rebase is in progress.
+pub fn run_watch<E: Display>(
+    secs: u64,
+    mut render: impl FnMut() -> Result<(), E>,
+) -> Result<(), E> {
+    let interval = Duration::from_secs(secs.max(1));
+    let mut first = true;
+    loop {
+        let seen = head();
+        print!("\x1b[2J\x1b[H");
+        let _ = io::stdout().flush();
+        match render() {
+            Ok(()) => {}
+            Err(e) if first => return Err(e),
+            Err(e) => println!("Error: {}", e),
+        }
+        first = false;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        println!();
+        println!("{}", watch_footer(now, secs, seen.as_deref()));
+        let _ = io::stdout().flush();
+        wait_for_change(interval, &seen);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watch_footer_and_first_failure() {
+        let sha = "0123456789abcdef";
+        assert_eq!(
+            watch_footer(1_700_000_000, 30, Some(sha)),
+            "Refreshed 22:13:20 UTC at 0123456; again in 30s or on new commits. Ctrl-C to stop."
+        );
+        assert!(watch_footer(0, 5, None).contains(" at -;"));
+
+        let result = run_watch(1, || Err::<(), _>("not a repo".to_string()));
+        assert_eq!(result, Err("not a repo".to_string()));
+    }
+}
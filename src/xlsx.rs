@@ -0,0 +1,356 @@
+//! Minimal `.xlsx` writer: inline-string worksheets in an uncompressed ZIP.
+
+use crate::error::GitInsightsError;
+use crate::hotspots::gather_churn;
+use crate::output::write_export;
+use crate::snapshot::{blame_head_files, stats_from_files};
+use crate::stats::gather_commit_statsx;
+use crate::svg::xml_escape;
+use std::fmt::Write as _;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Weeks of history in the timeline sheet.
+pub const TIMELINE_WEEKS: usize = 52;
+
+/// One spreadsheet cell.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Cell {
+    Text(String),
+    Number(f64),
+}
+
+impl From<&str> for Cell {
+    fn from(s: &str) -> Self {
+        Cell::Text(s.to_string())
+    }
+}
+
+impl From<String> for Cell {
+    fn from(s: String) -> Self {
+        Cell::Text(s)
+    }
+}
+
+impl From<usize> for Cell {
+    fn from(n: usize) -> Self {
+        Cell::Number(n as f64)
+    }
+}
+
+impl From<f64> for Cell {
+    fn from(n: f64) -> Self {
+        Cell::Number(n)
+    }
+}
+
+/// A named worksheet; the first row is the header.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Sheet {
+    pub name: String,
+    pub rows: Vec<Vec<Cell>>,
+}
+
+/// Spreadsheet column letters for a 0-based index (`0 -> A`, `26 -> AA`).
+pub fn column_name(mut idx: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (idx % 26) as u8);
+        if idx < 26 {
+            break;
+        }
+        idx = idx / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+/// XML-escape text and drop control characters XML 1.0 cannot carry.
+fn cell_text(s: &str) -> String {
+    let clean: String = s
+        .chars()
+        .filter(|&c| !c.is_control() || matches!(c, '\t' | '\n' | '\r'))
+        .collect();
+    xml_escape(&clean)
+}
+
+/// `xl/worksheets/sheetN.xml` for one sheet.
+pub fn sheet_xml(sheet: &Sheet) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\"><sheetData>",
+    );
+    for (r, row) in sheet.rows.iter().enumerate() {
+        let _ = write!(out, "<row r=\"{}\">", r + 1);
+        for (c, cell) in row.iter().enumerate() {
+            let at = format!("{}{}", column_name(c), r + 1);
+            match cell {
+                Cell::Text(s) => {
+                    let _ = write!(
+                        out,
+                        "<c r=\"{}\" t=\"inlineStr\"><is><t xml:space=\"preserve\">{}</t></is></c>",
+                        at,
+                        cell_text(s)
+                    );
+                }
+                Cell::Number(n) if n.is_finite() => {
+                    let _ = write!(out, "<c r=\"{}\"><v>{}</v></c>", at, n);
+                }
+                Cell::Number(_) => {}
+            }
+        }
+        out.push_str("</row>");
+    }
+    out.push_str("</sheetData></worksheet>\n");
+    out
+}
+
+/// Every part of the workbook package, in archive order.
+fn workbook_parts(sheets: &[Sheet]) -> Vec<(String, Vec<u8>)> {
+    const XML: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n";
+    const REL_NS: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+    let mut types = format!(
+        "{XML}<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\
+         <Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\
+         <Default Extension=\"xml\" ContentType=\"application/xml\"/>\
+         <Override PartName=\"/xl/workbook.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/>"
+    );
+    let mut workbook = format!(
+        "{XML}<workbook xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" xmlns:r=\"{REL_NS}\"><sheets>"
+    );
+    let mut rels = format!("{XML}<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">");
+    for (i, sheet) in sheets.iter().enumerate() {
+        let n = i + 1;
+        let _ = write!(
+            types,
+            "<Override PartName=\"/xl/worksheets/sheet{n}.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/>"
+        );
+        let _ = write!(
+            workbook,
+            "<sheet name=\"{}\" sheetId=\"{n}\" r:id=\"rId{n}\"/>",
+            cell_text(&sheet.name)
+        );
+        let _ = write!(
+            rels,
+            "<Relationship Id=\"rId{n}\" Type=\"{REL_NS}/worksheet\" Target=\"worksheets/sheet{n}.xml\"/>"
+        );
+    }
+    types.push_str("</Types>\n");
+    workbook.push_str("</sheets></workbook>\n");
+    rels.push_str("</Relationships>\n");
+    let root_rels = format!(
+        "{XML}<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+         <Relationship Id=\"rId1\" Type=\"{REL_NS}/officeDocument\" Target=\"xl/workbook.xml\"/></Relationships>\n"
+    );
+
+    let mut parts = vec![
+        ("[Content_Types].xml".to_string(), types.into_bytes()),
+        ("_rels/.rels".to_string(), root_rels.into_bytes()),
+        ("xl/workbook.xml".to_string(), workbook.into_bytes()),
+        ("xl/_rels/workbook.xml.rels".to_string(), rels.into_bytes()),
+    ];
+    for (i, sheet) in sheets.iter().enumerate() {
+        parts.push((
+            format!("xl/worksheets/sheet{}.xml", i + 1),
+            sheet_xml(sheet).into_bytes(),
+        ));
+    }
+    parts
+}
+
+/// CRC-32 (IEEE) as required by ZIP entries.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in bytes {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// ZIP archive with every entry stored (no compression), dated 1980-01-01.
+pub fn zip_stored(entries: &[(String, Vec<u8>)]) -> Vec<u8> {
+    const DOS_DATE: u16 = 0x21;
+    let mut out = Vec::new();
+    let mut central = Vec::new();
+    for (name, data) in entries {
+        let offset = out.len() as u32;
+        let crc = crc32(data);
+        let size = data.len() as u32;
+        let name_len = name.len() as u16;
+
+        out.extend(0x0403_4b50u32.to_le_bytes());
+        for v in [20u16, 0, 0, 0, DOS_DATE] {
+            out.extend(v.to_le_bytes());
+        }
+        for v in [crc, size, size] {
+            out.extend(v.to_le_bytes());
+        }
+        out.extend(name_len.to_le_bytes());
+        out.extend(0u16.to_le_bytes());
+        out.extend(name.as_bytes());
+        out.extend(data);
+
+        central.extend(0x0201_4b50u32.to_le_bytes());
+        for v in [20u16, 20, 0, 0, 0, DOS_DATE] {
+            central.extend(v.to_le_bytes());
+        }
+        for v in [crc, size, size] {
+            central.extend(v.to_le_bytes());
+        }
+        for v in [name_len, 0, 0, 0, 0] {
+            central.extend(v.to_le_bytes());
+        }
+        central.extend(0u32.to_le_bytes());
+        central.extend(offset.to_le_bytes());
+        central.extend(name.as_bytes());
+    }
+    let central_offset = out.len() as u32;
+    let count = entries.len() as u16;
+    out.extend(&central);
+    out.extend(0x0605_4b50u32.to_le_bytes());
+    for v in [0u16, 0, count, count] {
+        out.extend(v.to_le_bytes());
+    }
+    out.extend((central.len() as u32).to_le_bytes());
+    out.extend(central_offset.to_le_bytes());
+    out.extend(0u16.to_le_bytes());
+    out
+}
+
+/// Complete `.xlsx` file for `sheets`.
+pub fn workbook_bytes(sheets: &[Sheet]) -> Vec<u8> {
+    zip_stored(&workbook_parts(sheets))
+}
+
+/// Build the stats, ownership, churn and timeline sheets for the active filter.
+pub fn gather_workbook() -> Result<Vec<Sheet>, GitInsightsError> {
+    let files = blame_head_files(true)?;
+    let mut stats = stats_from_files(&files);
+    for (author, data) in gather_commit_statsx(true)? {
+        stats.entry(author).or_default().commits = data.commits;
+    }
+    let mut authors: Vec<_> = stats.into_iter().collect();
+    authors.sort_by(|a, b| b.1.loc.cmp(&a.1.loc).then_with(|| a.0.cmp(&b.0)));
+    let mut stats_sheet = Sheet {
+        name: "stats".to_string(),
+        rows: vec![vec![
+            "author".into(),
+            "loc".into(),
+            "commits".into(),
+            "files".into(),
+        ]],
+    };
+    for (author, s) in authors {
+        stats_sheet.rows.push(vec![
+            author.into(),
+            s.loc.into(),
+            s.commits.into(),
+            s.files.len().into(),
+        ]);
+    }
+
+    let mut ownership = Sheet {
+        name: "ownership".to_string(),
+        rows: vec![vec![
+            "file".into(),
+            "author".into(),
+            "lines".into(),
+            "share".into(),
+        ]],
+    };
+    for (file, owners) in &files {
+        let file_total: usize = owners.values().sum();
+        let mut owners: Vec<(&String, &usize)> = owners.iter().collect();
+        owners.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        for (author, &lines) in owners {
+            let share = lines as f64 / file_total.max(1) as f64;
+            ownership.rows.push(vec![
+                file.as_str().into(),
+                author.as_str().into(),
+                lines.into(),
+                ((share * 10_000.0).round() / 10_000.0).into(),
+            ]);
+        }
+    }
+
+    let mut churn: Vec<(String, usize)> = gather_churn()?.into_iter().collect();
+    churn.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let mut churn_sheet = Sheet {
+        name: "churn".to_string(),
+        rows: vec![vec!["file".into(), "commits".into()]],
+    };
+    for (file, commits) in churn {
+        churn_sheet.rows.push(vec![file.into(), commits.into()]);
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| format!("clock error: {e}"))?
+        .as_secs();
+    let timestamps = crate::visualize::collect_commit_timestamps()?;
+    let counts = crate::visualize::compute_timeline_weeks(&timestamps, TIMELINE_WEEKS, now);
+    let mut timeline = Sheet {
+        name: "timeline".to_string(),
+        rows: vec![vec!["week_start".into(), "commits".into()]],
+    };
+    for (i, &c) in counts.iter().enumerate() {
+        let (week_start, _) = crate::bursts::week_bounds(i, TIMELINE_WEEKS, now);
+        let (y, m, d) = crate::code_frequency::ymd_from_unix(week_start);
+        timeline
+            .rows
+            .push(vec![format!("{:04}-{:02}-{:02}", y, m, d).into(), c.into()]);
+    }
+
+    Ok(vec![stats_sheet, ownership, churn_sheet, timeline])
+}
+
+/// Write the workbook to `path` (`-` writes to stdout).
+pub fn export_xlsx(path: &str) -> Result<(), GitInsightsError> {
+    let sheets = gather_workbook()?;
+    write_export(Some(path), path, &workbook_bytes(&sheets))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_name_and_crc() {
+        assert_eq!(column_name(0), "A");
+        assert_eq!(column_name(25), "Z");
+        assert_eq!(column_name(26), "AA");
+        assert_eq!(column_name(701), "ZZ");
+        assert_eq!(column_name(702), "AAA");
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_workbook_package() {
+        let sheet = Sheet {
+            name: "stats".to_string(),
+            rows: vec![
+                vec!["author".into(), "loc".into()],
+                vec!["Ann <a&b>\u{1}".into(), 12usize.into()],
+            ],
+        };
+        let xml = sheet_xml(&sheet);
+        assert!(xml.contains(
+            "<c r=\"A2\" t=\"inlineStr\"><is><t xml:space=\"preserve\">Ann &lt;a&amp;b&gt;</t></is></c>"
+        ));
+        assert!(xml.contains("<c r=\"B2\"><v>12</v></c>"));
+
+        let bytes = workbook_bytes(&[sheet]);
+        assert_eq!(&bytes[..4], b"PK\x03\x04");
+        let eocd = &bytes[bytes.len() - 22..];
+        assert_eq!(&eocd[..4], b"PK\x05\x06");
+        // [Content_Types].xml, two .rels, workbook.xml and one sheet.
+        assert_eq!(u16::from_le_bytes([eocd[10], eocd[11]]), 5);
+    }
+}
@@ -26,6 +26,10 @@ class TestCLI(unittest.TestCase):
         code = self.ext.run(["git-insights", "--version"])
         self.assertEqual(code, 0)
 
+    def test_bad_arguments_exit_four(self):
+        code = self.ext.run(["git-insights", "stats", "--bogus"])
+        self.assertEqual(code, 4)
+
     def test_version_prints_correct_version_and_pip_channel(self):
         expected_ver = pkg_version("git-insights")
         buf = io.StringIO()